@@ -0,0 +1,40 @@
+use crate::mock::*;
+use frame_support::{assert_noop, assert_ok, traits::fungibles::Inspect};
+use primitives::traits::MultiAssetExtended;
+use sp_runtime::{DispatchError, TokenError};
+
+#[test]
+fn update_balance_mints_and_burns_by_sign() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Assets::update_balance(MTR, &BOB, 100));
+		assert_eq!(Assets::balance(MTR, &BOB), INITIAL_BALANCE + 100);
+
+		assert_ok!(Assets::update_balance(MTR, &BOB, -300));
+		assert_eq!(Assets::balance(MTR, &BOB), INITIAL_BALANCE - 200);
+
+		// Zero is a no-op, even for an asset that does not exist
+		let issuance = Assets::total_issuance(MTR);
+		assert_ok!(Assets::update_balance(MTR, &BOB, 0));
+		assert_ok!(Assets::update_balance(100, &BOB, 0));
+		assert_eq!(Assets::balance(MTR, &BOB), INITIAL_BALANCE - 200);
+		assert_eq!(Assets::total_issuance(MTR), issuance);
+	})
+}
+
+#[test]
+fn update_balance_does_not_burn_more_than_the_balance() {
+	new_test_ext().execute_with(|| {
+		let balance = Assets::balance(MTR, &BOB) as i128;
+		assert_noop!(
+			Assets::update_balance(MTR, &BOB, -(balance + 1)),
+			DispatchError::Token(TokenError::NoFunds)
+		);
+		assert_noop!(
+			Assets::update_balance(MTR, &BOB, i128::MIN),
+			DispatchError::Token(TokenError::NoFunds)
+		);
+
+		assert_ok!(Assets::update_balance(MTR, &BOB, -balance));
+		assert_eq!(Assets::balance(MTR, &BOB), 0);
+	})
+}
@@ -0,0 +1,139 @@
+use crate::mock::*;
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{fungibles::Inspect, OnInitialize},
+};
+use pallet_standard_buyback::{BuybackPolicy, Error as BuybackError, Event as BuybackEvent};
+use pallet_standard_oracle::CurrentEra;
+use sp_runtime::{DispatchError, Perbill};
+
+const TREASURY: AccountId = 100;
+
+// Pool of 50_000 MTR and 5_000 STND, and 10_000 MTR of fees in the treasury
+fn setup() {
+	assert_ok!(Assets::mint(Origin::signed(ALICE), STND, CHARLIE, 5_000));
+	assert_ok!(Market::mint_liquidity(Origin::signed(CHARLIE), MTR, 50_000, STND, 5_000));
+	assert_ok!(Assets::mint(Origin::signed(ALICE), MTR, TREASURY, 10_000));
+}
+
+fn policy(max_slippage: u32) -> BuybackPolicy {
+	BuybackPolicy {
+		share: Perbill::from_percent(10),
+		era_cap: 1_500,
+		max_slippage: Perbill::from_percent(max_slippage),
+	}
+}
+
+fn run_buyback(block: BlockNumber) {
+	System::set_block_number(block);
+	Buyback::on_initialize(block);
+}
+
+fn last_event() -> Event {
+	System::events().pop().expect("Event expected").event
+}
+
+#[test]
+fn set_policy_requires_a_pool_with_the_governance_token() {
+	new_test_ext().execute_with(|| {
+		setup();
+		assert_noop!(
+			Buyback::set_policy(Origin::signed(ALICE), MTR, policy(5)),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Buyback::set_policy(Origin::root(), STND, policy(5)),
+			BuybackError::<Test>::InvalidAsset
+		);
+		assert_noop!(
+			Buyback::set_policy(Origin::root(), DOT, policy(5)),
+			BuybackError::<Test>::InvalidAsset
+		);
+
+		assert_ok!(Buyback::set_policy(Origin::root(), MTR, policy(5)));
+		assert_eq!(last_event(), Event::Buyback(BuybackEvent::PolicySet(MTR, policy(5))));
+		assert_eq!(Buyback::policy(MTR), Some(policy(5)));
+	})
+}
+
+#[test]
+fn buyback_burns_the_bought_tokens_up_to_the_era_cap() {
+	new_test_ext().execute_with(|| {
+		setup();
+		assert_ok!(Buyback::set_policy(Origin::root(), MTR, policy(5)));
+
+		// Only every `BuybackPeriod` blocks
+		run_buyback(15);
+		assert_eq!(Assets::balance(MTR, &TREASURY), 10_000);
+
+		// 10% of the fees buy 97 STND against 100 at the TWAP
+		run_buyback(20);
+		assert_eq!(last_event(), Event::Buyback(BuybackEvent::BoughtBack(MTR, 1_000, 97)));
+		assert_eq!(Assets::balance(MTR, &TREASURY), 9_000);
+		assert_eq!(Assets::balance(STND, &TREASURY), 0);
+		assert_eq!(Assets::total_issuance(STND), 5_000 - 97);
+		assert_eq!(Buyback::era_spent(MTR), (0, 1_000));
+
+		// The rest of the cap of the era
+		run_buyback(30);
+		assert!(matches!(
+			last_event(),
+			Event::Buyback(BuybackEvent::BoughtBack(MTR, 500, burned)) if burned > 0
+		));
+		assert_eq!(Assets::balance(MTR, &TREASURY), 8_500);
+		assert_eq!(Buyback::era_spent(MTR), (0, 1_500));
+
+		run_buyback(40);
+		assert_eq!(last_event(), Event::Buyback(BuybackEvent::EraCapReached(MTR, 0)));
+		assert_eq!(Assets::balance(MTR, &TREASURY), 8_500);
+
+		// The cap applies again in the next era
+		CurrentEra::put(1);
+		run_buyback(50);
+		assert!(matches!(
+			last_event(),
+			Event::Buyback(BuybackEvent::BoughtBack(MTR, 850, burned)) if burned > 0
+		));
+		assert_eq!(Buyback::era_spent(MTR), (1, 850));
+	})
+}
+
+#[test]
+fn buyback_fails_without_effect_beyond_the_slippage_bound() {
+	new_test_ext().execute_with(|| {
+		setup();
+		assert_ok!(Buyback::set_policy(Origin::root(), MTR, policy(1)));
+
+		// No TWAP yet to bound the slippage
+		run_buyback(10);
+		assert_eq!(
+			last_event(),
+			Event::Buyback(BuybackEvent::BuybackFailed(
+				MTR,
+				1_000,
+				BuybackError::<Test>::NoReferencePrice.into()
+			))
+		);
+
+		// 97 STND are more than 1% less than 100 at the TWAP
+		run_buyback(20);
+		assert_eq!(
+			last_event(),
+			Event::Buyback(BuybackEvent::BuybackFailed(
+				MTR,
+				1_000,
+				BuybackError::<Test>::SlippageExceeded.into()
+			))
+		);
+		assert_eq!(Assets::balance(MTR, &TREASURY), 10_000);
+		assert_eq!(Assets::total_issuance(STND), 5_000);
+		assert_eq!(Buyback::era_spent(MTR), (0, 0));
+
+		assert_ok!(Buyback::remove_policy(Origin::root(), MTR));
+		assert_eq!(last_event(), Event::Buyback(BuybackEvent::PolicyRemoved(MTR)));
+		assert_noop!(
+			Buyback::remove_policy(Origin::root(), MTR),
+			BuybackError::<Test>::PolicyNotFound
+		);
+	})
+}
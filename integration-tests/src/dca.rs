@@ -0,0 +1,151 @@
+use crate::mock::*;
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{fungibles::Inspect, OnInitialize},
+};
+use pallet_standard_dca::{Error as DcaError, Event as DcaEvent};
+use sp_runtime::{traits::One, FixedPointNumber, FixedU128};
+
+fn setup_pool() {
+	assert_ok!(Market::mint_liquidity(Origin::signed(CHARLIE), MTR, 5_000, DOT, 500));
+}
+
+fn create_order(total: u128, max_price: u128) {
+	assert_ok!(Dca::create_order(
+		Origin::signed(BOB),
+		MTR,
+		DOT,
+		total,
+		100,
+		2,
+		FixedU128::saturating_from_integer(max_price)
+	));
+}
+
+fn last_event() -> Event {
+	System::events().pop().expect("Event expected").event
+}
+
+#[test]
+fn create_order_locks_total_amount() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Dca::create_order(Origin::signed(BOB), MTR, DOT, 100, 0, 2, FixedU128::one()),
+			DcaError::<Test>::InvalidAmount
+		);
+		assert_noop!(
+			Dca::create_order(Origin::signed(BOB), MTR, DOT, 100, 100, 2, FixedU128::one()),
+			DcaError::<Test>::InvalidPair
+		);
+
+		setup_pool();
+		create_order(300, 12);
+		assert_eq!(Assets::balance(MTR, &BOB), INITIAL_BALANCE - 300);
+		assert_eq!(Assets::balance(MTR, &Dca::account_id()), 300);
+		assert_eq!(Balances::reserved_balance(&BOB), 10);
+		assert_eq!(Dca::scheduled(3), vec![0]);
+	})
+}
+
+#[test]
+fn order_swaps_every_period_until_used_up() {
+	new_test_ext().execute_with(|| {
+		setup_pool();
+		create_order(200, 12);
+
+		// 100 MTR buy 9 DOT at a price of about 11 MTR
+		Dca::on_initialize(3);
+		assert_eq!(last_event(), Event::Dca(DcaEvent::OrderExecuted(0, 100, 9)));
+		assert_eq!(Assets::balance(DOT, &BOB), INITIAL_BALANCE + 9);
+		assert_eq!(Dca::order(0).map(|order| order.remaining), Some(100));
+		assert_eq!(Dca::scheduled(5), vec![0]);
+		assert_eq!(Dca::order(0).map(|order| order.next), Some(5));
+
+		Dca::on_initialize(5);
+		assert_eq!(last_event(), Event::Dca(DcaEvent::OrderCompleted(0)));
+		assert_eq!(Assets::balance(DOT, &BOB), INITIAL_BALANCE + 18);
+		assert_eq!(Dca::order(0), None);
+		assert_eq!(Assets::balance(MTR, &Dca::account_id()), 0);
+		assert_eq!(Balances::reserved_balance(&BOB), 0);
+	})
+}
+
+#[test]
+fn order_skips_rounds_above_max_price() {
+	new_test_ext().execute_with(|| {
+		setup_pool();
+		create_order(300, 10);
+
+		Dca::on_initialize(3);
+		assert_eq!(last_event(), Event::Dca(DcaEvent::OrderSkipped(0)));
+		assert_eq!(Dca::order(0).map(|order| order.remaining), Some(300));
+		assert_eq!(Dca::scheduled(5), vec![0]);
+	})
+}
+
+#[test]
+fn order_expires_after_max_skipped_rounds() {
+	new_test_ext().execute_with(|| {
+		setup_pool();
+		create_order(300, 10);
+		create_order(300, 12);
+
+		Dca::on_initialize(3);
+		assert_eq!(Dca::order(0).map(|order| order.skipped), Some(1));
+		assert_eq!(Dca::order(1).map(|order| order.skipped), Some(0));
+
+		// The second round in a row above the max price cancels the order
+		Dca::on_initialize(5);
+		assert!(System::events()
+			.iter()
+			.any(|record| record.event == Event::Dca(DcaEvent::OrderExpired(0, 300))));
+		assert_eq!(Dca::order(0), None);
+		assert_eq!(Dca::scheduled(7), vec![1]);
+		assert_eq!(Assets::balance(MTR, &Dca::account_id()), 100);
+		assert_eq!(Assets::balance(MTR, &BOB), INITIAL_BALANCE - 300);
+		assert_eq!(Balances::reserved_balance(&BOB), 10);
+
+		// Its slot is free for a new order
+		System::set_block_number(5);
+		create_order(300, 12);
+		assert_eq!(Dca::scheduled(7), vec![1, 2]);
+	})
+}
+
+#[test]
+fn cancel_order_refunds_remaining() {
+	new_test_ext().execute_with(|| {
+		setup_pool();
+		create_order(300, 12);
+		Dca::on_initialize(3);
+
+		assert_noop!(Dca::cancel_order(Origin::signed(CHARLIE), 0), DcaError::<Test>::NotOwner);
+		assert_ok!(Dca::cancel_order(Origin::signed(BOB), 0));
+		assert_eq!(Assets::balance(MTR, &BOB), INITIAL_BALANCE - 100);
+		assert_eq!(last_event(), Event::Dca(DcaEvent::OrderCancelled(0, 200)));
+		assert_eq!(Balances::reserved_balance(&BOB), 0);
+
+		// The order leaves the schedule
+		assert!(Dca::scheduled(5).is_empty());
+		Dca::on_initialize(5);
+		assert_eq!(Assets::balance(MTR, &Market::account_id()), 5_100);
+	})
+}
+
+#[test]
+fn full_block_rejects_new_orders() {
+	new_test_ext().execute_with(|| {
+		setup_pool();
+		create_order(300, 12);
+		create_order(300, 12);
+		assert_noop!(
+			Dca::create_order(Origin::signed(BOB), MTR, DOT, 300, 100, 2, FixedU128::one()),
+			DcaError::<Test>::BlockFull
+		);
+
+		// A cancelled order frees its slot
+		assert_ok!(Dca::cancel_order(Origin::signed(BOB), 0));
+		create_order(300, 12);
+		assert_eq!(Dca::scheduled(3), vec![1, 2]);
+	})
+}
@@ -0,0 +1,15 @@
+//! Tests exercising the Standard pallets together in a single mock runtime.
+//!
+//! Each pallet keeps its own isolated mock for unit tests; flows crossing pallet boundaries
+//! (oracle prices feeding vault health, liquidations moving market reserves) belong here.
+
+#![cfg(test)]
+
+mod assets;
+mod buyback;
+mod dca;
+mod market;
+mod mock;
+mod oracle;
+mod vault;
+mod voting_power;
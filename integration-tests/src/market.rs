@@ -0,0 +1,525 @@
+use crate::mock::*;
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{
+		fungibles::{Inspect, Mutate},
+		Currency, OnFinalize, OnInitialize, ReservableCurrency,
+	},
+};
+use pallet_standard_market::{Error as MarketError, PoolParameter, RawEvent as MarketEvent};
+use primitives::{constants::asset_ids::KSM, overview::LpTokenInfo, traits::Twap, LpTokenId};
+use sp_runtime::{FixedPointNumber, FixedU128, Perbill, Permill};
+
+#[test]
+fn reserves_are_backed_by_market_account() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Market::mint_liquidity(Origin::signed(CHARLIE), MTR, 5_000, DOT, 500));
+		assert_ok!(Market::swap(Origin::signed(BOB), DOT, 100, MTR));
+		assert_eq!(Market::reserve_discrepancies(), vec![]);
+
+		// Funds leaving the market account without going through the pool
+		let (mtr_reserve, dot_reserve) = Market::reserves(LPT);
+		assert_ok!(Assets::burn_from(DOT, &Market::account_id(), 10));
+		assert_eq!(Market::reserve_discrepancies(), vec![(DOT, dot_reserve, dot_reserve - 10)]);
+		assert_eq!(Assets::balance(MTR, &Market::account_id()), mtr_reserve);
+	})
+}
+
+#[test]
+fn cost_basis_tracks_minted_and_burned_liquidity() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Market::mint_liquidity(Origin::signed(CHARLIE), MTR, 5_000, DOT, 500));
+		assert_eq!(Market::cost_basis(CHARLIE, LPT), Some((1_580, 5_000, 500)));
+		assert_ok!(Market::swap(Origin::signed(BOB), DOT, 100, MTR));
+
+		let returns = Market::liquidity_returns(&CHARLIE);
+		assert_eq!(returns.len(), 1);
+		assert_eq!(returns[0].deposited, [(MTR, 5_000), (DOT, 500)]);
+		assert_eq!(returns[0].current, [(MTR, 5_000 - 831), (DOT, 600)]);
+
+		// DOT at 5 makes the pool sell it below the price of holding it
+		let usd = FixedU128::saturating_from_integer;
+		let priced = returns[0].clone().priced(|id| match id {
+			MTR => Some(usd(1)),
+			DOT => Some(usd(5)),
+			_ => None,
+		});
+		assert_eq!((priced.deposit_value, priced.current_value), (Some(7_500), Some(7_169)));
+		assert_eq!(priced.impermanent_loss(), Some(Perbill::from_rational(331u128, 7_500)));
+		assert_eq!(returns[0].clone().priced(|_| None).impermanent_loss(), None);
+
+		// Burns remove their share of the deposits
+		assert_ok!(Market::burn_liquidity(Origin::signed(CHARLIE), LPT, 790));
+		assert_eq!(Market::cost_basis(CHARLIE, LPT), Some((790, 2_500, 250)));
+		assert_ok!(Market::burn_liquidity(Origin::signed(CHARLIE), LPT, 790));
+		assert_eq!(Market::cost_basis(CHARLIE, LPT), None);
+		assert_eq!(Market::liquidity_returns(&CHARLIE), vec![]);
+	})
+}
+
+#[test]
+fn locked_native_currency_discounts_swap_fee() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Market::mint_liquidity(Origin::signed(CHARLIE), MTR, 5_000, DOT, 500));
+		assert_noop!(
+			Market::lock_for_fee_discount(Origin::signed(BOB), 10_000, 10),
+			MarketError::<Test>::LockTooShort
+		);
+
+		// 10_000 reaches the 20 bps tier, leaving a 10 bps fee
+		assert_ok!(Market::lock_for_fee_discount(Origin::signed(BOB), 10_000, 11));
+		assert_eq!(
+			System::events().pop().expect("Event expected").event,
+			Event::Market(MarketEvent::FeeDiscountLocked(BOB, 10_000, 11))
+		);
+		assert_eq!(Balances::usable_balance(&BOB), INITIAL_BALANCE - 10_000);
+		assert_eq!(Market::fee_discount(&BOB), 20);
+		assert_noop!(
+			Market::lock_for_fee_discount(Origin::signed(BOB), 5_000, 11),
+			MarketError::<Test>::LockReduced
+		);
+
+		assert_eq!(Market::quote(DOT, 100, MTR), Ok(831));
+		assert_ok!(Market::swap(Origin::signed(BOB), DOT, 100, MTR));
+		assert_eq!(Assets::balance(MTR, &BOB), INITIAL_BALANCE + 832);
+
+		System::set_block_number(5);
+		assert_noop!(
+			Market::unlock_fee_discount(Origin::signed(BOB)),
+			MarketError::<Test>::FeeLockActive
+		);
+
+		// The discount ends with the lock, which can then be released
+		System::set_block_number(11);
+		assert_eq!(Market::fee_discount(&BOB), 0);
+		assert_ok!(Market::unlock_fee_discount(Origin::signed(BOB)));
+		assert_eq!(Balances::usable_balance(&BOB), INITIAL_BALANCE);
+		assert_eq!(Market::fee_lock(&BOB), None);
+	})
+}
+
+#[test]
+fn swap_volume_is_tracked_per_era() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Market::mint_liquidity(Origin::signed(CHARLIE), MTR, 5_000, DOT, 500));
+
+		// Volume is counted in MTR, the first token of the pair
+		assert_ok!(Market::swap(Origin::signed(BOB), DOT, 100, MTR));
+		assert_ok!(Market::swap(Origin::signed(BOB), MTR, 100, DOT));
+		assert_eq!(Market::current_volume_era(), 0);
+		assert_eq!(Market::account_volume(0, BOB), vec![(LPT, 931)]);
+		assert_eq!(Market::pair_volume(0, LPT), 931);
+
+		System::set_block_number(10);
+		Market::on_initialize(10);
+		assert_ok!(Market::swap(Origin::signed(BOB), MTR, 100, DOT));
+		assert_eq!(Market::account_volume(1, BOB), vec![(LPT, 100)]);
+		assert_eq!(Market::account_volume(0, BOB), vec![(LPT, 931)]);
+
+		// Only the previous era is kept
+		System::set_block_number(20);
+		Market::on_initialize(20);
+		assert_eq!(Market::account_volume(0, BOB), vec![]);
+		assert_eq!(Market::pair_volume(0, LPT), 0);
+		assert_eq!(Market::pair_volume(1, LPT), 100);
+	})
+}
+
+#[test]
+fn swap_fees_are_accrued_per_pool() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Market::mint_liquidity(Origin::signed(CHARLIE), MTR, 50_000, DOT, 5_000));
+
+		// Fees are kept in the input token, 30 bps rounded down
+		assert_ok!(Market::swap(Origin::signed(BOB), DOT, 1_000, MTR));
+		assert_ok!(Market::swap(Origin::signed(BOB), MTR, 10_000, DOT));
+		let stats = Market::pool_stats(LPT).expect("Pool exists");
+		let (mtr_reserve, dot_reserve) = Market::reserves(LPT);
+		assert_eq!(stats.reserves, [(MTR, mtr_reserve), (DOT, dot_reserve)]);
+		assert_eq!(stats.lp_supply, Assets::total_issuance(LPT.0));
+		assert_eq!((stats.fees_24h, stats.fees_7d), ([30, 3], [30, 3]));
+		assert_eq!(stats.reserve_value, None);
+		assert_eq!(Market::pool_stats(LpTokenId(LPT.0 + 1)), None);
+
+		// A day later the first fees only count for the week, buckets being 2 blocks long
+		System::set_block_number(50);
+		assert_ok!(Market::swap(Origin::signed(BOB), MTR, 1_000, DOT));
+		let stats = Market::pool_stats(LPT).expect("Pool exists");
+		assert_eq!((stats.fees_24h, stats.fees_7d), ([3, 0], [33, 3]));
+
+		// And are removed a week after
+		System::set_block_number(336);
+		Market::on_initialize(336);
+		assert_eq!(Market::pool_fees(0, LPT), (0, 0));
+		let usd = FixedU128::saturating_from_integer;
+		let stats = Market::pool_stats(LPT)
+			.expect("Pool exists")
+			.priced(|id| (id == MTR).then(|| usd(2)).or((id == DOT).then(|| usd(25))));
+		let (mtr_reserve, dot_reserve) = Market::reserves(LPT);
+		assert_eq!((stats.fees_24h, stats.fees_7d), ([0, 0], [3, 0]));
+		assert_eq!(stats.reserve_value, Some(2 * mtr_reserve + 25 * dot_reserve));
+		assert_eq!((stats.fee_value_24h, stats.fee_value_7d), (Some(0), Some(6)));
+		assert_eq!(Market::all_pool_stats(), vec![Market::pool_stats(LPT).unwrap()]);
+
+		// Without a price for both assets the pool is not valued
+		let stats = Market::pool_stats(LPT).unwrap().priced(|id| (id == MTR).then(|| usd(2)));
+		assert_eq!((stats.reserve_value, stats.fee_value_7d), (None, None));
+	})
+}
+
+#[test]
+fn twap_window_is_bounded_by_price_observations() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Market::mint_liquidity(Origin::signed(CHARLIE), MTR, 5_000, DOT, 500));
+
+		// Pools without swaps are observed at the start of every TWAP period, keeping the last 4
+		for now in [10, 20, 30, 40] {
+			System::set_block_number(now);
+			Market::on_initialize(now);
+		}
+		let blocks: Vec<_> = Market::price_observations(LPT).iter().map(|obs| obs.2).collect();
+		assert_eq!(blocks, vec![10, 20, 30, 40]);
+
+		System::set_block_number(45);
+		let ten = FixedU128::saturating_from_integer(10);
+		assert_eq!(Market::twap_over(DOT, MTR, 10), Some(ten));
+		assert_eq!(
+			Market::twap_over(MTR, DOT, 10),
+			Some(FixedU128::saturating_from_rational(1, 10))
+		);
+		assert_eq!(<Market as Twap<BlockNumber>>::twap(DOT, MTR, 35), Some(ten));
+		assert_eq!(Market::twap_over(DOT, MTR, 36), None);
+		assert_eq!(Market::twap_over(DOT, STND, 10), None);
+	})
+}
+
+#[test]
+fn lp_tokens_are_registered_per_pair() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Market::mint_liquidity(Origin::signed(CHARLIE), MTR, 5_000, DOT, 500));
+		assert_eq!(AssetRegistry::asset_ids(b"LP-MTR-DOT".to_vec()), Some(LPT.0));
+
+		// The second pair gets its own liquidity token, named after the pair ordered by id
+		let second = LpTokenId(LPT.0 + 1);
+		assert_ok!(Assets::force_create(Origin::root(), second.0, ALICE, true, 1));
+		assert_ok!(Assets::mint_into(STND, &CHARLIE, 1_000));
+		assert_ok!(Market::mint_liquidity(Origin::signed(CHARLIE), DOT, 100, STND, 1_000));
+		assert_eq!(Market::pair((STND, DOT)), Some(second));
+		assert_eq!(
+			Market::lp_tokens(),
+			vec![
+				LpTokenInfo { lp_token: LPT, pair: (MTR, DOT), symbol: b"LP-MTR-DOT".to_vec() },
+				LpTokenInfo {
+					lp_token: second,
+					pair: (STND, DOT),
+					symbol: b"LP-STND-DOT".to_vec()
+				},
+			]
+		);
+		assert_eq!(Market::lp_token_info(LpTokenId(MTR)), None);
+
+		// Assets without a registered name are labelled by their id
+		assert_eq!(Market::lp_token_symbol(KSM, MTR), b"LP-MTR-4".to_vec());
+	})
+}
+
+#[test]
+fn committed_swaps_are_revealed_in_a_later_block() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Market::mint_liquidity(Origin::signed(CHARLIE), MTR, 5_000, DOT, 500));
+		let salt = [7; 32];
+		let reveal = |who, min_amount_out| {
+			Market::reveal_swap(Origin::signed(who), DOT, 100, MTR, min_amount_out, salt)
+		};
+		let commitment = Market::swap_commitment(&BOB, DOT, 100, MTR, 800, salt);
+		assert_ok!(Market::commit_swap(Origin::signed(BOB), commitment));
+		assert_eq!(Balances::reserved_balance(&BOB), 100);
+		assert_noop!(
+			Market::commit_swap(Origin::signed(BOB), commitment),
+			MarketError::<Test>::CommitmentExists
+		);
+		assert_noop!(reveal(BOB, 800), MarketError::<Test>::RevealTooEarly);
+
+		// Only the committed order of the committer is revealed
+		System::set_block_number(2);
+		assert_noop!(reveal(BOB, 700), MarketError::<Test>::CommitmentNotFound);
+		assert_noop!(reveal(CHARLIE, 800), MarketError::<Test>::CommitmentNotFound);
+		assert_ok!(reveal(BOB, 800));
+		assert_eq!(Assets::balance(MTR, &BOB), INITIAL_BALANCE + 831);
+		assert_eq!(Balances::reserved_balance(&BOB), 0);
+		System::assert_last_event(Event::Market(MarketEvent::SwapRevealed(BOB, commitment)));
+
+		// The revealed order still has to pay out its minimum
+		let greedy = Market::swap_commitment(&BOB, DOT, 100, MTR, 1_000, salt);
+		assert_ok!(Market::commit_swap(Origin::signed(BOB), greedy));
+		System::set_block_number(3);
+		assert_noop!(reveal(BOB, 1_000), MarketError::<Test>::InsufficientOutputAmount);
+
+		// Commitments not revealed within the window forfeit their bond
+		System::set_block_number(5);
+		assert_noop!(
+			Market::clear_expired_commitment(Origin::signed(CHARLIE), greedy),
+			MarketError::<Test>::CommitmentNotExpired
+		);
+		System::set_block_number(6);
+		assert_noop!(reveal(BOB, 1_000), MarketError::<Test>::CommitmentExpired);
+		let issuance = Balances::total_issuance();
+		assert_ok!(Market::clear_expired_commitment(Origin::signed(CHARLIE), greedy));
+		assert_eq!(Market::swap_commit(greedy), None);
+		assert_eq!(Balances::reserved_balance(&BOB), 0);
+		assert_eq!(Balances::total_issuance(), issuance - 100);
+	})
+}
+
+#[test]
+fn batched_swaps_clear_at_a_single_price() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Market::mint_liquidity(Origin::signed(CHARLIE), MTR, 5_000, DOT, 500));
+		assert_noop!(
+			Market::batch_swap(Origin::signed(BOB), DOT, 100, MTR, 0),
+			MarketError::<Test>::PairNotInBatchMode
+		);
+		assert_ok!(Market::set_batch_mode(Origin::root(), LPT, true));
+
+		// Swaps in the pair are reserved in the accounts of their owners until the end of the
+		// block, which cannot move the reserved assets meanwhile and keep the minimum balance
+		assert_ok!(Market::swap(Origin::signed(BOB), DOT, 100, MTR));
+		assert_ok!(Market::batch_swap(Origin::signed(CHARLIE), MTR, 500, DOT, 54));
+		assert_noop!(
+			Market::batch_swap(Origin::signed(BOB), DOT, 100, MTR, 0),
+			MarketError::<Test>::TooManyBatchOrders
+		);
+		assert_eq!(Assets::balance(DOT, &BOB), INITIAL_BALANCE);
+		assert_eq!(Market::reserved_asset(DOT, &BOB), 100);
+		assert_eq!(Assets::reducible_balance(DOT, &BOB, false), INITIAL_BALANCE - 101);
+		assert_noop!(
+			Assets::transfer(Origin::signed(BOB), DOT, ALICE, INITIAL_BALANCE),
+			pallet_assets::Error::<Test>::BalanceLow
+		);
+		assert_eq!(Market::reserves(LPT), (5_000, 500));
+		assert_eq!(Market::reserve_discrepancies(), vec![]);
+
+		// Opposing orders are matched and only their net flow is swapped against the pool, paying
+		// BOB more than the 831 MTR of a direct swap
+		let charlie_dot = Assets::balance(DOT, &CHARLIE);
+		Market::on_finalize(1);
+		assert_eq!(Assets::balance(MTR, &BOB), INITIAL_BALANCE + 914);
+		assert_eq!(Assets::balance(DOT, &CHARLIE), charlie_dot + 54);
+		assert_eq!(Market::reserves(LPT), (4_586, 546));
+		assert_eq!(Market::batch_orders(LPT), vec![]);
+		assert_eq!(Market::reserved_asset(DOT, &BOB), 0);
+
+		// Orders paid out less than their minimum are refunded and the batch cleared without them
+		System::set_block_number(2);
+		let charlie_mtr = Assets::balance(MTR, &CHARLIE);
+		assert_ok!(Market::swap(Origin::signed(BOB), DOT, 100, MTR));
+		assert_ok!(Market::batch_swap(Origin::signed(CHARLIE), MTR, 500, DOT, 64));
+		Market::on_finalize(2);
+		assert_eq!(Assets::balance(MTR, &CHARLIE), charlie_mtr);
+		assert_eq!(Market::reserved_asset(MTR, &CHARLIE), 0);
+		System::assert_has_event(Event::Market(MarketEvent::BatchOrderRefunded(
+			CHARLIE, LPT, MTR, 500,
+		)));
+		assert_eq!(Assets::balance(MTR, &BOB), INITIAL_BALANCE + 914 + 708);
+		assert_eq!(Market::reserves(LPT), (3_878, 646));
+		assert_eq!(Market::reserve_discrepancies(), vec![]);
+	})
+}
+
+#[test]
+fn block_prices_are_emitted_for_traded_pools() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Market::mint_liquidity(Origin::signed(CHARLIE), MTR, 5_000, DOT, 500));
+		Market::on_finalize(1);
+		System::assert_last_event(Event::Market(MarketEvent::BlockPrices(vec![(
+			LPT,
+			FixedU128::from_inner(0),
+			FixedU128::saturating_from_rational(500, 5_000),
+		)])));
+
+		// The open price is taken before the first swap of the block
+		System::set_block_number(2);
+		assert_ok!(Market::swap(Origin::signed(BOB), DOT, 100, MTR));
+		assert_ok!(Market::swap(Origin::signed(BOB), MTR, 100, DOT));
+		assert_eq!(Market::reserves(LPT), (4_269, 586));
+		Market::on_finalize(2);
+		System::assert_last_event(Event::Market(MarketEvent::BlockPrices(vec![(
+			LPT,
+			FixedU128::saturating_from_rational(500, 5_000),
+			FixedU128::saturating_from_rational(586, 4_269),
+		)])));
+		assert_eq!(Market::block_open_price(LPT), None);
+
+		// Blocks without trades emit nothing
+		System::set_block_number(3);
+		System::reset_events();
+		Market::on_finalize(3);
+		assert_eq!(System::events(), vec![]);
+	})
+}
+
+#[test]
+fn unchallenged_parameter_proposals_apply() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Market::mint_liquidity(Origin::signed(CHARLIE), MTR, 5_000, DOT, 500));
+		let propose =
+			|parameter| Market::propose_pool_parameter(Origin::signed(BOB), LPT, parameter);
+		assert_noop!(propose(PoolParameter::SwapFee(101)), MarketError::<Test>::InvalidParameter);
+		// The 4 price observations kept 10 blocks apart cover TWAP windows of 10 to 30 blocks
+		assert_noop!(propose(PoolParameter::TwapWindow(31)), MarketError::<Test>::InvalidParameter);
+		assert_ok!(propose(PoolParameter::SwapFee(10)));
+		assert_ok!(propose(PoolParameter::TwapWindow(30)));
+		assert_noop!(
+			propose(PoolParameter::SwapFee(20)),
+			MarketError::<Test>::TooManyParameterProposals
+		);
+		assert_eq!(Balances::reserved_balance(&BOB), 2_000);
+
+		// The changes apply once the challenge period ends
+		Market::on_initialize(5);
+		assert_eq!(Market::swap_fee_of(LPT), 30);
+		System::set_block_number(6);
+		Market::on_initialize(6);
+		assert_eq!(Market::swap_fee_of(LPT), 10);
+		assert_eq!(Market::pool_twap_window(LPT), Some(30));
+		assert_eq!(Balances::reserved_balance(&BOB), 0);
+		assert_eq!(Market::quote(DOT, 100, MTR), Ok(832));
+		assert_ok!(Market::swap(Origin::signed(BOB), DOT, 100, MTR));
+		assert_eq!(Assets::balance(MTR, &BOB), INITIAL_BALANCE + 832);
+	})
+}
+
+#[test]
+fn challenged_parameter_proposals_are_rejected() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Market::mint_liquidity(Origin::signed(CHARLIE), MTR, 5_000, DOT, 500));
+		let propose = || {
+			Market::propose_pool_parameter(Origin::signed(BOB), LPT, PoolParameter::SwapFee(100))
+		};
+		let challenge =
+			|id, amount| Market::challenge_pool_parameter(Origin::signed(CHARLIE), id, amount);
+		assert_ok!(propose());
+		let id = Market::next_parameter_proposal_id();
+
+		// Challenges below 10% of the 1_580 liquidity tokens leave the proposal open
+		assert_ok!(challenge(id, 100));
+		assert_eq!(Assets::balance(LPT.0, &CHARLIE), 1_580);
+		assert_eq!(Market::reserved_asset(LPT.0, &CHARLIE), 100);
+		assert_eq!(Market::parameter_proposal(id).map(|proposal| proposal.challenged), Some(100));
+		let issuance = Balances::total_issuance();
+		assert_ok!(challenge(id, 58));
+		System::assert_last_event(Event::Market(MarketEvent::ParameterProposalRejected(id)));
+		assert_eq!(Market::parameter_proposal(id), None);
+		assert_eq!(Market::reserved_asset(LPT.0, &CHARLIE), 0);
+		assert_eq!(Balances::reserved_balance(&BOB), 0);
+		assert_eq!(Balances::total_issuance(), issuance - 1_000);
+
+		// Proposals can only be challenged within their challenge period
+		assert_ok!(propose());
+		System::set_block_number(6);
+		assert_noop!(challenge(id + 1, 100), MarketError::<Test>::ChallengePeriodOver);
+		Market::on_initialize(6);
+		assert_eq!(Market::swap_fee_of(LPT), 100);
+	})
+}
+
+#[test]
+fn large_burns_in_guarded_pools_are_queued() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Market::mint_liquidity(Origin::signed(CHARLIE), MTR, 5_000, DOT, 500));
+		let guard = Some(Permill::from_percent(10));
+		assert_noop!(
+			Market::set_withdrawal_guard(Origin::signed(CHARLIE), LPT, guard),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(Market::set_withdrawal_guard(Origin::root(), LPT, guard));
+		assert_eq!(Assets::total_issuance(LPT.0), 1_580);
+
+		// Burns up to 10% of the supply go through at once
+		assert_ok!(Market::burn_liquidity(Origin::signed(CHARLIE), LPT, 150));
+		assert_eq!(Assets::total_issuance(LPT.0), 1_430);
+
+		assert_ok!(Market::burn_liquidity(Origin::signed(CHARLIE), LPT, 500));
+		assert_eq!(Assets::total_issuance(LPT.0), 1_430);
+		assert_eq!(Market::reserved_asset(LPT.0, &CHARLIE), 500);
+		assert_eq!(Assets::balance(LPT.0, &CHARLIE), 1_430);
+		System::assert_last_event(Event::Market(MarketEvent::WithdrawalQueued(
+			0, CHARLIE, LPT, 500, 11,
+		)));
+		assert_noop!(
+			Market::execute_withdrawal(Origin::signed(BOB), 0),
+			MarketError::<Test>::WithdrawalDelayed
+		);
+
+		// Governance cancels a queued burn by releasing the liquidity tokens
+		assert_ok!(Market::burn_liquidity(Origin::signed(CHARLIE), LPT, 200));
+		assert_eq!(Market::reserved_asset(LPT.0, &CHARLIE), 700);
+		assert_ok!(Market::cancel_withdrawal(Origin::root(), 1));
+		assert_eq!(Market::reserved_asset(LPT.0, &CHARLIE), 500);
+		assert_eq!(Market::queued_withdrawal(1), None);
+
+		// Anyone executes the burn for the owner after the delay
+		System::set_block_number(11);
+		let (mtr_reserve, dot_reserve) = Market::reserves(LPT);
+		let dot_before = Assets::balance(DOT, &CHARLIE);
+		assert_ok!(Market::execute_withdrawal(Origin::signed(BOB), 0));
+		assert_eq!(Assets::total_issuance(LPT.0), 930);
+		assert_eq!(Assets::balance(LPT.0, &CHARLIE), 930);
+		assert_eq!(Market::reserved_asset(LPT.0, &CHARLIE), 0);
+		assert_eq!(Assets::balance(DOT, &CHARLIE), dot_before + dot_reserve * 500 / 1_430);
+		assert!(Market::reserves(LPT).0 < mtr_reserve);
+		assert_eq!(Market::reserve_discrepancies(), vec![]);
+		assert_noop!(
+			Market::execute_withdrawal(Origin::signed(BOB), 0),
+			MarketError::<Test>::WithdrawalNotFound
+		);
+	})
+}
+
+#[test]
+fn swap_fee_changes_are_enacted_after_the_delay() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Market::mint_liquidity(Origin::signed(CHARLIE), MTR, 5_000, DOT, 500));
+		let delay = RiskEnactmentDelay::get();
+		assert_noop!(
+			Market::schedule_swap_fee(Origin::signed(BOB), LPT, Some(10), delay),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Market::schedule_swap_fee(Origin::root(), LPT, Some(10), delay - 1),
+			MarketError::<Test>::DelayTooShort
+		);
+		assert_noop!(
+			Market::schedule_swap_fee(Origin::root(), LPT, Some(101), delay),
+			MarketError::<Test>::InvalidParameter
+		);
+		assert_noop!(
+			Market::cancel_scheduled_swap_fee(Origin::root(), LPT),
+			MarketError::<Test>::NotScheduled
+		);
+
+		let when = System::block_number() + delay;
+		assert_ok!(Market::schedule_swap_fee(Origin::root(), LPT, Some(10), delay));
+		System::assert_last_event(Event::Market(MarketEvent::SwapFeeScheduled(
+			LPT,
+			Some(10),
+			when,
+		)));
+		assert_noop!(
+			Market::schedule_swap_fee(Origin::root(), LPT, Some(20), delay),
+			MarketError::<Test>::ScheduleFailed
+		);
+		Scheduler::on_initialize(when - 1);
+		assert_eq!(Market::swap_fee_of(LPT), 30);
+		Scheduler::on_initialize(when);
+		assert_eq!(Market::swap_fee_of(LPT), 10);
+
+		// A cancelled change is never enacted
+		System::set_block_number(when);
+		assert_ok!(Market::schedule_swap_fee(Origin::root(), LPT, None, delay));
+		assert_ok!(Market::cancel_scheduled_swap_fee(Origin::root(), LPT));
+		System::assert_last_event(Event::Market(MarketEvent::SwapFeeScheduleCancelled(LPT)));
+		Scheduler::on_initialize(when + delay);
+		assert_eq!(Market::swap_fee_of(LPT), 10);
+	})
+}
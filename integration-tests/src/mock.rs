@@ -0,0 +1,391 @@
+use frame_support::{
+	parameter_types,
+	traits::{ConstU128, ConstU32, ConstU64, EqualPrivilegeOnly, Everything},
+	PalletId,
+};
+use frame_system::EnsureRoot;
+use pallet_assets::FrozenBalance;
+use pallet_standard_oracle::price_from_reported;
+use primitives::{
+	constants::asset_ids, traits::GovernancePositions, AssetId, Balance, LpTokenId, RoundIndex,
+	VolumeEra,
+};
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+	FixedU128, Percent, Permill,
+};
+
+pub(crate) type AccountId = u64;
+pub(crate) type BlockNumber = u64;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+pub const ALICE: AccountId = 1;
+pub const BOB: AccountId = 2;
+pub const CHARLIE: AccountId = 3;
+
+pub const STND: AssetId = asset_ids::STND;
+pub const MTR: AssetId = asset_ids::MTR;
+pub const DOT: AssetId = asset_ids::DOT;
+/// Id the registry hands out to the first liquidity pool token.
+pub const LPT: LpTokenId = LpTokenId(asset_ids::NEXT_ASSET_ID);
+
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		Scheduler: pallet_scheduler::{Pallet, Call, Storage, Event<T>},
+		Assets: pallet_assets::{Pallet, Call, Storage, Config<T>, Event<T>},
+		AssetRegistry: pallet_asset_registry::{Pallet, Storage, Config<T>},
+		Market: pallet_standard_market::{Pallet, Call, Storage, Event<T>},
+		Oracle: pallet_standard_oracle::{Pallet, Call, Storage, Event<T>, Config<T>},
+		Vault: pallet_standard_vault::{Pallet, Call, Storage, Event<T>},
+		Dca: pallet_standard_dca::{Pallet, Call, Storage, Event<T>},
+		Buyback: pallet_standard_buyback::{Pallet, Call, Storage, Event<T>},
+		VotingPower: pallet_standard_voting_power::{Pallet, Call, Storage, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+}
+
+impl frame_system::Config for Test {
+	type OnSetCode = ();
+	type BaseCallFilter = Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Index = u64;
+	type BlockNumber = BlockNumber;
+	type Call = Call;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<Balance>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: Balance = 1;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type MaxLocks = ();
+	type Balance = Balance;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const MaximumSchedulerWeight: frame_support::weights::Weight = 1_000_000_000;
+}
+
+impl pallet_scheduler::Config for Test {
+	type Event = Event;
+	type Origin = Origin;
+	type PalletsOrigin = OriginCaller;
+	type Call = Call;
+	type MaximumWeight = MaximumSchedulerWeight;
+	type ScheduleOrigin = EnsureRoot<AccountId>;
+	type MaxScheduledPerBlock = ConstU32<10>;
+	type WeightInfo = ();
+	type OriginPrivilegeCmp = EqualPrivilegeOnly;
+	type PreimageProvider = ();
+	type NoPreimagePostponement = ();
+}
+
+impl pallet_assets::Config for Test {
+	type Event = Event;
+	type Balance = Balance;
+	type AssetId = AssetId;
+	type Currency = Balances;
+	type ForceOrigin = EnsureRoot<AccountId>;
+	type AssetDeposit = ();
+	type AssetAccountDeposit = ();
+	type MetadataDepositBase = ();
+	type MetadataDepositPerByte = ();
+	type ApprovalDeposit = ();
+	type StringLimit = ConstU32<50>;
+	type Freezer = AssetFreezer;
+	type Extra = ();
+	type WeightInfo = ();
+}
+
+impl pallet_asset_registry::Config for Test {
+	type AssetId = AssetId;
+}
+
+parameter_types! {
+	pub const SysPalletId: PalletId = PalletId(*b"stnd/mkt");
+	pub const VltPalletId: PalletId = PalletId(*b"stnd/vlt");
+	pub const RiskEnactmentDelay: BlockNumber = 10;
+	pub const LiquidationGracePeriod: BlockNumber = 3;
+	pub MtrPeg: FixedU128 = price_from_reported(100);
+	pub const PegTolerance: Permill = Permill::from_percent(1);
+	pub const RateUpdatePeriod: BlockNumber = 10;
+	pub const MintEraLength: BlockNumber = 10;
+	pub const MtrAssetId: AssetId = MTR;
+	pub const NativeAssetId: AssetId = STND;
+	pub const TwapPeriod: BlockNumber = 10;
+	pub const MaxTwapObservations: u32 = 4;
+	pub FeeDiscountTiers: Vec<(Balance, u32)> = vec![(1_000, 10), (10_000, 20), (50_000, 30)];
+	pub const MinFeeLockPeriod: BlockNumber = 10;
+	pub const VolumeEraLength: BlockNumber = 10;
+	pub const VolumeHistoryDepth: VolumeEra = 1;
+	pub const GuardedWithdrawalDelay: BlockNumber = 10;
+	pub const SwapRevealWindow: BlockNumber = 3;
+	pub const ParameterChallengePeriod: BlockNumber = 5;
+	pub const ParameterChallengeQuorum: Permill = Permill::from_percent(10);
+	pub const BlocksPerDay: BlockNumber = 48;
+	pub const TreasuryAccount: AccountId = 100;
+	pub const StalePeriod: BlockNumber = 100;
+	pub const OracleRoundLength: BlockNumber = 1;
+	pub const OracleRoundsPerEra: RoundIndex = 10;
+	pub const OracleMaxMissedRounds: Percent = Percent::from_percent(50);
+	pub const OracleMinRequestFee: Balance = 1;
+	pub const OracleMaxRequestPeriod: BlockNumber = 10;
+	pub const OracleMaxSymbolLength: u32 = 16;
+	pub const OracleRequestQuorum: u32 = 1;
+	pub const OracleMaxReportsPerRound: u32 = 16;
+}
+
+impl pallet_standard_market::Config for Test {
+	type Event = Event;
+	type Assets = Assets;
+	type SystemPalletId = SysPalletId;
+	type TwapPeriod = TwapPeriod;
+	type MaxTwapObservations = MaxTwapObservations;
+	type Currency = Balances;
+	type FeeDiscountTiers = FeeDiscountTiers;
+	type MinFeeLockPeriod = MinFeeLockPeriod;
+	type VolumeEraLength = VolumeEraLength;
+	type VolumeHistoryDepth = VolumeHistoryDepth;
+	type GuardedWithdrawalDelay = GuardedWithdrawalDelay;
+	type BlocksPerDay = BlocksPerDay;
+	type SwapCommitBond = ConstU128<100>;
+	type SwapRevealWindow = SwapRevealWindow;
+	type MaxBatchOrders = ConstU32<2>;
+	type ParameterProposalBond = ConstU128<1_000>;
+	type ParameterChallengePeriod = ParameterChallengePeriod;
+	type ParameterChallengeQuorum = ParameterChallengeQuorum;
+	type MaxParameterProposals = ConstU32<2>;
+	type SharedFunds = Vault;
+	type Proposal = Call;
+	type PalletsOrigin = OriginCaller;
+	type Scheduler = Scheduler;
+	type MinimumEnactmentDelay = RiskEnactmentDelay;
+}
+
+impl pallet_standard_oracle::Config for Test {
+	type Event = Event;
+	type WeightInfo = ();
+	type StalePeriod = StalePeriod;
+	type Twap = Market;
+	type TwapWindow = TwapPeriod;
+	type TwapQuoteAsset = MtrAssetId;
+	type RoundLength = OracleRoundLength;
+	type RoundsPerEra = OracleRoundsPerEra;
+	type MaxMissedRounds = OracleMaxMissedRounds;
+	type Currency = Balances;
+	type MinRequestFee = OracleMinRequestFee;
+	type MaxRequestPeriod = OracleMaxRequestPeriod;
+	type MaxSymbolLength = OracleMaxSymbolLength;
+	type RequestQuorum = OracleRequestQuorum;
+	type OnPriceResponse = ();
+	type OnRoundClosed = ();
+	type MaxReportsPerRound = OracleMaxReportsPerRound;
+	type EraFinder = Oracle;
+	type EraReward = ConstU128<0>;
+	type RewardPot = TreasuryAccount;
+	type HeartbeatPeriod = ConstU64<100>;
+	type HeartbeatSlash = ConstU128<1>;
+	type MaxMissedHeartbeats = ConstU32<3>;
+	type ProviderValidators = ();
+	type Proposal = Call;
+	type PalletsOrigin = OriginCaller;
+	type Scheduler = Scheduler;
+	type MinimumEnactmentDelay = RiskEnactmentDelay;
+}
+
+impl pallet_standard_vault::Config for Test {
+	type Event = Event;
+	type VaultPalletId = VltPalletId;
+	type Assets = Assets;
+	type SystemPalletId = SysPalletId;
+	type MtrAssetId = MtrAssetId;
+	type NativeAssetId = NativeAssetId;
+	type Proposal = Call;
+	type PalletsOrigin = OriginCaller;
+	type Scheduler = Scheduler;
+	type RiskOrigin = EnsureRoot<AccountId>;
+	type MinimumEnactmentDelay = RiskEnactmentDelay;
+	type LiquidationGracePeriod = LiquidationGracePeriod;
+	type MtrPeg = MtrPeg;
+	type PegTolerance = PegTolerance;
+	type RateUpdatePeriod = RateUpdatePeriod;
+	type MaxCollateralAssets = ConstU32<2>;
+	type MaxLeverageIterations = ConstU32<3>;
+	type MinCollateralProviders = ConstU32<1>;
+	type MinCollateralLiquidity = ConstU128<1_000>;
+	type MaxMintPerBlock = ConstU128<10_000>;
+	type MaxMintPerEra = ConstU128<20_000>;
+	type MintEraLength = MintEraLength;
+	type MaxIdleLiquidations = ConstU32<1>;
+	type PurgeReward = ConstU128<10>;
+	type TreasuryAccount = TreasuryAccount;
+	type HistoryDepth = ConstU64<2>;
+	type MaxPriceAge = ConstU64<10>;
+	type FrozenCollateral = VotingPower;
+}
+
+parameter_types! {
+	pub const DcaPalletId: PalletId = PalletId(*b"stnd/dca");
+}
+
+impl pallet_standard_dca::Config for Test {
+	type Event = Event;
+	type PalletId = DcaPalletId;
+	type MaxOrdersPerBlock = ConstU32<2>;
+	type MaxSkippedRounds = ConstU32<2>;
+	type OrderDeposit = ConstU128<10>;
+}
+
+impl pallet_standard_buyback::Config for Test {
+	type Event = Event;
+	type GovernanceOrigin = EnsureRoot<AccountId>;
+	type Treasury = TreasuryAccount;
+	type BuybackAsset = NativeAssetId;
+	type BuybackPeriod = ConstU64<10>;
+	type EraFinder = Oracle;
+	type MaxPolicies = ConstU32<2>;
+}
+
+/// Freezes the assets reserved by the market and the liquidity tokens backing votes, as the
+/// runtimes do.
+pub struct AssetFreezer;
+
+impl FrozenBalance<AssetId, AccountId, Balance> for AssetFreezer {
+	fn frozen_balance(asset: AssetId, who: &AccountId) -> Option<Balance> {
+		match (
+			<Market as FrozenBalance<_, _, _>>::frozen_balance(asset, who),
+			<VotingPower as FrozenBalance<_, _, _>>::frozen_balance(asset, who),
+		) {
+			(Some(reserved), Some(frozen)) => Some(reserved.saturating_add(frozen)),
+			(reserved, frozen) => reserved.or(frozen),
+		}
+	}
+
+	fn died(asset: AssetId, who: &AccountId) {
+		<Market as FrozenBalance<_, _, _>>::died(asset, who);
+		<VotingPower as FrozenBalance<_, _, _>>::died(asset, who);
+	}
+}
+
+/// STND of an account in the market pools and in its vault, as the runtimes count them.
+pub struct DefiPositions;
+
+impl GovernancePositions<AccountId> for DefiPositions {
+	fn liquidity(who: &AccountId, lp_token: LpTokenId) -> Option<(Balance, Balance)> {
+		let (token0, token1) = Market::lp_token_info(lp_token)?.pair;
+		if token0 != STND && token1 != STND {
+			return None
+		}
+		Some(
+			Market::liquidity_position(who, lp_token)
+				.map(|position| {
+					let underlying = position
+						.underlying
+						.iter()
+						.find(|(asset, _)| *asset == STND)
+						.map_or(0, |(_, amount)| *amount);
+					(position.amount, underlying)
+				})
+				.unwrap_or_default(),
+		)
+	}
+
+	fn collateral(who: &AccountId) -> Balance {
+		Vault::vault_of(who, STND)
+			.and_then(|vault| vault.collateral.get(&STND).copied())
+			.unwrap_or_default()
+	}
+}
+
+impl pallet_standard_voting_power::Config for Test {
+	type Event = Event;
+	type Currency = Balances;
+	type Positions = DefiPositions;
+	type GovernanceAssetId = NativeAssetId;
+	type MaxLiquidityPositions = ConstU32<2>;
+}
+
+pub const INITIAL_BALANCE: Balance = 100_000;
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(ALICE, INITIAL_BALANCE), (BOB, INITIAL_BALANCE), (CHARLIE, INITIAL_BALANCE)],
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
+	// Pool tokens are minted by the market, so the first one has to exist up front.
+	pallet_assets::GenesisConfig::<Test> {
+		assets: [STND, MTR, DOT, LPT.0].iter().map(|id| (*id, ALICE, true, 1)).collect(),
+		metadata: [(STND, "STND"), (MTR, "MTR"), (DOT, "DOT")]
+			.iter()
+			.map(|(id, symbol)| (*id, symbol.as_bytes().to_vec(), symbol.as_bytes().to_vec(), 12))
+			.collect(),
+		accounts: [BOB, CHARLIE]
+			.iter()
+			.flat_map(|who| [MTR, DOT].map(|id| (id, *who, INITIAL_BALANCE)))
+			.collect(),
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
+	pallet_asset_registry::GenesisConfig::<Test> {
+		core_asset_id: STND,
+		next_asset_id: LPT.0,
+		asset_ids: vec![(b"STND".to_vec(), STND), (b"MTR".to_vec(), MTR), (b"DOT".to_vec(), DOT)],
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
+	pallet_standard_oracle::GenesisConfig::<Test> {
+		oracles: vec![ALICE],
+		provider_count: 5,
+		prices: vec![],
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
@@ -0,0 +1,47 @@
+use crate::mock::*;
+use frame_support::assert_ok;
+use pallet_standard_oracle::{
+	price_from_reported, Call as OracleCall, PriceReport, PriceSource, VersionedPriceReport,
+};
+use primitives::{AssetId, Balance};
+use sp_runtime::{traits::Dispatchable, FixedPointNumber, FixedU128};
+
+const ALICE_SOCKET: u32 = 0;
+
+fn report(id: AssetId, price: Balance) {
+	let report = PriceReport { asset: id, price, round: Oracle::current_round(), timestamp: 0 };
+	assert_ok!(Call::Oracle(OracleCall::report(ALICE_SOCKET, VersionedPriceReport::V1(report)))
+		.dispatch(Origin::signed(ALICE)));
+}
+
+#[test]
+fn stale_price_falls_back_to_market_twap() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Oracle::register_operator(Origin::root(), ALICE_SOCKET, ALICE));
+		report(MTR, 2);
+		report(DOT, 25);
+		assert_ok!(Call::Oracle(OracleCall::set_twap_fallback(DOT, true)).dispatch(Origin::root()));
+		// The pool trades DOT at 10 MTR
+		assert_ok!(Market::mint_liquidity(Origin::signed(CHARLIE), MTR, 5_000, DOT, 500));
+		assert_eq!(
+			Oracle::price_with_source(DOT),
+			Ok((price_from_reported(25), PriceSource::Oracle))
+		);
+
+		// DOT reports go stale while MTR is still reported
+		System::set_block_number(101);
+		report(MTR, 2);
+		assert_eq!(Market::twap(DOT, MTR), Some(FixedU128::saturating_from_integer(10)));
+		assert_eq!(
+			Oracle::price_with_source(DOT),
+			Ok((price_from_reported(20), PriceSource::Twap))
+		);
+
+		// Without a fresh MTR price the stale DOT reports are kept
+		System::set_block_number(201);
+		assert_eq!(
+			Oracle::price_with_source(DOT),
+			Ok((price_from_reported(25), PriceSource::StaleOracle))
+		);
+	})
+}
@@ -0,0 +1,1028 @@
+use crate::mock::*;
+use frame_support::{
+	assert_noop, assert_ok,
+	codec::Encode,
+	dispatch::DispatchResultWithPostInfo,
+	storage::migration::put_storage_value,
+	traits::{
+		fungibles::Inspect, Currency, OnIdle, OnInitialize, OnRuntimeUpgrade, StorageVersion,
+	},
+	weights::GetDispatchInfo,
+	Blake2_128Concat, IterableStorageMap, StorageHasher, StorageMap, StorageValue,
+};
+use pallet_standard_market::{Error as MarketError, RawEvent as MarketEvent, Rewards};
+use pallet_standard_oracle::{
+	Call as OracleCall, CurrentEra, PriceReport, PriceSource, VersionedPriceReport,
+};
+use pallet_standard_vault::{
+	Call as VaultCall, CheckPriceAge, Error as VaultError, FeePayment, NextVaultId, Positions,
+	RawEvent as VaultEvent, VaultInfo, VaultOwners, Vaults, PRICE_TOO_OLD,
+};
+use primitives::{
+	overview::{LiquidityPosition, VaultPosition},
+	proof::Proven,
+	snapshot::{PoolSnapshot, VaultEraStats, VaultSnapshot},
+	AssetId, Balance, LpTokenId,
+};
+use sp_core::U256;
+use sp_runtime::{
+	traits::{Dispatchable, SignedExtension},
+	transaction_validity::{InvalidTransaction, TransactionValidity},
+	DispatchError, FixedPointNumber, FixedU128, TokenError,
+};
+
+const ALICE_SOCKET: u32 = 0;
+
+// Vault and oracle dispatchables are not public functions, so go through the runtime call.
+fn vault(who: Origin, call: VaultCall<Test>) -> DispatchResultWithPostInfo {
+	Call::Vault(call).dispatch(who)
+}
+
+fn report(id: AssetId, price: Balance) {
+	let report = PriceReport { asset: id, price, round: Oracle::current_round(), timestamp: 0 };
+	assert_ok!(Call::Oracle(OracleCall::report(ALICE_SOCKET, VersionedPriceReport::V1(report)))
+		.dispatch(Origin::signed(ALICE)));
+}
+
+/// Registers the oracle provider, reports prices and lists DOT as collateral with a 150%
+/// collateralization requirement, a 5% liquidation fee and a 1% stability fee.
+fn setup_market() {
+	assert_ok!(Oracle::register_operator(Origin::root(), ALICE_SOCKET, ALICE));
+	report(MTR, 1);
+	report(DOT, 10);
+
+	// MTR/DOT pool receiving liquidated collateral
+	assert_ok!(Market::mint_liquidity(Origin::signed(CHARLIE), MTR, 5_000, DOT, 500));
+	assert_eq!(Market::pair((MTR, DOT)), Some(LPT));
+	assert_eq!(Market::reserves(LPT), (5_000, 500));
+
+	assert_ok!(vault(
+		Origin::root(),
+		VaultCall::set_position(DOT, (5, 100), (U256::from(2), U256::from(3)), (1, 100))
+	));
+}
+
+fn vault_info(collateral: &[(AssetId, Balance)], debt: Balance) -> VaultInfo {
+	VaultInfo { collateral: collateral.iter().copied().collect(), debt }
+}
+
+#[test]
+fn generate_requires_valid_cdp() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+
+		// 1_000 DOT at 10 allows at most 6_666 MTR
+		assert_noop!(
+			vault(Origin::signed(BOB), VaultCall::generate(7_000, DOT, 1_000)),
+			VaultError::<Test>::InvalidCDP
+		);
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::generate(6_000, DOT, 1_000)));
+		assert_eq!(Vault::vault_of(&BOB, DOT), Some(vault_info(&[(DOT, 1_000)], 6_000)));
+		assert_eq!(Vault::vault_owner(0), Some(BOB));
+		assert_eq!(Assets::balance(DOT, &BOB), INITIAL_BALANCE - 1_000);
+		assert_eq!(Assets::balance(DOT, &Vault::sys_account_id()), 1_000 + 500);
+		assert_eq!(Assets::balance(MTR, &BOB), INITIAL_BALANCE + 6_000);
+		assert_eq!(Vault::circulating_supply(), 6_000);
+		System::assert_has_event(Event::Vault(VaultEvent::VaultOpened(0, BOB, DOT)));
+		System::assert_has_event(Event::Vault(VaultEvent::CollateralDeposited(0, BOB, DOT, 1_000)));
+		System::assert_last_event(Event::Vault(VaultEvent::DebtGenerated(0, BOB, 6_000, 6_000)));
+	})
+}
+
+#[test]
+fn era_aggregates_are_recorded_at_rollover() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::generate(6_000, DOT, 1_000)));
+		assert_ok!(vault(Origin::signed(CHARLIE), VaultCall::generate(3_000, DOT, 1_000)));
+
+		System::set_block_number(2);
+		report(DOT, 8);
+		assert_ok!(vault(Origin::signed(CHARLIE), VaultCall::liquidate_vault(0)));
+		System::set_block_number(5);
+		assert_ok!(vault(Origin::signed(CHARLIE), VaultCall::liquidate_vault(0)));
+		assert_eq!(Vault::liquidated_in_era(), 6_000);
+		assert_eq!(Vault::total_debt(), 3_000);
+		assert_eq!(Vault::vault_count(), 1);
+
+		// Nothing is recorded within the era
+		Vault::on_initialize(5);
+		assert_eq!(Vault::vault_era_stats(0), None);
+
+		CurrentEra::put(1);
+		Vault::on_initialize(6);
+		let era_stats = |era, debt, liquidated| VaultEraStats {
+			era,
+			collateral: vec![(DOT, 1_000)],
+			debt,
+			vaults: 1,
+			liquidated,
+		};
+		assert_eq!(Vault::vault_era_stats(0), Some(era_stats(0, 3_000, 6_000)));
+		assert_eq!(Vault::liquidated_in_era(), 0);
+
+		// Only the last `HistoryDepth` eras are kept
+		CurrentEra::put(2);
+		Vault::on_initialize(7);
+		CurrentEra::put(3);
+		Vault::on_initialize(8);
+		assert_eq!(Vault::vault_era_stats(0), None);
+		assert_eq!(Vault::vault_history(), vec![era_stats(1, 3_000, 0), era_stats(2, 3_000, 0)]);
+	})
+}
+
+#[test]
+fn minted_mtr_is_capped_per_block_and_era() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::generate(6_000, DOT, 1_000)));
+
+		// 10_000 MTR may be minted in a block
+		assert_noop!(
+			vault(Origin::signed(CHARLIE), VaultCall::generate(4_001, DOT, 1_000)),
+			VaultError::<Test>::BlockMintLimitExceeded
+		);
+		assert_ok!(vault(Origin::signed(CHARLIE), VaultCall::generate(4_000, DOT, 1_000)));
+
+		// and 20_000 MTR in a mint era of 10 blocks
+		System::set_block_number(2);
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::generate(6_000, DOT, 1_000)));
+		assert_noop!(
+			vault(Origin::signed(CHARLIE), VaultCall::generate(4_001, DOT, 1_000)),
+			VaultError::<Test>::EraMintLimitExceeded
+		);
+		assert_ok!(vault(Origin::signed(CHARLIE), VaultCall::generate(4_000, DOT, 1_000)));
+		assert_eq!(Vault::minted_in_era(), (0, 20_000));
+
+		System::set_block_number(10);
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::generate(1_000, DOT, 0)));
+		assert_eq!(Vault::minted_in_block(), (10, 1_000));
+		assert_eq!(Vault::minted_in_era(), (1, 1_000));
+	})
+}
+
+#[test]
+fn close_repays_debt_and_returns_collateral() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::generate(6_000, DOT, 1_000)));
+		assert_noop!(
+			vault(Origin::signed(CHARLIE), VaultCall::close(0)),
+			VaultError::<Test>::NotVaultOwner
+		);
+
+		// The debt is burned and 1% of the collateral is kept as stability fee
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::close(0)));
+		assert_eq!(Vault::vault(0), None);
+		assert_eq!(Vault::vault_of(&BOB, DOT), None);
+		assert_eq!(Assets::balance(MTR, &BOB), INITIAL_BALANCE);
+		assert_eq!(Assets::balance(DOT, &BOB), INITIAL_BALANCE - 10);
+		assert_eq!(Assets::balance(DOT, &Vault::account_id()), 10);
+		assert_eq!(Assets::balance(DOT, &Vault::sys_account_id()), 500);
+		assert_eq!(Assets::total_issuance(MTR), 2 * INITIAL_BALANCE);
+		assert_eq!(Vault::circulating_supply(), 0);
+		System::assert_has_event(Event::Vault(VaultEvent::DebtRepaid(0, BOB, 6_000, 0)));
+		System::assert_has_event(Event::Vault(VaultEvent::StabilityFeePaid(
+			0,
+			BOB,
+			DOT,
+			10,
+			FeePayment::Collateral,
+			10,
+		)));
+		System::assert_last_event(Event::Vault(VaultEvent::VaultClosed(0, BOB, 6_000)));
+	})
+}
+
+#[test]
+fn stability_fee_is_paid_in_mtr_or_native_currency() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		report(STND, 2);
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::generate(6_000, DOT, 1_000)));
+		System::set_block_number(2);
+		assert_ok!(vault(Origin::signed(CHARLIE), VaultCall::generate(6_000, DOT, 1_000)));
+
+		// The fee of 10 DOT is worth 100 MTR, burned on top of the debt, and the whole collateral
+		// is returned
+		assert_ok!(vault(
+			Origin::signed(BOB),
+			VaultCall::close_with_fee_payment(0, FeePayment::Mtr)
+		));
+		assert_eq!(Assets::balance(MTR, &BOB), INITIAL_BALANCE - 100);
+		assert_eq!(Assets::balance(DOT, &BOB), INITIAL_BALANCE);
+		assert_eq!(Assets::balance(DOT, &Vault::account_id()), 0);
+		assert_eq!(Vault::circulating_supply(), 6_000 - 100);
+		System::assert_has_event(Event::Vault(VaultEvent::StabilityFeePaid(
+			0,
+			BOB,
+			DOT,
+			10,
+			FeePayment::Mtr,
+			100,
+		)));
+
+		// or 50 of the native currency at a price of 2, sent to the treasury
+		let treasury = Balances::free_balance(&TreasuryAccount::get());
+		assert_ok!(vault(
+			Origin::signed(CHARLIE),
+			VaultCall::close_with_fee_payment(1, FeePayment::Native)
+		));
+		assert_eq!(Balances::free_balance(&CHARLIE), INITIAL_BALANCE - 50);
+		assert_eq!(Balances::free_balance(&TreasuryAccount::get()), treasury + 50);
+		assert_eq!(Assets::balance(MTR, &CHARLIE), INITIAL_BALANCE);
+		assert_eq!(Assets::balance(DOT, &Vault::sys_account_id()), 500);
+		System::assert_has_event(Event::Vault(VaultEvent::StabilityFeePaid(
+			1,
+			CHARLIE,
+			DOT,
+			10,
+			FeePayment::Native,
+			50,
+		)));
+	})
+}
+
+#[test]
+fn healthy_vault_cannot_be_liquidated() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::generate(6_000, DOT, 1_000)));
+
+		assert_noop!(
+			vault(Origin::signed(CHARLIE), VaultCall::liquidate_vault(0)),
+			VaultError::<Test>::Unavailable
+		);
+	})
+}
+
+#[test]
+fn price_drop_liquidates_vault_into_market() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::generate(6_000, DOT, 1_000)));
+
+		// 1_000 DOT at 8 only backs 5_333 MTR
+		System::set_block_number(2);
+		report(DOT, 8);
+		// The owner is warned first and the vault is liquidated after the grace period
+		assert_ok!(vault(Origin::signed(CHARLIE), VaultCall::liquidate_vault(0)));
+		assert_eq!(Vault::liquidation_warning(0), Some(2));
+		System::set_block_number(4);
+		assert_noop!(
+			vault(Origin::signed(CHARLIE), VaultCall::liquidate_vault(0)),
+			VaultError::<Test>::GracePeriod
+		);
+		System::set_block_number(5);
+		assert_ok!(vault(Origin::signed(CHARLIE), VaultCall::liquidate_vault(0)));
+
+		assert_eq!(Vault::vault(0), None);
+		assert_eq!(Vault::vault_of(&BOB, DOT), None);
+		// 5% of the collateral is the liquidation fee, the rest goes to the pool reserves
+		assert_eq!(Assets::balance(DOT, &Vault::account_id()), 50);
+		assert_eq!(Market::reserves(LPT), (5_000, 500 + 950));
+		System::assert_has_event(Event::Vault(VaultEvent::CollateralLiquidated(0, DOT, 1_000, 50)));
+		System::assert_last_event(Event::Vault(VaultEvent::VaultLiquidated(
+			0, BOB, CHARLIE, 6_000,
+		)));
+	})
+}
+
+fn check_price_age(who: AccountId, call: VaultCall<Test>) -> TransactionValidity {
+	let call = Call::Vault(call);
+	CheckPriceAge::<Test>::new().validate(&who, &call, &call.get_dispatch_info(), 0)
+}
+
+#[test]
+fn old_prices_invalidate_generate_and_liquidate() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(check_price_age(BOB, VaultCall::generate(6_000, DOT, 1_000)));
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::generate(6_000, DOT, 1_000)));
+
+		// The prices were reported at block 1 and may be used for 10 blocks
+		System::set_block_number(12);
+		let too_old: TransactionValidity = Err(InvalidTransaction::Custom(PRICE_TOO_OLD).into());
+		assert_eq!(check_price_age(BOB, VaultCall::generate(100, DOT, 0)), too_old);
+		assert_eq!(check_price_age(CHARLIE, VaultCall::liquidate_vault(0)), too_old);
+		assert_ok!(check_price_age(BOB, VaultCall::close(0)));
+
+		// Both the collateral and MTR have to be fresh
+		report(DOT, 10);
+		assert_eq!(check_price_age(CHARLIE, VaultCall::liquidate_vault(0)), too_old);
+		report(MTR, 1);
+		assert_ok!(check_price_age(CHARLIE, VaultCall::liquidate_vault(0)));
+		assert_ok!(check_price_age(BOB, VaultCall::generate(100, DOT, 0)));
+	})
+}
+
+#[test]
+fn liquidations_fall_back_to_the_twap_during_an_outage() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(Call::Oracle(OracleCall::set_twap_fallback(DOT, true)).dispatch(Origin::root()));
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::generate(6_000, DOT, 1_000)));
+
+		// DOT drops to about 7 MTR in the pool while its reports stop
+		System::set_block_number(2);
+		assert_ok!(Market::swap(Origin::signed(CHARLIE), DOT, 100, MTR));
+		System::set_block_number(101);
+		report(MTR, 1);
+		assert_eq!(Oracle::price_with_source(DOT).map(|(_, source)| source), Ok(PriceSource::Twap));
+
+		// Only liquidations may rely on the fallback
+		let too_old: TransactionValidity = Err(InvalidTransaction::Custom(PRICE_TOO_OLD).into());
+		assert_eq!(check_price_age(BOB, VaultCall::generate(100, DOT, 0)), too_old);
+		assert_ok!(check_price_age(CHARLIE, VaultCall::liquidate_vault(0)));
+		assert_ok!(vault(Origin::signed(CHARLIE), VaultCall::liquidate_vault(0)));
+		System::set_block_number(104);
+		assert_ok!(check_price_age(CHARLIE, VaultCall::liquidate_vault(0)));
+		assert_ok!(vault(Origin::signed(CHARLIE), VaultCall::liquidate_vault(0)));
+		assert_eq!(Vault::vault(0), None);
+		System::assert_last_event(Event::Vault(VaultEvent::VaultLiquidated(
+			0, BOB, CHARLIE, 6_000,
+		)));
+	})
+}
+
+#[test]
+fn warned_vaults_are_liquidated_in_idle_time() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::generate(6_000, DOT, 1_000)));
+		assert_ok!(vault(Origin::signed(CHARLIE), VaultCall::generate(600, DOT, 100)));
+
+		System::set_block_number(2);
+		report(DOT, 8);
+		assert_ok!(vault(Origin::signed(ALICE), VaultCall::liquidate_vault(0)));
+		assert_ok!(vault(Origin::signed(ALICE), VaultCall::liquidate_vault(1)));
+		assert_eq!(Vault::liquidation_backlog(), 2);
+
+		// Nothing is due before the end of the grace period
+		System::set_block_number(4);
+		Vault::on_idle(4, u64::MAX);
+		assert_eq!(Vault::liquidation_backlog(), 2);
+
+		// One liquidation is carried out per block at most
+		System::set_block_number(5);
+		Vault::on_idle(5, u64::MAX);
+		assert_eq!(Vault::liquidation_backlog(), 1);
+		assert_eq!(Vault::vault(0), None);
+		// 5% of the collateral is the liquidation fee, the rest goes to the pool reserves
+		assert_eq!(Assets::balance(DOT, &Vault::account_id()), 50);
+		assert_eq!(Market::reserves(LPT), (5_000, 500 + 950));
+		System::assert_last_event(Event::Vault(VaultEvent::VaultLiquidated(
+			0,
+			BOB,
+			Vault::sys_account_id(),
+			6_000,
+		)));
+
+		System::set_block_number(6);
+		Vault::on_idle(6, u64::MAX);
+		assert_eq!(Vault::liquidation_backlog(), 0);
+		assert_eq!(Vault::vault(1), None);
+	})
+}
+
+#[test]
+fn recovered_vault_restarts_grace_period() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::generate(6_000, DOT, 1_000)));
+
+		System::set_block_number(2);
+		report(DOT, 8);
+		assert_ok!(vault(Origin::signed(CHARLIE), VaultCall::liquidate_vault(0)));
+
+		// A single round spike does not liquidate the vault
+		System::set_block_number(3);
+		report(DOT, 10);
+		System::set_block_number(5);
+		assert_ok!(vault(Origin::signed(CHARLIE), VaultCall::liquidate_vault(0)));
+		assert_eq!(Vault::liquidation_warning(0), None);
+
+		report(DOT, 8);
+		assert_ok!(vault(Origin::signed(CHARLIE), VaultCall::liquidate_vault(0)));
+		assert_eq!(Vault::liquidation_warning(0), Some(5));
+		assert_eq!(Vault::vault_of(&BOB, DOT), Some(vault_info(&[(DOT, 1_000)], 6_000)));
+	})
+}
+
+#[test]
+fn expired_warning_restarts_grace_period() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::generate(6_000, DOT, 1_000)));
+
+		System::set_block_number(2);
+		report(DOT, 8);
+		assert_ok!(vault(Origin::signed(CHARLIE), VaultCall::liquidate_vault(0)));
+
+		// The price recovers without the warning being cleared and dips again after twice the
+		// grace period
+		System::set_block_number(3);
+		report(DOT, 10);
+		System::set_block_number(8);
+		report(DOT, 8);
+		Vault::on_idle(8, u64::MAX);
+		assert_eq!(Vault::liquidation_warning(0), None);
+		assert_eq!(Vault::vault_of(&BOB, DOT), Some(vault_info(&[(DOT, 1_000)], 6_000)));
+
+		// The owner is warned again and gets a new grace period
+		assert_ok!(vault(Origin::signed(CHARLIE), VaultCall::liquidate_vault(0)));
+		assert_eq!(Vault::liquidation_warning(0), Some(8));
+		System::assert_last_event(Event::Vault(VaultEvent::LiquidationWarning(0, BOB, 11)));
+		System::set_block_number(10);
+		assert_noop!(
+			vault(Origin::signed(CHARLIE), VaultCall::liquidate_vault(0)),
+			VaultError::<Test>::GracePeriod
+		);
+	})
+}
+
+#[test]
+fn vault_token_moves_position_to_new_owner() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::generate(6_000, DOT, 1_000)));
+		assert_ok!(vault(Origin::signed(CHARLIE), VaultCall::generate(600, DOT, 100)));
+
+		assert_noop!(
+			vault(Origin::signed(CHARLIE), VaultCall::transfer_vault(ALICE, 0)),
+			VaultError::<Test>::NotVaultOwner
+		);
+		assert_noop!(
+			vault(Origin::signed(BOB), VaultCall::transfer_vault(CHARLIE, 0)),
+			VaultError::<Test>::RecipientHasVault
+		);
+
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::transfer_vault(ALICE, 0)));
+		assert_eq!(Vault::vault_owner(0), Some(ALICE));
+		assert_eq!(Vault::vault_of(&ALICE, DOT), Some(vault_info(&[(DOT, 1_000)], 6_000)));
+		assert_eq!(Vault::vault_of(&BOB, DOT), None);
+
+		// The new owner can add to the vault, the previous one opens a new vault
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::generate(600, DOT, 100)));
+		assert_eq!(Vault::vault_id((BOB, DOT)), Some(2));
+	})
+}
+
+#[test]
+fn baseline_vaults_migrate_to_vault_ids() {
+	new_test_ext().execute_with(|| {
+		// Vaults of the baseline layout, keyed by owner and collateral
+		for (key, value) in [((BOB, DOT), (1_000, 6_000)), ((CHARLIE, STND), (500, 100))] {
+			put_storage_value::<(Balance, Balance)>(
+				b"Vault",
+				b"Vault",
+				&Blake2_128Concat::hash(&key.encode()),
+				value,
+			);
+		}
+		StorageVersion::new(0).put::<Vault>();
+		Vault::on_runtime_upgrade();
+		assert_eq!(StorageVersion::get::<Vault>(), 1);
+
+		let id = Vault::vault_id((BOB, DOT)).expect("Migrated vault expected");
+		assert_eq!(Vault::vault(id), Some(vault_info(&[(DOT, 1_000)], 6_000)));
+		assert_eq!(Vault::vault_owner(id), Some(BOB));
+		let id = Vault::vault_id((CHARLIE, STND)).expect("Migrated vault expected");
+		assert_eq!(Vault::vault(id), Some(vault_info(&[(STND, 500)], 100)));
+		assert_eq!(Vault::vault_owner(id), Some(CHARLIE));
+		assert_eq!(Vault::next_vault_id(), 2);
+
+		// Later upgrades leave the vaults alone, new vaults take the next ids
+		put_storage_value::<(Balance, Balance)>(
+			b"Vault",
+			b"Vault",
+			&Blake2_128Concat::hash(&(BOB, STND).encode()),
+			(1, 1),
+		);
+		Vault::on_runtime_upgrade();
+		assert_eq!(Vault::vault_id((BOB, STND)), None);
+		assert_eq!(Vault::next_vault_id(), 2);
+		setup_market();
+		assert_ok!(vault(Origin::signed(CHARLIE), VaultCall::generate(600, DOT, 100)));
+		assert_eq!(Vault::vault_id((CHARLIE, DOT)), Some(2));
+	})
+}
+
+fn last_fee_adjustment() -> Option<(AssetId, Balance, Balance)> {
+	System::events().into_iter().rev().find_map(|record| match record.event {
+		Event::Vault(VaultEvent::StabilityFeeAdjusted(id, numerator, denominator)) =>
+			Some((id, numerator, denominator)),
+		_ => None,
+	})
+}
+
+#[test]
+fn stability_fee_follows_mtr_peg() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		assert_noop!(
+			vault(Origin::root(), VaultCall::set_stability_fee_bounds(DOT, Some((5, 1, 1)))),
+			VaultError::<Test>::InvalidFeeBounds
+		);
+		// The fee may not reach 100% of the position's denominator
+		assert_noop!(
+			vault(Origin::root(), VaultCall::set_stability_fee_bounds(DOT, Some((1, 100, 1)))),
+			VaultError::<Test>::InvalidFeeBounds
+		);
+		assert_ok!(vault(
+			Origin::root(),
+			VaultCall::set_stability_fee_bounds(DOT, Some((1, 3, 1)))
+		));
+
+		// Only adjusted every `RateUpdatePeriod` blocks
+		System::set_block_number(2);
+		report(MTR, 95);
+		Vault::on_initialize(5);
+		assert_eq!(last_fee_adjustment(), None);
+
+		// Raised while MTR trades under the peg, up to the maximum
+		Vault::on_initialize(10);
+		assert_eq!(last_fee_adjustment(), Some((DOT, 2, 100)));
+		Vault::on_initialize(20);
+		Vault::on_initialize(30);
+		assert_eq!(last_fee_adjustment(), Some((DOT, 3, 100)));
+
+		// Within the tolerance the fee is kept
+		System::reset_events();
+		System::set_block_number(3);
+		report(MTR, 101);
+		Vault::on_initialize(40);
+		assert_eq!(last_fee_adjustment(), None);
+
+		// Lowered while MTR trades above the peg
+		System::set_block_number(4);
+		report(MTR, 110);
+		Vault::on_initialize(50);
+		assert_eq!(last_fee_adjustment(), Some((DOT, 2, 100)));
+	})
+}
+
+#[test]
+fn overview_lists_liquidity_and_vaults() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::generate(6_000, DOT, 1_000)));
+
+		assert_eq!(
+			Market::liquidity_of(&CHARLIE),
+			vec![LiquidityPosition {
+				lp_token: LPT,
+				amount: 1_580,
+				underlying: [(MTR, 5_000), (DOT, 500)]
+			}]
+		);
+		assert_eq!(Market::liquidity_of(&BOB), vec![]);
+
+		// 1_000 DOT at 10 can back 6_666 MTR
+		assert_eq!(
+			Vault::vaults_of(&BOB),
+			vec![VaultPosition {
+				vault_id: 0,
+				collateral: vec![(DOT, 1_000)],
+				debt: 6_000,
+				health_factor: Some(FixedU128::saturating_from_rational(6_666, 6_000)),
+			}]
+		);
+		assert_eq!(Vault::vaults_of(&CHARLIE), vec![]);
+	})
+}
+
+#[test]
+fn snapshot_lists_pools_and_vaults() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::generate(6_000, DOT, 1_000)));
+
+		assert_eq!(
+			Market::pools(),
+			vec![PoolSnapshot {
+				lp_token: LPT,
+				reserves: [(MTR, 5_000), (DOT, 500)],
+				total_supply: 1_580
+			}]
+		);
+		assert_eq!(
+			Vault::vault_snapshots(),
+			vec![VaultSnapshot {
+				vault_id: 0,
+				owner: BOB,
+				collateral: vec![(DOT, 1_000)],
+				debt: 6_000
+			}]
+		);
+		assert_eq!(Vault::collateral_ids(), vec![DOT]);
+	})
+}
+
+#[test]
+fn proofs_cover_vault_and_pool_storage() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::generate(6_000, DOT, 1_000)));
+
+		let proven = Vault::proven_vault(0);
+		assert_eq!(proven.value, Vault::vaults_of(&BOB).pop());
+		assert!(proven.keys.contains(&Vaults::hashed_key_for(0)));
+		assert!(proven.keys.contains(&Positions::hashed_key_for(DOT)));
+		for key in Oracle::price_keys(DOT).into_iter().chain(Oracle::price_keys(MTR)) {
+			assert!(proven.keys.contains(&key));
+		}
+		assert_eq!(
+			Vault::proven_vault(1),
+			Proven { value: None, keys: vec![Vaults::hashed_key_for(1)] }
+		);
+
+		let proven = Market::proven_pool(LPT);
+		assert_eq!(proven.value, Market::pools().pop());
+		assert_eq!(proven.keys.len(), 2);
+		assert_eq!(Market::proven_pool(LpTokenId(DOT)).value, None);
+	})
+}
+
+#[test]
+fn basket_collateral_backs_one_debt() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		report(STND, 5);
+		assert_ok!(Assets::mint(Origin::signed(ALICE), STND, CHARLIE, 500));
+		assert_ok!(Market::mint_liquidity(Origin::signed(CHARLIE), MTR, 2_500, STND, 500));
+		assert_ok!(vault(
+			Origin::root(),
+			VaultCall::set_position(STND, (10, 100), (U256::from(1), U256::from(2)), (1, 100))
+		));
+		assert_ok!(Assets::mint(Origin::signed(ALICE), STND, BOB, 1_000));
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::generate(6_000, DOT, 1_000)));
+		assert_noop!(
+			vault(Origin::signed(CHARLIE), VaultCall::deposit_collateral(0, STND, 1_000)),
+			VaultError::<Test>::NotVaultOwner
+		);
+
+		// 1_000 STND at 5 add 2_500 MTR of borrowing capacity
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::deposit_collateral(0, STND, 600)));
+		assert_eq!(Vault::vault_id((BOB, STND)), Some(0));
+		assert_eq!(Assets::balance(STND, &Vault::sys_account_id()), 600);
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::generate(3_000, STND, 400)));
+		assert_eq!(
+			Vault::vault_of(&BOB, STND),
+			Some(vault_info(&[(DOT, 1_000), (STND, 1_000)], 9_000))
+		);
+		assert_eq!(
+			Vault::vaults_of(&BOB)[0].health_factor,
+			Some(FixedU128::saturating_from_rational(6_666 + 2_500, 9_000))
+		);
+
+		// The basket is bounded and the rest of it has to back the debt
+		assert_noop!(
+			vault(Origin::signed(BOB), VaultCall::deposit_collateral(0, MTR, 1_000)),
+			VaultError::<Test>::CollateralNotSupported
+		);
+		// MTR has no market pair against itself, so it is listed without the onboarding checklist
+		Positions::insert(MTR, Vault::position(STND).unwrap());
+		assert_noop!(
+			vault(Origin::signed(BOB), VaultCall::deposit_collateral(0, MTR, 1_000)),
+			VaultError::<Test>::TooManyCollateralAssets
+		);
+		assert_noop!(
+			vault(Origin::signed(BOB), VaultCall::withdraw_collateral(0, STND, 100)),
+			VaultError::<Test>::InvalidCDP
+		);
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::withdraw_collateral(0, STND, 50)));
+		assert_eq!(Assets::balance(STND, &BOB), 50);
+
+		// A price drop of one collateral puts the whole basket under the liquidation threshold
+		System::set_block_number(2);
+		report(STND, 1);
+		assert_ok!(vault(Origin::signed(CHARLIE), VaultCall::liquidate_vault(0)));
+		assert_eq!(Vault::liquidation_warning(0), Some(2));
+
+		// The liquidator pays the fee of every collateral, one it can not pay reverts the
+		// liquidation of the others
+		assert_ok!(Assets::mint(Origin::signed(ALICE), STND, ALICE, 100));
+		System::set_block_number(5);
+		assert_noop!(
+			vault(Origin::signed(ALICE), VaultCall::liquidate_vault(0)),
+			DispatchError::Token(TokenError::NoFunds)
+		);
+		assert_ok!(Assets::transfer(Origin::signed(CHARLIE), DOT, ALICE, 100));
+		assert_ok!(vault(Origin::signed(ALICE), VaultCall::liquidate_vault(0)));
+		assert_eq!(Vault::vault(0), None);
+	})
+}
+
+#[test]
+fn leverage_loops_generated_mtr_into_collateral() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let target = FixedU128::saturating_from_rational(3, 2);
+		assert_noop!(
+			vault(
+				Origin::signed(BOB),
+				VaultCall::leverage(DOT, 1_000, FixedU128::saturating_from_integer(1), 2, 0)
+			),
+			VaultError::<Test>::InvalidTargetRatio
+		);
+		assert_noop!(
+			vault(Origin::signed(BOB), VaultCall::leverage(DOT, 1_000, target, 4, 0)),
+			VaultError::<Test>::TooManyIterations
+		);
+		// The swaps only buy 260 DOT
+		assert_noop!(
+			vault(Origin::signed(BOB), VaultCall::leverage(DOT, 1_000, target, 2, 261)),
+			VaultError::<Test>::SlippageExceeded
+		);
+
+		// 4_444 MTR buy 234 DOT, then 1_040 MTR buy 26 DOT
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::leverage(DOT, 1_000, target, 2, 260)));
+		assert_eq!(Vault::vault_of(&BOB, DOT), Some(vault_info(&[(DOT, 1_260)], 5_484)));
+		assert_eq!(Assets::balance(DOT, &BOB), INITIAL_BALANCE - 1_000);
+		assert_eq!(Assets::balance(MTR, &BOB), INITIAL_BALANCE);
+		assert_eq!(Vault::circulating_supply(), 5_484);
+		assert_eq!(Market::reserves(LPT), (5_000 + 5_484, 500 - 260));
+		assert!(Vault::vaults_of(&BOB)[0].health_factor >= Some(target));
+		System::assert_last_event(Event::Vault(VaultEvent::Leveraged(0, BOB, DOT, 260, 5_484)));
+	})
+}
+
+#[test]
+fn deleverage_repays_debt_with_collateral() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::generate(4_000, DOT, 1_000)));
+		assert_noop!(
+			vault(Origin::signed(BOB), VaultCall::deleverage(DOT, 4_001, 1_000)),
+			VaultError::<Test>::RepayExceedsDebt
+		);
+		// Buying 1_000 MTR takes 126 DOT
+		assert_noop!(
+			vault(Origin::signed(BOB), VaultCall::deleverage(DOT, 1_000, 125)),
+			VaultError::<Test>::SlippageExceeded
+		);
+
+		// 126 DOT buy 1_003 MTR, of which 1_000 repay the debt
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::deleverage(DOT, 1_000, 126)));
+		assert_eq!(Vault::vault_of(&BOB, DOT), Some(vault_info(&[(DOT, 874)], 3_000)));
+		assert_eq!(Assets::balance(DOT, &BOB), INITIAL_BALANCE - 1_000);
+		assert_eq!(Assets::balance(MTR, &BOB), INITIAL_BALANCE + 4_000 + 3);
+		assert_eq!(Vault::circulating_supply(), 3_000);
+		assert_eq!(Market::reserves(LPT), (5_000 - 1_003, 500 + 126));
+		System::assert_last_event(Event::Vault(VaultEvent::Deleveraged(0, BOB, DOT, 126, 1_000)));
+
+		// The rest of the DOT at a price of 4 does not back the remaining 2_900 MTR
+		System::set_block_number(2);
+		report(DOT, 4);
+		assert_noop!(
+			vault(Origin::signed(BOB), VaultCall::deleverage(DOT, 100, 1_000)),
+			VaultError::<Test>::InvalidCDP
+		);
+	})
+}
+
+#[test]
+fn set_position_enforces_onboarding_checklist() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let list_stnd = || {
+			vault(
+				Origin::root(),
+				VaultCall::set_position(STND, (10, 100), (U256::from(1), U256::from(2)), (1, 100)),
+			)
+		};
+		assert_ok!(Assets::force_clear_metadata(Origin::root(), STND));
+		assert_noop!(list_stnd(), VaultError::<Test>::NoAssetMetadata);
+		assert_ok!(Assets::force_set_metadata(
+			Origin::root(),
+			STND,
+			b"STND".to_vec(),
+			b"STND".to_vec(),
+			12,
+			false
+		));
+		assert_noop!(list_stnd(), VaultError::<Test>::NoOracleFeed);
+		report(STND, 5);
+		assert_noop!(list_stnd(), VaultError::<Test>::MarketDoesNotExist);
+		assert_ok!(Assets::mint(Origin::signed(ALICE), STND, CHARLIE, 100));
+		assert_ok!(Market::mint_liquidity(Origin::signed(CHARLIE), MTR, 500, STND, 100));
+		assert_noop!(list_stnd(), VaultError::<Test>::InsufficientMarketLiquidity);
+
+		// Listed collaterals are updated without the checklist
+		assert_ok!(Assets::force_clear_metadata(Origin::root(), DOT));
+		assert_ok!(vault(
+			Origin::root(),
+			VaultCall::set_position(DOT, (5, 100), (U256::from(1), U256::from(2)), (1, 100))
+		));
+	})
+}
+
+#[test]
+fn set_position_validates_risk_parameters() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let set_dot =
+			|fee: (Balance, Balance), rate: (u64, u64), stability_fee: (Balance, Balance)| {
+				let rate = (U256::from(rate.0), U256::from(rate.1));
+				vault(Origin::root(), VaultCall::set_position(DOT, fee, rate, stability_fee))
+			};
+		assert_noop!(
+			set_dot((100, 100), (1, 2), (1, 100)),
+			VaultError::<Test>::InvalidLiquidationFee
+		);
+		assert_noop!(set_dot((5, 0), (1, 2), (1, 100)), VaultError::<Test>::InvalidLiquidationFee);
+		assert_noop!(
+			set_dot((5, 100), (0, 2), (1, 100)),
+			VaultError::<Test>::InvalidCollateralizationRate
+		);
+		assert_noop!(
+			set_dot((5, 100), (1, 0), (1, 100)),
+			VaultError::<Test>::InvalidCollateralizationRate
+		);
+		// Lending more than 10/11 of the collateral value is below the minimum collateralization
+		assert_noop!(
+			set_dot((5, 100), (9, 9), (1, 100)),
+			VaultError::<Test>::InvalidCollateralizationRate
+		);
+		assert_noop!(set_dot((5, 100), (1, 2), (1, 0)), VaultError::<Test>::InvalidStabilityFee);
+		assert_noop!(
+			vault(
+				Origin::root(),
+				VaultCall::schedule_set_position(
+					DOT,
+					(100, 100),
+					(U256::from(1), U256::from(2)),
+					(1, 100),
+					RiskEnactmentDelay::get()
+				)
+			),
+			VaultError::<Test>::InvalidLiquidationFee
+		);
+		assert_noop!(
+			vault(
+				Origin::signed(ALICE),
+				VaultCall::set_position(DOT, (5, 100), (U256::from(1), U256::from(2)), (1, 100))
+			),
+			sp_runtime::DispatchError::BadOrigin
+		);
+
+		// The event carries the replaced position
+		let old = Vault::position(DOT);
+		assert_ok!(set_dot((10, 100), (10, 11), (2, 100)));
+		System::assert_last_event(Event::Vault(VaultEvent::SetPosition(
+			DOT,
+			old,
+			Vault::position(DOT).unwrap(),
+		)));
+	})
+}
+
+#[test]
+fn scheduled_positions_are_enacted_after_the_delay() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let delay = RiskEnactmentDelay::get();
+		let schedule = |liquidation_fee, delay| {
+			vault(
+				Origin::root(),
+				VaultCall::schedule_set_position(
+					DOT,
+					liquidation_fee,
+					(U256::from(2), U256::from(3)),
+					(1, 100),
+					delay,
+				),
+			)
+		};
+		assert_noop!(schedule((10, 100), delay - 1), VaultError::<Test>::DelayTooShort);
+		assert_noop!(
+			vault(Origin::root(), VaultCall::cancel_scheduled_position(DOT)),
+			VaultError::<Test>::NotScheduled
+		);
+
+		let initial = Vault::position(DOT);
+		let when = System::block_number() + delay;
+		assert_ok!(schedule((10, 100), delay));
+		System::assert_last_event(Event::Vault(VaultEvent::PositionScheduled(
+			DOT,
+			when,
+			10,
+			100,
+			U256::from(2),
+			U256::from(3),
+			1,
+			100,
+		)));
+		assert_noop!(schedule((8, 100), delay), VaultError::<Test>::ScheduleFailed);
+		Scheduler::on_initialize(when - 1);
+		assert_eq!(Vault::position(DOT), initial);
+		Scheduler::on_initialize(when);
+		let enacted = Vault::position(DOT);
+		assert_ne!(enacted, initial);
+		System::assert_has_event(Event::Vault(VaultEvent::SetPosition(
+			DOT,
+			initial,
+			enacted.clone().unwrap(),
+		)));
+
+		// A cancelled change is never enacted
+		System::set_block_number(when);
+		assert_ok!(schedule((8, 100), delay));
+		assert_ok!(vault(Origin::root(), VaultCall::cancel_scheduled_position(DOT)));
+		System::assert_last_event(Event::Vault(VaultEvent::PositionScheduleCancelled(DOT)));
+		Scheduler::on_initialize(when + delay);
+		assert_eq!(Vault::position(DOT), enacted);
+	})
+}
+
+#[test]
+fn only_excess_funds_can_be_rescued() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::generate(6_000, DOT, 1_000)));
+
+		// The market account holds the pool reserves and the vault collateral
+		assert_ok!(Assets::transfer(Origin::signed(BOB), DOT, Market::account_id(), 50));
+		assert_noop!(
+			Market::rescue_funds(Origin::signed(BOB), DOT, 50, ALICE),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Market::rescue_funds(Origin::root(), DOT, 51, ALICE),
+			MarketError::<Test>::ExceedsExcessFunds
+		);
+		assert_ok!(Market::rescue_funds(Origin::root(), DOT, 50, ALICE));
+		assert_eq!(Assets::balance(DOT, &ALICE), 50);
+		assert_eq!(Assets::balance(DOT, &Market::account_id()), 1_500);
+		assert_eq!(Market::reserve_discrepancies(), vec![]);
+
+		// Nothing in the vault account backs collateral or reserves
+		assert_ok!(Assets::transfer(Origin::signed(BOB), MTR, Vault::account_id(), 20));
+		assert_noop!(
+			vault(Origin::root(), VaultCall::rescue_funds(MTR, 21, ALICE)),
+			VaultError::<Test>::ExceedsExcessFunds
+		);
+		assert_ok!(vault(Origin::root(), VaultCall::rescue_funds(MTR, 20, ALICE)));
+		assert_eq!(Assets::balance(MTR, &ALICE), 20);
+		System::assert_last_event(Event::Vault(VaultEvent::FundsRescued(MTR, 20, ALICE)));
+
+		// Collected stability fees stay in the vault account
+		assert_eq!(Vault::total_collateral(DOT), 1_000);
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::close(0)));
+		assert_eq!(Vault::total_collateral(DOT), 0);
+		assert_eq!(Vault::collected_fees(DOT), 10);
+		assert_ok!(Assets::transfer(Origin::signed(BOB), DOT, Vault::account_id(), 5));
+		assert_noop!(
+			vault(Origin::root(), VaultCall::rescue_funds(DOT, 6, ALICE)),
+			VaultError::<Test>::ExceedsExcessFunds
+		);
+		assert_ok!(vault(Origin::root(), VaultCall::rescue_funds(DOT, 5, ALICE)));
+		assert_eq!(Assets::balance(DOT, &Vault::account_id()), 10);
+	})
+}
+
+#[test]
+fn emptied_vaults_and_pools_are_removed() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::generate(0, DOT, 100)));
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::withdraw_collateral(0, DOT, 100)));
+		assert_eq!(Vault::vault(0), None);
+		assert_eq!(Vault::vault_owner(0), None);
+		assert_eq!(Vault::vault_of(&BOB, DOT), None);
+		System::assert_last_event(Event::Vault(VaultEvent::VaultRemoved(0)));
+
+		// Emptied vaults left in storage are purged for a reward out of the treasury
+		Vaults::insert(1, vault_info(&[], 0));
+		VaultOwners::<Test>::insert(1, ALICE);
+		NextVaultId::put(2);
+		Balances::make_free_balance_be(&TreasuryAccount::get(), 100);
+		assert_ok!(vault(Origin::signed(CHARLIE), VaultCall::purge_closed(5)));
+		assert_eq!(Vault::vault(1), None);
+		assert_eq!(Vault::vault_owner(1), None);
+		assert_eq!(Balances::free_balance(CHARLIE), INITIAL_BALANCE + 10);
+		System::assert_last_event(Event::Vault(VaultEvent::ClosedPurged(CHARLIE, 1, 10)));
+		assert_ok!(vault(Origin::signed(CHARLIE), VaultCall::purge_closed(5)));
+		System::assert_last_event(Event::Vault(VaultEvent::ClosedPurged(CHARLIE, 0, 0)));
+
+		// Burning all liquidity tokens removes the pool
+		let liquidity = Assets::balance(LPT.0, &CHARLIE);
+		assert_ok!(Market::burn_liquidity(Origin::signed(CHARLIE), LPT, liquidity));
+		assert_eq!(Market::pair((MTR, DOT)), None);
+		assert_eq!(Market::lp_token_info(LPT), None);
+		System::assert_last_event(Event::Market(MarketEvent::PoolRemoved(LPT, MTR, DOT)));
+
+		// Closed pools left in storage are checked `limit` at a time after the last purge
+		Rewards::insert(LpTokenId(98), (MTR, DOT));
+		Rewards::insert(LpTokenId(99), (MTR, DOT));
+		assert_ok!(vault(Origin::signed(CHARLIE), VaultCall::purge_closed(1)));
+		System::assert_last_event(Event::Vault(VaultEvent::ClosedPurged(CHARLIE, 1, 10)));
+		assert_eq!(Rewards::iter_keys().count(), 1);
+		assert!(Market::pool_purge_cursor().is_some());
+		assert_ok!(vault(Origin::signed(CHARLIE), VaultCall::purge_closed(1)));
+		assert_eq!(Rewards::iter_keys().count(), 0);
+		assert_ok!(vault(Origin::signed(CHARLIE), VaultCall::purge_closed(1)));
+		assert_eq!(Market::pool_purge_cursor(), None);
+	})
+}
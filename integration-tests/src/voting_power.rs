@@ -0,0 +1,148 @@
+use crate::mock::*;
+use frame_support::{
+	assert_noop, assert_ok,
+	dispatch::DispatchResultWithPostInfo,
+	traits::{Currency, LockableCurrency, WithdrawReasons},
+};
+use pallet_standard_oracle::{Call as OracleCall, PriceReport, VersionedPriceReport};
+use pallet_standard_vault::{Call as VaultCall, Error as VaultError};
+use pallet_standard_voting_power::{Error as VotingPowerError, Event as VotingPowerEvent};
+use primitives::{traits::FrozenCollateral, AssetId, Balance, LpTokenId};
+use sp_core::U256;
+use sp_runtime::{traits::Dispatchable, DispatchError, TokenError};
+
+const VOTE_LOCK: [u8; 8] = *b"democrac";
+
+fn vault(who: Origin, call: VaultCall<Test>) -> DispatchResultWithPostInfo {
+	Call::Vault(call).dispatch(who)
+}
+
+fn report(id: AssetId, price: Balance) {
+	let report = PriceReport { asset: id, price, round: Oracle::current_round(), timestamp: 0 };
+	assert_ok!(Call::Oracle(OracleCall::report(0, VersionedPriceReport::V1(report)))
+		.dispatch(Origin::signed(ALICE)));
+}
+
+// BOB provides 1_000 STND of liquidity to the MTR/STND pool and locks 1_000 STND in a vault
+fn setup() {
+	assert_ok!(Oracle::register_operator(Origin::root(), 0, ALICE));
+	report(MTR, 1);
+	report(STND, 1);
+	for who in [BOB, CHARLIE] {
+		assert_ok!(Assets::mint(Origin::signed(ALICE), STND, who, 5_000));
+	}
+	assert_ok!(Market::mint_liquidity(Origin::signed(CHARLIE), MTR, 5_000, STND, 5_000));
+	assert_ok!(Market::mint_liquidity(Origin::signed(BOB), MTR, 1_000, STND, 1_000));
+	assert_ok!(vault(
+		Origin::root(),
+		VaultCall::set_position(STND, (5, 100), (U256::from(2), U256::from(3)), (1, 100))
+	));
+	assert_ok!(vault(Origin::signed(BOB), VaultCall::generate(100, STND, 1_000)));
+}
+
+fn liquidity(who: AccountId) -> (Balance, Balance) {
+	let position = Market::liquidity_position(&who, LPT).expect("Liquidity expected");
+	(position.amount, position.underlying.iter().find(|(id, _)| *id == STND).unwrap().1)
+}
+
+fn last_event() -> Event {
+	System::events().pop().expect("Event expected").event
+}
+
+#[test]
+fn snapshot_counts_and_freezes_the_positions() {
+	new_test_ext().execute_with(|| {
+		setup();
+		let (lp_amount, underlying) = liquidity(BOB);
+		assert_eq!(VotingPower::free_balance(&BOB), INITIAL_BALANCE);
+
+		assert_noop!(
+			VotingPower::snapshot(Origin::signed(BOB), vec![LpTokenId(99)]),
+			VotingPowerError::<Test>::NotGovernancePool
+		);
+		assert_ok!(VotingPower::snapshot(Origin::signed(BOB), vec![LPT]));
+		let power = underlying + 1_000;
+		assert_eq!(last_event(), Event::VotingPower(VotingPowerEvent::SnapshotTaken(BOB, power)));
+		assert_eq!(VotingPower::power(&BOB), power);
+		assert_eq!(VotingPower::free_balance(&BOB), INITIAL_BALANCE + power);
+		assert_eq!(Balances::free_balance(&BOB), INITIAL_BALANCE);
+		assert_eq!(VotingPower::frozen_liquidity(LPT.0, BOB), Some(lp_amount));
+
+		// The positions can not back the votes of another account
+		assert_noop!(
+			Assets::transfer(Origin::signed(BOB), LPT.0, CHARLIE, 1),
+			DispatchError::Token(TokenError::Frozen)
+		);
+		assert_noop!(
+			vault(Origin::signed(BOB), VaultCall::withdraw_collateral(0, STND, 1)),
+			VaultError::<Test>::CollateralFrozen
+		);
+		assert_noop!(
+			vault(Origin::signed(BOB), VaultCall::transfer_vault(CHARLIE, 0)),
+			VaultError::<Test>::CollateralFrozen
+		);
+
+		// More collateral is counted by the next snapshot, which keeps the liquidity
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::deposit_collateral(0, STND, 500)));
+		assert_ok!(VotingPower::snapshot(Origin::signed(BOB), vec![]));
+		assert_eq!(VotingPower::power(&BOB), power + 500);
+		assert_eq!(VotingPower::frozen_liquidity(LPT.0, BOB), Some(lp_amount));
+	})
+}
+
+#[test]
+fn positions_are_released_once_the_votes_are_unlocked() {
+	new_test_ext().execute_with(|| {
+		setup();
+		assert_noop!(
+			VotingPower::release(Origin::signed(BOB)),
+			VotingPowerError::<Test>::NoSnapshot
+		);
+		assert_ok!(VotingPower::snapshot(Origin::signed(BOB), vec![LPT]));
+
+		// Votes beyond the balance are backed by the positions
+		VotingPower::set_lock(VOTE_LOCK, &BOB, INITIAL_BALANCE, WithdrawReasons::TRANSFER);
+		assert_eq!(VotingPower::vote_lock(&BOB), INITIAL_BALANCE);
+		VotingPower::extend_lock(VOTE_LOCK, &BOB, INITIAL_BALANCE + 1, WithdrawReasons::TRANSFER);
+		assert_eq!(VotingPower::vote_lock(&BOB), INITIAL_BALANCE + 1);
+		assert_noop!(
+			VotingPower::release(Origin::signed(BOB)),
+			VotingPowerError::<Test>::PowerInUse
+		);
+
+		VotingPower::remove_lock(VOTE_LOCK, &BOB);
+		assert_ok!(VotingPower::release(Origin::signed(BOB)));
+		assert_eq!(last_event(), Event::VotingPower(VotingPowerEvent::SnapshotReleased(BOB)));
+		assert_eq!(VotingPower::power(&BOB), 0);
+		assert_eq!(VotingPower::frozen_liquidity(LPT.0, BOB), None);
+		assert_ok!(Assets::transfer(Origin::signed(BOB), LPT.0, CHARLIE, 1));
+		assert_ok!(vault(Origin::signed(BOB), VaultCall::withdraw_collateral(0, STND, 1)));
+	})
+}
+
+#[test]
+fn liquidated_collateral_stops_counting() {
+	new_test_ext().execute_with(|| {
+		setup();
+		let (lp_amount, underlying) = liquidity(BOB);
+		assert_ok!(VotingPower::snapshot(Origin::signed(BOB), vec![LPT]));
+		assert_eq!(VotingPower::power(&BOB), underlying + 1_000);
+
+		// 100 MTR at a price of 10 are worth more than 1_000 STND can back
+		assert_ok!(Assets::mint(Origin::signed(ALICE), STND, ALICE, 1_000));
+		System::set_block_number(2);
+		report(MTR, 10);
+		assert_ok!(vault(Origin::signed(ALICE), VaultCall::liquidate_vault(0)));
+		System::set_block_number(5);
+		assert_ok!(vault(Origin::signed(ALICE), VaultCall::liquidate_vault(0)));
+
+		System::assert_has_event(Event::VotingPower(VotingPowerEvent::CollateralLiquidated(
+			BOB, underlying,
+		)));
+		assert_eq!(VotingPower::power(&BOB), underlying);
+		assert_eq!(VotingPower::free_balance(&BOB), INITIAL_BALANCE + underlying);
+		assert_eq!(VotingPower::frozen_collateral(&BOB, STND), 0);
+		// The liquidity is still counted and frozen
+		assert_eq!(VotingPower::frozen_liquidity(LPT.0, BOB), Some(lp_amount));
+	})
+}
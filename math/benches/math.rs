@@ -0,0 +1,15 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use standard_math::{sqrt, sqrt_u256, to_u256};
+
+fn bench_sqrt(c: &mut Criterion) {
+	// Liquidity minted for the first deposit into a pool is the root of the product of the
+	// deposits, so benchmark small and large products
+	c.bench_function("sqrt small", |b| b.iter(|| sqrt(black_box(1_000_000))));
+	c.bench_function("sqrt u128::MAX", |b| b.iter(|| sqrt(black_box(u128::MAX))));
+	let product = to_u256(u128::MAX) * to_u256(u128::MAX);
+	c.bench_function("sqrt_u256 small", |b| b.iter(|| sqrt_u256(black_box(to_u256(1_000_000)))));
+	c.bench_function("sqrt_u256 product", |b| b.iter(|| sqrt_u256(black_box(product))));
+}
+
+criterion_group!(benches, bench_sqrt);
+criterion_main!(benches);
@@ -0,0 +1,136 @@
+//! Integer math shared by the Standard pallets.
+//!
+//! The functions come for `u128` balances and for `U256`, which holds the product of two balances
+//! without overflowing. Nothing in here touches storage, so they are tested without a runtime.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use core::ops::Sub;
+pub use sp_core::U256;
+
+/// Square root of `y`, rounded down.
+pub fn sqrt(y: u128) -> u128 {
+	if y > 3 {
+		// Babylonian method, the estimate decreases until it reaches the root
+		let mut z = y;
+		let mut x = y / 2 + 1;
+		while x < z {
+			z = x;
+			x = (y / x + x) / 2;
+		}
+		z
+	} else if y != 0 {
+		1
+	} else {
+		0
+	}
+}
+
+/// Square root of `y`, rounded down.
+pub fn sqrt_u256(y: U256) -> U256 {
+	y.integer_sqrt()
+}
+
+/// Smaller of `x` and `y`.
+pub fn min<T: Ord>(x: T, y: T) -> T {
+	if x < y {
+		x
+	} else {
+		y
+	}
+}
+
+/// Distance between `x` and `y`.
+pub fn absdiff<T: Ord + Sub<Output = T>>(x: T, y: T) -> T {
+	if x < y {
+		y - x
+	} else {
+		x - y
+	}
+}
+
+/// `value` widened to `U256`.
+pub fn to_u256(value: u128) -> U256 {
+	U256::from(value)
+}
+
+/// `value` narrowed to `u128`, `u128::MAX` if it does not fit.
+pub fn saturating_to_u128(value: U256) -> u128 {
+	if value > U256::from(u128::MAX) {
+		u128::MAX
+	} else {
+		value.as_u128()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use proptest::prelude::*;
+
+	#[test]
+	fn sqrt_of_small_numbers() {
+		for y in 0..=100_000_u128 {
+			let s = sqrt(y);
+			assert!(s * s <= y && (s + 1) * (s + 1) > y, "sqrt({}) = {}", y, s);
+			assert_eq!(sqrt_u256(to_u256(y)), to_u256(s));
+		}
+	}
+
+	#[test]
+	fn sqrt_of_extremes() {
+		assert_eq!(sqrt(u128::MAX), u64::MAX as u128);
+		assert_eq!(sqrt(u128::MAX - 1), u64::MAX as u128);
+		assert_eq!(sqrt_u256(U256::MAX), U256::from(u128::MAX));
+		let square = to_u256(u128::MAX) * to_u256(u128::MAX);
+		assert_eq!(sqrt_u256(square), to_u256(u128::MAX));
+		assert_eq!(sqrt_u256(square - 1), to_u256(u128::MAX - 1));
+	}
+
+	#[test]
+	fn min_and_absdiff_work() {
+		assert_eq!(min(1_u128, 3), 1);
+		assert_eq!(min(3_u128, 1), 1);
+		assert_eq!(min(U256::from(2), U256::from(2)), U256::from(2));
+		assert_eq!(absdiff(1_u128, 3), 2);
+		assert_eq!(absdiff(3_u128, 1), 2);
+		assert_eq!(absdiff(0, u128::MAX), u128::MAX);
+		assert_eq!(absdiff(U256::MAX, U256::zero()), U256::MAX);
+	}
+
+	#[test]
+	fn conversions_saturate() {
+		assert_eq!(saturating_to_u128(to_u256(u128::MAX)), u128::MAX);
+		assert_eq!(saturating_to_u128(to_u256(u128::MAX) + 1), u128::MAX);
+		assert_eq!(saturating_to_u128(U256::from(42)), 42);
+	}
+
+	proptest! {
+		#[test]
+		fn sqrt_is_floor(y in any::<u128>()) {
+			let s = to_u256(sqrt(y));
+			prop_assert!(s * s <= to_u256(y));
+			prop_assert!((s + 1) * (s + 1) > to_u256(y));
+		}
+
+		#[test]
+		fn sqrt_u256_is_floor(a in any::<u128>(), b in any::<u128>()) {
+			// Products of two balances, as in the liquidity of a pool
+			let y = to_u256(a) * to_u256(b);
+			let s = sqrt_u256(y);
+			prop_assert!(s * s <= y);
+			prop_assert!((s + 1).full_mul(s + 1) > y.into());
+		}
+
+		#[test]
+		fn sqrt_matches_u256(y in any::<u128>()) {
+			prop_assert_eq!(sqrt_u256(to_u256(y)), to_u256(sqrt(y)));
+		}
+
+		#[test]
+		fn absdiff_is_symmetric(x in any::<u128>(), y in any::<u128>()) {
+			prop_assert_eq!(absdiff(x, y), absdiff(y, x));
+			prop_assert_eq!(min(x, y) + absdiff(x, y), x.max(y));
+		}
+	}
+}
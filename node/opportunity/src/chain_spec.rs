@@ -1,5 +1,5 @@
 use pallet_im_online::sr25519::AuthorityId as ImOnlineId;
-use sc_chain_spec::{ChainSpecExtension, ChainType};
+use sc_chain_spec::{ChainSpecExtension, ChainType, Properties};
 use sc_client_api::{BadBlocks, ForkBlocks};
 use serde::{Deserialize, Serialize};
 use sp_authority_discovery::AuthorityId as AuthorityDiscoveryId;
@@ -14,13 +14,14 @@ use sp_runtime::{
 use opportunity_runtime::{
 	wasm_binary_unwrap, AssetRegistryConfig, AuraConfig, AuthorityDiscoveryConfig, BalancesConfig,
 	Block, CouncilConfig, DemocracyConfig, EVMConfig, ElectionsConfig, EthereumConfig,
-	GenesisConfig, GrandpaConfig, ImOnlineConfig, OracleConfig, Precompiles, SessionConfig,
-	SessionKeys, StakerStatus, StakingConfig, SudoConfig, SystemConfig, TechnicalCommitteeConfig,
-	TechnicalMembershipConfig, TreasuryConfig,
+	GenesisConfig, GrandpaConfig, ImOnlineConfig, OracleConfig, Precompiles, RiskCommitteeConfig,
+	SessionConfig, SessionKeys, StakerStatus, StakingConfig, SudoConfig, SystemConfig,
+	TechnicalCommitteeConfig, TechnicalMembershipConfig, TreasuryConfig,
+};
+use primitives::{
+	constants::{asset_ids, network},
+	AccountId, Balance, Signature,
 };
-use primitives::{AccountId, AssetId, Balance, Signature};
-
-pub const CORE_ASSET_ID: AssetId = 1;
 
 // Node `ChainSpec` extensions.
 // Additional parameters for some Substrate core modules,
@@ -42,14 +43,17 @@ pub type ChainSpec = sc_service::GenericChainSpec<opportunity_runtime::GenesisCo
 type AccountPublic = <Signature as Verify>::Signer;
 
 const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
-const OPPORTUNITY_PROPERTIES: &str = r#"
-        {
-            "ss58Format": 42,
-            "tokenDecimals": 18,
-            "tokenSymbol": "OPT"
-        }"#;
 const OPPORTUNITY_PROTOCOL_ID: &str = "opt";
 
+/// Address format and native token announced to wallets
+fn opportunity_properties() -> Properties {
+	let mut properties = Properties::new();
+	properties.insert("ss58Format".into(), network::SS58_PREFIX.into());
+	properties.insert("tokenDecimals".into(), network::TOKEN_DECIMALS.into());
+	properties.insert("tokenSymbol".into(), "OPT".into());
+	properties
+}
+
 fn session_keys(
 	grandpa: GrandpaId,
 	aura: AuraId,
@@ -140,7 +144,7 @@ pub fn opportunity_standalone_config() -> Result<ChainSpec, String> {
 		// Fork ID
 		None,
 		// Properties
-		serde_json::from_str(OPPORTUNITY_PROPERTIES).unwrap(),
+		Some(opportunity_properties()),
 		// Extensions
 		Default::default(),
 	))
@@ -242,14 +246,15 @@ fn opportunity_testnet_config_genesis(
 			..Default::default()
 		},
 		asset_registry: AssetRegistryConfig {
-			core_asset_id: CORE_ASSET_ID,
+			core_asset_id: asset_ids::STND,
 			asset_ids: vec![
-				(b"STD".to_vec(), 1),
-				(b"MTR".to_vec(), 2),
-				(b"DOT".to_vec(), 3),
-				(b"KSM".to_vec(), 4),
+				(b"STD".to_vec(), asset_ids::STND),
+				(b"MTR".to_vec(), asset_ids::MTR),
+				(b"DOT".to_vec(), asset_ids::DOT),
+				(b"KSM".to_vec(), asset_ids::KSM),
 			],
-			next_asset_id: 5,
+			// ROC is not registered on opportunity, so its id is the next free one
+			next_asset_id: asset_ids::ROC,
 		},
 		oracle: OracleConfig {
 			oracles: [get_account_id_from_seed::<sr25519::Public>("Alice")].to_vec(),
@@ -267,6 +272,7 @@ fn opportunity_testnet_config_genesis(
 			phantom: Default::default(),
 		},
 		technical_membership: TechnicalMembershipConfig::default(),
+		risk_committee: RiskCommitteeConfig::default(),
 		treasury: TreasuryConfig::default(),
 		evm: EVMConfig {
 			// We need _some_ code inserted at the precompile address so that
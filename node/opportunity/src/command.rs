@@ -23,9 +23,10 @@ use crate::{
 };
 use frame_benchmarking_cli::BenchmarkCmd;
 use opportunity_runtime::RuntimeApi;
-use primitives::Block;
+use primitives::{constants::network, Block};
 use sc_cli::{ChainSpec, RuntimeVersion, SubstrateCli};
 use sc_service::PartialComponents;
+use sp_core::crypto::{set_default_ss58_version, Ss58AddressFormat};
 use std::sync::Arc;
 
 impl SubstrateCli for Cli {
@@ -71,6 +72,8 @@ impl SubstrateCli for Cli {
 /// Parse and run command line arguments
 pub fn run() -> sc_cli::Result<()> {
 	let cli = Cli::from_args();
+	// Addresses are printed and parsed in the Standard format
+	set_default_ss58_version(Ss58AddressFormat::custom(network::SS58_PREFIX));
 
 	match &cli.subcommand {
 		Some(Subcommand::Key(cmd)) => cmd.run(&cli),
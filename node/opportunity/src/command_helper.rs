@@ -21,6 +21,7 @@
 
 use crate::service::FullClient;
 use opportunity_runtime::{
+	impls::{BoostPriority, ChargeFeeInAsset},
 	BlockHashCount, Call, Runtime, SignedExtra, SignedPayload, SystemCall, UncheckedExtrinsic,
 	VERSION,
 };
@@ -97,13 +98,27 @@ pub fn create_benchmark_extrinsic(
 		)),
 		frame_system::CheckNonce::<Runtime>::from(nonce),
 		frame_system::CheckWeight::<Runtime>::new(),
-		pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(0),
+		ChargeFeeInAsset::from(0, None),
+		pallet_standard_oracle::CheckProviderReport::<Runtime>::new(),
+		pallet_standard_vault::CheckPriceAge::<Runtime>::new(),
+		BoostPriority,
 	);
 
 	let raw_payload = SignedPayload::from_raw(
 		call.clone(),
 		extra.clone(),
-		(VERSION.spec_version, VERSION.transaction_version, genesis_hash, best_hash, (), (), ()),
+		(
+			VERSION.spec_version,
+			VERSION.transaction_version,
+			genesis_hash,
+			best_hash,
+			(),
+			(),
+			(),
+			(),
+			(),
+			(),
+		),
 	);
 	let signature = raw_payload.using_encoded(|e| sender.sign(e));
 
@@ -10,7 +10,7 @@ use fc_rpc_core::types::{FeeHistoryCache, FilterPool};
 use fp_storage::EthereumStorageSchema;
 // use opportunity_runtime::{opaque::Block, AccountId, Balance, Hash, Index};
 use jsonrpc_pubsub::manager::SubscriptionManager;
-use primitives::{AccountId, Balance, Block, Hash, Index};
+use primitives::{AccountId, Balance, Block, BlockNumber, Hash, Index};
 use sc_client_api::{
 	backend::{AuxStore, Backend, StateBackend, StorageProvider},
 	client::BlockchainEvents,
@@ -110,6 +110,8 @@ where
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
 	C::Api: BlockBuilder<Block>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+	C::Api: primitives::OracleApi<Block, AccountId>,
+	C::Api: primitives::ChainBridgeApi<Block, AccountId, BlockNumber>,
 	C::Api: fp_rpc::ConvertTransactionRuntimeApi<Block>,
 	C::Api: fp_rpc::EthereumRuntimeRPCApi<Block>,
 	P: TransactionPool<Block = Block> + 'static,
@@ -120,6 +122,8 @@ where
 		EthPubSubApiServer, EthSigner, HexEncodedIdProvider, NetApi, NetApiServer, Web3Api,
 		Web3ApiServer,
 	};
+	use pallet_standard_chainbridge_rpc::{ChainBridge, ChainBridgeApi};
+	use pallet_standard_oracle_rpc::{Oracle, OracleApi};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
 	use substrate_frame_rpc_system::{FullSystem, SystemApi};
 
@@ -148,6 +152,8 @@ where
 		deny_unsafe,
 	)));
 	io.extend_with(TransactionPaymentApi::to_delegate(TransactionPayment::new(client.clone())));
+	io.extend_with(OracleApi::to_delegate(Oracle::new(client.clone())));
+	io.extend_with(ChainBridgeApi::to_delegate(ChainBridge::new(client.clone())));
 
 	let mut signers = Vec::new();
 	if enable_dev_signer {
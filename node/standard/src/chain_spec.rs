@@ -1,30 +1,38 @@
 use cumulus_primitives_core::ParaId;
 use sc_chain_spec::{ChainSpecExtension, ChainSpecGroup};
-use sc_service::ChainType;
+use sc_service::{ChainType, Properties};
 use serde::{Deserialize, Serialize};
-use sp_core::{sr25519, Pair, Public};
-use sp_runtime::traits::{IdentifyAccount, Verify};
+use sp_core::{sr25519, Pair, Public, U256};
+use sp_runtime::{
+	traits::{IdentifyAccount, Verify},
+	FixedU128,
+};
 use standard_runtime::{
-	AssetRegistryConfig, AuraId, BalancesConfig, CollatorSelectionConfig, EVMConfig,
-	EthereumConfig, GenesisConfig, OracleConfig, ParachainInfoConfig, Precompiles, SessionConfig,
-	SessionKeys, SudoConfig, SystemConfig, VestingConfig, EXISTENTIAL_DEPOSIT, WASM_BINARY,
+	AssetRegistryConfig, AssetsConfig, AuraId, BalancesConfig, CollatorSelectionConfig, EVMConfig,
+	EthereumConfig, GenesisConfig, MarketConfig, OracleConfig, ParachainInfoConfig, Precompiles,
+	RiskCommitteeConfig, SessionConfig, SessionKeys, SudoConfig, SystemConfig, VaultConfig,
+	VestingConfig, EXISTENTIAL_DEPOSIT, WASM_BINARY,
 };
 
-use primitives::{AccountId, AssetId, Signature};
-
-pub const CORE_ASSET_ID: AssetId = 1;
+use primitives::{
+	constants::{asset_ids, network},
+	AccountId, Balance, Signature,
+};
 
 type AccountPublic = <Signature as Verify>::Signer;
 
 const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
-const STANDARD_PROPERTIES: &str = r#"
-        {
-            "ss58Format": 42,
-            "tokenDecimals": 18,
-            "tokenSymbol": "STND"
-        }"#;
 const STANDARD_PROTOCOL_ID: &str = "standard";
 
+/// Address format and native token announced to wallets
+fn standard_properties() -> Properties {
+	let mut properties = Properties::new();
+	properties.insert("ss58Format".into(), network::SS58_PREFIX.into());
+	properties.insert("tokenDecimals".into(), network::TOKEN_DECIMALS.into());
+	properties.insert("tokenSymbol".into(), "STND".into());
+	properties
+}
+
 /// Specialized `ChainSpec` for the normal parachain runtime.
 pub type StandardChainSpec = sc_service::GenericChainSpec<GenesisConfig, Extensions>;
 
@@ -109,6 +117,7 @@ pub fn standard_kusama_genesis_config() -> StandardChainSpec {
 					get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
 				],
 				2094.into(),
+				false,
 			)
 		},
 		// Bootnodes
@@ -123,7 +132,7 @@ pub fn standard_kusama_genesis_config() -> StandardChainSpec {
 		// Fork ID
 		None,
 		// Properties
-		serde_json::from_str(STANDARD_PROPERTIES).unwrap(),
+		Some(standard_properties()),
 		// Extensions
 		Extensions { relay_chain: "kusama".into(), para_id: 2094 },
 	)
@@ -163,6 +172,7 @@ pub fn standard_rococo_genesis_config() -> StandardChainSpec {
 					get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
 				],
 				2000.into(),
+				false,
 			)
 		},
 		// Bootnodes
@@ -177,7 +187,7 @@ pub fn standard_rococo_genesis_config() -> StandardChainSpec {
 		// Fork ID
 		None,
 		// Properties
-		serde_json::from_str(STANDARD_PROPERTIES).unwrap(),
+		Some(standard_properties()),
 		// Extensions
 		Extensions { relay_chain: "rococo".into(), para_id: 2000 },
 	)
@@ -212,6 +222,7 @@ pub fn development_config() -> StandardChainSpec {
 					get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
 				],
 				2000.into(),
+				true,
 			)
 		},
 		// Bootnodes
@@ -264,6 +275,7 @@ pub fn local_config() -> StandardChainSpec {
 					get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
 				],
 				2000.into(),
+				true,
 			)
 		},
 		// Bootnodes
@@ -281,12 +293,79 @@ pub fn local_config() -> StandardChainSpec {
 	)
 }
 
+/// Oracle providers, prices, assets, pool and collateral position of a development chain
+struct DefiFixtures {
+	oracles: Vec<AccountId>,
+	prices: Vec<(primitives::AssetId, FixedU128)>,
+	assets: AssetsConfig,
+	market: MarketConfig,
+	vault: VaultConfig,
+}
+
+/// Alice and Bob report STND and MTR at 1 USD, hold both assets and Alice provides a STND/MTR
+/// pool, against which STND can be deposited as collateral
+fn defi_fixtures() -> DefiFixtures {
+	const UNIT: Balance = 10u128.pow(network::TOKEN_DECIMALS as u32);
+	let alice = get_account_id_from_seed::<sr25519::Public>("Alice");
+	let bob = get_account_id_from_seed::<sr25519::Public>("Bob");
+	let assets = [(asset_ids::STND, "Standard", "STND"), (asset_ids::MTR, "Meter", "MTR")];
+
+	DefiFixtures {
+		oracles: vec![alice.clone(), bob.clone()],
+		prices: assets.iter().map(|(id, _, _)| (*id, FixedU128::from(1))).collect(),
+		assets: AssetsConfig {
+			assets: assets.iter().map(|(id, _, _)| (*id, alice.clone(), true, 1)).collect(),
+			metadata: assets
+				.iter()
+				.map(|(id, name, symbol)| {
+					(
+						*id,
+						name.as_bytes().to_vec(),
+						symbol.as_bytes().to_vec(),
+						network::TOKEN_DECIMALS,
+					)
+				})
+				.collect(),
+			accounts: assets
+				.iter()
+				.flat_map(|(id, _, _)| {
+					[(*id, alice.clone(), 10_000_000 * UNIT), (*id, bob.clone(), 1_000_000 * UNIT)]
+				})
+				.collect(),
+		},
+		market: MarketConfig {
+			pools: vec![(
+				alice,
+				asset_ids::STND,
+				1_000_000 * UNIT,
+				asset_ids::MTR,
+				1_000_000 * UNIT,
+			)],
+		},
+		vault: VaultConfig {
+			// 5% liquidation fee, lending up to 2/3 of the collateral value at 2% stability fee
+			positions: vec![(asset_ids::STND, (5, 100), (U256::from(2), U256::from(3)), (2, 100))],
+		},
+	}
+}
+
 fn testnet_genesis(
 	root_key: AccountId,
 	initial_authorities: Vec<(AccountId, AuraId)>,
 	endowed_accounts: Vec<AccountId>,
 	id: ParaId,
+	with_defi_fixtures: bool,
 ) -> GenesisConfig {
+	let fixtures = match with_defi_fixtures {
+		true => defi_fixtures(),
+		false => DefiFixtures {
+			oracles: vec![get_account_id_from_seed::<sr25519::Public>("Alice")],
+			prices: vec![],
+			assets: Default::default(),
+			market: Default::default(),
+			vault: Default::default(),
+		},
+	};
 	// This is supposed the be the simplest bytecode to revert without returning any data.
 	// We will pre-deploy it under all of our precompiles to ensure they can be called from
 	// within contracts.
@@ -323,20 +402,25 @@ fn testnet_genesis(
 		aura: Default::default(),
 		aura_ext: Default::default(),
 		asset_registry: AssetRegistryConfig {
-			core_asset_id: CORE_ASSET_ID,
+			core_asset_id: asset_ids::STND,
 			asset_ids: vec![
-				(b"STND".to_vec(), 1),
-				(b"MTR".to_vec(), 2),
-				(b"DOT".to_vec(), 3),
-				(b"KSM".to_vec(), 4),
-				(b"ROC".to_vec(), 5),
+				(b"STND".to_vec(), asset_ids::STND),
+				(b"MTR".to_vec(), asset_ids::MTR),
+				(b"DOT".to_vec(), asset_ids::DOT),
+				(b"KSM".to_vec(), asset_ids::KSM),
+				(b"ROC".to_vec(), asset_ids::ROC),
 			],
-			next_asset_id: 6,
+			next_asset_id: asset_ids::NEXT_ASSET_ID,
 		},
+		assets: fixtures.assets,
+		market: fixtures.market,
 		oracle: OracleConfig {
-			oracles: [get_account_id_from_seed::<sr25519::Public>("Alice")].to_vec(),
+			oracles: fixtures.oracles,
 			provider_count: 5,
+			prices: fixtures.prices,
 		},
+		vault: fixtures.vault,
+		risk_committee: RiskCommitteeConfig::default(),
 		evm: EVMConfig {
 			// We need _some_ code inserted at the precompile address so that
 			// the evm will actually call the address.
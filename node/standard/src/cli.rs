@@ -1,5 +1,6 @@
 use crate::chain_spec;
 use clap::Parser;
+use sc_cli::{BlockNumberOrHash, DatabaseParams, PruningParams, SharedParams};
 use std::path::PathBuf;
 
 /// Sub-commands supported by the collator.
@@ -25,6 +26,10 @@ pub enum Subcommand {
 	/// Export the state of a given block into a chain spec.
 	ExportState(sc_cli::ExportStateCmd),
 
+	/// Export the pools, vaults and prices at a given block as JSON.
+	#[clap(name = "export-defi-state")]
+	ExportDefiState(ExportDefiStateCommand),
+
 	/// Import blocks.
 	ImportBlocks(sc_cli::ImportBlocksCmd),
 
@@ -76,6 +81,27 @@ pub struct ExportGenesisWasmCommand {
 	pub chain: Option<String>,
 }
 
+/// Command for exporting the state of the DeFi pallets
+#[derive(Debug, Parser)]
+pub struct ExportDefiStateCommand {
+	/// Block hash or number to export the state at, the best block if unspecified.
+	#[clap(value_name = "HASH or NUMBER")]
+	pub input: Option<BlockNumberOrHash>,
+
+	/// Output file name or stdout if unspecified.
+	#[clap(long, parse(from_os_str))]
+	pub output: Option<PathBuf>,
+
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
 #[derive(Debug, Parser)]
 #[clap(
 	propagate_version = true,
@@ -89,6 +115,15 @@ pub struct Cli {
 	#[clap(flatten)]
 	pub run: cumulus_client_cli::RunCmd,
 
+	/// Start a development or local chain with the DeFi state exported by `export-defi-state`.
+	#[clap(long, parse(from_os_str))]
+	pub load_state: Option<PathBuf>,
+
+	/// Author blocks without a relay chain as soon as transactions arrive or `engine_createBlock`
+	/// is called.
+	#[clap(long, requires = "dev")]
+	pub instant_seal: bool,
+
 	/// Relay chain arguments
 	#[clap(raw = true)]
 	pub relay_chain_args: Vec<String>,
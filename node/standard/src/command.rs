@@ -2,6 +2,7 @@ use crate::{
 	chain_spec,
 	chain_spec::StandardChainSpec,
 	cli::{Cli, RelayChainCli, Subcommand},
+	defi_state,
 	service::{new_partial, StandardRuntimeExecutor},
 };
 use codec::Encode;
@@ -10,13 +11,16 @@ use cumulus_primitives_core::ParaId;
 use frame_benchmarking_cli::BenchmarkCmd;
 use log::info;
 use polkadot_parachain::primitives::AccountIdConversion;
-use primitives::Block;
+use primitives::{constants::network, Block};
 use sc_cli::{
 	ChainSpec, CliConfiguration, DefaultConfigurationValues, ImportParams, KeystoreParams,
 	NetworkParams, Result, RuntimeVersion, SharedParams, SubstrateCli,
 };
 use sc_service::config::{BasePath, PrometheusConfig};
-use sp_core::hexdisplay::HexDisplay;
+use sp_core::{
+	crypto::{set_default_ss58_version, Ss58AddressFormat},
+	hexdisplay::HexDisplay,
+};
 use sp_runtime::traits::Block as BlockT;
 use standard_runtime::RuntimeApi;
 use std::{io::Write, net::SocketAddr};
@@ -70,7 +74,11 @@ impl SubstrateCli for Cli {
 	}
 
 	fn load_spec(&self, id: &str) -> std::result::Result<Box<dyn sc_service::ChainSpec>, String> {
-		load_spec(id)
+		let spec = load_spec(id)?;
+		match &self.load_state {
+			Some(path) => defi_state::load_state(spec, path),
+			None => Ok(spec),
+		}
 	}
 
 	fn native_runtime_version(_: &Box<dyn ChainSpec>) -> &'static RuntimeVersion {
@@ -147,6 +155,8 @@ macro_rules! construct_async_run {
 /// Parse command line arguments into service configuration.
 pub fn run() -> Result<()> {
 	let cli = Cli::from_args();
+	// Addresses are printed and parsed in the Standard format
+	set_default_ss58_version(Ss58AddressFormat::custom(network::SS58_PREFIX));
 
 	match &cli.subcommand {
 		Some(Subcommand::BuildSpec(cmd)) => {
@@ -168,6 +178,11 @@ pub fn run() -> Result<()> {
 				Ok(cmd.run(components.client, config.chain_spec))
 			})
 		},
+		Some(Subcommand::ExportDefiState(cmd)) => {
+			construct_async_run!(|components, cli, cmd, config| {
+				Ok(cmd.run(components.client))
+			})
+		},
 		Some(Subcommand::ImportBlocks(cmd)) => {
 			construct_async_run!(|components, cli, cmd, config| {
 				Ok(cmd.run(components.client, components.import_queue))
@@ -202,7 +217,7 @@ pub fn run() -> Result<()> {
 			builder.with_profiling(sc_tracing::TracingReceiver::Log, "");
 			let _ = builder.init();
 
-			let spec = load_spec(&params.chain.clone().unwrap_or_default())?;
+			let spec = cli.load_spec(&params.chain.clone().unwrap_or_default())?;
 			let state_version = Cli::native_runtime_version(&spec).state_version();
 			let block: Block = generate_genesis_block(&spec, state_version)?;
 			let raw_header = block.header().encode();
@@ -279,6 +294,12 @@ pub fn run() -> Result<()> {
 			let collator_options = cli.run.collator_options();
 
 			runner.run_node_until_exit(|config| async move {
+				if cli.instant_seal {
+					return crate::service::start_instant_seal_node(config)
+						.await
+						.map_err(Into::into)
+				}
+
 				let para_id = chain_spec::Extensions::try_get(&*config.chain_spec)
 					.map(|e| e.para_id)
 					.ok_or_else(|| "Could not find parachain ID in chain-spec.")?;
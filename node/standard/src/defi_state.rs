@@ -0,0 +1,159 @@
+//! `export-defi-state`: writes the pools, vaults and prices at a block as JSON, for analytics,
+//! audits and forked test environments.
+//!
+//! `--load-state`: starts a development or local chain from such a snapshot.
+
+use crate::{chain_spec::get_account_id_from_seed, cli::ExportDefiStateCommand};
+use frame_support::{
+	storage::{StorageDoubleMap, StorageMap, StorageValue},
+	traits::tokens::fungibles::{Create, Mutate},
+	BasicExternalities,
+};
+use pallet_standard_market as market;
+use pallet_standard_oracle as oracle;
+use pallet_standard_vault as vault;
+use primitives::{
+	snapshot::DefiSnapshot, AccountId, AssetId, Balance, Block, DefiSnapshotApi, VaultId,
+};
+use sc_cli::{CliConfiguration, DatabaseParams, PruningParams, Result, SharedParams};
+use sc_service::{ChainSpec, ChainType};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::sr25519;
+use sp_runtime::generic::BlockId;
+use standard_runtime::{Assets, Market, Runtime, Vault};
+use std::{collections::BTreeMap, io::Write, path::Path, sync::Arc};
+
+impl ExportDefiStateCommand {
+	/// Writes the snapshot of the requested block, or of the best block if none is given.
+	pub async fn run<C>(&self, client: Arc<C>) -> Result<()>
+	where
+		C: ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+		C::Api: DefiSnapshotApi<Block, AccountId>,
+	{
+		let at = match &self.input {
+			Some(input) => input.parse::<Block>()?,
+			None => BlockId::Hash(client.info().best_hash),
+		};
+		let snapshot = client
+			.runtime_api()
+			.defi_snapshot(&at)
+			.map_err(|e| format!("Unable to query the DeFi state: {:?}", e))?;
+		let json = serde_json::to_vec_pretty(&snapshot)
+			.map_err(|e| format!("Unable to encode the DeFi state: {}", e))?;
+
+		if let Some(output) = &self.output {
+			std::fs::write(output, json)?;
+		} else {
+			std::io::stdout().write_all(&json)?;
+		}
+
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ExportDefiStateCommand {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
+
+/// Overlays the snapshot at `path` onto the genesis state of `spec`.
+///
+/// The assets are created with Alice as admin. The pool reserves and the vault collateral are
+/// held by the market and vault accounts, the rest of each issuance and all liquidity tokens go
+/// to Alice. Prices are reported by oracle socket 0. Collateral positions are not part of the
+/// snapshot and have to be set with sudo before the vaults can be used.
+pub fn load_state(
+	mut spec: Box<dyn ChainSpec>,
+	path: &Path,
+) -> std::result::Result<Box<dyn ChainSpec>, String> {
+	if !matches!(spec.chain_type(), ChainType::Development | ChainType::Local) {
+		return Err("The DeFi state can only be loaded into a development or local chain".into())
+	}
+	let json =
+		std::fs::read(path).map_err(|e| format!("Unable to read {}: {}", path.display(), e))?;
+	let snapshot: DefiSnapshot<AccountId> = serde_json::from_slice(&json)
+		.map_err(|e| format!("Unable to decode the DeFi state: {}", e))?;
+
+	let mut storage = spec.build_storage()?;
+	BasicExternalities::execute_with_storage(&mut storage, || apply_snapshot(snapshot))?;
+	spec.set_storage(storage);
+	Ok(spec)
+}
+
+fn apply_snapshot(snapshot: DefiSnapshot<AccountId>) -> std::result::Result<(), String> {
+	let alice = get_account_id_from_seed::<sr25519::Public>("Alice");
+	let mut issuance: BTreeMap<AssetId, Balance> = snapshot.issuance.into_iter().collect();
+	// Amount of each asset held by the market and vault accounts
+	let mut held: BTreeMap<AssetId, Vec<(AccountId, Balance)>> = BTreeMap::new();
+
+	for pool in snapshot.pools {
+		let [(token0, reserve0), (token1, reserve1)] = pool.reserves;
+		market::Rewards::insert(pool.lp_token, (token0, token1));
+		market::Reserves::insert(pool.lp_token, (reserve0, reserve1));
+		market::Pairs::insert((token0, token1), pool.lp_token);
+		market::Pairs::insert((token1, token0), pool.lp_token);
+		held.entry(token0).or_default().push((Market::account_id(), reserve0));
+		held.entry(token1).or_default().push((Market::account_id(), reserve1));
+		issuance.insert(pool.lp_token.into(), pool.total_supply);
+	}
+
+	let mut next_vault_id: VaultId = 0;
+	let mut circulating: Balance = 0;
+	for position in snapshot.vaults {
+		for (collateral_id, amount) in &position.collateral {
+			vault::VaultIds::<Runtime>::insert(
+				(position.owner.clone(), *collateral_id),
+				position.vault_id,
+			);
+			held.entry(*collateral_id).or_default().push((Vault::account_id(), *amount));
+		}
+		vault::Vaults::insert(
+			position.vault_id,
+			vault::VaultInfo {
+				collateral: position.collateral.into_iter().collect(),
+				debt: position.debt,
+			},
+		);
+		vault::VaultOwners::<Runtime>::insert(position.vault_id, position.owner);
+		next_vault_id = next_vault_id.max(position.vault_id + 1);
+		circulating = circulating.saturating_add(position.debt);
+	}
+	vault::NextVaultId::put(next_vault_id);
+	vault::CirculatingSupply::put(circulating);
+
+	for (asset, price) in snapshot.prices {
+		oracle::SocketPrices::<Runtime>::insert(asset, 0, (price, 0));
+	}
+
+	for (asset, total) in issuance {
+		if !pallet_assets::Asset::<Runtime>::contains_key(asset) {
+			Assets::create(asset, alice.clone(), true, 1)
+				.map_err(|e| format!("Unable to create asset {}: {:?}", asset, e))?;
+		}
+		let mut rest = total;
+		for (who, amount) in held.remove(&asset).unwrap_or_default() {
+			Assets::mint_into(asset, &who, amount)
+				.map_err(|e| format!("Unable to mint asset {}: {:?}", asset, e))?;
+			rest = rest.saturating_sub(amount);
+		}
+		if rest > 0 {
+			Assets::mint_into(asset, &alice, rest)
+				.map_err(|e| format!("Unable to mint asset {}: {:?}", asset, e))?;
+		}
+	}
+	if let Some(asset) = held.keys().next() {
+		return Err(format!("Issuance of asset {} is missing from the DeFi state", asset))
+	}
+
+	Ok(())
+}
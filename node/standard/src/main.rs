@@ -3,6 +3,7 @@ mod chain_spec;
 mod service;
 mod cli;
 mod command;
+mod defi_state;
 mod rpc;
 
 fn main() -> sc_cli::Result<()> {
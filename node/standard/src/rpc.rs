@@ -7,21 +7,44 @@
 
 use std::sync::Arc;
 
-use primitives::{AccountId, Balance, Block, Hash, Index as Nonce};
+use primitives::{
+	AccountApi, AccountId, AssetId, Balance, Block, BlockNumber, Hash, Index as Nonce, LpTokenId,
+	VaultId,
+};
 
+use codec::Decode;
 use fc_rpc_core::types::{FeeHistoryCache, FilterPool};
+use frame_metadata::{RuntimeMetadata, RuntimeMetadataPrefixed};
+use frame_support::storage::StorageMap;
+use futures::{FutureExt, SinkExt, StreamExt};
+use jsonrpc_core::{Error as RpcError, ErrorCode};
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{typed::Subscriber, SubscriptionId};
+use pallet_standard_chainbridge_rpc::{ChainBridge, ChainBridgeApi};
+use pallet_standard_market as market;
+use pallet_standard_oracle_rpc::{Oracle, OracleApi};
 use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
 use sc_client_api::{AuxStore, Backend, BlockchainEvents, StateBackend, StorageProvider};
+use sc_consensus_manual_seal::rpc::{EngineCommand, ManualSeal, ManualSealApi};
 use sc_network::NetworkService;
 pub use sc_rpc::{DenyUnsafe, SubscriptionTaskExecutor};
 use sc_transaction_pool::{ChainApi, Pool};
 use sc_transaction_pool_api::TransactionPool;
+use scale_info::TypeDef;
 use sp_api::ProvideRuntimeApi;
 use sp_block_builder::BlockBuilder;
 use sp_blockchain::{
 	Backend as BlockchainBackend, Error as BlockChainError, HeaderBackend, HeaderMetadata,
 };
-use sp_runtime::traits::BlakeTwo256;
+use sp_core::{
+	storage::{StorageData, StorageKey},
+	Bytes,
+};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{BlakeTwo256, Block as BlockT},
+	DispatchError, FixedU128, ModuleError,
+};
 use substrate_frame_rpc_system::{FullSystem, SystemApi};
 
 use fc_rpc::{
@@ -32,6 +55,7 @@ use fc_rpc::{
 };
 use fp_storage::EthereumStorageSchema;
 use jsonrpc_pubsub::manager::SubscriptionManager;
+use serde::Serialize;
 use std::collections::BTreeMap;
 
 /// Full client dependencies
@@ -62,6 +86,8 @@ pub struct FullDeps<C, P, A: ChainApi> {
 	pub overrides: Arc<OverrideHandle<Block>>,
 	/// Cache for Ethereum block data.
 	pub block_data_cache: Arc<EthBlockDataCache<Block>>,
+	/// Manual seal command sink, only set for instant seal dev nodes
+	pub command_sink: Option<futures::channel::mpsc::Sender<EngineCommand<Hash>>>,
 }
 
 pub fn overrides_handle<C, BE>(client: Arc<C>) -> Arc<OverrideHandle<Block>>
@@ -113,6 +139,372 @@ where
 // 	+ fp_rpc::ConvertTransactionRuntimeApi<Block>
 // 	+ BlockBuilder<Block>,
 
+/// Update of a subscription read at a finalized block
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtBlock<T> {
+	/// Finalized block the update is read at
+	pub block: Hash,
+	/// State that changed in the block
+	#[serde(flatten)]
+	pub update: T,
+}
+
+/// Health of the vaults of an account
+#[derive(Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountVaultHealth {
+	/// Owner of the vaults
+	pub account: AccountId,
+	/// Open vaults of the account
+	pub vaults: Vec<VaultHealth>,
+}
+
+/// Collateral, debt and health factor of a vault
+#[derive(Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultHealth {
+	/// Id of the vault
+	pub vault_id: VaultId,
+	/// Collateral basket of the vault
+	pub collateral: Vec<(AssetId, Balance)>,
+	/// MTR issued against the collateral
+	pub debt: Balance,
+	/// `None` without debt or without oracle prices
+	pub health_factor: Option<FixedU128>,
+}
+
+/// Reserves of the pool of a pair
+#[derive(Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairReserves {
+	/// Pair as subscribed to
+	pub pair: (AssetId, AssetId),
+	/// Liquidity token of the pool, `None` without a pool for the pair
+	pub lp_token: Option<LpTokenId>,
+	/// Reserves of the assets of `pair`, in its order
+	pub reserves: (Balance, Balance),
+}
+
+/// Subscriptions pushing the vault health of accounts and the reserves of pairs for every
+/// finalized block that changed them.
+#[rpc(server)]
+pub trait DefiPubSubApi {
+	/// RPC metadata
+	type Metadata;
+
+	/// Health of the vaults of `accounts`, sent for every account once subscribed and then for
+	/// every account whose vaults changed in a finalized block
+	#[pubsub(
+		subscription = "standard_vaultHealth",
+		subscribe,
+		name = "standard_subscribeVaultHealth"
+	)]
+	fn subscribe_vault_health(
+		&self,
+		metadata: Self::Metadata,
+		subscriber: Subscriber<AtBlock<AccountVaultHealth>>,
+		accounts: Vec<AccountId>,
+	);
+
+	/// Cancels a vault health subscription
+	#[pubsub(
+		subscription = "standard_vaultHealth",
+		unsubscribe,
+		name = "standard_unsubscribeVaultHealth"
+	)]
+	fn unsubscribe_vault_health(
+		&self,
+		metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> jsonrpc_core::Result<bool>;
+
+	/// Reserves of the pools of `pairs`, sent for every pair once subscribed and then for every
+	/// pair whose reserves changed in a finalized block
+	#[pubsub(subscription = "market_reserves", subscribe, name = "market_subscribeReserves")]
+	fn subscribe_reserves(
+		&self,
+		metadata: Self::Metadata,
+		subscriber: Subscriber<AtBlock<PairReserves>>,
+		pairs: Vec<(AssetId, AssetId)>,
+	);
+
+	/// Cancels a reserves subscription
+	#[pubsub(subscription = "market_reserves", unsubscribe, name = "market_unsubscribeReserves")]
+	fn unsubscribe_reserves(
+		&self,
+		metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> jsonrpc_core::Result<bool>;
+}
+
+/// Vault health and pool reserve subscriptions driven by the finality notifications of the
+/// client.
+pub struct DefiPubSub<C, BE> {
+	client: Arc<C>,
+	manager: SubscriptionManager,
+	_marker: std::marker::PhantomData<BE>,
+}
+
+impl<C, BE> DefiPubSub<C, BE> {
+	/// Subscriptions of `client`, whose updates are sent on `executor`
+	pub fn new(client: Arc<C>, executor: SubscriptionTaskExecutor) -> Self {
+		Self {
+			client,
+			manager: SubscriptionManager::new(Arc::new(executor)),
+			_marker: Default::default(),
+		}
+	}
+}
+
+impl<C, BE> DefiPubSub<C, BE>
+where
+	C: ProvideRuntimeApi<Block> + StorageProvider<Block, BE> + BlockchainEvents<Block>,
+	C: HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: AccountApi<Block, AccountId>,
+	BE: Backend<Block> + 'static,
+{
+	// Sends the items `read` returns at the last finalized block and then the ones which changed
+	// in each finalized block
+	fn subscribe<K, T, F>(&self, subscriber: Subscriber<AtBlock<T>>, keys: Vec<K>, read: F)
+	where
+		K: Send + 'static,
+		T: Clone + PartialEq + Serialize + Send + 'static,
+		F: Fn(&C, Hash, &K) -> Option<T> + Send + 'static,
+	{
+		let client = self.client.clone();
+		let finalized = client.info().finalized_hash;
+		let mut last: Vec<Option<T>> = keys.iter().map(|_| None).collect();
+		let updates = futures::stream::iter(Some(finalized))
+			.chain(client.finality_notification_stream().map(|notification| notification.hash))
+			.flat_map(move |hash| {
+				let mut changed = Vec::new();
+				for (key, last) in keys.iter().zip(last.iter_mut()) {
+					let item = read(&client, hash, key);
+					if item.is_some() && item != *last {
+						changed.extend(item.clone().map(|update| AtBlock { block: hash, update }));
+						*last = item;
+					}
+				}
+				futures::stream::iter(changed)
+			});
+
+		self.manager.add(subscriber, |sink| {
+			updates
+				.map(|item| Ok::<_, ()>(Ok(item)))
+				.forward(sink.sink_map_err(|e| log::warn!("Unable to send update: {:?}", e)))
+				.map(|_| ())
+		});
+	}
+}
+
+// Vaults of `account` with their health factor at `hash`
+fn read_vault_health<C>(client: &C, hash: Hash, account: &AccountId) -> Option<AccountVaultHealth>
+where
+	C: ProvideRuntimeApi<Block>,
+	C::Api: AccountApi<Block, AccountId>,
+{
+	let overview = client.runtime_api().account_overview(&BlockId::Hash(hash), account.clone());
+	let vaults = overview
+		.map_err(|e| log::warn!("Unable to read the vaults of {}: {:?}", account, e))
+		.ok()?
+		.vaults
+		.into_iter()
+		.map(|vault| VaultHealth {
+			vault_id: vault.vault_id,
+			collateral: vault.collateral,
+			debt: vault.debt,
+			health_factor: vault.health_factor,
+		})
+		.collect();
+	Some(AccountVaultHealth { account: account.clone(), vaults })
+}
+
+// Reserves of the pool of `pair` at `hash`, decoded from the market storage
+fn read_pair_reserves<C, BE>(
+	client: &C,
+	hash: Hash,
+	pair: &(AssetId, AssetId),
+) -> Option<PairReserves>
+where
+	C: StorageProvider<Block, BE>,
+	BE: Backend<Block>,
+{
+	let read = |key: Vec<u8>| -> Option<Option<StorageData>> {
+		client
+			.storage(&BlockId::Hash(hash), &StorageKey(key))
+			.map_err(|e| log::warn!("Unable to read the reserves of {:?}: {:?}", pair, e))
+			.ok()
+	};
+	// Value of the storage `data`, the default of `T` if there is none
+	fn decode<T: Decode + Default>(data: Option<StorageData>) -> Option<T> {
+		match data {
+			Some(data) => T::decode(&mut &data.0[..]).ok(),
+			None => Some(T::default()),
+		}
+	}
+
+	let lp_token = match read(market::Pairs::hashed_key_for(pair))? {
+		Some(data) => LpTokenId::decode(&mut &data.0[..]).ok()?,
+		None => {
+			let reserves = (0, 0);
+			return Some(PairReserves { pair: *pair, lp_token: None, reserves })
+		},
+	};
+	let (token0, _): (AssetId, AssetId) = decode(read(market::Rewards::hashed_key_for(lp_token))?)?;
+	let (reserve0, reserve1): (Balance, Balance) =
+		decode(read(market::Reserves::hashed_key_for(lp_token))?)?;
+	// The reserves are stored in the order of the pair the pool was created for
+	let reserves = match token0 == pair.0 {
+		true => (reserve0, reserve1),
+		false => (reserve1, reserve0),
+	};
+	Some(PairReserves { pair: *pair, lp_token: Some(lp_token), reserves })
+}
+
+impl<C, BE> DefiPubSubApi for DefiPubSub<C, BE>
+where
+	C: ProvideRuntimeApi<Block> + StorageProvider<Block, BE> + BlockchainEvents<Block>,
+	C: HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: AccountApi<Block, AccountId>,
+	BE: Backend<Block> + Send + Sync + 'static,
+{
+	type Metadata = sc_rpc::Metadata;
+
+	fn subscribe_vault_health(
+		&self,
+		_metadata: Self::Metadata,
+		subscriber: Subscriber<AtBlock<AccountVaultHealth>>,
+		accounts: Vec<AccountId>,
+	) {
+		self.subscribe(subscriber, accounts, read_vault_health::<C>);
+	}
+
+	fn unsubscribe_vault_health(
+		&self,
+		_metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> jsonrpc_core::Result<bool> {
+		Ok(self.manager.cancel(id))
+	}
+
+	fn subscribe_reserves(
+		&self,
+		_metadata: Self::Metadata,
+		subscriber: Subscriber<AtBlock<PairReserves>>,
+		pairs: Vec<(AssetId, AssetId)>,
+	) {
+		self.subscribe(subscriber, pairs, read_pair_reserves::<C, BE>);
+	}
+
+	fn unsubscribe_reserves(
+		&self,
+		_metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> jsonrpc_core::Result<bool> {
+		Ok(self.manager.cancel(id))
+	}
+}
+
+/// Outcome of applying an extrinsic on top of a block without importing it
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunOutcome {
+	/// Block the extrinsic is applied on
+	pub at: Hash,
+	/// Whether the extrinsic was valid and dispatched without error
+	pub success: bool,
+	/// Why the extrinsic failed, module errors are named as `Pallet::Error`, e.g.
+	/// `Vault::InvalidCDP`
+	pub error: Option<String>,
+}
+
+/// Dry runs of extrinsics reporting why they would fail
+#[rpc]
+pub trait DryRunApi {
+	/// Applies the SCALE encoded `extrinsic` on top of `at`, the best block by default, and
+	/// discards the changes
+	#[rpc(name = "standard_dryRun")]
+	fn dry_run(&self, extrinsic: Bytes, at: Option<Hash>) -> jsonrpc_core::Result<DryRunOutcome>;
+}
+
+/// Dry runs backed by the `BlockBuilder` API of the runtime, naming module errors from its
+/// metadata.
+pub struct DryRun<C> {
+	client: Arc<C>,
+	deny_unsafe: DenyUnsafe,
+}
+
+impl<C> DryRun<C> {
+	/// Dry runs on the state of `client`, only exposed to unsafe RPC like `system_dryRun`
+	pub fn new(client: Arc<C>, deny_unsafe: DenyUnsafe) -> Self {
+		Self { client, deny_unsafe }
+	}
+}
+
+fn dry_run_error(message: &str, err: impl std::fmt::Debug) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(1),
+		message: message.into(),
+		data: Some(format!("{:?}", err).into()),
+	}
+}
+
+impl<C> DryRun<C>
+where
+	C: ProvideRuntimeApi<Block>,
+	C::Api: sp_api::Metadata<Block>,
+{
+	// Name of the error variant `error` of the pallet at `index` in the metadata at `at`
+	fn module_error_name(&self, at: &BlockId<Block>, index: u8, error: u8) -> Option<String> {
+		let metadata = self.client.runtime_api().metadata(at).ok()?;
+		let metadata = match RuntimeMetadataPrefixed::decode(&mut &metadata[..]).ok()?.1 {
+			RuntimeMetadata::V14(metadata) => metadata,
+			_ => return None,
+		};
+		let pallet = metadata.pallets.iter().find(|pallet| pallet.index == index)?;
+		let ty = metadata.types.resolve(pallet.error.as_ref()?.ty.id())?;
+		match ty.type_def() {
+			TypeDef::Variant(errors) => errors
+				.variants()
+				.iter()
+				.find(|variant| variant.index() == error)
+				.map(|variant| format!("{}::{}", pallet.name, variant.name())),
+			_ => None,
+		}
+	}
+}
+
+impl<C> DryRunApi for DryRun<C>
+where
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: BlockBuilder<Block> + sp_api::Metadata<Block>,
+{
+	fn dry_run(&self, extrinsic: Bytes, at: Option<Hash>) -> jsonrpc_core::Result<DryRunOutcome> {
+		self.deny_unsafe.check_if_safe()?;
+		let hash = at.unwrap_or_else(|| self.client.info().best_hash);
+		let at = BlockId::Hash(hash);
+		let extrinsic = <Block as BlockT>::Extrinsic::decode(&mut &*extrinsic)
+			.map_err(|e| dry_run_error("Unable to decode the extrinsic.", e))?;
+		let applied = self
+			.client
+			.runtime_api()
+			.apply_extrinsic(&at, extrinsic)
+			.map_err(|e| dry_run_error("Unable to dry run the extrinsic.", e))?;
+
+		let error = match applied {
+			Ok(Ok(())) => None,
+			Ok(Err(DispatchError::Module(ModuleError { index, error, .. }))) => Some(
+				self.module_error_name(&at, index, error)
+					.unwrap_or_else(|| format!("Module {} error {}", index, error)),
+			),
+			Ok(Err(error)) => Some(format!("{:?}", error)),
+			Err(invalid) => Some(format!("{:?}", invalid)),
+		};
+		Ok(DryRunOutcome { at: hash, success: error.is_none(), error })
+	}
+}
+
 /// Instantiate all RPC extensions.
 pub fn create_full<C, P, BE, A>(
 	deps: FullDeps<C, P, A>,
@@ -125,7 +517,11 @@ where
 	C: Send + Sync + 'static,
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>,
 	C::Api: BlockBuilder<Block>,
+	C::Api: sp_api::Metadata<Block>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+	C::Api: primitives::OracleApi<Block, AccountId>,
+	C::Api: primitives::AccountApi<Block, AccountId>,
+	C::Api: primitives::ChainBridgeApi<Block, AccountId, BlockNumber>,
 	C::Api: fp_rpc::ConvertTransactionRuntimeApi<Block>,
 	C::Api: fp_rpc::EthereumRuntimeRPCApi<Block>,
 	P: TransactionPool<Block = Block> + Sync + Send + 'static,
@@ -149,6 +545,7 @@ where
 		fee_history_cache,
 		overrides,
 		block_data_cache,
+		command_sink,
 	} = deps;
 
 	io.extend_with(SystemApi::to_delegate(FullSystem::new(
@@ -157,6 +554,13 @@ where
 		deny_unsafe,
 	)));
 	io.extend_with(TransactionPaymentApi::to_delegate(TransactionPayment::new(client.clone())));
+	io.extend_with(DryRunApi::to_delegate(DryRun::new(client.clone(), deny_unsafe)));
+	io.extend_with(OracleApi::to_delegate(Oracle::new(client.clone())));
+	io.extend_with(ChainBridgeApi::to_delegate(ChainBridge::new(client.clone())));
+	io.extend_with(DefiPubSubApi::to_delegate(DefiPubSub::new(
+		client.clone(),
+		subscription_task_executor.clone(),
+	)));
 
 	io.extend_with(EthApiServer::to_delegate(EthApi::new(
 		client.clone(),
@@ -199,5 +603,10 @@ where
 		overrides,
 	)));
 
+	if let Some(command_sink) = command_sink {
+		// Lets `engine_createBlock` and `engine_finalizeBlock` drive the authorship task.
+		io.extend_with(ManualSealApi::to_delegate(ManualSeal::new(command_sink)));
+	}
+
 	io
 }
@@ -1,6 +1,6 @@
 // Local Types
 use crate::rpc::{create_full, FullDeps};
-use primitives::{AccountId, Balance, Block, Hash, Index as Nonce};
+use primitives::{AccountId, Balance, Block, BlockNumber, Hash, Index as Nonce};
 use standard_runtime::{self, RuntimeApi};
 
 // Cumulus Imports
@@ -21,9 +21,14 @@ use fc_consensus::FrontierBlockImport;
 use fc_mapping_sync::{MappingSyncWorker, SyncStrategy};
 use fc_rpc::EthTask;
 use fc_rpc_core::types::{FeeHistoryCache, FilterPool};
-use futures::StreamExt;
+use futures::{channel::mpsc, StreamExt};
 use polkadot_service::CollatorPair;
 use sc_client_api::{BlockchainEvents, ExecutorProvider};
+use sc_consensus_manual_seal::{
+	self as manual_seal,
+	consensus::{aura::AuraConsensusDataProvider, timestamp::SlotTimestampProvider},
+	rpc::EngineCommand,
+};
 use sc_executor::NativeElseWasmExecutor;
 use sc_network::NetworkService;
 use sc_service::{
@@ -31,6 +36,7 @@ use sc_service::{
 };
 use sc_telemetry::{Telemetry, TelemetryHandle, TelemetryWorker, TelemetryWorkerHandle};
 use sp_api::ConstructRuntimeApi;
+use sp_blockchain::HeaderBackend;
 use sp_keystore::SyncCryptoStorePtr;
 use sp_runtime::traits::BlakeTwo256;
 use std::{
@@ -266,6 +272,8 @@ where
 		+ sp_block_builder::BlockBuilder<Block>
 		+ substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>
 		+ pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>
+		+ primitives::OracleApi<Block, AccountId>
+		+ primitives::ChainBridgeApi<Block, AccountId, BlockNumber>
 		+ fp_rpc::EthereumRuntimeRPCApi<Block>
 		+ fp_rpc::ConvertTransactionRuntimeApi<Block>
 		+ cumulus_primitives_core::CollectCollationInfo<Block>,
@@ -387,6 +395,7 @@ where
 				fee_history_cache: fee_history_cache.clone(),
 				overrides: overrides.clone(),
 				block_data_cache: block_data_cache.clone(),
+				command_sink: None,
 			};
 
 			Ok(create_full(deps, subscription_task_executor))
@@ -642,3 +651,222 @@ pub async fn start_parachain_node(
 	)
 	.await
 }
+
+/// Build the manual seal import queue for the instant seal dev node.
+pub fn instant_seal_build_import_queue(
+	client: Arc<TFullClient<Block, RuntimeApi, NativeElseWasmExecutor<StandardRuntimeExecutor>>>,
+	_block_import: FrontierBlockImport<
+		Block,
+		Arc<TFullClient<Block, RuntimeApi, NativeElseWasmExecutor<StandardRuntimeExecutor>>>,
+		TFullClient<Block, RuntimeApi, NativeElseWasmExecutor<StandardRuntimeExecutor>>,
+	>,
+	config: &Configuration,
+	_telemetry: Option<TelemetryHandle>,
+	task_manager: &TaskManager,
+) -> Result<
+	sc_consensus::DefaultImportQueue<
+		Block,
+		TFullClient<Block, RuntimeApi, NativeElseWasmExecutor<StandardRuntimeExecutor>>,
+	>,
+	sc_service::Error,
+> {
+	Ok(manual_seal::import_queue(
+		Box::new(client),
+		&task_manager.spawn_essential_handle(),
+		config.prometheus_registry(),
+	))
+}
+
+/// Start a dev node which authors a block whenever a transaction enters the pool or
+/// `engine_createBlock` is called.
+///
+/// There is no relay chain: the parachain inherent is mocked and the Aura slot follows the
+/// block timestamps.
+pub async fn start_instant_seal_node(
+	config: Configuration,
+) -> sc_service::error::Result<TaskManager> {
+	let params = new_partial::<RuntimeApi, StandardRuntimeExecutor, _>(
+		&config,
+		instant_seal_build_import_queue,
+	)?;
+	let (mut telemetry, _, filter_pool, frontier_backend, fee_history_cache) = params.other;
+	let mut task_manager = params.task_manager;
+	let client = params.client.clone();
+	let backend = params.backend.clone();
+	let select_chain = sc_consensus::LongestChain::new(backend.clone());
+	let transaction_pool = params.transaction_pool.clone();
+	let is_authority = config.role.is_authority();
+	let prometheus_registry = config.prometheus_registry().cloned();
+
+	let (network, system_rpc_tx, start_network) =
+		sc_service::build_network(sc_service::BuildNetworkParams {
+			config: &config,
+			client: client.clone(),
+			transaction_pool: transaction_pool.clone(),
+			spawn_handle: task_manager.spawn_handle(),
+			import_queue: params.import_queue,
+			block_announce_validator_builder: None,
+			warp_sync: None,
+		})?;
+
+	// Channel for the rpc handler to communicate with the authorship task.
+	let (command_sink, commands_stream) = mpsc::channel(1000);
+
+	let overrides = crate::rpc::overrides_handle(client.clone());
+	let fee_history_limit: u64 = 2048;
+	let max_past_logs: u32 = 10_000;
+	let block_data_cache = Arc::new(fc_rpc::EthBlockDataCache::new(
+		task_manager.spawn_handle(),
+		overrides.clone(),
+		50,
+		50,
+	));
+
+	let rpc_extensions_builder = {
+		let client = client.clone();
+		let transaction_pool = transaction_pool.clone();
+		let backend = frontier_backend.clone();
+		let network = network.clone();
+		let overrides = overrides.clone();
+		let fee_history_cache = fee_history_cache.clone();
+		let filter_pool = filter_pool.clone();
+
+		Box::new(move |deny_unsafe, subscription_task_executor| {
+			let deps = FullDeps {
+				client: client.clone(),
+				pool: transaction_pool.clone(),
+				graph: transaction_pool.pool().clone(),
+				network: network.clone(),
+				is_authority,
+				deny_unsafe,
+				frontier_backend: backend.clone(),
+				max_past_logs,
+				filter_pool: filter_pool.clone(),
+				fee_history_limit,
+				fee_history_cache: fee_history_cache.clone(),
+				overrides: overrides.clone(),
+				block_data_cache: block_data_cache.clone(),
+				command_sink: Some(command_sink.clone()),
+			};
+
+			Ok(create_full(deps, subscription_task_executor))
+		})
+	};
+
+	sc_service::spawn_tasks(sc_service::SpawnTasksParams {
+		rpc_extensions_builder,
+		client: client.clone(),
+		transaction_pool: transaction_pool.clone(),
+		task_manager: &mut task_manager,
+		config,
+		keystore: params.keystore_container.sync_keystore(),
+		backend: backend.clone(),
+		network,
+		system_rpc_tx,
+		telemetry: telemetry.as_mut(),
+	})?;
+
+	task_manager.spawn_essential_handle().spawn(
+		"frontier-mapping-sync-worker",
+		Some("frontier"),
+		MappingSyncWorker::new(
+			client.import_notification_stream(),
+			Duration::new(6, 0),
+			client.clone(),
+			backend,
+			frontier_backend.clone(),
+			3,
+			0,
+			SyncStrategy::Normal,
+		)
+		.for_each(|()| futures::future::ready(())),
+	);
+
+	task_manager.spawn_essential_handle().spawn(
+		"frontier-fee-history",
+		None,
+		EthTask::fee_history_task(
+			Arc::clone(&client),
+			Arc::clone(&overrides),
+			fee_history_cache,
+			fee_history_limit,
+		),
+	);
+
+	if let Some(filter_pool) = filter_pool {
+		// Each filter is allowed to stay in the pool for 100 blocks.
+		const FILTER_RETAIN_THRESHOLD: u64 = 100;
+		task_manager.spawn_essential_handle().spawn(
+			"frontier-filter-pool",
+			None,
+			EthTask::filter_pool_task(Arc::clone(&client), filter_pool, FILTER_RETAIN_THRESHOLD),
+		);
+	}
+
+	task_manager.spawn_essential_handle().spawn(
+		"frontier-schema-cache-task",
+		Some("frontier"),
+		fc_rpc::EthTask::ethereum_schema_cache_task(client.clone(), frontier_backend),
+	);
+
+	if is_authority {
+		let env = sc_basic_authorship::ProposerFactory::new(
+			task_manager.spawn_handle(),
+			client.clone(),
+			transaction_pool.clone(),
+			prometheus_registry.as_ref(),
+			telemetry.as_ref().map(|x| x.handle()),
+		);
+
+		// Seal a block for every imported transaction, as well as on request.
+		let pool_stream =
+			transaction_pool.pool().validated_pool().import_notification_stream().map(|_| {
+				EngineCommand::SealNewBlock {
+					create_empty: false,
+					finalize: false,
+					parent_hash: None,
+					sender: None,
+				}
+			});
+		let commands_stream = futures::stream::select(commands_stream, pool_stream);
+
+		let client_for_inherents = client.clone();
+		let authorship_future = manual_seal::run_manual_seal(manual_seal::ManualSealParams {
+			block_import: client.clone(),
+			env,
+			client: client.clone(),
+			pool: transaction_pool,
+			commands_stream,
+			select_chain,
+			consensus_data_provider: Some(Box::new(AuraConsensusDataProvider::new(client.clone()))),
+			create_inherent_data_providers: move |block, ()| {
+				let current_para_block =
+					client_for_inherents.number(block).ok().flatten().unwrap_or_default();
+				let client = client_for_inherents.clone();
+				async move {
+					let timestamp = SlotTimestampProvider::new_aura(client)?;
+					let slot = sp_consensus_aura::inherents::InherentDataProvider::new(
+						timestamp.slot().into(),
+					);
+					let parachain_inherent =
+						cumulus_primitives_parachain_inherent::MockValidationDataInherentDataProvider {
+							current_para_block,
+							relay_offset: 1000,
+							relay_blocks_per_para_block: 2,
+						};
+					Ok((timestamp, slot, parachain_inherent))
+				}
+			},
+		});
+		// we spawn the future on a background thread managed by service.
+		task_manager.spawn_essential_handle().spawn_blocking(
+			"instant-seal",
+			None,
+			authorship_future,
+		);
+	}
+
+	start_network.start_network();
+
+	Ok(task_manager)
+}
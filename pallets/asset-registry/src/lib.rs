@@ -17,7 +17,7 @@ pub use pallet::*;
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
-	use frame_support::pallet_prelude::*;
+	use frame_support::{pallet_prelude::*, traits::StorageVersion};
 
 	#[pallet::config]
 	pub trait Config: frame_system::Config {
@@ -30,13 +30,33 @@ pub mod pallet {
 			+ MaybeSerializeDeserialize;
 	}
 
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
 	#[pallet::without_storage_info]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	#[pallet::hooks]
-	impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {}
+	impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() < 1 {
+				// Assets registered so far only have their id keyed by name.
+				let mut count = 0;
+				for (name, asset_id) in AssetIds::<T>::iter() {
+					if let Some(asset_id) = asset_id {
+						AssetNames::<T>::insert(asset_id, name);
+						count += 1;
+					}
+				}
+				STORAGE_VERSION.put::<Pallet<T>>();
+				T::DbWeight::get().reads_writes(1 + count, 1 + count)
+			} else {
+				T::DbWeight::get().reads(1)
+			}
+		}
+	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {}
@@ -59,6 +79,11 @@ pub mod pallet {
 	pub type AssetIds<T: Config> =
 		StorageMap<_, Twox64Concat, Vec<u8>, Option<T::AssetId>, ValueQuery>;
 
+	#[pallet::storage]
+	#[pallet::getter(fn asset_name)]
+	/// Name each asset is registered under, the reverse of `AssetIds`
+	pub type AssetNames<T: Config> = StorageMap<_, Twox64Concat, T::AssetId, Vec<u8>>;
+
 	#[pallet::genesis_config]
 	pub struct GenesisConfig<T: Config> {
 		pub core_asset_id: T::AssetId,
@@ -84,6 +109,7 @@ pub mod pallet {
 			NextAssetId::<T>::put(self.next_asset_id);
 			self.asset_ids.iter().for_each(|(name, asset_id)| {
 				AssetIds::<T>::insert(name, Some(asset_id));
+				AssetNames::<T>::insert(asset_id, name);
 			})
 		}
 	}
@@ -97,7 +123,8 @@ impl<T: Config> Pallet<T> {
 				let asset_id = Self::next_asset_id();
 				let next_id = asset_id.checked_add(&One::one()).ok_or(Error::<T>::NoIdAvailable)?;
 				<NextAssetId<T>>::put(next_id);
-				<AssetIds<T>>::insert(name, Some(asset_id));
+				<AssetIds<T>>::insert(&name, Some(asset_id));
+				<AssetNames<T>>::insert(asset_id, name);
 				Ok(asset_id)
 			},
 		}
@@ -17,5 +17,6 @@ fn create_asset() {
 		assert_eq!(AssetRegistryModule::next_asset_id(), current_asset_id);
 		assert_eq!(AssetRegistryModule::asset_ids(b"DOT".to_vec()).unwrap(), 1u32);
 		assert_eq!(AssetRegistryModule::asset_ids(b"AAA".to_vec()).is_none(), true);
+		assert_eq!(AssetRegistryModule::asset_name(dot_asset_id), Some(b"DOT".to_vec()));
 	});
 }
@@ -0,0 +1,212 @@
+// Ensure we're `no_std` when compiling for Wasm.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Bridge Governance Pallet
+//!
+//! Executes governance decisions of a bridged chain. The remote chain submits calls through the
+//! chainbridge under the resource set with `set_governance_resource`, and once the relayers reach
+//! their quorum the bridge dispatches `execute`.
+//!
+//! Calls are executed with the `RemoteGovernance` origin, which a pallet only accepts where its
+//! governance origin includes `EnsureRemoteGovernance`. On top of that only calls whitelisted by
+//! local governance with `whitelist_call` are accepted. Whitelisting calls which dispatch other
+//! calls (e.g. batches) would lift that restriction.
+
+use frame_support::{
+	dispatch::{Dispatchable, GetDispatchInfo, PostDispatchInfo},
+	pallet_prelude::*,
+	traits::{CallMetadata, GetCallMetadata},
+};
+use frame_system::pallet_prelude::*;
+use primitives::bridge::ResourceId;
+use sp_std::{boxed::Box, vec::Vec};
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+pub use pallet::*;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::without_storage_info]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// Runtime origin, which the calls of the remote governance are dispatched with
+		type Origin: From<RawOrigin>;
+		/// Call executed on behalf of the remote governance
+		type Call: Parameter
+			+ Dispatchable<Origin = <Self as Config>::Origin, PostInfo = PostDispatchInfo>
+			+ GetDispatchInfo
+			+ GetCallMetadata;
+		/// Origin of the calls relayed by the bridge
+		type BridgeOrigin: EnsureOrigin<<Self as frame_system::Config>::Origin>;
+		/// Origin allowed to configure the remote governance and the call whitelist
+		type GovernanceOrigin: EnsureOrigin<<Self as frame_system::Config>::Origin>;
+	}
+
+	/// Origin of the calls executed for the remote governance
+	#[derive(PartialEq, Eq, Clone, RuntimeDebug, Encode, Decode, TypeInfo, MaxEncodedLen)]
+	pub enum RawOrigin {
+		/// Call of the remote governance relayed under the governance resource
+		RemoteGovernance,
+	}
+
+	#[pallet::origin]
+	pub type Origin = RawOrigin;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Resource of the remote governance has changed (resource_id)
+		GovernanceResourceSet(Option<ResourceId>),
+		/// Call can be executed by the remote governance (pallet_name, function_name)
+		CallWhitelisted(Vec<u8>, Vec<u8>),
+		/// Call can no longer be executed by the remote governance (pallet_name, function_name)
+		CallRemovedFromWhitelist(Vec<u8>, Vec<u8>),
+		/// Call of the remote governance has been executed (resource_id, result)
+		Executed(ResourceId, DispatchResult),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Resource is not the one of the remote governance
+		UnknownResource,
+		/// Call is not whitelisted for the remote governance
+		CallNotWhitelisted,
+		/// Call is already whitelisted
+		AlreadyWhitelisted,
+		/// Call is not whitelisted
+		NotWhitelisted,
+	}
+
+	#[pallet::storage]
+	#[pallet::getter(fn governance_resource)]
+	/// Bridge resource under which the remote governance submits calls
+	pub type GovernanceResource<T> = StorageValue<_, ResourceId>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn whitelisted_calls)]
+	/// Calls the remote governance can execute, keyed by pallet name and call name
+	pub type WhitelistedCalls<T> =
+		StorageDoubleMap<_, Blake2_128Concat, Vec<u8>, Blake2_128Concat, Vec<u8>, bool, ValueQuery>;
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Sets the bridge resource of the remote governance. Without it no call is executed.
+		///
+		/// # <weight>
+		/// - O(1) write
+		/// # </weight>
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_governance_resource(
+			origin: OriginFor<T>,
+			resource_id: Option<ResourceId>,
+		) -> DispatchResult {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+
+			GovernanceResource::<T>::set(resource_id);
+			Self::deposit_event(Event::GovernanceResourceSet(resource_id));
+			Ok(())
+		}
+
+		/// Allows the remote governance to execute the call `function_name` of the pallet
+		/// `pallet_name`.
+		///
+		/// # <weight>
+		/// - O(1) lookup and insert
+		/// # </weight>
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn whitelist_call(
+			origin: OriginFor<T>,
+			pallet_name: Vec<u8>,
+			function_name: Vec<u8>,
+		) -> DispatchResult {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			ensure!(
+				!Self::whitelisted_calls(&pallet_name, &function_name),
+				Error::<T>::AlreadyWhitelisted
+			);
+
+			WhitelistedCalls::<T>::insert(&pallet_name, &function_name, true);
+			Self::deposit_event(Event::CallWhitelisted(pallet_name, function_name));
+			Ok(())
+		}
+
+		/// Removes a call whitelisted with `whitelist_call`.
+		///
+		/// # <weight>
+		/// - O(1) lookup and removal
+		/// # </weight>
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn remove_whitelisted_call(
+			origin: OriginFor<T>,
+			pallet_name: Vec<u8>,
+			function_name: Vec<u8>,
+		) -> DispatchResult {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			ensure!(
+				WhitelistedCalls::<T>::take(&pallet_name, &function_name),
+				Error::<T>::NotWhitelisted
+			);
+
+			Self::deposit_event(Event::CallRemovedFromWhitelist(pallet_name, function_name));
+			Ok(())
+		}
+
+		/// Executes a call of the remote governance with the `RemoteGovernance` origin.
+		///
+		/// # <weight>
+		/// - weight of the call
+		/// # </weight>
+		#[pallet::weight({
+			let info = call.get_dispatch_info();
+			(info.weight.saturating_add(T::DbWeight::get().reads(2)), info.class)
+		})]
+		pub fn execute(
+			origin: OriginFor<T>,
+			resource_id: ResourceId,
+			call: Box<<T as Config>::Call>,
+		) -> DispatchResult {
+			T::BridgeOrigin::ensure_origin(origin)?;
+			ensure!(Self::governance_resource() == Some(resource_id), Error::<T>::UnknownResource);
+			ensure!(Self::is_whitelisted(&call), Error::<T>::CallNotWhitelisted);
+
+			let result = call
+				.dispatch(RawOrigin::RemoteGovernance.into())
+				.map(|_| ())
+				.map_err(|e| e.error);
+			Self::deposit_event(Event::Executed(resource_id, result));
+			Ok(())
+		}
+	}
+}
+
+/// Accepts the calls executed for the remote governance, for the governance origins of the
+/// pallets it may configure.
+pub struct EnsureRemoteGovernance;
+impl<O: Into<Result<RawOrigin, O>> + From<RawOrigin>> EnsureOrigin<O> for EnsureRemoteGovernance {
+	type Success = ();
+	fn try_origin(o: O) -> Result<Self::Success, O> {
+		o.into().map(|o| match o {
+			RawOrigin::RemoteGovernance => (),
+		})
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Whether the remote governance can execute `call`.
+	pub fn is_whitelisted(call: &<T as Config>::Call) -> bool {
+		let CallMetadata { pallet_name, function_name } = call.get_call_metadata();
+		Self::whitelisted_calls(pallet_name.as_bytes().to_vec(), function_name.as_bytes().to_vec())
+	}
+}
@@ -0,0 +1,86 @@
+#![cfg(test)]
+
+use frame_support::{ord_parameter_types, parameter_types, traits::EnsureOneOf};
+use frame_system as system;
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+
+use crate::{self as bridge_governance, Config, EnsureRemoteGovernance};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+	pub enum Test where
+	 Block = Block,
+	 NodeBlock = Block,
+	 UncheckedExtrinsic = UncheckedExtrinsic,
+	 {
+		 System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		 BridgeGovernance: bridge_governance::{Pallet, Call, Storage, Event<T>, Origin},
+	 }
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 63;
+}
+
+impl system::Config for Test {
+	type OnSetCode = ();
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type DbWeight = ();
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+pub const BRIDGE: u64 = 1;
+pub const ALICE: u64 = 2;
+pub const GOVERNANCE_RESOURCE: [u8; 32] = [1; 32];
+
+ord_parameter_types! {
+	pub const Bridge: u64 = BRIDGE;
+}
+
+// The remote governance may move itself to another resource
+impl Config for Test {
+	type Event = Event;
+	type Origin = Origin;
+	type Call = Call;
+	type BridgeOrigin = frame_system::EnsureSignedBy<Bridge, u64>;
+	type GovernanceOrigin =
+		EnsureOneOf<frame_system::EnsureRoot<Self::AccountId>, EnsureRemoteGovernance>;
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+pub fn last_event() -> Event {
+	system::Pallet::<Test>::events().pop().expect("Event expected").event
+}
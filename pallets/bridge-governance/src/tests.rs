@@ -0,0 +1,153 @@
+use crate::{mock::*, Error, Event as GovernanceEvent};
+use frame_support::{assert_noop, assert_ok};
+use sp_runtime::DispatchError;
+
+fn set_heap_pages(pages: u64) -> Box<Call> {
+	Box::new(Call::System(frame_system::Call::set_heap_pages { pages }))
+}
+
+fn set_governance_resource(resource_id: [u8; 32]) -> Box<Call> {
+	Box::new(Call::BridgeGovernance(crate::Call::set_governance_resource {
+		resource_id: Some(resource_id),
+	}))
+}
+
+fn remark() -> Box<Call> {
+	Box::new(Call::System(frame_system::Call::remark { remark: vec![] }))
+}
+
+fn heap_pages() -> Option<Vec<u8>> {
+	sp_io::storage::get(sp_core::storage::well_known_keys::HEAP_PAGES)
+}
+
+#[test]
+fn configure_remote_governance() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			BridgeGovernance::set_governance_resource(
+				Origin::signed(ALICE),
+				Some(GOVERNANCE_RESOURCE)
+			),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(BridgeGovernance::set_governance_resource(
+			Origin::root(),
+			Some(GOVERNANCE_RESOURCE)
+		));
+		assert_eq!(BridgeGovernance::governance_resource(), Some(GOVERNANCE_RESOURCE));
+
+		assert_ok!(BridgeGovernance::whitelist_call(
+			Origin::root(),
+			b"System".to_vec(),
+			b"set_heap_pages".to_vec()
+		));
+		assert_noop!(
+			BridgeGovernance::whitelist_call(
+				Origin::root(),
+				b"System".to_vec(),
+				b"set_heap_pages".to_vec()
+			),
+			Error::<Test>::AlreadyWhitelisted
+		);
+		assert!(BridgeGovernance::is_whitelisted(&set_heap_pages(1)));
+		assert!(!BridgeGovernance::is_whitelisted(&remark()));
+
+		assert_ok!(BridgeGovernance::remove_whitelisted_call(
+			Origin::root(),
+			b"System".to_vec(),
+			b"set_heap_pages".to_vec()
+		));
+		assert_eq!(
+			last_event(),
+			Event::BridgeGovernance(GovernanceEvent::CallRemovedFromWhitelist(
+				b"System".to_vec(),
+				b"set_heap_pages".to_vec()
+			))
+		);
+		assert_noop!(
+			BridgeGovernance::remove_whitelisted_call(
+				Origin::root(),
+				b"System".to_vec(),
+				b"set_heap_pages".to_vec()
+			),
+			Error::<Test>::NotWhitelisted
+		);
+	})
+}
+
+#[test]
+fn execute_whitelisted_call() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			BridgeGovernance::execute(
+				Origin::signed(BRIDGE),
+				GOVERNANCE_RESOURCE,
+				set_heap_pages(1)
+			),
+			Error::<Test>::UnknownResource
+		);
+		assert_ok!(BridgeGovernance::set_governance_resource(
+			Origin::root(),
+			Some(GOVERNANCE_RESOURCE)
+		));
+		assert_noop!(
+			BridgeGovernance::execute(
+				Origin::signed(BRIDGE),
+				GOVERNANCE_RESOURCE,
+				set_heap_pages(1)
+			),
+			Error::<Test>::CallNotWhitelisted
+		);
+		assert_ok!(BridgeGovernance::whitelist_call(
+			Origin::root(),
+			b"System".to_vec(),
+			b"set_heap_pages".to_vec()
+		));
+
+		// Only the bridge can submit calls, under the governance resource
+		assert_noop!(
+			BridgeGovernance::execute(
+				Origin::signed(ALICE),
+				GOVERNANCE_RESOURCE,
+				set_heap_pages(1)
+			),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			BridgeGovernance::execute(Origin::signed(BRIDGE), [2; 32], set_heap_pages(1)),
+			Error::<Test>::UnknownResource
+		);
+
+		// Calls are not executed as root
+		assert_ok!(BridgeGovernance::execute(
+			Origin::signed(BRIDGE),
+			GOVERNANCE_RESOURCE,
+			set_heap_pages(64)
+		));
+		assert_eq!(
+			last_event(),
+			Event::BridgeGovernance(GovernanceEvent::Executed(
+				GOVERNANCE_RESOURCE,
+				Err(DispatchError::BadOrigin)
+			))
+		);
+		assert_eq!(heap_pages(), None);
+
+		// Pallets accepting the remote governance origin execute the call
+		assert_ok!(BridgeGovernance::whitelist_call(
+			Origin::root(),
+			b"BridgeGovernance".to_vec(),
+			b"set_governance_resource".to_vec()
+		));
+		assert_ok!(BridgeGovernance::execute(
+			Origin::signed(BRIDGE),
+			GOVERNANCE_RESOURCE,
+			set_governance_resource([2; 32])
+		));
+		assert_eq!(
+			last_event(),
+			Event::BridgeGovernance(GovernanceEvent::Executed(GOVERNANCE_RESOURCE, Ok(())))
+		);
+		assert_eq!(BridgeGovernance::governance_resource(), Some([2; 32]));
+	})
+}
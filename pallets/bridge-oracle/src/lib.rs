@@ -0,0 +1,161 @@
+// Ensure we're `no_std` when compiling for Wasm.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Bridge Oracle Pallet
+//!
+//! Exports the final median prices of the oracle rounds to other chains over chainbridge, so that
+//! contracts there can consume the prices of the Standard collateral as attested by the relayers.
+//!
+//! Governance enables the export of an asset to a chain with `enable_export`, under the resource
+//! the handler of the other chain listens to. Each time the oracle closes a round, the median of
+//! every exported asset reported in it is posted as a generic transfer to each of its chains. The
+//! attestation is ABI encoded as `(uint256 asset, uint256 round, uint256 price)`, with the price
+//! in 18 decimals.
+
+use frame_support::{traits::Get, weights::Weight, PalletId};
+use pallet_standard_chainbridge::{self as chainbridge, BridgeChainId, ResourceId, U256};
+use primitives::{traits::OnRoundClosed, AssetId, RoundIndex};
+use sp_runtime::{traits::AccountIdConversion, DispatchError, FixedPointNumber, FixedU128};
+use sp_std::vec::Vec;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+pub use pallet::*;
+
+/// ABI encoding of the attestation of `price` for `asset` in `round`.
+pub fn attestation(asset: AssetId, round: RoundIndex, price: FixedU128) -> Vec<u8> {
+	let mut payload = Vec::with_capacity(96);
+	for word in [U256::from(asset), U256::from(round), U256::from(price.into_inner())] {
+		let mut bytes = [0u8; 32];
+		word.to_big_endian(&mut bytes);
+		payload.extend_from_slice(&bytes);
+	}
+	payload
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + chainbridge::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// Origin enabling and disabling the exports
+		type AdminOrigin: EnsureOrigin<Self::Origin>;
+		/// Account the attestations are sent from
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Prices of an asset are exported to a chain (asset, dest_id, resource_id)
+		ExportEnabled(AssetId, BridgeChainId, ResourceId),
+		/// Prices of an asset are no longer exported to a chain (asset, dest_id)
+		ExportDisabled(AssetId, BridgeChainId),
+		/// Final price of a round is sent to a chain (asset, round, dest_id, price)
+		PriceExported(AssetId, RoundIndex, BridgeChainId, FixedU128),
+		/// Final price of a round could not be sent to a chain (asset, round, dest_id, error)
+		PriceExportFailed(AssetId, RoundIndex, BridgeChainId, DispatchError),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Chain is not whitelisted by the bridge
+		ChainNotWhitelisted,
+		/// Prices of the asset are not exported to the chain
+		ExportNotFound,
+	}
+
+	#[pallet::storage]
+	#[pallet::getter(fn export)]
+	/// Resources the prices of each asset are exported under, per chain
+	pub type Exports<T> =
+		StorageDoubleMap<_, Twox64Concat, AssetId, Blake2_128Concat, BridgeChainId, ResourceId>;
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Exports the final prices of `asset` to `dest_id` under `resource_id`, replacing the
+		/// resource of an enabled export.
+		///
+		/// # <weight>
+		/// - O(1) lookup and insert
+		/// # </weight>
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn enable_export(
+			origin: OriginFor<T>,
+			asset: AssetId,
+			dest_id: BridgeChainId,
+			resource_id: ResourceId,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(
+				chainbridge::Pallet::<T>::chain_whitelisted(dest_id),
+				Error::<T>::ChainNotWhitelisted
+			);
+
+			Exports::<T>::insert(asset, dest_id, resource_id);
+			Self::deposit_event(Event::ExportEnabled(asset, dest_id, resource_id));
+			Ok(())
+		}
+
+		/// Stops exporting the prices of `asset` to `dest_id`.
+		///
+		/// # <weight>
+		/// - O(1) removal
+		/// # </weight>
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn disable_export(
+			origin: OriginFor<T>,
+			asset: AssetId,
+			dest_id: BridgeChainId,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(Exports::<T>::take(asset, dest_id).is_some(), Error::<T>::ExportNotFound);
+
+			Self::deposit_event(Event::ExportDisabled(asset, dest_id));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Account the attestations are sent from
+	pub fn account_id() -> T::AccountId {
+		T::PalletId::get().into_account()
+	}
+}
+
+impl<T: Config> OnRoundClosed for Pallet<T> {
+	fn on_round_closed(round: RoundIndex, prices: &[(AssetId, FixedU128)]) -> Weight {
+		let mut count: Weight = 0;
+		for (asset, price) in prices {
+			for (dest_id, resource_id) in Exports::<T>::iter_prefix(asset) {
+				count += 1;
+				let sent = chainbridge::Pallet::<T>::transfer_generic(
+					Self::account_id(),
+					dest_id,
+					resource_id,
+					attestation(*asset, round, *price),
+				);
+				Self::deposit_event(match sent {
+					Ok(()) => Event::PriceExported(*asset, round, dest_id, *price),
+					Err(error) => Event::PriceExportFailed(*asset, round, dest_id, error),
+				});
+			}
+		}
+		// Reading the exports, and the bridge state and nonce of each chain
+		T::DbWeight::get().reads_writes(prices.len() as Weight + 3 * count, count)
+	}
+}
@@ -0,0 +1,125 @@
+#![cfg(test)]
+
+use frame_support::{
+	assert_ok, parameter_types,
+	traits::{ConstU32, Everything},
+	PalletId,
+};
+use frame_system as system;
+use pallet_standard_chainbridge::{self as chainbridge, BridgeChainId};
+use primitives::AssetId;
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+
+use crate::{self as bridge_oracle, Config};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+pub const MTR: AssetId = 2;
+pub const DOT: AssetId = 3;
+pub const DEST_ID: BridgeChainId = 1;
+/// Whitelisted chain without any export
+pub const OTHER_DEST_ID: BridgeChainId = 2;
+
+frame_support::construct_runtime!(
+	pub enum Test where
+	 Block = Block,
+	 NodeBlock = Block,
+	 UncheckedExtrinsic = UncheckedExtrinsic,
+	 {
+		 System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		 ChainBridge: chainbridge::{Pallet, Call, Storage, Event<T>},
+		 BridgeOracle: bridge_oracle::{Pallet, Call, Storage, Event<T>},
+	 }
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 63;
+}
+
+impl system::Config for Test {
+	type OnSetCode = ();
+	type BaseCallFilter = Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type DbWeight = ();
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type MaxConsumers = ConstU32<16>;
+}
+
+parameter_types! {
+	pub const TestBridgeChainId: u8 = 5;
+	pub const ProposalLifetime: u64 = 50;
+	pub const TransferLifetime: u64 = 100;
+	pub const NonceWindow: u64 = 128;
+	pub const MaxRelayerSnapshots: u32 = 3;
+	pub const MaxVotesPerBlock: u32 = 5;
+	pub const MaxQueuedProposals: u32 = 3;
+	pub const DeliveryQueueTimeout: u64 = 10;
+	pub DefaultAllowedProposals: Vec<(Vec<u8>, Vec<u8>)> = vec![];
+}
+
+impl chainbridge::Config for Test {
+	type Event = Event;
+	type RelayerAdminOrigin = frame_system::EnsureRoot<Self::AccountId>;
+	type ResourceAdminOrigin = frame_system::EnsureRoot<Self::AccountId>;
+	type PauseOrigin = frame_system::EnsureRoot<Self::AccountId>;
+	type Proposal = Call;
+	type BridgeChainId = TestBridgeChainId;
+	type ProposalLifetime = ProposalLifetime;
+	type TransferCallback = ();
+	type TransferLifetime = TransferLifetime;
+	type NonceWindow = NonceWindow;
+	type MaxRelayerSnapshots = MaxRelayerSnapshots;
+	type MaxVotesPerBlock = MaxVotesPerBlock;
+	type DefaultAllowedProposals = DefaultAllowedProposals;
+	type MaxQueuedProposals = MaxQueuedProposals;
+	type DeliveryQueueTimeout = DeliveryQueueTimeout;
+}
+
+parameter_types! {
+	pub const BridgeOraclePalletId: PalletId = PalletId(*b"stnd/bgo");
+}
+
+impl Config for Test {
+	type Event = Event;
+	type AdminOrigin = frame_system::EnsureRoot<u64>;
+	type PalletId = BridgeOraclePalletId;
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ChainBridge::whitelist_chain(Origin::root(), DEST_ID));
+		assert_ok!(ChainBridge::whitelist_chain(Origin::root(), OTHER_DEST_ID));
+	});
+	ext
+}
+
+pub fn last_event() -> Event {
+	system::Pallet::<Test>::events().pop().expect("Event expected").event
+}
@@ -0,0 +1,101 @@
+use crate::{attestation, mock::*, Error, Event as BridgeOracleEvent};
+use frame_support::{assert_noop, assert_ok, error::BadOrigin};
+use pallet_standard_chainbridge::{Error as BridgeError, Event as BridgeEvent};
+use primitives::traits::OnRoundClosed;
+use sp_runtime::{FixedPointNumber, FixedU128};
+
+const MTR_RESOURCE: [u8; 32] = [2; 32];
+
+fn price(value: u128) -> FixedU128 {
+	FixedU128::saturating_from_integer(value)
+}
+
+#[test]
+fn attestation_is_abi_encoded() {
+	let payload = attestation(MTR, 7, FixedU128::saturating_from_rational(3, 2));
+	assert_eq!(payload.len(), 96);
+	assert!(payload[..31].iter().all(|byte| *byte == 0));
+	assert_eq!(payload[31], MTR as u8);
+	assert!(payload[32..63].iter().all(|byte| *byte == 0));
+	assert_eq!(payload[63], 7);
+	// 1.5 in 18 decimals
+	let mut price = [0u8; 16];
+	price.copy_from_slice(&payload[80..]);
+	assert_eq!(u128::from_be_bytes(price), 1_500_000_000_000_000_000);
+	assert!(payload[64..80].iter().all(|byte| *byte == 0));
+}
+
+#[test]
+fn only_admin_enables_exports_to_whitelisted_chains() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			BridgeOracle::enable_export(Origin::signed(1), MTR, DEST_ID, MTR_RESOURCE),
+			BadOrigin
+		);
+		assert_noop!(
+			BridgeOracle::enable_export(Origin::root(), MTR, 9, MTR_RESOURCE),
+			Error::<Test>::ChainNotWhitelisted
+		);
+
+		assert_ok!(BridgeOracle::enable_export(Origin::root(), MTR, DEST_ID, MTR_RESOURCE));
+		assert_eq!(
+			last_event(),
+			Event::BridgeOracle(BridgeOracleEvent::ExportEnabled(MTR, DEST_ID, MTR_RESOURCE))
+		);
+		assert_eq!(BridgeOracle::export(MTR, DEST_ID), Some(MTR_RESOURCE));
+
+		assert_noop!(
+			BridgeOracle::disable_export(Origin::root(), MTR, OTHER_DEST_ID),
+			Error::<Test>::ExportNotFound
+		);
+		assert_ok!(BridgeOracle::disable_export(Origin::root(), MTR, DEST_ID));
+		assert_eq!(
+			last_event(),
+			Event::BridgeOracle(BridgeOracleEvent::ExportDisabled(MTR, DEST_ID))
+		);
+		assert_eq!(BridgeOracle::export(MTR, DEST_ID), None);
+	})
+}
+
+#[test]
+fn closed_rounds_export_the_prices_of_exported_assets() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(BridgeOracle::enable_export(Origin::root(), MTR, DEST_ID, MTR_RESOURCE));
+
+		// DOT is not exported
+		BridgeOracle::on_round_closed(3, &[(MTR, price(1)), (DOT, price(5))]);
+		System::assert_has_event(Event::ChainBridge(BridgeEvent::GenericTransfer(
+			DEST_ID,
+			1,
+			MTR_RESOURCE,
+			attestation(MTR, 3, price(1)),
+			BridgeOracle::account_id(),
+		)));
+		assert_eq!(
+			last_event(),
+			Event::BridgeOracle(BridgeOracleEvent::PriceExported(MTR, 3, DEST_ID, price(1)))
+		);
+		assert_eq!(ChainBridge::chains(DEST_ID), Some(1));
+		assert_eq!(ChainBridge::chains(OTHER_DEST_ID), Some(0));
+	})
+}
+
+#[test]
+fn export_fails_while_the_bridge_is_paused() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(BridgeOracle::enable_export(Origin::root(), MTR, DEST_ID, MTR_RESOURCE));
+		assert_ok!(ChainBridge::pause(Origin::root()));
+
+		BridgeOracle::on_round_closed(3, &[(MTR, price(1))]);
+		assert_eq!(
+			last_event(),
+			Event::BridgeOracle(BridgeOracleEvent::PriceExportFailed(
+				MTR,
+				3,
+				DEST_ID,
+				BridgeError::<Test>::BridgeIsPaused.into()
+			))
+		);
+		assert_eq!(ChainBridge::chains(DEST_ID), Some(0));
+	})
+}
@@ -0,0 +1,654 @@
+// Ensure we're `no_std` when compiling for Wasm.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Bridge Transfer Pallet
+//!
+//! Moves fungible assets to and from other chains over chainbridge.
+//!
+//! Governance registers the asset behind a bridge resource with `register_asset`, together with
+//! the decimals of the asset here and on the other chains. `transfer_out` reserves the amount in
+//! the account of the sender and hands the transfer to chainbridge. The amount is only taken once
+//! the relayers report the transfer as executed through `BridgeCallback`, and released if they
+//! report it as failed. A timed out transfer may still have been executed on the other chain, so
+//! it stays reserved until governance settles it with `resolve_timed_out`. Relayers bring
+//! transfers from other chains in with a `transfer_in` proposal, paid out of the pallet account.
+//!
+//! Owners of an asset may propose to bridge it with `propose_asset`, under the resource derived
+//! from the chain id and the asset id, which governance approves or rejects. Approved and
+//! registered assets are also registered as chainbridge resources.
+//!
+//! Each asset is bridged in one of two custody modes. Assets native to this chain are locked in
+//! the pallet account on the way out and released on the way back. Assets native to another
+//! chain are minted on the way in and burned on the way out. The amount locked or minted behind
+//! each resource is tracked in `TotalLocked` and `TotalMinted`, and never more than that is
+//! released or burned. Pending outbound transfers already count as locked or burned.
+//!
+//! Amounts are rescaled between the decimals of both sides and rounded down. Outbound only the
+//! part of the amount which can be represented on the other chains is taken from the sender.
+//! Inbound the part below the smallest local unit is dust, which is reported in `TransferredIn`
+//! and stays on the other chain.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{
+	ensure,
+	traits::{
+		tokens::fungibles::{Inspect, Mutate, Transfer},
+		Get, PalletInfoAccess,
+	},
+	transactional, PalletId,
+};
+use pallet_standard_chainbridge::{
+	self as chainbridge, BridgeCallback, BridgeChainId, DepositNonce, ResourceId, TransferOutcome,
+	U256,
+};
+use primitives::{traits::ReservableAssets, AssetId, Balance};
+use scale_info::TypeInfo;
+use sp_runtime::{traits::AccountIdConversion, DispatchResult, RuntimeDebug};
+use sp_std::vec::Vec;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+pub use pallet::*;
+
+/// Largest difference between the decimals of a bridged asset here and on the other chains, so
+/// that the scale between them fits a `Balance`
+pub const MAX_DECIMALS_DIFFERENCE: u8 = 38;
+
+/// Custody of a bridged asset on this chain.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum BridgeMode {
+	/// Native asset, locked in the pallet account while on other chains
+	LockRelease,
+	/// Foreign asset, minted while on this chain
+	MintBurn,
+}
+
+/// Asset behind a bridge resource.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct BridgedAsset {
+	pub asset: AssetId,
+	pub mode: BridgeMode,
+	/// Decimals of the asset on this chain
+	pub decimals: u8,
+	/// Decimals of the asset on the other chains
+	pub remote_decimals: u8,
+}
+
+impl BridgedAsset {
+	/// Difference between the decimals here and on the other chains
+	fn decimals_difference(&self) -> u8 {
+		if self.decimals > self.remote_decimals {
+			self.decimals - self.remote_decimals
+		} else {
+			self.remote_decimals - self.decimals
+		}
+	}
+
+	/// Checks if the decimals differ by at most `MAX_DECIMALS_DIFFERENCE`
+	pub fn valid_decimals(&self) -> bool {
+		self.decimals_difference() <= MAX_DECIMALS_DIFFERENCE
+	}
+
+	/// Units of the side with more decimals per unit of the other side
+	fn scale(&self) -> Balance {
+		10u128.pow(self.decimals_difference() as u32)
+	}
+
+	/// `amount` in the decimals of the other chains, along with the part of `amount` it
+	/// represents.
+	pub fn to_remote(&self, amount: Balance) -> (U256, Balance) {
+		let scale = self.scale();
+		if self.remote_decimals >= self.decimals {
+			(U256::from(amount) * U256::from(scale), amount)
+		} else {
+			let remote = amount / scale;
+			(U256::from(remote), remote * scale)
+		}
+	}
+
+	/// `amount` of the other chains in local decimals, along with the dust below the smallest
+	/// local unit. `None` if it does not fit a `Balance`.
+	pub fn to_local(&self, amount: U256) -> Option<(Balance, U256)> {
+		let scale = U256::from(self.scale());
+		let (local, dust) = if self.decimals >= self.remote_decimals {
+			(amount.checked_mul(scale)?, U256::zero())
+		} else {
+			(amount / scale, amount % scale)
+		};
+		(local <= U256::from(Balance::MAX)).then(|| (local.as_u128(), dust))
+	}
+}
+
+/// Asset proposed for bridging by its owner, waiting for governance.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct AssetProposal<AccountId> {
+	pub proposer: AccountId,
+	pub bridged: BridgedAsset,
+}
+
+/// Owner of an asset, who can propose to bridge it.
+pub trait AssetOwner<AccountId> {
+	fn owner(asset: AssetId) -> Option<AccountId>;
+}
+
+/// Outbound transfer reserved in the account of its sender until the relayers report its result.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct OutboundTransfer<AccountId> {
+	pub sender: AccountId,
+	pub asset: AssetId,
+	pub mode: BridgeMode,
+	pub amount: Balance,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::{pallet_prelude::*, transactional};
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + chainbridge::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		type Assets: Inspect<Self::AccountId, AssetId = AssetId, Balance = Balance>
+			+ Transfer<Self::AccountId, AssetId = AssetId, Balance = Balance>
+			+ Mutate<Self::AccountId, AssetId = AssetId, Balance = Balance>;
+		/// Origin of the transfers relayed by the bridge
+		type BridgeOrigin: EnsureOrigin<Self::Origin, Success = Self::AccountId>;
+		/// Origin registering the assets behind bridge resources
+		type AdminOrigin: EnsureOrigin<Self::Origin>;
+		/// Owners of the assets, who can propose to bridge them
+		type AssetOwner: AssetOwner<Self::AccountId>;
+		/// Reserves the amounts of the outbound transfers in the accounts of their senders
+		type Reserves: ReservableAssets<Self::AccountId>;
+		/// Account holding the bridged assets
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Asset is registered behind a resource (resource_id, asset, mode, decimals,
+		/// remote_decimals)
+		AssetRegistered(ResourceId, AssetId, BridgeMode, u8, u8),
+		/// Asset is removed from behind a resource (resource_id)
+		AssetRemoved(ResourceId),
+		/// Owner proposes to bridge an asset (resource_id, asset, proposer)
+		AssetProposed(ResourceId, AssetId, T::AccountId),
+		/// Proposal to bridge an asset is rejected (resource_id)
+		AssetProposalRejected(ResourceId),
+		/// Amount is sent to another chain (dest_id, nonce, sender, asset, amount)
+		TransferredOut(BridgeChainId, DepositNonce, T::AccountId, AssetId, Balance),
+		/// Amount is received from another chain, without the dust in the decimals of the other
+		/// chain (resource_id, recipient, asset, amount, dust)
+		TransferredIn(ResourceId, T::AccountId, AssetId, Balance, U256),
+		/// Failed outbound transfer is refunded (dest_id, nonce)
+		Refunded(BridgeChainId, DepositNonce),
+		/// Outbound transfer timed out and waits to be resolved (dest_id, nonce)
+		TransferTimedOut(BridgeChainId, DepositNonce),
+		/// Executed outbound transfer could not take the reserved amount and waits to be
+		/// resolved (dest_id, nonce)
+		SettlementFailed(BridgeChainId, DepositNonce),
+		/// Timed out transfer is resolved as executed on the other chain or not, in which case it
+		/// is refunded (dest_id, nonce, executed)
+		TimedOutResolved(BridgeChainId, DepositNonce, bool),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// No asset is registered behind the resource
+		AssetNotRegistered,
+		/// Decimals differ by more than `MAX_DECIMALS_DIFFERENCE`
+		InvalidDecimals,
+		/// Amount is zero in the decimals of the receiving chain
+		AmountTooSmall,
+		/// Amount does not fit a balance in local decimals
+		AmountTooLarge,
+		/// Amount exceeds what is locked or minted behind the resource
+		InsufficientBridgedSupply,
+		/// Mode cannot change while an amount is locked or minted behind the resource
+		BridgedSupplyOutstanding,
+		/// Only the owner of the asset can propose to bridge it
+		NotAssetOwner,
+		/// Asset is already registered behind its resource
+		AssetAlreadyRegistered,
+		/// Asset is already proposed for bridging
+		AssetAlreadyProposed,
+		/// No proposal to bridge an asset behind the resource
+		AssetProposalNotFound,
+		/// No timed out transfer with the nonce
+		TimedOutTransferNotFound,
+	}
+
+	#[pallet::storage]
+	#[pallet::getter(fn bridged_asset)]
+	/// Assets behind the bridge resources
+	pub type BridgedAssets<T> = StorageMap<_, Blake2_128Concat, ResourceId, BridgedAsset>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn asset_proposal)]
+	/// Assets proposed by their owners, keyed by the resource derived for them
+	pub type AssetProposals<T: Config> =
+		StorageMap<_, Blake2_128Concat, ResourceId, AssetProposal<T::AccountId>>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn total_locked)]
+	/// Amount of native assets locked in the pallet account per resource
+	pub type TotalLocked<T> = StorageMap<_, Blake2_128Concat, ResourceId, Balance, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn total_minted)]
+	/// Amount of foreign assets minted on this chain per resource
+	pub type TotalMinted<T> = StorageMap<_, Blake2_128Concat, ResourceId, Balance, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn outbound_transfer)]
+	/// Outbound transfers whose result the relayers have not reported yet
+	pub type OutboundTransfers<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		BridgeChainId,
+		Twox64Concat,
+		DepositNonce,
+		OutboundTransfer<T::AccountId>,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn timed_out_transfer)]
+	/// Outbound transfers the relayers did not agree on in time, or executed ones whose amount
+	/// could not be taken, with their resource, until governance resolves them
+	pub type TimedOutTransfers<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		BridgeChainId,
+		Twox64Concat,
+		DepositNonce,
+		(ResourceId, OutboundTransfer<T::AccountId>),
+	>;
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Registers `asset` behind `resource_id` in custody `mode`, with `decimals` here and
+		/// `remote_decimals` on the other chains. The mode of a resource only changes while
+		/// nothing is locked or minted behind it.
+		///
+		/// # <weight>
+		/// - O(1) lookup and write
+		/// # </weight>
+		#[pallet::weight(T::DbWeight::get().reads_writes(4, 2))]
+		pub fn register_asset(
+			origin: OriginFor<T>,
+			resource_id: ResourceId,
+			asset: AssetId,
+			mode: BridgeMode,
+			decimals: u8,
+			remote_decimals: u8,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			Self::do_register_asset(
+				resource_id,
+				BridgedAsset { asset, mode, decimals, remote_decimals },
+			)
+		}
+
+		/// Removes the asset behind `resource_id`. Pending outbound transfers are still refunded,
+		/// and the amounts locked or minted behind it stay accounted for.
+		///
+		/// # <weight>
+		/// - O(1) removal
+		/// # </weight>
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn remove_asset(origin: OriginFor<T>, resource_id: ResourceId) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(
+				BridgedAssets::<T>::take(resource_id).is_some(),
+				Error::<T>::AssetNotRegistered
+			);
+
+			Self::deposit_event(Event::AssetRemoved(resource_id));
+			Ok(())
+		}
+
+		/// Sends `amount` of the asset behind `resource_id` to `recipient` on `dest_id`.
+		///
+		/// Only the part of `amount` which can be represented in the decimals of the other chains
+		/// is reserved in the account of the sender, and locked or burned depending on the mode
+		/// of the asset once the transfer is executed.
+		#[pallet::weight(T::DbWeight::get().reads_writes(7, 8))]
+		#[transactional]
+		pub fn transfer_out(
+			origin: OriginFor<T>,
+			resource_id: ResourceId,
+			amount: Balance,
+			recipient: Vec<u8>,
+			dest_id: BridgeChainId,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let bridged = Self::bridged_asset(resource_id).ok_or(Error::<T>::AssetNotRegistered)?;
+			let (remote_amount, amount) = bridged.to_remote(amount);
+			ensure!(amount > 0, Error::<T>::AmountTooSmall);
+
+			Self::reserve_outbound(resource_id, &bridged, &sender, amount)?;
+			let nonce = chainbridge::Pallet::<T>::transfer_fungible(
+				sender.clone(),
+				dest_id,
+				resource_id,
+				recipient,
+				remote_amount,
+			)?;
+			OutboundTransfers::<T>::insert(
+				dest_id,
+				nonce,
+				OutboundTransfer {
+					sender: sender.clone(),
+					asset: bridged.asset,
+					mode: bridged.mode,
+					amount,
+				},
+			);
+
+			Self::deposit_event(Event::TransferredOut(
+				dest_id,
+				nonce,
+				sender,
+				bridged.asset,
+				amount,
+			));
+			Ok(())
+		}
+
+		/// Pays out a transfer of `amount`, in the decimals of the other chains, of the asset
+		/// behind `resource_id` to `recipient`, released or minted depending on the mode of the
+		/// asset. Called by the bridge once the relayers agree.
+		#[pallet::weight(T::DbWeight::get().reads_writes(3, 3))]
+		pub fn transfer_in(
+			origin: OriginFor<T>,
+			recipient: T::AccountId,
+			amount: U256,
+			resource_id: ResourceId,
+		) -> DispatchResult {
+			T::BridgeOrigin::ensure_origin(origin)?;
+			let bridged = Self::bridged_asset(resource_id).ok_or(Error::<T>::AssetNotRegistered)?;
+			let (amount, dust) = bridged.to_local(amount).ok_or(Error::<T>::AmountTooLarge)?;
+			ensure!(amount > 0, Error::<T>::AmountTooSmall);
+
+			Self::release_or_mint(resource_id, bridged.asset, bridged.mode, &recipient, amount)?;
+
+			Self::deposit_event(Event::TransferredIn(
+				resource_id,
+				recipient,
+				bridged.asset,
+				amount,
+				dust,
+			));
+			Ok(())
+		}
+
+		/// Proposes to bridge `asset` in custody `mode`, with `decimals` here and
+		/// `remote_decimals` on the other chains. Only the owner of the asset can propose it,
+		/// under the resource derived with `asset_resource_id`.
+		///
+		/// # <weight>
+		/// - O(1) lookups and insert
+		/// # </weight>
+		#[pallet::weight(T::DbWeight::get().reads_writes(3, 1))]
+		pub fn propose_asset(
+			origin: OriginFor<T>,
+			asset: AssetId,
+			mode: BridgeMode,
+			decimals: u8,
+			remote_decimals: u8,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(T::AssetOwner::owner(asset) == Some(who.clone()), Error::<T>::NotAssetOwner);
+			let bridged = BridgedAsset { asset, mode, decimals, remote_decimals };
+			ensure!(bridged.valid_decimals(), Error::<T>::InvalidDecimals);
+			let resource_id = Self::asset_resource_id(asset);
+			ensure!(
+				!BridgedAssets::<T>::contains_key(resource_id),
+				Error::<T>::AssetAlreadyRegistered
+			);
+			ensure!(
+				!AssetProposals::<T>::contains_key(resource_id),
+				Error::<T>::AssetAlreadyProposed
+			);
+
+			AssetProposals::<T>::insert(
+				resource_id,
+				AssetProposal { proposer: who.clone(), bridged },
+			);
+			Self::deposit_event(Event::AssetProposed(resource_id, asset, who));
+			Ok(())
+		}
+
+		/// Registers the asset proposed behind `resource_id`.
+		///
+		/// # <weight>
+		/// - O(1) lookups and writes
+		/// # </weight>
+		#[pallet::weight(T::DbWeight::get().reads_writes(5, 3))]
+		pub fn approve_asset(origin: OriginFor<T>, resource_id: ResourceId) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			let proposal =
+				Self::asset_proposal(resource_id).ok_or(Error::<T>::AssetProposalNotFound)?;
+
+			Self::do_register_asset(resource_id, proposal.bridged)?;
+			AssetProposals::<T>::remove(resource_id);
+			Ok(())
+		}
+
+		/// Rejects the asset proposed behind `resource_id`.
+		///
+		/// # <weight>
+		/// - O(1) removal
+		/// # </weight>
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn reject_asset(origin: OriginFor<T>, resource_id: ResourceId) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(
+				AssetProposals::<T>::take(resource_id).is_some(),
+				Error::<T>::AssetProposalNotFound
+			);
+
+			Self::deposit_event(Event::AssetProposalRejected(resource_id));
+			Ok(())
+		}
+
+		/// Resolves the timed out transfer `nonce` to `dest_id` once its result on the other
+		/// chain is known. The reserved amount is taken if the transfer was `executed`, and
+		/// released otherwise.
+		///
+		/// # <weight>
+		/// - O(1) lookup, removal and refund
+		/// # </weight>
+		#[pallet::weight(T::DbWeight::get().reads_writes(3, 3))]
+		#[transactional]
+		pub fn resolve_timed_out(
+			origin: OriginFor<T>,
+			dest_id: BridgeChainId,
+			nonce: DepositNonce,
+			executed: bool,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			let (resource_id, transfer) = TimedOutTransfers::<T>::take(dest_id, nonce)
+				.ok_or(Error::<T>::TimedOutTransferNotFound)?;
+
+			if executed {
+				Self::settle(&transfer)?;
+			} else {
+				Self::refund(resource_id, &transfer);
+			}
+			Self::deposit_event(Event::TimedOutResolved(dest_id, nonce, executed));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Account holding the bridged assets
+	pub fn account_id() -> T::AccountId {
+		T::PalletId::get().into_account()
+	}
+
+	/// Resource of `asset` on this chain, derived from the chain id and the asset id
+	pub fn asset_resource_id(asset: AssetId) -> ResourceId {
+		chainbridge::derive_resource_id(
+			<T as chainbridge::Config>::BridgeChainId::get(),
+			&asset.encode(),
+		)
+	}
+
+	/// Registers `bridged` behind `resource_id`, and `resource_id` with chainbridge unless it
+	/// already is.
+	fn do_register_asset(resource_id: ResourceId, bridged: BridgedAsset) -> DispatchResult {
+		ensure!(bridged.valid_decimals(), Error::<T>::InvalidDecimals);
+		if let Some(registered) = Self::bridged_asset(resource_id) {
+			ensure!(
+				registered.mode == bridged.mode ||
+					(Self::total_locked(resource_id) == 0 &&
+						Self::total_minted(resource_id) == 0),
+				Error::<T>::BridgedSupplyOutstanding
+			);
+		}
+
+		if !chainbridge::Pallet::<T>::resource_exists(resource_id) {
+			let mut method = <Self as PalletInfoAccess>::name().as_bytes().to_vec();
+			method.extend_from_slice(b".transfer_in");
+			chainbridge::Pallet::<T>::register_resource(resource_id, method)?;
+		}
+		BridgedAssets::<T>::insert(resource_id, &bridged);
+		Self::deposit_event(Event::AssetRegistered(
+			resource_id,
+			bridged.asset,
+			bridged.mode,
+			bridged.decimals,
+			bridged.remote_decimals,
+		));
+		Ok(())
+	}
+
+	/// Reserves `amount` of `who` for a transfer to another chain, counted as locked or burned
+	/// depending on the mode of the asset until the transfer is refunded.
+	fn reserve_outbound(
+		resource_id: ResourceId,
+		bridged: &BridgedAsset,
+		who: &T::AccountId,
+		amount: Balance,
+	) -> DispatchResult {
+		match bridged.mode {
+			BridgeMode::LockRelease => {
+				let locked = Self::total_locked(resource_id)
+					.checked_add(amount)
+					.ok_or(Error::<T>::AmountTooLarge)?;
+				T::Reserves::reserve(bridged.asset, who, amount)?;
+				TotalLocked::<T>::insert(resource_id, locked);
+			},
+			BridgeMode::MintBurn => {
+				let minted = Self::total_minted(resource_id)
+					.checked_sub(amount)
+					.ok_or(Error::<T>::InsufficientBridgedSupply)?;
+				T::Reserves::reserve(bridged.asset, who, amount)?;
+				TotalMinted::<T>::insert(resource_id, minted);
+			},
+		}
+		Ok(())
+	}
+
+	/// Takes the amount reserved for an executed outbound transfer, into the pallet account or
+	/// burned depending on the mode of the asset.
+	#[transactional]
+	fn settle(transfer: &OutboundTransfer<T::AccountId>) -> DispatchResult {
+		T::Reserves::unreserve(transfer.asset, &transfer.sender, transfer.amount);
+		match transfer.mode {
+			BridgeMode::LockRelease => T::Assets::transfer(
+				transfer.asset,
+				&transfer.sender,
+				&Self::account_id(),
+				transfer.amount,
+				false,
+			)
+			.map(|_| ()),
+			BridgeMode::MintBurn =>
+				T::Assets::burn_from(transfer.asset, &transfer.sender, transfer.amount).map(|_| ()),
+		}
+	}
+
+	/// Releases the amount reserved for an outbound transfer which did not happen.
+	fn refund(resource_id: ResourceId, transfer: &OutboundTransfer<T::AccountId>) {
+		T::Reserves::unreserve(transfer.asset, &transfer.sender, transfer.amount);
+		match transfer.mode {
+			BridgeMode::LockRelease => TotalLocked::<T>::mutate(resource_id, |locked| {
+				*locked = locked.saturating_sub(transfer.amount)
+			}),
+			BridgeMode::MintBurn => TotalMinted::<T>::mutate(resource_id, |minted| {
+				*minted = minted.saturating_add(transfer.amount)
+			}),
+		}
+	}
+
+	/// Releases or mints `amount` to `who` for a transfer from another chain.
+	fn release_or_mint(
+		resource_id: ResourceId,
+		asset: AssetId,
+		mode: BridgeMode,
+		who: &T::AccountId,
+		amount: Balance,
+	) -> DispatchResult {
+		match mode {
+			BridgeMode::LockRelease => {
+				let locked = Self::total_locked(resource_id)
+					.checked_sub(amount)
+					.ok_or(Error::<T>::InsufficientBridgedSupply)?;
+				T::Assets::transfer(asset, &Self::account_id(), who, amount, false)?;
+				TotalLocked::<T>::insert(resource_id, locked);
+			},
+			BridgeMode::MintBurn => {
+				let minted = Self::total_minted(resource_id)
+					.checked_add(amount)
+					.ok_or(Error::<T>::AmountTooLarge)?;
+				T::Assets::mint_into(asset, who, amount)?;
+				TotalMinted::<T>::insert(resource_id, minted);
+			},
+		}
+		Ok(())
+	}
+}
+
+impl<T: Config> BridgeCallback for Pallet<T> {
+	fn on_transfer_result(
+		dest_id: BridgeChainId,
+		nonce: DepositNonce,
+		resource_id: ResourceId,
+		outcome: TransferOutcome,
+	) {
+		let transfer = match OutboundTransfers::<T>::take(dest_id, nonce) {
+			Some(transfer) => transfer,
+			None => return,
+		};
+		match outcome {
+			TransferOutcome::Succeeded =>
+				if Self::settle(&transfer).is_err() {
+					TimedOutTransfers::<T>::insert(dest_id, nonce, (resource_id, transfer));
+					Self::deposit_event(Event::SettlementFailed(dest_id, nonce));
+				},
+			TransferOutcome::Failed => {
+				Self::refund(resource_id, &transfer);
+				Self::deposit_event(Event::Refunded(dest_id, nonce));
+			},
+			// The transfer may still have been executed, so it stays reserved
+			TransferOutcome::TimedOut => {
+				TimedOutTransfers::<T>::insert(dest_id, nonce, (resource_id, transfer));
+				Self::deposit_event(Event::TransferTimedOut(dest_id, nonce));
+			},
+		}
+	}
+}
@@ -0,0 +1,252 @@
+#![cfg(test)]
+
+use frame_support::{
+	assert_ok, ensure, parameter_types,
+	traits::{fungibles::Inspect, ConstU32, Everything},
+	PalletId,
+};
+use frame_system as system;
+use pallet_assets::FrozenBalance;
+use pallet_standard_chainbridge::{self as chainbridge, BridgeChainId, ResourceId};
+use primitives::{traits::ReservableAssets, AssetId, Balance};
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+	DispatchResult, TokenError,
+};
+use sp_std::{cell::RefCell, collections::btree_map::BTreeMap};
+
+use crate::{self as bridge_transfer, AssetOwner, BridgeMode, Config};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+pub const ALICE: u64 = 1;
+pub const RELAYER: u64 = 2;
+pub const BOB: u64 = 4;
+
+pub const STND: AssetId = 1;
+pub const WETH: AssetId = 2;
+/// Asset of BOB, not bridged yet
+pub const DOT: AssetId = 3;
+pub const DEST_ID: BridgeChainId = 1;
+/// Resource of STND, locked while bridged, with 18 decimals on the other chains against 12 here
+pub const STND_RESOURCE: ResourceId = [1; 32];
+/// Resource of WETH, minted while bridged, with 18 decimals on the other chains against 12 here
+pub const WETH_RESOURCE: ResourceId = [3; 32];
+
+frame_support::construct_runtime!(
+	pub enum Test where
+	 Block = Block,
+	 NodeBlock = Block,
+	 UncheckedExtrinsic = UncheckedExtrinsic,
+	 {
+		 System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		 Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		 Assets: pallet_assets::{Pallet, Call, Storage, Config<T>, Event<T>},
+		 ChainBridge: chainbridge::{Pallet, Call, Storage, Event<T>},
+		 BridgeTransfer: bridge_transfer::{Pallet, Call, Storage, Event<T>},
+	 }
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 63;
+}
+
+impl system::Config for Test {
+	type OnSetCode = ();
+	type BaseCallFilter = Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type DbWeight = ();
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<Balance>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type MaxConsumers = ConstU32<16>;
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: Balance = 1;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type MaxLocks = ();
+	type Balance = Balance;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+impl pallet_assets::Config for Test {
+	type Event = Event;
+	type Balance = Balance;
+	type AssetId = AssetId;
+	type Currency = Balances;
+	type ForceOrigin = frame_system::EnsureRoot<u64>;
+	type AssetDeposit = ();
+	type AssetAccountDeposit = ();
+	type MetadataDepositBase = ();
+	type MetadataDepositPerByte = ();
+	type ApprovalDeposit = ();
+	type StringLimit = ConstU32<50>;
+	type Freezer = Reserves;
+	type Extra = ();
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const TestBridgeChainId: u8 = 5;
+	pub const ProposalLifetime: u64 = 50;
+	pub const TransferLifetime: u64 = 100;
+	pub const NonceWindow: u64 = 128;
+	pub const MaxRelayerSnapshots: u32 = 3;
+	pub const MaxVotesPerBlock: u32 = 5;
+	pub const MaxQueuedProposals: u32 = 3;
+	pub const DeliveryQueueTimeout: u64 = 10;
+	pub DefaultAllowedProposals: Vec<(Vec<u8>, Vec<u8>)> =
+		vec![(b"BridgeTransfer".to_vec(), b"transfer_in".to_vec())];
+}
+
+impl chainbridge::Config for Test {
+	type Event = Event;
+	type RelayerAdminOrigin = frame_system::EnsureRoot<Self::AccountId>;
+	type ResourceAdminOrigin = frame_system::EnsureRoot<Self::AccountId>;
+	type PauseOrigin = frame_system::EnsureRoot<Self::AccountId>;
+	type Proposal = Call;
+	type BridgeChainId = TestBridgeChainId;
+	type ProposalLifetime = ProposalLifetime;
+	type TransferCallback = BridgeTransfer;
+	type TransferLifetime = TransferLifetime;
+	type NonceWindow = NonceWindow;
+	type MaxRelayerSnapshots = MaxRelayerSnapshots;
+	type MaxVotesPerBlock = MaxVotesPerBlock;
+	type DefaultAllowedProposals = DefaultAllowedProposals;
+	type MaxQueuedProposals = MaxQueuedProposals;
+	type DeliveryQueueTimeout = DeliveryQueueTimeout;
+}
+
+parameter_types! {
+	pub const BridgeTransferPalletId: PalletId = PalletId(*b"stnd/bgt");
+}
+
+/// Owners of the assets created in `new_test_ext`
+pub struct AssetOwners;
+impl AssetOwner<u64> for AssetOwners {
+	fn owner(asset: AssetId) -> Option<u64> {
+		match asset {
+			STND | WETH => Some(ALICE),
+			DOT => Some(BOB),
+			_ => None,
+		}
+	}
+}
+
+thread_local! {
+	pub static RESERVED: RefCell<BTreeMap<(AssetId, u64), Balance>> = RefCell::new(BTreeMap::new());
+}
+
+/// Reserves assets in place and freezes them through the assets pallet, as the market does.
+pub struct Reserves;
+impl ReservableAssets<u64> for Reserves {
+	fn reserve(asset: AssetId, who: &u64, amount: Balance) -> DispatchResult {
+		ensure!(Assets::reducible_balance(asset, who, true) >= amount, TokenError::NoFunds);
+		RESERVED.with(|r| *r.borrow_mut().entry((asset, *who)).or_default() += amount);
+		Ok(())
+	}
+
+	fn unreserve(asset: AssetId, who: &u64, amount: Balance) {
+		RESERVED.with(|r| {
+			let mut reserved = r.borrow_mut();
+			let left = reserved.remove(&(asset, *who)).unwrap_or_default().saturating_sub(amount);
+			if left > 0 {
+				reserved.insert((asset, *who), left);
+			}
+		});
+	}
+
+	fn reserved_balance(asset: AssetId, who: &u64) -> Balance {
+		RESERVED.with(|r| r.borrow().get(&(asset, *who)).copied().unwrap_or_default())
+	}
+}
+
+impl FrozenBalance<AssetId, u64, Balance> for Reserves {
+	fn frozen_balance(asset: AssetId, who: &u64) -> Option<Balance> {
+		RESERVED.with(|r| r.borrow().get(&(asset, *who)).copied())
+	}
+
+	fn died(asset: AssetId, who: &u64) {
+		RESERVED.with(|r| r.borrow_mut().remove(&(asset, *who)));
+	}
+}
+
+impl Config for Test {
+	type Event = Event;
+	type Assets = Assets;
+	type BridgeOrigin = chainbridge::EnsureBridge<Test>;
+	type AdminOrigin = frame_system::EnsureRoot<u64>;
+	type AssetOwner = AssetOwners;
+	type Reserves = Reserves;
+	type PalletId = BridgeTransferPalletId;
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_assets::GenesisConfig::<Test> {
+		assets: vec![(STND, ALICE, true, 1), (WETH, ALICE, true, 1), (DOT, BOB, true, 1)],
+		metadata: vec![],
+		accounts: vec![(STND, ALICE, 10_000_000)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(ChainBridge::set_threshold(Origin::root(), 1));
+		assert_ok!(ChainBridge::add_relayer(Origin::root(), RELAYER));
+		assert_ok!(ChainBridge::whitelist_chain(Origin::root(), DEST_ID));
+		assert_ok!(ChainBridge::set_resource(Origin::root(), STND_RESOURCE, b"STND".to_vec()));
+		assert_ok!(ChainBridge::set_resource(Origin::root(), WETH_RESOURCE, b"WETH".to_vec()));
+		assert_ok!(BridgeTransfer::register_asset(
+			Origin::root(),
+			STND_RESOURCE,
+			STND,
+			BridgeMode::LockRelease,
+			12,
+			18
+		));
+		assert_ok!(BridgeTransfer::register_asset(
+			Origin::root(),
+			WETH_RESOURCE,
+			WETH,
+			BridgeMode::MintBurn,
+			12,
+			18
+		));
+	});
+	ext
+}
+
+pub fn last_event() -> Event {
+	system::Pallet::<Test>::events().pop().expect("Event expected").event
+}
@@ -0,0 +1,514 @@
+use crate::{
+	mock::*, AssetProposal, BridgeMode, BridgedAsset, Error, Event as BridgeTransferEvent,
+	OutboundTransfer,
+};
+use codec::Encode;
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{fungibles::Inspect, OnInitialize},
+};
+use pallet_standard_chainbridge::{derive_resource_id, U256};
+use primitives::traits::ReservableAssets;
+
+/// Resource of STND, with 6 decimals on the other chains against 12 here
+const STND_6_RESOURCE: [u8; 32] = [2; 32];
+
+fn register_stnd_6() {
+	assert_ok!(BridgeTransfer::register_asset(
+		Origin::root(),
+		STND_6_RESOURCE,
+		STND,
+		BridgeMode::LockRelease,
+		12,
+		6
+	));
+}
+
+fn transfer_out(resource_id: [u8; 32], amount: u128) {
+	assert_ok!(BridgeTransfer::transfer_out(
+		Origin::signed(ALICE),
+		resource_id,
+		amount,
+		vec![1],
+		DEST_ID
+	));
+}
+
+fn acknowledge(nonce: u64, executed: bool) {
+	assert_ok!(ChainBridge::acknowledge_transfer(
+		Origin::signed(RELAYER),
+		DEST_ID,
+		nonce,
+		executed
+	));
+}
+
+#[test]
+fn decimals_are_converted_both_ways() {
+	let asset = |decimals, remote_decimals| BridgedAsset {
+		asset: STND,
+		mode: BridgeMode::LockRelease,
+		decimals,
+		remote_decimals,
+	};
+
+	let up = asset(12, 18);
+	assert_eq!(up.to_remote(1_234), (U256::from(1_234_000_000u128), 1_234));
+	assert_eq!(up.to_local(U256::from(1_234_000_567u128)), Some((1_234, U256::from(567))));
+	assert_eq!(up.to_local(U256::from(999_999)), Some((0, U256::from(999_999))));
+	assert_eq!(up.to_local(U256::MAX), None);
+
+	let down = asset(12, 6);
+	assert_eq!(down.to_remote(1_234_567), (U256::from(1), 1_000_000));
+	assert_eq!(down.to_local(U256::from(1)), Some((1_000_000, U256::zero())));
+	assert_eq!(down.to_local(U256::from(u128::MAX)), None);
+
+	let same = asset(12, 12);
+	assert_eq!(same.to_remote(u128::MAX), (U256::from(u128::MAX), u128::MAX));
+	assert_eq!(same.to_local(U256::from(u128::MAX)), Some((u128::MAX, U256::zero())));
+}
+
+#[test]
+fn only_admin_registers_assets() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			BridgeTransfer::register_asset(
+				Origin::signed(ALICE),
+				STND_6_RESOURCE,
+				STND,
+				BridgeMode::LockRelease,
+				12,
+				6
+			),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			BridgeTransfer::register_asset(
+				Origin::root(),
+				STND_6_RESOURCE,
+				STND,
+				BridgeMode::LockRelease,
+				0,
+				39
+			),
+			Error::<Test>::InvalidDecimals
+		);
+
+		register_stnd_6();
+		assert_eq!(
+			BridgeTransfer::bridged_asset(STND_6_RESOURCE),
+			Some(BridgedAsset {
+				asset: STND,
+				mode: BridgeMode::LockRelease,
+				decimals: 12,
+				remote_decimals: 6
+			})
+		);
+		assert_eq!(
+			last_event(),
+			Event::BridgeTransfer(BridgeTransferEvent::AssetRegistered(
+				STND_6_RESOURCE,
+				STND,
+				BridgeMode::LockRelease,
+				12,
+				6
+			))
+		);
+
+		assert_ok!(BridgeTransfer::remove_asset(Origin::root(), STND_6_RESOURCE));
+		assert_eq!(BridgeTransfer::bridged_asset(STND_6_RESOURCE), None);
+		assert_noop!(
+			BridgeTransfer::remove_asset(Origin::root(), STND_6_RESOURCE),
+			Error::<Test>::AssetNotRegistered
+		);
+	})
+}
+
+#[test]
+fn mode_is_kept_while_supply_is_bridged() {
+	new_test_ext().execute_with(|| {
+		transfer_out(STND_RESOURCE, 1_000);
+		assert_noop!(
+			BridgeTransfer::register_asset(
+				Origin::root(),
+				STND_RESOURCE,
+				STND,
+				BridgeMode::MintBurn,
+				12,
+				18
+			),
+			Error::<Test>::BridgedSupplyOutstanding
+		);
+		// Decimals may still be corrected
+		assert_ok!(BridgeTransfer::register_asset(
+			Origin::root(),
+			STND_RESOURCE,
+			STND,
+			BridgeMode::LockRelease,
+			12,
+			16
+		));
+
+		acknowledge(1, false);
+		assert_eq!(BridgeTransfer::total_locked(STND_RESOURCE), 0);
+		assert_ok!(BridgeTransfer::register_asset(
+			Origin::root(),
+			STND_RESOURCE,
+			STND,
+			BridgeMode::MintBurn,
+			12,
+			18
+		));
+	})
+}
+
+#[test]
+fn transfer_out_takes_representable_amount() {
+	new_test_ext().execute_with(|| {
+		register_stnd_6();
+		assert_noop!(
+			BridgeTransfer::transfer_out(
+				Origin::signed(ALICE),
+				STND_6_RESOURCE,
+				999_999,
+				vec![1],
+				DEST_ID
+			),
+			Error::<Test>::AmountTooSmall
+		);
+
+		transfer_out(STND_6_RESOURCE, 1_500_000);
+		assert_eq!(Reserves::reserved_balance(STND, &ALICE), 1_000_000);
+		assert_eq!(
+			ChainBridge::transfer_receipts(DEST_ID, 1).map(|receipt| receipt.amount),
+			Some(U256::from(1))
+		);
+		assert_eq!(
+			last_event(),
+			Event::BridgeTransfer(BridgeTransferEvent::TransferredOut(
+				DEST_ID, 1, ALICE, STND, 1_000_000
+			))
+		);
+
+		transfer_out(STND_RESOURCE, 1_000);
+		assert_eq!(
+			ChainBridge::transfer_receipts(DEST_ID, 2).map(|receipt| receipt.amount),
+			Some(U256::from(1_000_000_000u128))
+		);
+	})
+}
+
+#[test]
+fn native_assets_are_locked_and_released() {
+	new_test_ext().execute_with(|| {
+		let bridge = ChainBridge::account_id();
+		transfer_out(STND_RESOURCE, 2_000);
+		assert_eq!(Assets::balance(STND, &ALICE), 10_000_000);
+		assert_eq!(Reserves::reserved_balance(STND, &ALICE), 2_000);
+		assert_eq!(BridgeTransfer::total_locked(STND_RESOURCE), 2_000);
+		assert_noop!(
+			Assets::transfer(Origin::signed(ALICE), STND, 3, 9_998_000),
+			pallet_assets::Error::<Test>::BalanceLow
+		);
+
+		// The reserved amount is locked once the transfer is executed
+		acknowledge(1, true);
+		assert_eq!(Assets::balance(STND, &ALICE), 9_998_000);
+		assert_eq!(Reserves::reserved_balance(STND, &ALICE), 0);
+		assert_eq!(Assets::balance(STND, &BridgeTransfer::account_id()), 2_000);
+		assert_eq!(Assets::total_issuance(STND), 10_000_000);
+		assert_eq!(BridgeTransfer::total_locked(STND_RESOURCE), 2_000);
+
+		// Never more is released than was locked
+		assert_noop!(
+			BridgeTransfer::transfer_in(
+				Origin::signed(bridge),
+				3,
+				U256::from(2_001_000_000u128),
+				STND_RESOURCE
+			),
+			Error::<Test>::InsufficientBridgedSupply
+		);
+		assert_ok!(BridgeTransfer::transfer_in(
+			Origin::signed(bridge),
+			3,
+			U256::from(1_500_000_000u128),
+			STND_RESOURCE
+		));
+		assert_eq!(Assets::balance(STND, &3), 1_500);
+		assert_eq!(Assets::balance(STND, &BridgeTransfer::account_id()), 500);
+		assert_eq!(BridgeTransfer::total_locked(STND_RESOURCE), 500);
+	})
+}
+
+#[test]
+fn foreign_assets_are_minted_and_burned() {
+	new_test_ext().execute_with(|| {
+		let bridge = ChainBridge::account_id();
+		// Nothing was minted yet, so nothing can be burned
+		assert_noop!(
+			BridgeTransfer::transfer_out(
+				Origin::signed(ALICE),
+				WETH_RESOURCE,
+				1_000,
+				vec![1],
+				DEST_ID
+			),
+			Error::<Test>::InsufficientBridgedSupply
+		);
+
+		assert_ok!(BridgeTransfer::transfer_in(
+			Origin::signed(bridge),
+			ALICE,
+			U256::from(5_000_000_000u128),
+			WETH_RESOURCE
+		));
+		assert_eq!(Assets::balance(WETH, &ALICE), 5_000);
+		assert_eq!(Assets::total_issuance(WETH), 5_000);
+		assert_eq!(BridgeTransfer::total_minted(WETH_RESOURCE), 5_000);
+
+		// Refunds release the reserved amount
+		transfer_out(WETH_RESOURCE, 2_000);
+		assert_eq!(Reserves::reserved_balance(WETH, &ALICE), 2_000);
+		assert_eq!(BridgeTransfer::total_minted(WETH_RESOURCE), 3_000);
+		acknowledge(1, false);
+		assert_eq!(Reserves::reserved_balance(WETH, &ALICE), 0);
+		assert_eq!(Assets::balance(WETH, &ALICE), 5_000);
+		assert_eq!(BridgeTransfer::total_minted(WETH_RESOURCE), 5_000);
+
+		transfer_out(WETH_RESOURCE, 2_000);
+		acknowledge(2, true);
+		assert_eq!(Assets::balance(WETH, &ALICE), 3_000);
+		assert_eq!(Assets::balance(WETH, &BridgeTransfer::account_id()), 0);
+		assert_eq!(Assets::total_issuance(WETH), 3_000);
+		assert_eq!(BridgeTransfer::total_minted(WETH_RESOURCE), 3_000);
+		assert_eq!(BridgeTransfer::total_locked(WETH_RESOURCE), 0);
+	})
+}
+
+#[test]
+fn failed_transfers_are_refunded() {
+	new_test_ext().execute_with(|| {
+		transfer_out(STND_RESOURCE, 1_000);
+		transfer_out(STND_RESOURCE, 2_000);
+		assert_eq!(
+			BridgeTransfer::outbound_transfer(DEST_ID, 1),
+			Some(OutboundTransfer {
+				sender: ALICE,
+				asset: STND,
+				mode: BridgeMode::LockRelease,
+				amount: 1_000
+			})
+		);
+
+		acknowledge(1, true);
+		assert_eq!(BridgeTransfer::outbound_transfer(DEST_ID, 1), None);
+		assert_eq!(Assets::balance(STND, &ALICE), 9_999_000);
+		assert_eq!(Reserves::reserved_balance(STND, &ALICE), 2_000);
+
+		acknowledge(2, false);
+		assert_eq!(BridgeTransfer::outbound_transfer(DEST_ID, 2), None);
+		assert_eq!(Assets::balance(STND, &ALICE), 9_999_000);
+		assert_eq!(Reserves::reserved_balance(STND, &ALICE), 0);
+		assert_eq!(Assets::balance(STND, &BridgeTransfer::account_id()), 1_000);
+		assert_eq!(BridgeTransfer::total_locked(STND_RESOURCE), 1_000);
+		assert_eq!(last_event(), Event::BridgeTransfer(BridgeTransferEvent::Refunded(DEST_ID, 2)));
+	})
+}
+
+#[test]
+fn timed_out_transfers_wait_for_governance() {
+	new_test_ext().execute_with(|| {
+		transfer_out(STND_RESOURCE, 1_000);
+		transfer_out(STND_RESOURCE, 2_000);
+
+		// Timed out transfers are not refunded
+		ChainBridge::on_initialize(101);
+		assert_eq!(BridgeTransfer::outbound_transfer(DEST_ID, 1), None);
+		assert!(BridgeTransfer::timed_out_transfer(DEST_ID, 1).is_some());
+		assert_eq!(Reserves::reserved_balance(STND, &ALICE), 3_000);
+		assert_eq!(BridgeTransfer::total_locked(STND_RESOURCE), 3_000);
+		assert_eq!(
+			last_event(),
+			Event::BridgeTransfer(BridgeTransferEvent::TransferTimedOut(DEST_ID, 2))
+		);
+
+		assert_noop!(
+			BridgeTransfer::resolve_timed_out(Origin::signed(ALICE), DEST_ID, 1, false),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(BridgeTransfer::resolve_timed_out(Origin::root(), DEST_ID, 1, false));
+		assert_eq!(Reserves::reserved_balance(STND, &ALICE), 2_000);
+		assert_eq!(BridgeTransfer::total_locked(STND_RESOURCE), 2_000);
+		assert_noop!(
+			BridgeTransfer::resolve_timed_out(Origin::root(), DEST_ID, 1, false),
+			Error::<Test>::TimedOutTransferNotFound
+		);
+
+		// Executed transfers are locked
+		assert_ok!(BridgeTransfer::resolve_timed_out(Origin::root(), DEST_ID, 2, true));
+		assert_eq!(BridgeTransfer::timed_out_transfer(DEST_ID, 2), None);
+		assert_eq!(Reserves::reserved_balance(STND, &ALICE), 0);
+		assert_eq!(Assets::balance(STND, &ALICE), 9_998_000);
+		assert_eq!(Assets::balance(STND, &BridgeTransfer::account_id()), 2_000);
+		assert_eq!(BridgeTransfer::total_locked(STND_RESOURCE), 2_000);
+		assert_eq!(
+			last_event(),
+			Event::BridgeTransfer(BridgeTransferEvent::TimedOutResolved(DEST_ID, 2, true))
+		);
+	})
+}
+
+#[test]
+fn transfer_in_pays_out_without_dust() {
+	new_test_ext().execute_with(|| {
+		let bridge = ChainBridge::account_id();
+		transfer_out(STND_RESOURCE, 5_000);
+		acknowledge(1, true);
+		assert_noop!(
+			BridgeTransfer::transfer_in(
+				Origin::signed(ALICE),
+				3,
+				U256::from(1_000_000_000u128),
+				STND_RESOURCE
+			),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			BridgeTransfer::transfer_in(
+				Origin::signed(bridge),
+				3,
+				U256::from(999_999),
+				STND_RESOURCE
+			),
+			Error::<Test>::AmountTooSmall
+		);
+		assert_noop!(
+			BridgeTransfer::transfer_in(Origin::signed(bridge), 3, U256::MAX, STND_RESOURCE),
+			Error::<Test>::AmountTooLarge
+		);
+
+		assert_ok!(BridgeTransfer::transfer_in(
+			Origin::signed(bridge),
+			3,
+			U256::from(1_500_000_123u128),
+			STND_RESOURCE
+		));
+		assert_eq!(Assets::balance(STND, &3), 1_500);
+		assert_eq!(Assets::balance(STND, &BridgeTransfer::account_id()), 3_500);
+		assert_eq!(
+			last_event(),
+			Event::BridgeTransfer(BridgeTransferEvent::TransferredIn(
+				STND_RESOURCE,
+				3,
+				STND,
+				1_500,
+				U256::from(123)
+			))
+		);
+	})
+}
+
+#[test]
+fn owners_propose_assets_for_governance() {
+	new_test_ext().execute_with(|| {
+		let resource_id = BridgeTransfer::asset_resource_id(DOT);
+		assert_eq!(resource_id, derive_resource_id(5, &DOT.encode()));
+		assert_noop!(
+			BridgeTransfer::propose_asset(
+				Origin::signed(ALICE),
+				DOT,
+				BridgeMode::LockRelease,
+				10,
+				18
+			),
+			Error::<Test>::NotAssetOwner
+		);
+		assert_noop!(
+			BridgeTransfer::propose_asset(Origin::signed(BOB), DOT, BridgeMode::LockRelease, 0, 39),
+			Error::<Test>::InvalidDecimals
+		);
+
+		assert_ok!(BridgeTransfer::propose_asset(
+			Origin::signed(BOB),
+			DOT,
+			BridgeMode::LockRelease,
+			10,
+			18
+		));
+		let bridged = BridgedAsset {
+			asset: DOT,
+			mode: BridgeMode::LockRelease,
+			decimals: 10,
+			remote_decimals: 18,
+		};
+		assert_eq!(
+			BridgeTransfer::asset_proposal(resource_id),
+			Some(AssetProposal { proposer: BOB, bridged: bridged.clone() })
+		);
+		assert_eq!(
+			last_event(),
+			Event::BridgeTransfer(BridgeTransferEvent::AssetProposed(resource_id, DOT, BOB))
+		);
+		assert_noop!(
+			BridgeTransfer::propose_asset(Origin::signed(BOB), DOT, BridgeMode::MintBurn, 10, 18),
+			Error::<Test>::AssetAlreadyProposed
+		);
+
+		assert_noop!(
+			BridgeTransfer::approve_asset(Origin::signed(BOB), resource_id),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(BridgeTransfer::approve_asset(Origin::root(), resource_id));
+		assert_eq!(BridgeTransfer::bridged_asset(resource_id), Some(bridged));
+		assert_eq!(BridgeTransfer::asset_proposal(resource_id), None);
+		// Relayers find the resource in chainbridge
+		assert_eq!(
+			ChainBridge::resources(resource_id),
+			Some(b"BridgeTransfer.transfer_in".to_vec())
+		);
+		assert_noop!(
+			BridgeTransfer::propose_asset(
+				Origin::signed(BOB),
+				DOT,
+				BridgeMode::LockRelease,
+				10,
+				18
+			),
+			Error::<Test>::AssetAlreadyRegistered
+		);
+	})
+}
+
+#[test]
+fn governance_rejects_asset_proposals() {
+	new_test_ext().execute_with(|| {
+		let resource_id = BridgeTransfer::asset_resource_id(WETH);
+		assert_noop!(
+			BridgeTransfer::reject_asset(Origin::root(), resource_id),
+			Error::<Test>::AssetProposalNotFound
+		);
+		assert_ok!(BridgeTransfer::propose_asset(
+			Origin::signed(ALICE),
+			WETH,
+			BridgeMode::MintBurn,
+			12,
+			18
+		));
+
+		assert_noop!(
+			BridgeTransfer::reject_asset(Origin::signed(ALICE), resource_id),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(BridgeTransfer::reject_asset(Origin::root(), resource_id));
+		assert_eq!(BridgeTransfer::asset_proposal(resource_id), None);
+		assert_eq!(
+			last_event(),
+			Event::BridgeTransfer(BridgeTransferEvent::AssetProposalRejected(resource_id))
+		);
+		assert_noop!(
+			BridgeTransfer::approve_asset(Origin::root(), resource_id),
+			Error::<Test>::AssetProposalNotFound
+		);
+	})
+}
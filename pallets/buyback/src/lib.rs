@@ -0,0 +1,215 @@
+// Ensure we're `no_std` when compiling for Wasm.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Buyback Pallet
+//!
+//! Buys back the governance token on the Standard market with the fees accumulated by the
+//! treasury, and burns it.
+//!
+//! Governance sets a `BuybackPolicy` for each fee asset spent. Every `BuybackPeriod` blocks
+//! `on_initialize` swaps `share` of the treasury balance of the asset for `BuybackAsset`, at most
+//! `era_cap` of the asset per era of `EraFinder`, and burns the bought tokens. A buyback fails
+//! without any effect when the market pays more than `max_slippage` less than the market TWAP,
+//! so that a manipulated pool can not drain the treasury.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{
+	ensure,
+	traits::tokens::fungibles::{Inspect, Mutate},
+	transactional,
+};
+use pallet_standard_market as market;
+use primitives::{traits::EraFinder, AssetId, Balance, EraIndex};
+use scale_info::TypeInfo;
+use sp_runtime::{traits::Zero, DispatchError, FixedPointNumber, Perbill, RuntimeDebug};
+
+pub use pallet::*;
+
+type AssetsOf<T> = <T as market::Config>::Assets;
+
+/// How much of a fee asset is spent on buybacks.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct BuybackPolicy {
+	/// Share of the treasury balance of the asset spent by each buyback
+	pub share: Perbill,
+	/// Maximum amount of the asset spent in an era
+	pub era_cap: Balance,
+	/// Maximum shortfall of the bought amount from the amount at the market TWAP
+	pub max_slippage: Perbill,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + market::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// Origin allowed to set the buyback policies
+		type GovernanceOrigin: EnsureOrigin<Self::Origin>;
+		/// Account whose fees are spent on buybacks
+		type Treasury: Get<Self::AccountId>;
+		/// Governance token bought back and burned
+		#[pallet::constant]
+		type BuybackAsset: Get<AssetId>;
+		/// Number of blocks between buybacks
+		#[pallet::constant]
+		type BuybackPeriod: Get<Self::BlockNumber>;
+		/// Eras the spend caps of the policies apply to
+		type EraFinder: EraFinder;
+		/// Maximum number of assets spent on buybacks
+		#[pallet::constant]
+		type MaxPolicies: Get<u32>;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Policy of an asset is set (asset, policy)
+		PolicySet(AssetId, BuybackPolicy),
+		/// Asset is no longer spent on buybacks (asset)
+		PolicyRemoved(AssetId),
+		/// Governance token is bought back and burned (asset, spent, burned)
+		BoughtBack(AssetId, Balance, Balance),
+		/// Buyback failed and is retried in the next period (asset, amount, error)
+		BuybackFailed(AssetId, Balance, DispatchError),
+		/// Spend cap of an asset is used up for the era (asset, era)
+		EraCapReached(AssetId, EraIndex),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Asset is the governance token or the market has no pool with it
+		InvalidAsset,
+		/// `MaxPolicies` assets are already spent on buybacks
+		TooManyPolicies,
+		/// Asset has no policy
+		PolicyNotFound,
+		/// Market has no TWAP for the pair to bound the slippage
+		NoReferencePrice,
+		/// Market pays more than the maximum slippage less than the TWAP
+		SlippageExceeded,
+	}
+
+	#[pallet::storage]
+	#[pallet::getter(fn policy)]
+	/// Policies of the assets spent on buybacks
+	pub type Policies<T> = CountedStorageMap<_, Twox64Concat, AssetId, BuybackPolicy>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn era_spent)]
+	/// Amount of each asset spent in an era, as (era, spent)
+	pub type EraSpent<T> = StorageMap<_, Twox64Concat, AssetId, (EraIndex, Balance), ValueQuery>;
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let period = T::BuybackPeriod::get();
+			if period.is_zero() || !(now % period).is_zero() {
+				return 0
+			}
+			let mut count = 0;
+			for (asset, policy) in Policies::<T>::iter() {
+				count += 1;
+				Self::buy_back(asset, policy);
+			}
+			// Reading the policy and the spend, and the swap and burn
+			T::DbWeight::get().reads_writes(1 + 12 * count, 10 * count)
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Spends `asset` of the treasury on buybacks as set by `policy`.
+		///
+		/// The dispatch origin must be `GovernanceOrigin`.
+		#[pallet::weight(T::DbWeight::get().reads_writes(3, 2))]
+		pub fn set_policy(
+			origin: OriginFor<T>,
+			asset: AssetId,
+			policy: BuybackPolicy,
+		) -> DispatchResult {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			let token = T::BuybackAsset::get();
+			ensure!(
+				asset != token && market::Module::<T>::pair((asset, token)).is_some(),
+				Error::<T>::InvalidAsset
+			);
+			ensure!(
+				Policies::<T>::contains_key(asset) ||
+					Policies::<T>::count() < T::MaxPolicies::get(),
+				Error::<T>::TooManyPolicies
+			);
+
+			Policies::<T>::insert(asset, policy);
+			Self::deposit_event(Event::PolicySet(asset, policy));
+			Ok(())
+		}
+
+		/// Stops spending `asset` on buybacks.
+		///
+		/// The dispatch origin must be `GovernanceOrigin`.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 2))]
+		pub fn remove_policy(origin: OriginFor<T>, asset: AssetId) -> DispatchResult {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			ensure!(Policies::<T>::contains_key(asset), Error::<T>::PolicyNotFound);
+
+			Policies::<T>::remove(asset);
+			EraSpent::<T>::remove(asset);
+			Self::deposit_event(Event::PolicyRemoved(asset));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	// Spends the share of the treasury balance of `asset` allowed by the cap of the era
+	fn buy_back(asset: AssetId, policy: BuybackPolicy) {
+		let era = T::EraFinder::active_era();
+		let spent = match Self::era_spent(asset) {
+			(spent_era, spent) if spent_era == era => spent,
+			_ => 0,
+		};
+		let available = AssetsOf::<T>::reducible_balance(asset, &T::Treasury::get(), true);
+		let amount_in = (policy.share * available).min(policy.era_cap.saturating_sub(spent));
+		if amount_in.is_zero() {
+			if spent >= policy.era_cap {
+				Self::deposit_event(Event::EraCapReached(asset, era));
+			}
+			return
+		}
+
+		match Self::swap_and_burn(asset, amount_in, policy.max_slippage) {
+			Ok(burned) => {
+				EraSpent::<T>::insert(asset, (era, spent + amount_in));
+				Self::deposit_event(Event::BoughtBack(asset, amount_in, burned));
+			},
+			Err(error) => Self::deposit_event(Event::BuybackFailed(asset, amount_in, error)),
+		}
+	}
+
+	// Swaps `amount_in` of `asset` of the treasury for the governance token unless the market
+	// pays more than `max_slippage` less than its TWAP, and burns the tokens bought
+	#[transactional]
+	fn swap_and_burn(
+		asset: AssetId,
+		amount_in: Balance,
+		max_slippage: Perbill,
+	) -> Result<Balance, DispatchError> {
+		let token = T::BuybackAsset::get();
+		let twap = market::Module::<T>::twap(asset, token).ok_or(Error::<T>::NoReferencePrice)?;
+		let expected = twap.saturating_mul_int(amount_in);
+		let quoted = market::Module::<T>::quote(asset, amount_in, token)?;
+		ensure!(quoted >= expected - max_slippage * expected, Error::<T>::SlippageExceeded);
+
+		let treasury = T::Treasury::get();
+		let bought = market::Module::<T>::do_swap(&treasury, asset, amount_in, token, true)?;
+		AssetsOf::<T>::burn_from(token, &treasury, bought)
+	}
+}
@@ -0,0 +1,56 @@
+//! RPC methods exposing the bridge configuration, so that relayers can bootstrap from chain state
+//! instead of static config files.
+
+use std::sync::Arc;
+
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use primitives::{bridge::BridgeCatalogue, BlockNumber, ChainBridgeApi as ChainBridgeRuntimeApi};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+#[rpc]
+pub trait ChainBridgeApi<BlockHash, AccountId> {
+	/// Resources, whitelisted chains with their nonces, relayer set and threshold
+	#[rpc(name = "chainBridge_catalogue")]
+	fn catalogue(&self, at: Option<BlockHash>) -> Result<BridgeCatalogue<AccountId>>;
+}
+
+/// Bridge RPC methods backed by the `ChainBridgeApi` of the runtime.
+pub struct ChainBridge<C, B> {
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> ChainBridge<C, B> {
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+fn runtime_error(err: impl std::fmt::Debug) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(1),
+		message: "Unable to query the bridge.".into(),
+		data: Some(format!("{:?}", err).into()),
+	}
+}
+
+impl<C, Block, AccountId> ChainBridgeApi<<Block as BlockT>::Hash, AccountId>
+	for ChainBridge<C, Block>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: ChainBridgeRuntimeApi<Block, AccountId, BlockNumber>,
+	AccountId: Codec,
+{
+	fn catalogue(
+		&self,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<BridgeCatalogue<AccountId>> {
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		self.client.runtime_api().catalogue(&at).map_err(runtime_error)
+	}
+}
@@ -11,22 +11,31 @@ pub use pallet::*;
 #[frame_support::pallet]
 pub mod pallet {
 	use codec::{Decode, Encode, EncodeLike};
+	use frame_support::transactional;
 	pub use frame_support::{
-		pallet_prelude::*, traits::StorageVersion, weights::GetDispatchInfo, PalletId, Parameter,
+		pallet_prelude::*,
+		traits::{CallMetadata, GetCallMetadata, StorageVersion},
+		weights::GetDispatchInfo,
+		PalletId, Parameter,
 	};
 	use frame_system::{self as system, pallet_prelude::*};
+	pub use primitives::bridge::{
+		BridgeCatalogue, BridgeChain, BridgeChainId, DepositNonce, ResourceId, TransferReceipt,
+	};
 	use scale_info::TypeInfo;
-	pub use sp_core::U256;
-	use sp_runtime::traits::{AccountIdConversion, Dispatchable};
+	pub use sp_core::{H256, U256};
+	use sp_runtime::{
+		traits::{AccountIdConversion, Dispatchable},
+		PerThing, Perbill,
+	};
 	use sp_std::prelude::*;
 
 	const DEFAULT_RELAYER_THRESHOLD: u32 = 1;
+	const DEFAULT_RELAYER_WEIGHT: u32 = 1;
+	/// Number of nonces tracked by each `ResolvedNonces` entry
+	const NONCE_BITS: DepositNonce = 64;
 	const PALLET_ID: PalletId = PalletId(*b"stnd/cbg");
 
-	pub type BridgeChainId = u8;
-	pub type DepositNonce = u64;
-	pub type ResourceId = [u8; 32];
-
 	/// Helper function to concatenate a chain ID and some bytes to produce a resource ID.
 	/// The common format is (31 bytes unique ID + 1 byte chain ID).
 	pub fn derive_resource_id(chain: u8, id: &[u8]) -> ResourceId {
@@ -58,10 +67,26 @@ pub mod pallet {
 		/// Attempts to mark the proposal as approve or rejected.
 		/// Returns true if the status changes from active.
 		pub fn try_to_complete(&mut self, threshold: u32, total: u32) -> ProposalStatus {
-			if self.votes_for.len() >= threshold as usize {
+			self.try_to_complete_weighted(|_| 1, threshold, total)
+		}
+
+		/// Attempts to mark the proposal as approve or rejected, counting each vote with the
+		/// weight of the voter. `threshold` and `total` are vote weights.
+		pub fn try_to_complete_weighted(
+			&mut self,
+			weight_of: impl Fn(&A) -> u32,
+			threshold: u32,
+			total: u32,
+		) -> ProposalStatus {
+			let weight = |votes: &Vec<A>| {
+				votes.iter().fold(0u32, |acc, who| acc.saturating_add(weight_of(who)))
+			};
+			if weight(&self.votes_for) >= threshold {
 				self.status = ProposalStatus::Approved;
 				ProposalStatus::Approved
-			} else if total >= threshold && self.votes_against.len() as u32 + threshold > total {
+			} else if total >= threshold &&
+				weight(&self.votes_against).saturating_add(threshold) > total
+			{
 				self.status = ProposalStatus::Rejected;
 				ProposalStatus::Rejected
 			} else {
@@ -96,7 +121,112 @@ pub mod pallet {
 		}
 	}
 
-	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+	/// Format of the account addresses on a bridged chain.
+	#[derive(PartialEq, Eq, Clone, Copy, Encode, Decode, RuntimeDebug, TypeInfo)]
+	pub enum AddressFormat {
+		/// 20 byte addresses
+		Ethereum,
+		/// 32 byte account ids
+		Substrate,
+		/// Addresses of the given byte length
+		Other(u32),
+	}
+
+	impl AddressFormat {
+		/// Byte length of an address in this format.
+		pub fn address_len(&self) -> u32 {
+			match self {
+				AddressFormat::Ethereum => 20,
+				AddressFormat::Substrate => 32,
+				AddressFormat::Other(len) => *len,
+			}
+		}
+
+		/// Returns true if `address` is well formed.
+		pub fn is_valid(&self, address: &[u8]) -> bool {
+			address.len() == self.address_len() as usize
+		}
+	}
+
+	/// Metadata of a bridged chain.
+	#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
+	pub struct ChainInfo {
+		pub name: Vec<u8>,
+		pub address_format: AddressFormat,
+		/// Blocks the relayers wait for before considering a deposit final
+		pub confirmations: u32,
+		/// Resource the relayer fees are paid in
+		pub fee_asset: ResourceId,
+	}
+
+	/// Result of an outbound transfer on the destination chain.
+	#[derive(PartialEq, Eq, Clone, Copy, Encode, Decode, RuntimeDebug, TypeInfo)]
+	pub enum TransferOutcome {
+		/// Relayers reported the transfer as executed
+		Succeeded,
+		/// Relayers reported the transfer as failed
+		Failed,
+		/// Relayers did not agree on a result within `TransferLifetime`. The result is unknown,
+		/// the transfer may still have been executed on the destination chain.
+		TimedOut,
+	}
+
+	/// Notified once the result of an outbound fungible transfer is known, so that the pallet
+	/// which initiated it can e.g. refund the sender of a failed transfer.
+	///
+	/// Only `Failed` means the transfer did not happen. A `TimedOut` transfer must not be
+	/// refunded before its result has been settled some other way.
+	pub trait BridgeCallback {
+		/// Called with the result of the transfer `nonce` to `dest_id`.
+		fn on_transfer_result(
+			dest_id: BridgeChainId,
+			nonce: DepositNonce,
+			resource_id: ResourceId,
+			outcome: TransferOutcome,
+		);
+	}
+
+	impl BridgeCallback for () {
+		fn on_transfer_result(
+			_dest_id: BridgeChainId,
+			_nonce: DepositNonce,
+			_resource_id: ResourceId,
+			_outcome: TransferOutcome,
+		) {
+		}
+	}
+
+	/// Outbound fungible transfer waiting for the relayers to report its result. Votes in
+	/// favour report success, votes against report failure.
+	#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
+	pub struct PendingTransfer<AccountId, BlockNumber> {
+		pub resource_id: ResourceId,
+		pub votes: ProposalVotes<AccountId, BlockNumber>,
+	}
+
+	/// Relayer set and threshold as of a change, see `RelayerSetSnapshot`.
+	#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
+	pub struct RelayerSnapshot<BlockNumber> {
+		/// Blake2-256 hash of the relayers with their weights and the threshold weight
+		pub hash: H256,
+		/// Number of relayers in set
+		pub count: u32,
+		/// Block the change happened in
+		pub block_number: BlockNumber,
+	}
+
+	/// Delivery state of a source chain whose proposals are applied in nonce order.
+	#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
+	pub struct OrderedDelivery<BlockNumber> {
+		/// Next nonce to apply. Resolved proposals above it wait in `DeliveryQueue`.
+		pub next_nonce: DepositNonce,
+		/// Number of proposals in `DeliveryQueue`
+		pub queued: u32,
+		/// Block since which proposals are queued without `next_nonce` being applied
+		pub stalled_since: Option<BlockNumber>,
+	}
+
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(3);
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
@@ -107,13 +237,18 @@ pub mod pallet {
 	#[pallet::config]
 	pub trait Config: frame_system::Config {
 		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
-		/// Origin used to administer the pallet
-		type AdminOrigin: EnsureOrigin<Self::Origin>;
-		/// Proposed dispatchable call
+		/// Origin managing the relayer set, the relayer weights and the threshold
+		type RelayerAdminOrigin: EnsureOrigin<Self::Origin>;
+		/// Origin managing the resources and the whitelisted chains
+		type ResourceAdminOrigin: EnsureOrigin<Self::Origin>;
+		/// Origin pausing and resuming the bridge
+		type PauseOrigin: EnsureOrigin<Self::Origin>;
+		/// Proposed dispatchable call, limited to the calls in `AllowedProposals`
 		type Proposal: Parameter
 			+ Dispatchable<Origin = Self::Origin>
 			+ EncodeLike
-			+ GetDispatchInfo;
+			+ GetDispatchInfo
+			+ GetCallMetadata;
 		/// The identifier for this chain.
 		/// This must be unique and must not collide with existing IDs within a set of bridged
 		/// chains.
@@ -122,6 +257,40 @@ pub mod pallet {
 
 		#[pallet::constant]
 		type ProposalLifetime: Get<Self::BlockNumber>;
+
+		/// Notified of the result of outbound fungible transfers
+		type TransferCallback: BridgeCallback;
+
+		/// Number of blocks the relayers have to report the result of an outbound transfer
+		#[pallet::constant]
+		type TransferLifetime: Get<Self::BlockNumber>;
+
+		/// Number of nonces below the highest resolved nonce of a source chain which can still be
+		/// proposed. Older nonces are rejected.
+		#[pallet::constant]
+		type NonceWindow: Get<DepositNonce>;
+
+		/// Number of relayer set snapshots kept in storage
+		#[pallet::constant]
+		type MaxRelayerSnapshots: Get<u32>;
+
+		/// Number of proposal votes a relayer can cast per block
+		#[pallet::constant]
+		type MaxVotesPerBlock: Get<u32>;
+
+		/// Calls allowed as proposals when the whitelist is introduced by the upgrade to storage
+		/// version 3, as (pallet_name, function_name)
+		type DefaultAllowedProposals: Get<Vec<(Vec<u8>, Vec<u8>)>>;
+
+		/// Number of resolved proposals which can wait for earlier nonces of a source chain with
+		/// ordered delivery
+		#[pallet::constant]
+		type MaxQueuedProposals: Get<u32>;
+
+		/// Number of blocks a source chain with ordered delivery has to be stalled before its next
+		/// nonce can be skipped
+		#[pallet::constant]
+		type DeliveryQueueTimeout: Get<Self::BlockNumber>;
 	}
 
 	#[pallet::event]
@@ -131,18 +300,35 @@ pub mod pallet {
 		RelayerThresholdChanged(u32),
 		/// Chain now available for transfers (chain_id)
 		ChainWhitelisted(BridgeChainId),
+		/// Chain metadata has been set (chain_id)
+		ChainInfoSet(BridgeChainId),
+		/// Chain metadata has been removed (chain_id)
+		ChainInfoRemoved(BridgeChainId),
 		/// Relayer added to set
 		RelayerAdded(T::AccountId),
 		/// Relayer removed from set
 		RelayerRemoved(T::AccountId),
+		/// Vote weight of a relayer has changed (relayer, weight)
+		RelayerWeightChanged(T::AccountId, u32),
+		/// Share of the total relayer weight required for proposals has changed (ratio)
+		RelayerThresholdRatioChanged(Option<Perbill>),
 		/// FunglibleTransfer is for relaying fungibles (dest_id, nonce, resource_id, amount,
-		/// recipient, metadata)
-		FungibleTransfer(BridgeChainId, DepositNonce, ResourceId, U256, Vec<u8>),
+		/// recipient, sender)
+		FungibleTransfer(BridgeChainId, DepositNonce, ResourceId, U256, Vec<u8>, T::AccountId),
 		/// NonFungibleTransfer is for relaying NFTS (dest_id, nonce, resource_id, token_id,
-		/// recipient, metadata)
-		NonFungibleTransfer(BridgeChainId, DepositNonce, ResourceId, Vec<u8>, Vec<u8>, Vec<u8>),
-		/// GenericTransfer is for a generic data payload (dest_id, nonce, resource_id, metadata)
-		GenericTransfer(BridgeChainId, DepositNonce, ResourceId, Vec<u8>),
+		/// recipient, metadata, sender)
+		NonFungibleTransfer(
+			BridgeChainId,
+			DepositNonce,
+			ResourceId,
+			Vec<u8>,
+			Vec<u8>,
+			Vec<u8>,
+			T::AccountId,
+		),
+		/// GenericTransfer is for a generic data payload (dest_id, nonce, resource_id, metadata,
+		/// sender)
+		GenericTransfer(BridgeChainId, DepositNonce, ResourceId, Vec<u8>, T::AccountId),
 		/// Vote submitted in favour of proposal
 		VoteFor(BridgeChainId, DepositNonce, T::AccountId),
 		/// Vot submitted against proposal
@@ -155,6 +341,30 @@ pub mod pallet {
 		ProposalSucceeded(BridgeChainId, DepositNonce),
 		/// Execution of call failed
 		ProposalFailed(BridgeChainId, DepositNonce),
+		/// Vote submitted on the result of an outbound transfer (dest_id, nonce, relayer,
+		/// succeeded)
+		TransferResultVoted(BridgeChainId, DepositNonce, T::AccountId, bool),
+		/// Result of an outbound transfer is known (dest_id, nonce, outcome)
+		TransferResolved(BridgeChainId, DepositNonce, TransferOutcome),
+		/// Next nonce expected from a source chain (src_id, nonce)
+		ExpectedNonce(BridgeChainId, DepositNonce),
+		/// Bridge is paused
+		BridgePaused,
+		/// Bridge is resumed
+		BridgeResumed,
+		/// Relayer set, a relayer weight or the threshold has changed (hash, relayer_count). See
+		/// `RelayerSnapshot` for the hashed data.
+		RelayerSetSnapshot(H256, u32),
+		/// Call can be proposed by the relayers (pallet_name, function_name)
+		ProposalCallAllowed(Vec<u8>, Vec<u8>),
+		/// Call can no longer be proposed by the relayers (pallet_name, function_name)
+		ProposalCallDisallowed(Vec<u8>, Vec<u8>),
+		/// Proposals of a source chain are applied in nonce order or not (src_id, ordered)
+		OrderedDeliverySet(BridgeChainId, bool),
+		/// Resolved proposal waits for earlier nonces (src_id, nonce)
+		ProposalQueued(BridgeChainId, DepositNonce),
+		/// Nonce is skipped by the ordered delivery of a source chain (src_id, nonce)
+		DeliveryNonceSkipped(BridgeChainId, DepositNonce),
 	}
 
 	#[pallet::error]
@@ -165,6 +375,8 @@ pub mod pallet {
 		InvalidBridgeChainId,
 		/// Relayer threshold cannot be 0
 		InvalidThreshold,
+		/// Relayer weight cannot be 0
+		InvalidRelayerWeight,
 		/// Interactions with this chain is not permitted
 		ChainNotWhitelisted,
 		/// Chain has already been enabled
@@ -189,6 +401,34 @@ pub mod pallet {
 		ProposalAlreadyComplete,
 		/// Lifetime of proposal has been exceeded
 		ProposalExpired,
+		/// No pending outbound transfer with the nonce was found
+		TransferDoesNotExist,
+		/// Recipient is not a valid address on the destination chain
+		InvalidRecipient,
+		/// A proposal with this nonce has already been approved or rejected
+		NonceAlreadyResolved,
+		/// Nonce is older than the window of tracked nonces
+		NonceTooOld,
+		/// Relayer has cast `MaxVotesPerBlock` proposal votes in this block
+		TooManyVotes,
+		/// Bridge is paused
+		BridgeIsPaused,
+		/// Proposed call is not in `AllowedProposals`
+		ProposalNotAllowed,
+		/// Call can already be proposed
+		ProposalCallAlreadyAllowed,
+		/// Call can not be proposed
+		ProposalCallNotAllowed,
+		/// Proposals of the source chain are already applied in the requested order
+		OrderedDeliveryUnchanged,
+		/// Source chain does not have ordered delivery
+		OrderedDeliveryNotEnabled,
+		/// `MaxQueuedProposals` proposals already wait for earlier nonces
+		DeliveryQueueFull,
+		/// Ordered delivery cannot be disabled while proposals wait for earlier nonces
+		DeliveryQueueNotEmpty,
+		/// Source chain has not been stalled for `DeliveryQueueTimeout`
+		DeliveryNotStalled,
 	}
 
 	#[pallet::storage]
@@ -217,6 +457,27 @@ pub mod pallet {
 	/// Number of relayers in set
 	pub(super) type RelayerCount<T> = StorageValue<_, u32, ValueQuery>;
 
+	#[pallet::type_value]
+	pub(super) fn DefaultRelayerWeight<T: Config>() -> u32 {
+		DEFAULT_RELAYER_WEIGHT
+	}
+	#[pallet::storage]
+	#[pallet::getter(fn relayer_weight)]
+	/// Weight of the votes of each relayer
+	pub(super) type RelayerWeights<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery, DefaultRelayerWeight<T>>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn total_relayer_weight)]
+	/// Sum of the weights of the relayers in set
+	pub(super) type TotalRelayerWeight<T> = StorageValue<_, u32, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn relayer_threshold_ratio)]
+	/// Share of the total relayer weight required for a proposal to execute. Falls back to
+	/// `RelayerThreshold` when not set.
+	pub(super) type RelayerThresholdRatio<T> = StorageValue<_, Perbill>;
+
 	#[pallet::storage]
 	#[pallet::getter(fn votes)]
 	/// All known proposals.
@@ -235,6 +496,146 @@ pub mod pallet {
 	/// Utilized by the bridge software to map resource IDs to actual methods
 	pub(super) type Resources<T> = StorageMap<_, Blake2_128Concat, ResourceId, Vec<u8>>;
 
+	#[pallet::storage]
+	#[pallet::getter(fn resolved_nonces)]
+	/// Bitmaps of the approved or rejected nonces of each source chain, `NONCE_BITS` nonces per
+	/// entry. Entries below the nonce window are pruned.
+	pub(super) type ResolvedNonces<T> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		BridgeChainId,
+		Twox64Concat,
+		DepositNonce,
+		u64,
+		ValueQuery,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn highest_resolved_nonce)]
+	/// Highest approved or rejected nonce of each source chain
+	pub(super) type HighestResolvedNonce<T> =
+		StorageMap<_, Blake2_128Concat, BridgeChainId, DepositNonce, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn chain_info)]
+	/// Metadata of whitelisted chains
+	pub(super) type ChainInfos<T> = StorageMap<_, Blake2_128Concat, BridgeChainId, ChainInfo>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn transfer_receipts)]
+	/// Receipts of the outbound fungible transfers keyed by destination chain and nonce
+	pub(super) type TransferReceipts<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		BridgeChainId,
+		Twox64Concat,
+		DepositNonce,
+		TransferReceipt<T::AccountId, T::BlockNumber>,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn pending_transfers)]
+	/// Outbound fungible transfers whose result has not been reported yet
+	pub(super) type PendingTransfers<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		BridgeChainId,
+		Blake2_128Concat,
+		DepositNonce,
+		PendingTransfer<T::AccountId, T::BlockNumber>,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn is_paused)]
+	/// Whether transfers and proposals are halted
+	pub(super) type Paused<T> = StorageValue<_, bool, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn relayer_votes)]
+	/// Proposal votes of each relayer in the block they were last cast in
+	pub(super) type RelayerVotes<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, (T::BlockNumber, u32), ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn relayer_snapshots)]
+	/// Last `MaxRelayerSnapshots` snapshots of the relayer set, oldest first
+	pub(super) type RelayerSnapshots<T: Config> =
+		StorageValue<_, Vec<RelayerSnapshot<T::BlockNumber>>, ValueQuery>;
+
+	#[pallet::storage]
+	/// Pending transfers timing out at the given block
+	pub(super) type TransferExpiries<T: Config> =
+		StorageMap<_, Twox64Concat, T::BlockNumber, Vec<(BridgeChainId, DepositNonce)>, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn allowed_proposals)]
+	/// Calls the relayers can propose, keyed by pallet name and call name
+	pub(super) type AllowedProposals<T> =
+		StorageDoubleMap<_, Blake2_128Concat, Vec<u8>, Blake2_128Concat, Vec<u8>, bool, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn ordered_delivery)]
+	/// Source chains whose proposals are applied in nonce order
+	pub(super) type OrderedDeliveries<T: Config> =
+		StorageMap<_, Blake2_128Concat, BridgeChainId, OrderedDelivery<T::BlockNumber>>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn queued_proposal)]
+	/// Resolved proposals waiting for earlier nonces, `None` for rejected proposals
+	pub(super) type DeliveryQueue<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		BridgeChainId,
+		Twox64Concat,
+		DepositNonce,
+		Option<T::Proposal>,
+	>;
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let version = StorageVersion::get::<Pallet<T>>();
+			if version >= STORAGE_VERSION {
+				return T::DbWeight::get().reads(1)
+			}
+			let mut weight = T::DbWeight::get().reads_writes(1, 1);
+			if version < 2 {
+				// Relayers registered so far all have the default weight.
+				<TotalRelayerWeight<T>>::put(<RelayerCount<T>>::get() * DEFAULT_RELAYER_WEIGHT);
+				weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 1));
+			}
+			if version < 3 {
+				// Any call could be proposed so far, keep the ones the runtime relies on.
+				let calls = T::DefaultAllowedProposals::get();
+				weight = weight.saturating_add(T::DbWeight::get().writes(calls.len() as Weight));
+				for (pallet_name, function_name) in calls {
+					<AllowedProposals<T>>::insert(pallet_name, function_name, true);
+				}
+			}
+			STORAGE_VERSION.put::<Pallet<T>>();
+			weight
+		}
+
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let expired = TransferExpiries::<T>::take(now);
+			let count = expired.len() as u64;
+			for (dest_id, nonce) in expired {
+				// Transfers the relayers agreed on have already been removed.
+				if let Some(transfer) = PendingTransfers::<T>::take(dest_id, nonce) {
+					Self::resolve_transfer(
+						dest_id,
+						nonce,
+						transfer.resource_id,
+						TransferOutcome::TimedOut,
+					);
+				}
+			}
+			T::DbWeight::get()
+				.reads_writes(1 + count, 1 + count)
+				.saturating_add(Self::drain_delivery_queues(now))
+		}
+	}
+
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
 		/// Sets the vote threshold for proposals.
@@ -247,10 +648,27 @@ pub mod pallet {
 		/// # </weight>
 		#[pallet::weight(195_000_000)]
 		pub fn set_threshold(origin: OriginFor<T>, threshold: u32) -> DispatchResult {
-			T::AdminOrigin::ensure_origin(origin)?;
+			Self::ensure_relayer_admin(origin)?;
 			Self::set_relayer_threshold(threshold)
 		}
 
+		/// Sets the share of the total relayer weight required for proposals.
+		///
+		/// Once set it takes precedence over the flat threshold set with `set_threshold`.
+		///
+		/// # <weight>
+		/// - O(1) write
+		/// # </weight>
+		#[pallet::weight(195_000_000)]
+		pub fn set_threshold_ratio(origin: OriginFor<T>, ratio: Option<Perbill>) -> DispatchResult {
+			Self::ensure_relayer_admin(origin)?;
+			ensure!(ratio != Some(Perbill::zero()), Error::<T>::InvalidThreshold);
+			<RelayerThresholdRatio<T>>::set(ratio);
+			Self::deposit_event(Event::RelayerThresholdRatioChanged(ratio));
+			Self::snapshot_relayer_set();
+			Ok(())
+		}
+
 		/// Stores a method name on chain under an associated resource ID.
 		///
 		/// # <weight>
@@ -262,7 +680,7 @@ pub mod pallet {
 			id: ResourceId,
 			method: Vec<u8>,
 		) -> DispatchResult {
-			T::AdminOrigin::ensure_origin(origin)?;
+			Self::ensure_resource_admin(origin)?;
 			Self::register_resource(id, method)
 		}
 
@@ -276,7 +694,7 @@ pub mod pallet {
 		/// # </weight>
 		#[pallet::weight(195_000_000)]
 		pub fn remove_resource(origin: OriginFor<T>, id: ResourceId) -> DispatchResult {
-			T::AdminOrigin::ensure_origin(origin)?;
+			Self::ensure_resource_admin(origin)?;
 			Self::unregister_resource(id)
 		}
 
@@ -287,10 +705,44 @@ pub mod pallet {
 		/// # </weight>
 		#[pallet::weight(195_000_000)]
 		pub fn whitelist_chain(origin: OriginFor<T>, id: BridgeChainId) -> DispatchResult {
-			T::AdminOrigin::ensure_origin(origin)?;
+			Self::ensure_resource_admin(origin)?;
 			Self::whitelist(id)
 		}
 
+		/// Sets the metadata of a whitelisted chain.
+		///
+		/// Outbound transfers to the chain are rejected unless the recipient matches its address
+		/// format.
+		///
+		/// # <weight>
+		/// - O(1) lookup and insert
+		/// # </weight>
+		#[pallet::weight(195_000_000)]
+		pub fn set_chain_info(
+			origin: OriginFor<T>,
+			id: BridgeChainId,
+			info: ChainInfo,
+		) -> DispatchResult {
+			Self::ensure_resource_admin(origin)?;
+			ensure!(Self::chain_whitelisted(id), Error::<T>::ChainNotWhitelisted);
+			<ChainInfos<T>>::insert(id, info);
+			Self::deposit_event(Event::ChainInfoSet(id));
+			Ok(())
+		}
+
+		/// Removes the metadata of a chain.
+		///
+		/// # <weight>
+		/// - O(1) removal
+		/// # </weight>
+		#[pallet::weight(195_000_000)]
+		pub fn remove_chain_info(origin: OriginFor<T>, id: BridgeChainId) -> DispatchResult {
+			Self::ensure_resource_admin(origin)?;
+			<ChainInfos<T>>::remove(id);
+			Self::deposit_event(Event::ChainInfoRemoved(id));
+			Ok(())
+		}
+
 		/// Adds a new relayer to the relayer set.
 		///
 		/// # <weight>
@@ -298,7 +750,7 @@ pub mod pallet {
 		/// # </weight>
 		#[pallet::weight(195_000_000)]
 		pub fn add_relayer(origin: OriginFor<T>, v: T::AccountId) -> DispatchResult {
-			T::AdminOrigin::ensure_origin(origin)?;
+			Self::ensure_relayer_admin(origin)?;
 			Self::register_relayer(v)
 		}
 
@@ -309,19 +761,65 @@ pub mod pallet {
 		/// # </weight>
 		#[pallet::weight(195_000_000)]
 		pub fn remove_relayer(origin: OriginFor<T>, v: T::AccountId) -> DispatchResult {
-			T::AdminOrigin::ensure_origin(origin)?;
+			Self::ensure_relayer_admin(origin)?;
 			Self::unregister_relayer(v)
 		}
 
+		/// Sets the weight of the votes of an existing relayer.
+		///
+		/// # <weight>
+		/// - O(1) lookup and insert
+		/// # </weight>
+		#[pallet::weight(195_000_000)]
+		pub fn set_relayer_weight(
+			origin: OriginFor<T>,
+			v: T::AccountId,
+			weight: u32,
+		) -> DispatchResult {
+			Self::ensure_relayer_admin(origin)?;
+			Self::update_relayer_weight(v, weight)
+		}
+
+		/// Halts outbound transfers and inbound proposals until the bridge is resumed.
+		///
+		/// Relayers can still report the results of transfers initiated before.
+		///
+		/// # <weight>
+		/// - O(1) write
+		/// # </weight>
+		#[pallet::weight(195_000_000)]
+		pub fn pause(origin: OriginFor<T>) -> DispatchResult {
+			Self::ensure_pause_admin(origin)?;
+			<Paused<T>>::put(true);
+			Self::deposit_event(Event::BridgePaused);
+			Ok(())
+		}
+
+		/// Resumes a paused bridge.
+		///
+		/// # <weight>
+		/// - O(1) write
+		/// # </weight>
+		#[pallet::weight(195_000_000)]
+		pub fn resume(origin: OriginFor<T>) -> DispatchResult {
+			Self::ensure_pause_admin(origin)?;
+			<Paused<T>>::put(false);
+			Self::deposit_event(Event::BridgeResumed);
+			Ok(())
+		}
+
 		/// Commits a vote in favour of the provided proposal.
 		///
 		/// If a proposal with the given nonce and source chain ID does not already exist, it will
 		/// be created with an initial vote in favour from the caller.
 		///
+		/// Operational, so that relayer traffic uses the reserved operational weight of the block
+		/// instead of crowding out user transactions. Limited to `MaxVotesPerBlock` per relayer.
+		///
 		/// # <weight>
 		/// - weight of proposed call, regardless of whether execution is performed
 		/// # </weight>
-		#[pallet::weight((call.get_dispatch_info().weight + 195_000_000, call.get_dispatch_info().class, Pays::Yes))]
+		#[pallet::weight((call.get_dispatch_info().weight + 195_000_000, DispatchClass::Operational, Pays::Yes))]
 		pub fn acknowledge_proposal(
 			origin: OriginFor<T>,
 			nonce: DepositNonce,
@@ -330,19 +828,29 @@ pub mod pallet {
 			call: Box<<T as Config>::Proposal>,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			ensure!(!Self::is_paused(), Error::<T>::BridgeIsPaused);
 			ensure!(Self::is_relayer(&who), Error::<T>::MustBeRelayer);
 			ensure!(Self::chain_whitelisted(src_id), Error::<T>::ChainNotWhitelisted);
 			ensure!(Self::resource_exists(r_id), Error::<T>::ResourceDoesNotExist);
-
-			Self::vote_for(who, nonce, src_id, call)
+			ensure!(Self::is_allowed_proposal(&call), Error::<T>::ProposalNotAllowed);
+			ensure!(
+				Self::votes_this_block(&who) < T::MaxVotesPerBlock::get(),
+				Error::<T>::TooManyVotes
+			);
+
+			Self::vote_for(who.clone(), nonce, src_id, call)?;
+			Self::note_vote(&who);
+			Ok(())
 		}
 
 		/// Commits a vote against a provided proposal.
 		///
+		/// Operational and limited to `MaxVotesPerBlock` per relayer, like `acknowledge_proposal`.
+		///
 		/// # <weight>
 		/// - Fixed, since execution of proposal should not be included
 		/// # </weight>
-		#[pallet::weight(195_000_000)]
+		#[pallet::weight((195_000_000, DispatchClass::Operational))]
 		pub fn reject_proposal(
 			origin: OriginFor<T>,
 			nonce: DepositNonce,
@@ -351,11 +859,18 @@ pub mod pallet {
 			call: Box<<T as Config>::Proposal>,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			ensure!(!Self::is_paused(), Error::<T>::BridgeIsPaused);
 			ensure!(Self::is_relayer(&who), Error::<T>::MustBeRelayer);
 			ensure!(Self::chain_whitelisted(src_id), Error::<T>::ChainNotWhitelisted);
 			ensure!(Self::resource_exists(r_id), Error::<T>::ResourceDoesNotExist);
+			ensure!(
+				Self::votes_this_block(&who) < T::MaxVotesPerBlock::get(),
+				Error::<T>::TooManyVotes
+			);
 
-			Self::vote_against(who, nonce, src_id, call)
+			Self::vote_against(who.clone(), nonce, src_id, call)?;
+			Self::note_vote(&who);
+			Ok(())
 		}
 
 		/// Evaluate the state of a proposal given the current vote threshold.
@@ -374,9 +889,172 @@ pub mod pallet {
 			prop: Box<<T as Config>::Proposal>,
 		) -> DispatchResult {
 			ensure_signed(origin)?;
+			ensure!(!Self::is_paused(), Error::<T>::BridgeIsPaused);
 
 			Self::try_resolve_proposal(nonce, src_id, prop)
 		}
+
+		/// Reports the result of the outbound fungible transfer `nonce` to `dest_id`.
+		///
+		/// Once enough relayers agree on the result, the pallet which initiated the transfer is
+		/// notified through `TransferCallback`. Operational, like the proposal votes.
+		///
+		/// # <weight>
+		/// - O(1) lookup and insert
+		/// # </weight>
+		#[pallet::weight((195_000_000, DispatchClass::Operational))]
+		pub fn acknowledge_transfer(
+			origin: OriginFor<T>,
+			dest_id: BridgeChainId,
+			nonce: DepositNonce,
+			succeeded: bool,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(Self::is_relayer(&who), Error::<T>::MustBeRelayer);
+
+			Self::vote_transfer_result(who, dest_id, nonce, succeeded)
+		}
+
+		/// Signals the next nonce expected from `src_id` as an event, so relayers can resync.
+		///
+		/// # <weight>
+		/// - O(1) lookup
+		/// # </weight>
+		#[pallet::weight(195_000_000)]
+		pub fn resync_nonce(origin: OriginFor<T>, src_id: BridgeChainId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(Self::is_relayer(&who), Error::<T>::MustBeRelayer);
+			ensure!(Self::chain_whitelisted(src_id), Error::<T>::ChainNotWhitelisted);
+
+			Self::deposit_event(Event::ExpectedNonce(src_id, Self::expected_nonce(src_id)));
+			Ok(())
+		}
+
+		/// Allows the relayers to propose the call `function_name` of the pallet `pallet_name`.
+		///
+		/// Proposals are dispatched with the signed origin of the bridge account, so calls which
+		/// dispatch other calls (e.g. batches) should not be allowed.
+		///
+		/// # <weight>
+		/// - O(1) lookup and insert
+		/// # </weight>
+		#[pallet::weight(195_000_000)]
+		pub fn allow_proposal_call(
+			origin: OriginFor<T>,
+			pallet_name: Vec<u8>,
+			function_name: Vec<u8>,
+		) -> DispatchResult {
+			Self::ensure_resource_admin(origin)?;
+			ensure!(
+				!Self::allowed_proposals(&pallet_name, &function_name),
+				Error::<T>::ProposalCallAlreadyAllowed
+			);
+
+			<AllowedProposals<T>>::insert(&pallet_name, &function_name, true);
+			Self::deposit_event(Event::ProposalCallAllowed(pallet_name, function_name));
+			Ok(())
+		}
+
+		/// Removes a call allowed with `allow_proposal_call`.
+		///
+		/// Pending proposals of the call can no longer be executed.
+		///
+		/// # <weight>
+		/// - O(1) lookup and removal
+		/// # </weight>
+		#[pallet::weight(195_000_000)]
+		pub fn disallow_proposal_call(
+			origin: OriginFor<T>,
+			pallet_name: Vec<u8>,
+			function_name: Vec<u8>,
+		) -> DispatchResult {
+			Self::ensure_resource_admin(origin)?;
+			ensure!(
+				<AllowedProposals<T>>::take(&pallet_name, &function_name),
+				Error::<T>::ProposalCallNotAllowed
+			);
+
+			Self::deposit_event(Event::ProposalCallDisallowed(pallet_name, function_name));
+			Ok(())
+		}
+
+		/// Applies the proposals of `src_id` in nonce order, or in the order they are resolved.
+		///
+		/// With ordered delivery, proposals resolved ahead of the next nonce wait in
+		/// `DeliveryQueue` and are applied in `on_initialize` once the nonces before them are.
+		/// Nonces below the next nonce when it is enabled are still applied as they are resolved.
+		///
+		/// # <weight>
+		/// - O(1) lookup and insert
+		/// # </weight>
+		#[pallet::weight(195_000_000)]
+		pub fn set_ordered_delivery(
+			origin: OriginFor<T>,
+			src_id: BridgeChainId,
+			ordered: bool,
+		) -> DispatchResult {
+			Self::ensure_resource_admin(origin)?;
+			ensure!(Self::chain_whitelisted(src_id), Error::<T>::ChainNotWhitelisted);
+			let delivery = Self::ordered_delivery(src_id);
+			ensure!(delivery.is_some() != ordered, Error::<T>::OrderedDeliveryUnchanged);
+
+			if ordered {
+				<OrderedDeliveries<T>>::insert(
+					src_id,
+					OrderedDelivery {
+						next_nonce: Self::expected_nonce(src_id),
+						queued: 0,
+						stalled_since: None,
+					},
+				);
+			} else {
+				ensure!(
+					delivery.map_or(0, |delivery| delivery.queued) == 0,
+					Error::<T>::DeliveryQueueNotEmpty
+				);
+				<OrderedDeliveries<T>>::remove(src_id);
+			}
+			Self::deposit_event(Event::OrderedDeliverySet(src_id, ordered));
+			Ok(())
+		}
+
+		/// Skips the next nonce of `src_id` once the chain has been stalled for
+		/// `DeliveryQueueTimeout`, so that the proposals waiting for it can be applied.
+		///
+		/// The skipped nonce is resolved and can no longer be proposed.
+		///
+		/// # <weight>
+		/// - O(1) lookup and insert
+		/// # </weight>
+		#[pallet::weight(195_000_000)]
+		pub fn skip_delivery_nonce(origin: OriginFor<T>, src_id: BridgeChainId) -> DispatchResult {
+			Self::ensure_resource_admin(origin)?;
+			let mut delivery =
+				Self::ordered_delivery(src_id).ok_or(Error::<T>::OrderedDeliveryNotEnabled)?;
+			let now = <frame_system::Pallet<T>>::block_number();
+			ensure!(
+				delivery
+					.stalled_since
+					.map_or(false, |since| now >= since + T::DeliveryQueueTimeout::get()),
+				Error::<T>::DeliveryNotStalled
+			);
+
+			let nonce = delivery.next_nonce;
+			// Queued proposals are applied in the next block
+			ensure!(
+				!<DeliveryQueue<T>>::contains_key(src_id, nonce),
+				Error::<T>::DeliveryNotStalled
+			);
+			if !Self::nonce_resolved(src_id, nonce) {
+				Self::resolve_nonce(src_id, nonce);
+			}
+			// Further nonces can be skipped without waiting again, the queue is drained in the
+			// next block.
+			delivery.next_nonce = nonce.saturating_add(1);
+			<OrderedDeliveries<T>>::insert(src_id, delivery);
+			Self::deposit_event(Event::DeliveryNonceSkipped(src_id, nonce));
+			Ok(())
+		}
 	}
 
 	impl<T: Config> Pallet<T> {
@@ -392,6 +1070,24 @@ pub mod pallet {
 			PALLET_ID.into_account()
 		}
 
+		/// Ensures `origin` can manage the relayer set and the threshold
+		fn ensure_relayer_admin(origin: OriginFor<T>) -> DispatchResult {
+			T::RelayerAdminOrigin::ensure_origin(origin)?;
+			Ok(())
+		}
+
+		/// Ensures `origin` can manage the resources and the whitelisted chains
+		fn ensure_resource_admin(origin: OriginFor<T>) -> DispatchResult {
+			T::ResourceAdminOrigin::ensure_origin(origin)?;
+			Ok(())
+		}
+
+		/// Ensures `origin` can pause and resume the bridge
+		fn ensure_pause_admin(origin: OriginFor<T>) -> DispatchResult {
+			T::PauseOrigin::ensure_origin(origin)?;
+			Ok(())
+		}
+
 		/// Asserts if a resource is registered
 		pub fn resource_exists(id: ResourceId) -> bool {
 			return Self::resources(id) != None
@@ -402,6 +1098,73 @@ pub mod pallet {
 			return Self::chains(id) != None
 		}
 
+		/// Checks if the relayers can propose `call`
+		pub fn is_allowed_proposal(call: &T::Proposal) -> bool {
+			let CallMetadata { pallet_name, function_name } = call.get_call_metadata();
+			Self::allowed_proposals(
+				pallet_name.as_bytes().to_vec(),
+				function_name.as_bytes().to_vec(),
+			)
+		}
+
+		/// Checks if `to` is a valid address on the destination chain. Chains without metadata
+		/// accept any recipient.
+		pub fn valid_recipient(id: BridgeChainId, to: &[u8]) -> bool {
+			Self::chain_info(id).map_or(true, |info| info.address_format.is_valid(to))
+		}
+
+		/// Next nonce expected from a source chain
+		pub fn expected_nonce(src_id: BridgeChainId) -> DepositNonce {
+			Self::highest_resolved_nonce(src_id).saturating_add(1)
+		}
+
+		/// Lowest nonce of a source chain which can still be proposed
+		pub fn nonce_window_start(src_id: BridgeChainId) -> DepositNonce {
+			Self::expected_nonce(src_id).saturating_sub(T::NonceWindow::get())
+		}
+
+		/// Checks if a proposal with the nonce has already been approved or rejected. Nonces below
+		/// the window are reported as resolved.
+		pub fn nonce_resolved(src_id: BridgeChainId, nonce: DepositNonce) -> bool {
+			let bits = Self::resolved_nonces(src_id, nonce / NONCE_BITS);
+			nonce < Self::nonce_window_start(src_id) || bits & (1 << (nonce % NONCE_BITS)) != 0
+		}
+
+		/// Marks a nonce as resolved and prunes the bitmap entries falling out of the window.
+		fn resolve_nonce(src_id: BridgeChainId, nonce: DepositNonce) {
+			<ResolvedNonces<T>>::mutate(src_id, nonce / NONCE_BITS, |bits| {
+				*bits |= 1 << (nonce % NONCE_BITS)
+			});
+
+			let highest = Self::highest_resolved_nonce(src_id);
+			if nonce > highest {
+				let start = Self::nonce_window_start(src_id) / NONCE_BITS;
+				<HighestResolvedNonce<T>>::insert(src_id, nonce);
+				// Entries above the previous highest nonce don't exist.
+				let end =
+					(Self::nonce_window_start(src_id) / NONCE_BITS).min(highest / NONCE_BITS + 1);
+				for index in start..end {
+					<ResolvedNonces<T>>::remove(src_id, index);
+				}
+			}
+		}
+
+		/// Proposal votes cast by `who` in the current block
+		pub fn votes_this_block(who: &T::AccountId) -> u32 {
+			let (block_number, votes) = Self::relayer_votes(who);
+			if block_number == <frame_system::Pallet<T>>::block_number() {
+				votes
+			} else {
+				0
+			}
+		}
+
+		/// Counts a proposal vote of `who` towards `MaxVotesPerBlock`.
+		fn note_vote(who: &T::AccountId) {
+			let votes = Self::votes_this_block(who);
+			<RelayerVotes<T>>::insert(who, (<frame_system::Pallet<T>>::block_number(), votes + 1));
+		}
+
 		/// Increments the deposit nonce for the specified chain ID
 		fn bump_nonce(id: BridgeChainId) -> DepositNonce {
 			let nonce = Self::chains(id).unwrap_or_default() + 1;
@@ -416,6 +1179,7 @@ pub mod pallet {
 			ensure!(threshold > 0, Error::<T>::InvalidThreshold);
 			<RelayerThreshold<T>>::put(threshold);
 			Self::deposit_event(Event::RelayerThresholdChanged(threshold));
+			Self::snapshot_relayer_set();
 			Ok(())
 		}
 
@@ -447,8 +1211,11 @@ pub mod pallet {
 			ensure!(!Self::is_relayer(&relayer), Error::<T>::RelayerAlreadyExists);
 			<Relayers<T>>::insert(&relayer, true);
 			<RelayerCount<T>>::mutate(|i| *i += 1);
+			let weight = Self::relayer_weight(&relayer);
+			<TotalRelayerWeight<T>>::mutate(|w| *w = w.saturating_add(weight));
 
 			Self::deposit_event(Event::RelayerAdded(relayer));
+			Self::snapshot_relayer_set();
 			Ok(())
 		}
 
@@ -457,10 +1224,97 @@ pub mod pallet {
 			ensure!(Self::is_relayer(&relayer), Error::<T>::RelayerInvalid);
 			<Relayers<T>>::remove(&relayer);
 			<RelayerCount<T>>::mutate(|i| *i -= 1);
+			<RelayerVotes<T>>::remove(&relayer);
+			let weight = <RelayerWeights<T>>::take(&relayer);
+			<TotalRelayerWeight<T>>::mutate(|w| *w = w.saturating_sub(weight));
 			Self::deposit_event(Event::RelayerRemoved(relayer));
+			Self::snapshot_relayer_set();
+			Ok(())
+		}
+
+		/// Sets the vote weight of a relayer
+		pub fn update_relayer_weight(relayer: T::AccountId, weight: u32) -> DispatchResult {
+			ensure!(Self::is_relayer(&relayer), Error::<T>::RelayerInvalid);
+			ensure!(weight > 0, Error::<T>::InvalidRelayerWeight);
+			let previous = <RelayerWeights<T>>::get(&relayer);
+			<RelayerWeights<T>>::insert(&relayer, weight);
+			<TotalRelayerWeight<T>>::mutate(|w| {
+				*w = w.saturating_sub(previous).saturating_add(weight)
+			});
+			Self::deposit_event(Event::RelayerWeightChanged(relayer, weight));
+			Self::snapshot_relayer_set();
 			Ok(())
 		}
 
+		/// Vote weight required for a proposal to execute
+		pub fn threshold_weight() -> u32 {
+			match Self::relayer_threshold_ratio() {
+				Some(ratio) => ratio.mul_ceil(Self::total_relayer_weight()).max(1),
+				None => Self::relayer_threshold(),
+			}
+		}
+
+		/// Relayers in set with their vote weight, sorted by account
+		pub fn relayer_set() -> Vec<(T::AccountId, u32)> {
+			let mut relayers: Vec<_> = <Relayers<T>>::iter()
+				.filter(|(_, active)| *active)
+				.map(|(relayer, _)| {
+					let weight = Self::relayer_weight(&relayer);
+					(relayer, weight)
+				})
+				.collect();
+			relayers.sort();
+			relayers
+		}
+
+		/// Records the relayer set and threshold in `RelayerSnapshots`, keeping the last
+		/// `MaxRelayerSnapshots`.
+		fn snapshot_relayer_set() {
+			let relayers = Self::relayer_set();
+			let hash =
+				H256(sp_io::hashing::blake2_256(&(&relayers, Self::threshold_weight()).encode()));
+			let count = relayers.len() as u32;
+			let block_number = <frame_system::Pallet<T>>::block_number();
+			<RelayerSnapshots<T>>::mutate(|snapshots| {
+				snapshots.push(RelayerSnapshot { hash, count, block_number });
+				let excess = snapshots.len().saturating_sub(T::MaxRelayerSnapshots::get() as usize);
+				snapshots.drain(..excess);
+			});
+			Self::deposit_event(Event::RelayerSetSnapshot(hash, count));
+		}
+
+		/// Resources, whitelisted chains, relayer set and threshold, sorted for stable output
+		pub fn catalogue() -> BridgeCatalogue<T::AccountId> {
+			let mut resources: Vec<_> = <Resources<T>>::iter().collect();
+			resources.sort_by_key(|(id, _)| *id);
+			let mut chains: Vec<_> = <ChainNonces<T>>::iter()
+				.map(|(chain_id, deposit_nonce)| BridgeChain {
+					chain_id,
+					deposit_nonce,
+					expected_nonce: Self::expected_nonce(chain_id),
+				})
+				.collect();
+			chains.sort_by_key(|chain| chain.chain_id);
+
+			BridgeCatalogue {
+				resources,
+				chains,
+				relayers: Self::relayer_set(),
+				threshold: Self::threshold_weight(),
+			}
+		}
+
+		/// Attempts to mark the votes as approved or rejected given the relayer weights.
+		fn evaluate_votes(
+			votes: &mut ProposalVotes<T::AccountId, T::BlockNumber>,
+		) -> ProposalStatus {
+			votes.try_to_complete_weighted(
+				|who| Self::relayer_weight(who),
+				Self::threshold_weight(),
+				Self::total_relayer_weight(),
+			)
+		}
+
 		// *** Proposal voting and execution methods ***
 
 		/// Commits a vote for a proposal. If the proposal doesn't exist it will be created.
@@ -482,6 +1336,9 @@ pub mod pallet {
 			ensure!(!votes.is_complete(), Error::<T>::ProposalAlreadyComplete);
 			ensure!(!votes.is_expired(now), Error::<T>::ProposalExpired);
 			ensure!(!votes.has_voted(&who), Error::<T>::RelayerAlreadyVoted);
+			// The nonce may have been resolved with a different call
+			ensure!(nonce >= Self::nonce_window_start(src_id), Error::<T>::NonceTooOld);
+			ensure!(!Self::nonce_resolved(src_id, nonce), Error::<T>::NonceAlreadyResolved);
 
 			if in_favour {
 				votes.votes_for.push(who.clone());
@@ -507,13 +1364,20 @@ pub mod pallet {
 				ensure!(!votes.is_complete(), Error::<T>::ProposalAlreadyComplete);
 				ensure!(!votes.is_expired(now), Error::<T>::ProposalExpired);
 
-				let status =
-					votes.try_to_complete(<RelayerThreshold<T>>::get(), <RelayerCount<T>>::get());
+				let status = Self::evaluate_votes(&mut votes);
+				if votes.is_complete() {
+					// The votes are kept and the proposal can be resolved again once the queue
+					// has room.
+					ensure!(Self::can_deliver(src_id, nonce), Error::<T>::DeliveryQueueFull);
+				}
 				<Votes<T>>::insert(src_id, (nonce, prop.clone()), votes.clone());
+				if votes.is_complete() {
+					Self::resolve_nonce(src_id, nonce);
+				}
 
 				match status {
-					ProposalStatus::Approved => Self::finalize_execution(src_id, nonce, prop),
-					ProposalStatus::Rejected => Self::cancel_execution(src_id, nonce),
+					ProposalStatus::Approved => Self::deliver(src_id, nonce, Some(prop)),
+					ProposalStatus::Rejected => Self::deliver(src_id, nonce, None),
 					_ => Ok(()),
 				}
 			} else {
@@ -552,6 +1416,11 @@ pub mod pallet {
 			call: Box<T::Proposal>,
 		) -> DispatchResult {
 			Self::deposit_event(Event::ProposalApproved(src_id, nonce));
+			// The call may have been disallowed while the proposal was pending
+			if !Self::is_allowed_proposal(&call) {
+				Self::deposit_event(Event::ProposalFailed(src_id, nonce));
+				return Ok(())
+			}
 			call.dispatch(frame_system::RawOrigin::Signed(Self::account_id()).into())
 				.map(|_| ())
 				.map_err(|e| e.error)?;
@@ -565,30 +1434,192 @@ pub mod pallet {
 			Ok(())
 		}
 
-		/// Initiates a transfer of a fungible asset out of the chain. This should be called by
-		/// another pallet.
+		/// Checks if a proposal resolved now can be applied or queued
+		fn can_deliver(src_id: BridgeChainId, nonce: DepositNonce) -> bool {
+			Self::ordered_delivery(src_id).map_or(true, |delivery| {
+				nonce <= delivery.next_nonce || delivery.queued < T::MaxQueuedProposals::get()
+			})
+		}
+
+		/// Executes an approved proposal, or cancels a rejected one when `call` is `None`. With
+		/// ordered delivery, proposals ahead of the next nonce are queued instead.
+		fn deliver(
+			src_id: BridgeChainId,
+			nonce: DepositNonce,
+			call: Option<Box<T::Proposal>>,
+		) -> DispatchResult {
+			if let Some(mut delivery) = Self::ordered_delivery(src_id) {
+				let now = <frame_system::Pallet<T>>::block_number();
+				if nonce > delivery.next_nonce {
+					<DeliveryQueue<T>>::insert(src_id, nonce, call.map(|call| *call));
+					delivery.queued += 1;
+					delivery.stalled_since = delivery.stalled_since.or(Some(now));
+					<OrderedDeliveries<T>>::insert(src_id, delivery);
+					Self::deposit_event(Event::ProposalQueued(src_id, nonce));
+					return Ok(())
+				}
+				if nonce == delivery.next_nonce {
+					delivery.next_nonce = nonce.saturating_add(1);
+					delivery.stalled_since = if delivery.queued > 0 { Some(now) } else { None };
+					<OrderedDeliveries<T>>::insert(src_id, delivery);
+				}
+			}
+
+			match call {
+				Some(call) => Self::finalize_execution(src_id, nonce, call),
+				None => Self::cancel_execution(src_id, nonce),
+			}
+		}
+
+		/// Applies the queued proposals whose earlier nonces have been applied, unless the
+		/// bridge is paused.
+		fn drain_delivery_queues(now: T::BlockNumber) -> Weight {
+			if Self::is_paused() {
+				return T::DbWeight::get().reads(1)
+			}
+			let stalled: Vec<_> = <OrderedDeliveries<T>>::iter()
+				.filter(|(_, delivery)| delivery.queued > 0)
+				.collect();
+			let mut weight = T::DbWeight::get().reads(1 + stalled.len() as Weight);
+			for (src_id, mut delivery) in stalled {
+				let next_nonce = delivery.next_nonce;
+				while let Some(call) = <DeliveryQueue<T>>::take(src_id, delivery.next_nonce) {
+					let nonce = delivery.next_nonce;
+					delivery.next_nonce = nonce.saturating_add(1);
+					delivery.queued = delivery.queued.saturating_sub(1);
+					weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 1));
+					match call {
+						Some(call) => {
+							weight = weight.saturating_add(call.get_dispatch_info().weight);
+							if Self::execute_queued(src_id, nonce, call).is_err() {
+								Self::deposit_event(Event::ProposalFailed(src_id, nonce));
+							}
+						},
+						None => {
+							let _ = Self::cancel_execution(src_id, nonce);
+						},
+					}
+				}
+				if delivery.next_nonce != next_nonce {
+					delivery.stalled_since = if delivery.queued > 0 { Some(now) } else { None };
+					<OrderedDeliveries<T>>::insert(src_id, delivery);
+					weight = weight.saturating_add(T::DbWeight::get().writes(1));
+				}
+			}
+			weight
+		}
+
+		/// Executes a queued proposal, reverting its changes if it fails.
+		#[transactional]
+		fn execute_queued(
+			src_id: BridgeChainId,
+			nonce: DepositNonce,
+			call: T::Proposal,
+		) -> DispatchResult {
+			Self::finalize_execution(src_id, nonce, Box::new(call))
+		}
+
+		/// Commits a vote on the result of an outbound transfer and notifies the initiating
+		/// pallet once the vote threshold is met.
+		fn vote_transfer_result(
+			who: T::AccountId,
+			dest_id: BridgeChainId,
+			nonce: DepositNonce,
+			succeeded: bool,
+		) -> DispatchResult {
+			let mut transfer =
+				Self::pending_transfers(dest_id, nonce).ok_or(Error::<T>::TransferDoesNotExist)?;
+			let now = <frame_system::Pallet<T>>::block_number();
+			ensure!(!transfer.votes.is_expired(now), Error::<T>::ProposalExpired);
+			ensure!(!transfer.votes.has_voted(&who), Error::<T>::RelayerAlreadyVoted);
+
+			if succeeded {
+				transfer.votes.votes_for.push(who.clone());
+			} else {
+				transfer.votes.votes_against.push(who.clone());
+			}
+			Self::deposit_event(Event::TransferResultVoted(dest_id, nonce, who, succeeded));
+
+			let outcome = match Self::evaluate_votes(&mut transfer.votes) {
+				ProposalStatus::Approved => TransferOutcome::Succeeded,
+				ProposalStatus::Rejected => TransferOutcome::Failed,
+				ProposalStatus::Initiated => {
+					<PendingTransfers<T>>::insert(dest_id, nonce, transfer);
+					return Ok(())
+				},
+			};
+			<PendingTransfers<T>>::remove(dest_id, nonce);
+			Self::resolve_transfer(dest_id, nonce, transfer.resource_id, outcome);
+			Ok(())
+		}
+
+		/// Signals the result of an outbound transfer and notifies the initiating pallet.
+		fn resolve_transfer(
+			dest_id: BridgeChainId,
+			nonce: DepositNonce,
+			resource_id: ResourceId,
+			outcome: TransferOutcome,
+		) {
+			Self::deposit_event(Event::TransferResolved(dest_id, nonce, outcome));
+			T::TransferCallback::on_transfer_result(dest_id, nonce, resource_id, outcome);
+		}
+
+		/// Initiates a transfer of a fungible asset out of the chain on behalf of `sender`. This
+		/// should be called by another pallet, which is notified of the result through
+		/// `TransferCallback` under the returned nonce.
 		pub fn transfer_fungible(
+			sender: T::AccountId,
 			dest_id: BridgeChainId,
 			resource_id: ResourceId,
 			to: Vec<u8>,
 			amount: U256,
-		) -> DispatchResult {
+		) -> Result<DepositNonce, DispatchError> {
+			ensure!(!Self::is_paused(), Error::<T>::BridgeIsPaused);
 			ensure!(Self::chain_whitelisted(dest_id), Error::<T>::ChainNotWhitelisted);
+			ensure!(Self::valid_recipient(dest_id, &to), Error::<T>::InvalidRecipient);
 			let nonce = Self::bump_nonce(dest_id);
-			Self::deposit_event(Event::FungibleTransfer(dest_id, nonce, resource_id, amount, to));
-			Ok(())
+
+			let now = <frame_system::Pallet<T>>::block_number();
+			let expiry = now + T::TransferLifetime::get();
+			let votes = ProposalVotes { expiry, ..Default::default() };
+			<PendingTransfers<T>>::insert(dest_id, nonce, PendingTransfer { resource_id, votes });
+			<TransferExpiries<T>>::append(expiry, (dest_id, nonce));
+			<TransferReceipts<T>>::insert(
+				dest_id,
+				nonce,
+				TransferReceipt {
+					sender: sender.clone(),
+					resource_id,
+					recipient: to.clone(),
+					amount,
+					block_number: now,
+				},
+			);
+
+			Self::deposit_event(Event::FungibleTransfer(
+				dest_id,
+				nonce,
+				resource_id,
+				amount,
+				to,
+				sender,
+			));
+			Ok(nonce)
 		}
 
-		/// Initiates a transfer of a nonfungible asset out of the chain. This should be called by
-		/// another pallet.
+		/// Initiates a transfer of a nonfungible asset out of the chain on behalf of `sender`.
+		/// This should be called by another pallet.
 		pub fn transfer_nonfungible(
+			sender: T::AccountId,
 			dest_id: BridgeChainId,
 			resource_id: ResourceId,
 			token_id: Vec<u8>,
 			to: Vec<u8>,
 			metadata: Vec<u8>,
 		) -> DispatchResult {
+			ensure!(!Self::is_paused(), Error::<T>::BridgeIsPaused);
 			ensure!(Self::chain_whitelisted(dest_id), Error::<T>::ChainNotWhitelisted);
+			ensure!(Self::valid_recipient(dest_id, &to), Error::<T>::InvalidRecipient);
 			let nonce = Self::bump_nonce(dest_id);
 			Self::deposit_event(Event::NonFungibleTransfer(
 				dest_id,
@@ -597,20 +1628,29 @@ pub mod pallet {
 				token_id,
 				to,
 				metadata,
+				sender,
 			));
 			Ok(())
 		}
 
-		/// Initiates a transfer of generic data out of the chain. This should be called by another
-		/// pallet.
+		/// Initiates a transfer of generic data out of the chain on behalf of `sender`. This should
+		/// be called by another pallet.
 		pub fn transfer_generic(
+			sender: T::AccountId,
 			dest_id: BridgeChainId,
 			resource_id: ResourceId,
 			metadata: Vec<u8>,
 		) -> DispatchResult {
+			ensure!(!Self::is_paused(), Error::<T>::BridgeIsPaused);
 			ensure!(Self::chain_whitelisted(dest_id), Error::<T>::ChainNotWhitelisted);
 			let nonce = Self::bump_nonce(dest_id);
-			Self::deposit_event(Event::GenericTransfer(dest_id, nonce, resource_id, metadata));
+			Self::deposit_event(Event::GenericTransfer(
+				dest_id,
+				nonce,
+				resource_id,
+				metadata,
+				sender,
+			));
 			Ok(())
 		}
 	}
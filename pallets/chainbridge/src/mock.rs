@@ -12,6 +12,7 @@ use sp_runtime::{
 
 use crate::{self as bridge, Config};
 pub use pallet_balances as balances;
+use std::cell::RefCell;
 
 parameter_types! {
 	pub const BlockHashCount: u64 = 250;
@@ -54,6 +55,7 @@ parameter_types! {
 
 ord_parameter_types! {
 	pub const One: u64 = 1;
+	pub const PauseAdmin: u64 = 9;
 }
 
 impl pallet_balances::Config for Test {
@@ -71,14 +73,54 @@ impl pallet_balances::Config for Test {
 parameter_types! {
 	pub const TestBridgeChainId: u8 = 5;
 	pub const ProposalLifetime: u64 = 50;
+	pub const TransferLifetime: u64 = 100;
+	pub const NonceWindow: u64 = 128;
+	pub const MaxRelayerSnapshots: u32 = 3;
+	pub const MaxVotesPerBlock: u32 = 5;
+	pub const MaxQueuedProposals: u32 = 3;
+	pub const DeliveryQueueTimeout: u64 = 10;
+	pub DefaultAllowedProposals: Vec<(Vec<u8>, Vec<u8>)> =
+		vec![(b"System".to_vec(), b"remark".to_vec())];
+}
+
+thread_local! {
+	pub static TRANSFER_RESULTS: RefCell<Vec<(BridgeChainId, DepositNonce, TransferOutcome)>> =
+		RefCell::new(vec![]);
+}
+
+/// Records the transfer results the pallet reports.
+pub struct RecordTransferResults;
+impl BridgeCallback for RecordTransferResults {
+	fn on_transfer_result(
+		dest_id: BridgeChainId,
+		nonce: DepositNonce,
+		_resource_id: ResourceId,
+		outcome: TransferOutcome,
+	) {
+		TRANSFER_RESULTS.with(|r| r.borrow_mut().push((dest_id, nonce, outcome)));
+	}
+}
+
+pub fn transfer_results() -> Vec<(BridgeChainId, DepositNonce, TransferOutcome)> {
+	TRANSFER_RESULTS.with(|r| r.borrow().clone())
 }
 
 impl Config for Test {
 	type Event = Event;
-	type AdminOrigin = frame_system::EnsureRoot<Self::AccountId>;
+	type RelayerAdminOrigin = frame_system::EnsureRoot<Self::AccountId>;
+	type ResourceAdminOrigin = frame_system::EnsureRoot<Self::AccountId>;
+	type PauseOrigin = frame_system::EnsureSignedBy<PauseAdmin, Self::AccountId>;
 	type Proposal = Call;
 	type BridgeChainId = TestBridgeChainId;
 	type ProposalLifetime = ProposalLifetime;
+	type TransferCallback = RecordTransferResults;
+	type TransferLifetime = TransferLifetime;
+	type NonceWindow = NonceWindow;
+	type MaxRelayerSnapshots = MaxRelayerSnapshots;
+	type MaxVotesPerBlock = MaxVotesPerBlock;
+	type DefaultAllowedProposals = DefaultAllowedProposals;
+	type MaxQueuedProposals = MaxQueuedProposals;
+	type DeliveryQueueTimeout = DeliveryQueueTimeout;
 }
 
 pub type Block = frame_system::mocking::MockBlock<Test>;
@@ -110,7 +152,11 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
 		.assimilate_storage(&mut t)
 		.unwrap();
 	let mut ext = sp_io::TestExternalities::new(t);
-	ext.execute_with(|| System::set_block_number(1));
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		// Proposals of the tests are remarks
+		AllowedProposals::<Test>::insert(b"System".to_vec(), b"remark".to_vec(), true);
+	});
 	ext
 }
 
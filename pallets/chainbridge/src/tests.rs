@@ -2,14 +2,25 @@
 
 use super::{
 	mock::{
-		assert_events, new_test_ext, new_test_ext_initialized, Balances, Bridge, Call, Event,
-		Origin, ProposalLifetime, System, Test, TestBridgeChainId, ENDOWED_BALANCE, RELAYER_A,
-		RELAYER_B, RELAYER_C, TEST_THRESHOLD,
+		assert_events, new_test_ext, new_test_ext_initialized, transfer_results, Balances, Bridge,
+		Call, Event, MaxVotesPerBlock, NonceWindow, Origin, PauseAdmin, ProposalLifetime, System,
+		Test, TestBridgeChainId, TransferLifetime, ENDOWED_BALANCE, RELAYER_A, RELAYER_B,
+		RELAYER_C, TEST_THRESHOLD,
 	},
 	*,
 };
-use frame_support::{assert_noop, assert_ok};
+use codec::Encode;
+use frame_support::{assert_err, assert_noop, assert_ok, traits::Hooks};
 use frame_system as system;
+use sp_runtime::Perbill;
+
+const SENDER: u64 = 0x1;
+
+/// `RelayerSetSnapshot` of the relayers with their weights and the threshold weight
+fn snapshot(relayers: &[(u64, u32)], threshold: u32) -> Event {
+	let hash = H256(sp_io::hashing::blake2_256(&(relayers, threshold).encode()));
+	Event::Bridge(crate::Event::RelayerSetSnapshot(hash, relayers.len() as u32))
+}
 
 #[test]
 fn derive_ids() {
@@ -108,6 +119,54 @@ fn whitelist_chain() {
 	})
 }
 
+#[test]
+fn chain_info_validates_recipient() {
+	new_test_ext().execute_with(|| {
+		let dest_id = 2;
+		let resource_id = [1; 32];
+		let info = ChainInfo {
+			name: b"Ethereum".to_vec(),
+			address_format: AddressFormat::Ethereum,
+			confirmations: 12,
+			fee_asset: resource_id,
+		};
+
+		assert_noop!(
+			Bridge::set_chain_info(Origin::root(), dest_id, info.clone()),
+			Error::<Test>::ChainNotWhitelisted
+		);
+		assert_ok!(Bridge::whitelist_chain(Origin::root(), dest_id));
+		assert_noop!(
+			Bridge::set_chain_info(Origin::signed(RELAYER_A), dest_id, info.clone()),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(Bridge::set_chain_info(Origin::root(), dest_id, info.clone()));
+		assert_eq!(Bridge::chain_info(dest_id), Some(info));
+
+		assert_noop!(
+			Bridge::transfer_fungible(SENDER, dest_id, resource_id, vec![1; 32], 10.into()),
+			Error::<Test>::InvalidRecipient
+		);
+		assert_noop!(
+			Bridge::transfer_nonfungible(
+				SENDER,
+				dest_id,
+				resource_id,
+				vec![1],
+				vec![1; 19],
+				vec![]
+			),
+			Error::<Test>::InvalidRecipient
+		);
+		assert_ok!(Bridge::transfer_fungible(SENDER, dest_id, resource_id, vec![1; 20], 10.into()));
+
+		// Without metadata any recipient is accepted
+		assert_ok!(Bridge::remove_chain_info(Origin::root(), dest_id));
+		assert_eq!(Bridge::chain_info(dest_id), None);
+		assert_ok!(Bridge::transfer_fungible(SENDER, dest_id, resource_id, vec![1; 32], 10.into()));
+	})
+}
+
 #[test]
 fn set_get_threshold() {
 	new_test_ext().execute_with(|| {
@@ -121,7 +180,9 @@ fn set_get_threshold() {
 
 		assert_events(vec![
 			Event::Bridge(crate::Event::RelayerThresholdChanged(TEST_THRESHOLD)),
+			snapshot(&[], TEST_THRESHOLD),
 			Event::Bridge(crate::Event::RelayerThresholdChanged(5)),
+			snapshot(&[], 5),
 		]);
 	})
 }
@@ -140,6 +201,7 @@ fn asset_transfer_success() {
 
 		assert_ok!(Bridge::whitelist_chain(Origin::root(), dest_id.clone()));
 		assert_ok!(Bridge::transfer_fungible(
+			SENDER,
 			dest_id.clone(),
 			resource_id.clone(),
 			to.clone(),
@@ -153,10 +215,22 @@ fn asset_transfer_success() {
 				resource_id.clone(),
 				amount.into(),
 				to.clone(),
+				SENDER,
 			)),
 		]);
+		assert_eq!(
+			Bridge::transfer_receipts(dest_id, 1),
+			Some(TransferReceipt {
+				sender: SENDER,
+				resource_id,
+				recipient: to.clone(),
+				amount: amount.into(),
+				block_number: 1,
+			})
+		);
 
 		assert_ok!(Bridge::transfer_nonfungible(
+			SENDER,
 			dest_id.clone(),
 			resource_id.clone(),
 			token_id.clone(),
@@ -170,9 +244,11 @@ fn asset_transfer_success() {
 			token_id,
 			to.clone(),
 			metadata.clone(),
+			SENDER,
 		))]);
 
 		assert_ok!(Bridge::transfer_generic(
+			SENDER,
 			dest_id.clone(),
 			resource_id.clone(),
 			metadata.clone()
@@ -182,6 +258,7 @@ fn asset_transfer_success() {
 			3,
 			resource_id,
 			metadata,
+			SENDER,
 		))]);
 	})
 }
@@ -197,17 +274,30 @@ fn asset_transfer_invalid_chain() {
 		assert_events(vec![Event::Bridge(crate::Event::ChainWhitelisted(chain_id.clone()))]);
 
 		assert_noop!(
-			Bridge::transfer_fungible(bad_dest_id, resource_id.clone(), vec![], U256::zero()),
+			Bridge::transfer_fungible(
+				SENDER,
+				bad_dest_id,
+				resource_id.clone(),
+				vec![],
+				U256::zero()
+			),
 			Error::<Test>::ChainNotWhitelisted
 		);
 
 		assert_noop!(
-			Bridge::transfer_nonfungible(bad_dest_id, resource_id.clone(), vec![], vec![], vec![]),
+			Bridge::transfer_nonfungible(
+				SENDER,
+				bad_dest_id,
+				resource_id.clone(),
+				vec![],
+				vec![],
+				vec![]
+			),
 			Error::<Test>::ChainNotWhitelisted
 		);
 
 		assert_noop!(
-			Bridge::transfer_generic(bad_dest_id, resource_id.clone(), vec![]),
+			Bridge::transfer_generic(SENDER, bad_dest_id, resource_id.clone(), vec![]),
 			Error::<Test>::ChainNotWhitelisted
 		);
 	})
@@ -241,9 +331,13 @@ fn add_remove_relayer() {
 
 		assert_events(vec![
 			Event::Bridge(crate::Event::RelayerAdded(RELAYER_A)),
+			snapshot(&[(RELAYER_A, 1)], TEST_THRESHOLD),
 			Event::Bridge(crate::Event::RelayerAdded(RELAYER_B)),
+			snapshot(&[(RELAYER_A, 1), (RELAYER_B, 1)], TEST_THRESHOLD),
 			Event::Bridge(crate::Event::RelayerAdded(RELAYER_C)),
+			snapshot(&[(RELAYER_A, 1), (RELAYER_B, 1), (RELAYER_C, 1)], TEST_THRESHOLD),
 			Event::Bridge(crate::Event::RelayerRemoved(RELAYER_B)),
+			snapshot(&[(RELAYER_A, 1), (RELAYER_C, 1)], TEST_THRESHOLD),
 		]);
 	})
 }
@@ -445,6 +539,7 @@ fn execute_after_threshold_change() {
 		assert_events(vec![
 			Event::Bridge(crate::Event::VoteFor(src_id, prop_id, RELAYER_A)),
 			Event::Bridge(crate::Event::RelayerThresholdChanged(1)),
+			snapshot(&[(RELAYER_A, 1), (RELAYER_B, 1), (RELAYER_C, 1)], 1),
 			Event::Bridge(crate::Event::ProposalApproved(src_id, prop_id)),
 			Event::Bridge(crate::Event::ProposalSucceeded(src_id, prop_id)),
 		]);
@@ -524,3 +619,641 @@ fn proposal_expires() {
 		assert_events(vec![Event::Bridge(crate::Event::VoteFor(src_id, prop_id, RELAYER_A))]);
 	})
 }
+
+#[test]
+fn transfer_result_notifies_initiator() {
+	let dest_id = 1;
+	let r_id = derive_resource_id(dest_id, b"transfer");
+
+	new_test_ext_initialized(dest_id, r_id, b"Example.transfer".to_vec()).execute_with(|| {
+		assert_eq!(Bridge::transfer_fungible(SENDER, dest_id, r_id, vec![1], 10.into()), Ok(1));
+		assert_eq!(Bridge::transfer_fungible(SENDER, dest_id, r_id, vec![1], 10.into()), Ok(2));
+
+		assert_noop!(
+			Bridge::acknowledge_transfer(Origin::signed(1), dest_id, 1, true),
+			Error::<Test>::MustBeRelayer
+		);
+		assert_noop!(
+			Bridge::acknowledge_transfer(Origin::signed(RELAYER_A), dest_id, 3, true),
+			Error::<Test>::TransferDoesNotExist
+		);
+
+		// Succeeds once the threshold is met
+		assert_ok!(Bridge::acknowledge_transfer(Origin::signed(RELAYER_A), dest_id, 1, true));
+		assert_noop!(
+			Bridge::acknowledge_transfer(Origin::signed(RELAYER_A), dest_id, 1, true),
+			Error::<Test>::RelayerAlreadyVoted
+		);
+		assert_eq!(transfer_results(), vec![]);
+		assert_ok!(Bridge::acknowledge_transfer(Origin::signed(RELAYER_B), dest_id, 1, true));
+		assert_eq!(transfer_results(), vec![(dest_id, 1, TransferOutcome::Succeeded)]);
+		assert_eq!(Bridge::pending_transfers(dest_id, 1), None);
+		assert_noop!(
+			Bridge::acknowledge_transfer(Origin::signed(RELAYER_C), dest_id, 1, true),
+			Error::<Test>::TransferDoesNotExist
+		);
+
+		// Fails once the threshold can no longer be met
+		assert_ok!(Bridge::acknowledge_transfer(Origin::signed(RELAYER_A), dest_id, 2, false));
+		assert_ok!(Bridge::acknowledge_transfer(Origin::signed(RELAYER_B), dest_id, 2, false));
+		assert_eq!(
+			transfer_results(),
+			vec![(dest_id, 1, TransferOutcome::Succeeded), (dest_id, 2, TransferOutcome::Failed)]
+		);
+
+		assert_events(vec![
+			Event::Bridge(crate::Event::TransferResultVoted(dest_id, 2, RELAYER_A, false)),
+			Event::Bridge(crate::Event::TransferResultVoted(dest_id, 2, RELAYER_B, false)),
+			Event::Bridge(crate::Event::TransferResolved(dest_id, 2, TransferOutcome::Failed)),
+		]);
+	})
+}
+
+#[test]
+fn transfer_result_times_out() {
+	let dest_id = 1;
+	let r_id = derive_resource_id(dest_id, b"transfer");
+
+	new_test_ext_initialized(dest_id, r_id, b"Example.transfer".to_vec()).execute_with(|| {
+		assert_ok!(Bridge::transfer_fungible(SENDER, dest_id, r_id, vec![1], 10.into()));
+		assert_ok!(Bridge::acknowledge_transfer(Origin::signed(RELAYER_A), dest_id, 1, true));
+
+		let expiry = TransferLifetime::get() + 1;
+		Bridge::on_initialize(expiry - 1);
+		assert_eq!(transfer_results(), vec![]);
+
+		System::set_block_number(expiry);
+		assert_noop!(
+			Bridge::acknowledge_transfer(Origin::signed(RELAYER_B), dest_id, 1, true),
+			Error::<Test>::ProposalExpired
+		);
+		Bridge::on_initialize(expiry);
+		assert_eq!(transfer_results(), vec![(dest_id, 1, TransferOutcome::TimedOut)]);
+		assert_eq!(Bridge::pending_transfers(dest_id, 1), None);
+		assert_events(vec![Event::Bridge(crate::Event::TransferResolved(
+			dest_id,
+			1,
+			TransferOutcome::TimedOut,
+		))]);
+	})
+}
+
+#[test]
+fn relayer_weights() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Bridge::add_relayer(Origin::root(), RELAYER_A));
+		assert_ok!(Bridge::add_relayer(Origin::root(), RELAYER_B));
+		assert_eq!(Bridge::total_relayer_weight(), 2);
+
+		assert_noop!(
+			Bridge::set_relayer_weight(Origin::root(), RELAYER_C, 2),
+			Error::<Test>::RelayerInvalid
+		);
+		assert_noop!(
+			Bridge::set_relayer_weight(Origin::root(), RELAYER_A, 0),
+			Error::<Test>::InvalidRelayerWeight
+		);
+		assert_ok!(Bridge::set_relayer_weight(Origin::root(), RELAYER_A, 5));
+		assert_eq!(Bridge::relayer_weight(RELAYER_A), 5);
+		assert_eq!(Bridge::total_relayer_weight(), 6);
+
+		assert_ok!(Bridge::remove_relayer(Origin::root(), RELAYER_A));
+		assert_eq!(Bridge::relayer_weight(RELAYER_A), 1);
+		assert_eq!(Bridge::total_relayer_weight(), 1);
+
+		assert_noop!(
+			Bridge::set_threshold_ratio(Origin::root(), Some(Perbill::zero())),
+			Error::<Test>::InvalidThreshold
+		);
+		assert_ok!(Bridge::set_threshold_ratio(Origin::root(), Some(Perbill::from_percent(60))));
+		assert_eq!(Bridge::threshold_weight(), 1);
+
+		assert_events(vec![
+			Event::Bridge(crate::Event::RelayerWeightChanged(RELAYER_A, 5)),
+			snapshot(&[(RELAYER_A, 5), (RELAYER_B, 1)], 1),
+			Event::Bridge(crate::Event::RelayerRemoved(RELAYER_A)),
+			snapshot(&[(RELAYER_B, 1)], 1),
+			Event::Bridge(crate::Event::RelayerThresholdRatioChanged(Some(Perbill::from_percent(
+				60,
+			)))),
+			snapshot(&[(RELAYER_B, 1)], 1),
+		]);
+	})
+}
+
+#[test]
+fn catalogue_lists_bridge_configuration() {
+	let src_id = 1;
+	let r_id = derive_resource_id(src_id, b"remark");
+
+	new_test_ext_initialized(src_id, r_id, b"System.remark".to_vec()).execute_with(|| {
+		assert_ok!(Bridge::whitelist_chain(Origin::root(), 2));
+		assert_ok!(Bridge::transfer_fungible(SENDER, src_id, r_id, vec![1; 32], 10.into()));
+		assert_ok!(Bridge::set_relayer_weight(Origin::root(), RELAYER_A, 3));
+		assert_ok!(Bridge::remove_relayer(Origin::root(), RELAYER_C));
+
+		assert_eq!(
+			Bridge::catalogue(),
+			BridgeCatalogue {
+				resources: vec![(r_id, b"System.remark".to_vec())],
+				chains: vec![
+					BridgeChain { chain_id: src_id, deposit_nonce: 1, expected_nonce: 1 },
+					BridgeChain { chain_id: 2, deposit_nonce: 0, expected_nonce: 1 },
+				],
+				relayers: vec![(RELAYER_A, 3), (RELAYER_B, 1)],
+				threshold: TEST_THRESHOLD,
+			}
+		);
+	})
+}
+
+#[test]
+fn relayer_snapshots_are_bounded() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Bridge::add_relayer(Origin::root(), RELAYER_A));
+		assert_ok!(Bridge::add_relayer(Origin::root(), RELAYER_B));
+		System::set_block_number(2);
+		assert_ok!(Bridge::set_relayer_weight(Origin::root(), RELAYER_B, 2));
+		assert_ok!(Bridge::set_threshold(Origin::root(), TEST_THRESHOLD));
+
+		// The snapshot of the first relayer has been dropped
+		let snapshots = Bridge::relayer_snapshots();
+		assert_eq!(
+			snapshots.iter().map(|s| (s.count, s.block_number)).collect::<Vec<_>>(),
+			vec![(2, 1), (2, 2), (2, 2)]
+		);
+		assert_eq!(
+			snapshot(&[(RELAYER_A, 1), (RELAYER_B, 2)], TEST_THRESHOLD),
+			Event::Bridge(crate::Event::RelayerSetSnapshot(snapshots[2].hash, 2))
+		);
+		assert_ne!(snapshots[1].hash, snapshots[2].hash);
+	})
+}
+
+#[test]
+fn weighted_proposal_approved() {
+	let src_id = 1;
+	let r_id = derive_resource_id(src_id, b"remark");
+
+	new_test_ext_initialized(src_id, r_id, b"System.remark".to_vec()).execute_with(|| {
+		let prop_id = 1;
+		let proposal = make_proposal(vec![10]);
+
+		// 60% of a total weight of 5
+		assert_ok!(Bridge::set_relayer_weight(Origin::root(), RELAYER_A, 3));
+		assert_ok!(Bridge::set_threshold_ratio(Origin::root(), Some(Perbill::from_percent(60))));
+		assert_eq!(Bridge::threshold_weight(), 3);
+
+		assert_ok!(Bridge::acknowledge_proposal(
+			Origin::signed(RELAYER_A),
+			prop_id,
+			src_id,
+			r_id,
+			Box::new(proposal.clone())
+		));
+		let prop = Bridge::votes(src_id, (prop_id, proposal)).unwrap();
+		assert_eq!(prop.status, ProposalStatus::Approved);
+	})
+}
+
+#[test]
+fn weighted_proposal_rejected() {
+	let src_id = 1;
+	let r_id = derive_resource_id(src_id, b"remark");
+
+	new_test_ext_initialized(src_id, r_id, b"System.remark".to_vec()).execute_with(|| {
+		let prop_id = 1;
+		let proposal = make_proposal(vec![10]);
+
+		assert_ok!(Bridge::set_relayer_weight(Origin::root(), RELAYER_C, 3));
+		assert_ok!(Bridge::set_threshold_ratio(Origin::root(), Some(Perbill::from_percent(60))));
+
+		// Two relayers in favour do not outweigh the third
+		assert_ok!(Bridge::acknowledge_proposal(
+			Origin::signed(RELAYER_A),
+			prop_id,
+			src_id,
+			r_id,
+			Box::new(proposal.clone())
+		));
+		assert_ok!(Bridge::acknowledge_proposal(
+			Origin::signed(RELAYER_B),
+			prop_id,
+			src_id,
+			r_id,
+			Box::new(proposal.clone())
+		));
+		let prop = Bridge::votes(src_id, (prop_id, proposal.clone())).unwrap();
+		assert_eq!(prop.status, ProposalStatus::Initiated);
+
+		assert_ok!(Bridge::reject_proposal(
+			Origin::signed(RELAYER_C),
+			prop_id,
+			src_id,
+			r_id,
+			Box::new(proposal.clone())
+		));
+		let prop = Bridge::votes(src_id, (prop_id, proposal)).unwrap();
+		assert_eq!(prop.status, ProposalStatus::Rejected);
+	})
+}
+
+#[test]
+fn resolved_nonce_cannot_be_replayed() {
+	let src_id = 1;
+	let r_id = derive_resource_id(src_id, b"remark");
+
+	new_test_ext_initialized(src_id, r_id, b"System.remark".to_vec()).execute_with(|| {
+		let prop_id = 1;
+		assert_ok!(Bridge::set_threshold(Origin::root(), 1));
+		assert_ok!(Bridge::acknowledge_proposal(
+			Origin::signed(RELAYER_A),
+			prop_id,
+			src_id,
+			r_id,
+			Box::new(make_proposal(vec![10]))
+		));
+		assert!(Bridge::nonce_resolved(src_id, prop_id));
+		assert_eq!(Bridge::expected_nonce(src_id), 2);
+
+		// Same nonce with a different call
+		assert_noop!(
+			Bridge::acknowledge_proposal(
+				Origin::signed(RELAYER_B),
+				prop_id,
+				src_id,
+				r_id,
+				Box::new(make_proposal(vec![11]))
+			),
+			Error::<Test>::NonceAlreadyResolved
+		);
+
+		assert_noop!(Bridge::resync_nonce(Origin::signed(1), src_id), Error::<Test>::MustBeRelayer);
+		assert_ok!(Bridge::resync_nonce(Origin::signed(RELAYER_A), src_id));
+		assert_events(vec![Event::Bridge(crate::Event::ExpectedNonce(src_id, 2))]);
+	})
+}
+
+#[test]
+fn nonce_window_prunes_old_nonces() {
+	let src_id = 1;
+	let r_id = derive_resource_id(src_id, b"remark");
+
+	new_test_ext_initialized(src_id, r_id, b"System.remark".to_vec()).execute_with(|| {
+		assert_ok!(Bridge::set_threshold(Origin::root(), 1));
+		let resolve = |nonce| {
+			Bridge::acknowledge_proposal(
+				Origin::signed(RELAYER_A),
+				nonce,
+				src_id,
+				r_id,
+				Box::new(make_proposal(vec![10])),
+			)
+		};
+		assert_ok!(resolve(1));
+		assert_ok!(resolve(3));
+		assert_eq!(Bridge::resolved_nonces(src_id, 0), 0b1010);
+
+		// Moving the window by more than a bitmap entry prunes it
+		let nonce = NonceWindow::get() + 64;
+		assert_ok!(resolve(nonce));
+		assert_eq!(Bridge::nonce_window_start(src_id), 65);
+		assert_eq!(Bridge::resolved_nonces(src_id, 0), 0);
+		assert_noop!(resolve(2), Error::<Test>::NonceTooOld);
+
+		// Nonces within the window can still be resolved out of order
+		assert_ok!(resolve(nonce - 1));
+		assert!(Bridge::nonce_resolved(src_id, nonce - 1));
+		assert!(!Bridge::nonce_resolved(src_id, nonce - 2));
+		assert_eq!(Bridge::expected_nonce(src_id), nonce + 1);
+	})
+}
+
+#[test]
+fn relayer_votes_are_rate_limited() {
+	let src_id = 1;
+	let r_id = derive_resource_id(src_id, b"remark");
+
+	new_test_ext_initialized(src_id, r_id, b"System.remark".to_vec()).execute_with(|| {
+		let vote = |relayer, nonce| {
+			Bridge::acknowledge_proposal(
+				Origin::signed(relayer),
+				nonce,
+				src_id,
+				r_id,
+				Box::new(make_proposal(vec![10])),
+			)
+		};
+		for nonce in 1..=MaxVotesPerBlock::get() as u64 {
+			assert_ok!(vote(RELAYER_A, nonce));
+		}
+		assert_noop!(vote(RELAYER_A, 6), Error::<Test>::TooManyVotes);
+		assert_noop!(
+			Bridge::reject_proposal(
+				Origin::signed(RELAYER_A),
+				6,
+				src_id,
+				r_id,
+				Box::new(make_proposal(vec![10])),
+			),
+			Error::<Test>::TooManyVotes
+		);
+
+		// Other relayers are not affected
+		assert_ok!(vote(RELAYER_B, 6));
+
+		System::set_block_number(2);
+		assert_ok!(vote(RELAYER_A, 7));
+		assert_eq!(Bridge::votes_this_block(&RELAYER_A), 1);
+	})
+}
+
+#[test]
+fn relayer_calls_are_operational() {
+	let r_id = derive_resource_id(1, b"remark");
+	let call = crate::Call::<Test>::acknowledge_proposal {
+		nonce: 1,
+		src_id: 1,
+		r_id,
+		call: Box::new(make_proposal(vec![10])),
+	};
+	assert_eq!(call.get_dispatch_info().class, DispatchClass::Operational);
+	let call = crate::Call::<Test>::acknowledge_transfer { dest_id: 1, nonce: 1, succeeded: true };
+	assert_eq!(call.get_dispatch_info().class, DispatchClass::Operational);
+}
+
+#[test]
+fn paused_bridge_rejects_transfers_and_proposals() {
+	let src_id = 1;
+	let r_id = derive_resource_id(src_id, b"remark");
+
+	new_test_ext_initialized(src_id, r_id, b"System.remark".to_vec()).execute_with(|| {
+		assert_ok!(Bridge::transfer_fungible(SENDER, src_id, r_id, vec![1; 32], 10.into()));
+
+		assert_noop!(Bridge::pause(Origin::root()), sp_runtime::DispatchError::BadOrigin);
+		assert_ok!(Bridge::pause(Origin::signed(PauseAdmin::get())));
+		assert!(Bridge::is_paused());
+
+		assert_noop!(
+			Bridge::transfer_fungible(SENDER, src_id, r_id, vec![1; 32], 10.into()),
+			Error::<Test>::BridgeIsPaused
+		);
+		assert_noop!(
+			Bridge::transfer_generic(SENDER, src_id, r_id, vec![]),
+			Error::<Test>::BridgeIsPaused
+		);
+		assert_noop!(
+			Bridge::acknowledge_proposal(
+				Origin::signed(RELAYER_A),
+				1,
+				src_id,
+				r_id,
+				Box::new(make_proposal(vec![10])),
+			),
+			Error::<Test>::BridgeIsPaused
+		);
+		// Results of earlier transfers can still be reported
+		assert_ok!(Bridge::acknowledge_transfer(Origin::signed(RELAYER_A), src_id, 1, true));
+
+		assert_ok!(Bridge::resume(Origin::signed(PauseAdmin::get())));
+		assert_ok!(Bridge::transfer_generic(SENDER, src_id, r_id, vec![]));
+
+		assert_events(vec![
+			Event::Bridge(crate::Event::BridgePaused),
+			Event::Bridge(crate::Event::TransferResultVoted(src_id, 1, RELAYER_A, true)),
+			Event::Bridge(crate::Event::BridgeResumed),
+			Event::Bridge(crate::Event::GenericTransfer(src_id, 2, r_id, vec![], SENDER)),
+		]);
+	})
+}
+
+#[test]
+fn only_allowed_calls_can_be_proposed() {
+	let src_id = 1;
+	let r_id = derive_resource_id(src_id, b"remark");
+
+	new_test_ext_initialized(src_id, r_id, b"System.remark".to_vec()).execute_with(|| {
+		let transfer = Call::Balances(pallet_balances::Call::transfer { dest: SENDER, value: 10 });
+		assert_noop!(
+			Bridge::acknowledge_proposal(
+				Origin::signed(RELAYER_A),
+				1,
+				src_id,
+				r_id,
+				Box::new(transfer),
+			),
+			Error::<Test>::ProposalNotAllowed
+		);
+
+		// Pending proposals of a disallowed call are not executed
+		let proposal = make_proposal(vec![10]);
+		assert_ok!(Bridge::acknowledge_proposal(
+			Origin::signed(RELAYER_A),
+			1,
+			src_id,
+			r_id,
+			Box::new(proposal.clone())
+		));
+		assert_noop!(
+			Bridge::disallow_proposal_call(
+				Origin::signed(RELAYER_A),
+				b"System".to_vec(),
+				b"remark".to_vec()
+			),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(Bridge::disallow_proposal_call(
+			Origin::root(),
+			b"System".to_vec(),
+			b"remark".to_vec()
+		));
+		assert!(!Bridge::is_allowed_proposal(&proposal));
+		assert_ok!(Bridge::set_threshold(Origin::root(), 1));
+		assert_ok!(Bridge::eval_vote_state(
+			Origin::signed(RELAYER_B),
+			1,
+			src_id,
+			Box::new(proposal)
+		));
+		assert_events(vec![
+			Event::Bridge(crate::Event::ProposalApproved(src_id, 1)),
+			Event::Bridge(crate::Event::ProposalFailed(src_id, 1)),
+		]);
+
+		assert_ok!(Bridge::allow_proposal_call(
+			Origin::root(),
+			b"System".to_vec(),
+			b"remark".to_vec()
+		));
+		assert_noop!(
+			Bridge::allow_proposal_call(Origin::root(), b"System".to_vec(), b"remark".to_vec()),
+			Error::<Test>::ProposalCallAlreadyAllowed
+		);
+		assert_events(vec![
+			Event::Bridge(crate::Event::ProposalCallDisallowed(
+				b"System".to_vec(),
+				b"remark".to_vec(),
+			)),
+			Event::Bridge(crate::Event::ProposalCallAllowed(
+				b"System".to_vec(),
+				b"remark".to_vec(),
+			)),
+		]);
+	})
+}
+
+#[test]
+fn upgrade_allows_default_proposals() {
+	new_test_ext().execute_with(|| {
+		AllowedProposals::<Test>::remove(b"System".to_vec(), b"remark".to_vec());
+		StorageVersion::new(2).put::<Bridge>();
+
+		Bridge::on_runtime_upgrade();
+		assert!(Bridge::allowed_proposals(b"System".to_vec(), b"remark".to_vec()));
+		assert_eq!(StorageVersion::get::<Bridge>(), 3);
+	})
+}
+
+#[test]
+fn ordered_delivery_applies_proposals_in_nonce_order() {
+	let src_id = 1;
+	let r_id = derive_resource_id(src_id, b"remark");
+
+	new_test_ext_initialized(src_id, r_id, b"System.remark".to_vec()).execute_with(|| {
+		let acknowledge = |nonce: DepositNonce| {
+			Bridge::acknowledge_proposal(
+				Origin::signed(RELAYER_A),
+				nonce,
+				src_id,
+				r_id,
+				Box::new(make_proposal(vec![nonce as u8])),
+			)
+		};
+		assert_ok!(Bridge::set_threshold(Origin::root(), 1));
+		assert_noop!(
+			Bridge::set_ordered_delivery(Origin::signed(RELAYER_A), src_id, true),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Bridge::set_ordered_delivery(Origin::root(), 2, true),
+			Error::<Test>::ChainNotWhitelisted
+		);
+		assert_ok!(Bridge::set_ordered_delivery(Origin::root(), src_id, true));
+		assert_noop!(
+			Bridge::set_ordered_delivery(Origin::root(), src_id, true),
+			Error::<Test>::OrderedDeliveryUnchanged
+		);
+
+		// Nonces ahead of the next one wait, up to `MaxQueuedProposals`
+		assert_ok!(acknowledge(2));
+		assert_ok!(acknowledge(3));
+		assert_ok!(acknowledge(4));
+		assert_events(vec![
+			Event::Bridge(crate::Event::VoteFor(src_id, 4, RELAYER_A)),
+			Event::Bridge(crate::Event::ProposalQueued(src_id, 4)),
+		]);
+		assert_err!(acknowledge(5), Error::<Test>::DeliveryQueueFull);
+		assert_eq!(
+			Bridge::ordered_delivery(src_id),
+			Some(OrderedDelivery { next_nonce: 1, queued: 3, stalled_since: Some(1) })
+		);
+		assert_eq!(Bridge::queued_proposal(src_id, 3), Some(Some(make_proposal(vec![3]))));
+		assert_noop!(
+			Bridge::set_ordered_delivery(Origin::root(), src_id, false),
+			Error::<Test>::DeliveryQueueNotEmpty
+		);
+
+		// The next nonce is applied right away, the queue in the next block
+		assert_ok!(acknowledge(1));
+		assert_events(vec![
+			Event::Bridge(crate::Event::ProposalApproved(src_id, 1)),
+			Event::Bridge(crate::Event::ProposalSucceeded(src_id, 1)),
+		]);
+		System::set_block_number(2);
+		Bridge::on_initialize(2);
+		assert_events(vec![
+			Event::Bridge(crate::Event::ProposalApproved(src_id, 2)),
+			Event::Bridge(crate::Event::ProposalSucceeded(src_id, 2)),
+			Event::Bridge(crate::Event::ProposalApproved(src_id, 3)),
+			Event::Bridge(crate::Event::ProposalSucceeded(src_id, 3)),
+			Event::Bridge(crate::Event::ProposalApproved(src_id, 4)),
+			Event::Bridge(crate::Event::ProposalSucceeded(src_id, 4)),
+		]);
+		assert_eq!(
+			Bridge::ordered_delivery(src_id),
+			Some(OrderedDelivery { next_nonce: 5, queued: 0, stalled_since: None })
+		);
+		assert_eq!(Bridge::queued_proposal(src_id, 3), None);
+
+		// The vote kept while the queue was full resolves the proposal
+		assert_ok!(Bridge::eval_vote_state(
+			Origin::signed(RELAYER_B),
+			5,
+			src_id,
+			Box::new(make_proposal(vec![5]))
+		));
+		assert_events(vec![Event::Bridge(crate::Event::ProposalSucceeded(src_id, 5))]);
+
+		assert_ok!(Bridge::set_ordered_delivery(Origin::root(), src_id, false));
+		assert_eq!(Bridge::ordered_delivery(src_id), None);
+	})
+}
+
+#[test]
+fn stalled_delivery_nonce_can_be_skipped() {
+	let src_id = 1;
+	let r_id = derive_resource_id(src_id, b"remark");
+
+	new_test_ext_initialized(src_id, r_id, b"System.remark".to_vec()).execute_with(|| {
+		let acknowledge = |nonce: DepositNonce| {
+			Bridge::acknowledge_proposal(
+				Origin::signed(RELAYER_A),
+				nonce,
+				src_id,
+				r_id,
+				Box::new(make_proposal(vec![nonce as u8])),
+			)
+		};
+		assert_ok!(Bridge::set_threshold(Origin::root(), 1));
+		assert_noop!(
+			Bridge::skip_delivery_nonce(Origin::root(), src_id),
+			Error::<Test>::OrderedDeliveryNotEnabled
+		);
+		assert_ok!(Bridge::set_ordered_delivery(Origin::root(), src_id, true));
+		assert_noop!(
+			Bridge::skip_delivery_nonce(Origin::root(), src_id),
+			Error::<Test>::DeliveryNotStalled
+		);
+
+		assert_ok!(acknowledge(2));
+		System::set_block_number(10);
+		assert_noop!(
+			Bridge::skip_delivery_nonce(Origin::root(), src_id),
+			Error::<Test>::DeliveryNotStalled
+		);
+		System::set_block_number(11);
+		assert_noop!(
+			Bridge::skip_delivery_nonce(Origin::signed(RELAYER_A), src_id),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(Bridge::skip_delivery_nonce(Origin::root(), src_id));
+		assert_events(vec![Event::Bridge(crate::Event::DeliveryNonceSkipped(src_id, 1))]);
+		// The skipped nonce can no longer be proposed, nor the queued one skipped
+		assert!(Bridge::nonce_resolved(src_id, 1));
+		assert_noop!(acknowledge(1), Error::<Test>::NonceAlreadyResolved);
+		assert_noop!(
+			Bridge::skip_delivery_nonce(Origin::root(), src_id),
+			Error::<Test>::DeliveryNotStalled
+		);
+
+		Bridge::on_initialize(12);
+		assert_events(vec![
+			Event::Bridge(crate::Event::ProposalApproved(src_id, 2)),
+			Event::Bridge(crate::Event::ProposalSucceeded(src_id, 2)),
+		]);
+		assert_eq!(
+			Bridge::ordered_delivery(src_id),
+			Some(OrderedDelivery { next_nonce: 3, queued: 0, stalled_since: None })
+		);
+	})
+}
@@ -0,0 +1,176 @@
+// Ensure we're `no_std` when compiling for Wasm.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Crowdloan Rewards Pallet
+//!
+//! Distributes rewards to crowdloan contributors out of the pallet account, which has to be
+//! funded up front.
+//!
+//! Governance sets the Merkle root of all contributions with `set_merkle_root`. Every leaf is the
+//! `primitives::merkle` leaf of `(index, contributor, amount)`. Contributors `claim` their reward
+//! with the Merkle proof of their leaf. `InitialUnlock` of the reward is free right away, the rest
+//! vests linearly over `VestingPeriod` blocks. Claimed leaves are tracked so that every reward is
+//! paid out once.
+
+use frame_support::{
+	traits::{Currency, ExistenceRequirement, VestingSchedule},
+	PalletId,
+};
+use primitives::{
+	merkle::{self, MerkleHash},
+	Balance,
+};
+use sp_runtime::{
+	traits::{AccountIdConversion, UniqueSaturatedInto},
+	Perbill,
+};
+use sp_std::vec::Vec;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+pub use pallet::*;
+
+/// Position of a contribution in the contribution tree
+pub type LeafIndex = u32;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// Currency the rewards are paid in
+		type Currency: Currency<Self::AccountId, Balance = Balance>;
+		/// Vesting of the locked part of the rewards
+		type Vesting: VestingSchedule<
+			Self::AccountId,
+			Moment = Self::BlockNumber,
+			Currency = Self::Currency,
+		>;
+		/// Account holding the rewards
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+		/// Part of the reward which is not vested
+		#[pallet::constant]
+		type InitialUnlock: Get<Perbill>;
+		/// Number of blocks the rest of the reward vests over
+		#[pallet::constant]
+		type VestingPeriod: Get<Self::BlockNumber>;
+		/// Maximum number of hashes in a Merkle proof
+		#[pallet::constant]
+		type MaxProofLength: Get<u32>;
+		/// Origin allowed to set the Merkle root
+		type GovernanceOrigin: EnsureOrigin<Self::Origin>;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Merkle root of the contributions is set (root)
+		MerkleRootSet(MerkleHash),
+		/// Reward is claimed (contributor, index, amount)
+		RewardClaimed(T::AccountId, LeafIndex, Balance),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Merkle root is not set yet
+		NoMerkleRoot,
+		/// Proof does not lead to the Merkle root
+		InvalidProof,
+		/// Proof has more than `MaxProofLength` hashes
+		ProofTooLong,
+		/// Reward has already been claimed
+		AlreadyClaimed,
+	}
+
+	#[pallet::storage]
+	#[pallet::getter(fn merkle_root)]
+	/// Merkle root of the contributions
+	pub type MerkleRoot<T> = StorageValue<_, MerkleHash>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn claimed)]
+	/// Leaves whose reward has been claimed
+	pub type Claimed<T> = StorageMap<_, Identity, MerkleHash, bool, ValueQuery>;
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Sets the Merkle root of the contributions.
+		///
+		/// Leaves claimed under a previous root stay claimed.
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_merkle_root(origin: OriginFor<T>, root: MerkleHash) -> DispatchResult {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+
+			MerkleRoot::<T>::put(root);
+			Self::deposit_event(Event::MerkleRootSet(root));
+			Ok(())
+		}
+
+		/// Claims the reward `amount` of the contribution at `index` with its Merkle `proof`.
+		///
+		/// # <weight>
+		/// - O(P) hashes where P is the proof length, bounded by `MaxProofLength`
+		/// # </weight>
+		#[pallet::weight(T::DbWeight::get().reads_writes(5, 5))]
+		pub fn claim(
+			origin: OriginFor<T>,
+			index: LeafIndex,
+			amount: Balance,
+			proof: Vec<MerkleHash>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(proof.len() as u32 <= T::MaxProofLength::get(), Error::<T>::ProofTooLong);
+			let root = Self::merkle_root().ok_or(Error::<T>::NoMerkleRoot)?;
+			let leaf = Self::leaf(index, &who, amount);
+			ensure!(!Self::claimed(leaf), Error::<T>::AlreadyClaimed);
+			ensure!(merkle::verify_proof(root, leaf, &proof), Error::<T>::InvalidProof);
+
+			let locked = amount.saturating_sub(T::InitialUnlock::get() * amount);
+			let period: Balance = T::VestingPeriod::get().unique_saturated_into();
+			let per_block = (locked / period.max(1)).max(1);
+			let now = frame_system::Pallet::<T>::block_number();
+			if locked > 0 {
+				T::Vesting::can_add_vesting_schedule(&who, locked, per_block, now)?;
+			}
+
+			T::Currency::transfer(
+				&Self::account_id(),
+				&who,
+				amount,
+				ExistenceRequirement::AllowDeath,
+			)?;
+			if locked > 0 {
+				T::Vesting::add_vesting_schedule(&who, locked, per_block, now)?;
+			}
+			Claimed::<T>::insert(leaf, true);
+
+			Self::deposit_event(Event::RewardClaimed(who, index, amount));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Account holding the rewards
+	pub fn account_id() -> T::AccountId {
+		T::PalletId::get().into_account()
+	}
+
+	/// Leaf of the contribution tree for the reward `amount` of `who` at `index`
+	pub fn leaf(index: LeafIndex, who: &T::AccountId, amount: Balance) -> MerkleHash {
+		merkle::leaf(&(index, who, amount))
+	}
+}
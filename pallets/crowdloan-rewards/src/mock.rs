@@ -0,0 +1,132 @@
+#![cfg(test)]
+
+use frame_support::{
+	parameter_types,
+	traits::{ConstU32, Everything},
+	PalletId,
+};
+use frame_system as system;
+use primitives::Balance;
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{AccountIdConversion, BlakeTwo256, ConvertInto, IdentityLookup},
+	Perbill,
+};
+
+use crate::{self as crowdloan_rewards, Config};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+pub const ALICE: u64 = 1;
+pub const BOB: u64 = 2;
+pub const CHARLIE: u64 = 3;
+
+frame_support::construct_runtime!(
+	pub enum Test where
+	 Block = Block,
+	 NodeBlock = Block,
+	 UncheckedExtrinsic = UncheckedExtrinsic,
+	 {
+		 System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		 Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		 Vesting: pallet_vesting::{Pallet, Call, Storage, Event<T>},
+		 CrowdloanRewards: crowdloan_rewards::{Pallet, Call, Storage, Event<T>},
+	 }
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 63;
+}
+
+impl system::Config for Test {
+	type OnSetCode = ();
+	type BaseCallFilter = Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type DbWeight = ();
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<Balance>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type MaxConsumers = ConstU32<16>;
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: Balance = 1;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type MaxLocks = ConstU32<50>;
+	type Balance = Balance;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const MinVestedTransfer: Balance = 1;
+}
+
+impl pallet_vesting::Config for Test {
+	type Event = Event;
+	type Currency = Balances;
+	type BlockNumberToBalance = ConvertInto;
+	type MinVestedTransfer = MinVestedTransfer;
+	type WeightInfo = ();
+	const MAX_VESTING_SCHEDULES: u32 = 3;
+}
+
+parameter_types! {
+	pub const CrowdloanPalletId: PalletId = PalletId(*b"stnd/cwr");
+	pub const InitialUnlock: Perbill = Perbill::from_percent(20);
+	pub const VestingPeriod: u64 = 100;
+	pub const MaxProofLength: u32 = 4;
+}
+
+impl Config for Test {
+	type Event = Event;
+	type Currency = Balances;
+	type Vesting = Vesting;
+	type PalletId = CrowdloanPalletId;
+	type InitialUnlock = InitialUnlock;
+	type VestingPeriod = VestingPeriod;
+	type MaxProofLength = MaxProofLength;
+	type GovernanceOrigin = frame_system::EnsureRoot<u64>;
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(CrowdloanPalletId::get().into_account(), 100_000)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+pub fn last_event() -> Event {
+	system::Pallet::<Test>::events().pop().expect("Event expected").event
+}
@@ -0,0 +1,90 @@
+use crate::{mock::*, Error, Event as CrowdloanEvent};
+use frame_support::{assert_noop, assert_ok, traits::VestingSchedule};
+use primitives::merkle::{hash_pair, MerkleHash};
+
+fn leaves() -> [MerkleHash; 3] {
+	[
+		CrowdloanRewards::leaf(0, &ALICE, 1_000),
+		CrowdloanRewards::leaf(1, &BOB, 500),
+		CrowdloanRewards::leaf(2, &CHARLIE, 2_000),
+	]
+}
+
+/// Root of the tree with the leaves of ALICE and BOB on one branch and CHARLIE on the other
+fn root() -> MerkleHash {
+	let [alice, bob, charlie] = leaves();
+	hash_pair(hash_pair(alice, bob), charlie)
+}
+
+#[test]
+fn set_merkle_root_requires_governance() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			CrowdloanRewards::set_merkle_root(Origin::signed(ALICE), root()),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(CrowdloanRewards::set_merkle_root(Origin::root(), root()));
+		assert_eq!(CrowdloanRewards::merkle_root(), Some(root()));
+		assert_eq!(last_event(), Event::CrowdloanRewards(CrowdloanEvent::MerkleRootSet(root())));
+	})
+}
+
+#[test]
+fn claim_pays_vested_reward() {
+	new_test_ext().execute_with(|| {
+		let [alice, bob, charlie] = leaves();
+		assert_noop!(
+			CrowdloanRewards::claim(Origin::signed(ALICE), 0, 1_000, vec![bob, charlie]),
+			Error::<Test>::NoMerkleRoot
+		);
+		assert_ok!(CrowdloanRewards::set_merkle_root(Origin::root(), root()));
+
+		assert_ok!(CrowdloanRewards::claim(Origin::signed(ALICE), 0, 1_000, vec![bob, charlie]));
+		assert_eq!(
+			last_event(),
+			Event::CrowdloanRewards(CrowdloanEvent::RewardClaimed(ALICE, 0, 1_000))
+		);
+		assert_eq!(Balances::free_balance(ALICE), 1_000);
+		assert_eq!(Balances::free_balance(CrowdloanRewards::account_id()), 99_000);
+		// 20% is unlocked right away, the rest vests over 100 blocks
+		assert_eq!(Vesting::vesting_balance(&ALICE), Some(800));
+		System::set_block_number(51);
+		assert_eq!(Vesting::vesting_balance(&ALICE), Some(400));
+
+		assert_ok!(CrowdloanRewards::claim(
+			Origin::signed(CHARLIE),
+			2,
+			2_000,
+			vec![hash_pair(alice, bob)]
+		));
+		assert_eq!(Balances::free_balance(CHARLIE), 2_000);
+	})
+}
+
+#[test]
+fn claim_rejects_invalid_and_repeated_claims() {
+	new_test_ext().execute_with(|| {
+		let [alice, bob, charlie] = leaves();
+		assert_ok!(CrowdloanRewards::set_merkle_root(Origin::root(), root()));
+
+		// Wrong amount, wrong contributor and a proof that is too long
+		assert_noop!(
+			CrowdloanRewards::claim(Origin::signed(BOB), 1, 5_000, vec![alice, charlie]),
+			Error::<Test>::InvalidProof
+		);
+		assert_noop!(
+			CrowdloanRewards::claim(Origin::signed(CHARLIE), 1, 500, vec![alice, charlie]),
+			Error::<Test>::InvalidProof
+		);
+		assert_noop!(
+			CrowdloanRewards::claim(Origin::signed(BOB), 1, 500, vec![alice; 5]),
+			Error::<Test>::ProofTooLong
+		);
+
+		assert_ok!(CrowdloanRewards::claim(Origin::signed(BOB), 1, 500, vec![alice, charlie]));
+		assert_noop!(
+			CrowdloanRewards::claim(Origin::signed(BOB), 1, 500, vec![alice, charlie]),
+			Error::<Test>::AlreadyClaimed
+		);
+	})
+}
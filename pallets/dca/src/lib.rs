@@ -0,0 +1,314 @@
+// Ensure we're `no_std` when compiling for Wasm.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # DCA Pallet
+//!
+//! Dollar-cost averaging orders on the Standard market.
+//!
+//! `create_order` locks the total amount of the asset to sell in the pallet account and swaps
+//! `amount_per_swap` of it every `period` blocks in `on_initialize`, sending the bought asset to
+//! the owner. A round is skipped while the market price is above the `max_price` of the order.
+//! The order ends once the locked amount is used up, or when the owner cancels it and gets the
+//! rest refunded. An order that does not swap for `MaxSkippedRounds` rounds in a row is cancelled
+//! the same way, so orders that can never fill do not hold on to the schedule.
+//!
+//! Creating an order reserves `OrderDeposit` of the native currency of the owner until the order
+//! ends.
+
+use codec::{Decode, Encode};
+use frame_support::{
+	traits::{tokens::fungibles::Transfer, ReservableCurrency},
+	transactional, PalletId,
+};
+use pallet_standard_market as market;
+use primitives::{AssetId, Balance};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{AccountIdConversion, One, Zero},
+	FixedPointNumber, FixedU128, RuntimeDebug,
+};
+use sp_std::vec::Vec;
+
+pub use pallet::*;
+
+/// Identifier of an order
+pub type OrderId = u64;
+
+type AssetsOf<T> = <T as market::Config>::Assets;
+type CurrencyOf<T> = <T as market::Config>::Currency;
+
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct Order<AccountId, BlockNumber> {
+	pub owner: AccountId,
+	/// Asset sold
+	pub from: AssetId,
+	/// Asset bought
+	pub to: AssetId,
+	pub amount_per_swap: Balance,
+	/// Amount of `from` left to be swapped
+	pub remaining: Balance,
+	/// Blocks between swaps
+	pub period: BlockNumber,
+	/// Highest price paid in `from` per unit of `to`
+	pub max_price: FixedU128,
+	/// Block the next swap is scheduled at
+	pub next: BlockNumber,
+	/// Rounds in a row that were skipped or failed
+	pub skipped: u32,
+	/// Native currency reserved from the owner
+	pub deposit: Balance,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::without_storage_info]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + market::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// Account holding the assets locked by the orders
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+		/// Maximum number of orders executed in one block
+		#[pallet::constant]
+		type MaxOrdersPerBlock: Get<u32>;
+		/// Rounds in a row an order may skip or fail before it is cancelled
+		#[pallet::constant]
+		type MaxSkippedRounds: Get<u32>;
+		/// Native currency reserved from the owner of an order while it is open
+		#[pallet::constant]
+		type OrderDeposit: Get<Balance>;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Order is created (order_id, owner, from, to, total_amount)
+		OrderCreated(OrderId, T::AccountId, AssetId, AssetId, Balance),
+		/// Order is executed (order_id, amount_in, amount_out)
+		OrderExecuted(OrderId, Balance, Balance),
+		/// Execution is skipped as the price is above the bound of the order (order_id)
+		OrderSkipped(OrderId),
+		/// Swap of the order failed, it is retried in the next round (order_id, error)
+		OrderFailed(OrderId, DispatchError),
+		/// Order used up the locked amount (order_id)
+		OrderCompleted(OrderId),
+		/// Order is cancelled by its owner (order_id, refunded)
+		OrderCancelled(OrderId, Balance),
+		/// Order is cancelled after `MaxSkippedRounds` rounds in a row without a swap
+		/// (order_id, refunded)
+		OrderExpired(OrderId, Balance),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Order does not exist
+		OrderNotFound,
+		/// Origin does not own the order
+		NotOwner,
+		/// Amount per swap must be non-zero and at most the total amount
+		InvalidAmount,
+		/// Period must be non-zero
+		InvalidPeriod,
+		/// Market has no pool for the pair
+		InvalidPair,
+		/// Too many orders are due in the first execution block
+		BlockFull,
+	}
+
+	#[pallet::storage]
+	#[pallet::getter(fn order)]
+	pub type Orders<T: Config> =
+		StorageMap<_, Twox64Concat, OrderId, Order<T::AccountId, T::BlockNumber>>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn next_order_id)]
+	pub type NextOrderId<T> = StorageValue<_, OrderId, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn scheduled)]
+	/// Orders to be executed at a block
+	pub type Schedule<T: Config> =
+		StorageMap<_, Twox64Concat, T::BlockNumber, Vec<OrderId>, ValueQuery>;
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let due = Schedule::<T>::take(now);
+			let count = due.len() as u64;
+			for order_id in due {
+				Self::execute(order_id, now);
+			}
+			// Reading and rescheduling or refunding the order on top of the swap
+			T::DbWeight::get().reads_writes(1 + 9 * count, 1 + 9 * count)
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Locks `total_amount` of `from` and swaps `amount_per_swap` of it to `to` every
+		/// `period` blocks, starting `period` blocks from now.
+		///
+		/// `max_price` is the highest price, in `from` per unit of `to`, the order buys at.
+		/// Reserves `OrderDeposit` of the native currency of the sender until the order ends.
+		#[pallet::weight(T::DbWeight::get().reads_writes(4, 5))]
+		#[transactional]
+		pub fn create_order(
+			origin: OriginFor<T>,
+			from: AssetId,
+			to: AssetId,
+			total_amount: Balance,
+			amount_per_swap: Balance,
+			period: T::BlockNumber,
+			max_price: FixedU128,
+		) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+			ensure!(
+				amount_per_swap > 0 && amount_per_swap <= total_amount,
+				Error::<T>::InvalidAmount
+			);
+			ensure!(!period.is_zero(), Error::<T>::InvalidPeriod);
+			ensure!(market::Module::<T>::pair((from, to)).is_some(), Error::<T>::InvalidPair);
+
+			let order_id = NextOrderId::<T>::get();
+			let next = frame_system::Pallet::<T>::block_number() + period;
+			Self::schedule(order_id, next)?;
+			let deposit = T::OrderDeposit::get();
+			CurrencyOf::<T>::reserve(&owner, deposit)?;
+			AssetsOf::<T>::transfer(from, &owner, &Self::account_id(), total_amount, false)?;
+			NextOrderId::<T>::put(order_id + 1);
+			Orders::<T>::insert(
+				order_id,
+				Order {
+					owner: owner.clone(),
+					from,
+					to,
+					amount_per_swap,
+					remaining: total_amount,
+					period,
+					max_price,
+					next,
+					skipped: 0,
+					deposit,
+				},
+			);
+
+			Self::deposit_event(Event::OrderCreated(order_id, owner, from, to, total_amount));
+			Ok(())
+		}
+
+		/// Cancels the order, refunds the amount not swapped yet and releases the deposit.
+		#[pallet::weight(T::DbWeight::get().reads_writes(3, 5))]
+		pub fn cancel_order(origin: OriginFor<T>, order_id: OrderId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let order = Self::order(order_id).ok_or(Error::<T>::OrderNotFound)?;
+			ensure!(order.owner == who, Error::<T>::NotOwner);
+
+			AssetsOf::<T>::transfer(order.from, &Self::account_id(), &who, order.remaining, false)?;
+			Schedule::<T>::mutate(order.next, |orders| orders.retain(|id| *id != order_id));
+			CurrencyOf::<T>::unreserve(&who, order.deposit);
+			Orders::<T>::remove(order_id);
+
+			Self::deposit_event(Event::OrderCancelled(order_id, order.remaining));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Account holding the assets locked by the orders
+	pub fn account_id() -> T::AccountId {
+		T::PalletId::get().into_account()
+	}
+
+	fn schedule(order_id: OrderId, at: T::BlockNumber) -> Result<(), Error<T>> {
+		Schedule::<T>::try_mutate(at, |orders| {
+			if orders.len() as u32 >= T::MaxOrdersPerBlock::get() {
+				return Err(Error::<T>::BlockFull)
+			}
+			orders.push(order_id);
+			Ok(())
+		})
+	}
+
+	// Run one round of the order and schedule the next one
+	fn execute(order_id: OrderId, now: T::BlockNumber) {
+		let mut order = match Self::order(order_id) {
+			Some(order) => order,
+			None => return,
+		};
+		let amount_in = order.amount_per_swap.min(order.remaining);
+
+		match Self::swap(&order, amount_in) {
+			Ok(Some(amount_out)) => {
+				order.remaining -= amount_in;
+				order.skipped = 0;
+				Self::deposit_event(Event::OrderExecuted(order_id, amount_in, amount_out));
+			},
+			Ok(None) => {
+				order.skipped = order.skipped.saturating_add(1);
+				Self::deposit_event(Event::OrderSkipped(order_id));
+			},
+			Err(error) => {
+				order.skipped = order.skipped.saturating_add(1);
+				Self::deposit_event(Event::OrderFailed(order_id, error));
+			},
+		}
+
+		if order.remaining == 0 {
+			CurrencyOf::<T>::unreserve(&order.owner, order.deposit);
+			Orders::<T>::remove(order_id);
+			Self::deposit_event(Event::OrderCompleted(order_id));
+			return
+		}
+		// Refund an order that keeps missing its rounds. It stays scheduled if the refund fails.
+		if order.skipped >= T::MaxSkippedRounds::get() &&
+			AssetsOf::<T>::transfer(
+				order.from,
+				&Self::account_id(),
+				&order.owner,
+				order.remaining,
+				false,
+			)
+			.is_ok()
+		{
+			CurrencyOf::<T>::unreserve(&order.owner, order.deposit);
+			Orders::<T>::remove(order_id);
+			Self::deposit_event(Event::OrderExpired(order_id, order.remaining));
+			return
+		}
+		// Move to the next free block if the one due is full
+		let mut next = now + order.period;
+		while Self::schedule(order_id, next).is_err() {
+			next += One::one();
+		}
+		order.next = next;
+		Orders::<T>::insert(order_id, order);
+	}
+
+	// Swap `amount_in` for the owner unless the price is above the bound of the order
+	#[transactional]
+	fn swap(
+		order: &Order<T::AccountId, T::BlockNumber>,
+		amount_in: Balance,
+	) -> Result<Option<Balance>, sp_runtime::DispatchError> {
+		let quote = market::Module::<T>::quote(order.from, amount_in, order.to)?;
+		match FixedU128::checked_from_rational(amount_in, quote) {
+			Some(price) if price <= order.max_price => (),
+			_ => return Ok(None),
+		}
+
+		let account = Self::account_id();
+		let amount_out =
+			market::Module::<T>::do_swap(&account, order.from, amount_in, order.to, false)?;
+		AssetsOf::<T>::transfer(order.to, &account, &order.owner, amount_out, false)?;
+		Ok(Some(amount_out))
+	}
+}
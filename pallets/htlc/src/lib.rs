@@ -0,0 +1,194 @@
+// Ensure we're `no_std` when compiling for Wasm.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # HTLC Pallet
+//!
+//! Hashed timelock contracts for trust-minimized OTC swaps with chains that support HTLCs
+//! natively, e.g. chains bridged through chainbridge.
+//!
+//! `lock` moves `amount` of an asset into the pallet account under the sender and the SHA-256
+//! hash of a secret proof. Until `expiry` anyone can `claim` the locked amount for the recipient
+//! by revealing the proof, which is emitted in `Claimed` so the counterparty can use it on the
+//! other chain. From `expiry` on the sender can `refund` the locked amount instead.
+//!
+//! Swaps are keyed by their sender as well as the hashed proof, so a swap seen in the transaction
+//! pool can not be blocked by locking another one under the same hashed proof first.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{traits::tokens::fungibles::Transfer, PalletId};
+use primitives::{AssetId, Balance};
+use scale_info::TypeInfo;
+use sp_runtime::{traits::AccountIdConversion, RuntimeDebug};
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+pub use pallet::*;
+
+/// SHA-256 hash of the proof, identifying a swap of a sender
+pub type HashedProof = [u8; 32];
+
+/// Secret revealed to claim a swap
+pub type Proof = [u8; 32];
+
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct PendingSwap<AccountId, BlockNumber> {
+	pub sender: AccountId,
+	pub recipient: AccountId,
+	pub asset: AssetId,
+	pub amount: Balance,
+	/// Block from which the swap can no longer be claimed and is refundable
+	pub expiry: BlockNumber,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		type Assets: Transfer<Self::AccountId, AssetId = AssetId, Balance = Balance>;
+		/// Account holding the locked amounts of all swaps
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Swap is locked (hashed_proof, sender, recipient, asset, amount, expiry)
+		Locked(HashedProof, T::AccountId, T::AccountId, AssetId, Balance, T::BlockNumber),
+		/// Swap is claimed by the recipient (sender, hashed_proof, proof)
+		Claimed(T::AccountId, HashedProof, Proof),
+		/// Swap expired and is refunded to the sender (sender, hashed_proof)
+		Refunded(T::AccountId, HashedProof),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The sender already has a swap with the hashed proof
+		AlreadyExists,
+		/// Swap does not exist
+		SwapNotFound,
+		/// Amount must be non-zero
+		AmountZero,
+		/// Expiry must be in the future
+		InvalidExpiry,
+		/// Swap expired and can only be refunded
+		Expired,
+		/// Swap has not expired yet
+		NotExpired,
+	}
+
+	#[pallet::storage]
+	#[pallet::getter(fn swap)]
+	pub type Swaps<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Identity,
+		HashedProof,
+		PendingSwap<T::AccountId, T::BlockNumber>,
+	>;
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Locks `amount` of `asset` for `recipient` under `hashed_proof` until `expiry`.
+		///
+		/// `hashed_proof` is the SHA-256 hash of the proof, as used by HTLCs on other chains.
+		#[pallet::weight(T::DbWeight::get().reads_writes(2, 3))]
+		pub fn lock(
+			origin: OriginFor<T>,
+			recipient: T::AccountId,
+			asset: AssetId,
+			amount: Balance,
+			hashed_proof: HashedProof,
+			expiry: T::BlockNumber,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(amount > 0, Error::<T>::AmountZero);
+			ensure!(expiry > frame_system::Pallet::<T>::block_number(), Error::<T>::InvalidExpiry);
+			ensure!(!Swaps::<T>::contains_key(&sender, hashed_proof), Error::<T>::AlreadyExists);
+
+			T::Assets::transfer(asset, &sender, &Self::account_id(), amount, false)?;
+			Swaps::<T>::insert(
+				&sender,
+				hashed_proof,
+				PendingSwap {
+					sender: sender.clone(),
+					recipient: recipient.clone(),
+					asset,
+					amount,
+					expiry,
+				},
+			);
+
+			Self::deposit_event(Event::Locked(
+				hashed_proof,
+				sender,
+				recipient,
+				asset,
+				amount,
+				expiry,
+			));
+			Ok(())
+		}
+
+		/// Pays out the swap `sender` locked under the hash of `proof` to its recipient.
+		///
+		/// Can be called by anyone before the swap expires.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 3))]
+		pub fn claim(origin: OriginFor<T>, sender: T::AccountId, proof: Proof) -> DispatchResult {
+			ensure_signed(origin)?;
+			let hashed_proof = sp_io::hashing::sha2_256(&proof);
+			let swap = Self::swap(&sender, hashed_proof).ok_or(Error::<T>::SwapNotFound)?;
+			ensure!(swap.expiry > frame_system::Pallet::<T>::block_number(), Error::<T>::Expired);
+
+			T::Assets::transfer(
+				swap.asset,
+				&Self::account_id(),
+				&swap.recipient,
+				swap.amount,
+				false,
+			)?;
+			Swaps::<T>::remove(&sender, hashed_proof);
+
+			Self::deposit_event(Event::Claimed(sender, hashed_proof, proof));
+			Ok(())
+		}
+
+		/// Refunds an expired swap of the sender locked under `hashed_proof`.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 3))]
+		pub fn refund(origin: OriginFor<T>, hashed_proof: HashedProof) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let swap = Self::swap(&who, hashed_proof).ok_or(Error::<T>::SwapNotFound)?;
+			ensure!(
+				swap.expiry <= frame_system::Pallet::<T>::block_number(),
+				Error::<T>::NotExpired
+			);
+
+			T::Assets::transfer(swap.asset, &Self::account_id(), &who, swap.amount, false)?;
+			Swaps::<T>::remove(&who, hashed_proof);
+
+			Self::deposit_event(Event::Refunded(who, hashed_proof));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Account holding the locked amounts of all swaps
+	pub fn account_id() -> T::AccountId {
+		T::PalletId::get().into_account()
+	}
+}
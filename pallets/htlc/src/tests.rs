@@ -0,0 +1,106 @@
+use crate::{mock::*, Error, Event as HtlcEvent, PendingSwap};
+use frame_support::{assert_noop, assert_ok, traits::fungibles::Inspect};
+
+const PROOF: [u8; 32] = [7; 32];
+
+fn hashed_proof() -> [u8; 32] {
+	sp_io::hashing::sha2_256(&PROOF)
+}
+
+fn lock() {
+	// 1_000 for BOB until block 11
+	assert_ok!(Htlc::lock(Origin::signed(ALICE), BOB, STND, 1_000, hashed_proof(), 11));
+}
+
+#[test]
+fn lock_holds_amount() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Htlc::lock(Origin::signed(ALICE), BOB, STND, 0, hashed_proof(), 11),
+			Error::<Test>::AmountZero
+		);
+		assert_noop!(
+			Htlc::lock(Origin::signed(ALICE), BOB, STND, 1_000, hashed_proof(), 1),
+			Error::<Test>::InvalidExpiry
+		);
+
+		lock();
+		assert_eq!(Assets::balance(STND, &ALICE), 9_000);
+		assert_eq!(Assets::balance(STND, &Htlc::account_id()), 1_000);
+		assert_eq!(
+			Htlc::swap(ALICE, hashed_proof()),
+			Some(PendingSwap {
+				sender: ALICE,
+				recipient: BOB,
+				asset: STND,
+				amount: 1_000,
+				expiry: 11
+			})
+		);
+		assert_eq!(
+			last_event(),
+			Event::Htlc(HtlcEvent::Locked(hashed_proof(), ALICE, BOB, STND, 1_000, 11))
+		);
+		assert_noop!(
+			Htlc::lock(Origin::signed(ALICE), BOB, STND, 1_000, hashed_proof(), 11),
+			Error::<Test>::AlreadyExists
+		);
+	})
+}
+
+#[test]
+fn proof_claims_swap_for_recipient() {
+	new_test_ext().execute_with(|| {
+		lock();
+		assert_noop!(
+			Htlc::claim(Origin::signed(CHARLIE), ALICE, [8; 32]),
+			Error::<Test>::SwapNotFound
+		);
+		assert_noop!(Htlc::claim(Origin::signed(CHARLIE), BOB, PROOF), Error::<Test>::SwapNotFound);
+
+		System::set_block_number(10);
+		assert_ok!(Htlc::claim(Origin::signed(CHARLIE), ALICE, PROOF));
+		assert_eq!(Assets::balance(STND, &BOB), 1_000);
+		assert_eq!(Htlc::swap(ALICE, hashed_proof()), None);
+		assert_eq!(last_event(), Event::Htlc(HtlcEvent::Claimed(ALICE, hashed_proof(), PROOF)));
+	})
+}
+
+#[test]
+fn expired_swap_is_refunded_to_sender() {
+	new_test_ext().execute_with(|| {
+		lock();
+		assert_noop!(
+			Htlc::refund(Origin::signed(ALICE), hashed_proof()),
+			Error::<Test>::NotExpired
+		);
+
+		System::set_block_number(11);
+		assert_noop!(Htlc::claim(Origin::signed(BOB), ALICE, PROOF), Error::<Test>::Expired);
+		assert_noop!(
+			Htlc::refund(Origin::signed(BOB), hashed_proof()),
+			Error::<Test>::SwapNotFound
+		);
+		assert_ok!(Htlc::refund(Origin::signed(ALICE), hashed_proof()));
+		assert_eq!(Assets::balance(STND, &ALICE), 10_000);
+		assert_eq!(Htlc::swap(ALICE, hashed_proof()), None);
+		assert_eq!(last_event(), Event::Htlc(HtlcEvent::Refunded(ALICE, hashed_proof())));
+	})
+}
+
+#[test]
+fn swaps_of_other_senders_do_not_block_a_lock() {
+	new_test_ext().execute_with(|| {
+		// CHARLIE front-runs the lock of ALICE with the same hashed proof
+		assert_ok!(Htlc::lock(Origin::signed(CHARLIE), CHARLIE, STND, 1, hashed_proof(), 2));
+		lock();
+		assert_eq!(Htlc::swap(CHARLIE, hashed_proof()).map(|swap| swap.amount), Some(1));
+		assert_eq!(Htlc::swap(ALICE, hashed_proof()).map(|swap| swap.amount), Some(1_000));
+
+		// Revealing the proof for one swap leaves the other one in place
+		assert_ok!(Htlc::claim(Origin::signed(BOB), CHARLIE, PROOF));
+		assert_ok!(Htlc::claim(Origin::signed(BOB), ALICE, PROOF));
+		assert_eq!(Assets::balance(STND, &BOB), 1_000);
+		assert_eq!(Assets::balance(STND, &CHARLIE), 10_000);
+	})
+}
@@ -0,0 +1,201 @@
+// Ensure we're `no_std` when compiling for Wasm.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Maintenance Pallet
+//!
+//! Call filter to be used as the runtime's `BaseCallFilter`.
+//!
+//! In normal operation every call accepted by `NormalCallFilter` is allowed, except calls into
+//! pallets that governance has disabled with `disable_pallet`. Only pallets accepted by
+//! `PausablePallets` can be disabled.
+//!
+//! In maintenance mode only calls accepted by `MaintenanceCallFilter` are allowed. This is meant
+//! to be a short whitelist of inherents and governance calls so that the chain keeps producing
+//! blocks and can be brought back with `resume_normal_operation`.
+//!
+//! Calls accepted by `SunsetCallFilter` are rejected in both modes from the `SunsetBlock` on,
+//! e.g. to retire `pallet_sudo` at a block committed to in the runtime. `CallsSunset` is emitted
+//! at that block.
+
+use frame_support::traits::{CallMetadata, Contains, GetCallMetadata};
+use sp_std::vec::Vec;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+pub use pallet::*;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::without_storage_info]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// Calls allowed in normal operation
+		type NormalCallFilter: Contains<<Self as frame_system::Config>::Call>;
+		/// Calls allowed while the chain is in maintenance mode
+		type MaintenanceCallFilter: Contains<<Self as frame_system::Config>::Call>;
+		/// Names of the pallets which can be disabled individually
+		type PausablePallets: Contains<Vec<u8>>;
+		/// Origin allowed to switch maintenance mode and disable pallets
+		type MaintenanceOrigin: EnsureOrigin<Self::Origin>;
+		/// Calls rejected from `SunsetBlock` on
+		type SunsetCallFilter: Contains<<Self as frame_system::Config>::Call>;
+		/// Block from which calls accepted by `SunsetCallFilter` are rejected, if any
+		#[pallet::constant]
+		type SunsetBlock: Get<Option<Self::BlockNumber>>;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// The chain entered maintenance mode
+		EnteredMaintenanceMode,
+		/// The chain returned to normal operation
+		NormalOperationResumed,
+		/// Calls into the pallet are rejected (pallet_name)
+		PalletDisabled(Vec<u8>),
+		/// Calls into the pallet are allowed again (pallet_name)
+		PalletEnabled(Vec<u8>),
+		/// Calls accepted by `SunsetCallFilter` are rejected from now on
+		CallsSunset,
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The chain is already in maintenance mode
+		AlreadyInMaintenanceMode,
+		/// The chain is not in maintenance mode
+		NotInMaintenanceMode,
+		/// The pallet can not be disabled
+		PalletNotPausable,
+		/// The pallet is already disabled
+		PalletAlreadyDisabled,
+		/// The pallet is not disabled
+		PalletNotDisabled,
+	}
+
+	#[pallet::storage]
+	#[pallet::getter(fn maintenance_mode)]
+	/// Whether the chain is in maintenance mode
+	pub type MaintenanceMode<T> = StorageValue<_, bool, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn disabled_pallets)]
+	/// Pallets whose calls are rejected in normal operation
+	pub type DisabledPallets<T> = StorageMap<_, Blake2_128Concat, Vec<u8>, bool, ValueQuery>;
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			if T::SunsetBlock::get() == Some(now) {
+				Self::deposit_event(Event::CallsSunset);
+			}
+			0
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Switches the chain into maintenance mode.
+		///
+		/// # <weight>
+		/// - O(1) write
+		/// # </weight>
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn enter_maintenance_mode(origin: OriginFor<T>) -> DispatchResult {
+			T::MaintenanceOrigin::ensure_origin(origin)?;
+			ensure!(!Self::maintenance_mode(), Error::<T>::AlreadyInMaintenanceMode);
+
+			MaintenanceMode::<T>::put(true);
+			Self::deposit_event(Event::EnteredMaintenanceMode);
+			Ok(())
+		}
+
+		/// Returns the chain to normal operation.
+		///
+		/// # <weight>
+		/// - O(1) write
+		/// # </weight>
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn resume_normal_operation(origin: OriginFor<T>) -> DispatchResult {
+			T::MaintenanceOrigin::ensure_origin(origin)?;
+			ensure!(Self::maintenance_mode(), Error::<T>::NotInMaintenanceMode);
+
+			MaintenanceMode::<T>::put(false);
+			Self::deposit_event(Event::NormalOperationResumed);
+			Ok(())
+		}
+
+		/// Rejects all calls into the pallet named `pallet_name` until it is enabled again.
+		///
+		/// # <weight>
+		/// - O(1) lookup and insert
+		/// # </weight>
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn disable_pallet(origin: OriginFor<T>, pallet_name: Vec<u8>) -> DispatchResult {
+			T::MaintenanceOrigin::ensure_origin(origin)?;
+			ensure!(T::PausablePallets::contains(&pallet_name), Error::<T>::PalletNotPausable);
+			ensure!(!Self::disabled_pallets(&pallet_name), Error::<T>::PalletAlreadyDisabled);
+
+			DisabledPallets::<T>::insert(&pallet_name, true);
+			Self::deposit_event(Event::PalletDisabled(pallet_name));
+			Ok(())
+		}
+
+		/// Allows calls into a pallet previously disabled with `disable_pallet`.
+		///
+		/// # <weight>
+		/// - O(1) lookup and removal
+		/// # </weight>
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn enable_pallet(origin: OriginFor<T>, pallet_name: Vec<u8>) -> DispatchResult {
+			T::MaintenanceOrigin::ensure_origin(origin)?;
+			ensure!(Self::disabled_pallets(&pallet_name), Error::<T>::PalletNotDisabled);
+
+			DisabledPallets::<T>::remove(&pallet_name);
+			Self::deposit_event(Event::PalletEnabled(pallet_name));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Whether calls accepted by `SunsetCallFilter` are rejected.
+	pub fn is_sunset() -> bool {
+		T::SunsetBlock::get().map_or(false, |at| frame_system::Pallet::<T>::block_number() >= at)
+	}
+}
+
+impl<T: Config> Contains<<T as frame_system::Config>::Call> for Pallet<T>
+where
+	<T as frame_system::Config>::Call: GetCallMetadata,
+{
+	fn contains(call: &<T as frame_system::Config>::Call) -> bool {
+		if Self::is_sunset() && T::SunsetCallFilter::contains(call) {
+			return false
+		}
+
+		if Self::maintenance_mode() {
+			return T::MaintenanceCallFilter::contains(call)
+		}
+
+		let CallMetadata { pallet_name, .. } = call.get_call_metadata();
+		if Self::disabled_pallets(pallet_name.as_bytes().to_vec()) {
+			return false
+		}
+
+		T::NormalCallFilter::contains(call)
+	}
+}
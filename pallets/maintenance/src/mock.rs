@@ -0,0 +1,104 @@
+#![cfg(test)]
+
+use frame_support::{
+	parameter_types,
+	traits::{Contains, Everything},
+};
+use frame_system as system;
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+use sp_std::vec::Vec;
+
+use crate::{self as maintenance, Config};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+	pub enum Test where
+	 Block = Block,
+	 NodeBlock = Block,
+	 UncheckedExtrinsic = UncheckedExtrinsic,
+	 {
+		 System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		 Maintenance: maintenance::{Pallet, Call, Storage, Event<T>},
+	 }
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 63;
+	pub const SunsetBlock: Option<u64> = Some(10);
+}
+
+impl system::Config for Test {
+	type OnSetCode = ();
+	type BaseCallFilter = Maintenance;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type DbWeight = ();
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+pub struct MaintenanceCalls;
+impl Contains<Call> for MaintenanceCalls {
+	fn contains(call: &Call) -> bool {
+		matches!(call, Call::Maintenance(_))
+	}
+}
+
+pub struct SunsetCalls;
+impl Contains<Call> for SunsetCalls {
+	fn contains(call: &Call) -> bool {
+		matches!(call, Call::System(frame_system::Call::remark { .. }))
+	}
+}
+
+pub struct PausablePallets;
+impl Contains<Vec<u8>> for PausablePallets {
+	fn contains(name: &Vec<u8>) -> bool {
+		name.as_slice() == b"System"
+	}
+}
+
+impl Config for Test {
+	type Event = Event;
+	type NormalCallFilter = Everything;
+	type MaintenanceCallFilter = MaintenanceCalls;
+	type PausablePallets = PausablePallets;
+	type MaintenanceOrigin = frame_system::EnsureRoot<Self::AccountId>;
+	type SunsetCallFilter = SunsetCalls;
+	type SunsetBlock = SunsetBlock;
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+pub fn last_event() -> Event {
+	system::Pallet::<Test>::events().pop().expect("Event expected").event
+}
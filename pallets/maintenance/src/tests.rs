@@ -0,0 +1,111 @@
+use crate::{mock::*, Error, Event as MaintenanceEvent};
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{Contains, Hooks},
+};
+
+fn remark() -> Call {
+	Call::System(frame_system::Call::remark { remark: vec![] })
+}
+
+fn resume() -> Call {
+	Call::Maintenance(crate::Call::resume_normal_operation {})
+}
+
+#[test]
+fn maintenance_mode_filters_calls() {
+	new_test_ext().execute_with(|| {
+		assert!(Maintenance::contains(&remark()));
+
+		assert_ok!(Maintenance::enter_maintenance_mode(Origin::root()));
+		assert_eq!(last_event(), Event::Maintenance(MaintenanceEvent::EnteredMaintenanceMode));
+		assert!(!Maintenance::contains(&remark()));
+		assert!(Maintenance::contains(&resume()));
+		assert_noop!(
+			Maintenance::enter_maintenance_mode(Origin::root()),
+			Error::<Test>::AlreadyInMaintenanceMode
+		);
+
+		assert_ok!(Maintenance::resume_normal_operation(Origin::root()));
+		assert_eq!(last_event(), Event::Maintenance(MaintenanceEvent::NormalOperationResumed));
+		assert!(Maintenance::contains(&remark()));
+		assert_noop!(
+			Maintenance::resume_normal_operation(Origin::root()),
+			Error::<Test>::NotInMaintenanceMode
+		);
+	})
+}
+
+#[test]
+fn disable_pallet_filters_its_calls() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Maintenance::disable_pallet(Origin::root(), b"System".to_vec()));
+		assert_eq!(
+			last_event(),
+			Event::Maintenance(MaintenanceEvent::PalletDisabled(b"System".to_vec()))
+		);
+		assert!(!Maintenance::contains(&remark()));
+		assert!(Maintenance::contains(&resume()));
+		assert_noop!(
+			Maintenance::disable_pallet(Origin::root(), b"System".to_vec()),
+			Error::<Test>::PalletAlreadyDisabled
+		);
+
+		assert_ok!(Maintenance::enable_pallet(Origin::root(), b"System".to_vec()));
+		assert_eq!(
+			last_event(),
+			Event::Maintenance(MaintenanceEvent::PalletEnabled(b"System".to_vec()))
+		);
+		assert!(Maintenance::contains(&remark()));
+		assert_noop!(
+			Maintenance::enable_pallet(Origin::root(), b"System".to_vec()),
+			Error::<Test>::PalletNotDisabled
+		);
+	})
+}
+
+#[test]
+fn only_pausable_pallets_can_be_disabled() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Maintenance::disable_pallet(Origin::root(), b"Maintenance".to_vec()),
+			Error::<Test>::PalletNotPausable
+		);
+	})
+}
+
+#[test]
+fn requires_maintenance_origin() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Maintenance::enter_maintenance_mode(Origin::signed(1)),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Maintenance::disable_pallet(Origin::signed(1), b"System".to_vec()),
+			sp_runtime::DispatchError::BadOrigin
+		);
+	})
+}
+
+#[test]
+fn sunset_calls_are_rejected_from_sunset_block() {
+	new_test_ext().execute_with(|| {
+		let set_heap_pages = Call::System(frame_system::Call::set_heap_pages { pages: 1 });
+		System::set_block_number(9);
+		assert!(Maintenance::contains(&remark()));
+
+		System::set_block_number(10);
+		Maintenance::on_initialize(10);
+		assert_eq!(last_event(), Event::Maintenance(MaintenanceEvent::CallsSunset));
+		assert!(!Maintenance::contains(&remark()));
+		assert!(Maintenance::contains(&set_heap_pages));
+
+		// Emitted once, at the sunset block only
+		System::reset_events();
+		System::set_block_number(11);
+		Maintenance::on_initialize(11);
+		assert!(System::events().is_empty());
+		assert!(!Maintenance::contains(&remark()));
+	})
+}
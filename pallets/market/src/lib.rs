@@ -62,6 +62,36 @@
 //!   The assets must have different identifier.
 //! * `burn_liquidity` - Burns liquidity token for a pair and receives each asset in the pair.
 //! * `swap` - Swaps from one asset to the another, paying 0.3% fee to the liquidity providers.
+//! * `lock_for_fee_discount` - Locks native currency until a block for a swap fee discount of the
+//!   highest `FeeDiscountTiers` tier the locked amount reaches. The discount ends at that block.
+//! * `unlock_fee_discount` - Releases an expired fee discount lock.
+//!
+//! Swap volume is tracked per account and pair for every volume era of `VolumeEraLength` blocks,
+//! in units of the first token of the pair, and kept for `VolumeHistoryDepth` eras so that
+//! incentive programs can be settled on chain.
+//!
+//! Swap fees paid into each pool are accumulated in hourly buckets, derived from `BlocksPerDay`,
+//! and kept for 7 days so that clients can show the fee yield of the pools.
+//!
+//! The accumulated prices of every pool are observed at the start of each `TwapPeriod`, even
+//! without swaps, so that other pallets can read time weighted average prices over a window of
+//! up to `MaxTwapObservations - 1` periods through the `Twap` trait.
+//!
+//! Anyone can propose a swap fee or minimum TWAP window for a pool by reserving
+//! `ParameterProposalBond`. The change applies after `ParameterChallengePeriod` blocks unless
+//! liquidity providers holding `ParameterChallengeQuorum` of the liquidity token supply challenge
+//! it by escrowing their liquidity tokens, which rejects the proposal and slashes its bond.
+//! Governance sets swap fees with `schedule_swap_fee`, enacted after `MinimumEnactmentDelay`.
+//!
+//! Swap orders placed in the batch of a pair reserve the amount sold in the account of their
+//! owner until the batch is cleared, instead of moving it into the market account. Queued
+//! withdrawals and parameter challenges reserve their liquidity tokens the same way. Reserved
+//! assets are frozen by the assets pallet, with the market as its `Freezer`, and a refund only
+//! releases the reserve. Other pallets reserve assets through `ReservableAssets`.
+//!
+//! With the `price-telemetry` feature, the spot prices of the pools whose reserves changed in a
+//! block are emitted at its end as they were at its start and end, so that sandwiching and
+//! other value extracted by ordering swaps can be quantified on chain.
 //!
 //! Please refer to the [`Call`](./enum.Call.html) enum and its associated variants for
 //! documentation on each function.
@@ -98,37 +128,185 @@
 // Ensure we're `no_std` when compiling for Wasm.
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use codec::{Decode, Encode};
 use frame_support::{
 	decl_error, decl_event, decl_module, decl_storage, dispatch, ensure,
+	storage::migration::{remove_storage_prefix, storage_key_iter},
 	traits::{
 		fungibles::{Inspect, Mutate, Transfer},
-		tokens::fungibles,
-		Get,
+		schedule::{DispatchTime, Named as ScheduleNamed, HARD_DEADLINE},
+		tokens::{fungibles, DepositConsequence, WithdrawConsequence},
+		Get, LockIdentifier, LockableCurrency, ReservableCurrency, WithdrawReasons,
 	},
-	PalletId,
+	transactional,
+	weights::Weight,
+	Blake2_128Concat, PalletId,
 };
-use frame_system::ensure_signed;
+use frame_system::{ensure_root, ensure_signed};
 use pallet_asset_registry;
-use primitives::{AssetId, Balance};
-use sp_core::U256;
+use pallet_assets::FrozenBalance;
+use primitives::{
+	overview::{LiquidityPosition, LiquidityReturn, LpTokenInfo, PoolStats},
+	proof::Proven,
+	snapshot::PoolSnapshot,
+	traits::{AccountedFunds, ReservableAssets, Twap},
+	AssetId, Balance, LpTokenId, VolumeEra,
+};
+use scale_info::TypeInfo;
+use sp_io::KillStorageResult;
 use sp_runtime::{
-	traits::{AccountIdConversion, UniqueSaturatedFrom, UniqueSaturatedInto, Zero},
-	FixedU128,
+	traits::{AccountIdConversion, Hash as HashT, One, Saturating, UniqueSaturatedInto, Zero},
+	FixedPointNumber, FixedU128, Permill, RuntimeDebug,
 };
+use sp_std::{collections::btree_map::BTreeMap, vec, vec::Vec};
 // use crate::sp_api_hidden_includes_decl_storage::hidden_include::traits::Get;
-mod math;
+pub mod math;
+
+/// Target of the logs of the pallet, enabled on a node with `--log standard::market=debug`
+const LOG_TARGET: &str = "standard::market";
+
+// Logs on `LOG_TARGET` in native execution only, so that the runtime blob doesn't carry the
+// formatting code
+macro_rules! log {
+	($level:tt, $pattern:expr $(, $values:expr)* $(,)?) => {
+		#[cfg(feature = "std")]
+		log::$level!(target: crate::LOG_TARGET, $pattern $(, $values)*)
+	};
+}
+
+/// Scheduler task name prefix of swap fee changes
+const MARKET_SCHEDULE_ID: &[u8] = b"stnd/mkt";
+
+/// Lock of the native currency held for swap fee discounts
+const FEE_DISCOUNT_LOCK: LockIdentifier = *b"mkt/disc";
+
+/// Identifier of a queued withdrawal
+pub type WithdrawalId = u32;
+
+/// Identifier of a pool parameter proposal
+pub type ParameterProposalId = u32;
+
+/// Index of a bucket swap fees are accumulated in
+pub type FeeBucket = u32;
+
+/// Number of fee buckets in a day
+pub const FEE_BUCKETS_PER_DAY: FeeBucket = 24;
+/// Number of days the fees of the pools are kept for
+pub const FEE_HISTORY_DAYS: FeeBucket = 7;
+
+/// Liquidity burn held back by the withdrawal guard of its pool
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct QueuedWithdrawal<AccountId, BlockNumber> {
+	/// Account the liquidity is withdrawn to
+	pub owner: AccountId,
+	pub lp_token: LpTokenId,
+	/// Liquidity tokens to burn, reserved in the account of the owner until then
+	pub amount: Balance,
+	/// Block from which the burn can be executed
+	pub executable_at: BlockNumber,
+}
+
+/// Swap order committed to by its hash, to be revealed and executed in a later block
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct SwapCommit<AccountId, BlockNumber> {
+	pub owner: AccountId,
+	/// Block the commitment was made in
+	pub committed_at: BlockNumber,
+	/// Native currency reserved until the order is revealed, forfeited if it expires
+	pub bond: Balance,
+}
+
+/// Parameter of a pool changed by a parameter proposal
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub enum PoolParameter<BlockNumber> {
+	/// Swap fee in basis points, at most `math::MAX_SWAP_FEE`
+	SwapFee(u32),
+	/// Minimum window of the TWAPs of the pool, within the observations kept
+	TwapWindow(BlockNumber),
+}
+
+/// Pool parameter change applying unless challenged by the liquidity providers of the pool
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct ParameterProposal<AccountId, BlockNumber> {
+	pub proposer: AccountId,
+	pub lp_token: LpTokenId,
+	pub parameter: PoolParameter<BlockNumber>,
+	/// Native currency reserved from the proposer, slashed if the proposal is rejected
+	pub bond: Balance,
+	/// Block the change applies at
+	pub applies_at: BlockNumber,
+	/// Liquidity tokens reserved by the challengers
+	pub challenged: Balance,
+}
+
+/// Swap order waiting for the batch of its pair to be cleared at the end of the block
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct BatchOrder<AccountId> {
+	pub owner: AccountId,
+	/// Asset sold for the other token of the pair
+	pub from: AssetId,
+	/// Amount sold, reserved in the account of the owner until the batch is cleared
+	pub amount_in: Balance,
+	/// Minimum amount bought, below which the order is refunded
+	pub min_amount_out: Balance,
+}
 
 /// The module configuration trait.
 pub trait Config: frame_system::Config + pallet_asset_registry::Config {
 	/// The overarching event type.
-	type Event: From<Event> + Into<<Self as frame_system::Config>::Event>;
+	type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
 	type SystemPalletId: Get<PalletId>;
+	/// Number of blocks between the price observations of a pair, the minimum averaged by its
+	/// TWAP
+	type TwapPeriod: Get<Self::BlockNumber>;
+	/// Number of price observations kept per pair, bounding the TWAP window to
+	/// `TwapPeriod * (MaxTwapObservations - 1)` blocks
+	type MaxTwapObservations: Get<u32>;
+	/// Native currency locked for swap fee discounts and reserved for swap commitments
+	type Currency: LockableCurrency<Self::AccountId, Moment = Self::BlockNumber, Balance = Balance>
+		+ ReservableCurrency<Self::AccountId, Balance = Balance>;
+	/// Swap fee discounts as (minimum locked amount, discount in basis points)
+	type FeeDiscountTiers: Get<Vec<(Balance, u32)>>;
+	/// Minimum number of blocks the native currency is locked for a fee discount
+	type MinFeeLockPeriod: Get<Self::BlockNumber>;
+	/// Number of blocks in a volume era
+	type VolumeEraLength: Get<Self::BlockNumber>;
+	/// Number of past volume eras whose volume is kept
+	type VolumeHistoryDepth: Get<VolumeEra>;
+	/// Number of blocks a liquidity burn above the withdrawal guard of its pool is queued for
+	type GuardedWithdrawalDelay: Get<Self::BlockNumber>;
+	/// Number of blocks in a day, split into `FEE_BUCKETS_PER_DAY` fee buckets
+	type BlocksPerDay: Get<Self::BlockNumber>;
+	/// Native currency reserved for a swap commitment until it is revealed
+	type SwapCommitBond: Get<Balance>;
+	/// Number of blocks after its commitment block a swap can be revealed in
+	type SwapRevealWindow: Get<Self::BlockNumber>;
+	/// Maximum number of swap orders in the batch of a pair per block
+	type MaxBatchOrders: Get<u32>;
+	/// Native currency reserved to propose a pool parameter change
+	type ParameterProposalBond: Get<Balance>;
+	/// Number of blocks a pool parameter proposal can be challenged in before it applies
+	type ParameterChallengePeriod: Get<Self::BlockNumber>;
+	/// Share of the liquidity token supply of a pool whose challenge rejects a proposal
+	type ParameterChallengeQuorum: Get<Permill>;
+	/// Maximum number of open pool parameter proposals
+	type MaxParameterProposals: Get<u32>;
+	/// The runtime call that scheduled swap fee changes are dispatched as
+	type Proposal: From<Call<Self>>;
+	/// The caller origin, overarching type of all pallets origins
+	type PalletsOrigin: From<frame_system::RawOrigin<Self::AccountId>>;
+	/// The scheduler used to enact swap fee changes after a public delay
+	type Scheduler: ScheduleNamed<Self::BlockNumber, Self::Proposal, Self::PalletsOrigin>;
+	/// Minimum number of blocks between scheduling a swap fee change and its enactment
+	type MinimumEnactmentDelay: Get<Self::BlockNumber>;
 	//   type AssetId: Parameter + Member + Into<u32> + AtLeast32Bit + Default + Copy +
 	// MaybeSerializeDeserialize;
 
 	type Assets: fungibles::Inspect<Self::AccountId, AssetId = AssetId, Balance = Balance>
 		+ fungibles::Mutate<Self::AccountId, AssetId = AssetId, Balance = Balance>
 		+ fungibles::Transfer<Self::AccountId, AssetId = AssetId, Balance = Balance>;
+	/// Funds other pallets keep in the market account, like the collateral of the vaults
+	type SharedFunds: AccountedFunds<Self::AccountId>;
 }
 
 decl_module! {
@@ -137,6 +315,39 @@ decl_module! {
 
 		fn deposit_event() = default;
 
+		fn on_runtime_upgrade() -> Weight {
+			Self::migrate_to_price_observations()
+		}
+
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let mut weight = 0;
+			if (now % T::TwapPeriod::get()).is_zero() {
+				weight += Self::observe_prices();
+			}
+			if (now % T::VolumeEraLength::get()).is_zero() {
+				weight += Self::prune_volume(Self::volume_era(now));
+			}
+			if (now % Self::fee_bucket_length()).is_zero() {
+				weight += Self::prune_fees(Self::fee_bucket(now));
+			}
+			weight += Self::apply_parameter_proposals(now);
+			weight
+		}
+
+		fn on_finalize(_now: T::BlockNumber) {
+			let batches: Vec<_> = BatchOrders::<T>::drain().collect();
+			for (lpt, orders) in batches {
+				Self::clear_batch(lpt, orders);
+			}
+			#[cfg(feature = "price-telemetry")]
+			Self::emit_block_prices();
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			Self::assert_reserves_match_balances()
+		}
+
 		// Mint liquidity by adding a liquidity in a pair
 		#[weight = 10_000 + T::DbWeight::get().reads_writes(1,1)]
 		pub fn mint_liquidity(origin, token0: AssetId, amount0: Balance, token1: AssetId, amount1: Balance) -> dispatch::DispatchResult {
@@ -148,6 +359,11 @@ decl_module! {
 			T::Assets::transfer(token0, &sender,  &Self::account_id(), amount0, true)?;
 			T::Assets::transfer(token1, &sender,  &Self::account_id(), amount1, true)?;
 			let zero_bal: Balance = 0;
+			// Deposits ordered as the pair, for the cost basis of the sender
+			let deposited = match token0 > token1 {
+				true => (amount1, amount0),
+				false => (amount0, amount1),
+			};
 
 			match Pairs::get((token0.clone(), token1.clone())) {
 				// create pair if lpt does not exist
@@ -155,20 +371,21 @@ decl_module! {
 					let mut lptoken_amount: Balance = math::sqrt(amount0 * amount1);
 					lptoken_amount = lptoken_amount.checked_sub(minimum_liquidity).expect("Integer overflow");
 					// Issue LPtoken
-					let lptoken_id: AssetId = <pallet_asset_registry::Pallet<T>>::get_or_create_asset((*b"lptoken").to_vec())?.into();
+					let lptoken_id = LpTokenId(<pallet_asset_registry::Pallet<T>>::get_or_create_asset(Self::lp_token_symbol(token0, token1))?.into());
 					// Deposit assets to the reserve
 					Self::_set_reserves(token0, token1, amount0, amount1, lptoken_id);
 					// Set pairs for swap lookup
 					Self::_set_pair(token0, token1, lptoken_id);
 					Self::_set_rewards(token0, token1, lptoken_id);
 					// Mint LPtoken to the sender
-					T::Assets::mint_into(lptoken_id, &sender, lptoken_amount)?;
-					Self::deposit_event(Event::CreatePair(token0, token1, lptoken_id));
+					T::Assets::mint_into(lptoken_id.into(), &sender, lptoken_amount)?;
+					Self::add_cost_basis(&sender, lptoken_id, lptoken_amount, deposited);
+					Self::deposit_event(RawEvent::CreatePair(token0, token1, lptoken_id));
 					Ok(())
 				},
 				// when lpt exists and total supply is bigger than 0
-				Some(lpt) if T::Assets::total_issuance(lpt) > Zero::zero() => {
-					let total_supply = T::Assets::total_issuance(lpt);
+				Some(lpt) if T::Assets::total_issuance(lpt.into()) > Zero::zero() => {
+					let total_supply = T::Assets::total_issuance(lpt.into());
 					let mut reserves = Self::reserves(lpt);
 					let thousand: Balance = 1000;
 					if token0 > token1 {
@@ -176,103 +393,463 @@ decl_module! {
 					} else {
 						ensure!(math::absdiff(reserves.0/reserves.1 * amount1, amount0) < amount0.checked_div(thousand).expect("Divide by zero error"), Error::<T>::K);
 					}
-					let left = amount0.checked_mul(total_supply).expect("Multiplicaiton overflow").checked_div(reserves.0).expect("Divide by zero error");
-					let right = amount1.checked_mul(total_supply).expect("Multiplicaiton overflow").checked_div(reserves.1).expect("Divide by zero error");
-					let lptoken_amount = math::min(left, right);
+					let lptoken_amount = math::liquidity_minted((amount0, amount1), reserves, total_supply);
 					// Deposit assets to the reserve
 					reserves.0 += amount0;
 					reserves.1 += amount1;
 					Self::_set_reserves(token0, token1, reserves.0, reserves.1, lpt);
 					// Mint LPtoken to the sender
-					T::Assets::mint_into(lpt, &sender, lptoken_amount)?;
-					Self::deposit_event(Event::MintedLiquidity(token0, token1, lpt));
-					//Self::_update(&lpt)?;
+					T::Assets::mint_into(lpt.into(), &sender, lptoken_amount)?;
+					Self::add_cost_basis(&sender, lpt, lptoken_amount, deposited);
+					Self::deposit_event(RawEvent::MintedLiquidity(token0, token1, lpt));
 					Ok(())
 				},
 				// <= ?? or just <
-				Some(lpt) if T::Assets::total_issuance(lpt) < zero_bal => {
+				Some(lpt) if T::Assets::total_issuance(lpt.into()) < zero_bal => {
 					Err(Error::<T>::InsufficientLiquidityMinted)?
 				},
 				Some(_) => Err(Error::<T>::NoneValue)?,
 			}
 		}
 
-		#[weight = 10_000 + T::DbWeight::get().reads_writes(1,1)]
-		pub fn burn_liquidity(origin, lpt: AssetId, amount: Balance) -> dispatch::DispatchResult{
+		/// Burn `amount` of the liquidity token `lpt` for its share of the pool reserves.
+		///
+		/// If the pool has a withdrawal guard and `amount` is above its share of the liquidity
+		/// token supply, the tokens are reserved and the burn is queued for
+		/// `GuardedWithdrawalDelay` blocks instead.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(3, 3)]
+		pub fn burn_liquidity(origin, lpt: LpTokenId, amount: Balance) -> dispatch::DispatchResult{
 			let sender = ensure_signed(origin)?;
-			let mut reserves = Self::reserves(lpt);
-			let tokens = Self::reward(lpt);
-			let total_supply = T::Assets::total_issuance(lpt);
-
-			// Calculate rewards for providing liquidity with pro-rata distribution
-			let reward0 = amount.checked_mul(reserves.0).expect("Multiplicaiton overflow").checked_div(total_supply).expect("Divide by zero error");
-			let reward1 = amount.checked_mul(reserves.1).expect("Multiplicaiton overflow").checked_div(total_supply).expect("Divide by zero error");
-
-			// Ensure rewards exist
-			ensure!(reward0 > Zero::zero() && reward1 > Zero::zero(), Error::<T>::InsufficientLiquidityBurned);
-
-			// Distribute reward to the sender
-			T::Assets::burn_from(lpt, &sender, amount)?;
-			T::Assets::transfer(tokens.0,  &Self::account_id(), &sender, reward0, true)?;
-			T::Assets::transfer(tokens.1,  &Self::account_id(), &sender, reward1, true)?;
-
-			// Update reserve when the balance is set
-			reserves.0 -= reward0;
-			reserves.1 -= reward1;
-			Self::_set_reserves(tokens.0, tokens.1, reserves.0, reserves.1, lpt);
-			// Deposit event that the liquidity is burned successfully
-			Self::deposit_event(Event::BurnedLiquidity(lpt, tokens.0, tokens.1));
-			// Update price
-			//Self::_update(&lpt)?;
-			Ok(())
+			match Self::withdrawal_guard(lpt) {
+				Some(threshold) if amount > threshold * T::Assets::total_issuance(lpt.into()) =>
+					Self::queue_withdrawal(sender, lpt, amount),
+				_ => Self::do_burn_liquidity(&sender, &sender, lpt, amount),
+			}
 		}
 
-		#[weight = 10_000 + T::DbWeight::get().reads_writes(1,1)]
+		/// Swap `amount_in` of `from` for `to`.
+		///
+		/// In a pair in batch mode the swap is placed in the batch of the block instead, like
+		/// `batch_swap` without a minimum.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(6, 6)]
 		pub fn swap(origin, from: AssetId, amount_in: Balance, to: AssetId) -> dispatch::DispatchResult {
 			let sender = ensure_signed(origin)?;
-			ensure!(amount_in > Zero::zero(), Error::<T>::InsufficientAmount);
-			// Find pair
-			let lpt = Self::pair((from, to));
-			ensure!(lpt.is_some(), Error::<T>::InvalidPair);
-			let reserves = Self::reserves(lpt.unwrap());
-			ensure!(reserves.0 > Zero::zero() && reserves.1 > Zero::zero(), Error::<T>::InsufficientLiquidity);
-			let (mut reserve_in, mut reserve_out) = match from > to {
-				true => (reserves.1, reserves.0),
-				false => (reserves.0, reserves.1)
-			};
-			// get amount out
-			let amount_out = Self::_get_amount_out(amount_in, reserve_in, reserve_out);
-			// transfer amount in to system
-			T::Assets::transfer(from, &sender,  &Self::account_id(), amount_in, true)?;
-			// transfer swapped amount
-			T::Assets::transfer(to,  &Self::account_id(), &sender, amount_out, true)?;
-			// update reserves
-			reserve_in += amount_in;
-			reserve_out -= amount_out;
-			Self::_set_reserves(from, to, reserve_in, reserve_out, lpt.unwrap());
-			// Deposit event that the liquidity is burned successfully
-			Self::deposit_event(Event::Swap(from, amount_in, to, amount_out));
-			// Update price
-			//Self::_update(&lpt.unwrap())?;
+			match Self::pair((from, to)).map_or(false, Self::batch_mode) {
+				true => Self::place_batch_order(sender, from, amount_in, to, 0),
+				false => Self::do_swap(&sender, from, amount_in, to, true).map(|_| ()),
+			}
+		}
+
+		/// Locks `amount` of the native currency until `until` for a swap fee discount.
+		///
+		/// An existing lock can only be raised and extended.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(2, 2)]
+		pub fn lock_for_fee_discount(origin, amount: Balance, until: T::BlockNumber) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let now = <frame_system::Pallet<T>>::block_number();
+			ensure!(until >= now + T::MinFeeLockPeriod::get(), Error::<T>::LockTooShort);
+			if let Some((locked, locked_until)) = Self::fee_lock(&sender) {
+				ensure!(amount >= locked && until >= locked_until, Error::<T>::LockReduced);
+			}
+			ensure!(T::Currency::free_balance(&sender) >= amount, Error::<T>::InSufficientBalance);
+
+			T::Currency::set_lock(FEE_DISCOUNT_LOCK, &sender, amount, WithdrawReasons::all());
+			FeeLocks::<T>::insert(&sender, (amount, until));
+			Self::deposit_event(RawEvent::FeeDiscountLocked(sender, amount, until));
+			Ok(())
+		}
+
+		/// Releases the fee discount lock of the sender once it expired.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(2, 2)]
+		pub fn unlock_fee_discount(origin) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let (_, until) = Self::fee_lock(&sender).ok_or(Error::<T>::NoFeeLock)?;
+			ensure!(<frame_system::Pallet<T>>::block_number() >= until, Error::<T>::FeeLockActive);
+
+			T::Currency::remove_lock(FEE_DISCOUNT_LOCK, &sender);
+			FeeLocks::<T>::remove(&sender);
+			Self::deposit_event(RawEvent::FeeDiscountUnlocked(sender));
+			Ok(())
+		}
+
+		/// Move `amount` of `asset` sent to the market account outside of the pools to
+		/// `beneficiary`.
+		///
+		/// Only the balance above the pool reserves and the funds other pallets keep in the
+		/// account can be moved.
+		///
+		/// # <weight>
+		/// - O(P) reads where P is the number of pools, `SharedFunds` keeps running totals
+		/// # </weight>
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(4, 2)]
+		#[transactional]
+		pub fn rescue_funds(origin, asset: AssetId, amount: Balance, beneficiary: T::AccountId) -> dispatch::DispatchResult {
+			ensure_root(origin)?;
+			let account = Self::account_id();
+			let accounted = Self::accounted(&account, asset)
+				.saturating_add(T::SharedFunds::accounted(&account, asset));
+			let excess = T::Assets::balance(asset, &account).saturating_sub(accounted);
+			ensure!(amount <= excess, Error::<T>::ExceedsExcessFunds);
+			T::Assets::transfer(asset, &account, &beneficiary, amount, false)?;
+			// The pools and the other pallets stay fully backed
+			ensure!(T::Assets::balance(asset, &account) >= accounted, Error::<T>::ExceedsExcessFunds);
+			Self::deposit_event(RawEvent::FundsRescued(asset, amount, beneficiary));
+			Ok(())
+		}
+
+		/// Execute a queued liquidity burn once its delay passed, paying the reserves out to its
+		/// owner.
+		///
+		/// Can be called by anyone.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(4, 4)]
+		#[transactional]
+		pub fn execute_withdrawal(origin, id: WithdrawalId) -> dispatch::DispatchResult {
+			ensure_signed(origin)?;
+			let withdrawal = Self::queued_withdrawal(id).ok_or(Error::<T>::WithdrawalNotFound)?;
+			ensure!(
+				<frame_system::Pallet<T>>::block_number() >= withdrawal.executable_at,
+				Error::<T>::WithdrawalDelayed
+			);
+
+			QueuedWithdrawals::<T>::remove(id);
+			Self::unreserve_asset(withdrawal.lp_token.into(), &withdrawal.owner, withdrawal.amount);
+			Self::do_burn_liquidity(&withdrawal.owner, &withdrawal.owner, withdrawal.lp_token, withdrawal.amount)?;
+			Self::deposit_event(RawEvent::WithdrawalExecuted(id));
+			Ok(())
+		}
+
+		/// Cancel a queued liquidity burn, releasing the liquidity tokens of its owner.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(2, 2)]
+		pub fn cancel_withdrawal(origin, id: WithdrawalId) -> dispatch::DispatchResult {
+			ensure_root(origin)?;
+			let withdrawal = Self::queued_withdrawal(id).ok_or(Error::<T>::WithdrawalNotFound)?;
+
+			Self::unreserve_asset(withdrawal.lp_token.into(), &withdrawal.owner, withdrawal.amount);
+			QueuedWithdrawals::<T>::remove(id);
+			Self::deposit_event(RawEvent::WithdrawalCancelled(id));
+			Ok(())
+		}
+
+		/// Queue liquidity burns in the pool of `lpt` above `threshold` of the liquidity token
+		/// supply. `None` lets all burns through at once.
+		///
+		/// Meant for pools seeded by the treasury, whose liquidity should not be pulled at once.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(1, 1)]
+		pub fn set_withdrawal_guard(origin, lpt: LpTokenId, threshold: Option<Permill>) -> dispatch::DispatchResult {
+			ensure_root(origin)?;
+			ensure!(Rewards::contains_key(lpt), Error::<T>::InvalidPair);
+
+			match threshold {
+				Some(threshold) => WithdrawalGuards::insert(lpt, threshold),
+				None => WithdrawalGuards::remove(lpt),
+			}
+			Self::deposit_event(RawEvent::WithdrawalGuardSet(lpt, threshold));
+			Ok(())
+		}
+
+		/// Commit to a swap by the hash of the order, see `swap_commitment`, to execute it with
+		/// `reveal_swap` in one of the next `SwapRevealWindow` blocks.
+		///
+		/// Collators only learn the order once it is revealed, too late to trade ahead of it in
+		/// the block it was submitted in. `SwapCommitBond` is reserved until the reveal and
+		/// forfeited if the commitment expires.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(2, 2)]
+		pub fn commit_swap(origin, commitment: T::Hash) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(!SwapCommits::<T>::contains_key(commitment), Error::<T>::CommitmentExists);
+
+			let bond = T::SwapCommitBond::get();
+			T::Currency::reserve(&sender, bond)?;
+			let committed_at = <frame_system::Pallet<T>>::block_number();
+			SwapCommits::<T>::insert(commitment, SwapCommit { owner: sender.clone(), committed_at, bond });
+			Self::deposit_event(RawEvent::SwapCommitted(sender, commitment));
+			Ok(())
+		}
+
+		/// Execute the swap committed to with `commit_swap`, failing if it pays out less than
+		/// `min_amount_out`, and return the bond.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(5, 5)]
+		#[transactional]
+		pub fn reveal_swap(
+			origin,
+			from: AssetId,
+			amount_in: Balance,
+			to: AssetId,
+			min_amount_out: Balance,
+			salt: [u8; 32]
+		) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let commitment = Self::swap_commitment(&sender, from, amount_in, to, min_amount_out, salt);
+			let commit = Self::swap_commit(commitment).ok_or(Error::<T>::CommitmentNotFound)?;
+			let now = <frame_system::Pallet<T>>::block_number();
+			ensure!(now > commit.committed_at, Error::<T>::RevealTooEarly);
+			ensure!(
+				now <= commit.committed_at.saturating_add(T::SwapRevealWindow::get()),
+				Error::<T>::CommitmentExpired
+			);
+
+			SwapCommits::<T>::remove(commitment);
+			T::Currency::unreserve(&sender, commit.bond);
+			let amount_out = Self::do_swap(&sender, from, amount_in, to, true)?;
+			ensure!(amount_out >= min_amount_out, Error::<T>::InsufficientOutputAmount);
+			Self::deposit_event(RawEvent::SwapRevealed(sender, commitment));
+			Ok(())
+		}
+
+		/// Remove a swap commitment which was not revealed in time, slashing its bond.
+		///
+		/// Can be called by anyone.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(2, 2)]
+		pub fn clear_expired_commitment(origin, commitment: T::Hash) -> dispatch::DispatchResult {
+			ensure_signed(origin)?;
+			let commit = Self::swap_commit(commitment).ok_or(Error::<T>::CommitmentNotFound)?;
+			ensure!(
+				<frame_system::Pallet<T>>::block_number() >
+					commit.committed_at.saturating_add(T::SwapRevealWindow::get()),
+				Error::<T>::CommitmentNotExpired
+			);
+
+			SwapCommits::<T>::remove(commitment);
+			let _ = T::Currency::slash_reserved(&commit.owner, commit.bond);
+			Self::deposit_event(RawEvent::SwapCommitmentExpired(commit.owner, commitment, commit.bond));
+			Ok(())
+		}
+
+		/// Place a swap order in the batch of a pair in batch mode, refunded if the batch clears
+		/// at a price paying out less than `min_amount_out`.
+		///
+		/// All orders of the block are cleared at the end of it at a single price, so their order
+		/// within the block does not matter. Fee discounts do not apply.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(6, 6)]
+		pub fn batch_swap(
+			origin,
+			from: AssetId,
+			amount_in: Balance,
+			to: AssetId,
+			min_amount_out: Balance
+		) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+			Self::place_batch_order(sender, from, amount_in, to, min_amount_out)
+		}
+
+		/// Clear the swaps in the pool of `lpt` in batches per block if `enabled`, else execute
+		/// them one by one.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(1, 1)]
+		pub fn set_batch_mode(origin, lpt: LpTokenId, enabled: bool) -> dispatch::DispatchResult {
+			ensure_root(origin)?;
+			ensure!(Rewards::contains_key(lpt), Error::<T>::InvalidPair);
+
+			match enabled {
+				true => BatchMode::insert(lpt, true),
+				false => BatchMode::remove(lpt),
+			}
+			Self::deposit_event(RawEvent::BatchModeSet(lpt, enabled));
+			Ok(())
+		}
+
+		/// Propose changing `parameter` of the pool of `lpt`, reserving `ParameterProposalBond`.
+		///
+		/// The change applies after `ParameterChallengePeriod` blocks unless challenged.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(4, 3)]
+		pub fn propose_pool_parameter(
+			origin,
+			lpt: LpTokenId,
+			parameter: PoolParameter<T::BlockNumber>
+		) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(Rewards::contains_key(lpt), Error::<T>::InvalidPair);
+			Self::check_parameter(&parameter)?;
+			ensure!(
+				(ParameterProposals::<T>::iter_keys().count() as u32) < T::MaxParameterProposals::get(),
+				Error::<T>::TooManyParameterProposals
+			);
+
+			let bond = T::ParameterProposalBond::get();
+			T::Currency::reserve(&sender, bond)?;
+			let applies_at = <frame_system::Pallet<T>>::block_number()
+				.saturating_add(T::ParameterChallengePeriod::get());
+			let id = NextParameterProposalId::mutate(|id| {
+				*id = id.wrapping_add(1);
+				*id
+			});
+			ParameterProposals::<T>::insert(id, ParameterProposal {
+				proposer: sender.clone(),
+				lp_token: lpt,
+				parameter: parameter.clone(),
+				bond,
+				applies_at,
+				challenged: 0,
+			});
+			Self::deposit_event(RawEvent::ParameterProposed(id, sender, lpt, parameter, applies_at));
+			Ok(())
+		}
+
+		/// Challenge the parameter proposal `id` with `amount` liquidity tokens of its pool,
+		/// reserved until the proposal is resolved.
+		///
+		/// The proposal is rejected and its bond slashed once the challenges reach
+		/// `ParameterChallengeQuorum` of the liquidity token supply.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(6, 6)]
+		#[transactional]
+		pub fn challenge_pool_parameter(origin, id: ParameterProposalId, amount: Balance) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(amount > Zero::zero(), Error::<T>::AmountZero);
+			let mut proposal = Self::parameter_proposal(id).ok_or(Error::<T>::ProposalNotFound)?;
+			ensure!(
+				<frame_system::Pallet<T>>::block_number() < proposal.applies_at,
+				Error::<T>::ChallengePeriodOver
+			);
+
+			let lp_asset = AssetId::from(proposal.lp_token);
+			Self::reserve_asset(lp_asset, &sender, amount)?;
+			ParameterChallenges::<T>::mutate(id, &sender, |challenged| {
+				*challenged = challenged.saturating_add(amount)
+			});
+			proposal.challenged = proposal.challenged.saturating_add(amount);
+			Self::deposit_event(RawEvent::ParameterChallenged(id, sender, amount));
+
+			let quorum = T::ParameterChallengeQuorum::get() * T::Assets::total_issuance(lp_asset);
+			if proposal.challenged >= quorum {
+				let _ = T::Currency::slash_reserved(&proposal.proposer, proposal.bond);
+				Self::resolve_parameter_proposal(id, &proposal);
+				Self::deposit_event(RawEvent::ParameterProposalRejected(id));
+			} else {
+				ParameterProposals::<T>::insert(id, proposal);
+			}
+			Ok(())
+		}
+
+		/// Set the swap fee of the pool of `lpt` in basis points, `None` for the default fee.
+		///
+		/// Governance changes the fee through `schedule_swap_fee`, which dispatches this call as
+		/// Root once its delay passed.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(1, 1)]
+		pub fn set_swap_fee(origin, lpt: LpTokenId, fee: Option<u32>) -> dispatch::DispatchResult {
+			ensure_root(origin)?;
+			ensure!(Rewards::contains_key(lpt), Error::<T>::InvalidPair);
+			if let Some(fee) = fee {
+				Self::check_parameter(&PoolParameter::SwapFee(fee))?;
+			}
+
+			match fee {
+				Some(fee) => PoolSwapFees::insert(lpt, fee),
+				None => PoolSwapFees::remove(lpt),
+			}
+			Self::deposit_event(RawEvent::SwapFeeSet(lpt, fee));
+			Ok(())
+		}
+
+		/// Schedule `set_swap_fee` for the pool of `lpt` to be enacted after `delay` blocks.
+		///
+		/// The delay must be at least `MinimumEnactmentDelay` so that traders and liquidity
+		/// providers have time to react to the announced fee. Only one change can be pending per
+		/// pool.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(3, 2)]
+		pub fn schedule_swap_fee(
+			origin,
+			lpt: LpTokenId,
+			fee: Option<u32>,
+			delay: T::BlockNumber
+		) -> dispatch::DispatchResult {
+			ensure_root(origin)?;
+			ensure!(Rewards::contains_key(lpt), Error::<T>::InvalidPair);
+			ensure!(delay >= T::MinimumEnactmentDelay::get(), Error::<T>::DelayTooShort);
+			if let Some(fee) = fee {
+				Self::check_parameter(&PoolParameter::SwapFee(fee))?;
+			}
+
+			let when = <frame_system::Pallet<T>>::block_number().saturating_add(delay);
+			T::Scheduler::schedule_named(
+				Self::schedule_id(lpt),
+				DispatchTime::At(when),
+				None,
+				HARD_DEADLINE,
+				frame_system::RawOrigin::Root.into(),
+				Call::<T>::set_swap_fee(lpt, fee).into(),
+			).map_err(|_| Error::<T>::ScheduleFailed)?;
+			Self::deposit_event(RawEvent::SwapFeeScheduled(lpt, fee, when));
 			Ok(())
 		}
 
+		/// Cancel a swap fee change scheduled with `schedule_swap_fee`.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(2, 2)]
+		pub fn cancel_scheduled_swap_fee(origin, lpt: LpTokenId) -> dispatch::DispatchResult {
+			ensure_root(origin)?;
+			T::Scheduler::cancel_named(Self::schedule_id(lpt))
+				.map_err(|_| Error::<T>::NotScheduled)?;
+			Self::deposit_event(RawEvent::SwapFeeScheduleCancelled(lpt));
+			Ok(())
+		}
 	}
 }
 
 decl_event! {
-	pub enum Event
+	pub enum Event<T> where
+		<T as frame_system::Config>::AccountId,
+		<T as frame_system::Config>::BlockNumber,
+		<T as frame_system::Config>::Hash,
 	{
 		/// Pair between two assets is created. \[token0, token1, lptoken]
-		CreatePair(AssetId, AssetId, AssetId),
+		CreatePair(AssetId, AssetId, LpTokenId),
 		/// An asset is swapped to another asset. \[token0, amount_in, token1, amount_out]
 		Swap(AssetId, Balance, AssetId, Balance),
 		/// Liquidity is minted. \[token0, token1, lptoken]
-		MintedLiquidity(AssetId, AssetId, AssetId),
+		MintedLiquidity(AssetId, AssetId, LpTokenId),
 		/// Liquidity is burned. \[lptoken, token0, token1]
-		BurnedLiquidity(AssetId, AssetId, AssetId),
+		BurnedLiquidity(LpTokenId, AssetId, AssetId),
 		/// Sync oracle. \[price0, price1]
 		SyncOracle(FixedU128, FixedU128),
+		/// Native currency is locked for a swap fee discount. \[who, amount, until]
+		FeeDiscountLocked(AccountId, Balance, BlockNumber),
+		/// Fee discount lock is released. \[who]
+		FeeDiscountUnlocked(AccountId),
+		/// Funds outside of the pools are moved out of the market account.
+		/// \[asset, amount, beneficiary]
+		FundsRescued(AssetId, Balance, AccountId),
+		/// Liquidity burn above the withdrawal guard of the pool is queued.
+		/// \[id, owner, lptoken, amount, executable_at]
+		WithdrawalQueued(WithdrawalId, AccountId, LpTokenId, Balance, BlockNumber),
+		/// Queued liquidity burn is executed. \[id]
+		WithdrawalExecuted(WithdrawalId),
+		/// Queued liquidity burn is cancelled and the liquidity tokens released. \[id]
+		WithdrawalCancelled(WithdrawalId),
+		/// Withdrawal guard of a pool is set. \[lptoken, threshold]
+		WithdrawalGuardSet(LpTokenId, Option<Permill>),
+		/// Pool whose liquidity tokens are all burned is removed. \[lptoken, token0, token1]
+		PoolRemoved(LpTokenId, AssetId, AssetId),
+		/// Swap order is committed to. \[who, commitment]
+		SwapCommitted(AccountId, Hash),
+		/// Committed swap order is revealed and executed. \[who, commitment]
+		SwapRevealed(AccountId, Hash),
+		/// Swap commitment expired without a reveal and its bond is slashed.
+		/// \[who, commitment, bond]
+		SwapCommitmentExpired(AccountId, Hash, Balance),
+		/// Pool is switched in or out of batch mode. \[lptoken, enabled]
+		BatchModeSet(LpTokenId, bool),
+		/// Swap order is placed in the batch of a pair. \[who, lptoken, from, amount_in]
+		BatchOrderPlaced(AccountId, LpTokenId, AssetId, Balance),
+		/// Swap order below its minimum output is refunded. \[who, lptoken, from, amount_in]
+		BatchOrderRefunded(AccountId, LpTokenId, AssetId, Balance),
+		/// Batch of a pair is cleared. \[lptoken, orders, price of token0 in token1]
+		BatchCleared(LpTokenId, u32, FixedU128),
+		/// Spot prices of token0 in token1 at the start and end of the block of the pools whose
+		/// reserves changed in it, with the `price-telemetry` feature. \[(lptoken, open, close)]
+		BlockPrices(Vec<(LpTokenId, FixedU128, FixedU128)>),
+		/// Pool parameter change is proposed.
+		/// \[id, proposer, lptoken, parameter, applies_at]
+		ParameterProposed(ParameterProposalId, AccountId, LpTokenId, PoolParameter<BlockNumber>, BlockNumber),
+		/// Liquidity tokens are escrowed against a parameter proposal. \[id, who, amount]
+		ParameterChallenged(ParameterProposalId, AccountId, Balance),
+		/// Parameter proposal reached the challenge quorum and its bond is slashed. \[id]
+		ParameterProposalRejected(ParameterProposalId),
+		/// Unchallenged parameter proposal is applied. \[id, lptoken, parameter]
+		PoolParameterSet(ParameterProposalId, LpTokenId, PoolParameter<BlockNumber>),
+		/// Swap fee of a pool is set by governance. \[lptoken, fee]
+		SwapFeeSet(LpTokenId, Option<u32>),
+		/// Swap fee change of a pool is scheduled. \[lptoken, fee, enacted_at]
+		SwapFeeScheduled(LpTokenId, Option<u32>, BlockNumber),
+		/// Scheduled swap fee change of a pool is cancelled. \[lptoken]
+		SwapFeeScheduleCancelled(LpTokenId),
 	}
 }
 
@@ -314,6 +891,48 @@ decl_error! {
 		InsufficientLiquidity,
 		/// The ratio does not match from previous K
 		K,
+		/// Fee discount lock must last at least `MinFeeLockPeriod` blocks
+		LockTooShort,
+		/// Fee discount lock can not be lowered or shortened
+		LockReduced,
+		/// No fee discount lock
+		NoFeeLock,
+		/// Fee discount lock has not expired yet
+		FeeLockActive,
+		/// Amount is more than the funds of the account outside of the pools
+		ExceedsExcessFunds,
+		/// No queued withdrawal with the id
+		WithdrawalNotFound,
+		/// Queued withdrawal can not be executed yet
+		WithdrawalDelayed,
+		/// Swap commitment already exists
+		CommitmentExists,
+		/// No swap commitment matches the order
+		CommitmentNotFound,
+		/// Swap can only be revealed after the block it was committed in
+		RevealTooEarly,
+		/// Swap was not revealed within `SwapRevealWindow` blocks
+		CommitmentExpired,
+		/// Swap commitment can still be revealed
+		CommitmentNotExpired,
+		/// Pair does not clear its swaps in batches
+		PairNotInBatchMode,
+		/// Batch of the pair already holds `MaxBatchOrders` orders this block
+		TooManyBatchOrders,
+		/// Swap fee above `MAX_SWAP_FEE` or TWAP window outside of the observations kept
+		InvalidParameter,
+		/// `MaxParameterProposals` proposals are open already
+		TooManyParameterProposals,
+		/// No parameter proposal with the id
+		ProposalNotFound,
+		/// Parameter proposal can no longer be challenged
+		ChallengePeriodOver,
+		/// Enactment delay is shorter than `MinimumEnactmentDelay`
+		DelayTooShort,
+		/// Swap fee change could not be scheduled, e.g. one is already pending for the pool
+		ScheduleFailed,
+		/// No swap fee change is scheduled for the pool
+		NotScheduled,
 
 	}
 }
@@ -323,10 +942,69 @@ decl_storage! {
 		/// Market storage
 		//pub LastBlockTimestamp get(fn last_block_timestamp): T::Moment;
 		// Accumulated price data for each pair. key is lptoken identifier
-		pub LastAccumulativePrice get(fn last_cumulative_price): map hasher(blake2_128_concat) AssetId => (FixedU128, FixedU128);
-		pub Rewards get(fn reward): map hasher(blake2_128_concat) AssetId => (AssetId, AssetId);
-		pub Reserves get(fn reserves): map hasher(blake2_128_concat) AssetId => (Balance, Balance);
-		pub Pairs get(fn pair): map hasher(blake2_128_concat) (AssetId, AssetId) => Option<AssetId>;
+		pub LastAccumulativePrice get(fn last_cumulative_price): map hasher(blake2_128_concat) LpTokenId => (FixedU128, FixedU128);
+		/// Block at which the accumulated prices of each pair were last updated
+		pub LastPriceUpdate get(fn last_price_update): map hasher(blake2_128_concat) LpTokenId => T::BlockNumber;
+		/// Accumulated prices of each pair at the start of its last `MaxTwapObservations` TWAP
+		/// periods, oldest first, as (price0, price1, block)
+		pub PriceObservations get(fn price_observations): map hasher(blake2_128_concat) LpTokenId => Vec<(FixedU128, FixedU128, T::BlockNumber)>;
+		pub Rewards get(fn reward): map hasher(blake2_128_concat) LpTokenId => (AssetId, AssetId);
+		pub Reserves get(fn reserves): map hasher(blake2_128_concat) LpTokenId => (Balance, Balance);
+		pub Pairs get(fn pair): map hasher(blake2_128_concat) (AssetId, AssetId) => Option<LpTokenId>;
+		/// Native currency locked for a swap fee discount and the block the lock expires at
+		pub FeeLocks get(fn fee_lock): map hasher(blake2_128_concat) T::AccountId => Option<(Balance, T::BlockNumber)>;
+		/// Volume each account swapped per volume era as (lptoken, volume)
+		pub AccountVolume get(fn account_volume): double_map hasher(twox_64_concat) VolumeEra, hasher(blake2_128_concat) T::AccountId => Vec<(LpTokenId, Balance)>;
+		/// Volume swapped in each pair per volume era
+		pub PairVolume get(fn pair_volume): double_map hasher(twox_64_concat) VolumeEra, hasher(blake2_128_concat) LpTokenId => Balance;
+		/// Share of the liquidity token supply above which burns in the pool are queued
+		pub WithdrawalGuards get(fn withdrawal_guard): map hasher(blake2_128_concat) LpTokenId => Option<Permill>;
+		/// Liquidity burns waiting for the delay of the withdrawal guard
+		pub QueuedWithdrawals get(fn queued_withdrawal): map hasher(twox_64_concat) WithdrawalId => Option<QueuedWithdrawal<T::AccountId, T::BlockNumber>>;
+		pub NextWithdrawalId get(fn next_withdrawal_id): WithdrawalId;
+		/// Swap orders committed to and not revealed yet, by commitment
+		pub SwapCommits get(fn swap_commit): map hasher(blake2_128_concat) T::Hash => Option<SwapCommit<T::AccountId, T::BlockNumber>>;
+		/// Swap fees paid into each pool per fee bucket, in the tokens of the pair
+		pub PoolFees get(fn pool_fees): double_map hasher(twox_64_concat) FeeBucket, hasher(blake2_128_concat) LpTokenId => (Balance, Balance);
+		/// Liquidity tokens each account minted and did not burn yet with the tokens of the pair
+		/// deposited for them, as (liquidity, amount0, amount1)
+		pub CostBasis get(fn cost_basis): double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) LpTokenId => Option<(Balance, Balance, Balance)>;
+		/// Pools whose swaps are cleared in batches per block
+		pub BatchMode get(fn batch_mode): map hasher(blake2_128_concat) LpTokenId => bool;
+		/// Swap orders placed in the batch of each pair this block, cleared in `on_finalize`
+		pub BatchOrders get(fn batch_orders): map hasher(blake2_128_concat) LpTokenId => Vec<BatchOrder<T::AccountId>>;
+		/// Assets of each account reserved for pending swap orders, frozen by the assets pallet
+		pub ReservedAssets get(fn reserved_asset): double_map hasher(blake2_128_concat) AssetId, hasher(blake2_128_concat) T::AccountId => Balance;
+		/// Spot prices at the start of the block of the pools whose reserves changed in it, only
+		/// recorded with the `price-telemetry` feature
+		pub BlockOpenPrices get(fn block_open_price): map hasher(blake2_128_concat) LpTokenId => Option<FixedU128>;
+		/// Swap fee of each pool in basis points set by a parameter proposal, `SWAP_FEE` if unset
+		pub PoolSwapFees get(fn pool_swap_fee): map hasher(blake2_128_concat) LpTokenId => Option<u32>;
+		/// Minimum window of the TWAPs of each pool set by a parameter proposal
+		pub PoolTwapWindows get(fn pool_twap_window): map hasher(blake2_128_concat) LpTokenId => Option<T::BlockNumber>;
+		/// Open pool parameter proposals
+		pub ParameterProposals get(fn parameter_proposal): map hasher(twox_64_concat) ParameterProposalId => Option<ParameterProposal<T::AccountId, T::BlockNumber>>;
+		pub NextParameterProposalId get(fn next_parameter_proposal_id): ParameterProposalId;
+		/// Liquidity tokens each account escrowed against an open parameter proposal
+		pub ParameterChallenges get(fn parameter_challenge): double_map hasher(twox_64_concat) ParameterProposalId, hasher(blake2_128_concat) T::AccountId => Balance;
+		/// Last pool checked by `purge_closed_pools`, the next purge continues after it
+		pub PoolPurgeCursor get(fn pool_purge_cursor): Option<LpTokenId>;
+	} add_extra_genesis {
+		/// Pools minted at genesis as `(owner, token0, amount0, token1, amount1)`, the owner
+		/// deposits the reserves and receives the liquidity tokens
+		config(pools): Vec<(T::AccountId, AssetId, Balance, AssetId, Balance)>;
+		build(|config: &GenesisConfig<T>| {
+			for (owner, token0, amount0, token1, amount1) in config.pools.iter() {
+				Module::<T>::mint_liquidity(
+					frame_system::RawOrigin::Signed(owner.clone()).into(),
+					*token0,
+					*amount0,
+					*token1,
+					*amount1,
+				)
+				.expect("Genesis pool owners hold their deposits");
+			}
+		});
 	}
 }
 
@@ -336,14 +1014,917 @@ impl<T: Config> Module<T> {
 		<T as Config>::SystemPalletId::get().into_account()
 	}
 
+	/// Assets whose recorded reserves exceed what the module account holds, as
+	/// `(asset, recorded, held)`.
+	///
+	/// The module account may hold more than the reserves (e.g. after a direct transfer), but
+	/// holding less means swaps and withdrawals of the asset will fail.
+	pub fn reserve_discrepancies() -> Vec<(AssetId, Balance, Balance)> {
+		let mut recorded: BTreeMap<AssetId, Balance> = BTreeMap::new();
+		for (lpt, reserves) in Reserves::iter() {
+			let tokens = Self::reward(lpt);
+			*recorded.entry(tokens.0).or_default() += reserves.0;
+			*recorded.entry(tokens.1).or_default() += reserves.1;
+		}
+		let account = Self::account_id();
+		recorded
+			.into_iter()
+			.map(|(asset, recorded)| (asset, recorded, T::Assets::balance(asset, &account)))
+			.filter(|(_, recorded, held)| held < recorded)
+			.collect()
+	}
+
+	// Burn `amount` of `lpt` held by `holder` and pay its share of the reserves to `recipient`
+	fn do_burn_liquidity(
+		holder: &T::AccountId,
+		recipient: &T::AccountId,
+		lpt: LpTokenId,
+		amount: Balance,
+	) -> dispatch::DispatchResult {
+		let mut reserves = Self::reserves(lpt);
+		let tokens = Self::reward(lpt);
+		let total_supply = T::Assets::total_issuance(lpt.into());
+
+		// Calculate rewards for providing liquidity with pro-rata distribution
+		let (reward0, reward1) = math::liquidity_burned(amount, reserves, total_supply)
+			.ok_or(Error::<T>::InsufficientLiquidityBurned)?;
+
+		// Ensure rewards exist
+		ensure!(
+			reward0 > Zero::zero() && reward1 > Zero::zero(),
+			Error::<T>::InsufficientLiquidityBurned
+		);
+
+		// Distribute reward to the recipient
+		T::Assets::burn_from(lpt.into(), holder, amount)?;
+		T::Assets::transfer(tokens.0, &Self::account_id(), recipient, reward0, true)?;
+		T::Assets::transfer(tokens.1, &Self::account_id(), recipient, reward1, true)?;
+
+		Self::remove_cost_basis(recipient, lpt, amount);
+
+		// Update reserve when the balance is set
+		reserves.0 -= reward0;
+		reserves.1 -= reward1;
+		Self::_set_reserves(tokens.0, tokens.1, reserves.0, reserves.1, lpt);
+		// Deposit event that the liquidity is burned successfully
+		Self::deposit_event(RawEvent::BurnedLiquidity(lpt, tokens.0, tokens.1));
+		// A pool without liquidity tokens can not be minted into again
+		if T::Assets::total_issuance(lpt.into()).is_zero() {
+			Self::remove_pool(lpt);
+		}
+		Ok(())
+	}
+
+	/// Checks the next `limit` pools after `PoolPurgeCursor` and removes those whose liquidity
+	/// tokens are all burned, returning how many were removed.
+	///
+	/// # <weight>
+	/// - O(L) reads where L is `limit`
+	/// # </weight>
+	pub fn purge_closed_pools(limit: u32) -> u32 {
+		let pools = match Self::pool_purge_cursor() {
+			Some(cursor) => Rewards::iter_keys_from(Rewards::hashed_key_for(cursor)),
+			None => Rewards::iter_keys(),
+		};
+		let checked: Vec<LpTokenId> = pools.take(limit as usize).collect();
+		// Start over from the first pool once the last one was checked
+		if (checked.len() as u32) < limit {
+			PoolPurgeCursor::kill();
+		} else {
+			PoolPurgeCursor::set(checked.last().copied());
+		}
+
+		let closed: Vec<LpTokenId> = checked
+			.into_iter()
+			.filter(|lpt| T::Assets::total_issuance((*lpt).into()).is_zero())
+			.collect();
+		for lpt in &closed {
+			Self::remove_pool(*lpt);
+		}
+		closed.len() as u32
+	}
+
+	// Remove the pair, reserves and price history of the pool of `lpt`. Reserves left as rounding
+	// dust become excess funds of the market account, and creating the pair again reuses `lpt`
+	fn remove_pool(lpt: LpTokenId) {
+		let (token0, token1) = Rewards::take(lpt);
+		Pairs::remove((token0, token1));
+		Pairs::remove((token1, token0));
+		Reserves::remove(lpt);
+		LastAccumulativePrice::remove(lpt);
+		LastPriceUpdate::<T>::remove(lpt);
+		PriceObservations::<T>::remove(lpt);
+		WithdrawalGuards::remove(lpt);
+		BatchMode::remove(lpt);
+		PoolSwapFees::remove(lpt);
+		PoolTwapWindows::<T>::remove(lpt);
+		Self::deposit_event(RawEvent::PoolRemoved(lpt, token0, token1));
+	}
+
+	// Reserve the liquidity tokens of `owner` until the burn can be executed
+	fn queue_withdrawal(
+		owner: T::AccountId,
+		lpt: LpTokenId,
+		amount: Balance,
+	) -> dispatch::DispatchResult {
+		Self::reserve_asset(lpt.into(), &owner, amount)?;
+		let id = NextWithdrawalId::mutate(|next| {
+			let id = *next;
+			*next += 1;
+			id
+		});
+		let executable_at = <frame_system::Pallet<T>>::block_number()
+			.saturating_add(T::GuardedWithdrawalDelay::get());
+		QueuedWithdrawals::<T>::insert(
+			id,
+			QueuedWithdrawal { owner: owner.clone(), lp_token: lpt, amount, executable_at },
+		);
+		Self::deposit_event(RawEvent::WithdrawalQueued(id, owner, lpt, amount, executable_at));
+		Ok(())
+	}
+
+	// Reserves `amount` of `asset` in the account of `who` for a pending operation
+	fn reserve_asset(
+		asset: AssetId,
+		who: &T::AccountId,
+		amount: Balance,
+	) -> dispatch::DispatchResult {
+		ensure!(
+			T::Assets::reducible_balance(asset, who, true) >= amount,
+			Error::<T>::InSufficientBalance
+		);
+		ReservedAssets::<T>::mutate(asset, who, |reserved| {
+			*reserved = reserved.saturating_add(amount)
+		});
+		Ok(())
+	}
+
+	// Releases up to `amount` of `asset` reserved in the account of `who`
+	fn unreserve_asset(asset: AssetId, who: &T::AccountId, amount: Balance) {
+		ReservedAssets::<T>::mutate_exists(asset, who, |reserved| {
+			*reserved = reserved.map(|r| r.saturating_sub(amount)).filter(|r| !r.is_zero())
+		});
+	}
+
+	// Pool reserves of `asset`
+	fn reserve_of(asset: AssetId) -> Balance {
+		Reserves::iter().fold(0, |total: Balance, (lpt, reserves)| {
+			let (token0, token1) = Self::reward(lpt);
+			let reserve = if asset == token0 {
+				reserves.0
+			} else if asset == token1 {
+				reserves.1
+			} else {
+				0
+			};
+			total.saturating_add(reserve)
+		})
+	}
+
+	/// Liquidity tokens held by `who` and the reserves they can be redeemed for.
+	pub fn liquidity_of(who: &T::AccountId) -> Vec<LiquidityPosition> {
+		Rewards::iter()
+			.filter_map(|(lpt, tokens)| Self::position_of(who, lpt, tokens))
+			.collect()
+	}
+
+	/// Liquidity tokens of `lp_token` held by `who` and the reserves they can be redeemed for,
+	/// `None` without a pool or without liquidity tokens.
+	pub fn liquidity_position(
+		who: &T::AccountId,
+		lp_token: LpTokenId,
+	) -> Option<LiquidityPosition> {
+		Rewards::contains_key(lp_token)
+			.then(|| Self::position_of(who, lp_token, Self::reward(lp_token)))
+			.flatten()
+	}
+
+	fn position_of(
+		who: &T::AccountId,
+		lpt: LpTokenId,
+		tokens: (AssetId, AssetId),
+	) -> Option<LiquidityPosition> {
+		let amount = T::Assets::balance(lpt.into(), who);
+		let total_supply = T::Assets::total_issuance(lpt.into());
+		if amount.is_zero() || total_supply.is_zero() {
+			return None
+		}
+		let (amount0, amount1) = math::liquidity_burned(amount, Self::reserves(lpt), total_supply)?;
+		Some(LiquidityPosition {
+			lp_token: lpt,
+			amount,
+			underlying: [(tokens.0, amount0), (tokens.1, amount1)],
+		})
+	}
+
+	/// Liquidity tokens minted by `who` against the deposits they were minted for and the reserves
+	/// they can be redeemed for now, ordered by liquidity token.
+	///
+	/// The values are left to the runtime, which knows the prices.
+	pub fn liquidity_returns(who: &T::AccountId) -> Vec<LiquidityReturn> {
+		let mut returns: Vec<LiquidityReturn> = CostBasis::<T>::iter_prefix(who)
+			.filter_map(|(lpt, (liquidity, amount0, amount1))| {
+				let total_supply = T::Assets::total_issuance(lpt.into());
+				if total_supply.is_zero() || !Rewards::contains_key(lpt) {
+					return None
+				}
+				let tokens = Self::reward(lpt);
+				let (current0, current1) =
+					math::liquidity_burned(liquidity, Self::reserves(lpt), total_supply)?;
+				Some(LiquidityReturn {
+					lp_token: lpt,
+					liquidity,
+					deposited: [(tokens.0, amount0), (tokens.1, amount1)],
+					current: [(tokens.0, current0), (tokens.1, current1)],
+					deposit_value: None,
+					current_value: None,
+				})
+			})
+			.collect();
+		returns.sort_by_key(|position| position.lp_token);
+		returns
+	}
+
+	/// Commitment of `who` to swap `amount_in` of `from` for at least `min_amount_out` of `to`,
+	/// blinded by `salt`.
+	pub fn swap_commitment(
+		who: &T::AccountId,
+		from: AssetId,
+		amount_in: Balance,
+		to: AssetId,
+		min_amount_out: Balance,
+		salt: [u8; 32],
+	) -> T::Hash {
+		T::Hashing::hash_of(&(who, from, amount_in, to, min_amount_out, salt))
+	}
+
+	// Adds `liquidity` minted for `deposited`, ordered as the pair, to the cost basis of `who`
+	fn add_cost_basis(
+		who: &T::AccountId,
+		lpt: LpTokenId,
+		liquidity: Balance,
+		deposited: (Balance, Balance),
+	) {
+		CostBasis::<T>::mutate(who, lpt, |basis| {
+			let (total, amount0, amount1) = basis.get_or_insert((0, 0, 0));
+			*total = total.saturating_add(liquidity);
+			*amount0 = amount0.saturating_add(deposited.0);
+			*amount1 = amount1.saturating_add(deposited.1);
+		});
+	}
+
+	// Removes the deposits of `burned` liquidity tokens from the cost basis of `who`, pro rata.
+	// Tokens received by transfer have no cost basis, so at most the tracked liquidity is removed
+	fn remove_cost_basis(who: &T::AccountId, lpt: LpTokenId, burned: Balance) {
+		CostBasis::<T>::mutate_exists(who, lpt, |basis| {
+			if let Some((liquidity, amount0, amount1)) = *basis {
+				*basis = match burned < liquidity {
+					true => {
+						let (removed0, removed1) =
+							math::liquidity_burned(burned, (amount0, amount1), liquidity)
+								.unwrap_or_default();
+						Some((liquidity - burned, amount0 - removed0, amount1 - removed1))
+					},
+					false => None,
+				};
+			}
+		});
+	}
+
+	/// Reserves and liquidity token supply of every pool, ordered by liquidity token.
+	pub fn pools() -> Vec<PoolSnapshot> {
+		let mut pools: Vec<PoolSnapshot> =
+			Rewards::iter().map(|(lpt, tokens)| Self::pool_snapshot(lpt, tokens)).collect();
+		pools.sort_by_key(|pool| pool.lp_token);
+		pools
+	}
+
+	/// Pool of `lp_token` with the storage keys of its pair and reserves.
+	///
+	/// The liquidity token supply is kept by `T::Assets`, whose storage key the runtime adds.
+	pub fn proven_pool(lp_token: LpTokenId) -> Proven<Option<PoolSnapshot>> {
+		let keys = vec![Rewards::hashed_key_for(lp_token), Reserves::hashed_key_for(lp_token)];
+		let value = Rewards::contains_key(lp_token)
+			.then(|| Self::pool_snapshot(lp_token, Self::reward(lp_token)));
+		Proven { value, keys }
+	}
+
+	/// Pair and symbol of the liquidity token `lp_token`.
+	pub fn lp_token_info(lp_token: LpTokenId) -> Option<LpTokenInfo> {
+		Rewards::contains_key(lp_token)
+			.then(|| Self::lp_token_info_of(lp_token, Self::reward(lp_token)))
+	}
+
+	/// Pair and symbol of every liquidity token, ordered by liquidity token.
+	pub fn lp_tokens() -> Vec<LpTokenInfo> {
+		let mut tokens: Vec<LpTokenInfo> =
+			Rewards::iter().map(|(lpt, pair)| Self::lp_token_info_of(lpt, pair)).collect();
+		tokens.sort_by_key(|info| info.lp_token);
+		tokens
+	}
+
+	fn lp_token_info_of(lpt: LpTokenId, pair: (AssetId, AssetId)) -> LpTokenInfo {
+		let symbol = pallet_asset_registry::Pallet::<T>::asset_name(AssetId::from(lpt).into())
+			.unwrap_or_else(|| Self::lp_token_symbol(pair.0, pair.1));
+		LpTokenInfo { lp_token: lpt, pair, symbol }
+	}
+
+	/// Symbol the liquidity token of a pair is registered under, `LP-<symbol0>-<symbol1>` with the
+	/// assets ordered by id.
+	///
+	/// The symbols are the names of the assets in the registry, or their ids if they have none.
+	pub fn lp_token_symbol(token0: AssetId, token1: AssetId) -> Vec<u8> {
+		let (token0, token1) = if token0 > token1 { (token1, token0) } else { (token0, token1) };
+		let mut symbol = b"LP-".to_vec();
+		symbol.extend(Self::asset_symbol(token0));
+		symbol.push(b'-');
+		symbol.extend(Self::asset_symbol(token1));
+		symbol
+	}
+
+	fn asset_symbol(asset: AssetId) -> Vec<u8> {
+		pallet_asset_registry::Pallet::<T>::asset_name(asset.into()).unwrap_or_else(|| {
+			let mut digits = Vec::new();
+			let mut rest = asset;
+			loop {
+				digits.push(b'0' + (rest % 10) as u8);
+				rest /= 10;
+				if rest == 0 {
+					break
+				}
+			}
+			digits.reverse();
+			digits
+		})
+	}
+
+	fn pool_snapshot(lpt: LpTokenId, tokens: (AssetId, AssetId)) -> PoolSnapshot {
+		let reserves = Self::reserves(lpt);
+		PoolSnapshot {
+			lp_token: lpt,
+			reserves: [(tokens.0, reserves.0), (tokens.1, reserves.1)],
+			total_supply: T::Assets::total_issuance(lpt.into()),
+		}
+	}
+
+	/// Output of swapping `amount_in` of `from` to `to` at the current reserves.
+	pub fn quote(
+		from: AssetId,
+		amount_in: Balance,
+		to: AssetId,
+	) -> Result<Balance, dispatch::DispatchError> {
+		let (lpt, reserve_in, reserve_out) = Self::swap_reserves(from, to)?;
+		let fee = Self::swap_fee_of(lpt);
+		Ok(math::get_amount_out_with_fee(amount_in, reserve_in, reserve_out, fee))
+	}
+
+	/// Amount of `from` `sender` has to swap to receive at least `amount_out` of `to` at the
+	/// current reserves, after the swap fee of `sender`.
+	pub fn quote_in(
+		sender: &T::AccountId,
+		from: AssetId,
+		to: AssetId,
+		amount_out: Balance,
+	) -> Result<Balance, dispatch::DispatchError> {
+		let (lpt, reserve_in, reserve_out) = Self::swap_reserves(from, to)?;
+		let fee = Self::swap_fee_of(lpt).saturating_sub(Self::fee_discount(sender).into());
+		math::get_amount_in_with_fee(amount_out, reserve_in, reserve_out, fee)
+			.ok_or_else(|| Error::<T>::InsufficientLiquidity.into())
+	}
+
+	/// Swaps `amount_in` of `from` owned by `sender` to `to`, returning the amount received.
+	///
+	/// With `keep_alive` the swap fails if it would reap the account of `sender`.
+	pub fn do_swap(
+		sender: &T::AccountId,
+		from: AssetId,
+		amount_in: Balance,
+		to: AssetId,
+		keep_alive: bool,
+	) -> Result<Balance, dispatch::DispatchError> {
+		ensure!(amount_in > Zero::zero(), Error::<T>::InsufficientAmount);
+		let (lpt, mut reserve_in, mut reserve_out) = Self::swap_reserves(from, to)?;
+		// get amount out
+		let fee = Self::swap_fee_of(lpt).saturating_sub(Self::fee_discount(sender).into());
+		let amount_out = math::get_amount_out_with_fee(amount_in, reserve_in, reserve_out, fee);
+		let fee_paid = math::swap_fee(amount_in, fee);
+		log!(
+			debug,
+			"swap on {:?}: {} of {} for {} of {} at reserves ({}, {}) and fee {}",
+			lpt,
+			amount_in,
+			from,
+			amount_out,
+			to,
+			reserve_in,
+			reserve_out,
+			fee,
+		);
+		// transfer amount in to system
+		T::Assets::transfer(from, sender, &Self::account_id(), amount_in, keep_alive)?;
+		// transfer swapped amount
+		T::Assets::transfer(to, &Self::account_id(), sender, amount_out, true)?;
+		// update reserves
+		reserve_in += amount_in;
+		reserve_out -= amount_out;
+		Self::_set_reserves(from, to, reserve_in, reserve_out, lpt);
+		// volume is counted in the first token of the pair
+		let volume = if from < to { amount_in } else { amount_out };
+		Self::record_volume(sender, lpt, volume);
+		Self::record_fee(lpt, from < to, fee_paid);
+		// Deposit event that the liquidity is burned successfully
+		Self::deposit_event(RawEvent::Swap(from, amount_in, to, amount_out));
+		Ok(amount_out)
+	}
+
+	// Reserves `amount_in` of `from` in the account of `owner` and places the order in the batch
+	// of the pair of `from` and `to`
+	fn place_batch_order(
+		owner: T::AccountId,
+		from: AssetId,
+		amount_in: Balance,
+		to: AssetId,
+		min_amount_out: Balance,
+	) -> dispatch::DispatchResult {
+		ensure!(amount_in > Zero::zero(), Error::<T>::InsufficientAmount);
+		let (lpt, _, _) = Self::swap_reserves(from, to)?;
+		ensure!(Self::batch_mode(lpt), Error::<T>::PairNotInBatchMode);
+		let mut orders = Self::batch_orders(lpt);
+		ensure!((orders.len() as u32) < T::MaxBatchOrders::get(), Error::<T>::TooManyBatchOrders);
+
+		Self::reserve_asset(from, &owner, amount_in)?;
+		orders.push(BatchOrder { owner: owner.clone(), from, amount_in, min_amount_out });
+		BatchOrders::<T>::insert(lpt, orders);
+		Self::deposit_event(RawEvent::BatchOrderPlaced(owner, lpt, from, amount_in));
+		Ok(())
+	}
+
+	// Clears the swap orders placed in the batch of `lpt` at a single price, at which the net
+	// flow of the batch is swapped against the pool. Orders the price pays out less than their
+	// minimum to are refunded and the price computed again without them.
+	//
+	// The reserves of all orders are released first. Filled orders then pay the amount sold into
+	// the market account, which pays out the amount bought, and refunded orders keep theirs.
+	// Orders which can not pay the amount sold are refunded before the price is computed.
+	fn clear_batch(lpt: LpTokenId, mut orders: Vec<BatchOrder<T::AccountId>>) {
+		for order in &orders {
+			Self::unreserve_asset(order.from, &order.owner, order.amount_in);
+		}
+		let (token0, token1) = Self::reward(lpt);
+		let reserves = Self::reserves(lpt);
+		let account = Self::account_id();
+		let pay_out = |order: &BatchOrder<T::AccountId>, price| {
+			let (to, first) = match order.from == token0 {
+				true => (token1, true),
+				false => (token0, false),
+			};
+			(to, math::batch_amount_out(order.amount_in, price, first))
+		};
+
+		let mut refunded = Vec::new();
+		// The pool may have been removed since the orders were placed
+		if reserves.0.is_zero() || reserves.1.is_zero() {
+			refunded.append(&mut orders);
+		}
+		let fee = Self::swap_fee_of(lpt);
+		let mut cleared = None;
+		while !orders.is_empty() {
+			let sold = orders.iter().fold((0, 0), |sold: (Balance, Balance), order| {
+				match order.from == token0 {
+					true => (sold.0.saturating_add(order.amount_in), sold.1),
+					false => (sold.0, sold.1.saturating_add(order.amount_in)),
+				}
+			});
+			let price = math::batch_clearing_price(sold, reserves, fee);
+			let (filled, unfilled): (Vec<_>, Vec<_>) = orders.into_iter().partition(|order| {
+				let (to, amount_out) = pay_out(order, price);
+				amount_out >= order.min_amount_out &&
+					T::Assets::can_withdraw(order.from, &order.owner, order.amount_in) ==
+						WithdrawConsequence::Success &&
+					T::Assets::can_deposit(to, &order.owner, amount_out) ==
+						DepositConsequence::Success
+			});
+			orders = filled;
+			if unfilled.is_empty() {
+				cleared = Some((sold, price));
+				break
+			}
+			refunded.extend(unfilled);
+		}
+
+		log!(
+			debug,
+			"batch of {:?}: {} orders filled at {:?}, {} refunded",
+			lpt,
+			orders.len(),
+			cleared,
+			refunded.len(),
+		);
+		for order in refunded {
+			Self::deposit_event(RawEvent::BatchOrderRefunded(
+				order.owner,
+				lpt,
+				order.from,
+				order.amount_in,
+			));
+		}
+		let (mut sold, price) = match cleared {
+			Some(cleared) => cleared,
+			None => return,
+		};
+
+		// Orders are only filled once the amount sold is paid in
+		orders.retain(|order| {
+			let paid_in =
+				T::Assets::transfer(order.from, &order.owner, &account, order.amount_in, false)
+					.is_ok();
+			if !paid_in {
+				match order.from == token0 {
+					true => sold.0 = sold.0.saturating_sub(order.amount_in),
+					false => sold.1 = sold.1.saturating_sub(order.amount_in),
+				}
+				Self::deposit_event(RawEvent::BatchOrderRefunded(
+					order.owner.clone(),
+					lpt,
+					order.from,
+					order.amount_in,
+				));
+			}
+			paid_in
+		});
+		let mut paid: (Balance, Balance) = (0, 0);
+		for order in &orders {
+			let (to, amount_out) = pay_out(order, price);
+			let _ = T::Assets::transfer(to, &account, &order.owner, amount_out, false);
+			// volume is counted in the first token of the pair
+			let volume = match order.from == token0 {
+				true => {
+					paid.1 = paid.1.saturating_add(amount_out);
+					order.amount_in
+				},
+				false => {
+					paid.0 = paid.0.saturating_add(amount_out);
+					amount_out
+				},
+			};
+			Self::record_volume(&order.owner, lpt, volume);
+			Self::deposit_event(RawEvent::Swap(order.from, order.amount_in, to, amount_out));
+		}
+		Self::_set_reserves(
+			token0,
+			token1,
+			reserves.0.saturating_add(sold.0).saturating_sub(paid.0),
+			reserves.1.saturating_add(sold.1).saturating_sub(paid.1),
+			lpt,
+		);
+		// The fee is paid on the net flow of the batch into the pool
+		let (net0, net1) = (sold.0.saturating_sub(paid.0), sold.1.saturating_sub(paid.1));
+		match math::batch_amount_out(net0, price, true) >= net1 {
+			true => Self::record_fee(lpt, true, math::swap_fee(net0, fee)),
+			false => Self::record_fee(lpt, false, math::swap_fee(net1, fee)),
+		}
+		let price =
+			FixedU128::from_inner(math::batch_amount_out(FixedU128::accuracy(), price, true));
+		Self::deposit_event(RawEvent::BatchCleared(lpt, orders.len() as u32, price));
+	}
+
+	// Scheduler task name for swap fee changes of a pool
+	fn schedule_id(lpt: LpTokenId) -> Vec<u8> {
+		(MARKET_SCHEDULE_ID, lpt).encode()
+	}
+
+	/// Swap fee of the pool of `lpt` in basis points, before fee discounts.
+	pub fn swap_fee_of(lpt: LpTokenId) -> u128 {
+		Self::pool_swap_fee(lpt).map_or(math::SWAP_FEE, u128::from)
+	}
+
+	// Checks the swap fee or TWAP window proposed for a pool
+	fn check_parameter(parameter: &PoolParameter<T::BlockNumber>) -> dispatch::DispatchResult {
+		let valid = match parameter {
+			PoolParameter::SwapFee(fee) => u128::from(*fee) <= math::MAX_SWAP_FEE,
+			PoolParameter::TwapWindow(window) => {
+				let observed = T::TwapPeriod::get()
+					.saturating_mul(T::MaxTwapObservations::get().saturating_sub(1).into());
+				*window >= T::TwapPeriod::get() && *window <= observed
+			},
+		};
+		ensure!(valid, Error::<T>::InvalidParameter);
+		Ok(())
+	}
+
+	// Applies the parameter proposals whose challenge period ends at `now`
+	fn apply_parameter_proposals(now: T::BlockNumber) -> Weight {
+		let due: Vec<_> = ParameterProposals::<T>::iter()
+			.filter(|(_, proposal)| proposal.applies_at <= now)
+			.collect();
+		let count = due.len() as Weight;
+		for (id, proposal) in due {
+			match proposal.parameter.clone() {
+				PoolParameter::SwapFee(fee) => PoolSwapFees::insert(proposal.lp_token, fee),
+				PoolParameter::TwapWindow(window) =>
+					PoolTwapWindows::<T>::insert(proposal.lp_token, window),
+			}
+			T::Currency::unreserve(&proposal.proposer, proposal.bond);
+			Self::resolve_parameter_proposal(id, &proposal);
+			Self::deposit_event(RawEvent::PoolParameterSet(
+				id,
+				proposal.lp_token,
+				proposal.parameter,
+			));
+		}
+		T::DbWeight::get().reads_writes(1 + 4 * count, 4 * count)
+	}
+
+	// Removes the parameter proposal `id` and releases the liquidity tokens reserved against it
+	fn resolve_parameter_proposal(
+		id: ParameterProposalId,
+		proposal: &ParameterProposal<T::AccountId, T::BlockNumber>,
+	) {
+		ParameterProposals::<T>::remove(id);
+		let lp_asset = AssetId::from(proposal.lp_token);
+		for (challenger, amount) in ParameterChallenges::<T>::drain_prefix(id) {
+			Self::unreserve_asset(lp_asset, &challenger, amount);
+		}
+	}
+
+	/// Swap fee discount of `who` in basis points.
+	///
+	/// Highest tier of `FeeDiscountTiers` reached by the native currency `who` locked, until the
+	/// lock expires.
+	pub fn fee_discount(who: &T::AccountId) -> u32 {
+		let (locked, until) = match Self::fee_lock(who) {
+			Some(lock) => lock,
+			None => return 0,
+		};
+		if <frame_system::Pallet<T>>::block_number() >= until {
+			return 0
+		}
+		T::FeeDiscountTiers::get()
+			.into_iter()
+			.filter(|(minimum, _)| locked >= *minimum)
+			.map(|(_, discount)| discount)
+			.max()
+			.unwrap_or(0)
+	}
+
+	/// Volume era of the block `now`
+	pub fn volume_era(now: T::BlockNumber) -> VolumeEra {
+		(now / T::VolumeEraLength::get()).unique_saturated_into()
+	}
+
+	/// Volume era of the current block
+	pub fn current_volume_era() -> VolumeEra {
+		Self::volume_era(<frame_system::Pallet<T>>::block_number())
+	}
+
+	// Adds `volume` swapped by `who` in the pair `lpt` to the current volume era
+	fn record_volume(who: &T::AccountId, lpt: LpTokenId, volume: Balance) {
+		let era = Self::current_volume_era();
+		PairVolume::mutate(era, lpt, |total| *total = total.saturating_add(volume));
+		AccountVolume::<T>::mutate(era, who, |volumes| {
+			match volumes.iter_mut().find(|(pair, _)| *pair == lpt) {
+				Some((_, total)) => *total = total.saturating_add(volume),
+				None => volumes.push((lpt, volume)),
+			}
+		});
+	}
+
+	// Removes the volume of the era which falls out of the history once `era` starts
+	fn prune_volume(era: VolumeEra) -> Weight {
+		let expired = match era.checked_sub(T::VolumeHistoryDepth::get() + 1) {
+			Some(expired) => expired,
+			None => return 0,
+		};
+		let removed = [
+			AccountVolume::<T>::remove_prefix(expired, None),
+			PairVolume::remove_prefix(expired, None),
+		]
+		.iter()
+		.map(|result| match result {
+			KillStorageResult::AllRemoved(count) | KillStorageResult::SomeRemaining(count) =>
+				*count as Weight,
+		})
+		.sum();
+		T::DbWeight::get().writes(removed)
+	}
+
+	// Blocks in a fee bucket
+	fn fee_bucket_length() -> T::BlockNumber {
+		(T::BlocksPerDay::get() / FEE_BUCKETS_PER_DAY.into()).max(1u32.into())
+	}
+
+	/// Fee bucket of the block `now`
+	pub fn fee_bucket(now: T::BlockNumber) -> FeeBucket {
+		(now / Self::fee_bucket_length()).unique_saturated_into()
+	}
+
+	// Adds `fee` paid in the first token of the pair if `first`, else in the second one, to the
+	// current fee bucket of the pool `lpt`
+	fn record_fee(lpt: LpTokenId, first: bool, fee: Balance) {
+		let bucket = Self::fee_bucket(<frame_system::Pallet<T>>::block_number());
+		PoolFees::mutate(bucket, lpt, |fees| match first {
+			true => fees.0 = fees.0.saturating_add(fee),
+			false => fees.1 = fees.1.saturating_add(fee),
+		});
+	}
+
+	// Removes the fees of the bucket which falls out of the history once `bucket` starts
+	fn prune_fees(bucket: FeeBucket) -> Weight {
+		let expired = match bucket.checked_sub(FEE_HISTORY_DAYS * FEE_BUCKETS_PER_DAY) {
+			Some(expired) => expired,
+			None => return 0,
+		};
+		match PoolFees::remove_prefix(expired, None) {
+			KillStorageResult::AllRemoved(count) | KillStorageResult::SomeRemaining(count) =>
+				T::DbWeight::get().writes(count as Weight),
+		}
+	}
+
+	/// Swap fees paid into the pool of `lpt` over the last `buckets` fee buckets, the current one
+	/// included, in the tokens of the pair.
+	pub fn accrued_fees(lpt: LpTokenId, buckets: FeeBucket) -> (Balance, Balance) {
+		if buckets.is_zero() {
+			return (0, 0)
+		}
+		let current = Self::fee_bucket(<frame_system::Pallet<T>>::block_number());
+		let first = current.saturating_sub(buckets - 1);
+		(first..=current).fold((0, 0), |total: (Balance, Balance), bucket| {
+			let fees = Self::pool_fees(bucket, lpt);
+			(total.0.saturating_add(fees.0), total.1.saturating_add(fees.1))
+		})
+	}
+
+	/// Reserves, liquidity token supply and swap fees of the last day and week of the pool of
+	/// `lp_token`.
+	///
+	/// Fees of a pool removed and created again within a week are counted for the new pool. The
+	/// values are left to the runtime, which knows the prices.
+	pub fn pool_stats(lp_token: LpTokenId) -> Option<PoolStats> {
+		if !Rewards::contains_key(lp_token) {
+			return None
+		}
+		let (token0, token1) = Self::reward(lp_token);
+		let reserves = Self::reserves(lp_token);
+		let fees_24h = Self::accrued_fees(lp_token, FEE_BUCKETS_PER_DAY);
+		let fees_7d = Self::accrued_fees(lp_token, FEE_HISTORY_DAYS * FEE_BUCKETS_PER_DAY);
+		Some(PoolStats {
+			lp_token,
+			reserves: [(token0, reserves.0), (token1, reserves.1)],
+			lp_supply: T::Assets::total_issuance(lp_token.into()),
+			fees_24h: [fees_24h.0, fees_24h.1],
+			fees_7d: [fees_7d.0, fees_7d.1],
+			reserve_value: None,
+			fee_value_24h: None,
+			fee_value_7d: None,
+		})
+	}
+
+	/// Stats of every pool, ordered by liquidity token.
+	pub fn all_pool_stats() -> Vec<PoolStats> {
+		let mut lp_tokens: Vec<LpTokenId> = Rewards::iter_keys().collect();
+		lp_tokens.sort();
+		lp_tokens.into_iter().filter_map(Self::pool_stats).collect()
+	}
+
+	// Pool of the pair with its reserves ordered as (lptoken, reserve_in, reserve_out)
+	fn swap_reserves(
+		from: AssetId,
+		to: AssetId,
+	) -> Result<(LpTokenId, Balance, Balance), dispatch::DispatchError> {
+		// Find pair
+		let lpt = Self::pair((from, to)).ok_or(Error::<T>::InvalidPair)?;
+		let reserves = Self::reserves(lpt);
+		ensure!(
+			reserves.0 > Zero::zero() && reserves.1 > Zero::zero(),
+			Error::<T>::InsufficientLiquidity
+		);
+		Ok(match from > to {
+			true => (lpt, reserves.1, reserves.0),
+			false => (lpt, reserves.0, reserves.1),
+		})
+	}
+
+	/// Checks that the module account backs all recorded reserves.
+	#[cfg(any(feature = "try-runtime", test))]
+	pub fn assert_reserves_match_balances() -> Result<(), &'static str> {
+		match Self::reserve_discrepancies().is_empty() {
+			true => Ok(()),
+			false => Err("Market reserves exceed the module account balance"),
+		}
+	}
+
+	/// Time weighted average amount of `quote` paid for one `base` over at least `TwapPeriod`
+	/// blocks.
+	pub fn twap(base: AssetId, quote: AssetId) -> Option<FixedU128> {
+		Self::twap_over(base, quote, T::TwapPeriod::get())
+	}
+
+	/// Time weighted average amount of `quote` paid for one `base` over at least `window`
+	/// blocks, or the TWAP window of the pool if longer.
+	///
+	/// Averages from the latest price observation `window` blocks old, so that up to one more
+	/// `TwapPeriod` is covered. `None` for unknown pairs or without an observation that old.
+	pub fn twap_over(base: AssetId, quote: AssetId, window: T::BlockNumber) -> Option<FixedU128> {
+		let lpt = Self::pair((base, quote))?;
+		let now = <frame_system::Pallet<T>>::block_number();
+		let window = window.max(Self::pool_twap_window(lpt).unwrap_or_default()).max(One::one());
+		let start = Self::price_observations(lpt)
+			.into_iter()
+			.rev()
+			.find(|observation| now.saturating_sub(observation.2) >= window)?;
+		let elapsed = UniqueSaturatedInto::<u128>::unique_saturated_into(now - start.2);
+		let cumulative = Self::current_cumulative_prices(lpt);
+		// Prices of the pool are quoted in its other token, price0 is in token1
+		Some(match base < quote {
+			true => math::average_price(start.0, cumulative.0, elapsed),
+			false => math::average_price(start.1, cumulative.1, elapsed),
+		})
+	}
+
+	// Accumulates the prices of every pool at the start of a TWAP period, so that pools without
+	// swaps keep their observations
+	fn observe_prices() -> Weight {
+		let mut count: Weight = 0;
+		for lpt in Rewards::iter_keys() {
+			count += 1;
+			Self::update_cumulative_prices(lpt);
+		}
+		T::DbWeight::get().reads_writes(1 + 5 * count, 3 * count)
+	}
+
+	// Moves the accumulated prices at the start of the previous and of the current TWAP period of
+	// each pair into its price observations
+	fn migrate_to_price_observations() -> Weight {
+		let mut count: Weight = 0;
+		for (lpt, [previous, current]) in storage_key_iter::<
+			LpTokenId,
+			[(FixedU128, FixedU128, T::BlockNumber); 2],
+			Blake2_128Concat,
+		>(b"Assets", b"PriceCheckpoints")
+		{
+			count += 1;
+			let observations = match previous.2 == current.2 {
+				true => vec![current],
+				false => vec![previous, current],
+			};
+			PriceObservations::<T>::insert(lpt, observations);
+		}
+		remove_storage_prefix(b"Assets", b"PriceCheckpoints", &[]);
+		T::DbWeight::get().reads_writes(count + 1, count + 1)
+	}
+
+	// Accumulated prices of the pool including the blocks since its last update
+	fn current_cumulative_prices(lpt: LpTokenId) -> (FixedU128, FixedU128) {
+		let (cumulative0, cumulative1) = Self::last_cumulative_price(lpt);
+		let elapsed = <frame_system::Pallet<T>>::block_number() - Self::last_price_update(lpt);
+		let elapsed = UniqueSaturatedInto::<u128>::unique_saturated_into(elapsed);
+		let (reserve0, reserve1) = Self::reserves(lpt);
+		if elapsed.is_zero() || reserve0.is_zero() || reserve1.is_zero() {
+			return (cumulative0, cumulative1)
+		}
+		(
+			math::accumulate_price(
+				cumulative0,
+				FixedU128::saturating_from_rational(reserve1, reserve0),
+				elapsed,
+			),
+			math::accumulate_price(
+				cumulative1,
+				FixedU128::saturating_from_rational(reserve0, reserve1),
+				elapsed,
+			),
+		)
+	}
+
+	// Accumulates the prices at the reserves before they change and observes them once per TWAP
+	// period, dropping the oldest observation beyond `MaxTwapObservations`
+	fn update_cumulative_prices(lpt: LpTokenId) {
+		let now = <frame_system::Pallet<T>>::block_number();
+		let (cumulative0, cumulative1) = Self::current_cumulative_prices(lpt);
+		LastAccumulativePrice::insert(lpt, (cumulative0, cumulative1));
+		LastPriceUpdate::<T>::insert(lpt, now);
+
+		let period = |block: T::BlockNumber| block / T::TwapPeriod::get();
+		PriceObservations::<T>::mutate(lpt, |observations| {
+			if observations.last().map_or(true, |last| period(last.2) != period(now)) {
+				observations.push((cumulative0, cumulative1, now));
+			}
+			let excess = observations.len().saturating_sub(T::MaxTwapObservations::get() as usize);
+			observations.drain(..excess);
+		});
+	}
+
 	// Market methods
 	pub fn _set_reserves(
 		token0: AssetId,
 		token1: AssetId,
 		amount0: Balance,
 		amount1: Balance,
-		lptoken: AssetId,
+		lptoken: LpTokenId,
 	) {
+		Self::update_cumulative_prices(lptoken);
+		#[cfg(feature = "price-telemetry")]
+		Self::record_open_price(lptoken);
 		match token0 > token1 {
 			true => {
 				Reserves::insert(lptoken, (amount1, amount0));
@@ -354,12 +1935,39 @@ impl<T: Config> Module<T> {
 		}
 	}
 
-	fn _set_pair(token0: AssetId, token1: AssetId, lptoken: AssetId) {
+	// Spot price of token0 in token1 of `reserves`, zero while the pool is empty
+	#[cfg(feature = "price-telemetry")]
+	fn spot_price(reserves: (Balance, Balance)) -> FixedU128 {
+		FixedU128::checked_from_rational(reserves.1, reserves.0).unwrap_or_default()
+	}
+
+	// Keeps the spot price of the pool of `lpt` before its reserves first change in the block
+	#[cfg(feature = "price-telemetry")]
+	fn record_open_price(lpt: LpTokenId) {
+		if !BlockOpenPrices::contains_key(lpt) {
+			BlockOpenPrices::insert(lpt, Self::spot_price(Self::reserves(lpt)));
+		}
+	}
+
+	// Emits the open and close spot prices of the pools whose reserves changed in the block
+	#[cfg(feature = "price-telemetry")]
+	fn emit_block_prices() {
+		let mut prices: Vec<_> = BlockOpenPrices::drain()
+			.map(|(lpt, open)| (lpt, open, Self::spot_price(Self::reserves(lpt))))
+			.collect();
+		if prices.is_empty() {
+			return
+		}
+		prices.sort_by_key(|(lpt, _, _)| *lpt);
+		Self::deposit_event(RawEvent::BlockPrices(prices));
+	}
+
+	fn _set_pair(token0: AssetId, token1: AssetId, lptoken: LpTokenId) {
 		Pairs::insert((token0, token1), lptoken);
 		Pairs::insert((token1, token0), lptoken);
 	}
 
-	fn _set_rewards(token0: AssetId, token1: AssetId, lptoken: AssetId) {
+	fn _set_rewards(token0: AssetId, token1: AssetId, lptoken: LpTokenId) {
 		match token0 > token1 {
 			true => {
 				Rewards::insert(lptoken, (token1, token0));
@@ -370,54 +1978,51 @@ impl<T: Config> Module<T> {
 		}
 	}
 
-	pub fn to_u256(value: Balance) -> U256 {
-		U256::from(UniqueSaturatedInto::<u128>::unique_saturated_into(value))
-	}
-
 	pub fn _get_amount_out(
 		amount_in: Balance,
 		reserve_in: Balance,
 		reserve_out: Balance,
 	) -> Balance {
-		let amount_in_256 = Self::to_u256(amount_in);
-		let reserve_in_256 = Self::to_u256(reserve_in);
-		let reserve_out_256 = Self::to_u256(reserve_out);
-		let amount_in_with_fee =
-			amount_in_256.checked_mul(U256::from(997)).expect("Multiplication overflow");
-		let numerator = amount_in_with_fee
-			.checked_mul(reserve_out_256)
-			.expect("Multiplication overflow");
-		let denominator = reserve_in_256
-			.checked_mul(U256::from(1000))
-			.expect("Multiplication overflow")
-			.checked_add(amount_in_with_fee)
-			.expect("Overflow");
-		Balance::unique_saturated_from(
-			numerator.checked_div(denominator).expect("divided by zero").as_u128(),
-		)
+		math::get_amount_out(amount_in, reserve_in, reserve_out)
+	}
+}
+
+impl<T: Config> Twap<T::BlockNumber> for Module<T> {
+	fn twap(base: AssetId, quote: AssetId, window: T::BlockNumber) -> Option<FixedU128> {
+		Self::twap_over(base, quote, window)
+	}
+}
+
+impl<T: Config> FrozenBalance<AssetId, T::AccountId, Balance> for Module<T> {
+	fn frozen_balance(asset: AssetId, who: &T::AccountId) -> Option<Balance> {
+		ReservedAssets::<T>::try_get(asset, who).ok()
+	}
+
+	fn died(asset: AssetId, who: &T::AccountId) {
+		ReservedAssets::<T>::remove(asset, who);
+	}
+}
+
+impl<T: Config> ReservableAssets<T::AccountId> for Module<T> {
+	fn reserve(asset: AssetId, who: &T::AccountId, amount: Balance) -> dispatch::DispatchResult {
+		Self::reserve_asset(asset, who, amount)
+	}
+
+	fn unreserve(asset: AssetId, who: &T::AccountId, amount: Balance) {
+		Self::unreserve_asset(asset, who, amount)
+	}
+
+	fn reserved_balance(asset: AssetId, who: &T::AccountId) -> Balance {
+		Self::reserved_asset(asset, who)
+	}
+}
+
+impl<T: Config> AccountedFunds<T::AccountId> for Module<T> {
+	fn accounted(account: &T::AccountId, asset: AssetId) -> Balance {
+		if *account == Self::account_id() {
+			Self::reserve_of(asset)
+		} else {
+			0
+		}
 	}
-	// TODO: Reimplement TWAP so that checked calculation does not lose values
-	// fn _update(pair: &T::AssetId) -> dispatch::DispatchResult {
-	// let block_timestamp = <timestamp::Module<T>>::get() % T::Moment::from(2u32.pow(32));
-	// let time_elapsed = block_timestamp - Self::last_block_timestamp();
-	// let reserves = Self::reserves(pair);
-	// if time_elapsed > Zero::zero() && reserves.0 != Zero::zero() && reserves.1 != Zero::zero() {
-	// let reserve0 = FixedU128::saturating_from_integer(reserves.0.saturated_into());
-	// let reserve1 = FixedU128::saturating_from_integer(reserves.1.saturated_into());
-	// let price0_cumulative_last = reserve1.checked_div(&reserve0).unwrap()
-	// FixedU128::saturating_from_integer(time_elapsed.saturated_into());
-	// let price1_cumulative_last = reserve0.checked_div(&reserve1).unwrap()
-	// FixedU128::saturating_from_integer(time_elapsed.saturated_into());
-	// <LastAccumulativePrice<T>>::insert(
-	// &pair,
-	// (price0_cumulative_last.clone(), price1_cumulative_last.clone()),
-	// );
-	// <LastBlockTimestamp<T>>::put(block_timestamp);
-	// Self::deposit_event(RawEvent::SyncOracle(
-	// price0_cumulative_last,
-	// price1_cumulative_last,
-	// ));
-	// }
-	// Ok(())
-	// }
 }
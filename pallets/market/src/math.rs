@@ -1,54 +1,308 @@
+//! Pure AMM arithmetic used by the market module.
+//!
+//! Nothing in here touches storage, so the functions can be tested (and fuzzed) without a mock
+//! runtime.
+
 use primitives::Balance;
+use sp_core::U256;
+use sp_runtime::FixedU128;
+pub use standard_math::{absdiff, min, saturating_to_u128, sqrt};
 
-const ONE: Balance = 1;
-const TWO: Balance = 2;
-const THREE: Balance = 3;
-const ZERO: Balance = 0;
-
-pub fn sqrt(y: Balance) -> Balance {
-	if y > Balance::from(THREE) {
-		let mut z = y;
-		let mut x: Balance = y / Balance::from(TWO);
-		x += Balance::from(ONE);
-		while x < z {
-			z = x;
-			x = (y / x + x) / Balance::from(TWO);
-		}
-		z
-	} else if y != Balance::from(ZERO) {
-		let z = Balance::from(ONE);
-		z
-	} else {
-		y
+/// Fee taken on every swap, in basis points of the input amount.
+pub const SWAP_FEE: u128 = 30;
+/// Highest swap fee a pool parameter proposal can set, in basis points.
+pub const MAX_SWAP_FEE: u128 = 100;
+/// Basis points in one
+const BPS: u128 = 10_000;
+
+/// Output of swapping `amount_in` against the reserves, after the swap fee.
+pub fn get_amount_out(amount_in: Balance, reserve_in: Balance, reserve_out: Balance) -> Balance {
+	get_amount_out_with_fee(amount_in, reserve_in, reserve_out, SWAP_FEE)
+}
+
+/// Output of swapping `amount_in` against the reserves, after a swap fee of `fee` basis points.
+///
+/// Computed in `U256` so that the intermediate products can not overflow.
+pub fn get_amount_out_with_fee(
+	amount_in: Balance,
+	reserve_in: Balance,
+	reserve_out: Balance,
+	fee: u128,
+) -> Balance {
+	let amount_in_with_fee = U256::from(amount_in) * U256::from(BPS - fee.min(BPS));
+	let numerator = amount_in_with_fee * U256::from(reserve_out);
+	let denominator = U256::from(reserve_in) * U256::from(BPS) + amount_in_with_fee;
+	numerator.checked_div(denominator).expect("divided by zero").as_u128()
+}
+
+/// Part of `amount_in` kept by the pool as a swap fee of `fee` basis points, rounded down.
+pub fn swap_fee(amount_in: Balance, fee: u128) -> Balance {
+	(U256::from(amount_in) * U256::from(fee.min(BPS)) / U256::from(BPS)).as_u128()
+}
+
+/// Input needed to receive at least `amount_out` from the reserves, after a swap fee of `fee`
+/// basis points.
+///
+/// `None` if the reserves can not pay out `amount_out`.
+pub fn get_amount_in_with_fee(
+	amount_out: Balance,
+	reserve_in: Balance,
+	reserve_out: Balance,
+	fee: u128,
+) -> Option<Balance> {
+	if amount_out >= reserve_out {
+		return None
+	}
+	let numerator = U256::from(reserve_in) * U256::from(amount_out) * U256::from(BPS);
+	let denominator =
+		U256::from(reserve_out - amount_out) * U256::from(BPS - fee.min(BPS - 1));
+	let amount_in = numerator / denominator + U256::one();
+	(amount_in <= U256::from(Balance::MAX)).then(|| amount_in.as_u128())
+}
+
+/// Uniform price clearing a batch of swaps which sells `sold.0` of the first token and `sold.1` of
+/// the second one against `reserves`, as the (numerator, denominator) of the amount of the second
+/// token paid for one of the first.
+///
+/// Opposing orders are matched at the price the net flow of the batch swaps at against the pool,
+/// after a swap fee of `fee` basis points. With `g = 1 - fee`, a batch selling the first token on
+/// net clears at `g (r1 + s1) / (r0 + g s0)`, and one selling the second token at
+/// `(r1 + g s1) / (g (r0 + s0))`.
+pub fn batch_clearing_price(
+	sold: (Balance, Balance),
+	reserves: (Balance, Balance),
+	fee: u128,
+) -> (U256, U256) {
+	let bps = U256::from(BPS);
+	let g = U256::from(BPS - fee.min(BPS - 1));
+	let (sold0, sold1) = (U256::from(sold.0), U256::from(sold.1));
+	let (reserve0, reserve1) = (U256::from(reserves.0), U256::from(reserves.1));
+	let numerator = g * (reserve1 + sold1);
+	let denominator = bps * reserve0 + g * sold0;
+	// The sellers of the first token receive at least what the sellers of the second one pay in
+	if sold0 * numerator >= sold1 * denominator {
+		return (numerator, denominator)
 	}
+	(bps * reserve1 + g * sold1, g * (reserve0 + sold0))
 }
 
-pub fn min(x: Balance, y: Balance) -> Balance {
-	let z = match x < y {
-		true => x,
-		_ => y,
+/// Amount paid out for selling `amount_in` of the first token if `first`, else of the second one,
+/// at a `price` of `batch_clearing_price`, rounded down.
+pub fn batch_amount_out(amount_in: Balance, price: (U256, U256), first: bool) -> Balance {
+	let (numerator, denominator) = match first {
+		true => price,
+		false => (price.1, price.0),
 	};
-	z
+	saturating_to_u128(U256::from(amount_in) * numerator / denominator)
 }
 
-pub fn absdiff(x: Balance, y: Balance) -> Balance {
-	let z = match x < y {
-		true => y - x,
-		_ => x - y,
+/// Liquidity tokens minted for depositing `amounts` into a pool with `reserves` and
+/// `total_supply` liquidity tokens outstanding.
+pub fn liquidity_minted(
+	amounts: (Balance, Balance),
+	reserves: (Balance, Balance),
+	total_supply: Balance,
+) -> Balance {
+	let left = amounts
+		.0
+		.checked_mul(total_supply)
+		.expect("Multiplicaiton overflow")
+		.checked_div(reserves.0)
+		.expect("Divide by zero error");
+	let right = amounts
+		.1
+		.checked_mul(total_supply)
+		.expect("Multiplicaiton overflow")
+		.checked_div(reserves.1)
+		.expect("Divide by zero error");
+	min(left, right)
+}
+
+/// Share of the reserves paid out for burning `amount` of `total_supply` liquidity tokens.
+///
+/// Computed in `U256` so that the intermediate products can not overflow. `None` without
+/// liquidity tokens or if `amount` exceeds `total_supply` by so much that a share overflows.
+pub fn liquidity_burned(
+	amount: Balance,
+	reserves: (Balance, Balance),
+	total_supply: Balance,
+) -> Option<(Balance, Balance)> {
+	if total_supply == 0 {
+		return None
+	}
+	let share = |reserve: Balance| -> Option<Balance> {
+		let share = U256::from(amount) * U256::from(reserve) / U256::from(total_supply);
+		(share <= U256::from(Balance::MAX)).then(|| share.as_u128())
 	};
-	z
+	Some((share(reserves.0)?, share(reserves.1)?))
+}
+
+/// Adds `price` held for `elapsed` blocks to a cumulative price.
+///
+/// Only differences of cumulative prices are meaningful, so they wrap around on overflow.
+pub fn accumulate_price(cumulative: FixedU128, price: FixedU128, elapsed: u128) -> FixedU128 {
+	FixedU128::from_inner(
+		cumulative.into_inner().wrapping_add(price.into_inner().wrapping_mul(elapsed)),
+	)
+}
+
+/// Average price between two cumulative prices taken `elapsed` blocks apart.
+pub fn average_price(from: FixedU128, to: FixedU128, elapsed: u128) -> FixedU128 {
+	FixedU128::from_inner(to.into_inner().wrapping_sub(from.into_inner()) / elapsed)
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use proptest::prelude::*;
+
+	#[test]
+	fn get_amount_out_works() {
+		// 1_000 in against 10_000/10_000 pays 0.3% fee and the price impact
+		assert_eq!(get_amount_out(1_000, 10_000, 10_000), 906);
+	}
+
+	#[test]
+	fn get_amount_out_with_fee_works() {
+		assert_eq!(get_amount_out_with_fee(1_000, 10_000, 10_000, SWAP_FEE), 906);
+		// A 20 bps discount leaves a 0.1% fee, without fee only the price impact is paid
+		assert_eq!(get_amount_out_with_fee(1_000, 10_000, 10_000, 10), 908);
+		assert_eq!(get_amount_out_with_fee(1_000, 10_000, 10_000, 0), 909);
+	}
+
+	#[test]
+	fn swap_fee_works() {
+		assert_eq!(swap_fee(1_000, SWAP_FEE), 3);
+		assert_eq!(swap_fee(999, SWAP_FEE), 2);
+		assert_eq!(swap_fee(Balance::MAX, BPS), Balance::MAX);
+	}
+
 	#[test]
-	fn sqrt_works() {
-		assert_eq!(2_u128, sqrt(4_u128));
+	fn get_amount_in_with_fee_works() {
+		assert_eq!(get_amount_in_with_fee(906, 10_000, 10_000, SWAP_FEE), Some(1_000));
+		assert_eq!(get_amount_in_with_fee(909, 10_000, 10_000, 0), Some(1_000));
+		assert_eq!(get_amount_in_with_fee(10_000, 10_000, 10_000, SWAP_FEE), None);
 	}
 
 	#[test]
-	fn min_works() {
-		assert_eq!(1_u128, min(1_u128, 3_u128));
+	fn batch_clearing_works() {
+		// Selling 500 of the first token and 100 of the second one against 5_000/500 nets out to
+		// selling the second one
+		let price = batch_clearing_price((500, 100), (5_000, 500), SWAP_FEE);
+		assert_eq!(price, (U256::from(5_997_000), U256::from(54_835_000)));
+		assert_eq!(batch_amount_out(500, price, true), 54);
+		assert_eq!(batch_amount_out(100, price, false), 914);
+
+		// A batch without opposing orders clears at the price of a single swap
+		let price = batch_clearing_price((0, 100), (5_000, 500), SWAP_FEE);
+		assert_eq!(batch_amount_out(100, price, false), get_amount_out(100, 500, 5_000));
+	}
+
+	#[test]
+	fn liquidity_burned_works() {
+		assert_eq!(liquidity_burned(100, (5_000, 500), 1_000), Some((500, 50)));
+		// Products beyond u128 do not overflow, a share beyond it does
+		assert_eq!(
+			liquidity_burned(Balance::MAX, (Balance::MAX, 1), Balance::MAX),
+			Some((Balance::MAX, 1))
+		);
+		assert_eq!(liquidity_burned(2, (Balance::MAX, 1), 1), None);
+		assert_eq!(liquidity_burned(100, (5_000, 500), 0), None);
+	}
+
+	// Swaps are computed in U256, so reserves and amounts up to 2^64 are fine.
+	const MAX: Balance = u64::MAX as Balance;
+	// Minting liquidity is plain u128, and a pool with tiny reserves mints a lot of liquidity.
+	const MAX_LIQUIDITY: Balance = u32::MAX as Balance;
+
+	proptest! {
+		#[test]
+		fn swap_never_decreases_k(
+			reserve_in in 1..MAX,
+			reserve_out in 1..MAX,
+			amount_in in 1..MAX,
+		) {
+			let amount_out = get_amount_out(amount_in, reserve_in, reserve_out);
+			prop_assert!(amount_out < reserve_out);
+
+			let k_before = U256::from(reserve_in) * U256::from(reserve_out);
+			let k_after = U256::from(reserve_in + amount_in) * U256::from(reserve_out - amount_out);
+			prop_assert!(k_after >= k_before);
+
+			// Net of fees the invariant still holds, i.e. the fee is all that grows K
+			let in_net_of_fee = U256::from(reserve_in) * BPS + U256::from(amount_in) * (BPS - SWAP_FEE);
+			prop_assert!(in_net_of_fee * U256::from(reserve_out - amount_out) >= k_before * BPS);
+		}
+
+		#[test]
+		fn batch_never_decreases_k(
+			reserve0 in 1..MAX,
+			reserve1 in 1..MAX,
+			sold0 in 0..MAX,
+			sold1 in 0..MAX,
+		) {
+			let price = batch_clearing_price((sold0, sold1), (reserve0, reserve1), SWAP_FEE);
+			let paid0 = batch_amount_out(sold1, price, false);
+			let paid1 = batch_amount_out(sold0, price, true);
+			prop_assert!(paid0 < reserve0 + sold0);
+			prop_assert!(paid1 < reserve1 + sold1);
+
+			let k_before = U256::from(reserve0) * U256::from(reserve1);
+			let k_after = U256::from(reserve0 + sold0 - paid0) * U256::from(reserve1 + sold1 - paid1);
+			prop_assert!(k_after >= k_before);
+		}
+
+		#[test]
+		fn amount_out_is_monotonic(
+			reserve_in in 1..MAX,
+			reserve_out in 1..MAX,
+			amount_in in 0..MAX,
+			extra in 0..MAX,
+		) {
+			prop_assert!(
+				get_amount_out(amount_in, reserve_in, reserve_out) <=
+					get_amount_out(amount_in + extra, reserve_in, reserve_out)
+			);
+		}
+
+		#[test]
+		fn amount_in_buys_amount_out(
+			reserve_in in 1..MAX,
+			reserve_out in 1..MAX,
+			amount_out in 0..MAX,
+		) {
+			let amount_in = get_amount_in_with_fee(amount_out, reserve_in, reserve_out, SWAP_FEE);
+			if let Some(amount_in) = amount_in {
+				prop_assert!(get_amount_out(amount_in, reserve_in, reserve_out) >= amount_out);
+			}
+		}
+
+		#[test]
+		fn mint_then_burn_creates_no_value(
+			reserve0 in 1..MAX_LIQUIDITY,
+			reserve1 in 1..MAX_LIQUIDITY,
+			total_supply in 1..MAX_LIQUIDITY,
+			amount0 in 0..MAX_LIQUIDITY,
+			amount1 in 0..MAX_LIQUIDITY,
+		) {
+			let minted = liquidity_minted((amount0, amount1), (reserve0, reserve1), total_supply);
+			let (out0, out1) =
+				liquidity_burned(minted, (reserve0 + amount0, reserve1 + amount1), total_supply + minted)
+					.unwrap();
+			prop_assert!(out0 <= amount0);
+			prop_assert!(out1 <= amount1);
+		}
+
+		#[test]
+		fn average_of_constant_price(
+			cumulative in any::<u128>(),
+			price in 0..u128::MAX >> 32,
+			elapsed in 1..u32::MAX as u128,
+		) {
+			let cumulative = FixedU128::from_inner(cumulative);
+			let price = FixedU128::from_inner(price);
+			let later = accumulate_price(cumulative, price, elapsed);
+			prop_assert_eq!(average_price(cumulative, later, elapsed), price);
+		}
 	}
 }
@@ -0,0 +1,235 @@
+// Ensure we're `no_std` when compiling for Wasm.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Merkle Distributor Pallet
+//!
+//! Airdrops and other one-off distributions of an asset to many accounts, computed off chain.
+//!
+//! Governance registers a distribution of an asset with the Merkle root of all claims and an
+//! expiry with `create_distribution`. Every leaf is the `primitives::merkle` leaf of
+//! `(index, account, amount)`. The distribution is paid out of its own account,
+//! `distribution_account`, which has to be funded separately. Until the expiry the accounts
+//! `claim` their amount with the Merkle proof of their leaf. From the expiry on anyone can `sweep`
+//! the unclaimed funds to the treasury, which removes the distribution.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{
+	traits::tokens::fungibles::{Inspect, Transfer},
+	PalletId,
+};
+use primitives::{
+	merkle::{self, MerkleHash},
+	AssetId, Balance,
+};
+use scale_info::TypeInfo;
+use sp_runtime::{traits::AccountIdConversion, RuntimeDebug};
+use sp_std::vec::Vec;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+pub use pallet::*;
+
+/// Identifier of a distribution
+pub type DistributionId = u32;
+
+/// Position of a claim in the tree of a distribution
+pub type LeafIndex = u32;
+
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct Distribution<BlockNumber> {
+	pub asset: AssetId,
+	/// Merkle root of all claims
+	pub root: MerkleHash,
+	/// Block from which the distribution can no longer be claimed and can be swept
+	pub expiry: BlockNumber,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		type Assets: Inspect<Self::AccountId, AssetId = AssetId, Balance = Balance>
+			+ Transfer<Self::AccountId, AssetId = AssetId, Balance = Balance>;
+		/// Parent account of the distribution accounts
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+		/// Account unclaimed funds are swept to
+		type TreasuryAccount: Get<Self::AccountId>;
+		/// Maximum number of hashes in a Merkle proof
+		#[pallet::constant]
+		type MaxProofLength: Get<u32>;
+		/// Origin allowed to create distributions
+		type GovernanceOrigin: EnsureOrigin<Self::Origin>;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Distribution is created (distribution_id, asset, root, expiry)
+		DistributionCreated(DistributionId, AssetId, MerkleHash, T::BlockNumber),
+		/// Amount is claimed (distribution_id, account, amount)
+		Claimed(DistributionId, T::AccountId, Balance),
+		/// Expired distribution is removed and the unclaimed funds swept to the treasury
+		/// (distribution_id, amount)
+		Swept(DistributionId, Balance),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Distribution does not exist
+		DistributionNotFound,
+		/// Expiry must be in the future
+		InvalidExpiry,
+		/// Distribution expired and can no longer be claimed
+		Expired,
+		/// Distribution has not expired yet
+		NotExpired,
+		/// Proof does not lead to the Merkle root
+		InvalidProof,
+		/// Proof has more than `MaxProofLength` hashes
+		ProofTooLong,
+		/// Amount has already been claimed
+		AlreadyClaimed,
+	}
+
+	#[pallet::storage]
+	#[pallet::getter(fn distribution)]
+	pub type Distributions<T: Config> =
+		StorageMap<_, Twox64Concat, DistributionId, Distribution<T::BlockNumber>>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn next_distribution_id)]
+	pub type NextDistributionId<T> = StorageValue<_, DistributionId, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn claimed)]
+	/// Leaves of a distribution which have been claimed
+	pub type Claimed<T> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		DistributionId,
+		Twox64Concat,
+		LeafIndex,
+		bool,
+		ValueQuery,
+	>;
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Registers a distribution of `asset` to the leaves of `root`, claimable until `expiry`.
+		///
+		/// The distribution is paid out of `distribution_account`, which has to be funded with
+		/// the total of all claims.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 2))]
+		pub fn create_distribution(
+			origin: OriginFor<T>,
+			asset: AssetId,
+			root: MerkleHash,
+			expiry: T::BlockNumber,
+		) -> DispatchResult {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			ensure!(expiry > frame_system::Pallet::<T>::block_number(), Error::<T>::InvalidExpiry);
+
+			let distribution_id = NextDistributionId::<T>::mutate(|id| {
+				let distribution_id = *id;
+				*id += 1;
+				distribution_id
+			});
+			Distributions::<T>::insert(distribution_id, Distribution { asset, root, expiry });
+
+			Self::deposit_event(Event::DistributionCreated(distribution_id, asset, root, expiry));
+			Ok(())
+		}
+
+		/// Claims `amount` of the leaf at `index` of the distribution with its Merkle `proof`.
+		///
+		/// # <weight>
+		/// - O(P) hashes where P is the proof length, bounded by `MaxProofLength`
+		/// # </weight>
+		#[pallet::weight(T::DbWeight::get().reads_writes(3, 3))]
+		pub fn claim(
+			origin: OriginFor<T>,
+			distribution_id: DistributionId,
+			index: LeafIndex,
+			amount: Balance,
+			proof: Vec<MerkleHash>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(proof.len() as u32 <= T::MaxProofLength::get(), Error::<T>::ProofTooLong);
+			let distribution =
+				Self::distribution(distribution_id).ok_or(Error::<T>::DistributionNotFound)?;
+			ensure!(
+				distribution.expiry > frame_system::Pallet::<T>::block_number(),
+				Error::<T>::Expired
+			);
+			ensure!(!Self::claimed(distribution_id, index), Error::<T>::AlreadyClaimed);
+			let leaf = merkle::leaf(&(index, &who, amount));
+			ensure!(
+				merkle::verify_proof(distribution.root, leaf, &proof),
+				Error::<T>::InvalidProof
+			);
+
+			let account = Self::distribution_account(distribution_id);
+			T::Assets::transfer(distribution.asset, &account, &who, amount, false)?;
+			Claimed::<T>::insert(distribution_id, index, true);
+
+			Self::deposit_event(Event::Claimed(distribution_id, who, amount));
+			Ok(())
+		}
+
+		/// Sweeps the unclaimed funds of an expired distribution to the treasury and removes it.
+		///
+		/// Can be called by anyone.
+		///
+		/// # <weight>
+		/// - O(C) removal where C is the number of claimed leaves
+		/// # </weight>
+		#[pallet::weight(T::DbWeight::get().reads_writes(3, 4))]
+		pub fn sweep(origin: OriginFor<T>, distribution_id: DistributionId) -> DispatchResult {
+			ensure_signed(origin)?;
+			let distribution =
+				Self::distribution(distribution_id).ok_or(Error::<T>::DistributionNotFound)?;
+			ensure!(
+				distribution.expiry <= frame_system::Pallet::<T>::block_number(),
+				Error::<T>::NotExpired
+			);
+
+			let account = Self::distribution_account(distribution_id);
+			let unclaimed = T::Assets::balance(distribution.asset, &account);
+			if unclaimed > 0 {
+				T::Assets::transfer(
+					distribution.asset,
+					&account,
+					&T::TreasuryAccount::get(),
+					unclaimed,
+					false,
+				)?;
+			}
+			Distributions::<T>::remove(distribution_id);
+			Claimed::<T>::remove_prefix(distribution_id, None);
+
+			Self::deposit_event(Event::Swept(distribution_id, unclaimed));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Account the distribution is paid out of
+	pub fn distribution_account(distribution_id: DistributionId) -> T::AccountId {
+		T::PalletId::get().into_sub_account(distribution_id)
+	}
+}
@@ -0,0 +1,95 @@
+use crate::{mock::*, Distribution, Error, Event as DistributorEvent};
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::fungibles::{Inspect, Transfer},
+};
+use primitives::merkle::{self, MerkleHash};
+
+fn leaves() -> [MerkleHash; 2] {
+	[merkle::leaf(&(0u32, BOB, 300u128)), merkle::leaf(&(1u32, CHARLIE, 200u128))]
+}
+
+fn root() -> MerkleHash {
+	let [bob, charlie] = leaves();
+	merkle::hash_pair(bob, charlie)
+}
+
+/// Creates distribution 0 of STND until block 11 and funds it with 1_000
+fn create_distribution() {
+	assert_ok!(MerkleDistributor::create_distribution(Origin::root(), STND, root(), 11));
+	let account = MerkleDistributor::distribution_account(0);
+	assert_ok!(<Assets as Transfer<_>>::transfer(STND, &ALICE, &account, 1_000, false));
+}
+
+#[test]
+fn create_distribution_requires_governance() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			MerkleDistributor::create_distribution(Origin::signed(ALICE), STND, root(), 11),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			MerkleDistributor::create_distribution(Origin::root(), STND, root(), 1),
+			Error::<Test>::InvalidExpiry
+		);
+
+		create_distribution();
+		assert_eq!(
+			MerkleDistributor::distribution(0),
+			Some(Distribution { asset: STND, root: root(), expiry: 11 })
+		);
+		assert_eq!(MerkleDistributor::next_distribution_id(), 1);
+	})
+}
+
+#[test]
+fn claim_pays_leaf_once() {
+	new_test_ext().execute_with(|| {
+		let [bob, charlie] = leaves();
+		create_distribution();
+		assert_noop!(
+			MerkleDistributor::claim(Origin::signed(BOB), 1, 0, 300, vec![charlie]),
+			Error::<Test>::DistributionNotFound
+		);
+		assert_noop!(
+			MerkleDistributor::claim(Origin::signed(BOB), 0, 0, 3_000, vec![charlie]),
+			Error::<Test>::InvalidProof
+		);
+		assert_noop!(
+			MerkleDistributor::claim(Origin::signed(BOB), 0, 0, 300, vec![charlie; 5]),
+			Error::<Test>::ProofTooLong
+		);
+
+		assert_ok!(MerkleDistributor::claim(Origin::signed(BOB), 0, 0, 300, vec![charlie]));
+		assert_eq!(Assets::balance(STND, &BOB), 300);
+		assert_eq!(last_event(), Event::MerkleDistributor(DistributorEvent::Claimed(0, BOB, 300)));
+		assert_noop!(
+			MerkleDistributor::claim(Origin::signed(BOB), 0, 0, 300, vec![charlie]),
+			Error::<Test>::AlreadyClaimed
+		);
+
+		System::set_block_number(11);
+		assert_noop!(
+			MerkleDistributor::claim(Origin::signed(CHARLIE), 0, 1, 200, vec![bob]),
+			Error::<Test>::Expired
+		);
+	})
+}
+
+#[test]
+fn sweep_moves_unclaimed_funds_to_treasury() {
+	new_test_ext().execute_with(|| {
+		let [_, charlie] = leaves();
+		create_distribution();
+		assert_ok!(MerkleDistributor::claim(Origin::signed(BOB), 0, 0, 300, vec![charlie]));
+		assert_noop!(MerkleDistributor::sweep(Origin::signed(ALICE), 0), Error::<Test>::NotExpired);
+
+		System::set_block_number(11);
+		assert_ok!(MerkleDistributor::sweep(Origin::signed(ALICE), 0));
+		assert_eq!(Assets::balance(STND, &TREASURY), 700);
+		assert_eq!(Assets::balance(STND, &MerkleDistributor::distribution_account(0)), 0);
+		assert_eq!(MerkleDistributor::distribution(0), None);
+		assert!(!MerkleDistributor::claimed(0, 0));
+		assert_eq!(last_event(), Event::MerkleDistributor(DistributorEvent::Swept(0, 700)));
+	})
+}
@@ -0,0 +1,77 @@
+//! RPC methods exposing the oracle provider set and slash history, so that monitoring does not
+//! have to decode raw storage.
+
+use std::sync::Arc;
+
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use primitives::{EraIndex, OracleApi as OracleRuntimeApi, SocketIndex};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+#[rpc]
+pub trait OracleApi<BlockHash, AccountId> {
+	/// Registered providers
+	#[rpc(name = "oracle_providers")]
+	fn providers(&self, at: Option<BlockHash>) -> Result<Vec<AccountId>>;
+
+	/// Providers assigned to the reporting sockets as `(socket, provider)`
+	#[rpc(name = "oracle_slotAssignments")]
+	fn slot_assignments(&self, at: Option<BlockHash>) -> Result<Vec<(SocketIndex, AccountId)>>;
+
+	/// Providers slashed in `era`
+	#[rpc(name = "oracle_slashes")]
+	fn slashes(&self, era: EraIndex, at: Option<BlockHash>) -> Result<Vec<AccountId>>;
+}
+
+/// Oracle RPC methods backed by the `OracleApi` of the runtime.
+pub struct Oracle<C, B> {
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> Oracle<C, B> {
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+fn runtime_error(err: impl std::fmt::Debug) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(1),
+		message: "Unable to query the oracle.".into(),
+		data: Some(format!("{:?}", err).into()),
+	}
+}
+
+impl<C, Block, AccountId> OracleApi<<Block as BlockT>::Hash, AccountId> for Oracle<C, Block>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: OracleRuntimeApi<Block, AccountId>,
+	AccountId: Codec,
+{
+	fn providers(&self, at: Option<<Block as BlockT>::Hash>) -> Result<Vec<AccountId>> {
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		self.client.runtime_api().providers(&at).map_err(runtime_error)
+	}
+
+	fn slot_assignments(
+		&self,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<(SocketIndex, AccountId)>> {
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		self.client.runtime_api().slot_assignments(&at).map_err(runtime_error)
+	}
+
+	fn slashes(
+		&self,
+		era: EraIndex,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<AccountId>> {
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		self.client.runtime_api().slashes(&at, era).map_err(runtime_error)
+	}
+}
@@ -1,11 +1,37 @@
 // Ensure we're `no_std` when compiling for Wasm.
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use frame_support::{decl_error, decl_event, decl_module, decl_storage, ensure};
+use codec::{Decode, Encode};
+use frame_support::{
+	decl_error, decl_event, decl_module, decl_storage, ensure,
+	storage::migration::{remove_storage_prefix, storage_key_iter},
+	traits::{
+		schedule::{DispatchTime, Named as ScheduleNamed, HARD_DEADLINE},
+		BalanceStatus, Currency, ExistenceRequirement, Get, Imbalance, IsSubType,
+		ReservableCurrency, StorageVersion, ValidatorSetWithIdentification,
+	},
+	weights::{Pays, Weight},
+	Blake2_128Concat,
+};
 use frame_system::{ensure_root, ensure_signed};
-use primitives::{AssetId, Balance, EraIndex, SocketIndex};
-use sp_runtime::{DispatchError, DispatchResult, Percent};
-use sp_std::prelude::*;
+use primitives::{
+	constants::price::PRICE_DECIMALS,
+	traits::{EraFinder, OnPriceResponse, OnRoundClosed, Twap},
+	AssetId, Balance, EraIndex, HeartbeatSession, RequestId, RoundIndex, SocketIndex,
+};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{Convert, DispatchInfoOf, Saturating, SignedExtension, UniqueSaturatedInto, Zero},
+	transaction_validity::{
+		InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+	},
+	DispatchError, DispatchResult, FixedPointNumber, FixedU128, Perbill, Percent, RuntimeDebug,
+};
+use sp_staking::{
+	offence::{Kind, Offence, ReportOffence},
+	SessionIndex,
+};
+use sp_std::{marker::PhantomData, prelude::*};
 mod math;
 pub mod weights;
 pub use weights::WeightInfo;
@@ -21,6 +47,314 @@ pub trait Config: frame_system::Config {
 	type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
 
 	type WeightInfo: WeightInfo;
+
+	/// Number of blocks after which the reports of an asset are stale
+	type StalePeriod: Get<Self::BlockNumber>;
+
+	/// Market TWAP used as fallback price for stale assets
+	type Twap: Twap<Self::BlockNumber>;
+
+	/// Minimum number of blocks averaged by the TWAP fallback
+	type TwapWindow: Get<Self::BlockNumber>;
+
+	/// Asset the TWAP fallback is quoted in, converted with its own oracle price
+	type TwapQuoteAsset: Get<AssetId>;
+
+	/// Number of blocks in a reporting round, each provider reports an asset once per round
+	type RoundLength: Get<Self::BlockNumber>;
+
+	/// Number of reporting rounds in an era
+	type RoundsPerEra: Get<RoundIndex>;
+
+	/// Share of the rounds of an era a provider may miss before it is rotated out of its socket
+	type MaxMissedRounds: Get<Percent>;
+
+	/// Currency the fees of price requests are paid in
+	type Currency: ReservableCurrency<Self::AccountId, Balance = Balance>;
+
+	/// Minimum fee of a price request
+	type MinRequestFee: Get<Balance>;
+
+	/// Maximum number of blocks until the deadline of a price request
+	type MaxRequestPeriod: Get<Self::BlockNumber>;
+
+	/// Maximum length of the symbol of a price request
+	type MaxSymbolLength: Get<u32>;
+
+	/// Number of provider answers at which a price request is aggregated
+	type RequestQuorum: Get<u32>;
+
+	/// Receives the aggregated price or the timeout of each price request
+	type OnPriceResponse: OnPriceResponse<Self::AccountId>;
+
+	/// Receives the final median price of each asset reported in a round once it closes
+	type OnRoundClosed: OnRoundClosed;
+
+	/// Maximum number of fee-less reports of a provider per round
+	type MaxReportsPerRound: Get<u32>;
+
+	/// Eras slashes are recorded in and provider rewards are paid for
+	type EraFinder: EraFinder;
+
+	/// Reward shared by the providers holding a socket when an era ends, except the ones
+	/// slashed in it
+	type EraReward: Get<Balance>;
+
+	/// Account the provider rewards are paid from
+	type RewardPot: Get<Self::AccountId>;
+
+	/// Number of blocks in a heartbeat session, each provider sends a heartbeat or a report at
+	/// least once per session
+	type HeartbeatPeriod: Get<Self::BlockNumber>;
+
+	/// Amount slashed from a provider for each session without a heartbeat, paid to `RewardPot`
+	type HeartbeatSlash: Get<Balance>;
+
+	/// Number of sessions in a row without a heartbeat after which a provider loses its socket
+	type MaxMissedHeartbeats: Get<u32>;
+
+	/// Validators the providers mirror in validator mode, `()` for providers registered by
+	/// governance
+	type ProviderValidators: ProviderValidators<Self::AccountId>;
+
+	/// The runtime call that scheduled provider count changes are dispatched as.
+	type Proposal: From<Call<Self>>;
+
+	/// The caller origin, overarching type of all pallets origins.
+	type PalletsOrigin: From<frame_system::RawOrigin<Self::AccountId>>;
+
+	/// The scheduler used to enact provider count changes after a public delay.
+	type Scheduler: ScheduleNamed<Self::BlockNumber, Self::Proposal, Self::PalletsOrigin>;
+
+	/// Minimum number of blocks between scheduling a provider count change and its enactment.
+	type MinimumEnactmentDelay: Get<Self::BlockNumber>;
+}
+
+/// Reporting performance of a provider.
+#[derive(Clone, Copy, Default, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct ProviderMetrics {
+	/// Number of accepted reports
+	pub submissions: u32,
+	/// Number of rounds closed without any report of the provider
+	pub missed_rounds: u32,
+	/// Last round the provider reported in
+	pub last_round: Option<RoundIndex>,
+	/// Sum of the deviations of the reports from the final median of their round, in parts
+	/// per billion
+	pub total_deviation: u64,
+	/// Number of reports included in `total_deviation`
+	pub deviation_samples: u32,
+}
+
+impl ProviderMetrics {
+	/// Average deviation of the reports from the final median of their round.
+	pub fn average_deviation(&self) -> Perbill {
+		match self.deviation_samples {
+			0 => Perbill::zero(),
+			n => Perbill::from_parts((self.total_deviation / n as u64) as u32),
+		}
+	}
+}
+
+/// Scheduler task name of provider count changes
+const ORACLE_SCHEDULE_ID: &[u8] = b"stnd/orc";
+
+/// Target of the logs of the pallet, enabled on a node with `--log standard::oracle=debug`
+const LOG_TARGET: &str = "standard::oracle";
+
+// Logs on `LOG_TARGET` in native execution only, so that the runtime blob doesn't carry the
+// formatting code
+macro_rules! log {
+	($level:tt, $pattern:expr $(, $values:expr)* $(,)?) => {
+		#[cfg(feature = "std")]
+		log::$level!(target: crate::LOG_TARGET, $pattern $(, $values)*)
+	};
+}
+
+/// Inner value of a `FixedU128` price per unit of a reported price
+const REPORTED_PRICE_SCALE: u128 = FixedU128::DIV / 10u128.pow(PRICE_DECIMALS);
+
+/// Price of a report, whose integer price has `PRICE_DECIMALS` decimals.
+pub fn price_from_reported(price: Balance) -> FixedU128 {
+	FixedU128::from_inner(price.saturating_mul(REPORTED_PRICE_SCALE))
+}
+
+/// Price of an asset observed by a provider in a reporting round.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct PriceReport {
+	pub asset: AssetId,
+	/// Price in USD with `PRICE_DECIMALS` decimals
+	pub price: Balance,
+	/// Round the price is reported for, must be the current round
+	pub round: RoundIndex,
+	/// Unix time in milliseconds at which the provider observed the price
+	pub timestamp: u64,
+}
+
+/// Report payload submitted by providers, versioned so that the format can change without
+/// breaking the providers still on an older version.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub enum VersionedPriceReport {
+	#[codec(index = 1)]
+	V1(PriceReport),
+}
+
+impl From<VersionedPriceReport> for PriceReport {
+	fn from(report: VersionedPriceReport) -> Self {
+		match report {
+			VersionedPriceReport::V1(report) => report,
+		}
+	}
+}
+
+/// Request for the price of an arbitrary symbol, answered by the providers until its deadline.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct PriceRequest<AccountId, BlockNumber> {
+	pub requester: AccountId,
+	/// Symbol the price is requested for, e.g. `BTC/USD`
+	pub symbol: Vec<u8>,
+	/// Reserved from the requester and shared by the answering providers
+	pub fee: Balance,
+	/// Block at which the request times out
+	pub deadline: BlockNumber,
+	/// Prices answered by the providers so far
+	pub answers: Vec<(AccountId, Balance)>,
+}
+
+/// Where a price returned by `price_with_source` comes from.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub enum PriceSource {
+	/// Median of the provider reports
+	Oracle,
+	/// Market TWAP converted with the oracle price of `TwapQuoteAsset`
+	Twap,
+	/// Median of provider reports older than `StalePeriod`, without a fallback price
+	StaleOracle,
+}
+
+/// Misbehavior of a provider reported as an offence in validator mode.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, Ord, PartialOrd, RuntimeDebug, TypeInfo)]
+pub enum OracleOffenceKind {
+	/// Reported a price found to be an outlier by `slash`
+	Outlier,
+	/// Sent no heartbeat or report in a heartbeat session
+	Unresponsive,
+}
+
+/// Active validators which are also the oracle providers, each holding the socket of its
+/// position in the validator set.
+///
+/// The provider set follows the validators from the first block of each session, and the
+/// misbehavior of the providers is reported as offences against them.
+pub trait ProviderValidators<AccountId> {
+	/// Session of the active validators, `None` unless the providers mirror the validators
+	fn session_index() -> Option<SessionIndex>;
+
+	/// Active validators, in the order of their sockets
+	fn validators() -> Vec<AccountId>;
+
+	/// Reports `offenders` for `kind` of misbehavior in `session`
+	fn report_offence(session: SessionIndex, offenders: Vec<AccountId>, kind: OracleOffenceKind);
+}
+
+/// Providers registered by governance.
+impl<AccountId> ProviderValidators<AccountId> for () {
+	fn session_index() -> Option<SessionIndex> {
+		None
+	}
+
+	fn validators() -> Vec<AccountId> {
+		Vec::new()
+	}
+
+	fn report_offence(_: SessionIndex, _: Vec<AccountId>, _: OracleOffenceKind) {}
+}
+
+/// Offence of oracle providers in validator mode, slashing only correlated misbehavior like
+/// `pallet_im_online`.
+#[derive(Clone, Eq, PartialEq, RuntimeDebug)]
+pub struct OracleOffence<Offender> {
+	/// Session the providers misbehaved in
+	pub session_index: SessionIndex,
+	/// Number of validators in the session
+	pub validator_set_count: u32,
+	/// Misbehaving providers with their identification
+	pub offenders: Vec<Offender>,
+	pub kind: OracleOffenceKind,
+}
+
+impl<Offender: Clone> Offence<Offender> for OracleOffence<Offender> {
+	const ID: Kind = *b"oracle:misbehave";
+	// Outliers and unresponsive providers of a session are separate offences
+	type TimeSlot = (SessionIndex, OracleOffenceKind);
+
+	fn offenders(&self) -> Vec<Offender> {
+		self.offenders.clone()
+	}
+
+	fn session_index(&self) -> SessionIndex {
+		self.session_index
+	}
+
+	fn validator_set_count(&self) -> u32 {
+		self.validator_set_count
+	}
+
+	fn time_slot(&self) -> Self::TimeSlot {
+		(self.session_index, self.kind)
+	}
+
+	fn slash_fraction(offenders: u32, validator_set_count: u32) -> Perbill {
+		// More than 10% of the providers misbehaving slashes up to 7%
+		match offenders.checked_sub(validator_set_count / 10 + 1) {
+			Some(threshold) => Perbill::from_rational(3 * threshold, validator_set_count)
+				.saturating_mul(Perbill::from_percent(7)),
+			None => Perbill::zero(),
+		}
+	}
+}
+
+/// `ProviderValidators` of the session validators `V`, whose offences are reported to `R`.
+///
+/// `V` is typically `pallet_session::historical`, so that the offenders are identified by their
+/// exposure, and `R` is `pallet_offences`.
+pub struct SessionValidators<V, R>(PhantomData<(V, R)>);
+
+impl<AccountId, V, R> ProviderValidators<AccountId> for SessionValidators<V, R>
+where
+	AccountId: Clone,
+	V: ValidatorSetWithIdentification<AccountId, ValidatorId = AccountId>,
+	R: ReportOffence<
+		AccountId,
+		(AccountId, V::Identification),
+		OracleOffence<(AccountId, V::Identification)>,
+	>,
+{
+	fn session_index() -> Option<SessionIndex> {
+		Some(V::session_index())
+	}
+
+	fn validators() -> Vec<AccountId> {
+		V::validators()
+	}
+
+	fn report_offence(session: SessionIndex, offenders: Vec<AccountId>, kind: OracleOffenceKind) {
+		let offenders: Vec<_> = offenders
+			.into_iter()
+			.filter_map(|who| V::IdentificationOf::convert(who.clone()).map(|id| (who, id)))
+			.collect();
+		if offenders.is_empty() {
+			return
+		}
+		let offence = OracleOffence {
+			session_index: session,
+			validator_set_count: V::validators().len() as u32,
+			offenders,
+			kind,
+		};
+		// Offenders already reported in the session are a `DuplicateReport`, nothing to do
+		let _ = R::report_offence(Vec::new(), offence);
+	}
 }
 
 decl_module! {
@@ -29,6 +363,35 @@ decl_module! {
 
 		fn deposit_event() = default;
 
+		fn on_runtime_upgrade() -> Weight {
+			Self::migrate_to_fixed_prices().saturating_add(Self::migrate_to_socket_prices())
+		}
+
+		/// Times out the price requests due, and closes the open round once the block round moved
+		/// on, and the era with it. In validator mode the providers then follow the validators.
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let weight = Self::time_out_requests(now)
+				.saturating_add(Self::pay_era_rewards())
+				.saturating_add(Self::check_heartbeats());
+			let round = Self::current_round();
+			let closing = Self::open_round();
+			let mut weight = weight.saturating_add(T::DbWeight::get().reads(1));
+			if round != closing {
+				OpenRound::put(round);
+				weight = weight.saturating_add(Self::close_round(closing));
+
+				let era = (round / T::RoundsPerEra::get().max(1)) as EraIndex;
+				let closing_era = Self::current_era();
+				if era != closing_era {
+					CurrentEra::put(era);
+					weight = weight.saturating_add(Self::close_era(closing_era));
+				}
+				weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 2));
+			}
+			// The providers are scored for the closed round and session before they change
+			weight.saturating_add(Self::sync_validators())
+		}
+
 
 		// REVIEW: Use `///` instead of `//` to make these doc comments that are part of the crate documentation.
 		// Register a new Provider.
@@ -36,6 +399,13 @@ decl_module! {
 		#[weight = 10_000]
 		pub fn register_operator(origin, _socket: SocketIndex, _who: T::AccountId) -> DispatchResult {
 			ensure_root(origin)?;
+			Self::ensure_governed_providers()?;
+			// A provider holds one socket, e.g. the one assigned at genesis is given up
+			let previous = Self::oracle(&_who)
+				.filter(|socket| Self::provider_at(socket).as_ref() == Some(&_who));
+			if let Some(previous) = previous {
+				Sockets::<T>::remove(previous);
+			}
 			Providers::<T>::insert(&_who, true);
 			Sockets::<T>::insert(_socket, _who.clone());
 			Oracles::<T>::insert(_who.clone(), _socket);
@@ -49,6 +419,7 @@ decl_module! {
 		#[weight = 10_000]
 		pub fn deregister_operator(origin, _socket: SocketIndex, _who: T::AccountId) -> DispatchResult {
 			ensure_root(origin)?;
+			Self::ensure_governed_providers()?;
 			Providers::<T>::remove(&_who);
 			Sockets::<T>::remove(_socket);
 			Oracles::<T>::remove(_who.clone());
@@ -57,32 +428,38 @@ decl_module! {
 			Ok(())
 		}
 
-		#[weight = 0]
-		fn report(origin, _socket: SocketIndex, _id: AssetId, _price: Balance) -> DispatchResult {
+		/// Submits the price observed by the provider at `_socket`.
+		///
+		/// The report has to be for the current round and each provider reports an asset at
+		/// most once per round, and at most `MaxReportsPerRound` assets.
+		///
+		/// Reports do not pay fees, `CheckProviderReport` keeps the invalid ones out of blocks.
+		#[weight = (0, Pays::No)]
+		fn report(origin, _socket: SocketIndex, report: VersionedPriceReport) -> DispatchResult {
 			let who : <T as frame_system::Config>::AccountId = ensure_signed(origin)?;
-			ensure!(Providers::<T>::contains_key(who.clone()), Error::<T>::WrongProvider);
-			ensure!(Sockets::<T>::get(_socket) == Some(who.clone()), Error::<T>::WrongSocket);
-			let results = match Self::asset_price(_id) {
-				Some(mut x) => {
-					if x.len() != Self::provider_count() as usize {
-						let oracles = Self::provider_count();
-						let mut batch = vec!{0; oracles as usize};
-						batch[_socket as usize] = _price;
-						batch
-					} else {
-						  x[_socket as usize] = _price;
-						  x
-					}
-				},
-				_ => {
-				  let oracles = Self::provider_count();
-				  let mut batch = vec!{0; oracles as usize};
-				  batch[_socket as usize] = _price;
-				  batch
+			let report = PriceReport::from(report);
+			Self::check_report(&who, _socket, &report)?;
+			RoundReports::<T>::mutate(&who, |(round, count)| {
+				if *round != report.round {
+					*round = report.round;
+					*count = 0;
 				}
-			  };
-			Prices::insert(_id, results);
-			Self::deposit_event(RawEvent::PriceSubmitted(_socket, who, _price));
+				*count += 1;
+			});
+			let PriceReport { asset: _id, price: _price, .. } = report;
+			let now = frame_system::Pallet::<T>::block_number();
+			SocketPrices::<T>::insert(_id, _socket, (price_from_reported(_price), now));
+			PriceUpdatedAt::<T>::insert(_id, now);
+			LastReports::<T>::insert(&who, _id, report);
+			Metrics::<T>::mutate(&who, |metrics| {
+				metrics.submissions = metrics.submissions.saturating_add(1);
+				metrics.last_round = Some(report.round);
+			});
+			RoundAssets::mutate(|assets| if !assets.contains(&_id) {
+				assets.push(_id);
+			});
+			Self::note_heartbeat(_socket);
+			Self::deposit_event(RawEvent::PriceSubmitted(_socket, who, report));
 
 			Ok(())
 		}
@@ -91,23 +468,29 @@ decl_module! {
 		/// For now, it just checks the value is an outlier and excludes from the provider slot
 		/// Effects will be felt at the beginning of the next era.
 		///
+		/// The dispatch origin must be Root, as the provider is reported as an offence against its
+		/// validator in validator mode.
 		///
 		/// # <weight>
 		/// ----------
 		/// Weight: O(1)
 		/// DB Weight:
-		/// - Read: Sockets, Prices
+		/// - Read: Sockets, SocketPrices
 		/// - Write:  Sockets New Account, Sockets Old Account
 		/// # </weight>
 		#[weight = 10_000]
 		fn slash(origin, _socket: SocketIndex, _id: AssetId) -> DispatchResult {
-			let batch = Prices::get(_id).unwrap();
-			let value = batch[_socket as usize];
-			let det = Self::determine_outlier(batch, value);
+			ensure_root(origin)?;
+			let (value, _) = Self::socket_price(_id, _socket).ok_or(Error::<T>::PriceDoesNotExist)?;
+			let batch = Self::reports(_id).into_iter().map(|(_, price)| price.into_inner()).collect();
+			let det = Self::determine_outlier(batch, value.into_inner());
 			ensure!(det, Error::<T>::NotOutlier);
 			// Add provider to the slash list of the current era
 			let provider = Self::provider_at(_socket);
-			Slashes::<T>::insert(1, vec!{provider});
+			Slashes::<T>::append(T::EraFinder::active_era(), provider.clone());
+			if let (Some(session), Some(who)) = (T::ProviderValidators::session_index(), provider) {
+				T::ProviderValidators::report_offence(session, vec![who], OracleOffenceKind::Outlier);
+			}
 			// remove provider from the slot
 			Sockets::<T>::remove(_socket);
 			Ok(())
@@ -117,7 +500,7 @@ decl_module! {
 		fn remove_batch(origin, _id: AssetId) {
 			ensure_root(origin)?;
 
-			Prices::remove(_id);
+			SocketPrices::<T>::remove_prefix(_id, None);
 		}
 
 		/// Sets the ideal number of validators.
@@ -131,6 +514,7 @@ decl_module! {
 		#[weight = T::WeightInfo::set_validator_count()]
 		fn set_validator_count(origin, #[compact] new: u32) {
 			ensure_root(origin)?;
+			Self::ensure_governed_providers()?;
 			ProviderCount::put(new);
 		}
 
@@ -144,6 +528,7 @@ decl_module! {
 		#[weight = T::WeightInfo::set_validator_count()]
 		fn increase_validator_count(origin, #[compact] additional: u32) {
 			ensure_root(origin)?;
+			Self::ensure_governed_providers()?;
 			ProviderCount::mutate(|n| *n += additional);
 		}
 
@@ -157,16 +542,153 @@ decl_module! {
 		#[weight = T::WeightInfo::set_validator_count()]
 		fn scale_validator_count(origin, factor: Percent) {
 			ensure_root(origin)?;
+			Self::ensure_governed_providers()?;
 			ProviderCount::mutate(|n| *n += factor * *n);
 		}
 
+		/// Replaces the provider of `socket` with `new_provider`, which must not hold a socket.
+		///
+		/// The replaced provider is deregistered. The socket may be empty, e.g. after its provider
+		/// was slashed or rotated out.
+		///
+		/// The dispatch origin must be Root, outside of validator mode.
+		#[weight = 10_000]
+		pub fn rotate_slot(origin, socket: SocketIndex, new_provider: T::AccountId) -> DispatchResult {
+			ensure_root(origin)?;
+			Self::ensure_governed_providers()?;
+			ensure!(socket < Self::provider_count(), Error::<T>::WrongSocket);
+			let held = Self::oracle(&new_provider)
+				.filter(|socket| Self::provider_at(socket).as_ref() == Some(&new_provider));
+			ensure!(held.is_none(), Error::<T>::ProviderAlreadyRegistered);
+			let old_provider = Sockets::<T>::get(socket);
+			if let Some(old) = &old_provider {
+				Providers::<T>::remove(old);
+				Oracles::<T>::remove(old);
+			}
+			Providers::<T>::insert(&new_provider, true);
+			Sockets::<T>::insert(socket, new_provider.clone());
+			Oracles::<T>::insert(new_provider.clone(), socket);
+			Self::deposit_event(RawEvent::SlotRotated(socket, old_provider, new_provider));
+
+			Ok(())
+		}
+
+		/// Sets whether the price of `id` falls back to the market TWAP while its reports are
+		/// stale.
+		///
+		/// The dispatch origin must be Root.
+		///
+		/// # <weight>
+		/// Weight: O(1)
+		/// Write: TwapFallback
+		/// # </weight>
+		#[weight = 10_000]
+		fn set_twap_fallback(origin, id: AssetId, enabled: bool) {
+			ensure_root(origin)?;
+			TwapFallback::insert(id, enabled);
+			Self::deposit_event(RawEvent::TwapFallbackSet(id, enabled));
+		}
+
+		/// Requests the price of `symbol` from the providers until `deadline`.
+		///
+		/// `fee` is reserved and shared by the answering providers once `RequestQuorum` of them
+		/// answered. A request without quorum times out at `deadline` and its fee is refunded.
+		///
+		/// # <weight>
+		/// Weight: O(1)
+		/// Write: Requests, NextRequestId, RequestDeadlines, requester balance
+		/// # </weight>
+		#[weight = 10_000]
+		fn request_price(origin, symbol: Vec<u8>, fee: Balance, deadline: T::BlockNumber) {
+			let who = ensure_signed(origin)?;
+			Self::do_request_price(who, symbol, fee, deadline)?;
+		}
+
+		/// Answers the open price request `request_id` with `price`.
+		///
+		/// Each provider answers a request once. The answer completing `RequestQuorum` aggregates
+		/// the request into the median of the answers.
+		///
+		/// # <weight>
+		/// Weight: O(Q) where Q is `RequestQuorum`
+		/// Read: Providers, Requests
+		/// Write: Requests, requester and provider balances on aggregation
+		/// # </weight>
+		#[weight = 10_000]
+		fn answer_request(origin, request_id: RequestId, price: Balance) {
+			let who = ensure_signed(origin)?;
+			ensure!(Self::operator(&who), Error::<T>::WrongProvider);
+			ensure!(!price.is_zero(), Error::<T>::InvalidPrice);
+			let mut request = Self::price_request(request_id).ok_or(Error::<T>::UnknownRequest)?;
+			ensure!(
+				!request.answers.iter().any(|(provider, _)| provider == &who),
+				Error::<T>::AlreadyAnswered
+			);
+
+			request.answers.push((who.clone(), price));
+			Self::deposit_event(RawEvent::RequestAnswered(request_id, who, price));
+			if request.answers.len() as u32 >= T::RequestQuorum::get().max(1) {
+				Self::fulfill_request(request_id, request);
+			} else {
+				Requests::<T>::insert(request_id, request);
+			}
+		}
+
+		/// Signals that the provider at `socket` is online in the current heartbeat session.
+		///
+		/// Providers without a heartbeat or report in a session are slashed `HeartbeatSlash`.
+		/// Heartbeats do not pay fees, `CheckProviderReport` keeps the invalid ones out of blocks.
+		#[weight = (0, Pays::No)]
+		fn heartbeat(origin, socket: SocketIndex) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::check_heartbeat(&who, socket)?;
+			Self::note_heartbeat(socket);
+			Self::deposit_event(RawEvent::HeartbeatReceived(Self::current_heartbeat_session(), who));
+
+			Ok(())
+		}
 
+		/// Schedule `set_validator_count` with `new` to be enacted after `delay` blocks.
+		///
+		/// The delay must be at least `MinimumEnactmentDelay` so that providers and price
+		/// consumers have time to react to the announced count. Only one change can be pending.
+		///
+		/// The dispatch origin must be Root.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(2, 2)]
+		fn schedule_validator_count(origin, #[compact] new: u32, delay: T::BlockNumber) {
+			ensure_root(origin)?;
+			Self::ensure_governed_providers()?;
+			ensure!(delay >= T::MinimumEnactmentDelay::get(), Error::<T>::DelayTooShort);
+
+			let when = <frame_system::Pallet<T>>::block_number().saturating_add(delay);
+			T::Scheduler::schedule_named(
+				ORACLE_SCHEDULE_ID.to_vec(),
+				DispatchTime::At(when),
+				None,
+				HARD_DEADLINE,
+				frame_system::RawOrigin::Root.into(),
+				Call::<T>::set_validator_count(new).into(),
+			).map_err(|_| Error::<T>::ScheduleFailed)?;
+			Self::deposit_event(RawEvent::ProviderCountScheduled(new, when));
+		}
+
+		/// Cancel a provider count change scheduled with `schedule_validator_count`.
+		///
+		/// The dispatch origin must be Root.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(2, 2)]
+		fn cancel_scheduled_validator_count(origin) {
+			ensure_root(origin)?;
+			T::Scheduler::cancel_named(ORACLE_SCHEDULE_ID.to_vec())
+				.map_err(|_| Error::<T>::NotScheduled)?;
+			Self::deposit_event(RawEvent::ProviderCountScheduleCancelled);
+		}
 	}
 }
 
 decl_event! {
 	pub enum Event<T> where
 		<T as frame_system::Config>::AccountId,
+		<T as frame_system::Config>::BlockNumber,
 	{
 		// A new operator has been registered
 		ProviderRegistered(AccountId),
@@ -175,7 +697,52 @@ decl_event! {
 		ProviderDeregistered(AccountId),
 
 		// Price reported by an oracle provider
-		PriceSubmitted(SocketIndex, AccountId, u128),
+		PriceSubmitted(SocketIndex, AccountId, PriceReport),
+
+		/// Provider of a socket is replaced by governance (socket, old_provider, new_provider)
+		SlotRotated(SocketIndex, Option<AccountId>, AccountId),
+
+		/// TWAP fallback of an asset has been enabled or disabled
+		TwapFallbackSet(AssetId, bool),
+
+		/// Provider missed too many rounds and lost its socket (era, provider, missed_rounds)
+		ProviderRotatedOut(EraIndex, AccountId, u32),
+
+		/// Price of a symbol is requested (request_id, requester, symbol, fee, deadline)
+		PriceRequested(RequestId, AccountId, Vec<u8>, Balance, BlockNumber),
+
+		/// Provider answered a price request (request_id, provider, price)
+		RequestAnswered(RequestId, AccountId, Balance),
+
+		/// Price request reached quorum and is aggregated (request_id, price)
+		RequestFulfilled(RequestId, Balance),
+
+		/// Price request reached its deadline without quorum and its fee is refunded
+		/// (request_id)
+		RequestTimedOut(RequestId),
+
+		/// Provider is paid its share of the era reward (era, provider, reward)
+		ProviderRewarded(EraIndex, AccountId, Balance),
+
+		/// Provider signalled it is online (session, provider)
+		HeartbeatReceived(HeartbeatSession, AccountId),
+
+		/// Provider sent no heartbeat in a session and is slashed
+		/// (session, provider, missed_sessions, slashed)
+		HeartbeatMissed(HeartbeatSession, AccountId, u32, Balance),
+
+		/// Provider missed `MaxMissedHeartbeats` sessions in a row and lost its socket
+		/// (session, provider)
+		ProviderWentOffline(HeartbeatSession, AccountId),
+
+		/// Providers are replaced by the validators of a session (session, providers)
+		ProvidersSynced(SessionIndex, u32),
+
+		/// Provider count change is scheduled (count, enacted_at)
+		ProviderCountScheduled(u32, BlockNumber),
+
+		/// Scheduled provider count change is cancelled
+		ProviderCountScheduleCancelled,
 	}
 }
 
@@ -200,7 +767,31 @@ decl_error! {
 		/// Wrong socket to submit
 		WrongSocket,
 		/// Outlier not determined
-		NotOutlier
+		NotOutlier,
+		/// Report is not for the current round
+		WrongRound,
+		/// Provider already reported the asset in this round
+		DuplicateReport,
+		/// Deadline is not in the future or further than `MaxRequestPeriod`
+		InvalidDeadline,
+		/// Symbol is longer than `MaxSymbolLength`
+		SymbolTooLong,
+		/// Price must be non-zero
+		InvalidPrice,
+		/// Provider already answered the request
+		AlreadyAnswered,
+		/// Provider submitted `MaxReportsPerRound` reports in this round
+		TooManyReports,
+		/// Provider is already marked online in this heartbeat session
+		DuplicateHeartbeat,
+		/// Providers mirror the validators and can not be changed by governance
+		ValidatorMode,
+		/// Enactment delay is shorter than `MinimumEnactmentDelay`
+		DelayTooShort,
+		/// Provider count change could not be scheduled, e.g. one is already pending
+		ScheduleFailed,
+		/// No provider count change is scheduled
+		NotScheduled,
 	}
 }
 
@@ -210,8 +801,35 @@ decl_storage! {
 		// A set of all registered Provider
 		pub Providers get(fn operator): map hasher(blake2_128_concat) T::AccountId => bool;
 
-		// Price batch from oracle providers
-		pub Prices get(fn asset_price): map hasher(blake2_128_concat) AssetId => Option<Vec<Balance>>;
+		// Latest price reported from each socket and the block it was reported at
+		pub SocketPrices get(fn socket_price): double_map hasher(blake2_128_concat) AssetId, hasher(twox_64_concat) SocketIndex => Option<(FixedU128, T::BlockNumber)>;
+
+		/// Block of the latest report of each asset
+		pub PriceUpdatedAt get(fn price_updated_at): map hasher(blake2_128_concat) AssetId => Option<T::BlockNumber>;
+
+		/// Assets whose price falls back to the market TWAP while their reports are stale
+		pub TwapFallback get(fn twap_fallback): map hasher(blake2_128_concat) AssetId => bool;
+
+		/// Reporting performance of each provider
+		pub Metrics get(fn provider_metrics): map hasher(blake2_128_concat) T::AccountId => ProviderMetrics;
+
+		/// Rounds missed by each provider in the current era
+		pub EraMissedRounds get(fn era_missed_rounds): map hasher(blake2_128_concat) T::AccountId => u32;
+
+		/// Round whose reports are collected, closed once the block round moves on
+		pub OpenRound get(fn open_round): RoundIndex;
+
+		/// Assets reported in the open round
+		pub RoundAssets get(fn round_assets): Vec<AssetId>;
+
+		/// Era of the open round
+		pub CurrentEra get(fn current_era): EraIndex;
+
+		/// Latest report of each provider per asset
+		pub LastReports get(fn last_report): double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) AssetId => Option<PriceReport>;
+
+		/// Number of reports of each provider in its latest reporting round, as (round, count)
+		pub RoundReports get(fn round_reports): map hasher(blake2_128_concat) T::AccountId => (RoundIndex, u32);
 
 		// Oracles: key as account id, value as oracle socket index
 		pub Oracles get(fn oracle): map hasher(blake2_128_concat) T::AccountId => Option<SocketIndex>;
@@ -225,45 +843,584 @@ decl_storage! {
 		/// The ideal number of staking participants.
 		pub ProviderCount get(fn provider_count) config(): u32;
 
+		/// Open price requests
+		pub Requests get(fn price_request): map hasher(twox_64_concat) RequestId => Option<PriceRequest<T::AccountId, T::BlockNumber>>;
+
+		/// Identifier of the next price request
+		pub NextRequestId get(fn next_request_id): RequestId;
+
+		/// Price requests timing out at a block
+		pub RequestDeadlines get(fn request_deadlines): map hasher(twox_64_concat) T::BlockNumber => Vec<RequestId>;
+
+		/// Era whose rewards are paid to the providers once `EraFinder` moves past it
+		pub PayoutEra get(fn payout_era): EraIndex;
+
+		/// Heartbeat session whose heartbeats are collected, closed once the block session moves on
+		pub OpenHeartbeatSession get(fn open_heartbeat_session): HeartbeatSession;
+
+		/// Sockets which sent a heartbeat or report in each session, one bit per socket
+		pub ReceivedHeartbeats get(fn received_heartbeats): map hasher(twox_64_concat) HeartbeatSession => Vec<u8>;
+
+		/// Heartbeat sessions each provider missed in a row
+		pub MissedHeartbeats get(fn missed_heartbeats): map hasher(blake2_128_concat) T::AccountId => u32;
+
+		/// Session of the validators the providers mirror in validator mode
+		pub ValidatorSession get(fn validator_session): Option<SessionIndex>;
+
 	} add_extra_genesis {
 		config(oracles):
 			Vec<<T as frame_system::Config>::AccountId>;
+		/// Prices reported by every genesis oracle at genesis
+		config(prices): Vec<(AssetId, FixedU128)>;
 		build(|config: &GenesisConfig<T>| {
-			for oracle in &config.oracles {
+			assert!(
+				config.oracles.len() <= config.provider_count as usize,
+				"Genesis oracles exceed the provider count",
+			);
+			// Assign the sockets in order, so that the oracles can report from the first block
+			for (socket, oracle) in config.oracles.iter().enumerate() {
 				Providers::<T>::insert(oracle, true);
+				Sockets::<T>::insert(socket as SocketIndex, oracle.clone());
+				Oracles::<T>::insert(oracle.clone(), socket as SocketIndex);
+				for (asset, price) in config.prices.iter() {
+					SocketPrices::<T>::insert(asset, socket as SocketIndex, (*price, T::BlockNumber::zero()));
+				}
+			}
+			for (asset, _) in config.prices.iter() {
+				PriceUpdatedAt::<T>::insert(asset, T::BlockNumber::zero());
 			}
+			StorageVersion::new(1).put::<Module<T>>();
 		});
 	}
 }
 
 // The main implementation block for the module.
 impl<T: Config> Module<T> {
-	pub fn price(id: AssetId) -> sp_std::result::Result<Balance, DispatchError> {
-		match Self::asset_price(id) {
-			Some(reports) => {
-				// get median value
-				let median = Self::get_median(reports);
-				return Ok(median)
-			},
-			None => return Err(DispatchError::from(crate::Error::<T>::PriceDoesNotExist).into()),
+	/// Price of one unit of `id` in USD.
+	pub fn price(id: AssetId) -> sp_std::result::Result<FixedU128, DispatchError> {
+		Self::price_with_source(id).map(|(price, _)| price)
+	}
+
+	/// Price of `id` and where it comes from.
+	///
+	/// While the reports are stale the price falls back to the market TWAP if enabled for the
+	/// asset. Without a fallback price the stale reports are used.
+	pub fn price_with_source(
+		id: AssetId,
+	) -> sp_std::result::Result<(FixedU128, PriceSource), DispatchError> {
+		// get median value
+		let reported = Self::median(id);
+		let stale = Self::is_stale(id);
+		log!(trace, "price of {}: median {:?}, stale {}", id, reported, stale);
+		if let (Some(price), false) = (reported, stale) {
+			return Ok((price, PriceSource::Oracle))
+		}
+		if let Some(price) = Self::twap_price(id) {
+			return Ok((price, PriceSource::Twap))
+		}
+		reported
+			.map(|price| (price, PriceSource::StaleOracle))
+			.ok_or_else(|| crate::Error::<T>::PriceDoesNotExist.into())
+	}
+
+	/// Prices reported for `id` as `(socket, price)` by the sockets below the provider count,
+	/// ordered by socket.
+	pub fn reports(id: AssetId) -> Vec<(SocketIndex, FixedU128)> {
+		let count = Self::provider_count();
+		let mut reports: Vec<_> = SocketPrices::<T>::iter_prefix(id)
+			.filter(|(socket, _)| *socket < count)
+			.map(|(socket, (price, _))| (socket, price))
+			.collect();
+		reports.sort_by_key(|(socket, _)| *socket);
+		reports
+	}
+
+	/// Median of the non-zero prices reported for `id`.
+	pub fn median(id: AssetId) -> Option<FixedU128> {
+		let prices = Self::preprocess(
+			Self::reports(id).into_iter().map(|(_, price)| price.into_inner()).collect(),
+		);
+		prices.get(prices.len() / 2).copied().map(FixedU128::from_inner)
+	}
+
+	/// Checks that `who` may submit `report` at `socket`.
+	fn check_report(
+		who: &T::AccountId,
+		socket: SocketIndex,
+		report: &PriceReport,
+	) -> Result<(), Error<T>> {
+		ensure!(Providers::<T>::contains_key(who), Error::<T>::WrongProvider);
+		ensure!(Sockets::<T>::get(socket).as_ref() == Some(who), Error::<T>::WrongSocket);
+		ensure!(report.round == Self::current_round(), Error::<T>::WrongRound);
+		ensure!(
+			Self::last_report(who, report.asset).map(|last| last.round) != Some(report.round),
+			Error::<T>::DuplicateReport
+		);
+		ensure!(socket < Self::provider_count(), Error::<T>::WrongSocket);
+		let (round, count) = Self::round_reports(who);
+		ensure!(
+			round != report.round || count < T::MaxReportsPerRound::get(),
+			Error::<T>::TooManyReports
+		);
+		Ok(())
+	}
+
+	/// Checks that `who` may send a heartbeat from `socket`.
+	fn check_heartbeat(who: &T::AccountId, socket: SocketIndex) -> Result<(), Error<T>> {
+		ensure!(Sockets::<T>::get(socket).as_ref() == Some(who), Error::<T>::WrongSocket);
+		ensure!(socket < Self::provider_count(), Error::<T>::WrongSocket);
+		ensure!(
+			!Self::is_online(Self::current_heartbeat_session(), socket),
+			Error::<T>::DuplicateHeartbeat
+		);
+		Ok(())
+	}
+
+	// Keeps governance from changing the providers while they mirror the validators
+	fn ensure_governed_providers() -> DispatchResult {
+		ensure!(T::ProviderValidators::session_index().is_none(), Error::<T>::ValidatorMode);
+		Ok(())
+	}
+
+	// Assigns the sockets to the validators of a new session in validator mode, in the order of
+	// the validator set, and deregisters the providers which are no longer validators
+	fn sync_validators() -> Weight {
+		let session = match T::ProviderValidators::session_index() {
+			Some(session) => session,
+			None => return 0,
+		};
+		if Self::validator_session() == Some(session) {
+			return T::DbWeight::get().reads(1)
+		}
+		ValidatorSession::put(session);
+
+		let validators = T::ProviderValidators::validators();
+		let previous: Vec<_> = Sockets::<T>::drain().collect();
+		for (_, who) in previous.iter() {
+			Providers::<T>::remove(who);
+			Oracles::<T>::remove(who);
+		}
+		for (socket, who) in validators.iter().enumerate() {
+			Providers::<T>::insert(who, true);
+			Sockets::<T>::insert(socket as SocketIndex, who.clone());
+			Oracles::<T>::insert(who.clone(), socket as SocketIndex);
+		}
+		let count = validators.len() as u32;
+		ProviderCount::put(count);
+		log!(debug, "providers synced to the {} validators of session {}", count, session);
+		Self::deposit_event(RawEvent::ProvidersSynced(session, count));
+
+		let (previous, validators) = (previous.len() as u64, count as u64);
+		T::DbWeight::get().reads_writes(1 + previous, 2 + 3 * previous + 3 * validators)
+	}
+
+	/// Whether the provider at `socket` sent a heartbeat or report in `session`.
+	pub fn is_online(session: HeartbeatSession, socket: SocketIndex) -> bool {
+		Self::received_heartbeats(session)
+			.get(socket as usize / 8)
+			.map_or(false, |bits| bits & (1 << (socket % 8)) != 0)
+	}
+
+	// Sets the bit of `socket` in the heartbeats of the current session
+	fn note_heartbeat(socket: SocketIndex) {
+		ReceivedHeartbeats::mutate(Self::current_heartbeat_session(), |received| {
+			let index = socket as usize / 8;
+			if received.len() <= index {
+				received.resize(index + 1, 0);
+			}
+			received[index] |= 1 << (socket % 8);
+		});
+	}
+
+	/// Storage keys the reported prices of `id` are read from.
+	pub fn price_keys(id: AssetId) -> Vec<Vec<u8>> {
+		let mut keys = vec![ProviderCount::hashed_key().to_vec()];
+		keys.extend(
+			(0..Self::provider_count()).map(|socket| SocketPrices::<T>::hashed_key_for(id, socket)),
+		);
+		keys
+	}
+
+	/// Number of providers with a price reported for `id`.
+	pub fn reporting_providers(id: AssetId) -> u32 {
+		Self::reports(id).iter().filter(|(_, price)| !price.is_zero()).count() as u32
+	}
+
+	/// Registered providers.
+	pub fn providers() -> Vec<T::AccountId> {
+		Providers::<T>::iter().filter(|(_, registered)| *registered).map(|(who, _)| who).collect()
+	}
+
+	/// Providers assigned to the sockets as `(socket, provider)`, ordered by socket.
+	pub fn slot_assignments() -> Vec<(SocketIndex, T::AccountId)> {
+		let mut assignments: Vec<_> = Sockets::<T>::iter().collect();
+		assignments.sort_by_key(|(socket, _)| *socket);
+		assignments
+	}
+
+	/// Providers slashed in `era`.
+	pub fn slashes(era: EraIndex) -> Vec<T::AccountId> {
+		Self::slashes_at(era).into_iter().flatten().collect()
+	}
+
+	/// Opens a request for the price of `symbol` until `deadline` on behalf of `requester`,
+	/// reserving `fee`.
+	///
+	/// Lets other pallets request prices, the outcome is passed to `OnPriceResponse`.
+	pub fn do_request_price(
+		requester: T::AccountId,
+		symbol: Vec<u8>,
+		fee: Balance,
+		deadline: T::BlockNumber,
+	) -> sp_std::result::Result<RequestId, DispatchError> {
+		let now = frame_system::Pallet::<T>::block_number();
+		ensure!(
+			deadline > now && deadline - now <= T::MaxRequestPeriod::get(),
+			Error::<T>::InvalidDeadline
+		);
+		ensure!(symbol.len() as u32 <= T::MaxSymbolLength::get(), Error::<T>::SymbolTooLong);
+		ensure!(fee >= T::MinRequestFee::get(), Error::<T>::InsufficientFee);
+		T::Currency::reserve(&requester, fee)?;
+
+		let request_id = NextRequestId::mutate(|id| {
+			let request_id = *id;
+			*id += 1;
+			request_id
+		});
+		RequestDeadlines::<T>::append(deadline, request_id);
+		Self::deposit_event(RawEvent::PriceRequested(
+			request_id,
+			requester.clone(),
+			symbol.clone(),
+			fee,
+			deadline,
+		));
+		Requests::<T>::insert(
+			request_id,
+			PriceRequest { requester, symbol, fee, deadline, answers: Vec::new() },
+		);
+		Ok(request_id)
+	}
+
+	// Aggregates the answers of a request at quorum and shares its fee among the answering
+	// providers, the remainder of the division goes back to the requester
+	fn fulfill_request(request_id: RequestId, request: PriceRequest<T::AccountId, T::BlockNumber>) {
+		let price = Self::get_median(request.answers.iter().map(|(_, price)| *price).collect());
+		log!(debug, "request {} fulfilled at {} from {:?}", request_id, price, request.answers);
+		let share = request.fee / request.answers.len() as Balance;
+		let mut remainder = request.fee;
+		for (provider, _) in &request.answers {
+			let unpaid = T::Currency::repatriate_reserved(
+				&request.requester,
+				provider,
+				share,
+				BalanceStatus::Free,
+			)
+			.unwrap_or(share);
+			remainder = remainder.saturating_sub(share.saturating_sub(unpaid));
+		}
+		T::Currency::unreserve(&request.requester, remainder);
+		Requests::<T>::remove(request_id);
+
+		Self::deposit_event(RawEvent::RequestFulfilled(request_id, price));
+		T::OnPriceResponse::on_response(
+			request_id,
+			&request.requester,
+			&request.symbol,
+			Some(price),
+		);
+	}
+
+	// Refunds the requests whose deadline is `now` without quorum
+	fn time_out_requests(now: T::BlockNumber) -> Weight {
+		let request_ids = RequestDeadlines::<T>::take(now);
+		let mut reads = 1;
+		let mut writes = 1;
+		for request_id in request_ids {
+			reads += 1;
+			// Requests which reached quorum are already removed
+			let request = match Requests::<T>::take(request_id) {
+				Some(request) => request,
+				None => continue,
+			};
+			T::Currency::unreserve(&request.requester, request.fee);
+			writes += 2;
+			Self::deposit_event(RawEvent::RequestTimedOut(request_id));
+			T::OnPriceResponse::on_response(request_id, &request.requester, &request.symbol, None);
+		}
+		T::DbWeight::get().reads_writes(reads, writes)
+	}
+
+	// Shares `EraReward` between the providers holding a socket and not slashed in the payout
+	// era once `EraFinder` moved past it. Providers the reward pot can not pay are skipped
+	fn pay_era_rewards() -> Weight {
+		let era = Self::payout_era();
+		let active = T::EraFinder::active_era();
+		if active <= era {
+			return T::DbWeight::get().reads(2)
+		}
+		PayoutEra::put(active);
+
+		let slashed = Self::slashes(era);
+		let providers: Vec<T::AccountId> =
+			Sockets::<T>::iter_values().filter(|who| !slashed.contains(who)).collect();
+		let count = providers.len() as u64;
+		let reward = T::EraReward::get().checked_div(count.into()).unwrap_or_default();
+		if !reward.is_zero() {
+			let pot = T::RewardPot::get();
+			for who in providers {
+				let paid =
+					T::Currency::transfer(&pot, &who, reward, ExistenceRequirement::KeepAlive);
+				if paid.is_ok() {
+					Self::deposit_event(RawEvent::ProviderRewarded(era, who, reward));
+				}
+			}
+		}
+		T::DbWeight::get().reads_writes(3 + count, 1 + 2 * count)
+	}
+
+	// Scores the reports of `round` against the final median of each asset, counts a missed
+	// round for providers without any report and hands the medians to `OnRoundClosed`
+	fn close_round(round: RoundIndex) -> Weight {
+		let assets = RoundAssets::take();
+		let mut reads = 1 + assets.len() as u64;
+		let mut writes = 1;
+		let mut medians = Vec::with_capacity(assets.len());
+		for asset in assets {
+			let median = match Self::median(asset) {
+				Some(median) => median,
+				None => continue,
+			};
+			medians.push((asset, median));
+			let reports = Self::reports(asset);
+			log!(
+				debug,
+				"round {} closed for asset {} at {:?} from {:?}",
+				round,
+				asset,
+				median,
+				reports,
+			);
+			for (socket, price) in reports {
+				reads += 2;
+				let who = match Self::provider_at(socket) {
+					Some(who) => who,
+					None => continue,
+				};
+				if Self::last_report(&who, asset).map(|report| report.round) != Some(round) {
+					continue
+				}
+				let deviation = Perbill::from_rational(
+					price.max(median).saturating_sub(price.min(median)).into_inner(),
+					median.into_inner(),
+				);
+				Metrics::<T>::mutate(&who, |metrics| {
+					metrics.total_deviation =
+						metrics.total_deviation.saturating_add(deviation.deconstruct() as u64);
+					metrics.deviation_samples = metrics.deviation_samples.saturating_add(1);
+				});
+				writes += 1;
+			}
+		}
+
+		for (_, who) in Sockets::<T>::iter() {
+			reads += 2;
+			Metrics::<T>::mutate(&who, |metrics| {
+				if metrics.last_round != Some(round) {
+					metrics.missed_rounds = metrics.missed_rounds.saturating_add(1);
+					EraMissedRounds::<T>::mutate(&who, |missed| *missed = missed.saturating_add(1));
+					writes += 2;
+				}
+			});
+		}
+		T::DbWeight::get()
+			.reads_writes(reads, writes)
+			.saturating_add(T::OnRoundClosed::on_round_closed(round, &medians))
+	}
+
+	// Moves the dense price batches of the former `Prices` item into `SocketPrices`, a no-op once
+	// it is empty
+	fn migrate_to_socket_prices() -> Weight {
+		let now = frame_system::Pallet::<T>::block_number();
+		let mut reads: Weight = 1;
+		let mut writes: Weight = 1;
+		for (id, batch) in
+			storage_key_iter::<AssetId, Vec<Balance>, Blake2_128Concat>(b"Oracle", b"Prices")
+		{
+			reads += 2;
+			let reported_at = Self::price_updated_at(id).unwrap_or(now);
+			for (socket, price) in batch.into_iter().enumerate() {
+				if !price.is_zero() {
+					let price = price_from_reported(price);
+					SocketPrices::<T>::insert(id, socket as SocketIndex, (price, reported_at));
+					writes += 1;
+				}
+			}
+		}
+		remove_storage_prefix(b"Oracle", b"Prices", &[]);
+		T::DbWeight::get().reads_writes(reads, writes)
+	}
+
+	// Scales the integer prices of `SocketPrices`, reported with `PRICE_DECIMALS` decimals, to
+	// `FixedU128`
+	fn migrate_to_fixed_prices() -> Weight {
+		if StorageVersion::get::<Module<T>>() >= 1 {
+			return T::DbWeight::get().reads(1)
+		}
+		let mut count: Weight = 0;
+		SocketPrices::<T>::translate::<(Balance, T::BlockNumber), _>(|_, _, (price, at)| {
+			count += 1;
+			Some((price_from_reported(price), at))
+		});
+		StorageVersion::new(1).put::<Module<T>>();
+		T::DbWeight::get().reads_writes(count + 1, count + 1)
+	}
+
+	// Rotates providers which missed more than `MaxMissedRounds` of the era out of their socket
+	fn close_era(era: EraIndex) -> Weight {
+		let rounds = T::RoundsPerEra::get().max(1);
+		let mut reads = 0;
+		let mut writes = 0;
+		for (who, missed) in EraMissedRounds::<T>::drain() {
+			reads += 1;
+			writes += 1;
+			if Percent::from_rational(missed, rounds) <= T::MaxMissedRounds::get() {
+				continue
+			}
+			let socket = Oracles::<T>::take(&who)
+				.filter(|socket| Self::provider_at(socket) == Some(who.clone()));
+			if let Some(socket) = socket {
+				Sockets::<T>::remove(socket);
+				writes += 2;
+				Self::deposit_event(RawEvent::ProviderRotatedOut(era, who, missed));
+			}
+		}
+		T::DbWeight::get().reads_writes(reads, writes)
+	}
+
+	// Slashes the providers without a heartbeat in the open session once the block session moved
+	// on, and rotates the ones which missed `MaxMissedHeartbeats` sessions in a row out of their
+	// socket
+	fn check_heartbeats() -> Weight {
+		let session = Self::current_heartbeat_session();
+		let closing = Self::open_heartbeat_session();
+		if session == closing {
+			return T::DbWeight::get().reads(1)
+		}
+		OpenHeartbeatSession::put(session);
+
+		let count = Self::provider_count();
+		let providers: Vec<_> =
+			Sockets::<T>::iter().filter(|(socket, _)| *socket < count).collect();
+		let mut writes = 2;
+		let mut offenders = Vec::new();
+		for (socket, who) in providers.iter() {
+			if Self::is_online(closing, *socket) {
+				MissedHeartbeats::<T>::remove(who);
+				writes += 1;
+				continue
+			}
+			let missed = MissedHeartbeats::<T>::mutate(who, |missed| {
+				*missed = missed.saturating_add(1);
+				*missed
+			});
+			let (slashed, _) = T::Currency::slash(who, T::HeartbeatSlash::get());
+			let amount = slashed.peek();
+			log!(
+				warn,
+				"socket {} missed heartbeat session {}, {} in a row",
+				socket,
+				closing,
+				missed,
+			);
+			T::Currency::resolve_creating(&T::RewardPot::get(), slashed);
+			writes += 3;
+			Self::deposit_event(RawEvent::HeartbeatMissed(closing, who.clone(), missed, amount));
+			offenders.push(who.clone());
+
+			if missed >= T::MaxMissedHeartbeats::get() {
+				Sockets::<T>::remove(socket);
+				Oracles::<T>::remove(who);
+				MissedHeartbeats::<T>::remove(who);
+				writes += 3;
+				Self::deposit_event(RawEvent::ProviderWentOffline(closing, who.clone()));
+			}
+		}
+		ReceivedHeartbeats::remove(closing);
+		if let Some(session) = T::ProviderValidators::session_index() {
+			if !offenders.is_empty() {
+				T::ProviderValidators::report_offence(
+					session,
+					offenders,
+					OracleOffenceKind::Unresponsive,
+				);
+			}
+		}
+		let providers = providers.len() as u64;
+		T::DbWeight::get().reads_writes(3 + 2 * providers, writes)
+	}
+
+	/// Heartbeat session of the current block.
+	pub fn current_heartbeat_session() -> HeartbeatSession {
+		let now = frame_system::Pallet::<T>::block_number();
+		let period = T::HeartbeatPeriod::get();
+		match period.is_zero() {
+			true => now,
+			false => now / period,
+		}
+		.unique_saturated_into()
+	}
+
+	/// Reporting round of the current block.
+	pub fn current_round() -> RoundIndex {
+		let now = frame_system::Pallet::<T>::block_number();
+		let length = T::RoundLength::get();
+		match length.is_zero() {
+			true => now,
+			false => now / length,
 		}
+		.unique_saturated_into()
+	}
+
+	/// Whether the latest report of `id` is older than `StalePeriod`.
+	pub fn is_stale(id: AssetId) -> bool {
+		let now = frame_system::Pallet::<T>::block_number();
+		match Self::price_updated_at(id) {
+			Some(at) => now.saturating_sub(at) >= T::StalePeriod::get(),
+			None => true,
+		}
+	}
+
+	// Market TWAP of `id` in the quote asset, converted with the oracle price of the quote asset
+	fn twap_price(id: AssetId) -> Option<FixedU128> {
+		let quote = T::TwapQuoteAsset::get();
+		if id == quote || !Self::twap_fallback(id) || Self::is_stale(quote) {
+			return None
+		}
+		let quote_price = Self::median(quote)?;
+		Some(T::Twap::twap(id, quote, T::TwapWindow::get())?.saturating_mul(quote_price))
 	}
 
 	pub fn determine_outlier(batch: Vec<Balance>, value: Balance) -> bool {
 		let processed = Self::preprocess(batch);
+		if processed.is_empty() {
+			return false
+		}
 		let len = processed.len();
 		let mid = len / 2;
 		let quartile = mid / 2;
 		let q3 = mid + quartile;
 		let q1 = mid - quartile;
-		let iqr = 3 * (processed[q3] - processed[q1]) / 2;
-		return processed[q3] + iqr < value || processed[q1] - iqr > value
+		// The batch is sorted, so the third quartile is at least the first one
+		let iqr = processed[q3].saturating_sub(processed[q1]).saturating_mul(3) / 2;
+		processed[q3].saturating_add(iqr) < value ||
+			processed[q1].checked_sub(iqr).map_or(false, |lower| lower > value)
 	}
 
 	pub fn get_median(batch: Vec<Balance>) -> Balance {
 		let processed = Self::preprocess(batch);
 		let mid = processed.len() / 2;
-		processed[mid]
+		processed.get(mid).copied().unwrap_or_default()
 	}
 
 	pub fn preprocess(mut batch: Vec<Balance>) -> Vec<u128> {
@@ -272,3 +1429,82 @@ impl<T: Config> Module<T> {
 		batch
 	}
 }
+
+/// Eras of the reporting rounds, `RoundsPerEra` rounds each, for runtimes without staking.
+impl<T: Config> EraFinder for Module<T> {
+	fn active_era() -> EraIndex {
+		Self::current_era()
+	}
+}
+
+/// Keeps the fee-less `report` and `heartbeat` calls which would fail out of the transaction pool
+/// and blocks.
+///
+/// `report` does not pay fees, so without this check a provider could fill blocks with failing
+/// reports for free. Valid reports are limited to `MaxReportsPerRound` per provider and round,
+/// and heartbeats to one per provider and session.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct CheckProviderReport<T: Config + Send + Sync>(PhantomData<T>);
+
+impl<T: Config + Send + Sync> CheckProviderReport<T> {
+	pub fn new() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<T: Config + Send + Sync> Default for CheckProviderReport<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: Config + Send + Sync> sp_std::fmt::Debug for CheckProviderReport<T> {
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		write!(f, "CheckProviderReport")
+	}
+}
+
+impl<T: Config + Send + Sync> SignedExtension for CheckProviderReport<T>
+where
+	<T as frame_system::Config>::Call: IsSubType<Call<T>>,
+{
+	const IDENTIFIER: &'static str = "CheckProviderReport";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::Call;
+	type AdditionalSigned = ();
+	type Pre = ();
+
+	fn additional_signed(&self) -> sp_std::result::Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		let (socket, report) = match call.is_sub_type() {
+			Some(Call::report(socket, report)) => (*socket, PriceReport::from(*report)),
+			Some(Call::heartbeat(socket)) => {
+				Module::<T>::check_heartbeat(who, *socket).map_err(|_| InvalidTransaction::Call)?;
+				return ValidTransaction::with_tag_prefix("OracleHeartbeat")
+					.and_provides((who, Module::<T>::current_heartbeat_session()))
+					.longevity(T::HeartbeatPeriod::get().unique_saturated_into())
+					.build()
+			},
+			_ => return Ok(ValidTransaction::default()),
+		};
+		Module::<T>::check_report(who, socket, &report).map_err(|e| match e {
+			Error::<T>::TooManyReports => InvalidTransaction::ExhaustsResources,
+			_ => InvalidTransaction::Call,
+		})?;
+
+		ValidTransaction::with_tag_prefix("OracleReport")
+			.and_provides((who, report.asset, report.round))
+			.longevity(T::RoundLength::get().unique_saturated_into())
+			.build()
+	}
+}
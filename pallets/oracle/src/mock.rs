@@ -4,7 +4,7 @@ use frame_support::{parameter_types, weights::constants::RocksDbWeight};
 use pallet_balances;
 use sp_core::H256;
 use sp_io;
-use sp_runtime::{testing::Header, traits::IdentityLookup};
+use sp_runtime::{testing::Header, traits::IdentityLookup, FixedU128};
 
 /// The AccountId alias in this test module.
 pub(crate) type AccountId = u64;
@@ -67,9 +67,139 @@ impl pallet_balances::Config for Test {
 	type WeightInfo = ();
 }
 
+pub const MTR: AssetId = 1;
+
+/// Prices every asset at 3/2 of the quote asset.
+pub struct FixedTwap;
+
+impl primitives::traits::Twap<BlockNumber> for FixedTwap {
+	fn twap(_base: AssetId, _quote: AssetId, _window: BlockNumber) -> Option<FixedU128> {
+		Some(FixedU128::saturating_from_rational(3, 2))
+	}
+}
+
+parameter_types! {
+	pub const StalePeriod: BlockNumber = 10;
+	pub const TwapQuoteAsset: AssetId = MTR;
+	pub const TwapWindow: BlockNumber = 10;
+	pub const RoundLength: BlockNumber = 5;
+	pub const RoundsPerEra: RoundIndex = 2;
+	pub const MaxMissedRounds: Percent = Percent::from_percent(50);
+	pub const MinRequestFee: Balance = 10;
+	pub const MaxRequestPeriod: BlockNumber = 10;
+	pub const MaxSymbolLength: u32 = 16;
+	pub const RequestQuorum: u32 = 3;
+	pub const MaxReportsPerRound: u32 = 3;
+	pub const EraReward: Balance = 100;
+	pub const RewardPot: AccountId = REWARD_POT;
+	pub const HeartbeatPeriod: BlockNumber = 10;
+	pub const HeartbeatSlash: Balance = 10;
+	pub const MaxMissedHeartbeats: u32 = 2;
+	pub const EnactmentDelay: BlockNumber = 10;
+	pub static Responses: Vec<(RequestId, Option<Balance>)> = vec![];
+	pub static ValidatorSession: Option<SessionIndex> = None;
+	pub static Validators: Vec<AccountId> = vec![];
+	pub static Offences: Vec<(SessionIndex, Vec<AccountId>, OracleOffenceKind)> = vec![];
+	pub static ClosedRounds: Vec<(RoundIndex, Vec<(AssetId, FixedU128)>)> = vec![];
+}
+
+pub const REQUESTER: AccountId = 10;
+pub const REWARD_POT: AccountId = 20;
+
+/// Records the outcome of the price requests in `Responses`.
+pub struct RecordResponses;
+
+impl primitives::traits::OnPriceResponse<AccountId> for RecordResponses {
+	fn on_response(
+		request_id: RequestId,
+		_requester: &AccountId,
+		_symbol: &[u8],
+		price: Option<Balance>,
+	) {
+		let mut responses = Responses::get();
+		responses.push((request_id, price));
+		Responses::set(responses);
+	}
+}
+
+/// Records the medians of the closed rounds in `ClosedRounds`.
+pub struct RecordClosedRounds;
+
+impl primitives::traits::OnRoundClosed for RecordClosedRounds {
+	fn on_round_closed(round: RoundIndex, prices: &[(AssetId, FixedU128)]) -> Weight {
+		let mut closed = ClosedRounds::get();
+		closed.push((round, prices.to_vec()));
+		ClosedRounds::set(closed);
+		0
+	}
+}
+
+/// Validators of `ValidatorSession` in `Validators`, recording the offences in `Offences`.
+pub struct MockValidators;
+
+impl ProviderValidators<AccountId> for MockValidators {
+	fn session_index() -> Option<SessionIndex> {
+		ValidatorSession::get()
+	}
+
+	fn validators() -> Vec<AccountId> {
+		Validators::get()
+	}
+
+	fn report_offence(session: SessionIndex, offenders: Vec<AccountId>, kind: OracleOffenceKind) {
+		let mut offences = Offences::get();
+		offences.push((session, offenders, kind));
+		Offences::set(offences);
+	}
+}
+
 impl Config for Test {
 	type WeightInfo = ();
 	type Event = Event;
+	type StalePeriod = StalePeriod;
+	type Twap = FixedTwap;
+	type TwapWindow = TwapWindow;
+	type TwapQuoteAsset = TwapQuoteAsset;
+	type RoundLength = RoundLength;
+	type RoundsPerEra = RoundsPerEra;
+	type MaxMissedRounds = MaxMissedRounds;
+	type Currency = Balances;
+	type MinRequestFee = MinRequestFee;
+	type MaxRequestPeriod = MaxRequestPeriod;
+	type MaxSymbolLength = MaxSymbolLength;
+	type RequestQuorum = RequestQuorum;
+	type OnPriceResponse = RecordResponses;
+	type OnRoundClosed = RecordClosedRounds;
+	type MaxReportsPerRound = MaxReportsPerRound;
+	type EraFinder = Oracle;
+	type EraReward = EraReward;
+	type RewardPot = RewardPot;
+	type HeartbeatPeriod = HeartbeatPeriod;
+	type HeartbeatSlash = HeartbeatSlash;
+	type MaxMissedHeartbeats = MaxMissedHeartbeats;
+	type ProviderValidators = MockValidators;
+	type Proposal = Call;
+	type PalletsOrigin = OriginCaller;
+	type Scheduler = Scheduler;
+	type MinimumEnactmentDelay = EnactmentDelay;
+}
+
+parameter_types! {
+	pub const MaximumSchedulerWeight: frame_support::weights::Weight = 1_000_000_000;
+}
+
+impl pallet_scheduler::Config for Test {
+	type Event = Event;
+	type Origin = Origin;
+	type PalletsOrigin = OriginCaller;
+	type Call = Call;
+	type MaximumWeight = MaximumSchedulerWeight;
+	type ScheduleOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxScheduledPerBlock = frame_support::traits::ConstU32<10>;
+	type WeightInfo = ();
+	type OriginPrivilegeCmp = frame_support::traits::EqualPrivilegeOnly;
+	type PreimageProvider = ();
+	type NoPreimagePostponement = ();
 }
 
 frame_support::construct_runtime!(
@@ -80,16 +210,38 @@ frame_support::construct_runtime!(
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		Scheduler: pallet_scheduler::{Pallet, Call, Storage, Event<T>},
 		Oracle: oracle::{Pallet, Call, Config<T>, Storage, Event<T>}
 	}
 );
 
+/// Report of `price` for `asset` in the current round.
+pub fn price_report(asset: AssetId, price: Balance) -> VersionedPriceReport {
+	VersionedPriceReport::V1(PriceReport {
+		asset,
+		price,
+		round: Oracle::current_round(),
+		timestamp: 0,
+	})
+}
+
+/// Price of a report of `price`
+pub fn reported(price: Balance) -> FixedU128 {
+	oracle::price_from_reported(price)
+}
+
 pub fn new_test_ext() -> sp_io::TestExternalities {
 	let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
 
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(REQUESTER, 1_000), (REWARD_POT, 1_000)],
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
 	let oracles: Vec<AccountId> = vec![1];
 	let provider_count = 5;
-	oracle::GenesisConfig::<Test> { oracles, provider_count }
+	oracle::GenesisConfig::<Test> { oracles, provider_count, prices: vec![] }
 		.assimilate_storage(&mut storage)
 		.unwrap();
 
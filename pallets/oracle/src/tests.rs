@@ -1,7 +1,23 @@
 #![cfg(test)]
 
-use crate::{mock::*, Error};
-use frame_support::{assert_noop, assert_ok, error::BadOrigin};
+use crate::{
+	mock::*, Call as OracleCall, CheckProviderReport, Error, OracleOffence, OracleOffenceKind,
+	PriceReport, PriceSource, RawEvent, Slashes, SocketPrices, VersionedPriceReport,
+};
+use frame_support::{
+	assert_noop, assert_ok,
+	error::BadOrigin,
+	storage::{unhashed, StorageDoubleMap, StorageMap},
+	traits::{Currency, OnInitialize, OnRuntimeUpgrade, StorageVersion},
+	weights::{GetDispatchInfo, Pays},
+};
+use primitives::{AssetId, SocketIndex};
+use sp_runtime::{
+	traits::SignedExtension,
+	transaction_validity::{InvalidTransaction, TransactionValidity},
+	FixedPointNumber, FixedU128, Perbill,
+};
+use sp_staking::offence::Offence;
 
 #[test]
 fn add_oracle_provider_works() {
@@ -19,15 +35,50 @@ fn oracle_report_works() {
 
 		assert_ok!(Oracle::register_operator(Origin::root(), 1, provider));
 
-		assert_ok!(Oracle::report(Origin::signed(provider.into()), 1, 1, 2));
+		assert_ok!(Oracle::report(Origin::signed(provider.into()), 1, price_report(1, 2)));
 
 		// Oracle should only be able to submit data in a given slot
 		assert_noop!(
-			Oracle::report(Origin::signed(provider.into()), 2, 1, 2),
+			Oracle::report(Origin::signed(provider.into()), 2, price_report(1, 2)),
 			Error::<Test>::WrongSocket
 		);
 
-		assert_eq!(Oracle::asset_price(1), Some(vec! {0,2,0,0,0}));
+		assert_eq!(Oracle::reports(1), vec![(1, reported(2))]);
+	})
+}
+
+#[test]
+fn reports_are_free_and_limited_per_round() {
+	new_test_ext().execute_with(|| {
+		let provider = 1u64;
+		assert_ok!(Oracle::register_operator(Origin::root(), 0, provider));
+		let validate = |who: u64, asset: AssetId| -> TransactionValidity {
+			let call = Call::Oracle(OracleCall::report(0, price_report(asset, 10)));
+			CheckProviderReport::<Test>::new().validate(&who, &call, &call.get_dispatch_info(), 0)
+		};
+		assert_eq!(
+			OracleCall::<Test>::report(0, price_report(1, 10)).get_dispatch_info().pays_fee,
+			Pays::No
+		);
+		assert_eq!(validate(2, 1), Err(InvalidTransaction::Call.into()));
+		assert_ok!(validate(provider, 1));
+
+		for asset in 1..=MaxReportsPerRound::get() {
+			assert_ok!(Oracle::report(Origin::signed(provider), 0, price_report(asset, 10)));
+		}
+		assert_eq!(Oracle::round_reports(provider), (0, MaxReportsPerRound::get()));
+		assert_eq!(validate(provider, 1), Err(InvalidTransaction::Call.into()));
+		assert_eq!(validate(provider, 10), Err(InvalidTransaction::ExhaustsResources.into()));
+		assert_noop!(
+			Oracle::report(Origin::signed(provider), 0, price_report(10, 10)),
+			Error::<Test>::TooManyReports
+		);
+
+		// The allowance is renewed with the round
+		System::set_block_number(RoundLength::get());
+		assert_ok!(validate(provider, 10));
+		assert_ok!(Oracle::report(Origin::signed(provider), 0, price_report(10, 10)));
+		assert_eq!(Oracle::round_reports(provider), (1, 1));
 	})
 }
 
@@ -39,7 +90,6 @@ fn oracle_slash_works() {
 		let provider_3 = 3u64;
 		let provider_4 = 4u64;
 		let provider_5 = 5u64;
-		let slasher = 6u64;
 
 		// setup batch of oracle providers
 		assert_ok!(Oracle::register_operator(Origin::root(), 0, provider_1));
@@ -49,20 +99,56 @@ fn oracle_slash_works() {
 		assert_ok!(Oracle::register_operator(Origin::root(), 4, provider_5));
 
 		// setup batch of oracle values [1,2,1,2,1]
-		assert_ok!(Oracle::report(Origin::signed(provider_1.into()), 0, 1, 1));
-		assert_ok!(Oracle::report(Origin::signed(provider_2.into()), 1, 1, 2));
-		assert_ok!(Oracle::report(Origin::signed(provider_3.into()), 2, 1, 1));
-		assert_ok!(Oracle::report(Origin::signed(provider_4.into()), 3, 1, 2));
-		assert_ok!(Oracle::report(Origin::signed(provider_5.into()), 4, 1, 1));
-		assert_eq!(Oracle::asset_price(1), Some(vec! {1,2,1,2,1}));
+		assert_ok!(Oracle::report(Origin::signed(provider_1.into()), 0, price_report(1, 1)));
+		assert_ok!(Oracle::report(Origin::signed(provider_2.into()), 1, price_report(1, 2)));
+		assert_ok!(Oracle::report(Origin::signed(provider_3.into()), 2, price_report(1, 1)));
+		assert_ok!(Oracle::report(Origin::signed(provider_4.into()), 3, price_report(1, 2)));
+		assert_ok!(Oracle::report(Origin::signed(provider_5.into()), 4, price_report(1, 1)));
+		assert_eq!(
+			Oracle::reports(1),
+			vec![
+				(0, reported(1)),
+				(1, reported(2)),
+				(2, reported(1)),
+				(3, reported(2)),
+				(4, reported(1))
+			]
+		);
 
 		// and one of providers submit an manipulated value which goes out of acceptable error range
-		assert_ok!(Oracle::report(Origin::signed(provider_1.into()), 0, 1, 4));
-		assert_eq!(Oracle::asset_price(1), Some(vec! {4,2,1,2,1}));
-		// should detect outlier and slash the provider
-		assert_ok!(Oracle::slash(Origin::signed(slasher), 0, 1));
+		System::set_block_number(5);
+		assert_ok!(Oracle::report(Origin::signed(provider_1.into()), 0, price_report(1, 4)));
+		assert_eq!(
+			Oracle::reports(1),
+			vec![
+				(0, reported(4)),
+				(1, reported(2)),
+				(2, reported(1)),
+				(3, reported(2)),
+				(4, reported(1))
+			]
+		);
+		// should detect outlier and slash the provider, which only governance can do
+		assert_noop!(Oracle::slash(Origin::signed(provider_2), 0, 1), BadOrigin);
+		assert_ok!(Oracle::slash(Origin::root(), 0, 1));
 		// slot for oracle submission is now empty
 		assert_eq!(Oracle::provider_at(0), None);
+		assert_eq!(Oracle::slashes(Oracle::current_era()), vec![provider_1]);
+	})
+}
+
+#[test]
+fn outliers_of_any_batch_are_determined_without_overflow() {
+	new_test_ext().execute_with(|| {
+		assert!(!Oracle::determine_outlier(vec![], 1));
+		assert!(!Oracle::determine_outlier(vec![0, 0], 1));
+		assert_eq!(Oracle::get_median(vec![]), 0);
+
+		// The interquartile range of 27 reaches below zero
+		assert!(!Oracle::determine_outlier(vec![1, 2, 10, 20, 30], 1));
+		assert!(!Oracle::determine_outlier(vec![1, 2, 10, 20, 30], 47));
+		assert!(Oracle::determine_outlier(vec![1, 2, 10, 20, 30], 48));
+		assert!(!Oracle::determine_outlier(vec![u128::MAX - 1, u128::MAX], u128::MAX));
 	})
 }
 
@@ -84,15 +170,24 @@ fn oracle_excludes_zeros_and_return_median() {
 		assert_ok!(Oracle::register_operator(Origin::root(), 4, provider_5));
 
 		// setup batch of oracle values [0,0,1,2,3,4]
-		assert_ok!(Oracle::report(Origin::signed(provider_1.into()), 0, 1, 0));
-		assert_ok!(Oracle::report(Origin::signed(provider_2.into()), 1, 1, 0));
-		assert_ok!(Oracle::report(Origin::signed(provider_3.into()), 2, 1, 1));
-		assert_ok!(Oracle::report(Origin::signed(provider_4.into()), 3, 1, 2));
-		assert_ok!(Oracle::report(Origin::signed(provider_5.into()), 4, 1, 3));
-		assert_eq!(Oracle::asset_price(1), Some(vec! {0,0,1,2,3}));
+		assert_ok!(Oracle::report(Origin::signed(provider_1.into()), 0, price_report(1, 0)));
+		assert_ok!(Oracle::report(Origin::signed(provider_2.into()), 1, price_report(1, 0)));
+		assert_ok!(Oracle::report(Origin::signed(provider_3.into()), 2, price_report(1, 1)));
+		assert_ok!(Oracle::report(Origin::signed(provider_4.into()), 3, price_report(1, 2)));
+		assert_ok!(Oracle::report(Origin::signed(provider_5.into()), 4, price_report(1, 3)));
+		assert_eq!(
+			Oracle::reports(1),
+			vec![
+				(0, reported(0)),
+				(1, reported(0)),
+				(2, reported(1)),
+				(3, reported(2)),
+				(4, reported(3))
+			]
+		);
 
 		// and the median should be 2
-		assert_eq!(Oracle::get_median(Oracle::asset_price(1).unwrap()), 2);
+		assert_eq!(Oracle::median(1), Some(reported(2)));
 	})
 }
 
@@ -116,15 +211,520 @@ fn oracle_excludes_zeros_and_return_median_even() {
 		assert_ok!(Oracle::register_operator(Origin::root(), 5, provider_6));
 
 		// setup batch of oracle values [0,0,1,2,3,4]
-		assert_ok!(Oracle::report(Origin::signed(provider_1.into()), 0, 1, 0));
-		assert_ok!(Oracle::report(Origin::signed(provider_2.into()), 1, 1, 0));
-		assert_ok!(Oracle::report(Origin::signed(provider_3.into()), 2, 1, 1));
-		assert_ok!(Oracle::report(Origin::signed(provider_4.into()), 3, 1, 2));
-		assert_ok!(Oracle::report(Origin::signed(provider_5.into()), 4, 1, 3));
-		assert_ok!(Oracle::report(Origin::signed(provider_6.into()), 5, 1, 4));
-		assert_eq!(Oracle::asset_price(1), Some(vec! {0,0,1,2,3,4}));
+		assert_ok!(Oracle::report(Origin::signed(provider_1.into()), 0, price_report(1, 0)));
+		assert_ok!(Oracle::report(Origin::signed(provider_2.into()), 1, price_report(1, 0)));
+		assert_ok!(Oracle::report(Origin::signed(provider_3.into()), 2, price_report(1, 1)));
+		assert_ok!(Oracle::report(Origin::signed(provider_4.into()), 3, price_report(1, 2)));
+		assert_ok!(Oracle::report(Origin::signed(provider_5.into()), 4, price_report(1, 3)));
+		assert_ok!(Oracle::report(Origin::signed(provider_6.into()), 5, price_report(1, 4)));
+		assert_eq!(
+			Oracle::reports(1),
+			vec![
+				(0, reported(0)),
+				(1, reported(0)),
+				(2, reported(1)),
+				(3, reported(2)),
+				(4, reported(3)),
+				(5, reported(4))
+			]
+		);
 
 		// and the median should be 3
-		assert_eq!(Oracle::get_median(Oracle::asset_price(1).unwrap()), 3);
+		assert_eq!(Oracle::median(1), Some(reported(3)));
+	})
+}
+
+#[test]
+fn stale_price_falls_back_to_twap() {
+	new_test_ext().execute_with(|| {
+		let provider = 1u64;
+		assert_ok!(Oracle::register_operator(Origin::root(), 0, provider));
+		assert_ok!(Oracle::report(Origin::signed(provider), 0, price_report(2, 20)));
+		assert_ok!(Oracle::report(Origin::signed(provider), 0, price_report(MTR, 10)));
+		assert_eq!(Oracle::price_with_source(2), Ok((reported(20), PriceSource::Oracle)));
+
+		// Without the fallback the stale reports are used
+		System::set_block_number(11);
+		assert_ok!(Oracle::report(Origin::signed(provider), 0, price_report(MTR, 10)));
+		assert!(Oracle::is_stale(2));
+		assert_eq!(Oracle::price_with_source(2), Ok((reported(20), PriceSource::StaleOracle)));
+
+		assert_noop!(Oracle::set_twap_fallback(Origin::signed(provider), 2, true), BadOrigin);
+		assert_ok!(Oracle::set_twap_fallback(Origin::root(), 2, true));
+		assert_eq!(Oracle::price_with_source(2), Ok((reported(15), PriceSource::Twap)));
+		assert_eq!(Oracle::price(2), Ok(reported(15)));
+
+		// The quote price has to be fresh as well
+		System::set_block_number(21);
+		assert_eq!(Oracle::price_with_source(2), Ok((reported(20), PriceSource::StaleOracle)));
+
+		assert_noop!(Oracle::price_with_source(3), Error::<Test>::PriceDoesNotExist);
+	})
+}
+
+#[test]
+fn report_is_accepted_once_per_round() {
+	new_test_ext().execute_with(|| {
+		let provider = 1u64;
+		assert_ok!(Oracle::register_operator(Origin::root(), 0, provider));
+		assert_eq!(Oracle::current_round(), 0);
+
+		assert_ok!(Oracle::report(Origin::signed(provider), 0, price_report(1, 2)));
+		assert_noop!(
+			Oracle::report(Origin::signed(provider), 0, price_report(1, 3)),
+			Error::<Test>::DuplicateReport
+		);
+		// Other assets can still be reported in the round
+		assert_ok!(Oracle::report(Origin::signed(provider), 0, price_report(2, 3)));
+
+		System::set_block_number(5);
+		assert_eq!(Oracle::current_round(), 1);
+		let report = PriceReport { asset: 1, price: 3, round: 0, timestamp: 0 };
+		assert_noop!(
+			Oracle::report(Origin::signed(provider), 0, VersionedPriceReport::V1(report)),
+			Error::<Test>::WrongRound
+		);
+		let report = PriceReport { round: 2, ..report };
+		assert_noop!(
+			Oracle::report(Origin::signed(provider), 0, VersionedPriceReport::V1(report)),
+			Error::<Test>::WrongRound
+		);
+		assert_ok!(Oracle::report(Origin::signed(provider), 0, price_report(1, 3)));
+		assert_eq!(Oracle::reports(1), vec![(0, reported(3))]);
+		assert_eq!(
+			Oracle::last_report(provider, 1),
+			Some(PriceReport { asset: 1, price: 3, round: 1, timestamp: 0 })
+		);
+	})
+}
+
+#[test]
+fn era_reward_is_shared_by_providers_not_slashed() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Oracle::register_operator(Origin::root(), 1, 2));
+		assert_ok!(Oracle::register_operator(Origin::root(), 2, 3));
+		Slashes::<Test>::append(0, Some(3));
+
+		// All providers report in both rounds of era 0
+		for block in [1, 5] {
+			System::set_block_number(block);
+			Oracle::on_initialize(block);
+			for provider in 1..=3u64 {
+				let socket = provider as SocketIndex - 1;
+				assert_ok!(Oracle::report(Origin::signed(provider), socket, price_report(1, 10)));
+			}
+		}
+		System::set_block_number(10);
+		Oracle::on_initialize(10);
+		assert_eq!(Oracle::current_era(), 1);
+		assert_eq!(Balances::free_balance(REWARD_POT), 1_000);
+
+		// Era 0 is paid once the era finder moved past it, without the slashed provider
+		System::set_block_number(11);
+		Oracle::on_initialize(11);
+		assert_eq!(Oracle::payout_era(), 1);
+		assert_eq!((Balances::free_balance(1), Balances::free_balance(2)), (50, 50));
+		assert_eq!(Balances::free_balance(3), 0);
+		assert_eq!(Balances::free_balance(REWARD_POT), 900);
+		System::assert_has_event(Event::Oracle(RawEvent::ProviderRewarded(0, 2, 50)));
+	})
+}
+
+#[test]
+fn providers_missing_rounds_are_rotated_out() {
+	new_test_ext().execute_with(|| {
+		for provider in 1..=4u64 {
+			assert_ok!(Oracle::register_operator(Origin::root(), provider as u32, provider));
+		}
+
+		// Round 0: provider 4 reports 50% above the median, provider 2 misses the round
+		assert_ok!(Oracle::report(Origin::signed(1), 1, price_report(1, 10)));
+		assert_ok!(Oracle::report(Origin::signed(3), 3, price_report(1, 10)));
+		assert_ok!(Oracle::report(Origin::signed(4), 4, price_report(1, 15)));
+		System::set_block_number(5);
+		Oracle::on_initialize(5);
+		assert_eq!(Oracle::provider_metrics(4).average_deviation(), Perbill::from_percent(50));
+		assert_eq!(Oracle::provider_metrics(2).missed_rounds, 1);
+
+		// Round 1: providers 1 and 2 miss the round
+		assert_ok!(Oracle::report(Origin::signed(3), 3, price_report(1, 10)));
+		assert_ok!(Oracle::report(Origin::signed(4), 4, price_report(1, 10)));
+		System::set_block_number(10);
+		Oracle::on_initialize(10);
+
+		assert_eq!(Oracle::provider_metrics(4).submissions, 2);
+		assert_eq!(Oracle::provider_metrics(4).average_deviation(), Perbill::from_percent(25));
+		assert_eq!(Oracle::provider_metrics(1).missed_rounds, 1);
+		assert_eq!(Oracle::provider_metrics(2).missed_rounds, 2);
+
+		// Missing half of the era is tolerated, missing all of it is not
+		assert_eq!(Oracle::current_era(), 1);
+		System::assert_has_event(Event::Oracle(RawEvent::ProviderRotatedOut(0, 2, 2)));
+		assert_eq!(Oracle::provider_at(2), None);
+		assert_eq!(Oracle::oracle(2), None);
+		assert_eq!(Oracle::provider_at(1), Some(1));
+		assert_eq!(Oracle::era_missed_rounds(1), 0);
+	})
+}
+
+#[test]
+fn closed_rounds_hand_over_their_medians() {
+	new_test_ext().execute_with(|| {
+		for provider in 1..=3u64 {
+			assert_ok!(Oracle::register_operator(Origin::root(), provider as u32, provider));
+		}
+		assert_ok!(Oracle::report(Origin::signed(1), 1, price_report(1, 10)));
+		assert_ok!(Oracle::report(Origin::signed(2), 2, price_report(1, 12)));
+		assert_ok!(Oracle::report(Origin::signed(3), 3, price_report(1, 15)));
+		assert_ok!(Oracle::report(Origin::signed(1), 1, price_report(2, 3)));
+		System::set_block_number(5);
+		Oracle::on_initialize(5);
+		assert_eq!(ClosedRounds::get(), vec![(0, vec![(1, reported(12)), (2, reported(3))])]);
+
+		// Rounds without reports close without prices
+		System::set_block_number(10);
+		Oracle::on_initialize(10);
+		assert_eq!(ClosedRounds::get().pop(), Some((1, vec![])));
+	})
+}
+
+#[test]
+fn providers_without_heartbeats_are_slashed_and_rotated_out() {
+	new_test_ext().execute_with(|| {
+		for provider in 2..=3u64 {
+			assert_ok!(Oracle::register_operator(Origin::root(), provider as u32 - 1, provider));
+			Balances::make_free_balance_be(&provider, 100);
+		}
+		let validate = |who: u64, socket: SocketIndex| -> TransactionValidity {
+			let call = Call::Oracle(OracleCall::heartbeat(socket));
+			CheckProviderReport::<Test>::new().validate(&who, &call, &call.get_dispatch_info(), 0)
+		};
+		assert_eq!(OracleCall::<Test>::heartbeat(1).get_dispatch_info().pays_fee, Pays::No);
+
+		// Session 0: provider 1 reports, provider 2 sends a heartbeat and provider 3 neither
+		assert_ok!(Oracle::report(Origin::signed(1), 0, price_report(1, 10)));
+		assert_eq!(validate(2, 2), Err(InvalidTransaction::Call.into()));
+		assert_ok!(validate(2, 1));
+		assert_ok!(Oracle::heartbeat(Origin::signed(2), 1));
+		assert_eq!(validate(2, 1), Err(InvalidTransaction::Call.into()));
+		assert_noop!(Oracle::heartbeat(Origin::signed(2), 1), Error::<Test>::DuplicateHeartbeat);
+		assert!(Oracle::is_online(0, 0) && Oracle::is_online(0, 1));
+		assert!(!Oracle::is_online(0, 2));
+
+		// The slash of provider 3 goes to the reward pot
+		System::set_block_number(10);
+		Oracle::on_initialize(10);
+		System::assert_has_event(Event::Oracle(RawEvent::HeartbeatMissed(0, 3, 1, 10)));
+		assert_eq!((Balances::free_balance(2), Balances::free_balance(3)), (100, 90));
+		assert_eq!(Balances::free_balance(REWARD_POT), 1_010);
+		assert_eq!(Oracle::missed_heartbeats(3), 1);
+		assert_eq!(Oracle::received_heartbeats(0), Vec::<u8>::new());
+
+		// Missing a second session in a row costs provider 3 its socket
+		assert_ok!(Oracle::heartbeat(Origin::signed(1), 0));
+		assert_ok!(Oracle::heartbeat(Origin::signed(2), 1));
+		System::set_block_number(20);
+		Oracle::on_initialize(20);
+		System::assert_has_event(Event::Oracle(RawEvent::HeartbeatMissed(1, 3, 2, 10)));
+		System::assert_has_event(Event::Oracle(RawEvent::ProviderWentOffline(1, 3)));
+		assert_eq!(Oracle::provider_at(2), None);
+		assert_eq!(Oracle::oracle(3), None);
+		assert_eq!(Oracle::missed_heartbeats(3), 0);
+		assert_eq!(Oracle::provider_at(1), Some(2));
+	})
+}
+
+#[test]
+fn price_request_is_aggregated_at_quorum() {
+	new_test_ext().execute_with(|| {
+		for provider in 1..=3u64 {
+			assert_ok!(Oracle::register_operator(Origin::root(), provider as u32, provider));
+		}
+		let symbol = b"BTC/USD".to_vec();
+		assert_noop!(
+			Oracle::request_price(Origin::signed(REQUESTER), symbol.clone(), 100, 1),
+			Error::<Test>::InvalidDeadline
+		);
+		assert_noop!(
+			Oracle::request_price(Origin::signed(REQUESTER), symbol.clone(), 100, 12),
+			Error::<Test>::InvalidDeadline
+		);
+		assert_noop!(
+			Oracle::request_price(Origin::signed(REQUESTER), symbol.clone(), 9, 5),
+			Error::<Test>::InsufficientFee
+		);
+		assert_noop!(
+			Oracle::request_price(Origin::signed(REQUESTER), vec![b'A'; 17], 100, 5),
+			Error::<Test>::SymbolTooLong
+		);
+
+		assert_ok!(Oracle::request_price(Origin::signed(REQUESTER), symbol.clone(), 100, 5));
+		assert_eq!(Balances::reserved_balance(REQUESTER), 100);
+		System::assert_last_event(Event::Oracle(RawEvent::PriceRequested(
+			0, REQUESTER, symbol, 100, 5,
+		)));
+
+		assert_ok!(Oracle::answer_request(Origin::signed(1), 0, 100));
+		assert_noop!(
+			Oracle::answer_request(Origin::signed(1), 0, 100),
+			Error::<Test>::AlreadyAnswered
+		);
+		assert_noop!(
+			Oracle::answer_request(Origin::signed(4), 0, 100),
+			Error::<Test>::WrongProvider
+		);
+		assert_noop!(Oracle::answer_request(Origin::signed(2), 0, 0), Error::<Test>::InvalidPrice);
+		assert_ok!(Oracle::answer_request(Origin::signed(2), 0, 120));
+		assert!(Responses::get().is_empty());
+
+		// The third answer completes the quorum
+		assert_ok!(Oracle::answer_request(Origin::signed(3), 0, 110));
+		System::assert_last_event(Event::Oracle(RawEvent::RequestFulfilled(0, 110)));
+		assert_eq!(Responses::get(), vec![(0, Some(110))]);
+		assert_eq!(Oracle::price_request(0), None);
+		// Fee is shared among the providers, the remainder is refunded
+		assert_eq!(Balances::free_balance(1), 33);
+		assert_eq!(Balances::free_balance(3), 33);
+		assert_eq!(Balances::reserved_balance(REQUESTER), 0);
+		assert_eq!(Balances::free_balance(REQUESTER), 901);
+	})
+}
+
+#[test]
+fn price_request_times_out_at_deadline() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Oracle::request_price(Origin::signed(REQUESTER), b"BTC/USD".to_vec(), 100, 5));
+		assert_ok!(Oracle::answer_request(Origin::signed(1), 0, 100));
+
+		System::set_block_number(4);
+		Oracle::on_initialize(4);
+		assert!(Oracle::price_request(0).is_some());
+
+		System::set_block_number(5);
+		Oracle::on_initialize(5);
+		System::assert_has_event(Event::Oracle(RawEvent::RequestTimedOut(0)));
+		assert_eq!(Responses::get(), vec![(0, None)]);
+		assert_eq!(Oracle::price_request(0), None);
+		assert_eq!(Balances::free_balance(REQUESTER), 1_000);
+		assert_noop!(
+			Oracle::answer_request(Origin::signed(1), 0, 100),
+			Error::<Test>::UnknownRequest
+		);
+	})
+}
+
+#[test]
+fn provider_set_is_listed_by_socket() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Oracle::register_operator(Origin::root(), 2, 3));
+		assert_ok!(Oracle::register_operator(Origin::root(), 0, 2));
+		assert_ok!(Oracle::deregister_operator(Origin::root(), 2, 3));
+
+		let mut providers = Oracle::providers();
+		providers.sort();
+		assert_eq!(providers, vec![1, 2]);
+		assert_eq!(Oracle::slot_assignments(), vec![(0, 2)]);
+		assert_eq!(Oracle::slashes(1), Vec::<u64>::new());
+	})
+}
+
+#[test]
+fn genesis_oracles_report_from_their_sockets() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Oracle::slot_assignments(), vec![(0, 1)]);
+		assert_eq!(Oracle::oracle(1), Some(0));
+		assert_ok!(Oracle::report(Origin::signed(1), 0, price_report(1, 2)));
+		assert_eq!(Oracle::reports(1), vec![(0, reported(2))]);
+	})
+}
+
+#[test]
+fn rotate_slot_replaces_provider() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Oracle::rotate_slot(Origin::signed(1), 0, 7), BadOrigin);
+		assert_noop!(Oracle::rotate_slot(Origin::root(), 5, 7), Error::<Test>::WrongSocket);
+		assert_noop!(
+			Oracle::rotate_slot(Origin::root(), 1, 1),
+			Error::<Test>::ProviderAlreadyRegistered
+		);
+
+		assert_ok!(Oracle::rotate_slot(Origin::root(), 0, 7));
+		System::assert_last_event(Event::Oracle(RawEvent::SlotRotated(0, Some(1), 7)));
+		assert_eq!(Oracle::providers(), vec![7]);
+		assert_eq!(Oracle::slot_assignments(), vec![(0, 7)]);
+		assert_noop!(
+			Oracle::report(Origin::signed(1), 0, price_report(1, 2)),
+			Error::<Test>::WrongProvider
+		);
+		assert_ok!(Oracle::report(Origin::signed(7), 0, price_report(1, 2)));
+
+		// Empty sockets are filled as well
+		assert_ok!(Oracle::rotate_slot(Origin::root(), 3, 8));
+		System::assert_last_event(Event::Oracle(RawEvent::SlotRotated(3, None, 8)));
+		assert_eq!(Oracle::slot_assignments(), vec![(0, 7), (3, 8)]);
+	})
+}
+
+#[test]
+fn reports_are_bounded_by_provider_count() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Oracle::register_operator(Origin::root(), 4, 5));
+		assert_ok!(Oracle::report(Origin::signed(1), 0, price_report(1, 2)));
+		assert_ok!(Oracle::report(Origin::signed(5), 4, price_report(1, 4)));
+		assert_eq!(Oracle::socket_price(1, 4), Some((reported(4), 1)));
+		assert_eq!(Oracle::median(1), Some(reported(4)));
+
+		// Shrinking the provider count drops the reports of the sockets above it
+		assert_ok!(Oracle::set_validator_count(Origin::root(), 4));
+		assert_eq!(Oracle::reports(1), vec![(0, reported(2))]);
+		assert_eq!(Oracle::median(1), Some(reported(2)));
+		System::set_block_number(5);
+		assert_noop!(
+			Oracle::report(Origin::signed(5), 4, price_report(1, 4)),
+			Error::<Test>::WrongSocket
+		);
+
+		// Growing it keeps the existing reports in place
+		assert_ok!(Oracle::set_validator_count(Origin::root(), 6));
+		assert_eq!(Oracle::reports(1), vec![(0, reported(2)), (4, reported(4))]);
+		assert_eq!(Oracle::median(2), None);
+	})
+}
+
+#[test]
+fn provider_count_changes_are_enacted_after_the_delay() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Oracle::schedule_validator_count(Origin::signed(1), 7, EnactmentDelay::get()),
+			BadOrigin
+		);
+		assert_noop!(
+			Oracle::schedule_validator_count(Origin::root(), 7, EnactmentDelay::get() - 1),
+			Error::<Test>::DelayTooShort
+		);
+		assert_noop!(
+			Oracle::cancel_scheduled_validator_count(Origin::root()),
+			Error::<Test>::NotScheduled
+		);
+
+		assert_ok!(Oracle::schedule_validator_count(Origin::root(), 7, EnactmentDelay::get()));
+		System::assert_last_event(Event::Oracle(RawEvent::ProviderCountScheduled(7, 11)));
+		// Only one change can be pending
+		assert_noop!(
+			Oracle::schedule_validator_count(Origin::root(), 8, EnactmentDelay::get()),
+			Error::<Test>::ScheduleFailed
+		);
+
+		Scheduler::on_initialize(10);
+		assert_eq!(Oracle::provider_count(), 5);
+		Scheduler::on_initialize(11);
+		assert_eq!(Oracle::provider_count(), 7);
+
+		// A cancelled change is never enacted
+		System::set_block_number(11);
+		assert_ok!(Oracle::schedule_validator_count(Origin::root(), 3, EnactmentDelay::get()));
+		assert_ok!(Oracle::cancel_scheduled_validator_count(Origin::root()));
+		System::assert_last_event(Event::Oracle(RawEvent::ProviderCountScheduleCancelled));
+		Scheduler::on_initialize(11 + EnactmentDelay::get());
+		assert_eq!(Oracle::provider_count(), 7);
+	})
+}
+
+#[test]
+fn integer_prices_are_scaled_on_upgrade() {
+	new_test_ext().execute_with(|| {
+		// Prices stored before the upgrade have `PRICE_DECIMALS` decimals
+		StorageVersion::new(0).put::<Oracle>();
+		unhashed::put(&SocketPrices::<Test>::hashed_key_for(1, 0), &(250_000_000u128, 1u64));
+		Oracle::on_runtime_upgrade();
+		assert_eq!(
+			Oracle::socket_price(1, 0),
+			Some((FixedU128::saturating_from_rational(5, 2), 1))
+		);
+		assert_eq!(StorageVersion::get::<Oracle>(), 1);
+
+		// Once upgraded the prices are kept
+		Oracle::on_runtime_upgrade();
+		assert_eq!(Oracle::median(1), Some(FixedU128::saturating_from_rational(5, 2)));
+	})
+}
+
+#[test]
+fn providers_follow_validators_in_validator_mode() {
+	new_test_ext().execute_with(|| {
+		ValidatorSession::set(Some(3));
+		Validators::set(vec![4, 5, 6]);
+		Oracle::on_initialize(1);
+		System::assert_last_event(Event::Oracle(RawEvent::ProvidersSynced(3, 3)));
+		assert_eq!(Oracle::slot_assignments(), vec![(0, 4), (1, 5), (2, 6)]);
+		assert_eq!(Oracle::provider_count(), 3);
+		// The genesis provider is not a validator
+		assert_eq!(Oracle::oracle(1), None);
+		assert!(!Oracle::operator(1));
+		assert_noop!(
+			Oracle::report(Origin::signed(1), 0, price_report(1, 10)),
+			Error::<Test>::WrongProvider
+		);
+		assert_ok!(Oracle::report(Origin::signed(5), 1, price_report(1, 10)));
+
+		// Governance can not change the providers
+		assert_noop!(Oracle::register_operator(Origin::root(), 3, 7), Error::<Test>::ValidatorMode);
+		assert_noop!(
+			Oracle::deregister_operator(Origin::root(), 0, 4),
+			Error::<Test>::ValidatorMode
+		);
+		assert_noop!(Oracle::rotate_slot(Origin::root(), 0, 7), Error::<Test>::ValidatorMode);
+		assert_noop!(Oracle::set_validator_count(Origin::root(), 5), Error::<Test>::ValidatorMode);
+
+		// Nothing changes within the session
+		Oracle::on_initialize(2);
+		assert_eq!(Oracle::slot_assignments(), vec![(0, 4), (1, 5), (2, 6)]);
+
+		ValidatorSession::set(Some(4));
+		Validators::set(vec![6, 7]);
+		Oracle::on_initialize(3);
+		System::assert_last_event(Event::Oracle(RawEvent::ProvidersSynced(4, 2)));
+		assert_eq!(Oracle::slot_assignments(), vec![(0, 6), (1, 7)]);
+		assert_eq!(Oracle::provider_count(), 2);
+		assert_eq!((Oracle::oracle(4), Oracle::oracle(5)), (None, None));
+		assert_eq!(Oracle::oracle(6), Some(0));
+	})
+}
+
+#[test]
+fn provider_misbehavior_is_reported_as_offence() {
+	new_test_ext().execute_with(|| {
+		ValidatorSession::set(Some(1));
+		Validators::set(vec![1, 2, 3, 4, 5]);
+		Oracle::on_initialize(1);
+
+		// Provider 1 reports an outlier and providers 4 and 5 neither report nor send heartbeats
+		for (provider, price) in [(1, 4), (2, 2), (3, 1)] {
+			assert_ok!(Oracle::report(
+				Origin::signed(provider),
+				provider as u32 - 1,
+				price_report(1, price)
+			));
+		}
+		assert_ok!(Oracle::report(Origin::signed(4), 3, price_report(2, 1)));
+		assert_ok!(Oracle::slash(Origin::root(), 0, 1));
+		assert_eq!(Offences::get(), vec![(1, vec![1], OracleOffenceKind::Outlier)]);
+
+		System::set_block_number(10);
+		Oracle::on_initialize(10);
+		assert_eq!(
+			Offences::get(),
+			vec![
+				(1, vec![1], OracleOffenceKind::Outlier),
+				(1, vec![5], OracleOffenceKind::Unresponsive)
+			]
+		);
+
+		// Only correlated misbehavior is slashed
+		assert_eq!(OracleOffence::<AccountId>::slash_fraction(1, 10), Perbill::zero());
+		assert_eq!(
+			OracleOffence::<AccountId>::slash_fraction(5, 10),
+			Perbill::from_rational(63u32, 1_000)
+		);
 	})
 }
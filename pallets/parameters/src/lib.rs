@@ -0,0 +1,272 @@
+// Ensure we're `no_std` when compiling for Wasm.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Parameters Pallet
+//!
+//! Stores runtime parameters which governance can tune without a runtime upgrade, like the
+//! liquidation grace period of the vaults, the lifetime of outbound bridge transfers or the
+//! staking inflation curve.
+//!
+//! Each parameter is a variant of `Parameter` carrying a value of its type, stored under the
+//! matching `ParameterKey`. The runtime reads them through the `BalanceParameter`,
+//! `BlockNumberParameter` and `PerbillParameter` adapters, keyed by the types in `keys`, in place
+//! of a `parameter_types!` constant. The adapters fall back to the constant until governance sets
+//! the parameter with `set_parameter` and again once it is reset with `reset_parameter`.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::traits::Get;
+use primitives::Balance;
+use scale_info::TypeInfo;
+use sp_runtime::{traits::Zero, Perbill, RuntimeDebug};
+use sp_std::marker::PhantomData;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+pub use pallet::*;
+
+/// Key a parameter is stored under.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum ParameterKey {
+	LiquidationGracePeriod,
+	MaxPriceAge,
+	MaxMtrMintPerBlock,
+	MaxMtrMintPerEra,
+	PurgeReward,
+	BridgeTransferLifetime,
+	MinInflation,
+	MaxInflation,
+	IdealStake,
+	InflationFalloff,
+}
+
+/// Parameter along with its value.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum Parameter<BlockNumber> {
+	/// Number of blocks a warned vault has to stay undercollateralized to be liquidated
+	LiquidationGracePeriod(BlockNumber),
+	/// Maximum number of blocks since the oracle report of a price the vaults rely on
+	MaxPriceAge(BlockNumber),
+	/// Maximum amount of MTR minted in a block
+	MaxMtrMintPerBlock(Balance),
+	/// Maximum amount of MTR minted in a mint era
+	MaxMtrMintPerEra(Balance),
+	/// Native currency paid for every closed vault or pool purged
+	PurgeReward(Balance),
+	/// Number of blocks after which an unanswered outbound bridge transfer is refunded
+	BridgeTransferLifetime(BlockNumber),
+	/// Yearly staking inflation without any stake
+	MinInflation(Perbill),
+	/// Yearly staking inflation at the ideal stake
+	MaxInflation(Perbill),
+	/// Share of the issuance staked at which the staking inflation peaks
+	IdealStake(Perbill),
+	/// Stake above the ideal stake which halves the staking inflation above the minimum, at least
+	/// 1%
+	InflationFalloff(Perbill),
+}
+
+impl<BlockNumber: Copy + Zero> Parameter<BlockNumber> {
+	/// Key the parameter is stored under
+	pub fn key(&self) -> ParameterKey {
+		match self {
+			Self::LiquidationGracePeriod(_) => ParameterKey::LiquidationGracePeriod,
+			Self::MaxPriceAge(_) => ParameterKey::MaxPriceAge,
+			Self::MaxMtrMintPerBlock(_) => ParameterKey::MaxMtrMintPerBlock,
+			Self::MaxMtrMintPerEra(_) => ParameterKey::MaxMtrMintPerEra,
+			Self::PurgeReward(_) => ParameterKey::PurgeReward,
+			Self::BridgeTransferLifetime(_) => ParameterKey::BridgeTransferLifetime,
+			Self::MinInflation(_) => ParameterKey::MinInflation,
+			Self::MaxInflation(_) => ParameterKey::MaxInflation,
+			Self::IdealStake(_) => ParameterKey::IdealStake,
+			Self::InflationFalloff(_) => ParameterKey::InflationFalloff,
+		}
+	}
+
+	/// Value of a balance parameter
+	pub fn balance(&self) -> Option<Balance> {
+		match self {
+			Self::MaxMtrMintPerBlock(value) |
+			Self::MaxMtrMintPerEra(value) |
+			Self::PurgeReward(value) => Some(*value),
+			_ => None,
+		}
+	}
+
+	/// Value of a block number parameter
+	pub fn block_number(&self) -> Option<BlockNumber> {
+		match self {
+			Self::LiquidationGracePeriod(value) |
+			Self::MaxPriceAge(value) |
+			Self::BridgeTransferLifetime(value) => Some(*value),
+			_ => None,
+		}
+	}
+
+	/// Value of a ratio parameter
+	pub fn perbill(&self) -> Option<Perbill> {
+		match self {
+			Self::MinInflation(value) |
+			Self::MaxInflation(value) |
+			Self::IdealStake(value) |
+			Self::InflationFalloff(value) => Some(*value),
+			_ => None,
+		}
+	}
+
+	/// Checks if the value keeps the pallets reading the parameter working. Prices must be
+	/// usable for at least a block, bridge transfers must not be refunded right away and the
+	/// staking inflation must decay above the ideal stake.
+	pub fn is_valid(&self) -> bool {
+		match self {
+			Self::MaxPriceAge(value) | Self::BridgeTransferLifetime(value) => !value.is_zero(),
+			Self::InflationFalloff(value) => *value >= Perbill::from_percent(1),
+			_ => true,
+		}
+	}
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// Origin allowed to set and reset the parameters
+		type AdminOrigin: EnsureOrigin<Self::Origin>;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Parameter is set (parameter)
+		ParameterSet(Parameter<T::BlockNumber>),
+		/// Parameter is reset to the default of the runtime (key)
+		ParameterReset(ParameterKey),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Value would stop the pallets reading the parameter from working
+		InvalidParameter,
+		/// Parameter is not set
+		NotSet,
+	}
+
+	#[pallet::storage]
+	#[pallet::getter(fn parameter)]
+	/// Parameters set by governance, the defaults of the runtime apply to the others
+	pub type Parameters<T: Config> =
+		StorageMap<_, Twox64Concat, ParameterKey, Parameter<T::BlockNumber>>;
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Sets `parameter` to the value it carries, replacing the default of the runtime.
+		///
+		/// # <weight>
+		/// - O(1) insert
+		/// # </weight>
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_parameter(
+			origin: OriginFor<T>,
+			parameter: Parameter<T::BlockNumber>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(parameter.is_valid(), Error::<T>::InvalidParameter);
+
+			Parameters::<T>::insert(parameter.key(), parameter);
+			Self::deposit_event(Event::ParameterSet(parameter));
+			Ok(())
+		}
+
+		/// Resets the parameter stored under `key` to the default of the runtime.
+		///
+		/// # <weight>
+		/// - O(1) removal
+		/// # </weight>
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn reset_parameter(origin: OriginFor<T>, key: ParameterKey) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(Parameters::<T>::take(key).is_some(), Error::<T>::NotSet);
+
+			Self::deposit_event(Event::ParameterReset(key));
+			Ok(())
+		}
+	}
+}
+
+/// Keys as `Get` types, to name them in the parameters of the adapters.
+pub mod keys {
+	use super::{Get, ParameterKey};
+
+	macro_rules! keys {
+		($($key:ident),*) => {
+			$(
+				pub struct $key;
+				impl Get<ParameterKey> for $key {
+					fn get() -> ParameterKey {
+						ParameterKey::$key
+					}
+				}
+			)*
+		};
+	}
+
+	keys!(
+		LiquidationGracePeriod,
+		MaxPriceAge,
+		MaxMtrMintPerBlock,
+		MaxMtrMintPerEra,
+		PurgeReward,
+		BridgeTransferLifetime,
+		MinInflation,
+		MaxInflation,
+		IdealStake,
+		InflationFalloff
+	);
+}
+
+/// Balance parameter stored under the key `K`, `D` unless governance set it.
+pub struct BalanceParameter<T, K, D>(PhantomData<(T, K, D)>);
+
+impl<T: Config, K: Get<ParameterKey>, D: Get<Balance>> Get<Balance> for BalanceParameter<T, K, D> {
+	fn get() -> Balance {
+		Pallet::<T>::parameter(K::get())
+			.and_then(|parameter| parameter.balance())
+			.unwrap_or_else(D::get)
+	}
+}
+
+/// Block number parameter stored under the key `K`, `D` unless governance set it.
+pub struct BlockNumberParameter<T, K, D>(PhantomData<(T, K, D)>);
+
+impl<T: Config, K: Get<ParameterKey>, D: Get<T::BlockNumber>> Get<T::BlockNumber>
+	for BlockNumberParameter<T, K, D>
+{
+	fn get() -> T::BlockNumber {
+		Pallet::<T>::parameter(K::get())
+			.and_then(|parameter| parameter.block_number())
+			.unwrap_or_else(D::get)
+	}
+}
+
+/// Ratio parameter stored under the key `K`, `D` unless governance set it.
+pub struct PerbillParameter<T, K, D>(PhantomData<(T, K, D)>);
+
+impl<T: Config, K: Get<ParameterKey>, D: Get<Perbill>> Get<Perbill> for PerbillParameter<T, K, D> {
+	fn get() -> Perbill {
+		Pallet::<T>::parameter(K::get())
+			.and_then(|parameter| parameter.perbill())
+			.unwrap_or_else(D::get)
+	}
+}
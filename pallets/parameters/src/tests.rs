@@ -0,0 +1,102 @@
+use crate::{mock::*, Error, Event as ParametersEvent, Parameter, ParameterKey};
+use frame_support::{assert_noop, assert_ok, traits::Get};
+use sp_runtime::{traits::BadOrigin, Perbill};
+
+#[test]
+fn set_parameter_overrides_default() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(GracePeriod::get(), 10);
+		assert_noop!(
+			Parameters::set_parameter(Origin::signed(1), Parameter::LiquidationGracePeriod(20)),
+			BadOrigin
+		);
+
+		assert_ok!(Parameters::set_parameter(
+			Origin::root(),
+			Parameter::LiquidationGracePeriod(20)
+		));
+		assert_eq!(
+			last_event(),
+			Event::Parameters(ParametersEvent::ParameterSet(Parameter::LiquidationGracePeriod(20)))
+		);
+		assert_eq!(GracePeriod::get(), 20);
+		// Other parameters keep their defaults
+		assert_eq!(PurgeReward::get(), 100);
+
+		assert_ok!(Parameters::set_parameter(Origin::root(), Parameter::PurgeReward(0)));
+		assert_eq!(PurgeReward::get(), 0);
+		assert_eq!(
+			Parameters::parameter(ParameterKey::PurgeReward),
+			Some(Parameter::PurgeReward(0))
+		);
+	})
+}
+
+#[test]
+fn reset_parameter_restores_default() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Parameters::reset_parameter(Origin::root(), ParameterKey::LiquidationGracePeriod),
+			Error::<Test>::NotSet
+		);
+		assert_ok!(Parameters::set_parameter(
+			Origin::root(),
+			Parameter::LiquidationGracePeriod(20)
+		));
+
+		assert_ok!(Parameters::reset_parameter(
+			Origin::root(),
+			ParameterKey::LiquidationGracePeriod
+		));
+		assert_eq!(
+			last_event(),
+			Event::Parameters(ParametersEvent::ParameterReset(
+				ParameterKey::LiquidationGracePeriod
+			))
+		);
+		assert_eq!(GracePeriod::get(), 10);
+		assert_eq!(Parameters::parameter(ParameterKey::LiquidationGracePeriod), None);
+	})
+}
+
+#[test]
+fn zero_durations_are_rejected() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Parameters::set_parameter(Origin::root(), Parameter::MaxPriceAge(0)),
+			Error::<Test>::InvalidParameter
+		);
+		assert_noop!(
+			Parameters::set_parameter(Origin::root(), Parameter::BridgeTransferLifetime(0)),
+			Error::<Test>::InvalidParameter
+		);
+		assert_noop!(
+			Parameters::set_parameter(
+				Origin::root(),
+				Parameter::InflationFalloff(Perbill::from_perthousand(5))
+			),
+			Error::<Test>::InvalidParameter
+		);
+		assert_ok!(Parameters::set_parameter(Origin::root(), Parameter::LiquidationGracePeriod(0)));
+		assert_eq!(GracePeriod::get(), 0);
+	})
+}
+
+#[test]
+fn set_ratio_parameter_overrides_default() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Falloff::get(), Perbill::from_percent(5));
+
+		assert_ok!(Parameters::set_parameter(
+			Origin::root(),
+			Parameter::InflationFalloff(Perbill::from_percent(10))
+		));
+		assert_eq!(Falloff::get(), Perbill::from_percent(10));
+		// A value stored under another key is never read as the ratio
+		assert_ok!(Parameters::set_parameter(Origin::root(), Parameter::PurgeReward(7)));
+		assert_eq!(Falloff::get(), Perbill::from_percent(10));
+
+		assert_ok!(Parameters::reset_parameter(Origin::root(), ParameterKey::InflationFalloff));
+		assert_eq!(Falloff::get(), Perbill::from_percent(5));
+	})
+}
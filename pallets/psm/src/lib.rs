@@ -0,0 +1,194 @@
+// Ensure we're `no_std` when compiling for Wasm.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Peg Stability Module
+//!
+//! Swaps approved stable assets for MTR at face value, minus a small fee, and back.
+//!
+//! Stable assets deposited with `swap_to_mtr` are held by the module account and newly minted
+//! MTR is paid out. `swap_from_mtr` burns MTR and releases the stable asset again. The MTR minted
+//! against each stable asset is bounded by a ceiling set by governance, so the exposure to any
+//! one stable asset stays limited. All MTR minted also counts against the global `MintGuard`.
+//!
+//! As the swap is always available at face value, arbitrageurs can bring MTR back to the peg
+//! whenever it trades away from it in the market.
+//!
+//! Stable assets are assumed to use the same decimals as MTR.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{
+	traits::tokens::fungibles::{Inspect, Mutate, Transfer},
+	transactional, PalletId,
+};
+use primitives::{traits::MintGuard, AssetId, Balance};
+use scale_info::TypeInfo;
+use sp_runtime::{traits::AccountIdConversion, Permill, RuntimeDebug};
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+pub use pallet::*;
+
+/// Swap parameters of an approved stable asset
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct StableAsset {
+	/// Fee taken on swaps in both directions
+	pub fee: Permill,
+	/// Maximum amount of MTR minted against the asset
+	pub ceiling: Balance,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		type Assets: Inspect<Self::AccountId, AssetId = AssetId, Balance = Balance>
+			+ Mutate<Self::AccountId, AssetId = AssetId, Balance = Balance>
+			+ Transfer<Self::AccountId, AssetId = AssetId, Balance = Balance>;
+		/// Asset id of MTR
+		#[pallet::constant]
+		type MtrAssetId: Get<AssetId>;
+		/// Account holding the deposited stable assets
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+		/// Origin allowed to approve stable assets and set their parameters
+		type GovernanceOrigin: EnsureOrigin<Self::Origin>;
+		/// Limit on the MTR minted across the protocol
+		type MintGuard: MintGuard;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Stable asset is approved or its parameters changed (asset, fee, ceiling)
+		StableAssetSet(AssetId, Permill, Balance),
+		/// Stable asset is no longer accepted (asset)
+		StableAssetRemoved(AssetId),
+		/// Stable asset is swapped for MTR (who, asset, amount_in, mtr_out)
+		SwappedToMtr(T::AccountId, AssetId, Balance, Balance),
+		/// MTR is swapped for a stable asset (who, asset, mtr_in, amount_out)
+		SwappedFromMtr(T::AccountId, AssetId, Balance, Balance),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Asset is not an approved stable asset
+		NotStableAsset,
+		/// MTR can not be used as a stable asset
+		InvalidStableAsset,
+		/// Swap would mint more MTR than the ceiling of the asset
+		CeilingExceeded,
+		/// Not enough of the stable asset is held to swap back
+		InsufficientReserve,
+		/// Amount after fees is zero
+		AmountZero,
+	}
+
+	#[pallet::storage]
+	#[pallet::getter(fn stable_asset)]
+	/// Approved stable assets and their swap parameters
+	pub type StableAssets<T> = StorageMap<_, Twox64Concat, AssetId, StableAsset>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn minted)]
+	/// MTR minted against each stable asset and not yet swapped back
+	pub type Minted<T> = StorageMap<_, Twox64Concat, AssetId, Balance, ValueQuery>;
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Approves `asset` for swaps or updates its parameters.
+		///
+		/// Lowering the ceiling under the amount already minted only stops new swaps into MTR.
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_stable_asset(
+			origin: OriginFor<T>,
+			asset: AssetId,
+			fee: Permill,
+			ceiling: Balance,
+		) -> DispatchResult {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			ensure!(asset != T::MtrAssetId::get(), Error::<T>::InvalidStableAsset);
+
+			StableAssets::<T>::insert(asset, StableAsset { fee, ceiling });
+			Self::deposit_event(Event::StableAssetSet(asset, fee, ceiling));
+			Ok(())
+		}
+
+		/// Stops accepting `asset`. MTR already minted against it can still be swapped back.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn remove_stable_asset(origin: OriginFor<T>, asset: AssetId) -> DispatchResult {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			ensure!(StableAssets::<T>::take(asset).is_some(), Error::<T>::NotStableAsset);
+
+			Self::deposit_event(Event::StableAssetRemoved(asset));
+			Ok(())
+		}
+
+		/// Deposits `amount` of the stable asset and mints the same amount of MTR minus the fee.
+		#[pallet::weight(T::DbWeight::get().reads_writes(6, 6))]
+		#[transactional]
+		pub fn swap_to_mtr(
+			origin: OriginFor<T>,
+			asset: AssetId,
+			amount: Balance,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let params = Self::stable_asset(asset).ok_or(Error::<T>::NotStableAsset)?;
+			let mtr_out = amount.saturating_sub(params.fee * amount);
+			ensure!(mtr_out > 0, Error::<T>::AmountZero);
+			let minted = Self::minted(asset).saturating_add(mtr_out);
+			ensure!(minted <= params.ceiling, Error::<T>::CeilingExceeded);
+
+			T::Assets::transfer(asset, &who, &Self::account_id(), amount, false)?;
+			T::MintGuard::on_mint(mtr_out)?;
+			T::Assets::mint_into(T::MtrAssetId::get(), &who, mtr_out)?;
+			Minted::<T>::insert(asset, minted);
+
+			Self::deposit_event(Event::SwappedToMtr(who, asset, amount, mtr_out));
+			Ok(())
+		}
+
+		/// Burns `amount` of MTR and releases the same amount of the stable asset minus the fee.
+		#[pallet::weight(T::DbWeight::get().reads_writes(3, 4))]
+		#[transactional]
+		pub fn swap_from_mtr(
+			origin: OriginFor<T>,
+			asset: AssetId,
+			amount: Balance,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let minted = Self::minted(asset);
+			ensure!(minted >= amount, Error::<T>::InsufficientReserve);
+			// Removed assets are released without fee
+			let fee = Self::stable_asset(asset).map(|params| params.fee).unwrap_or_default();
+			let amount_out = amount.saturating_sub(fee * amount);
+			ensure!(amount_out > 0, Error::<T>::AmountZero);
+
+			T::Assets::burn_from(T::MtrAssetId::get(), &who, amount)?;
+			T::Assets::transfer(asset, &Self::account_id(), &who, amount_out, false)?;
+			Minted::<T>::insert(asset, minted - amount);
+
+			Self::deposit_event(Event::SwappedFromMtr(who, asset, amount, amount_out));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Account holding the deposited stable assets and the collected fees
+	pub fn account_id() -> T::AccountId {
+		T::PalletId::get().into_account()
+	}
+}
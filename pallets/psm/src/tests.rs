@@ -0,0 +1,106 @@
+use crate::{mock::*, Error, Event as PsmEvent, StableAsset};
+use frame_support::{assert_noop, assert_ok, traits::fungibles::Inspect};
+use sp_runtime::{DispatchError, Permill};
+
+fn approve_usdc(ceiling: primitives::Balance) {
+	assert_ok!(Psm::set_stable_asset(Origin::root(), USDC, Permill::from_percent(1), ceiling));
+}
+
+#[test]
+fn only_governance_approves_stable_assets() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Psm::set_stable_asset(Origin::signed(ALICE), USDC, Permill::zero(), 1_000),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Psm::set_stable_asset(Origin::root(), MTR, Permill::zero(), 1_000),
+			Error::<Test>::InvalidStableAsset
+		);
+
+		approve_usdc(1_000);
+		assert_eq!(
+			Psm::stable_asset(USDC),
+			Some(StableAsset { fee: Permill::from_percent(1), ceiling: 1_000 })
+		);
+		assert_eq!(
+			last_event(),
+			Event::Psm(PsmEvent::StableAssetSet(USDC, Permill::from_percent(1), 1_000))
+		);
+
+		assert_ok!(Psm::remove_stable_asset(Origin::root(), USDC));
+		assert_eq!(Psm::stable_asset(USDC), None);
+		assert_noop!(Psm::remove_stable_asset(Origin::root(), USDC), Error::<Test>::NotStableAsset);
+	})
+}
+
+#[test]
+fn swap_to_mtr_mints_at_face_value_minus_fee() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Psm::swap_to_mtr(Origin::signed(ALICE), USDC, 100),
+			Error::<Test>::NotStableAsset
+		);
+		approve_usdc(1_000);
+
+		assert_ok!(Psm::swap_to_mtr(Origin::signed(ALICE), USDC, 500));
+		assert_eq!(Assets::balance(MTR, &ALICE), 495);
+		assert_eq!(Assets::balance(USDC, &ALICE), 9_500);
+		assert_eq!(Assets::balance(USDC, &Psm::account_id()), 500);
+		assert_eq!(Psm::minted(USDC), 495);
+		assert_eq!(last_event(), Event::Psm(PsmEvent::SwappedToMtr(ALICE, USDC, 500, 495)));
+	})
+}
+
+#[test]
+fn swap_to_mtr_is_bounded_by_ceiling() {
+	new_test_ext().execute_with(|| {
+		approve_usdc(1_000);
+
+		assert_ok!(Psm::swap_to_mtr(Origin::signed(ALICE), USDC, 1_000));
+		assert_noop!(
+			Psm::swap_to_mtr(Origin::signed(BOB), USDC, 100),
+			Error::<Test>::CeilingExceeded
+		);
+		assert_noop!(Psm::swap_to_mtr(Origin::signed(BOB), USDC, 0), Error::<Test>::AmountZero);
+		assert_ok!(Psm::swap_to_mtr(Origin::signed(BOB), USDC, 10));
+	})
+}
+
+#[test]
+fn swap_from_mtr_releases_stable_asset() {
+	new_test_ext().execute_with(|| {
+		approve_usdc(1_000);
+		assert_ok!(Psm::swap_to_mtr(Origin::signed(ALICE), USDC, 1_000));
+
+		assert_noop!(
+			Psm::swap_from_mtr(Origin::signed(ALICE), USDC, 991),
+			Error::<Test>::InsufficientReserve
+		);
+		assert_ok!(Psm::swap_from_mtr(Origin::signed(ALICE), USDC, 500));
+		assert_eq!(Assets::balance(MTR, &ALICE), 490);
+		assert_eq!(Assets::balance(USDC, &ALICE), 9_495);
+		assert_eq!(Psm::minted(USDC), 490);
+		assert_eq!(last_event(), Event::Psm(PsmEvent::SwappedFromMtr(ALICE, USDC, 500, 495)));
+
+		// The module keeps the fees of both directions
+		assert_eq!(Assets::balance(USDC, &Psm::account_id()), 505);
+	})
+}
+
+#[test]
+fn failed_release_keeps_mtr() {
+	new_test_ext().execute_with(|| {
+		approve_usdc(1_000);
+		assert_ok!(Psm::swap_to_mtr(Origin::signed(ALICE), USDC, 1_000));
+
+		// The stable asset can not leave the frozen module account, so no MTR is burned
+		assert_ok!(Assets::freeze(Origin::signed(ALICE), USDC, Psm::account_id()));
+		assert_noop!(
+			Psm::swap_from_mtr(Origin::signed(ALICE), USDC, 500),
+			pallet_assets::Error::<Test>::Frozen
+		);
+		assert_eq!(Assets::balance(MTR, &ALICE), 990);
+		assert_eq!(Psm::minted(USDC), 990);
+	})
+}
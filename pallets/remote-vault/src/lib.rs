@@ -0,0 +1,265 @@
+// Ensure we're `no_std` when compiling for Wasm.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Remote Vault Pallet
+//!
+//! Lets Ethereum accounts of a bridged chain manage vaults without a Substrate wallet. The
+//! account signs an intent to execute a call, the contract on the other chain emits it under the
+//! resource set with `set_intent_resource`, and once the relayers reach their quorum the bridge
+//! dispatches `execute_intent`.
+//!
+//! Each Ethereum address acts for a local account derived with `AddressMapping`, the one its EVM
+//! transactions on this chain are paid from, which owns the vaults it opens. Intents are signed
+//! with `personal_sign` over the SCALE encoded `(chain_id, nonce, call)`, so that they are bound
+//! to this chain, and are executed in the order of their nonce, once each. The signer of an
+//! intent can also be checked from contracts with the intent signer precompile of the runtime.
+//!
+//! Only calls whitelisted by governance with `whitelist_call` are executed, which should be
+//! limited to the vault calls. Whitelisting calls which dispatch other calls (e.g. batches) would
+//! lift that restriction.
+
+use codec::{Decode, Encode};
+use frame_support::{
+	dispatch::{Dispatchable, GetDispatchInfo, PostDispatchInfo},
+	pallet_prelude::*,
+	traits::{CallMetadata, GetCallMetadata},
+};
+use frame_system::pallet_prelude::*;
+use primitives::bridge::{BridgeChainId, ResourceId};
+use scale_info::TypeInfo;
+use sp_core::H160;
+use sp_io::{crypto::secp256k1_ecdsa_recover, hashing::keccak_256};
+use sp_runtime::traits::Convert;
+use sp_std::{boxed::Box, vec::Vec};
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+pub use pallet::*;
+
+/// Call signed by an Ethereum account.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct RemoteIntent<Call> {
+	/// Signature of the payload, with the recovery id in the last byte
+	pub signature: [u8; 65],
+	/// Number of intents of the signer executed before this one
+	pub nonce: u64,
+	pub call: Call,
+}
+
+impl<Call: Encode> RemoteIntent<Call> {
+	/// Bytes signed for the chain `chain_id`
+	pub fn payload(&self, chain_id: BridgeChainId) -> Vec<u8> {
+		(chain_id, self.nonce, &self.call).encode()
+	}
+
+	/// Ethereum address which signed the intent for the chain `chain_id`
+	pub fn signer(&self, chain_id: BridgeChainId) -> Option<H160> {
+		recover_signer(&self.signature, &self.payload(chain_id))
+	}
+}
+
+/// Hash of `payload` signed with `personal_sign`, as defined by EIP-191.
+pub fn signed_message_hash(payload: &[u8]) -> [u8; 32] {
+	let mut length = Vec::new();
+	let mut remaining = payload.len();
+	loop {
+		length.push(b'0' + (remaining % 10) as u8);
+		remaining /= 10;
+		if remaining == 0 {
+			break
+		}
+	}
+	length.reverse();
+	let mut message = b"\x19Ethereum Signed Message:\n".to_vec();
+	message.extend_from_slice(&length);
+	message.extend_from_slice(payload);
+	keccak_256(&message)
+}
+
+/// Ethereum address which signed `payload` with `personal_sign`.
+pub fn recover_signer(signature: &[u8; 65], payload: &[u8]) -> Option<H160> {
+	let public = secp256k1_ecdsa_recover(signature, &signed_message_hash(payload)).ok()?;
+	Some(H160::from_slice(&keccak_256(&public)[12..]))
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::without_storage_info]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// Call executed on behalf of the Ethereum accounts
+		type Call: Parameter
+			+ Dispatchable<Origin = Self::Origin, PostInfo = PostDispatchInfo>
+			+ GetDispatchInfo
+			+ GetCallMetadata;
+		/// Origin of the intents relayed by the bridge
+		type BridgeOrigin: EnsureOrigin<Self::Origin>;
+		/// Origin allowed to set the intent resource and the call whitelist
+		type GovernanceOrigin: EnsureOrigin<Self::Origin>;
+		/// Local account of an Ethereum address
+		type AddressMapping: Convert<H160, Self::AccountId>;
+		/// Bridge id of this chain, which the intents are signed for
+		#[pallet::constant]
+		type BridgeChainId: Get<BridgeChainId>;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Resource of the intents has changed (resource_id)
+		IntentResourceSet(Option<ResourceId>),
+		/// Call can be signed by Ethereum accounts (pallet_name, function_name)
+		CallWhitelisted(Vec<u8>, Vec<u8>),
+		/// Call can no longer be signed by Ethereum accounts (pallet_name, function_name)
+		CallRemovedFromWhitelist(Vec<u8>, Vec<u8>),
+		/// Intent has been executed (signer, account, nonce, result)
+		IntentExecuted(H160, T::AccountId, u64, DispatchResult),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Resource is not the one of the intents
+		UnknownResource,
+		/// Call is not whitelisted for the intents
+		CallNotWhitelisted,
+		/// Call is already whitelisted
+		AlreadyWhitelisted,
+		/// Call is not whitelisted
+		NotWhitelisted,
+		/// Signer of the intent can not be recovered
+		InvalidSignature,
+		/// Nonce is not the next one of the signer
+		InvalidNonce,
+	}
+
+	#[pallet::storage]
+	#[pallet::getter(fn intent_resource)]
+	/// Bridge resource under which the intents are relayed
+	pub type IntentResource<T> = StorageValue<_, ResourceId>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn whitelisted_calls)]
+	/// Calls the Ethereum accounts can sign, keyed by pallet name and call name
+	pub type WhitelistedCalls<T> =
+		StorageDoubleMap<_, Blake2_128Concat, Vec<u8>, Blake2_128Concat, Vec<u8>, bool, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn nonce)]
+	/// Nonce of the next intent of each Ethereum address
+	pub type Nonces<T> = StorageMap<_, Blake2_128Concat, H160, u64, ValueQuery>;
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Sets the bridge resource of the intents. Without it no intent is executed.
+		///
+		/// # <weight>
+		/// - O(1) write
+		/// # </weight>
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_intent_resource(
+			origin: OriginFor<T>,
+			resource_id: Option<ResourceId>,
+		) -> DispatchResult {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+
+			IntentResource::<T>::set(resource_id);
+			Self::deposit_event(Event::IntentResourceSet(resource_id));
+			Ok(())
+		}
+
+		/// Allows Ethereum accounts to sign the call `function_name` of the pallet `pallet_name`.
+		///
+		/// # <weight>
+		/// - O(1) lookup and insert
+		/// # </weight>
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn whitelist_call(
+			origin: OriginFor<T>,
+			pallet_name: Vec<u8>,
+			function_name: Vec<u8>,
+		) -> DispatchResult {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			ensure!(
+				!Self::whitelisted_calls(&pallet_name, &function_name),
+				Error::<T>::AlreadyWhitelisted
+			);
+
+			WhitelistedCalls::<T>::insert(&pallet_name, &function_name, true);
+			Self::deposit_event(Event::CallWhitelisted(pallet_name, function_name));
+			Ok(())
+		}
+
+		/// Removes a call whitelisted with `whitelist_call`.
+		///
+		/// # <weight>
+		/// - O(1) lookup and removal
+		/// # </weight>
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn remove_whitelisted_call(
+			origin: OriginFor<T>,
+			pallet_name: Vec<u8>,
+			function_name: Vec<u8>,
+		) -> DispatchResult {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			ensure!(
+				WhitelistedCalls::<T>::take(&pallet_name, &function_name),
+				Error::<T>::NotWhitelisted
+			);
+
+			Self::deposit_event(Event::CallRemovedFromWhitelist(pallet_name, function_name));
+			Ok(())
+		}
+
+		/// Executes the call of an intent with the signed origin of the local account of its
+		/// signer. The nonce is used up even if the call fails.
+		///
+		/// # <weight>
+		/// - weight of the call
+		/// # </weight>
+		#[pallet::weight({
+			let info = intent.call.get_dispatch_info();
+			(info.weight.saturating_add(T::DbWeight::get().reads_writes(3, 1)), info.class)
+		})]
+		pub fn execute_intent(
+			origin: OriginFor<T>,
+			resource_id: ResourceId,
+			intent: Box<RemoteIntent<<T as Config>::Call>>,
+		) -> DispatchResult {
+			T::BridgeOrigin::ensure_origin(origin)?;
+			ensure!(Self::intent_resource() == Some(resource_id), Error::<T>::UnknownResource);
+			ensure!(Self::is_whitelisted(&intent.call), Error::<T>::CallNotWhitelisted);
+			let signer =
+				intent.signer(T::BridgeChainId::get()).ok_or(Error::<T>::InvalidSignature)?;
+			ensure!(intent.nonce == Self::nonce(signer), Error::<T>::InvalidNonce);
+
+			Nonces::<T>::insert(signer, intent.nonce.saturating_add(1));
+			let account = T::AddressMapping::convert(signer);
+			let result = intent
+				.call
+				.dispatch(frame_system::RawOrigin::Signed(account.clone()).into())
+				.map(|_| ())
+				.map_err(|e| e.error);
+			Self::deposit_event(Event::IntentExecuted(signer, account, intent.nonce, result));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Whether Ethereum accounts can sign `call`.
+	pub fn is_whitelisted(call: &<T as Config>::Call) -> bool {
+		let CallMetadata { pallet_name, function_name } = call.get_call_metadata();
+		Self::whitelisted_calls(pallet_name.as_bytes().to_vec(), function_name.as_bytes().to_vec())
+	}
+}
@@ -0,0 +1,203 @@
+use crate::{
+	mock::*, recover_signer, signed_message_hash, Error, Event as RemoteVaultEvent, RemoteIntent,
+};
+use frame_support::{assert_noop, assert_ok};
+use hex_literal::hex;
+use sp_core::{ecdsa, Pair, H160};
+use sp_runtime::{
+	traits::{BlakeTwo256, Convert, Hash},
+	DispatchError,
+};
+
+/// Address of the private key 1
+const SIGNER: H160 = H160(hex!("7e5f4552091a69125d5dfcb7b8c2659029395bdf"));
+
+fn remark() -> Call {
+	Call::System(frame_system::Call::remark_with_event { remark: b"vault".to_vec() })
+}
+
+fn set_heap_pages() -> Call {
+	Call::System(frame_system::Call::set_heap_pages { pages: 1 })
+}
+
+fn sign(nonce: u64, call: Call, chain_id: u8) -> Box<RemoteIntent<Call>> {
+	let mut secret = [0u8; 32];
+	secret[31] = 1;
+	let unsigned = RemoteIntent { signature: [0; 65], nonce, call };
+	let message = signed_message_hash(&unsigned.payload(chain_id));
+	let signature = ecdsa::Pair::from_seed(&secret).sign_prehashed(&message);
+	Box::new(RemoteIntent { signature: signature.0, ..unsigned })
+}
+
+fn configure() {
+	assert_ok!(RemoteVault::set_intent_resource(Origin::root(), Some(INTENT_RESOURCE)));
+	assert_ok!(RemoteVault::whitelist_call(
+		Origin::root(),
+		b"System".to_vec(),
+		b"remark_with_event".to_vec()
+	));
+}
+
+#[test]
+fn signed_message_hash_follows_eip_191() {
+	assert_eq!(
+		signed_message_hash(b"hello"),
+		hex!("50b2c43fd39106bafbba0da34fc430e1f91e3c96ea2acee2bc34119f92b37750")
+	);
+	assert_eq!(recover_signer(&[0; 65], b"hello"), None);
+	// Intents are bound to the chain they are signed for
+	assert_eq!(sign(0, remark(), CHAIN_ID).signer(CHAIN_ID), Some(SIGNER));
+	assert_ne!(sign(0, remark(), CHAIN_ID + 1).signer(CHAIN_ID), Some(SIGNER));
+}
+
+#[test]
+fn configure_intents() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			RemoteVault::set_intent_resource(Origin::signed(ALICE), Some(INTENT_RESOURCE)),
+			DispatchError::BadOrigin
+		);
+		configure();
+		assert_eq!(RemoteVault::intent_resource(), Some(INTENT_RESOURCE));
+		assert!(RemoteVault::is_whitelisted(&remark()));
+		assert!(!RemoteVault::is_whitelisted(&set_heap_pages()));
+		assert_noop!(
+			RemoteVault::whitelist_call(
+				Origin::root(),
+				b"System".to_vec(),
+				b"remark_with_event".to_vec()
+			),
+			Error::<Test>::AlreadyWhitelisted
+		);
+
+		assert_ok!(RemoteVault::remove_whitelisted_call(
+			Origin::root(),
+			b"System".to_vec(),
+			b"remark_with_event".to_vec()
+		));
+		assert_eq!(
+			last_event(),
+			Event::RemoteVault(RemoteVaultEvent::CallRemovedFromWhitelist(
+				b"System".to_vec(),
+				b"remark_with_event".to_vec()
+			))
+		);
+		assert_noop!(
+			RemoteVault::remove_whitelisted_call(
+				Origin::root(),
+				b"System".to_vec(),
+				b"remark_with_event".to_vec()
+			),
+			Error::<Test>::NotWhitelisted
+		);
+	})
+}
+
+#[test]
+fn intent_is_executed_once_for_the_local_account_of_its_signer() {
+	new_test_ext().execute_with(|| {
+		configure();
+		let account = LowBytes::convert(SIGNER);
+
+		assert_ok!(RemoteVault::execute_intent(
+			Origin::signed(BRIDGE),
+			INTENT_RESOURCE,
+			sign(0, remark(), CHAIN_ID)
+		));
+		System::assert_has_event(Event::System(frame_system::Event::Remarked {
+			sender: account,
+			hash: BlakeTwo256::hash(b"vault"),
+		}));
+		assert_eq!(
+			last_event(),
+			Event::RemoteVault(RemoteVaultEvent::IntentExecuted(SIGNER, account, 0, Ok(())))
+		);
+		assert_eq!(RemoteVault::nonce(SIGNER), 1);
+
+		// Replayed intent
+		assert_noop!(
+			RemoteVault::execute_intent(
+				Origin::signed(BRIDGE),
+				INTENT_RESOURCE,
+				sign(0, remark(), CHAIN_ID)
+			),
+			Error::<Test>::InvalidNonce
+		);
+		assert_ok!(RemoteVault::execute_intent(
+			Origin::signed(BRIDGE),
+			INTENT_RESOURCE,
+			sign(1, remark(), CHAIN_ID)
+		));
+		assert_eq!(RemoteVault::nonce(SIGNER), 2);
+	})
+}
+
+#[test]
+fn failed_call_uses_up_the_nonce() {
+	new_test_ext().execute_with(|| {
+		configure();
+		assert_ok!(RemoteVault::whitelist_call(
+			Origin::root(),
+			b"System".to_vec(),
+			b"set_heap_pages".to_vec()
+		));
+
+		// The local account is not root
+		assert_ok!(RemoteVault::execute_intent(
+			Origin::signed(BRIDGE),
+			INTENT_RESOURCE,
+			sign(0, set_heap_pages(), CHAIN_ID)
+		));
+		assert_eq!(
+			last_event(),
+			Event::RemoteVault(RemoteVaultEvent::IntentExecuted(
+				SIGNER,
+				LowBytes::convert(SIGNER),
+				0,
+				Err(DispatchError::BadOrigin)
+			))
+		);
+		assert_eq!(RemoteVault::nonce(SIGNER), 1);
+	})
+}
+
+#[test]
+fn only_valid_intents_of_the_bridge_are_executed() {
+	new_test_ext().execute_with(|| {
+		configure();
+		let intent = || sign(0, remark(), CHAIN_ID);
+
+		assert_noop!(
+			RemoteVault::execute_intent(Origin::signed(ALICE), INTENT_RESOURCE, intent()),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			RemoteVault::execute_intent(Origin::signed(BRIDGE), [2; 32], intent()),
+			Error::<Test>::UnknownResource
+		);
+		assert_noop!(
+			RemoteVault::execute_intent(
+				Origin::signed(BRIDGE),
+				INTENT_RESOURCE,
+				sign(0, set_heap_pages(), CHAIN_ID)
+			),
+			Error::<Test>::CallNotWhitelisted
+		);
+		assert_noop!(
+			RemoteVault::execute_intent(
+				Origin::signed(BRIDGE),
+				INTENT_RESOURCE,
+				Box::new(RemoteIntent { signature: [0; 65], nonce: 0, call: remark() })
+			),
+			Error::<Test>::InvalidSignature
+		);
+		assert_noop!(
+			RemoteVault::execute_intent(
+				Origin::signed(BRIDGE),
+				INTENT_RESOURCE,
+				sign(1, remark(), CHAIN_ID)
+			),
+			Error::<Test>::InvalidNonce
+		);
+	})
+}
@@ -0,0 +1,249 @@
+// Ensure we're `no_std` when compiling for Wasm.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Stream Pallet
+//!
+//! Continuous payments streamed from a sender to a recipient, e.g. for contributor payroll or
+//! liquidity mining rewards paid out of the treasury.
+//!
+//! `create_stream` locks `rate_per_block` for every block until `end` in the pallet account.
+//! The recipient can withdraw what has been streamed so far at any time with
+//! `withdraw_from_stream`. Either party can `cancel_stream`, which pays out the streamed amount to
+//! the recipient and refunds the rest to the sender.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{traits::tokens::fungibles::Transfer, transactional, PalletId};
+use primitives::{overview::PendingReward, AssetId, Balance};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{AccountIdConversion, Saturating, UniqueSaturatedInto},
+	RuntimeDebug,
+};
+use sp_std::vec::Vec;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+pub use pallet::*;
+
+/// Identifier of a stream
+pub type StreamId = u64;
+
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct Stream<AccountId, BlockNumber> {
+	pub sender: AccountId,
+	pub recipient: AccountId,
+	pub asset: AssetId,
+	pub rate_per_block: Balance,
+	/// Block the stream started at
+	pub start: BlockNumber,
+	/// Block the stream ends at
+	pub end: BlockNumber,
+	/// Amount already withdrawn by the recipient
+	pub withdrawn: Balance,
+}
+
+impl<AccountId, BlockNumber: Copy + Ord + Saturating + UniqueSaturatedInto<Balance>>
+	Stream<AccountId, BlockNumber>
+{
+	/// Amount streamed up to `now`
+	pub fn streamed(&self, now: BlockNumber) -> Balance {
+		let elapsed: Balance = now.min(self.end).saturating_sub(self.start).unique_saturated_into();
+		self.rate_per_block.saturating_mul(elapsed)
+	}
+
+	/// Amount streamed up to `now` and not withdrawn yet
+	pub fn available(&self, now: BlockNumber) -> Balance {
+		self.streamed(now).saturating_sub(self.withdrawn)
+	}
+
+	/// Amount deposited for the whole stream
+	pub fn deposit(&self) -> Balance {
+		self.streamed(self.end)
+	}
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// Assets streamed. The `fungibles` traits of `pallet_assets` stand in for orml
+		/// `MultiCurrency`, which the runtimes do not include, as in the other Standard pallets.
+		type Assets: Transfer<Self::AccountId, AssetId = AssetId, Balance = Balance>;
+		/// Account holding the deposits of all streams
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Stream is created (stream_id, sender, recipient, asset, rate_per_block, end)
+		StreamCreated(StreamId, T::AccountId, T::AccountId, AssetId, Balance, T::BlockNumber),
+		/// Recipient withdrew from the stream (stream_id, amount)
+		Withdrawn(StreamId, Balance),
+		/// Stream is cancelled (stream_id, paid_to_recipient, refunded_to_sender)
+		StreamCancelled(StreamId, Balance, Balance),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Stream does not exist
+		StreamNotFound,
+		/// Stream must end after the current block
+		InvalidEnd,
+		/// Rate must be non-zero
+		RateZero,
+		/// Origin is not allowed to manage the stream
+		NotAllowed,
+		/// Nothing to withdraw yet
+		NothingToWithdraw,
+		/// Deposit of the stream overflows
+		Overflow,
+	}
+
+	#[pallet::storage]
+	#[pallet::getter(fn stream)]
+	pub type Streams<T: Config> =
+		StorageMap<_, Twox64Concat, StreamId, Stream<T::AccountId, T::BlockNumber>>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn next_stream_id)]
+	pub type NextStreamId<T> = StorageValue<_, StreamId, ValueQuery>;
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Streams `rate_per_block` of `asset` to `recipient` from now until `end`.
+		///
+		/// The whole amount is transferred to the pallet account up front.
+		#[pallet::weight(T::DbWeight::get().reads_writes(2, 4))]
+		pub fn create_stream(
+			origin: OriginFor<T>,
+			asset: AssetId,
+			recipient: T::AccountId,
+			rate_per_block: Balance,
+			end: T::BlockNumber,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(rate_per_block > 0, Error::<T>::RateZero);
+			let start = frame_system::Pallet::<T>::block_number();
+			ensure!(end > start, Error::<T>::InvalidEnd);
+			let blocks: Balance = (end - start).unique_saturated_into();
+			let deposit = rate_per_block.checked_mul(blocks).ok_or(Error::<T>::Overflow)?;
+
+			T::Assets::transfer(asset, &sender, &Self::account_id(), deposit, false)?;
+			let stream_id = NextStreamId::<T>::mutate(|id| {
+				let stream_id = *id;
+				*id += 1;
+				stream_id
+			});
+			Streams::<T>::insert(
+				stream_id,
+				Stream {
+					sender: sender.clone(),
+					recipient: recipient.clone(),
+					asset,
+					rate_per_block,
+					start,
+					end,
+					withdrawn: 0,
+				},
+			);
+
+			Self::deposit_event(Event::StreamCreated(
+				stream_id,
+				sender,
+				recipient,
+				asset,
+				rate_per_block,
+				end,
+			));
+			Ok(())
+		}
+
+		/// Withdraws everything streamed so far to the recipient.
+		///
+		/// The stream is removed once it ended and has been withdrawn completely.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 3))]
+		pub fn withdraw_from_stream(origin: OriginFor<T>, stream_id: StreamId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let mut stream = Self::stream(stream_id).ok_or(Error::<T>::StreamNotFound)?;
+			ensure!(stream.recipient == who, Error::<T>::NotAllowed);
+			let now = frame_system::Pallet::<T>::block_number();
+			let amount = stream.available(now);
+			ensure!(amount > 0, Error::<T>::NothingToWithdraw);
+
+			T::Assets::transfer(stream.asset, &Self::account_id(), &who, amount, false)?;
+			stream.withdrawn += amount;
+			if stream.withdrawn == stream.deposit() {
+				Streams::<T>::remove(stream_id);
+			} else {
+				Streams::<T>::insert(stream_id, stream);
+			}
+
+			Self::deposit_event(Event::Withdrawn(stream_id, amount));
+			Ok(())
+		}
+
+		/// Ends the stream, paying out what has been streamed and refunding the rest.
+		///
+		/// Can be called by the sender or the recipient.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 4))]
+		#[transactional]
+		pub fn cancel_stream(origin: OriginFor<T>, stream_id: StreamId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let stream = Self::stream(stream_id).ok_or(Error::<T>::StreamNotFound)?;
+			ensure!(stream.sender == who || stream.recipient == who, Error::<T>::NotAllowed);
+			let now = frame_system::Pallet::<T>::block_number();
+			let paid = stream.available(now);
+			let refunded = stream.deposit().saturating_sub(stream.streamed(now));
+
+			let account = Self::account_id();
+			if paid > 0 {
+				T::Assets::transfer(stream.asset, &account, &stream.recipient, paid, false)?;
+			}
+			if refunded > 0 {
+				T::Assets::transfer(stream.asset, &account, &stream.sender, refunded, false)?;
+			}
+			Streams::<T>::remove(stream_id);
+
+			Self::deposit_event(Event::StreamCancelled(stream_id, paid, refunded));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Account holding the deposits of all streams
+	pub fn account_id() -> T::AccountId {
+		T::PalletId::get().into_account()
+	}
+
+	/// Amounts streamed to `who` and not withdrawn yet, ordered by stream id.
+	pub fn pending_rewards(who: &T::AccountId) -> Vec<PendingReward> {
+		let now = frame_system::Pallet::<T>::block_number();
+		let mut rewards: Vec<PendingReward> = Streams::<T>::iter()
+			.filter(|(_, stream)| stream.recipient == *who)
+			.map(|(stream_id, stream)| PendingReward {
+				stream_id,
+				asset: stream.asset,
+				amount: stream.available(now),
+			})
+			.filter(|reward| reward.amount > 0)
+			.collect();
+		rewards.sort_by_key(|reward| reward.stream_id);
+		rewards
+	}
+}
@@ -0,0 +1,111 @@
+use crate::{mock::*, Error, Event as StreamEvent, Stream as StreamInfo};
+use frame_support::{assert_noop, assert_ok, traits::fungibles::Inspect};
+use primitives::overview::PendingReward;
+use sp_runtime::TokenError;
+
+fn create_stream() {
+	// 10 per block from block 1 to 101
+	assert_ok!(Stream::create_stream(Origin::signed(ALICE), STND, BOB, 10, 101));
+}
+
+#[test]
+fn create_stream_locks_deposit() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Stream::create_stream(Origin::signed(ALICE), STND, BOB, 0, 101),
+			Error::<Test>::RateZero
+		);
+		assert_noop!(
+			Stream::create_stream(Origin::signed(ALICE), STND, BOB, 10, 1),
+			Error::<Test>::InvalidEnd
+		);
+
+		create_stream();
+		assert_eq!(Assets::balance(STND, &ALICE), 9_000);
+		assert_eq!(Assets::balance(STND, &Stream::account_id()), 1_000);
+		assert_eq!(
+			Stream::stream(0),
+			Some(StreamInfo {
+				sender: ALICE,
+				recipient: BOB,
+				asset: STND,
+				rate_per_block: 10,
+				start: 1,
+				end: 101,
+				withdrawn: 0
+			})
+		);
+		assert_eq!(
+			last_event(),
+			Event::Stream(StreamEvent::StreamCreated(0, ALICE, BOB, STND, 10, 101))
+		);
+	})
+}
+
+#[test]
+fn recipient_withdraws_streamed_amount() {
+	new_test_ext().execute_with(|| {
+		create_stream();
+		assert_noop!(
+			Stream::withdraw_from_stream(Origin::signed(BOB), 0),
+			Error::<Test>::NothingToWithdraw
+		);
+
+		System::set_block_number(11);
+		assert_eq!(
+			Stream::pending_rewards(&BOB),
+			vec![PendingReward { stream_id: 0, asset: STND, amount: 100 }]
+		);
+		assert_eq!(Stream::pending_rewards(&ALICE), vec![]);
+		assert_noop!(
+			Stream::withdraw_from_stream(Origin::signed(ALICE), 0),
+			Error::<Test>::NotAllowed
+		);
+		assert_ok!(Stream::withdraw_from_stream(Origin::signed(BOB), 0));
+		assert_eq!(Assets::balance(STND, &BOB), 100);
+		assert_eq!(last_event(), Event::Stream(StreamEvent::Withdrawn(0, 100)));
+		assert_eq!(Stream::pending_rewards(&BOB), vec![]);
+
+		// Nothing streams after the end and the stream is removed once drained
+		System::set_block_number(200);
+		assert_ok!(Stream::withdraw_from_stream(Origin::signed(BOB), 0));
+		assert_eq!(Assets::balance(STND, &BOB), 1_000);
+		assert_eq!(Stream::stream(0), None);
+	})
+}
+
+#[test]
+fn cancel_stream_splits_deposit() {
+	new_test_ext().execute_with(|| {
+		create_stream();
+		System::set_block_number(21);
+		assert_ok!(Stream::withdraw_from_stream(Origin::signed(BOB), 0));
+
+		System::set_block_number(31);
+		assert_noop!(Stream::cancel_stream(Origin::signed(3), 0), Error::<Test>::NotAllowed);
+		assert_ok!(Stream::cancel_stream(Origin::signed(ALICE), 0));
+		assert_eq!(Assets::balance(STND, &BOB), 300);
+		assert_eq!(Assets::balance(STND, &ALICE), 9_700);
+		assert_eq!(Assets::balance(STND, &Stream::account_id()), 0);
+		assert_eq!(Stream::stream(0), None);
+		assert_eq!(last_event(), Event::Stream(StreamEvent::StreamCancelled(0, 100, 700)));
+	})
+}
+
+#[test]
+fn failed_refund_reverts_the_payout() {
+	new_test_ext().execute_with(|| {
+		// ALICE streams all of her DOT, so the refund has to open her account again
+		assert_ok!(Stream::create_stream(Origin::signed(ALICE), DOT, BOB, 10, 101));
+		assert_eq!(Assets::balance(DOT, &ALICE), 0);
+
+		// 950 are paid out, but a refund of 50 is below the minimum balance
+		System::set_block_number(96);
+		assert_noop!(Stream::cancel_stream(Origin::signed(BOB), 0), TokenError::BelowMinimum);
+		assert_eq!(Assets::balance(DOT, &BOB), 0);
+		assert_eq!(Stream::stream(0).map(|stream| stream.withdrawn), Some(0));
+
+		assert_ok!(Stream::withdraw_from_stream(Origin::signed(BOB), 0));
+		assert_eq!(Assets::balance(DOT, &BOB), 950);
+	})
+}
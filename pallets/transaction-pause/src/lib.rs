@@ -0,0 +1,173 @@
+// Ensure we're `no_std` when compiling for Wasm.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Transaction Pause Pallet
+//!
+//! Call filter which rejects individual calls, keyed by pallet name and call name
+//! (e.g. `Market` / `swap`). Pauses can be given an expiry block after which the call is
+//! unpaused automatically in `on_initialize`.
+//!
+//! Calls of this pallet and of the `UnpausablePallets` can not be paused, so a pause can always
+//! be lifted and governance can not lock itself out.
+
+use frame_support::{
+	pallet_prelude::*,
+	traits::{CallMetadata, Contains, GetCallMetadata, PalletInfoAccess},
+};
+use frame_system::pallet_prelude::*;
+use sp_std::vec::Vec;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+pub use pallet::*;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::without_storage_info]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// Origin allowed to pause and unpause calls
+		type PauseOrigin: EnsureOrigin<Self::Origin>;
+		/// Pallets whose calls can not be paused, like the governance pallets
+		type UnpausablePallets: Contains<Vec<u8>>;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Call is paused (pallet_name, function_name, expiry)
+		TransactionPaused(Vec<u8>, Vec<u8>, Option<T::BlockNumber>),
+		/// Call is unpaused (pallet_name, function_name)
+		TransactionUnpaused(Vec<u8>, Vec<u8>),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Calls of this pallet or of an unpausable pallet can not be paused
+		CannotPause,
+		/// Expiry must be in the future
+		InvalidExpiry,
+		/// Call is not paused
+		NotPaused,
+	}
+
+	#[pallet::storage]
+	#[pallet::getter(fn paused_transactions)]
+	/// Paused calls and the block at which the pause expires, if any
+	pub type PausedTransactions<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Blake2_128Concat,
+		Vec<u8>,
+		Option<T::BlockNumber>,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn pause_expiries)]
+	/// Calls to be unpaused at the given block
+	pub type PauseExpiries<T: Config> =
+		StorageMap<_, Twox64Concat, T::BlockNumber, Vec<(Vec<u8>, Vec<u8>)>, ValueQuery>;
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let expired = PauseExpiries::<T>::take(now);
+			let count = expired.len() as u64;
+			for (pallet_name, function_name) in expired {
+				// The pause may have been lifted or renewed with a different expiry since.
+				if Self::paused_transactions(&pallet_name, &function_name) == Some(Some(now)) {
+					PausedTransactions::<T>::remove(&pallet_name, &function_name);
+					Self::deposit_event(Event::TransactionUnpaused(pallet_name, function_name));
+				}
+			}
+			T::DbWeight::get().reads_writes(1 + count, 1 + count)
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Pauses the call `function_name` of the pallet `pallet_name`.
+		///
+		/// With `expiry` set the call is unpaused automatically at that block. Pausing an already
+		/// paused call replaces its expiry.
+		///
+		/// # <weight>
+		/// - O(1) lookup and insert
+		/// # </weight>
+		#[pallet::weight(T::DbWeight::get().reads_writes(2, 2))]
+		pub fn pause_transaction(
+			origin: OriginFor<T>,
+			pallet_name: Vec<u8>,
+			function_name: Vec<u8>,
+			expiry: Option<T::BlockNumber>,
+		) -> DispatchResult {
+			T::PauseOrigin::ensure_origin(origin)?;
+			ensure!(
+				pallet_name.as_slice() != <Self as PalletInfoAccess>::name().as_bytes() &&
+					!T::UnpausablePallets::contains(&pallet_name),
+				Error::<T>::CannotPause
+			);
+			if let Some(at) = expiry {
+				ensure!(at > frame_system::Pallet::<T>::block_number(), Error::<T>::InvalidExpiry);
+				PauseExpiries::<T>::append(at, (pallet_name.clone(), function_name.clone()));
+			}
+
+			PausedTransactions::<T>::insert(&pallet_name, &function_name, expiry);
+			Self::deposit_event(Event::TransactionPaused(pallet_name, function_name, expiry));
+			Ok(())
+		}
+
+		/// Unpauses a call paused with `pause_transaction`.
+		///
+		/// # <weight>
+		/// - O(1) lookup and removal
+		/// # </weight>
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn unpause_transaction(
+			origin: OriginFor<T>,
+			pallet_name: Vec<u8>,
+			function_name: Vec<u8>,
+		) -> DispatchResult {
+			T::PauseOrigin::ensure_origin(origin)?;
+			ensure!(
+				PausedTransactions::<T>::take(&pallet_name, &function_name).is_some(),
+				Error::<T>::NotPaused
+			);
+
+			Self::deposit_event(Event::TransactionUnpaused(pallet_name, function_name));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Whether the call `function_name` of the pallet `pallet_name` is paused.
+	pub fn is_paused(pallet_name: &[u8], function_name: &[u8]) -> bool {
+		PausedTransactions::<T>::contains_key(pallet_name, function_name)
+	}
+}
+
+/// Call filter rejecting paused calls, to be used in the runtime's `BaseCallFilter`.
+pub struct PausedTransactionFilter<T>(PhantomData<T>);
+
+impl<T: Config> Contains<<T as frame_system::Config>::Call> for PausedTransactionFilter<T>
+where
+	<T as frame_system::Config>::Call: GetCallMetadata,
+{
+	fn contains(call: &<T as frame_system::Config>::Call) -> bool {
+		let CallMetadata { pallet_name, function_name } = call.get_call_metadata();
+		!Pallet::<T>::is_paused(pallet_name.as_bytes(), function_name.as_bytes())
+	}
+}
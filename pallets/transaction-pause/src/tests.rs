@@ -0,0 +1,143 @@
+use crate::{mock::*, Error, Event as PauseEvent, PausedTransactionFilter};
+use frame_support::{assert_noop, assert_ok, traits::{Contains, Hooks}};
+
+fn remark() -> Call {
+	Call::System(frame_system::Call::remark { remark: vec![] })
+}
+
+fn remark_with_event() -> Call {
+	Call::System(frame_system::Call::remark_with_event { remark: vec![] })
+}
+
+#[test]
+fn pause_and_unpause_transaction() {
+	new_test_ext().execute_with(|| {
+		assert!(PausedTransactionFilter::<Test>::contains(&remark()));
+
+		assert_ok!(TransactionPause::pause_transaction(
+			Origin::root(),
+			b"System".to_vec(),
+			b"remark".to_vec(),
+			None
+		));
+		assert_eq!(
+			last_event(),
+			Event::TransactionPause(PauseEvent::TransactionPaused(
+				b"System".to_vec(),
+				b"remark".to_vec(),
+				None
+			))
+		);
+		assert!(!PausedTransactionFilter::<Test>::contains(&remark()));
+		assert!(PausedTransactionFilter::<Test>::contains(&remark_with_event()));
+
+		assert_ok!(TransactionPause::unpause_transaction(
+			Origin::root(),
+			b"System".to_vec(),
+			b"remark".to_vec()
+		));
+		assert_eq!(
+			last_event(),
+			Event::TransactionPause(PauseEvent::TransactionUnpaused(
+				b"System".to_vec(),
+				b"remark".to_vec()
+			))
+		);
+		assert!(PausedTransactionFilter::<Test>::contains(&remark()));
+		assert_noop!(
+			TransactionPause::unpause_transaction(
+				Origin::root(),
+				b"System".to_vec(),
+				b"remark".to_vec()
+			),
+			Error::<Test>::NotPaused
+		);
+	})
+}
+
+#[test]
+fn pause_expires() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TransactionPause::pause_transaction(
+			Origin::root(),
+			b"System".to_vec(),
+			b"remark".to_vec(),
+			Some(5)
+		));
+		TransactionPause::on_initialize(4);
+		assert!(!PausedTransactionFilter::<Test>::contains(&remark()));
+
+		TransactionPause::on_initialize(5);
+		assert!(PausedTransactionFilter::<Test>::contains(&remark()));
+		assert_eq!(
+			last_event(),
+			Event::TransactionPause(PauseEvent::TransactionUnpaused(
+				b"System".to_vec(),
+				b"remark".to_vec()
+			))
+		);
+	})
+}
+
+#[test]
+fn renewed_pause_does_not_expire_early() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TransactionPause::pause_transaction(
+			Origin::root(),
+			b"System".to_vec(),
+			b"remark".to_vec(),
+			Some(5)
+		));
+		assert_ok!(TransactionPause::pause_transaction(
+			Origin::root(),
+			b"System".to_vec(),
+			b"remark".to_vec(),
+			None
+		));
+		TransactionPause::on_initialize(5);
+		assert!(!PausedTransactionFilter::<Test>::contains(&remark()));
+	})
+}
+
+#[test]
+fn pause_checks() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			TransactionPause::pause_transaction(
+				Origin::signed(1),
+				b"System".to_vec(),
+				b"remark".to_vec(),
+				None
+			),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			TransactionPause::pause_transaction(
+				Origin::root(),
+				b"TransactionPause".to_vec(),
+				b"unpause_transaction".to_vec(),
+				None
+			),
+			Error::<Test>::CannotPause
+		);
+		// Governance pallets can not be locked out
+		assert_noop!(
+			TransactionPause::pause_transaction(
+				Origin::root(),
+				b"Sudo".to_vec(),
+				b"sudo".to_vec(),
+				None
+			),
+			Error::<Test>::CannotPause
+		);
+		assert_noop!(
+			TransactionPause::pause_transaction(
+				Origin::root(),
+				b"System".to_vec(),
+				b"remark".to_vec(),
+				Some(1)
+			),
+			Error::<Test>::InvalidExpiry
+		);
+	})
+}
@@ -148,21 +148,43 @@
 use crate::sp_api_hidden_includes_decl_storage::hidden_include::traits::Get;
 use codec::{Decode, Encode};
 use frame_support::{
-	decl_error, decl_event, decl_module, decl_storage, ensure,
-	traits::{fungibles::Transfer, tokens::fungibles},
-	PalletId,
+	decl_error, decl_event, decl_module, decl_storage,
+	dispatch::DispatchResult,
+	ensure,
+	storage::migration::storage_key_iter,
+	traits::{
+		fungibles::{Inspect, Mutate, Transfer},
+		schedule::{DispatchTime, Named as ScheduleNamed, HARD_DEADLINE},
+		tokens::fungibles,
+		Currency, EnsureOrigin, ExistenceRequirement, IsSubType, StorageVersion,
+	},
+	transactional,
+	weights::Weight,
+	Blake2_128Concat, PalletId,
 };
 use frame_system::{ensure_root, ensure_signed};
 use pallet_standard_market as market;
 use pallet_standard_oracle as oracle;
-use primitives::{AssetId, Balance};
+use primitives::{
+	overview::VaultPosition,
+	proof::Proven,
+	snapshot::{VaultEraStats, VaultSnapshot},
+	traits::{AccountedFunds, EraFinder, FrozenCollateral, MintGuard},
+	AssetId, Balance, EraIndex,
+};
+pub use primitives::VaultId;
 use scale_info::TypeInfo;
 use sp_core::U256;
 use sp_runtime::{
-	traits::{AccountIdConversion, UniqueSaturatedInto},
-	RuntimeDebug,
+	traits::{AccountIdConversion, DispatchInfoOf, One, Saturating, SignedExtension, Zero},
+	transaction_validity::{
+		InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+	},
+	DispatchError, FixedPointNumber, FixedU128, Permill, RuntimeDebug,
 };
-use sp_std::{fmt::Debug, prelude::*};
+use sp_std::{collections::btree_map::BTreeMap, fmt::Debug, marker::PhantomData, prelude::*};
+use standard_math::{saturating_to_u128, to_u256};
+use static_assertions::const_assert;
 
 #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
 pub struct CDP<Balance: Encode + Decode + Clone + Debug + Eq + PartialEq> {
@@ -173,7 +195,48 @@ pub struct CDP<Balance: Encode + Decode + Clone + Debug + Eq + PartialEq> {
 	/// Fee paid for stability \[numerator, denominator]
 	stability_fee: (Balance, Balance),
 }
-pub const MTR: AssetId = 1_u32;
+
+/// Collateral basket of a vault and the MTR issued against it.
+#[derive(Clone, Default, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct VaultInfo {
+	/// Collateral per asset, at most `MaxCollateralAssets` assets
+	pub collateral: BTreeMap<AssetId, Balance>,
+	/// MTR issued against the collateral
+	pub debt: Balance,
+}
+
+/// Asset the stability fee of a vault is paid in when it is closed.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub enum FeePayment {
+	/// Part of the collateral, kept by the vault account
+	Collateral,
+	/// MTR worth the fee at the oracle prices, burned
+	Mtr,
+	/// Native currency worth the fee at the oracle prices, sent to the treasury
+	Native,
+}
+
+/// Target of the logs of the pallet, enabled on a node with `--log standard::vault=debug`
+const LOG_TARGET: &str = "standard::vault";
+
+// Logs on `LOG_TARGET` in native execution only, so that the runtime blob doesn't carry the
+// formatting code
+macro_rules! log {
+	($level:tt, $pattern:expr $(, $values:expr)* $(,)?) => {
+		#[cfg(feature = "std")]
+		log::$level!(target: crate::LOG_TARGET, $pattern $(, $values)*)
+	};
+}
+
+/// Prefix of the scheduler task names used for delayed position changes.
+const VAULT_SCHEDULE_ID: &[u8] = b"stnd/vlt";
+
+/// Lowest collateralization a position may require \[numerator, denominator], i.e. a position
+/// lends at most 10/11 of the collateral value.
+pub const MIN_COLLATERALIZATION: (u128, u128) = (11, 10);
+
+// A position must never allow debt worth more than its collateral
+const_assert!(MIN_COLLATERALIZATION.1 > 0 && MIN_COLLATERALIZATION.0 > MIN_COLLATERALIZATION.1);
 
 /// The module configuration trait.
 pub trait Config: frame_system::Config + market::Config + oracle::Config {
@@ -185,9 +248,87 @@ pub trait Config: frame_system::Config + market::Config + oracle::Config {
 
 	type VaultPalletId: Get<PalletId>;
 
+	/// Asset id of the stablecoin issued against collateral
+	type MtrAssetId: Get<AssetId>;
+
+	/// Asset id the oracle reports the price of the native currency under.
+	type NativeAssetId: Get<AssetId>;
+
 	type Assets: fungibles::Inspect<Self::AccountId, AssetId = AssetId, Balance = Balance>
 		+ fungibles::Mutate<Self::AccountId, AssetId = AssetId, Balance = Balance>
-		+ fungibles::Transfer<Self::AccountId, AssetId = AssetId, Balance = Balance>;
+		+ fungibles::Transfer<Self::AccountId, AssetId = AssetId, Balance = Balance>
+		+ fungibles::metadata::Inspect<Self::AccountId>;
+
+	/// The runtime call that scheduled position changes are dispatched as.
+	type Proposal: From<Call<Self>>;
+
+	/// The caller origin, overarching type of all pallets origins.
+	type PalletsOrigin: From<frame_system::RawOrigin<Self::AccountId>>;
+
+	/// The scheduler used to enact position changes after a public delay.
+	type Scheduler: ScheduleNamed<Self::BlockNumber, Self::Proposal, Self::PalletsOrigin>;
+
+	/// Origin allowed to set and schedule collateral positions. Must accept root, which
+	/// scheduled position changes are dispatched as.
+	type RiskOrigin: EnsureOrigin<Self::Origin>;
+
+	/// Minimum number of blocks between scheduling a position change and its enactment.
+	type MinimumEnactmentDelay: Get<Self::BlockNumber>;
+
+	/// Number of blocks a vault has to stay under the liquidation threshold after the owner
+	/// is warned before it can be liquidated. Warnings expire after twice the grace period.
+	type LiquidationGracePeriod: Get<Self::BlockNumber>;
+
+	/// Oracle price of MTR at the peg.
+	type MtrPeg: Get<FixedU128>;
+
+	/// Deviation of the MTR price from the peg which does not change stability fees.
+	type PegTolerance: Get<Permill>;
+
+	/// Number of blocks between stability fee adjustments.
+	type RateUpdatePeriod: Get<Self::BlockNumber>;
+
+	/// Maximum number of collateral assets held by a vault.
+	type MaxCollateralAssets: Get<u32>;
+
+	/// Maximum number of generate and swap rounds of a `leverage` call.
+	type MaxLeverageIterations: Get<u32>;
+
+	/// Minimum number of oracle providers reporting the price of a collateral to activate it.
+	type MinCollateralProviders: Get<u32>;
+
+	/// Minimum MTR reserve of the market pair of a collateral against MTR to activate it.
+	type MinCollateralLiquidity: Get<Balance>;
+
+	/// Maximum amount of MTR minted in a block, by the vaults and the peg stability module.
+	type MaxMintPerBlock: Get<Balance>;
+
+	/// Maximum amount of MTR minted in a mint era, by the vaults and the peg stability module.
+	type MaxMintPerEra: Get<Balance>;
+
+	/// Number of blocks of a mint era.
+	type MintEraLength: Get<Self::BlockNumber>;
+
+	/// Maximum number of queued liquidations carried out in the idle time of a block.
+	type MaxIdleLiquidations: Get<u32>;
+
+	/// Native currency paid out of the treasury for every closed vault or pool purged.
+	type PurgeReward: Get<Balance>;
+
+	/// Account the purge rewards are paid out of.
+	type TreasuryAccount: Get<Self::AccountId>;
+
+	/// Number of past eras whose vault aggregates are kept.
+	type HistoryDepth: Get<EraIndex>;
+
+	/// Maximum number of blocks since the last oracle report of a price `generate` and
+	/// `liquidate_vault` may rely on, enforced by `CheckPriceAge`. Liquidations may also rely on
+	/// the TWAP fallback of the oracle.
+	type MaxPriceAge: Get<Self::BlockNumber>;
+
+	/// Collateral the owners of the vaults can not withdraw, nor take along by closing or
+	/// transferring their vaults. Notified when a vault is liquidated.
+	type FrozenCollateral: FrozenCollateral<Self::AccountId>;
 }
 
 decl_module! {
@@ -197,147 +338,502 @@ decl_module! {
 
 		fn deposit_event() = default;
 
+		fn on_runtime_upgrade() -> Weight {
+			Self::migrate_to_vault_ids()
+		}
+
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let weight = Self::record_era_stats();
+			if (now % T::RateUpdatePeriod::get()).is_zero() {
+				weight.saturating_add(Self::adjust_stability_fees())
+			} else {
+				weight
+			}
+		}
+
+		fn on_idle(now: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			Self::process_liquidation_queue(now, remaining_weight)
+		}
+
 		#[weight= 0]
+		#[transactional]
 		fn generate(
 			origin,
 			#[compact] request_amount: Balance,
 			#[compact] collateral_id: AssetId,
 			#[compact] collateral_amount: Balance) {
 			let origin = ensure_signed(origin)?;
-			// Get position for the collateral
-			let position = Self::position(collateral_id);
-			ensure!(position.is_some(), Error::<T>::CollateralNotSupported);
-			// Get price from oracles
-			let collateral_price = oracle::Module::<T>::price(collateral_id)?;
-			let mtr_price = oracle::Module::<T>::price(MTR)?;
-			// Get vault from sender and divide cases
-			let (total_collateral, total_request) = match Self::vault((origin.clone(), collateral_id)) {
-				// vault exists for the sender
-				Some(x) => {
-					// Add collateral and mtr amount from existing vault
-					let collateral_total = collateral_amount + x.0;
-					let request_total = request_amount + x.1;
-					(collateral_total, request_total)
-				},
-				// vault does not exist for the sender
-				None => {
-					(collateral_amount, request_amount)
-				}
-			};
+			ensure!(Positions::contains_key(collateral_id), Error::<T>::CollateralNotSupported);
+			// Get vault from sender, the collateral opens a new vault otherwise
+			let vault_id = Self::vault_id((origin.clone(), collateral_id));
+			let mut vault = vault_id.and_then(Self::vault).unwrap_or_default();
+			Self::add_collateral(&mut vault, collateral_id, collateral_amount)?;
+			vault.debt += request_amount;
 
-			let result = Self::is_cdp_valid(&position.unwrap(), collateral_price, total_collateral, mtr_price, total_request);
 			// Check whether CDP is valid
-			ensure!(result, Error::<T>::InvalidCDP);
+			ensure!(Self::is_vault_valid(&vault)?, Error::<T>::InvalidCDP);
 
 			// Send collateral to Standard Protocol
 			<T as Config>::Assets::transfer(collateral_id, &origin, &Self::sys_account_id(), collateral_amount, true)?;
 
-			// Update CDP
-			<Vault<T>>::mutate((origin.clone(), collateral_id), |vlt|{
-				*vlt = Some((total_collateral, total_request));
-			});
+			// Update CDP, issuing a vault token for a new vault
+			let vault_id = match vault_id {
+				Some(id) => id,
+				None => Self::mint_vault_token(&origin, collateral_id),
+			};
+			let debt = vault.debt;
+			Self::put_vault(vault_id, vault);
+			// The vault is healthy again after the update
+			<LiquidationWarnings<T>>::remove(vault_id);
 
-			// Send mtr to sender
-			<T as Config>::Assets::transfer(MTR, &origin, &Self::sys_account_id(), request_amount, true)?;
+			// Mint mtr to sender
+			Self::mint_mtr(&origin, request_amount)?;
 
-			// deposit event
-			Self::deposit_event(RawEvent::UpdateVault(origin, collateral_id, total_collateral, request_amount))
+			// deposit events
+			if !collateral_amount.is_zero() {
+				Self::deposit_event(RawEvent::CollateralDeposited(vault_id, origin.clone(), collateral_id, collateral_amount));
+			}
+			if !request_amount.is_zero() {
+				Self::deposit_event(RawEvent::DebtGenerated(vault_id, origin, request_amount, debt));
+			}
 		}
 
-
 		#[weight=0]
+		#[transactional]
 		fn liquidate_vault(
 			origin,
-			account: T::AccountId,
-			#[compact] collateral_id: AssetId) {
+			#[compact] vault_id: VaultId) {
 			let origin = ensure_signed(origin)?;
-			let vault = <Vault<T>>::get((account.clone(), collateral_id));
-			ensure!(vault.is_some(), Error::<T>::VaultDoesNotExist);
+			let vault = Self::vault(vault_id).ok_or(Error::<T>::VaultDoesNotExist)?;
+			let owner = Self::vault_owner(vault_id).ok_or(Error::<T>::VaultDoesNotExist)?;
 			// check if the vault is still valid
-			let position = Self::position(collateral_id);
-			ensure!(position.is_some(), Error::<T>::CollateralNotSupported);
-			// Get price from oracles
-			let collateral_price = oracle::Module::<T>::price(collateral_id)?;
-			let mtr_price = oracle::Module::<T>::price(MTR)?;
-			let (collateral_amount, request_amount) = vault.unwrap();
-			let result = Self::is_cdp_valid(&position.clone().unwrap(), collateral_price, collateral_amount, mtr_price, request_amount);
-			// Check whether cdp is invalid
-			ensure!(!result, Error::<T>::Unavailable);
-			// liquidate the vault
-			// Pay liquidation fee to the liquidator
-			let liquidation_rate = position.unwrap().liquidation_fee;
-			let fee = collateral_amount/liquidation_rate.1*liquidation_rate.0;
-			<T as Config>::Assets::transfer(collateral_id, &origin, &Self::account_id(), fee, true)?;
+			let result = Self::is_vault_valid(&vault)?;
+			let now = <frame_system::Pallet<T>>::block_number();
+			let warned_at = Self::active_warning(vault_id, now);
+			log!(debug, "liquidation check of vault {}: valid {}, health factor {:?}, warned at {:?}", vault_id, result, Self::health_factor(&vault), warned_at);
+			// Check whether cdp is invalid, resetting the grace period once it recovered
+			if result {
+				ensure!(warned_at.is_some(), Error::<T>::Unavailable);
+				<LiquidationWarnings<T>>::remove(vault_id);
+				Self::deposit_event(RawEvent::LiquidationWarningCleared(vault_id));
+				return Ok(());
+			}
+			// The first call only warns the owner, the vault has to stay undercollateralized
+			// for the grace period to be liquidated
+			let warned_at = match warned_at {
+				Some(at) => at,
+				None => {
+					<LiquidationWarnings<T>>::insert(vault_id, now);
+					Self::queue_liquidation(vault_id, now);
+					Self::deposit_event(RawEvent::LiquidationWarning(vault_id, owner, now.saturating_add(T::LiquidationGracePeriod::get())));
+					return Ok(());
+				}
+			};
+			ensure!(now >= warned_at.saturating_add(T::LiquidationGracePeriod::get()), Error::<T>::GracePeriod);
+			Self::liquidate(vault_id, vault, owner, origin)?;
+		}
 
-			let rest = collateral_amount - fee;
-			// Check pairs in the market
-			let lpt = market::Pairs::get((MTR, collateral_id.clone()));
-			ensure!(lpt.is_some(), Error::<T>::MarketDoesNotExist);
+		/// Repay the debt of the vault and return its collateral, paying the stability fee of
+		/// each collateral out of the collateral.
+		#[weight=0]
+		#[transactional]
+		fn close(
+			origin,
+			#[compact] vault_id: VaultId) {
+			let origin = ensure_signed(origin)?;
+			Self::do_close(origin, vault_id, FeePayment::Collateral)?;
+		}
 
-			// Send collateral to the market
-			let reserves = market::Reserves::get(lpt.unwrap());
-			let liquidated = rest+reserves.1;
-			market::Module::<T>::_set_reserves(MTR, collateral_id, reserves.0, liquidated, lpt.unwrap());
+		/// Set the position parameters of the collateral.
+		///
+		/// A collateral without a position is only activated once it passes the onboarding
+		/// checklist: its asset metadata is registered, at least `MinCollateralProviders` oracle
+		/// providers report its price and its market pair against MTR holds at least
+		/// `MinCollateralLiquidity` MTR.
+		///
+		/// The liquidation and stability fees must be below 100% and the maximum
+		/// collateralization rate must require at least `MIN_COLLATERALIZATION`.
+		#[weight=0]
+		fn set_position(
+			origin,
+			collateral_id: AssetId,
+			liqudation_rate: (Balance, Balance),
+			max_collateraization_rate: (U256, U256),
+			stability_fee: (Balance, Balance)
+		) {
+			T::RiskOrigin::ensure_origin(origin)?;
+			let position = CDP {
+				liquidation_fee: liqudation_rate,
+				max_collateraization_rate,
+				stability_fee
+			};
+			Self::check_position(&position)?;
+			let old = Positions::get(collateral_id);
+			if old.is_none() {
+				Self::check_onboarding(collateral_id)?;
+			}
 
-			// destroy the vault
-			<Vault<T>>::take((account.clone(), collateral_id.clone()));
+			Positions::insert(collateral_id, position.clone());
 
 			// deposit event
-			Self::deposit_event(RawEvent::Liquidate(collateral_id, collateral_amount));
+			Self::deposit_event(RawEvent::SetPosition(collateral_id, old, position));
 		}
 
+		/// Add `amount` of `collateral_id` to the collateral basket of the vault.
+		///
+		/// An account holds at most one vault per collateral, so the collateral must not be held
+		/// by another vault of the owner.
 		#[weight=0]
-		fn close(
+		fn deposit_collateral(
 			origin,
-			#[compact] collateral_id: AssetId) {
+			#[compact] vault_id: VaultId,
+			#[compact] collateral_id: AssetId,
+			#[compact] amount: Balance) {
 			let origin = ensure_signed(origin)?;
-			let vault = Vault::<T>::get((origin.clone(), collateral_id));
-			ensure!(vault.is_some(), Error::<T>::VaultDoesNotExist);
-			// check if the vault is still valid
-			let position = Self::position(collateral_id);
-			ensure!(position.is_some(), Error::<T>::CollateralNotSupported);
-			// Get price from oracles
-			let collateral_price = oracle::Module::<T>::price(collateral_id)?;
-			let mtr_price = oracle::Module::<T>::price(MTR)?;
-			let (collateral_amount, request_amount) = vault.unwrap();
-			let result = Self::is_cdp_valid(&position.clone().unwrap(), collateral_price, collateral_amount, mtr_price, request_amount);
-			// Check whether cdp is valid and safe from liquidation.
-			ensure!(result, Error::<T>::AddMoreCollateral);
-			// close the vault
-
-			// Pay stability fee with collateral to the Standard treasury
-			let stability_rate = position.unwrap().stability_fee;
-			let fee = collateral_amount/stability_rate.1*stability_rate.0;
-			<T as Config>::Assets::transfer(collateral_id, &Self::account_id(), &Self::sys_account_id(), fee, true)?;
+			ensure!(!amount.is_zero(), Error::<T>::AmountZero);
+			let mut vault = Self::owned_vault(&origin, vault_id)?;
+			ensure!(Positions::contains_key(collateral_id), Error::<T>::CollateralNotSupported);
+			ensure!(
+				Self::vault_id((origin.clone(), collateral_id)).map_or(true, |id| id == vault_id),
+				Error::<T>::CollateralInOtherVault
+			);
+			Self::add_collateral(&mut vault, collateral_id, amount)?;
 
-			let rest = collateral_amount - fee;
+			<T as Config>::Assets::transfer(collateral_id, &origin, &Self::sys_account_id(), amount, true)?;
+			VaultIds::<T>::insert((origin.clone(), collateral_id), vault_id);
+			Self::put_vault(vault_id, vault);
 
-			// Give back the collateral
-			let _ = <T as Config>::Assets::transfer(collateral_id, &Self::sys_account_id(), &origin, rest, true);
+			Self::deposit_event(RawEvent::CollateralDeposited(vault_id, origin, collateral_id, amount));
+		}
 
-			// deposit event
-			Self::deposit_event(RawEvent::CloseVault(collateral_id, collateral_amount, request_amount));
+		/// Withdraw `amount` of `collateral_id` from the collateral basket of the vault.
+		///
+		/// The rest of the basket has to back the debt of the vault.
+		#[weight=0]
+		fn withdraw_collateral(
+			origin,
+			#[compact] vault_id: VaultId,
+			#[compact] collateral_id: AssetId,
+			#[compact] amount: Balance) {
+			let origin = ensure_signed(origin)?;
+			ensure!(!amount.is_zero(), Error::<T>::AmountZero);
+			let mut vault = Self::owned_vault(&origin, vault_id)?;
+			let held = vault.collateral.get(&collateral_id).copied().unwrap_or_default();
+			ensure!(held >= amount, Error::<T>::BalanceLow);
+			let remaining = held - amount;
+			Self::ensure_unfrozen(&origin, collateral_id, remaining)?;
+			match remaining.is_zero() {
+				true => vault.collateral.remove(&collateral_id),
+				false => vault.collateral.insert(collateral_id, remaining),
+			};
+			// A vault without debt can be emptied, which removes it
+			ensure!(vault.debt.is_zero() || Self::is_vault_valid(&vault)?, Error::<T>::InvalidCDP);
+
+			<T as Config>::Assets::transfer(collateral_id, &Self::sys_account_id(), &origin, amount, true)?;
+			if remaining.is_zero() {
+				VaultIds::<T>::remove((origin.clone(), collateral_id));
+			}
+			Self::put_vault(vault_id, vault);
+
+			Self::deposit_event(RawEvent::CollateralWithdrawn(vault_id, origin, collateral_id, amount));
+			Self::remove_if_empty(vault_id);
+		}
+
+		/// Open or extend a leveraged position on `collateral_id` in one transaction.
+		///
+		/// Deposits `initial_amount` of the collateral into the vault of the sender for it, then
+		/// up to `max_iterations` times generates MTR until the health factor of the vault is
+		/// `target_ratio`, swaps the MTR to the collateral on the market and deposits the
+		/// collateral bought. Fails unless the swaps buy at least `min_collateral_out` in total.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(8, 6).saturating_add(
+			T::DbWeight::get()
+				.reads_writes(8 + T::MaxCollateralAssets::get() as Weight, 8)
+				.saturating_mul(*max_iterations as Weight)
+		)]
+		#[transactional]
+		fn leverage(
+			origin,
+			#[compact] collateral_id: AssetId,
+			#[compact] initial_amount: Balance,
+			target_ratio: FixedU128,
+			max_iterations: u32,
+			#[compact] min_collateral_out: Balance) {
+			let origin = ensure_signed(origin)?;
+			ensure!(Positions::contains_key(collateral_id), Error::<T>::CollateralNotSupported);
+			ensure!(target_ratio > FixedU128::one(), Error::<T>::InvalidTargetRatio);
+			ensure!(max_iterations <= T::MaxLeverageIterations::get(), Error::<T>::TooManyIterations);
+			let vault_id = Self::vault_id((origin.clone(), collateral_id));
+			let mut vault = vault_id.and_then(Self::vault).unwrap_or_default();
+			Self::add_collateral(&mut vault, collateral_id, initial_amount)?;
+			if !initial_amount.is_zero() {
+				<T as Config>::Assets::transfer(collateral_id, &origin, &Self::sys_account_id(), initial_amount, true)?;
+			}
+
+			let mtr_price = oracle::Module::<T>::price(T::MtrAssetId::get())?;
+			let mut bought: Balance = 0;
+			let mut generated: Balance = 0;
+			for _ in 0..max_iterations {
+				// Debt at which the health factor of the vault is the target ratio
+				let target_debt = Self::borrowing_capacity(&vault)?
+					.saturating_mul(U256::from(FixedU128::accuracy()))
+					.checked_div(to_u256(target_ratio.into_inner()).saturating_mul(to_u256(mtr_price.into_inner())))
+					.unwrap_or_default();
+				let amount = saturating_to_u128(target_debt).saturating_sub(vault.debt);
+				if amount.is_zero() {
+					break
+				}
+				Self::mint_mtr(&origin, amount)?;
+				let collateral_out = market::Module::<T>::do_swap(&origin, T::MtrAssetId::get(), amount, collateral_id, false)?;
+				<T as Config>::Assets::transfer(collateral_id, &origin, &Self::sys_account_id(), collateral_out, true)?;
+				Self::add_collateral(&mut vault, collateral_id, collateral_out)?;
+				vault.debt = vault.debt.saturating_add(amount);
+				bought = bought.saturating_add(collateral_out);
+				generated = generated.saturating_add(amount);
+			}
+			ensure!(bought >= min_collateral_out, Error::<T>::SlippageExceeded);
+			ensure!(Self::is_vault_valid(&vault)?, Error::<T>::InvalidCDP);
+
+			let vault_id = match vault_id {
+				Some(id) => id,
+				None => Self::mint_vault_token(&origin, collateral_id),
+			};
+			let debt = vault.debt;
+			Self::put_vault(vault_id, vault);
+			<LiquidationWarnings<T>>::remove(vault_id);
+
+			if !initial_amount.is_zero() {
+				Self::deposit_event(RawEvent::CollateralDeposited(vault_id, origin.clone(), collateral_id, initial_amount));
+			}
+			if !generated.is_zero() {
+				Self::deposit_event(RawEvent::DebtGenerated(vault_id, origin.clone(), generated, debt));
+			}
+			Self::deposit_event(RawEvent::Leveraged(vault_id, origin, collateral_id, bought, generated));
+		}
+
+		/// Unwind a position by repaying `repay_amount` of the debt of the vault of the sender for
+		/// `collateral_id` with its own collateral.
+		///
+		/// Sells just enough of the collateral on the market to buy `repay_amount` of MTR, which
+		/// is burned against the debt. Fails if that takes more than `max_collateral_in`, or if
+		/// the rest of the collateral does not back the rest of the debt.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(14 + T::MaxCollateralAssets::get() as Weight, 10)]
+		#[transactional]
+		fn deleverage(
+			origin,
+			#[compact] collateral_id: AssetId,
+			#[compact] repay_amount: Balance,
+			#[compact] max_collateral_in: Balance) {
+			let origin = ensure_signed(origin)?;
+			ensure!(!repay_amount.is_zero(), Error::<T>::AmountZero);
+			let vault_id = Self::vault_id((origin.clone(), collateral_id)).ok_or(Error::<T>::VaultDoesNotExist)?;
+			let mut vault = Self::owned_vault(&origin, vault_id)?;
+			ensure!(vault.debt >= repay_amount, Error::<T>::RepayExceedsDebt);
+			let collateral_in = market::Module::<T>::quote_in(&origin, collateral_id, T::MtrAssetId::get(), repay_amount)?;
+			ensure!(collateral_in <= max_collateral_in, Error::<T>::SlippageExceeded);
+			let held = vault.collateral.get(&collateral_id).copied().unwrap_or_default();
+			ensure!(held >= collateral_in, Error::<T>::BalanceLow);
+			let remaining = held - collateral_in;
+			Self::ensure_unfrozen(&origin, collateral_id, remaining)?;
+			match remaining.is_zero() {
+				true => vault.collateral.remove(&collateral_id),
+				false => vault.collateral.insert(collateral_id, remaining),
+			};
+			vault.debt -= repay_amount;
+			// The rest of the collateral has to back the rest of the debt
+			ensure!(
+				vault.debt.is_zero() || (!remaining.is_zero() && Self::is_vault_valid(&vault)?),
+				Error::<T>::InvalidCDP
+			);
 
+			<T as Config>::Assets::transfer(collateral_id, &Self::sys_account_id(), &origin, collateral_in, true)?;
+			market::Module::<T>::do_swap(&origin, collateral_id, collateral_in, T::MtrAssetId::get(), false)?;
+			Self::burn_mtr(&origin, repay_amount)?;
+			let debt = vault.debt;
+			if remaining.is_zero() {
+				VaultIds::<T>::remove((origin.clone(), collateral_id));
+			}
+			Self::put_vault(vault_id, vault);
+
+			Self::deposit_event(RawEvent::DebtRepaid(vault_id, origin.clone(), repay_amount, debt));
+			Self::deposit_event(RawEvent::Deleveraged(vault_id, origin, collateral_id, collateral_in, repay_amount));
+			Self::remove_if_empty(vault_id);
 		}
 
+		/// Transfer the vault token, and with it the collateral and the debt of the vault, to `dest`.
+		///
+		/// An account holds at most one vault per collateral, so `dest` must not have a vault for
+		/// any collateral of the vault.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(2, 1).saturating_add(
+			T::DbWeight::get().reads_writes(2, 2).saturating_mul(T::MaxCollateralAssets::get() as Weight)
+		)]
+		fn transfer_vault(
+			origin,
+			dest: T::AccountId,
+			#[compact] vault_id: VaultId) {
+			let origin = ensure_signed(origin)?;
+			Self::do_transfer_vault(&origin, &dest, vault_id)?;
+		}
+
+		/// Let the stability fee of the collateral follow the MTR peg within the bounds
+		/// `(min, max, step)`, given as numerators over the stability fee denominator of the
+		/// position. The maximum must stay below 100%. `None` stops the adjustment and keeps the
+		/// current fee.
+		///
+		/// Every `RateUpdatePeriod` the fee is raised by `step` while MTR trades under the peg,
+		/// making new debt more expensive, and lowered by `step` while it trades above.
 		#[weight=0]
-		fn set_position(
+		fn set_stability_fee_bounds(
 			origin,
 			collateral_id: AssetId,
-			liqudation_rate: (Balance, Balance),
-			max_collateraization_rate: (U256, U256),
-			stability_fee: (Balance, Balance)
+			bounds: Option<(Balance, Balance, Balance)>
 		) {
 			ensure_root(origin)?;
+			let position = Self::position(collateral_id).ok_or(Error::<T>::CollateralNotSupported)?;
+
+			match bounds {
+				Some((min, max, step)) => {
+					ensure!(min <= max && max < position.stability_fee.1, Error::<T>::InvalidFeeBounds);
+					StabilityFeeBounds::insert(collateral_id, (min, max, step));
+				},
+				None => StabilityFeeBounds::remove(collateral_id),
+			}
 
-			Positions::insert(collateral_id, CDP{
+			// deposit event
+			Self::deposit_event(RawEvent::StabilityFeeBoundsSet(collateral_id, bounds));
+		}
+
+		/// Schedule `set_position` for the collateral to be enacted after `delay` blocks.
+		///
+		/// The delay must be at least `MinimumEnactmentDelay` so that vault owners have time to
+		/// react to the announced parameters. Only one change can be pending per collateral.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(2, 2)]
+		fn schedule_set_position(
+			origin,
+			collateral_id: AssetId,
+			liqudation_rate: (Balance, Balance),
+			max_collateraization_rate: (U256, U256),
+			stability_fee: (Balance, Balance),
+			delay: T::BlockNumber
+		) {
+			T::RiskOrigin::ensure_origin(origin)?;
+			ensure!(delay >= T::MinimumEnactmentDelay::get(), Error::<T>::DelayTooShort);
+			Self::check_position(&CDP {
 				liquidation_fee: liqudation_rate,
 				max_collateraization_rate,
 				stability_fee
-			});
+			})?;
+
+			let when = <frame_system::Pallet<T>>::block_number().saturating_add(delay);
+			let call = Call::<T>::set_position(collateral_id, liqudation_rate, max_collateraization_rate, stability_fee);
+			T::Scheduler::schedule_named(
+				Self::schedule_id(collateral_id),
+				DispatchTime::At(when),
+				None,
+				HARD_DEADLINE,
+				frame_system::RawOrigin::Root.into(),
+				call.into(),
+			).map_err(|_| Error::<T>::ScheduleFailed)?;
+
+			// deposit event
+			Self::deposit_event(RawEvent::PositionScheduled(collateral_id, when, liqudation_rate.0, liqudation_rate.1, max_collateraization_rate.0, max_collateraization_rate.1, stability_fee.0, stability_fee.1));
+		}
+
+		/// Cancel a position change scheduled with `schedule_set_position`.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(2, 2)]
+		fn cancel_scheduled_position(
+			origin,
+			collateral_id: AssetId
+		) {
+			T::RiskOrigin::ensure_origin(origin)?;
+
+			T::Scheduler::cancel_named(Self::schedule_id(collateral_id))
+				.map_err(|_| Error::<T>::NotScheduled)?;
+
+			// deposit event
+			Self::deposit_event(RawEvent::PositionScheduleCancelled(collateral_id));
+		}
+
+		/// Move `amount` of `asset` sent to the vault account by mistake to `beneficiary`.
+		///
+		/// The vault account collects the liquidation and stability fees, while the collateral
+		/// of the vaults is kept in the system account it shares with the market. Collected fees,
+		/// collateral and pool reserves held in the vault account can not be moved.
+		///
+		/// # <weight>
+		/// - O(1) reads of the running totals of the fees and the collateral
+		/// # </weight>
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(5, 2)]
+		#[transactional]
+		fn rescue_funds(
+			origin,
+			asset: AssetId,
+			#[compact] amount: Balance,
+			beneficiary: T::AccountId
+		) {
+			ensure_root(origin)?;
+			let account = Self::account_id();
+			let accounted = <Self as AccountedFunds<_>>::accounted(&account, asset)
+				.saturating_add(<market::Module<T> as AccountedFunds<_>>::accounted(&account, asset))
+				.saturating_add(Self::collected_fees(asset));
+			let excess = <T as Config>::Assets::balance(asset, &account).saturating_sub(accounted);
+			ensure!(amount <= excess, Error::<T>::ExceedsExcessFunds);
+			<T as Config>::Assets::transfer(asset, &account, &beneficiary, amount, false)?;
+			// Fees, collateral and reserves stay fully backed
+			ensure!(
+				<T as Config>::Assets::balance(asset, &account) >= accounted,
+				Error::<T>::ExceedsExcessFunds
+			);
+
+			// deposit event
+			Self::deposit_event(RawEvent::FundsRescued(asset, amount, beneficiary));
+		}
+
+		/// Remove emptied vaults and pools left in storage, paying `PurgeReward` out of the
+		/// treasury for each of them.
+		///
+		/// Checks the next `limit` vault ids and the next `limit` pools after the last purge,
+		/// wrapping around at the newest vault and the last pool. The reward is skipped if the
+		/// treasury can not pay it.
+		///
+		/// Can be called by anyone.
+		///
+		/// # <weight>
+		/// - O(L) where L is `limit`
+		/// # </weight>
+		#[weight = T::DbWeight::get().reads_writes(4, 12).saturating_mul(*limit as Weight)]
+		fn purge_closed(origin, limit: u32) {
+			let who = ensure_signed(origin)?;
+			let purged = Self::purge_closed_vaults(limit)
+				.saturating_add(market::Module::<T>::purge_closed_pools(limit));
+
+			let reward = T::PurgeReward::get().saturating_mul(Balance::from(purged));
+			let paid = <T as market::Config>::Currency::transfer(
+				&T::TreasuryAccount::get(),
+				&who,
+				reward,
+				ExistenceRequirement::KeepAlive,
+			)
+			.map_or(0, |_| reward);
 
 			// deposit event
-			Self::deposit_event(RawEvent::SetPosition(collateral_id, liqudation_rate.0, liqudation_rate.1, max_collateraization_rate.0, max_collateraization_rate.1, stability_fee.0, stability_fee.1));
+			Self::deposit_event(RawEvent::ClosedPurged(who, purged, paid));
+		}
+
+		/// Repay the debt of the vault and return its collateral, paying the stability fee of
+		/// each collateral in the asset chosen with `fee_payment`.
+		///
+		/// The fee is a share of the collateral. Paid in MTR or the native currency it is
+		/// converted at the oracle prices and the whole collateral is returned.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(6, 6).saturating_add(
+			T::DbWeight::get().reads_writes(9, 7).saturating_mul(T::MaxCollateralAssets::get() as Weight)
+		)]
+		#[transactional]
+		fn close_with_fee_payment(
+			origin,
+			#[compact] vault_id: VaultId,
+			fee_payment: FeePayment) {
+			let origin = ensure_signed(origin)?;
+			Self::do_close(origin, vault_id, fee_payment)?;
 		}
 	}
 }
@@ -345,17 +841,59 @@ decl_module! {
 decl_event! {
 	pub enum Event<T> where
 		<T as frame_system::Config>::AccountId,
+		<T as frame_system::Config>::BlockNumber,
 		Balance = Balance,
 		AssetId = AssetId,
 	{
-		/// A vault is created with the collateral. \[who, collateral, collateral_amount, meter_amount]
-		UpdateVault(AccountId, AssetId, Balance, Balance),
-		/// A vault is liquidated \[collateral, collateral_amount]
-		Liquidate(AssetId, Balance),
-		/// Close vault by paying back meter. \[collateral, collateral_amount, paid_meter_amount]
-		CloseVault(AssetId, Balance, Balance),
-		/// Set position for collateral. \[collateral, liquidation_fee[numerator/denominator], max_collateraization_rate[numerator/denominator], stability_fee[numerator/denominator]]
-		SetPosition(AssetId, Balance, Balance, U256, U256, Balance, Balance),
+		/// A vault is opened and its vault token issued. \[vault_id, owner, collateral]
+		VaultOpened(VaultId, AccountId, AssetId),
+		/// A vault token is transferred. \[vault_id, from, to]
+		VaultTransferred(VaultId, AccountId, AccountId),
+		/// A vault fell under the liquidation threshold and can be liquidated from the given block
+		/// on unless it recovers. \[vault_id, owner, liquidatable_at]
+		LiquidationWarning(VaultId, AccountId, BlockNumber),
+		/// A warned vault is back above the liquidation threshold. \[vault_id]
+		LiquidationWarningCleared(VaultId),
+		/// Collateral is added to the basket of a vault. \[vault_id, owner, collateral, amount]
+		CollateralDeposited(VaultId, AccountId, AssetId, Balance),
+		/// Collateral is withdrawn from the basket of a vault. \[vault_id, owner, collateral, amount]
+		CollateralWithdrawn(VaultId, AccountId, AssetId, Balance),
+		/// MTR is generated against a vault. \[vault_id, owner, meter_amount, total_debt]
+		DebtGenerated(VaultId, AccountId, Balance, Balance),
+		/// MTR debt of a vault is repaid. \[vault_id, owner, meter_amount, remaining_debt]
+		DebtRepaid(VaultId, AccountId, Balance, Balance),
+		/// Collateral is bought with MTR generated against the vault and deposited into it.
+		/// \[vault_id, owner, collateral, collateral_bought, meter_amount]
+		Leveraged(VaultId, AccountId, AssetId, Balance, Balance),
+		/// Collateral of a vault is sold for MTR which repays its debt.
+		/// \[vault_id, owner, collateral, collateral_sold, meter_amount]
+		Deleveraged(VaultId, AccountId, AssetId, Balance, Balance),
+		/// A collateral of a liquidated vault is sent to the market.
+		/// \[vault_id, collateral, collateral_amount, liquidation_fee]
+		CollateralLiquidated(VaultId, AssetId, Balance, Balance),
+		/// An undercollateralized vault is liquidated. \[vault_id, owner, liquidator, debt]
+		VaultLiquidated(VaultId, AccountId, AccountId, Balance),
+		/// A vault is closed and its collateral returned. \[vault_id, owner, debt]
+		VaultClosed(VaultId, AccountId, Balance),
+		/// Stability fee of a collateral is paid on closing a vault.
+		/// \[vault_id, owner, collateral, fee_in_collateral, fee_payment, amount_paid]
+		StabilityFeePaid(VaultId, AccountId, AssetId, Balance, FeePayment, Balance),
+		/// Set position for collateral. \[collateral, old_position, new_position]
+		SetPosition(AssetId, Option<CDP<Balance>>, CDP<Balance>),
+		/// Position change for collateral is announced. \[collateral, enactment_block, liquidation_fee[numerator/denominator], max_collateraization_rate[numerator/denominator], stability_fee[numerator/denominator]]
+		PositionScheduled(AssetId, BlockNumber, Balance, Balance, U256, U256, Balance, Balance),
+		/// Automatic stability fee adjustment bounds are set. \[collateral, (min, max, step)]
+		StabilityFeeBoundsSet(AssetId, Option<(Balance, Balance, Balance)>),
+		/// Stability fee is adjusted to the MTR price. \[collateral, numerator, denominator]
+		StabilityFeeAdjusted(AssetId, Balance, Balance),
+		/// Scheduled position change for collateral is cancelled. \[collateral]
+		PositionScheduleCancelled(AssetId),
+		/// Funds sent by mistake are moved out of the vault account. \[asset, amount, beneficiary]
+		FundsRescued(AssetId, Balance, AccountId),
+		/// A vault without collateral and debt is removed. \[vault_id]
+		VaultRemoved(VaultId),
+		/// Emptied vaults and pools are purged. \[who, purged, reward]
+		ClosedPurged(AccountId, u32, Balance),
 	}
 }
 
@@ -378,16 +916,115 @@ decl_error! {
 		/// Vault does not exist
 		VaultDoesNotExist,
 		/// Market does not exist
-		MarketDoesNotExist
+		MarketDoesNotExist,
+		/// Enactment delay is shorter than the minimum
+		DelayTooShort,
+		/// Position change could not be scheduled
+		ScheduleFailed,
+		/// No position change is scheduled for the collateral
+		NotScheduled,
+		/// Origin does not own the vault token
+		NotVaultOwner,
+		/// Recipient already has a vault for the collateral
+		RecipientHasVault,
+		/// Vault is still in the liquidation grace period
+		GracePeriod,
+		/// Minimum stability fee is above the maximum or the maximum is not below 100%
+		InvalidFeeBounds,
+		/// Vault already holds `MaxCollateralAssets` collateral assets
+		TooManyCollateralAssets,
+		/// Collateral is held by another vault of the owner
+		CollateralInOtherVault,
+		/// Target health factor of a leveraged position must be above 1
+		InvalidTargetRatio,
+		/// More leverage rounds than `MaxLeverageIterations`
+		TooManyIterations,
+		/// Swaps bought less than the minimum or sold more than the maximum
+		SlippageExceeded,
+		/// Repaid amount is more than the debt of the vault
+		RepayExceedsDebt,
+		/// Collateral has no registered asset metadata
+		NoAssetMetadata,
+		/// No oracle provider reports the price of the collateral
+		NoOracleFeed,
+		/// Fewer oracle providers than `MinCollateralProviders` report the price of the collateral
+		TooFewOracleProviders,
+		/// Market pair of the collateral against MTR holds less than `MinCollateralLiquidity` MTR
+		InsufficientMarketLiquidity,
+		/// More MTR would be minted in the block than `MaxMintPerBlock`
+		BlockMintLimitExceeded,
+		/// More MTR would be minted in the mint era than `MaxMintPerEra`
+		EraMintLimitExceeded,
+		/// Amount is more than the funds of the account outside of the collateral and reserves
+		ExceedsExcessFunds,
+		/// Liquidation fee must be below 100% with a nonzero denominator
+		InvalidLiquidationFee,
+		/// Maximum collateralization rate must be nonzero and require at least
+		/// `MIN_COLLATERALIZATION`
+		InvalidCollateralizationRate,
+		/// Stability fee must be below 100% with a nonzero denominator
+		InvalidStabilityFee,
+		/// Collateral is frozen in the vault
+		CollateralFrozen
 	}
 }
 
 decl_storage! {
 	trait Store for Module<T: Config> as Vault {
-		// Vault to keep the number of collatral amount and meter amount. \[collateral_amount, meter_amount]
-		pub Vault get(fn vault): map hasher(blake2_128_concat) (T::AccountId, AssetId) => Option<(Balance, Balance)>;
+		// Collateral basket and meter amount of each vault
+		pub Vaults get(fn vault): map hasher(twox_64_concat) VaultId => Option<VaultInfo>;
+		// Owner of each vault token
+		pub VaultOwners get(fn vault_owner): map hasher(twox_64_concat) VaultId => Option<T::AccountId>;
+		// Vault token held by an account for each collateral in its basket
+		pub VaultIds get(fn vault_id): map hasher(blake2_128_concat) (T::AccountId, AssetId) => Option<VaultId>;
+		pub NextVaultId get(fn next_vault_id): VaultId;
+		// Block at which an undercollateralized vault was first reported for liquidation
+		pub LiquidationWarnings get(fn liquidation_warning): map hasher(twox_64_concat) VaultId => Option<T::BlockNumber>;
 		pub Positions get(fn position): map hasher(blake2_128_concat) AssetId => Option<CDP<Balance>>;
+		// MTR minted against vaults and not burned yet
 		pub CirculatingSupply get(fn circulating_supply): Balance;
+		// Bounds of the automatic stability fee adjustment. \[min, max, step]
+		pub StabilityFeeBounds get(fn stability_fee_bounds): map hasher(twox_64_concat) AssetId => Option<(Balance, Balance, Balance)>;
+		// MTR minted in the last block anything was minted in. \[block, amount]
+		pub MintedInBlock get(fn minted_in_block): (T::BlockNumber, Balance);
+		// MTR minted in the last mint era anything was minted in. \[era, amount]
+		pub MintedInEra get(fn minted_in_era): (T::BlockNumber, Balance);
+		// Warned vaults liquidated in `on_idle` after the grace period, by queue position. \[vault, warned_at]
+		pub LiquidationQueue get(fn queued_liquidation): map hasher(twox_64_concat) u32 => Option<(VaultId, T::BlockNumber)>;
+		// Queue position of the next liquidation
+		pub LiquidationQueueHead get(fn liquidation_queue_head): u32;
+		// Number of liquidations waiting in the queue
+		pub LiquidationBacklog get(fn liquidation_backlog): u32;
+		// Vault id the next purge of emptied vaults starts at
+		pub PurgeCursor get(fn purge_cursor): VaultId;
+		// Vault aggregates of the last `HistoryDepth` eras
+		pub VaultHistory get(fn vault_era_stats): map hasher(twox_64_concat) EraIndex => Option<VaultEraStats>;
+		// Era the running aggregates are collected for
+		pub StatsEra get(fn stats_era): EraIndex;
+		// Debt of the vaults liquidated in `StatsEra`
+		pub LiquidatedInEra get(fn liquidated_in_era): Balance;
+		// Collateral of all vaults, held in the system account
+		pub TotalCollateral get(fn total_collateral): map hasher(twox_64_concat) AssetId => Balance;
+		// Debt of all vaults
+		pub TotalDebt get(fn total_debt): Balance;
+		// Number of open vaults
+		pub VaultCount get(fn vault_count): u32;
+		// Liquidation and stability fees collected in the vault account
+		pub CollectedFees get(fn collected_fees): map hasher(twox_64_concat) AssetId => Balance;
+	} add_extra_genesis {
+		/// Collateral positions as `(collateral_id, liquidation_fee, max_collateraization_rate,
+		/// stability_fee)`, set without the onboarding checks of `set_position`
+		config(positions): Vec<(AssetId, (Balance, Balance), (U256, U256), (Balance, Balance))>;
+		build(|config: &GenesisConfig| {
+			for (collateral_id, liquidation_fee, max_collateraization_rate, stability_fee) in
+				config.positions.iter().cloned()
+			{
+				let position = CDP { liquidation_fee, max_collateraization_rate, stability_fee };
+				Module::<T>::check_position(&position).expect("Genesis positions are valid");
+				Positions::insert(collateral_id, position);
+			}
+			StorageVersion::new(1).put::<Module<T>>();
+		});
 	}
 }
 
@@ -402,31 +1039,818 @@ impl<T: Config> Module<T> {
 		<T as Config>::SystemPalletId::get().into_account()
 	}
 
-	fn is_cdp_valid(
-		position: &CDP<Balance>,
-		collateral_price: Balance,
-		collateral_amount: Balance,
-		request_price: Balance,
-		request_amount: Balance,
-	) -> bool {
-		let collateral_price_256 = Self::to_u256(collateral_price);
-		let mtr_price_256 = Self::to_u256(request_price);
-		let total_collateral_256 = Self::to_u256(collateral_amount);
-		let collateral = collateral_price_256
-			.checked_mul(total_collateral_256)
-			.expect("Multiplication overflow");
-		let total_request_256 = Self::to_u256(request_amount);
-		let request =
-			mtr_price_256.checked_mul(total_request_256).expect("Multiplication overflow");
-		let determinant = collateral
-			.checked_div(position.max_collateraization_rate.1)
-			.expect("divided by zero")
-			.checked_mul(position.max_collateraization_rate.0)
-			.unwrap_or(U256::from(0));
-		request < determinant
-	}
-
-	pub fn to_u256(value: Balance) -> U256 {
-		U256::from(UniqueSaturatedInto::<u128>::unique_saturated_into(value))
+	/// Vault of `who` holding the collateral in its basket.
+	pub fn vault_of(who: &T::AccountId, collateral_id: AssetId) -> Option<VaultInfo> {
+		Self::vault_id((who.clone(), collateral_id)).and_then(Self::vault)
+	}
+
+	/// Open vaults of `who` with their health factor at current oracle prices.
+	pub fn vaults_of(who: &T::AccountId) -> Vec<VaultPosition> {
+		let mut vault_ids: Vec<VaultId> = Positions::iter()
+			.filter_map(|(collateral_id, _)| Self::vault_id((who.clone(), collateral_id)))
+			.collect();
+		// A vault is listed under every collateral of its basket
+		vault_ids.sort();
+		vault_ids.dedup();
+		vault_ids
+			.into_iter()
+			.filter_map(|vault_id| Some(Self::vault_position(vault_id, Self::vault(vault_id)?)))
+			.collect()
+	}
+
+	/// Vault `vault_id` with its health factor at current oracle prices, and the storage keys of
+	/// the vault, the positions of its collateral and the prices of its collateral and MTR.
+	pub fn proven_vault(vault_id: VaultId) -> Proven<Option<VaultPosition>> {
+		let mut keys = vec![Vaults::hashed_key_for(vault_id)];
+		let vault = match Self::vault(vault_id) {
+			Some(vault) => vault,
+			None => return Proven { value: None, keys },
+		};
+		for collateral_id in vault.collateral.keys() {
+			keys.push(Positions::hashed_key_for(collateral_id));
+			keys.extend(oracle::Module::<T>::price_keys(*collateral_id));
+		}
+		keys.extend(oracle::Module::<T>::price_keys(T::MtrAssetId::get()));
+		keys.sort();
+		keys.dedup();
+		Proven { value: Some(Self::vault_position(vault_id, vault)), keys }
+	}
+
+	fn vault_position(vault_id: VaultId, vault: VaultInfo) -> VaultPosition {
+		let health_factor = Self::health_factor(&vault);
+		VaultPosition {
+			vault_id,
+			collateral: vault.collateral.into_iter().collect(),
+			debt: vault.debt,
+			health_factor,
+		}
+	}
+
+	/// Every vault with its owner, ordered by id.
+	pub fn vault_snapshots() -> Vec<VaultSnapshot<T::AccountId>> {
+		let mut vaults: Vec<_> = Vaults::iter()
+			.filter_map(|(vault_id, vault)| {
+				Some(VaultSnapshot {
+					vault_id,
+					owner: Self::vault_owner(vault_id)?,
+					collateral: vault.collateral.into_iter().collect(),
+					debt: vault.debt,
+				})
+			})
+			.collect();
+		vaults.sort_by_key(|vault| vault.vault_id);
+		vaults
+	}
+
+	/// Assets with a collateral position
+	pub fn collateral_ids() -> Vec<AssetId> {
+		Positions::iter_keys().collect()
+	}
+
+	/// Move the vault token from `from` to `to`, for use by other pallets and contracts.
+	pub fn do_transfer_vault(
+		from: &T::AccountId,
+		to: &T::AccountId,
+		vault_id: VaultId,
+	) -> DispatchResult {
+		let vault = Self::owned_vault(from, vault_id)?;
+		if from == to {
+			return Ok(())
+		}
+		for collateral_id in vault.collateral.keys() {
+			Self::ensure_unfrozen(from, *collateral_id, 0)?;
+		}
+		ensure!(
+			vault
+				.collateral
+				.keys()
+				.all(|collateral_id| !VaultIds::<T>::contains_key((to.clone(), *collateral_id))),
+			Error::<T>::RecipientHasVault
+		);
+
+		for collateral_id in vault.collateral.keys() {
+			VaultIds::<T>::remove((from.clone(), *collateral_id));
+			VaultIds::<T>::insert((to.clone(), *collateral_id), vault_id);
+		}
+		VaultOwners::<T>::insert(vault_id, to.clone());
+
+		Self::deposit_event(RawEvent::VaultTransferred(vault_id, from.clone(), to.clone()));
+		Ok(())
+	}
+
+	// Vault `vault_id` if it is owned by `who`
+	fn owned_vault(who: &T::AccountId, vault_id: VaultId) -> Result<VaultInfo, DispatchError> {
+		let owner = Self::vault_owner(vault_id).ok_or(Error::<T>::VaultDoesNotExist)?;
+		ensure!(&owner == who, Error::<T>::NotVaultOwner);
+		Self::vault(vault_id).ok_or_else(|| Error::<T>::VaultDoesNotExist.into())
+	}
+
+	// Fails if the vault of `who` for `collateral_id` would keep less than its frozen collateral
+	fn ensure_unfrozen(
+		who: &T::AccountId,
+		collateral_id: AssetId,
+		remaining: Balance,
+	) -> DispatchResult {
+		ensure!(
+			remaining >= T::FrozenCollateral::frozen_collateral(who, collateral_id),
+			Error::<T>::CollateralFrozen
+		);
+		Ok(())
+	}
+
+	// Onboarding checklist a collateral has to pass before its first position is set
+	fn check_onboarding(collateral_id: AssetId) -> DispatchResult {
+		let symbol = <<T as Config>::Assets as fungibles::metadata::Inspect<T::AccountId>>::symbol(
+			&collateral_id,
+		);
+		ensure!(!symbol.is_empty(), Error::<T>::NoAssetMetadata);
+		let providers = oracle::Module::<T>::reporting_providers(collateral_id);
+		ensure!(providers > 0, Error::<T>::NoOracleFeed);
+		ensure!(providers >= T::MinCollateralProviders::get(), Error::<T>::TooFewOracleProviders);
+		let mtr = T::MtrAssetId::get();
+		let lpt = market::Pairs::get((mtr, collateral_id)).ok_or(Error::<T>::MarketDoesNotExist)?;
+		let reserves = market::Reserves::get(lpt);
+		let mtr_reserve = if mtr < collateral_id { reserves.0 } else { reserves.1 };
+		ensure!(
+			mtr_reserve >= T::MinCollateralLiquidity::get(),
+			Error::<T>::InsufficientMarketLiquidity
+		);
+		Ok(())
+	}
+
+	// Add `amount` of `collateral_id` to the basket of the vault, bounded by
+	// `MaxCollateralAssets` assets
+	fn add_collateral(
+		vault: &mut VaultInfo,
+		collateral_id: AssetId,
+		amount: Balance,
+	) -> DispatchResult {
+		ensure!(
+			vault.collateral.contains_key(&collateral_id) ||
+				(vault.collateral.len() as u32) < T::MaxCollateralAssets::get(),
+			Error::<T>::TooManyCollateralAssets
+		);
+		let total = vault.collateral.entry(collateral_id).or_default();
+		*total = total.saturating_add(amount);
+		Ok(())
+	}
+
+	// Mint `amount` of MTR generated against a vault to `who`
+	fn mint_mtr(who: &T::AccountId, amount: Balance) -> DispatchResult {
+		Self::record_mint(amount)?;
+		<T as Config>::Assets::mint_into(T::MtrAssetId::get(), who, amount)?;
+		CirculatingSupply::mutate(|supply| *supply = supply.saturating_add(amount));
+		Ok(())
+	}
+
+	// Liquidate the collateral of the vault into the market pairs against MTR, the liquidation
+	// fee is paid by the liquidator
+	fn liquidate(
+		vault_id: VaultId,
+		vault: VaultInfo,
+		owner: T::AccountId,
+		liquidator: T::AccountId,
+	) -> DispatchResult {
+		// Check pairs in the market for every collateral before liquidating any of it
+		let mut liquidations = Vec::with_capacity(vault.collateral.len());
+		for (collateral_id, collateral_amount) in vault.collateral {
+			let position =
+				Self::position(collateral_id).ok_or(Error::<T>::CollateralNotSupported)?;
+			let lpt = market::Pairs::get((T::MtrAssetId::get(), collateral_id))
+				.ok_or(Error::<T>::MarketDoesNotExist)?;
+			liquidations.push((collateral_id, collateral_amount, position.liquidation_fee, lpt));
+		}
+
+		// liquidate the vault
+		for (collateral_id, collateral_amount, liquidation_rate, lpt) in liquidations {
+			// Pay liquidation fee to the liquidator
+			let fee = collateral_amount / liquidation_rate.1 * liquidation_rate.0;
+			<T as Config>::Assets::transfer(
+				collateral_id,
+				&liquidator,
+				&Self::account_id(),
+				fee,
+				true,
+			)?;
+			Self::collect_fee(collateral_id, fee);
+
+			let rest = collateral_amount - fee;
+			// Send collateral to the market
+			let reserves = market::Reserves::get(lpt);
+			let liquidated = rest + reserves.1;
+			log!(
+				debug,
+				"vault {} liquidates {} of {} into {:?} at reserves {:?}, paying {} to {:?}",
+				vault_id,
+				rest,
+				collateral_id,
+				lpt,
+				reserves,
+				fee,
+				liquidator,
+			);
+			market::Module::<T>::_set_reserves(
+				T::MtrAssetId::get(),
+				collateral_id,
+				reserves.0,
+				liquidated,
+				lpt,
+			);
+
+			T::FrozenCollateral::on_liquidation(&owner, collateral_id);
+
+			// deposit event
+			Self::deposit_event(RawEvent::CollateralLiquidated(
+				vault_id,
+				collateral_id,
+				collateral_amount,
+				fee,
+			));
+		}
+
+		// destroy the vault
+		Self::burn_vault_token(vault_id);
+		LiquidatedInEra::mutate(|liquidated| *liquidated = liquidated.saturating_add(vault.debt));
+
+		// deposit event
+		Self::deposit_event(RawEvent::VaultLiquidated(vault_id, owner, liquidator, vault.debt));
+		Ok(())
+	}
+
+	// Queue the warned vault for liquidation in `on_idle` once its grace period ended
+	fn queue_liquidation(vault_id: VaultId, warned_at: T::BlockNumber) {
+		let backlog = Self::liquidation_backlog();
+		let position = Self::liquidation_queue_head().wrapping_add(backlog);
+		<LiquidationQueue<T>>::insert(position, (vault_id, warned_at));
+		LiquidationBacklog::put(backlog.saturating_add(1));
+	}
+
+	// Weight of carrying out a queued liquidation of a vault with the most collateral assets
+	fn queued_liquidation_weight() -> Weight {
+		let assets = T::MaxCollateralAssets::get() as Weight;
+		T::DbWeight::get().reads_writes(8 + 9 * assets, 6 + 5 * assets)
+	}
+
+	// Warning of a vault unless it expired. The price may have recovered in between, so a vault
+	// warned more than twice the grace period ago has to be warned again.
+	fn active_warning(vault_id: VaultId, now: T::BlockNumber) -> Option<T::BlockNumber> {
+		let grace = T::LiquidationGracePeriod::get();
+		Self::liquidation_warning(vault_id)
+			.filter(|at| now < at.saturating_add(grace).saturating_add(grace))
+	}
+
+	/// Liquidates the queued vaults whose grace period ended, oldest warning first, within
+	/// `remaining_weight` and `MaxIdleLiquidations`.
+	///
+	/// Vaults which recovered in the meantime get their warning cleared, expired warnings are
+	/// removed. Entries of vaults which were liquidated by a keeper, closed or warned again are
+	/// dropped.
+	fn process_liquidation_queue(now: T::BlockNumber, remaining_weight: Weight) -> Weight {
+		let per_liquidation = Self::queued_liquidation_weight();
+		let mut used = T::DbWeight::get().reads(3);
+		let mut processed = 0;
+		while processed < T::MaxIdleLiquidations::get() &&
+			used.saturating_add(per_liquidation) <= remaining_weight
+		{
+			let head = Self::liquidation_queue_head();
+			let (vault_id, warned_at) = match Self::queued_liquidation(head) {
+				Some(entry) => entry,
+				None => break,
+			};
+			// Entries are queued in the order of their warnings, so the rest is not due either
+			if now < warned_at.saturating_add(T::LiquidationGracePeriod::get()) {
+				break
+			}
+			<LiquidationQueue<T>>::remove(head);
+			LiquidationQueueHead::put(head.wrapping_add(1));
+			LiquidationBacklog::mutate(|backlog| *backlog = backlog.saturating_sub(1));
+			used = used.saturating_add(per_liquidation);
+			processed += 1;
+
+			if Self::liquidation_warning(vault_id) == Some(warned_at) {
+				if Self::active_warning(vault_id, now).is_some() {
+					// A vault failing to liquidate stays warned for the keepers
+					let _ = Self::liquidate_queued(vault_id);
+				} else {
+					<LiquidationWarnings<T>>::remove(vault_id);
+				}
+			}
+		}
+		used
+	}
+
+	// Liquidate a queued vault out of the collateral held by the system account, or clear its
+	// warning if it recovered
+	#[transactional]
+	fn liquidate_queued(vault_id: VaultId) -> DispatchResult {
+		let vault = Self::vault(vault_id).ok_or(Error::<T>::VaultDoesNotExist)?;
+		let owner = Self::vault_owner(vault_id).ok_or(Error::<T>::VaultDoesNotExist)?;
+		let valid = Self::is_vault_valid(&vault)?;
+		log!(
+			debug,
+			"queued liquidation of vault {}: valid {}, health factor {:?}",
+			vault_id,
+			valid,
+			Self::health_factor(&vault),
+		);
+		if valid {
+			<LiquidationWarnings<T>>::remove(vault_id);
+			Self::deposit_event(RawEvent::LiquidationWarningCleared(vault_id));
+			return Ok(())
+		}
+		Self::liquidate(vault_id, vault, owner, Self::sys_account_id())
+	}
+
+	/// Counts `amount` of newly minted MTR against the limits of the current block and mint era.
+	fn record_mint(amount: Balance) -> DispatchResult {
+		let now = <frame_system::Pallet<T>>::block_number();
+		let era = now / T::MintEraLength::get();
+		let (block, minted) = <MintedInBlock<T>>::get();
+		let in_block = if block == now { minted.saturating_add(amount) } else { amount };
+		ensure!(in_block <= T::MaxMintPerBlock::get(), Error::<T>::BlockMintLimitExceeded);
+		let (last_era, minted) = <MintedInEra<T>>::get();
+		let in_era = if last_era == era { minted.saturating_add(amount) } else { amount };
+		ensure!(in_era <= T::MaxMintPerEra::get(), Error::<T>::EraMintLimitExceeded);
+
+		<MintedInBlock<T>>::put((now, in_block));
+		<MintedInEra<T>>::put((era, in_era));
+		Ok(())
+	}
+
+	// Repays the debt of `vault_id` of `origin`, pays the stability fee of each collateral in the
+	// asset of `fee_payment` and returns the rest of the collateral
+	fn do_close(
+		origin: T::AccountId,
+		vault_id: VaultId,
+		fee_payment: FeePayment,
+	) -> DispatchResult {
+		let vault = Self::owned_vault(&origin, vault_id)?;
+		for collateral_id in vault.collateral.keys() {
+			Self::ensure_unfrozen(&origin, *collateral_id, 0)?;
+		}
+		// Check whether cdp is valid and safe from liquidation.
+		ensure!(Self::is_vault_valid(&vault)?, Error::<T>::AddMoreCollateral);
+		// Pay back the debt
+		Self::burn_mtr(&origin, vault.debt)?;
+		// close the vault
+		for (collateral_id, collateral_amount) in vault.collateral {
+			let position =
+				Self::position(collateral_id).ok_or(Error::<T>::CollateralNotSupported)?;
+			let stability_rate = position.stability_fee;
+			let fee = collateral_amount / stability_rate.1 * stability_rate.0;
+			let paid = Self::pay_stability_fee(&origin, collateral_id, fee, fee_payment)?;
+			Self::deposit_event(RawEvent::StabilityFeePaid(
+				vault_id,
+				origin.clone(),
+				collateral_id,
+				fee,
+				fee_payment,
+				paid,
+			));
+
+			let rest = match fee_payment {
+				FeePayment::Collateral => collateral_amount - fee,
+				FeePayment::Mtr | FeePayment::Native => collateral_amount,
+			};
+
+			// Give back the collateral
+			let _ = <T as Config>::Assets::transfer(
+				collateral_id,
+				&Self::sys_account_id(),
+				&origin,
+				rest,
+				true,
+			);
+		}
+
+		// destroy the vault
+		Self::burn_vault_token(vault_id);
+
+		// deposit events
+		if !vault.debt.is_zero() {
+			Self::deposit_event(RawEvent::DebtRepaid(vault_id, origin.clone(), vault.debt, 0));
+		}
+		Self::deposit_event(RawEvent::VaultClosed(vault_id, origin, vault.debt));
+		Ok(())
+	}
+
+	// Pay the stability fee of `fee` of `collateral_id` in the asset of `payment`, returning the
+	// amount paid in it
+	fn pay_stability_fee(
+		who: &T::AccountId,
+		collateral_id: AssetId,
+		fee: Balance,
+		payment: FeePayment,
+	) -> Result<Balance, DispatchError> {
+		match payment {
+			FeePayment::Collateral => {
+				<T as Config>::Assets::transfer(
+					collateral_id,
+					&Self::sys_account_id(),
+					&Self::account_id(),
+					fee,
+					true,
+				)?;
+				Self::collect_fee(collateral_id, fee);
+				Ok(fee)
+			},
+			FeePayment::Mtr => {
+				let amount = Self::quote_fee(collateral_id, fee, T::MtrAssetId::get())?;
+				Self::burn_mtr(who, amount)?;
+				Ok(amount)
+			},
+			FeePayment::Native => {
+				let amount = Self::quote_fee(collateral_id, fee, T::NativeAssetId::get())?;
+				<T as market::Config>::Currency::transfer(
+					who,
+					&T::TreasuryAccount::get(),
+					amount,
+					ExistenceRequirement::KeepAlive,
+				)?;
+				Ok(amount)
+			},
+		}
+	}
+
+	// Amount of `asset` worth `fee` of `collateral_id` at the oracle prices, rounded up
+	fn quote_fee(
+		collateral_id: AssetId,
+		fee: Balance,
+		asset: AssetId,
+	) -> Result<Balance, DispatchError> {
+		let value = value_of(oracle::Module::<T>::price(collateral_id)?, fee);
+		let price = to_u256(oracle::Module::<T>::price(asset)?.into_inner());
+		ensure!(!price.is_zero(), Error::<T>::NoOracleFeed);
+		Ok(saturating_to_u128((value + price - 1) / price))
+	}
+
+	// Burn `amount` of MTR of `who` repaying the debt of a vault
+	fn burn_mtr(who: &T::AccountId, amount: Balance) -> DispatchResult {
+		<T as Config>::Assets::burn_from(T::MtrAssetId::get(), who, amount)?;
+		CirculatingSupply::mutate(|supply| *supply = supply.saturating_sub(amount));
+		Ok(())
+	}
+
+	fn mint_vault_token(owner: &T::AccountId, collateral_id: AssetId) -> VaultId {
+		let vault_id = NextVaultId::mutate(|id| {
+			let vault_id = *id;
+			*id += 1;
+			vault_id
+		});
+		VaultOwners::<T>::insert(vault_id, owner.clone());
+		VaultIds::<T>::insert((owner.clone(), collateral_id), vault_id);
+
+		Self::deposit_event(RawEvent::VaultOpened(vault_id, owner.clone(), collateral_id));
+		vault_id
+	}
+
+	// Store `vault`, keeping the totals over all vaults in line with it
+	fn put_vault(vault_id: VaultId, vault: VaultInfo) {
+		let previous = Self::vault(vault_id).unwrap_or_else(|| {
+			VaultCount::mutate(|count| *count = count.saturating_add(1));
+			VaultInfo::default()
+		});
+		Self::update_totals(&previous, &vault);
+		Vaults::insert(vault_id, vault);
+	}
+
+	fn update_totals(previous: &VaultInfo, vault: &VaultInfo) {
+		for (collateral_id, amount) in previous.collateral.iter() {
+			TotalCollateral::mutate(collateral_id, |total| *total = total.saturating_sub(*amount));
+		}
+		for (collateral_id, amount) in vault.collateral.iter() {
+			TotalCollateral::mutate(collateral_id, |total| *total = total.saturating_add(*amount));
+		}
+		TotalDebt::mutate(|total| {
+			*total = total.saturating_sub(previous.debt).saturating_add(vault.debt)
+		});
+	}
+
+	// Count `fee` of `asset` paid into the vault account
+	fn collect_fee(asset: AssetId, fee: Balance) {
+		CollectedFees::mutate(asset, |collected| *collected = collected.saturating_add(fee));
+	}
+
+	fn burn_vault_token(vault_id: VaultId) {
+		let vault = match Vaults::take(vault_id) {
+			Some(vault) => {
+				VaultCount::mutate(|count| *count = count.saturating_sub(1));
+				Self::update_totals(&vault, &VaultInfo::default());
+				vault
+			},
+			None => VaultInfo::default(),
+		};
+		<LiquidationWarnings<T>>::remove(vault_id);
+		if let Some(owner) = VaultOwners::<T>::take(vault_id) {
+			for collateral_id in vault.collateral.keys() {
+				VaultIds::<T>::remove((owner.clone(), *collateral_id));
+			}
+		}
+	}
+
+	// Remove the vault if it has neither collateral nor debt left
+	fn remove_if_empty(vault_id: VaultId) -> bool {
+		match Self::vault(vault_id) {
+			Some(vault) if vault.collateral.is_empty() && vault.debt.is_zero() => {
+				Self::burn_vault_token(vault_id);
+				Self::deposit_event(RawEvent::VaultRemoved(vault_id));
+				true
+			},
+			_ => false,
+		}
+	}
+
+	/// Checks the next `limit` vault ids from `PurgeCursor` on and removes the empty vaults among
+	/// them, returning how many were removed.
+	fn purge_closed_vaults(limit: u32) -> u32 {
+		let next_vault_id = Self::next_vault_id();
+		if next_vault_id == 0 {
+			return 0
+		}
+		let mut vault_id = Self::purge_cursor();
+		let mut purged = 0;
+		for _ in 0..(limit as VaultId).min(next_vault_id) {
+			if vault_id >= next_vault_id {
+				vault_id = 0;
+			}
+			if Self::remove_if_empty(vault_id) {
+				purged += 1;
+			}
+			vault_id += 1;
+		}
+		PurgeCursor::put(vault_id);
+		purged
+	}
+
+	// Moves the vaults of the former `Vault` item, keyed by owner and collateral, into vault ids
+	// with single collateral baskets
+	fn migrate_to_vault_ids() -> Weight {
+		if StorageVersion::get::<Module<T>>() >= 1 {
+			return T::DbWeight::get().reads(1)
+		}
+		let mut count: Weight = 0;
+		let mut next_id = NextVaultId::get();
+		for ((owner, collateral_id), (collateral, debt)) in storage_key_iter::<
+			(T::AccountId, AssetId),
+			(Balance, Balance),
+			Blake2_128Concat,
+		>(b"Vault", b"Vault")
+		.drain()
+		{
+			count += 1;
+			let vault_id = next_id;
+			next_id += 1;
+			Self::put_vault(
+				vault_id,
+				VaultInfo { collateral: BTreeMap::from([(collateral_id, collateral)]), debt },
+			);
+			VaultOwners::<T>::insert(vault_id, owner.clone());
+			VaultIds::<T>::insert((owner, collateral_id), vault_id);
+		}
+		NextVaultId::put(next_id);
+		StorageVersion::new(1).put::<Module<T>>();
+		T::DbWeight::get().reads_writes(count + 3, 4 * count + 2)
+	}
+	/// Vault aggregates of the eras in the history, ordered by era.
+	pub fn vault_history() -> Vec<VaultEraStats> {
+		let mut history: Vec<_> = VaultHistory::iter_values().collect();
+		history.sort_by_key(|stats| stats.era);
+		history
+	}
+
+	// Record the vault aggregates of the ended era once the era finder moves on
+	fn record_era_stats() -> Weight {
+		let era = <T as oracle::Config>::EraFinder::active_era();
+		let ended = Self::stats_era();
+		if era <= ended {
+			return T::DbWeight::get().reads(1)
+		}
+
+		// The totals are kept up to date as vaults change, and there is one entry per collateral
+		let mut collateral: Vec<(AssetId, Balance)> =
+			TotalCollateral::iter().filter(|(_, amount)| !amount.is_zero()).collect();
+		collateral.sort_unstable_by_key(|(collateral_id, _)| *collateral_id);
+		let assets = collateral.len() as Weight;
+		VaultHistory::insert(
+			ended,
+			VaultEraStats {
+				era: ended,
+				collateral,
+				debt: Self::total_debt(),
+				vaults: Self::vault_count(),
+				liquidated: LiquidatedInEra::take(),
+			},
+		);
+		if let Some(expired) = ended.checked_sub(T::HistoryDepth::get()) {
+			VaultHistory::remove(expired);
+		}
+		StatsEra::put(era);
+		T::DbWeight::get().reads_writes(5 + assets, 4)
+	}
+
+	// Move stability fees one step towards restoring the MTR peg
+	fn adjust_stability_fees() -> Weight {
+		let mtr_price = match oracle::Module::<T>::price(T::MtrAssetId::get()) {
+			Ok(price) => price,
+			Err(_) => return T::DbWeight::get().reads(1),
+		};
+		let peg = T::MtrPeg::get();
+		let tolerance = FixedU128::from_inner(T::PegTolerance::get() * peg.into_inner());
+		let below_peg = mtr_price < peg.saturating_sub(tolerance);
+		let above_peg = mtr_price > peg.saturating_add(tolerance);
+		if !below_peg && !above_peg {
+			return T::DbWeight::get().reads(1)
+		}
+
+		let mut count: Weight = 0;
+		for (collateral_id, (min, max, step)) in StabilityFeeBounds::iter() {
+			count += 1;
+			Positions::mutate(collateral_id, |position| {
+				if let Some(position) = position {
+					let (fee, fee_base) = position.stability_fee;
+					// The denominator may have changed since the bounds were set
+					let adjusted = match below_peg {
+						true => fee.saturating_add(step),
+						false => fee.saturating_sub(step),
+					}
+					.max(min)
+					.min(max)
+					.min(fee_base.saturating_sub(1));
+					if adjusted != fee {
+						position.stability_fee.0 = adjusted;
+						Self::deposit_event(RawEvent::StabilityFeeAdjusted(
+							collateral_id,
+							adjusted,
+							fee_base,
+						));
+					}
+				}
+			});
+		}
+		T::DbWeight::get().reads_writes(1 + 2 * count, count)
+	}
+
+	// Bounds of the risk parameters of a position
+	fn check_position(position: &CDP<Balance>) -> DispatchResult {
+		let (fee, fee_base) = position.liquidation_fee;
+		ensure!(fee < fee_base, Error::<T>::InvalidLiquidationFee);
+		let (rate, rate_base) = position.max_collateraization_rate;
+		ensure!(
+			!rate.is_zero() &&
+				rate.saturating_mul(MIN_COLLATERALIZATION.0.into()) <=
+					rate_base.saturating_mul(MIN_COLLATERALIZATION.1.into()),
+			Error::<T>::InvalidCollateralizationRate
+		);
+		let (stability_fee, stability_fee_base) = position.stability_fee;
+		ensure!(stability_fee < stability_fee_base, Error::<T>::InvalidStabilityFee);
+		Ok(())
+	}
+
+	// Scheduler task name for position changes of a collateral
+	fn schedule_id(collateral_id: AssetId) -> Vec<u8> {
+		(VAULT_SCHEDULE_ID, collateral_id).encode()
+	}
+
+	// Price weighted sum of the collateral basket, each asset counted at the maximum
+	// collateralization rate of its position
+	fn borrowing_capacity(vault: &VaultInfo) -> Result<U256, DispatchError> {
+		vault
+			.collateral
+			.iter()
+			.try_fold(U256::zero(), |capacity, (collateral_id, amount)| {
+				let position =
+					Self::position(collateral_id).ok_or(Error::<T>::CollateralNotSupported)?;
+				let price = oracle::Module::<T>::price(*collateral_id)?;
+				let rate = position.max_collateraization_rate;
+				let weighted = value_of(price, *amount)
+					.checked_div(rate.1)
+					.and_then(|value| value.checked_mul(rate.0))
+					.unwrap_or_default();
+				Ok(capacity.saturating_add(weighted))
+			})
+	}
+
+	// Borrowing capacity over the value of the debt, using the same rounding as `is_vault_valid`
+	fn health_factor(vault: &VaultInfo) -> Option<FixedU128> {
+		let mtr_price = oracle::Module::<T>::price(T::MtrAssetId::get()).ok()?;
+		let request = value_of(mtr_price, vault.debt);
+		if request.is_zero() {
+			return None
+		}
+		let capacity = Self::borrowing_capacity(vault).ok()?;
+		let ratio = capacity.checked_mul(U256::from(FixedU128::accuracy()))? / request;
+		Some(FixedU128::from_inner(saturating_to_u128(ratio)))
+	}
+
+	// Whether the borrowing capacity of the collateral basket covers the value of the debt
+	fn is_vault_valid(vault: &VaultInfo) -> Result<bool, DispatchError> {
+		let capacity = Self::borrowing_capacity(vault)?;
+		let mtr_price = oracle::Module::<T>::price(T::MtrAssetId::get())?;
+		let request = value_of(mtr_price, vault.debt);
+		Ok(request < capacity)
+	}
+}
+
+/// Value of `amount` at the oracle `price`, scaled by the accuracy of `FixedU128`.
+///
+/// Computed in `U256` so that the values of different assets compare without rounding.
+fn value_of(price: FixedU128, amount: Balance) -> U256 {
+	to_u256(price.into_inner()).saturating_mul(to_u256(amount))
+}
+
+/// `InvalidTransaction::Custom` code of calls relying on a price older than `MaxPriceAge`
+pub const PRICE_TOO_OLD: u8 = 1;
+
+/// Rejects `generate` and `liquidate_vault` at validation when the oracle price of MTR or of a
+/// collateral of the vault was reported more than `MaxPriceAge` blocks ago.
+///
+/// Without this check the calls fail, or use an outdated price, only once dispatched, after
+/// taking up block space. Liquidations still go through on the market TWAP the oracle falls
+/// back to while the reports of an asset are stale, so that an oracle outage does not keep
+/// undercollateralized vaults open.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct CheckPriceAge<T: Config + Send + Sync>(PhantomData<T>);
+
+impl<T: Config + Send + Sync> CheckPriceAge<T> {
+	pub fn new() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<T: Config + Send + Sync> Default for CheckPriceAge<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: Config + Send + Sync> sp_std::fmt::Debug for CheckPriceAge<T> {
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		write!(f, "CheckPriceAge")
+	}
+}
+
+impl<T: Config + Send + Sync> SignedExtension for CheckPriceAge<T>
+where
+	<T as frame_system::Config>::Call: IsSubType<Call<T>>,
+{
+	const IDENTIFIER: &'static str = "CheckPriceAge";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::Call;
+	type AdditionalSigned = ();
+	type Pre = ();
+
+	fn additional_signed(&self) -> sp_std::result::Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		let (vault_id, mut assets, liquidation) = match call.is_sub_type() {
+			Some(Call::generate(_, collateral_id, _)) => (
+				Module::<T>::vault_id((who.clone(), *collateral_id)),
+				vec![*collateral_id],
+				false,
+			),
+			Some(Call::liquidate_vault(vault_id)) => (Some(*vault_id), vec![], true),
+			_ => return Ok(ValidTransaction::default()),
+		};
+		assets.push(T::MtrAssetId::get());
+		if let Some(vault) = vault_id.and_then(Module::<T>::vault) {
+			assets.extend(vault.collateral.into_keys());
+		}
+
+		let now = frame_system::Pallet::<T>::block_number();
+		for asset in assets {
+			let fresh = oracle::Module::<T>::price_updated_at(asset)
+				.map_or(false, |at| now.saturating_sub(at) <= T::MaxPriceAge::get());
+			let fallback = liquidation &&
+				matches!(
+					oracle::Module::<T>::price_with_source(asset),
+					Ok((_, oracle::PriceSource::Twap))
+				);
+			ensure!(fresh || fallback, InvalidTransaction::Custom(PRICE_TOO_OLD));
+		}
+		Ok(ValidTransaction::default())
+	}
+}
+
+impl<T: Config> MintGuard for Module<T> {
+	fn on_mint(amount: Balance) -> DispatchResult {
+		Self::record_mint(amount)
+	}
+}
+
+impl<T: Config> AccountedFunds<T::AccountId> for Module<T> {
+	fn accounted(account: &T::AccountId, asset: AssetId) -> Balance {
+		if *account != Self::sys_account_id() {
+			return 0
+		}
+		Self::total_collateral(asset)
 	}
 }
@@ -0,0 +1,394 @@
+// Ensure we're `no_std` when compiling for Wasm.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Voting Power Pallet
+//!
+//! Counts the governance tokens an account provides as liquidity or as vault collateral in its
+//! voting power, so that DeFi users are not disenfranchised for putting their tokens to work.
+//!
+//! An account takes a snapshot of its positions with `snapshot`: the liquidity tokens of the
+//! governance token pools it lists, and the governance token collateral of its vault. The
+//! governance tokens they can be redeemed for at that time are its voting power on top of its
+//! free balance, which the pallet reports as the `Currency` of the voting pallet. The positions
+//! of a snapshot are frozen, the liquidity tokens through the `FrozenBalance` of the assets and
+//! the collateral through the `FrozenCollateral` of the vaults, so that they can not back the
+//! votes of another account. Taking a new snapshot keeps the positions of the previous one.
+//! Liquidating the vault takes the collateral regardless, which then stops counting in the
+//! voting power of the snapshot.
+//!
+//! The positions are released with `release` once the votes of the account are covered by its
+//! free balance alone, i.e. after the locks of its votes expired.
+
+use codec::{Decode, Encode};
+use frame_support::{
+	dispatch::DispatchResult,
+	traits::{
+		BalanceStatus, Currency, ExistenceRequirement, LockIdentifier, LockableCurrency,
+		ReservableCurrency, SignedImbalance, WithdrawReasons,
+	},
+};
+use pallet_assets::FrozenBalance;
+use primitives::{
+	traits::{FrozenCollateral, GovernancePositions},
+	AssetId, Balance, LpTokenId,
+};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{Saturating, Zero},
+	DispatchError, RuntimeDebug,
+};
+use sp_std::vec::Vec;
+
+pub use pallet::*;
+
+/// Positions counted in the voting power of an account.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct VotingSnapshot<BlockNumber> {
+	/// Liquidity tokens of the governance token pools, frozen
+	pub liquidity: Vec<(LpTokenId, Balance)>,
+	/// Governance tokens in the collateral of the vault, frozen
+	pub collateral: Balance,
+	/// Governance tokens the positions could be redeemed for
+	pub power: Balance,
+	pub taken_at: BlockNumber,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::without_storage_info]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// Governance token held in balances, which the votes lock
+		type Currency: ReservableCurrency<Self::AccountId, Balance = Balance>
+			+ LockableCurrency<Self::AccountId, Moment = Self::BlockNumber>;
+		/// Positions of the accounts in the governance token
+		type Positions: GovernancePositions<Self::AccountId>;
+		/// Asset of the governance token in the vaults
+		#[pallet::constant]
+		type GovernanceAssetId: Get<AssetId>;
+		/// Maximum number of liquidity tokens in a snapshot
+		#[pallet::constant]
+		type MaxLiquidityPositions: Get<u32>;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Positions of an account are counted in its voting power (who, power)
+		SnapshotTaken(T::AccountId, Balance),
+		/// Positions of an account are released (who)
+		SnapshotReleased(T::AccountId),
+		/// Collateral of a snapshot is liquidated and no longer counted (who, power)
+		CollateralLiquidated(T::AccountId, Balance),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Liquidity token is not the one of a governance token pool
+		NotGovernancePool,
+		/// Snapshot would hold more than `MaxLiquidityPositions` liquidity tokens
+		TooManyPositions,
+		/// Account has no snapshot
+		NoSnapshot,
+		/// Votes of the account are not covered by its free balance
+		PowerInUse,
+	}
+
+	#[pallet::storage]
+	#[pallet::getter(fn snapshot_of)]
+	/// Positions counted in the voting power of each account
+	pub type Snapshots<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, VotingSnapshot<T::BlockNumber>>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn frozen_liquidity)]
+	/// Liquidity tokens of each account frozen by its snapshot
+	pub type FrozenLiquidity<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, AssetId, Blake2_128Concat, T::AccountId, Balance>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn vote_lock)]
+	/// Balance locked by the votes of each account
+	pub type VoteLocks<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, Balance, ValueQuery>;
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Counts the liquidity tokens of `lp_tokens` and the positions of the previous snapshot
+		/// of the sender, along with the governance token collateral of its vault, in its voting
+		/// power, and freezes them.
+		///
+		/// # <weight>
+		/// - O(P) lookups and inserts, P = MaxLiquidityPositions
+		/// # </weight>
+		#[pallet::weight({
+			let positions = T::MaxLiquidityPositions::get() as Weight;
+			T::DbWeight::get().reads_writes(3 + 4 * positions, 1 + 2 * positions)
+		})]
+		pub fn snapshot(origin: OriginFor<T>, lp_tokens: Vec<LpTokenId>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let previous = Self::snapshot_of(&who);
+			let mut tokens: Vec<LpTokenId> = previous
+				.iter()
+				.flat_map(|snapshot| snapshot.liquidity.iter().map(|(lp_token, _)| *lp_token))
+				.collect();
+			for lp_token in lp_tokens {
+				if !tokens.contains(&lp_token) {
+					tokens.push(lp_token);
+				}
+			}
+			ensure!(
+				tokens.len() <= T::MaxLiquidityPositions::get() as usize,
+				Error::<T>::TooManyPositions
+			);
+
+			let collateral = T::Positions::collateral(&who);
+			let mut power = collateral;
+			let mut liquidity = Vec::with_capacity(tokens.len());
+			for lp_token in tokens {
+				let (amount, value) =
+					T::Positions::liquidity(&who, lp_token).ok_or(Error::<T>::NotGovernancePool)?;
+				if !amount.is_zero() {
+					liquidity.push((lp_token, amount));
+					power = power.saturating_add(value);
+				}
+			}
+
+			if let Some(previous) = previous {
+				Self::unfreeze(&who, &previous);
+			}
+			for (lp_token, amount) in &liquidity {
+				FrozenLiquidity::<T>::insert(AssetId::from(*lp_token), &who, amount);
+			}
+			let taken_at = frame_system::Pallet::<T>::block_number();
+			Snapshots::<T>::insert(&who, VotingSnapshot { liquidity, collateral, power, taken_at });
+			Self::deposit_event(Event::SnapshotTaken(who, power));
+			Ok(())
+		}
+
+		/// Releases the positions of the snapshot of the sender, once its votes are covered by its
+		/// free balance.
+		///
+		/// # <weight>
+		/// - O(P) removals, P = MaxLiquidityPositions
+		/// # </weight>
+		#[pallet::weight({
+			let positions = T::MaxLiquidityPositions::get() as Weight;
+			T::DbWeight::get().reads_writes(3, 1 + positions)
+		})]
+		pub fn release(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let snapshot = Self::snapshot_of(&who).ok_or(Error::<T>::NoSnapshot)?;
+			ensure!(
+				Self::vote_lock(&who) <= T::Currency::free_balance(&who),
+				Error::<T>::PowerInUse
+			);
+
+			Self::unfreeze(&who, &snapshot);
+			Snapshots::<T>::remove(&who);
+			Self::deposit_event(Event::SnapshotReleased(who));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Governance tokens of `who` in the positions of its snapshot
+	pub fn power(who: &T::AccountId) -> Balance {
+		Self::snapshot_of(who).map(|snapshot| snapshot.power).unwrap_or_default()
+	}
+
+	fn unfreeze(who: &T::AccountId, snapshot: &VotingSnapshot<T::BlockNumber>) {
+		for (lp_token, _) in &snapshot.liquidity {
+			FrozenLiquidity::<T>::remove(AssetId::from(*lp_token), who);
+		}
+	}
+}
+
+impl<T: Config> FrozenBalance<AssetId, T::AccountId, Balance> for Pallet<T> {
+	fn frozen_balance(asset: AssetId, who: &T::AccountId) -> Option<Balance> {
+		Self::frozen_liquidity(asset, who)
+	}
+
+	fn died(asset: AssetId, who: &T::AccountId) {
+		FrozenLiquidity::<T>::remove(asset, who);
+	}
+}
+
+impl<T: Config> FrozenCollateral<T::AccountId> for Pallet<T> {
+	fn frozen_collateral(who: &T::AccountId, asset: AssetId) -> Balance {
+		if asset != T::GovernanceAssetId::get() {
+			return 0
+		}
+		Self::snapshot_of(who).map(|snapshot| snapshot.collateral).unwrap_or_default()
+	}
+
+	// The vault is gone, so its collateral stops counting in the voting power right away
+	fn on_liquidation(who: &T::AccountId, asset: AssetId) {
+		if asset != T::GovernanceAssetId::get() {
+			return
+		}
+		let power = Snapshots::<T>::mutate(who, |snapshot| match snapshot {
+			Some(snapshot) if !snapshot.collateral.is_zero() => {
+				snapshot.power = snapshot.power.saturating_sub(snapshot.collateral);
+				snapshot.collateral = 0;
+				Some(snapshot.power)
+			},
+			_ => None,
+		});
+		if let Some(power) = power {
+			Self::deposit_event(Event::CollateralLiquidated(who.clone(), power));
+		}
+	}
+}
+
+// The governance token with the voting power of the snapshots added to the free balances, for
+// the voting pallet. Everything else is passed through to `T::Currency`, and the lock of the
+// voting pallet is recorded to tell when the positions stop backing its votes.
+impl<T: Config> Currency<T::AccountId> for Pallet<T> {
+	type Balance = Balance;
+	type PositiveImbalance = <T::Currency as Currency<T::AccountId>>::PositiveImbalance;
+	type NegativeImbalance = <T::Currency as Currency<T::AccountId>>::NegativeImbalance;
+
+	fn total_balance(who: &T::AccountId) -> Balance {
+		T::Currency::total_balance(who)
+	}
+
+	fn can_slash(who: &T::AccountId, value: Balance) -> bool {
+		T::Currency::can_slash(who, value)
+	}
+
+	fn total_issuance() -> Balance {
+		T::Currency::total_issuance()
+	}
+
+	fn minimum_balance() -> Balance {
+		T::Currency::minimum_balance()
+	}
+
+	fn burn(amount: Balance) -> Self::PositiveImbalance {
+		T::Currency::burn(amount)
+	}
+
+	fn issue(amount: Balance) -> Self::NegativeImbalance {
+		T::Currency::issue(amount)
+	}
+
+	fn free_balance(who: &T::AccountId) -> Balance {
+		T::Currency::free_balance(who).saturating_add(Self::power(who))
+	}
+
+	fn ensure_can_withdraw(
+		who: &T::AccountId,
+		amount: Balance,
+		reasons: WithdrawReasons,
+		new_balance: Balance,
+	) -> DispatchResult {
+		T::Currency::ensure_can_withdraw(who, amount, reasons, new_balance)
+	}
+
+	fn transfer(
+		source: &T::AccountId,
+		dest: &T::AccountId,
+		value: Balance,
+		existence_requirement: ExistenceRequirement,
+	) -> DispatchResult {
+		T::Currency::transfer(source, dest, value, existence_requirement)
+	}
+
+	fn slash(who: &T::AccountId, value: Balance) -> (Self::NegativeImbalance, Balance) {
+		T::Currency::slash(who, value)
+	}
+
+	fn deposit_into_existing(
+		who: &T::AccountId,
+		value: Balance,
+	) -> Result<Self::PositiveImbalance, DispatchError> {
+		T::Currency::deposit_into_existing(who, value)
+	}
+
+	fn deposit_creating(who: &T::AccountId, value: Balance) -> Self::PositiveImbalance {
+		T::Currency::deposit_creating(who, value)
+	}
+
+	fn withdraw(
+		who: &T::AccountId,
+		value: Balance,
+		reasons: WithdrawReasons,
+		liveness: ExistenceRequirement,
+	) -> Result<Self::NegativeImbalance, DispatchError> {
+		T::Currency::withdraw(who, value, reasons, liveness)
+	}
+
+	fn make_free_balance_be(
+		who: &T::AccountId,
+		balance: Balance,
+	) -> SignedImbalance<Balance, Self::PositiveImbalance> {
+		T::Currency::make_free_balance_be(who, balance)
+	}
+}
+
+impl<T: Config> ReservableCurrency<T::AccountId> for Pallet<T> {
+	fn can_reserve(who: &T::AccountId, value: Balance) -> bool {
+		T::Currency::can_reserve(who, value)
+	}
+
+	fn slash_reserved(who: &T::AccountId, value: Balance) -> (Self::NegativeImbalance, Balance) {
+		T::Currency::slash_reserved(who, value)
+	}
+
+	fn reserved_balance(who: &T::AccountId) -> Balance {
+		T::Currency::reserved_balance(who)
+	}
+
+	fn reserve(who: &T::AccountId, value: Balance) -> DispatchResult {
+		T::Currency::reserve(who, value)
+	}
+
+	fn unreserve(who: &T::AccountId, value: Balance) -> Balance {
+		T::Currency::unreserve(who, value)
+	}
+
+	fn repatriate_reserved(
+		slashed: &T::AccountId,
+		beneficiary: &T::AccountId,
+		value: Balance,
+		status: BalanceStatus,
+	) -> Result<Balance, DispatchError> {
+		T::Currency::repatriate_reserved(slashed, beneficiary, value, status)
+	}
+}
+
+impl<T: Config> LockableCurrency<T::AccountId> for Pallet<T> {
+	type Moment = T::BlockNumber;
+	type MaxLocks = <T::Currency as LockableCurrency<T::AccountId>>::MaxLocks;
+
+	fn set_lock(id: LockIdentifier, who: &T::AccountId, amount: Balance, reasons: WithdrawReasons) {
+		T::Currency::set_lock(id, who, amount, reasons);
+		VoteLocks::<T>::insert(who, amount);
+	}
+
+	fn extend_lock(
+		id: LockIdentifier,
+		who: &T::AccountId,
+		amount: Balance,
+		reasons: WithdrawReasons,
+	) {
+		T::Currency::extend_lock(id, who, amount, reasons);
+		VoteLocks::<T>::mutate(who, |locked| *locked = (*locked).max(amount));
+	}
+
+	fn remove_lock(id: LockIdentifier, who: &T::AccountId) {
+		T::Currency::remove_lock(id, who);
+		VoteLocks::<T>::remove(who);
+	}
+}
@@ -0,0 +1,56 @@
+//! Types shared with the chainbridge pallet, returned by `ChainBridgeApi`.
+
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_core::U256;
+use sp_runtime::RuntimeDebug;
+use sp_std::vec::Vec;
+
+/// Identifier of a bridged chain
+pub type BridgeChainId = u8;
+/// Counter of the transfers to or from a bridged chain
+pub type DepositNonce = u64;
+/// Identifier of a bridged resource, (31 bytes unique ID + 1 byte chain ID)
+pub type ResourceId = [u8; 32];
+
+/// Outbound fungible transfer as recorded by the bridge
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct TransferReceipt<AccountId, BlockNumber> {
+	/// Local account which initiated the transfer
+	pub sender: AccountId,
+	pub resource_id: ResourceId,
+	/// Recipient on the destination chain
+	pub recipient: Vec<u8>,
+	pub amount: U256,
+	/// Block the transfer was initiated in
+	pub block_number: BlockNumber,
+}
+
+/// Whitelisted chain with its nonces
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct BridgeChain {
+	pub chain_id: BridgeChainId,
+	/// Nonce of the last outbound transfer to the chain
+	pub deposit_nonce: DepositNonce,
+	/// Next nonce expected from the chain
+	pub expected_nonce: DepositNonce,
+}
+
+/// Configuration of the bridge, so that relayers can bootstrap from chain state
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct BridgeCatalogue<AccountId> {
+	/// Registered resources and the method they are executed with
+	pub resources: Vec<(ResourceId, Vec<u8>)>,
+	/// Whitelisted chains
+	pub chains: Vec<BridgeChain>,
+	/// Relayer set with the vote weight of every relayer
+	pub relayers: Vec<(AccountId, u32)>,
+	/// Vote weight required for a proposal to execute
+	pub threshold: u32,
+}
@@ -0,0 +1,45 @@
+//! Well-known identifiers shared by the runtimes, pallets and chain specs.
+
+/// Asset ids registered in the asset registry at genesis.
+pub mod asset_ids {
+	use crate::AssetId;
+
+	/// Standard token
+	pub const STND: AssetId = 1;
+	/// Meter stablecoin issued by the vault
+	pub const MTR: AssetId = 2;
+	/// Polkadot
+	pub const DOT: AssetId = 3;
+	/// Kusama
+	pub const KSM: AssetId = 4;
+	/// Rococo
+	pub const ROC: AssetId = 5;
+	/// First id handed out by the asset registry after genesis on chains registering ROC
+	pub const NEXT_ASSET_ID: AssetId = 6;
+}
+
+/// Ids of module accounts shared by the runtimes, wrapped in a `PalletId` by the runtimes.
+pub mod pallet_ids {
+	/// Treasury collecting protocol dust and fees
+	pub const TREASURY: [u8; 8] = *b"ty/trsry";
+}
+
+/// Scale of the prices reported to the oracle.
+pub mod price {
+	/// Decimals of the integer prices in oracle reports, a report of `10^PRICE_DECIMALS` is
+	/// 1 USD. The oracle scales them to `FixedU128` when they are submitted.
+	pub const PRICE_DECIMALS: u32 = 8;
+}
+
+/// Address format and native token of the Standard networks, announced in the chain specs.
+pub mod network {
+	/// SS58 prefix of Standard addresses.
+	///
+	/// Accounts keep their keys under the new prefix, only the string form of their addresses
+	/// changes.
+	pub const SS58_PREFIX: u16 = 1900;
+	/// Generic Substrate prefix used before `SS58_PREFIX`
+	pub const LEGACY_SS58_PREFIX: u16 = 42;
+	/// Decimals of the native token
+	pub const TOKEN_DECIMALS: u8 = 18;
+}
@@ -1,10 +1,23 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use codec::{Codec, Decode, Encode};
+use scale_info::TypeInfo;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
 use sp_runtime::{
 	generic,
 	traits::{BlakeTwo256, IdentifyAccount, Verify},
-	MultiSignature,
+	MultiSignature, RuntimeDebug,
 };
+use sp_std::vec::Vec;
+
+pub mod bridge;
+pub mod constants;
+pub mod merkle;
+pub mod overview;
+pub mod proof;
+pub mod snapshot;
+pub mod traits;
 
 /// Some way of identifying an account on the chain. We intentionally make it equivalent
 /// to the public key of our transaction signing scheme.
@@ -25,7 +38,17 @@ pub type Header = generic::Header<BlockNumber, BlakeTwo256>;
 pub type EraIndex = u64;
 /// Index for oracle to provide information
 pub type SocketIndex = u32;
-/// Primary asset ID to use
+/// Index of an oracle reporting round
+pub type RoundIndex = u32;
+/// Index of an oracle heartbeat session
+pub type HeartbeatSession = u32;
+/// Identifier of a vault token
+pub type VaultId = u64;
+/// Index of a market volume era
+pub type VolumeEra = u32;
+/// Identifier of an oracle price request
+pub type RequestId = u64;
+/// Asset id of the native currency held in balances
 pub const CORE_ASSET_ID: AssetId = 0;
 /// Alias to 512-bit hash when used in the context of a transaction signature on the chain.
 pub type Signature = MultiSignature;
@@ -40,3 +63,124 @@ pub type AccountIndex = u32;
 pub type Moment = u64;
 /// Block type.
 pub type Block = sp_runtime::generic::Block<Header, sp_runtime::OpaqueExtrinsic>;
+
+/// Asset id of a market liquidity token.
+///
+/// Encoded as the `AssetId` it wraps, so storage keyed by liquidity tokens keeps its layout, but
+/// a distinct type, so a liquidity token can not be passed where a pair asset or a collateral is
+/// expected. The market converts it back into an `AssetId` to move the tokens.
+#[derive(
+	Clone, Copy, Default, Encode, Decode, Eq, PartialEq, Ord, PartialOrd, RuntimeDebug, TypeInfo,
+)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(transparent))]
+pub struct LpTokenId(pub AssetId);
+
+impl From<LpTokenId> for AssetId {
+	fn from(lp_token: LpTokenId) -> Self {
+		lp_token.0
+	}
+}
+
+sp_api::decl_runtime_apis! {
+	/// Exposes well-known constants of the runtime so clients don't hard-code them.
+	pub trait ConstantsApi {
+		/// Asset id of the native currency held in balances
+		fn native_asset_id() -> AssetId;
+		/// Asset id of the stablecoin issued by the vault
+		fn mtr_asset_id() -> AssetId;
+		/// Asset ids registered at genesis keyed by their symbol
+		fn well_known_assets() -> Vec<(Vec<u8>, AssetId)>;
+		/// SS58 prefix of account addresses. Runtimes without this method use
+		/// `constants::network::LEGACY_SS58_PREFIX`.
+		fn ss58_prefix() -> u16;
+	}
+
+	/// Audits the bookkeeping of the market, labels its liquidity tokens and reports pool stats.
+	pub trait MarketApi {
+		/// Assets whose recorded reserves exceed the market account balance as
+		/// `(asset, recorded, held)`. Empty when the reserves are fully backed.
+		fn reserve_discrepancies() -> Vec<(AssetId, Balance, Balance)>;
+		/// Pair and symbol of the liquidity token `lp_token`, `None` if it is not one
+		fn lp_token_info(lp_token: LpTokenId) -> Option<overview::LpTokenInfo>;
+		/// Pair and symbol of every liquidity token, ordered by liquidity token
+		fn lp_tokens() -> Vec<overview::LpTokenInfo>;
+		/// Reserves, liquidity token supply and swap fees of the pool of `lp_token`, valued at the
+		/// oracle prices. `None` if it is not a liquidity token.
+		fn pool_stats(lp_token: LpTokenId) -> Option<overview::PoolStats>;
+		/// Stats of every pool, ordered by liquidity token
+		fn all_pool_stats() -> Vec<overview::PoolStats>;
+	}
+
+	/// Swap volume of the market, for settling incentive programs on chain.
+	pub trait MarketVolumeApi<AccountId> where AccountId: Codec {
+		/// Index of the current volume era
+		fn current_volume_era() -> VolumeEra;
+		/// Volume `account` swapped in each pair during `era` as `(lp_token, volume)`
+		fn account_volume(account: AccountId, era: VolumeEra) -> Vec<(LpTokenId, Balance)>;
+		/// Volume swapped in the pair of `lp_token` during `era`
+		fn pair_volume(lp_token: LpTokenId, era: VolumeEra) -> Balance;
+	}
+
+	/// Provider set and misbehaviour of the oracle, for monitoring.
+	pub trait OracleApi<AccountId> where AccountId: Codec {
+		/// Registered providers
+		fn providers() -> Vec<AccountId>;
+		/// Providers assigned to the reporting sockets as `(socket, provider)`
+		fn slot_assignments() -> Vec<(SocketIndex, AccountId)>;
+		/// Providers slashed in `era`
+		fn slashes(era: EraIndex) -> Vec<AccountId>;
+	}
+
+	/// Cost basis of the liquidity provided by an account, for impermanent loss estimates
+	/// without off-chain indexing.
+	pub trait LiquidityReturnsApi<AccountId> where AccountId: Codec {
+		/// Liquidity minted by `account` in each pool with the deposits it was minted for and
+		/// the reserves it is redeemable for, valued at the oracle prices
+		fn liquidity_returns(account: AccountId) -> Vec<overview::LiquidityReturn>;
+	}
+
+	/// Aggregated view of an account so that clients can render a portfolio with one call.
+	pub trait AccountApi<AccountId> where AccountId: Codec {
+		/// Balances, liquidity positions, vaults and pending rewards of the account
+		fn account_overview(account: AccountId) -> overview::AccountOverview;
+	}
+
+	/// Pools, vaults and prices in one call, for analytics, audits and forked test environments.
+	pub trait DefiSnapshotApi<AccountId> where AccountId: Codec {
+		/// State of the market, the vaults and the oracle
+		fn defi_snapshot() -> snapshot::DefiSnapshot<AccountId>;
+	}
+
+	/// Vault aggregates of past eras, so that analytics don't have to replay the vault events.
+	pub trait VaultHistoryApi {
+		/// Aggregates recorded at the end of `era`, `None` if it is not in the history
+		fn vault_era_stats(era: EraIndex) -> Option<snapshot::VaultEraStats>;
+		/// Every era in the history, ordered by era
+		fn vault_history() -> Vec<snapshot::VaultEraStats>;
+	}
+
+	/// Configuration and outbound transfers of the bridge, for relayers and off-chain
+	/// reconciliation.
+	pub trait ChainBridgeApi<AccountId, BlockNumber> where AccountId: Codec, BlockNumber: Codec {
+		/// Receipt of the fungible transfer `nonce` to `dest_id`
+		fn transfer_receipt(
+			dest_id: bridge::BridgeChainId,
+			nonce: bridge::DepositNonce,
+		) -> Option<bridge::TransferReceipt<AccountId, BlockNumber>>;
+
+		/// Resources, whitelisted chains, relayer set and threshold of the bridge
+		fn catalogue() -> bridge::BridgeCatalogue<AccountId>;
+	}
+
+	/// Vault and pool state with the storage keys it is read from, so that light clients can
+	/// request a proof of it in the same round trip.
+	pub trait StateProofApi {
+		/// Collateral, debt and health factor of a vault. The keys cover the vault, the
+		/// positions of its collateral and the oracle reports of its collateral and MTR.
+		fn proven_vault(vault_id: VaultId) -> proof::Proven<Option<overview::VaultPosition>>;
+
+		/// Reserves and liquidity token supply of the pool of `lp_token`
+		fn proven_pool(lp_token: LpTokenId) -> proof::Proven<Option<snapshot::PoolSnapshot>>;
+	}
+}
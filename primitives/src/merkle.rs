@@ -0,0 +1,26 @@
+//! Merkle trees of claims, e.g. reward distributions computed off chain.
+//!
+//! Leaves and nodes are keccak-256 hashes and every node is the hash of its two children in
+//! ascending order, so a proof is just the list of sibling hashes from the leaf up to the root.
+
+use codec::Encode;
+use sp_core::hashing::keccak_256;
+
+/// Hash of a leaf or node of a Merkle tree
+pub type MerkleHash = [u8; 32];
+
+/// Leaf of the SCALE encoded `data`
+pub fn leaf<T: Encode>(data: &T) -> MerkleHash {
+	keccak_256(&data.encode())
+}
+
+/// Hash of the node with the children `a` and `b`
+pub fn hash_pair(a: MerkleHash, b: MerkleHash) -> MerkleHash {
+	let (left, right) = if a <= b { (a, b) } else { (b, a) };
+	keccak_256(&(left, right).encode())
+}
+
+/// Whether `proof` leads from `leaf` to `root`
+pub fn verify_proof(root: MerkleHash, leaf: MerkleHash, proof: &[MerkleHash]) -> bool {
+	proof.iter().fold(leaf, |node, sibling| hash_pair(node, *sibling)) == root
+}
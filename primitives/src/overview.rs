@@ -0,0 +1,147 @@
+//! Portfolio of an account across the Standard pallets, returned by `AccountApi`, the labels
+//! and stats of the market pools, returned by `MarketApi`, and the liquidity returns of an
+//! account, returned by `LiquidityReturnsApi`.
+
+use crate::{AssetId, Balance, LpTokenId, VaultId};
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use sp_runtime::{traits::Zero, FixedPointNumber, FixedU128, Perbill, RuntimeDebug};
+use sp_std::vec::Vec;
+
+/// Liquidity provided to a market pool
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct LiquidityPosition {
+	/// Liquidity token of the pool
+	pub lp_token: LpTokenId,
+	/// Liquidity tokens held
+	pub amount: Balance,
+	/// Share of each pool reserve redeemable for the liquidity tokens
+	pub underlying: [(AssetId, Balance); 2],
+}
+
+/// Liquidity minted by an account against what it deposited for it, for estimating the
+/// impermanent loss of the position
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct LiquidityReturn {
+	pub lp_token: LpTokenId,
+	/// Liquidity tokens minted by the account and not burned yet
+	pub liquidity: Balance,
+	/// Tokens deposited for `liquidity`, ordered by id
+	pub deposited: [(AssetId, Balance); 2],
+	/// Share of each pool reserve redeemable for `liquidity`, swap fees included
+	pub current: [(AssetId, Balance); 2],
+	/// Value of `deposited` at the oracle prices, i.e. of holding the deposits instead.
+	/// `None` without a price for both assets.
+	pub deposit_value: Option<Balance>,
+	/// Value of `current` at the oracle prices
+	pub current_value: Option<Balance>,
+}
+
+impl LiquidityReturn {
+	/// Fills in the values at the prices of `price`, as the sum of price times amount.
+	pub fn priced(self, price: impl Fn(AssetId) -> Option<FixedU128>) -> Self {
+		let prices = [price(self.current[0].0), price(self.current[1].0)];
+		let value = |amounts: [(AssetId, Balance); 2]| -> Option<Balance> {
+			Some(
+				prices[0]?
+					.saturating_mul_int(amounts[0].1)
+					.saturating_add(prices[1]?.saturating_mul_int(amounts[1].1)),
+			)
+		};
+		Self { deposit_value: value(self.deposited), current_value: value(self.current), ..self }
+	}
+
+	/// Share of the deposit value lost by providing liquidity instead of holding the deposits,
+	/// zero if the position gained. `None` without values.
+	pub fn impermanent_loss(&self) -> Option<Perbill> {
+		let (deposit_value, current_value) = (self.deposit_value?, self.current_value?);
+		Some(match deposit_value.is_zero() {
+			true => Perbill::zero(),
+			false =>
+				Perbill::from_rational(deposit_value.saturating_sub(current_value), deposit_value),
+		})
+	}
+}
+
+/// Pair of a market liquidity token
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct LpTokenInfo {
+	pub lp_token: LpTokenId,
+	/// Assets of the pair, ordered by id
+	pub pair: (AssetId, AssetId),
+	/// Symbol the token is registered under, `LP-<symbol0>-<symbol1>`
+	pub symbol: Vec<u8>,
+}
+
+/// Reserves and swap fees of a market pool, for showing its fee yield
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct PoolStats {
+	pub lp_token: LpTokenId,
+	/// Reserve of each asset of the pair, ordered by id
+	pub reserves: [(AssetId, Balance); 2],
+	/// Liquidity tokens outstanding
+	pub lp_supply: Balance,
+	/// Swap fees paid into the pool over the last 24 hours, in each asset of the pair
+	pub fees_24h: [Balance; 2],
+	/// Swap fees paid into the pool over the last 7 days, in each asset of the pair
+	pub fees_7d: [Balance; 2],
+	/// Value of the reserves at the oracle prices.
+	/// `None` without a price for both assets.
+	pub reserve_value: Option<Balance>,
+	/// Value of `fees_24h` at the oracle prices
+	pub fee_value_24h: Option<Balance>,
+	/// Value of `fees_7d` at the oracle prices
+	pub fee_value_7d: Option<Balance>,
+}
+
+impl PoolStats {
+	/// Fills in the values at the prices of `price`, as the sum of price times amount.
+	pub fn priced(self, price: impl Fn(AssetId) -> Option<FixedU128>) -> Self {
+		let prices = [price(self.reserves[0].0), price(self.reserves[1].0)];
+		let value = |amounts: [Balance; 2]| -> Option<Balance> {
+			Some(
+				prices[0]?
+					.saturating_mul_int(amounts[0])
+					.saturating_add(prices[1]?.saturating_mul_int(amounts[1])),
+			)
+		};
+		Self {
+			reserve_value: value([self.reserves[0].1, self.reserves[1].1]),
+			fee_value_24h: value(self.fees_24h),
+			fee_value_7d: value(self.fees_7d),
+			..self
+		}
+	}
+}
+
+/// Open vault
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct VaultPosition {
+	pub vault_id: VaultId,
+	/// Collateral basket of the vault
+	pub collateral: Vec<(AssetId, Balance)>,
+	/// MTR issued against the collateral
+	pub debt: Balance,
+	/// Price weighted borrowing capacity of the collateral over the debt, the vault can be
+	/// liquidated under 1.
+	/// `None` without debt or without oracle prices.
+	pub health_factor: Option<FixedU128>,
+}
+
+/// Reward streamed to an account and not withdrawn yet, e.g. of liquidity mining
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct PendingReward {
+	/// Stream paying out the reward, withdrawn with `withdraw_from_stream`
+	pub stream_id: u64,
+	pub asset: AssetId,
+	pub amount: Balance,
+}
+
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, Default)]
+pub struct AccountOverview {
+	/// Balances of the native currency and the well-known assets
+	pub balances: Vec<(AssetId, Balance)>,
+	pub liquidity: Vec<LiquidityPosition>,
+	pub vaults: Vec<VaultPosition>,
+	pub rewards: Vec<PendingReward>,
+}
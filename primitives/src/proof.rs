@@ -0,0 +1,21 @@
+//! Values returned together with the storage keys they are read from, by `StateProofApi`.
+
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_runtime::RuntimeDebug;
+use sp_std::vec::Vec;
+
+/// Value and the storage keys it is computed from.
+///
+/// Clients fetch a proof of `keys` with `state_getReadProof` at the same block and check it
+/// against the state root of a finalized header.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct Proven<T> {
+	pub value: T,
+	/// Storage keys read to compute the value, without duplicates
+	pub keys: Vec<Vec<u8>>,
+}
@@ -0,0 +1,65 @@
+//! State of the DeFi pallets at a block, returned by `DefiSnapshotApi`.
+
+use crate::{AssetId, Balance, EraIndex, LpTokenId, VaultId};
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_runtime::{FixedU128, RuntimeDebug};
+use sp_std::vec::Vec;
+
+/// Market pool
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct PoolSnapshot {
+	/// Liquidity token of the pool
+	pub lp_token: LpTokenId,
+	/// Reserve of each asset of the pair
+	pub reserves: [(AssetId, Balance); 2],
+	/// Liquidity tokens issued
+	pub total_supply: Balance,
+}
+
+/// Vault with its owner
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct VaultSnapshot<AccountId> {
+	pub vault_id: VaultId,
+	pub owner: AccountId,
+	/// Collateral basket of the vault
+	pub collateral: Vec<(AssetId, Balance)>,
+	/// MTR issued against the collateral
+	pub debt: Balance,
+}
+
+/// Vault aggregates recorded at the end of an era
+#[derive(Clone, Default, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct VaultEraStats {
+	pub era: EraIndex,
+	/// Collateral held by all vaults, ordered by asset
+	pub collateral: Vec<(AssetId, Balance)>,
+	/// MTR issued against all vaults
+	pub debt: Balance,
+	/// Number of open vaults
+	pub vaults: u32,
+	/// Debt of the vaults liquidated during the era
+	pub liquidated: Balance,
+}
+
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct DefiSnapshot<AccountId> {
+	/// Pools ordered by liquidity token
+	pub pools: Vec<PoolSnapshot>,
+	/// Vaults ordered by id
+	pub vaults: Vec<VaultSnapshot<AccountId>>,
+	/// Oracle prices of the well-known assets and the collaterals, in USD
+	pub prices: Vec<(AssetId, FixedU128)>,
+	/// Total issuance of each well-known asset and collateral
+	pub issuance: Vec<(AssetId, Balance)>,
+}
@@ -0,0 +1,160 @@
+//! Interfaces between the Standard pallets.
+
+use crate::{Amount, AssetId, Balance, EraIndex, LpTokenId, RequestId, RoundIndex};
+use frame_support::{traits::tokens::fungibles, weights::Weight};
+use sp_runtime::{DispatchResult, FixedU128};
+
+/// Time weighted average prices of the market pairs.
+pub trait Twap<BlockNumber> {
+	/// Average amount of `quote` paid for one `base` over at least the last `window` blocks,
+	/// `None` without a pool or enough history.
+	fn twap(base: AssetId, quote: AssetId, window: BlockNumber) -> Option<FixedU128>;
+}
+
+impl<BlockNumber> Twap<BlockNumber> for () {
+	fn twap(_base: AssetId, _quote: AssetId, _window: BlockNumber) -> Option<FixedU128> {
+		None
+	}
+}
+
+/// Receives the outcome of the oracle price requests.
+pub trait OnPriceResponse<AccountId> {
+	/// Called with the aggregated price of `request_id` once enough providers answered, or with
+	/// `None` if the request timed out. Runs in the block hook or the answering call, so it has to
+	/// be cheap.
+	fn on_response(
+		request_id: RequestId,
+		requester: &AccountId,
+		symbol: &[u8],
+		price: Option<Balance>,
+	);
+}
+
+impl<AccountId> OnPriceResponse<AccountId> for () {
+	fn on_response(
+		_request_id: RequestId,
+		_requester: &AccountId,
+		_symbol: &[u8],
+		_price: Option<Balance>,
+	) {
+	}
+}
+
+/// Receives the final median prices of the closed oracle rounds.
+pub trait OnRoundClosed {
+	/// Called with the final median of each asset reported in `round` once it closes, in the
+	/// block hook of the oracle. Returns the weight consumed.
+	fn on_round_closed(round: RoundIndex, prices: &[(AssetId, FixedU128)]) -> Weight;
+}
+
+impl OnRoundClosed for () {
+	fn on_round_closed(_round: RoundIndex, _prices: &[(AssetId, FixedU128)]) -> Weight {
+		0
+	}
+}
+
+/// Global limit on newly minted MTR, shared by all modules issuing it.
+pub trait MintGuard {
+	/// Accounts for `amount` of MTR about to be minted, failing if it exceeds the limit.
+	fn on_mint(amount: Balance) -> DispatchResult;
+}
+
+impl MintGuard for () {
+	fn on_mint(_amount: Balance) -> DispatchResult {
+		Ok(())
+	}
+}
+
+/// Funds a pallet keeps for its users in a module account, which governance must not move.
+pub trait AccountedFunds<AccountId> {
+	/// Amount of `asset` the pallet keeps in `account`, zero if it does not use the account.
+	fn accounted(account: &AccountId, asset: AssetId) -> Balance;
+}
+
+impl<AccountId> AccountedFunds<AccountId> for () {
+	fn accounted(_account: &AccountId, _asset: AssetId) -> Balance {
+		0
+	}
+}
+
+/// Assets reserved in the accounts of their owners for pending operations, like
+/// `MultiReservableCurrency` of orml. Reserved assets can not leave the account until released.
+pub trait ReservableAssets<AccountId> {
+	/// Reserves `amount` of `asset` in the account of `who`, failing if it is not free.
+	fn reserve(asset: AssetId, who: &AccountId, amount: Balance) -> DispatchResult;
+	/// Releases up to `amount` of `asset` reserved in the account of `who`.
+	fn unreserve(asset: AssetId, who: &AccountId, amount: Balance);
+	/// Amount of `asset` reserved in the account of `who`.
+	fn reserved_balance(asset: AssetId, who: &AccountId) -> Balance;
+}
+
+/// Collateral the vaults must keep for their owners, e.g. because it backs governance votes.
+pub trait FrozenCollateral<AccountId> {
+	/// Collateral of `asset` the vault of `who` for it can not release.
+	fn frozen_collateral(who: &AccountId, asset: AssetId) -> Balance;
+	/// Called once the collateral of `asset` of the vault of `who` is liquidated, which releases
+	/// it whether it is frozen or not.
+	fn on_liquidation(who: &AccountId, asset: AssetId);
+}
+
+impl<AccountId> FrozenCollateral<AccountId> for () {
+	fn frozen_collateral(_who: &AccountId, _asset: AssetId) -> Balance {
+		0
+	}
+
+	fn on_liquidation(_who: &AccountId, _asset: AssetId) {}
+}
+
+/// Governance tokens an account holds in the market pools and the vaults.
+pub trait GovernancePositions<AccountId> {
+	/// Liquidity tokens of `lp_token` held by `who` and the governance tokens they can be redeemed
+	/// for, `None` if the pool is not one of the governance token.
+	fn liquidity(who: &AccountId, lp_token: LpTokenId) -> Option<(Balance, Balance)>;
+	/// Governance tokens in the collateral of the vaults of `who`.
+	fn collateral(who: &AccountId) -> Balance;
+}
+
+/// Eras of the staking system of the runtime, for pallets recording slashes and paying rewards per
+/// era.
+pub trait EraFinder {
+	/// Era the chain is in, `0` before the first one starts.
+	fn active_era() -> EraIndex;
+}
+
+impl EraFinder for () {
+	fn active_era() -> EraIndex {
+		0
+	}
+}
+
+/// Splits the inflation of an era between the validators, the oracle providers and the treasury.
+pub trait ComputeEraReward {
+	/// Shares of `total` minted for an era as `(validators, oracle providers, treasury)`, adding
+	/// up to `total`.
+	fn compute_era_reward(total: Balance) -> (Balance, Balance, Balance);
+}
+
+/// Signed balance adjustments of the assets, like `MultiCurrencyExtended` of orml.
+///
+/// Implemented for every `fungibles::Mutate` of the assets, so that callers moving a balance in
+/// either direction do not have to branch on the sign.
+pub trait MultiAssetExtended<AccountId> {
+	/// Mints `amount` of `id` to `who` if it is positive, burns it from `who` if it is negative.
+	fn update_balance(id: AssetId, who: &AccountId, amount: Amount) -> DispatchResult;
+}
+
+impl<AccountId, Assets> MultiAssetExtended<AccountId> for Assets
+where
+	Assets: fungibles::Mutate<AccountId, AssetId = AssetId, Balance = Balance>,
+{
+	fn update_balance(id: AssetId, who: &AccountId, amount: Amount) -> DispatchResult {
+		let value = amount.unsigned_abs();
+		if amount < 0 {
+			Assets::burn_from(id, who, value).map(|_| ())
+		} else if amount > 0 {
+			Assets::mint_into(id, who, value)
+		} else {
+			Ok(())
+		}
+	}
+}
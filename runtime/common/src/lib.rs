@@ -0,0 +1,173 @@
+//! Constants and parameter types shared by the Standard and Opportunity runtimes.
+//!
+//! Values depending on the block time are declared by each runtime with [`impl_time_units`], so
+//! that the derived units can not drift between the runtimes.
+//!
+//! With the `testnet` feature eras are shorter, bonds unlock sooner and the oracle tolerates
+//! more missed rounds, so that the canary network can exercise them quickly.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+/// Money matters.
+pub mod currency {
+	pub use primitives::Balance;
+
+	pub const MILLICENTS: Balance = 1_000_000_000;
+	pub const CENTS: Balance = 1_000 * MILLICENTS; // assume this is worth about a cent.
+	pub const DOLLARS: Balance = 100 * CENTS;
+	pub const MICROSTD: Balance = 1_000_000_000_000;
+	pub const MILLISTD: Balance = 1_000_000_000_000_000;
+	pub const STD: Balance = 1_000 * MILLISTD;
+
+	pub const fn deposit(items: u32, bytes: u32) -> Balance {
+		items as Balance * 15 * CENTS + (bytes as Balance) * 6 * CENTS
+	}
+}
+
+/// Time.
+pub mod time {
+	pub use primitives::{BlockNumber, Moment};
+
+	// 1 in 4 blocks (on average, not counting collisions) will be primary BABE blocks.
+	pub const PRIMARY_PROBABILITY: (u64, u64) = (1, 4);
+
+	/// Declares the time units of a runtime producing a block every `$millisecs_per_block`
+	/// milliseconds.
+	#[macro_export]
+	macro_rules! impl_time_units {
+		($millisecs_per_block:expr) => {
+			pub use $crate::time::PRIMARY_PROBABILITY;
+			use $crate::time::{BlockNumber, Moment};
+
+			pub const MILLISECS_PER_BLOCK: Moment = $millisecs_per_block;
+			pub const SECS_PER_BLOCK: Moment = MILLISECS_PER_BLOCK / 1000;
+			pub const SLOT_DURATION: Moment = MILLISECS_PER_BLOCK;
+
+			pub const EPOCH_DURATION_IN_BLOCKS: BlockNumber = 1 * HOURS;
+			pub const EPOCH_DURATION_IN_SLOTS: u64 = {
+				const SLOT_FILL_RATE: f64 = MILLISECS_PER_BLOCK as f64 / SLOT_DURATION as f64;
+				(EPOCH_DURATION_IN_BLOCKS as f64 * SLOT_FILL_RATE) as u64
+			};
+
+			// These time units are defined in number of blocks.
+			pub const MINUTES: BlockNumber = 60 / (SECS_PER_BLOCK as BlockNumber);
+			pub const HOURS: BlockNumber = MINUTES * 60;
+			pub const DAYS: BlockNumber = HOURS * 24;
+		};
+	}
+}
+
+/// Block weight limits.
+pub mod weights {
+	use sp_runtime::Perbill;
+
+	/// We assume that ~10% of the block weight is consumed by `on_initalize` handlers.
+	/// This is used to limit the maximal weight of a single extrinsic.
+	pub const AVERAGE_ON_INITIALIZE_RATIO: Perbill = Perbill::from_percent(10);
+	/// We allow `Normal` extrinsics to fill up the block up to 75%, the rest can be used
+	/// by  Operational  extrinsics.
+	pub const NORMAL_DISPATCH_RATIO: Perbill = Perbill::from_percent(75);
+}
+
+/// Transaction fees.
+pub mod fee {
+	use frame_support::parameter_types;
+	use primitives::Balance;
+	use sp_runtime::{FixedPointNumber, FixedU128, Permill, Perquintill};
+
+	parameter_types! {
+		pub const TransactionByteFee: Balance = 1;
+		pub const OperationalFeeMultiplier: u8 = 5;
+		pub const TargetBlockFullness: Perquintill = Perquintill::from_percent(25);
+		/// Fee multiplier adjustment, `FixedU128` is the `Multiplier` of transaction payment
+		pub AdjustmentVariable: FixedU128 = FixedU128::saturating_from_rational(1, 100_000);
+		pub MinimumMultiplier: FixedU128 = FixedU128::saturating_from_rational(1, 1_000_000_000u128);
+		/// Markup on fees paid in other assets than the native currency
+		pub const AssetFeeSurcharge: Permill = Permill::from_percent(10);
+	}
+}
+
+/// Staking eras and bonding.
+pub mod staking {
+	use frame_support::parameter_types;
+	use sp_staking::{EraIndex, SessionIndex};
+
+	#[cfg(not(feature = "testnet"))]
+	const SESSIONS_PER_ERA: SessionIndex = 24;
+	#[cfg(feature = "testnet")]
+	const SESSIONS_PER_ERA: SessionIndex = 6;
+	#[cfg(not(feature = "testnet"))]
+	const BONDING_DURATION: EraIndex = 12;
+	#[cfg(feature = "testnet")]
+	const BONDING_DURATION: EraIndex = 2;
+	// 1/4 the bonding duration.
+	#[cfg(not(feature = "testnet"))]
+	const SLASH_DEFER_DURATION: EraIndex = 3;
+	#[cfg(feature = "testnet")]
+	const SLASH_DEFER_DURATION: EraIndex = 1;
+
+	parameter_types! {
+		pub const SessionsPerEra: SessionIndex = SESSIONS_PER_ERA;
+		pub const BondingDuration: EraIndex = BONDING_DURATION;
+		pub const SlashDeferDuration: EraIndex = SLASH_DEFER_DURATION;
+	}
+}
+
+/// Parameters of the Standard pallets which do not depend on the block time.
+pub mod defi {
+	use crate::currency::DOLLARS;
+	use frame_support::{parameter_types, PalletId};
+	use primitives::{constants::asset_ids, AssetId, Balance, EraIndex, RoundIndex};
+	use sp_runtime::{traits::One, FixedU128, Percent, Permill};
+
+	#[cfg(not(feature = "testnet"))]
+	const ORACLE_ROUNDS_PER_ERA: RoundIndex = 24 * 60;
+	#[cfg(feature = "testnet")]
+	const ORACLE_ROUNDS_PER_ERA: RoundIndex = 60;
+	#[cfg(not(feature = "testnet"))]
+	const ORACLE_MAX_MISSED_ROUNDS: Percent = Percent::from_percent(20);
+	#[cfg(feature = "testnet")]
+	const ORACLE_MAX_MISSED_ROUNDS: Percent = Percent::from_percent(50);
+
+	parameter_types! {
+		pub const SysPalletId: PalletId = PalletId(*b"stnd/mkt");
+		pub const VltPalletId: PalletId = PalletId(*b"stnd/vlt");
+		pub const PsmPalletId: PalletId = PalletId(*b"stnd/psm");
+		pub const StreamPalletId: PalletId = PalletId(*b"stnd/stm");
+		pub const DcaPalletId: PalletId = PalletId(*b"stnd/dca");
+		pub const HtlcPalletId: PalletId = PalletId(*b"stnd/htl");
+		pub const MerkleDistributorPalletId: PalletId = PalletId(*b"stnd/mdr");
+		pub const BridgeTransferPalletId: PalletId = PalletId(*b"stnd/bgt");
+		pub const BridgeOraclePalletId: PalletId = PalletId(*b"stnd/bgo");
+		pub const MtrAssetId: AssetId = asset_ids::MTR;
+		/// Asset the oracle reports the price of the native currency under
+		pub const NativeAssetId: AssetId = asset_ids::STND;
+		/// Oracle price of 1 USD
+		pub MtrPeg: FixedU128 = FixedU128::one();
+		pub const PegTolerance: Permill = Permill::from_percent(1);
+		pub const MaxDcaOrdersPerBlock: u32 = 20;
+		pub const MaxDcaSkippedRounds: u32 = 10;
+		pub const DcaOrderDeposit: Balance = DOLLARS;
+		pub const MaxMerkleProofLength: u32 = 32;
+		pub const MaxBuybackPolicies: u32 = 8;
+		pub const OracleRoundsPerEra: RoundIndex = ORACLE_ROUNDS_PER_ERA;
+		pub const OracleMaxMissedRounds: Percent = ORACLE_MAX_MISSED_ROUNDS;
+		/// Shared by the oracle providers of an era, paid by the treasury
+		pub const OracleEraReward: Balance = 100 * DOLLARS;
+		/// Past eras whose vault aggregates are kept for analytics
+		pub const VaultHistoryDepth: EraIndex = 90;
+	}
+}
+
+/// Priorities added to operational transactions on top of their fee based priority, so that
+/// they are included before regular transactions when blocks are full.
+pub mod priority {
+	use sp_runtime::transaction_validity::TransactionPriority;
+
+	/// Oracle reports, which do not pay fees and which liquidations and swaps depend on
+	pub const ORACLE_REPORT_BOOST: TransactionPriority = TransactionPriority::MAX / 2;
+	/// Liquidations of undercollateralized vaults
+	pub const LIQUIDATION_BOOST: TransactionPriority = TransactionPriority::MAX / 4;
+	/// Votes of the relayers on bridge proposals
+	pub const BRIDGE_PROPOSAL_BOOST: TransactionPriority = TransactionPriority::MAX / 8;
+}
@@ -1,7 +1,40 @@
-use crate::{AccountId, Runtime};
+use crate::{
+	constants::priority, AccountId, AssetFeeSurcharge, Assets, Call, FeeAssets, IdealStake,
+	InflationFalloff, Market, MaxInflation, MinInflation, NativeAssetId, Oracle,
+	OracleInflationReward, OracleInflationShare, Runtime, Staking, TreasuryInflationShare, Vault,
+	VotingPower,
+};
 use frame_election_provider_support::{onchain, ExtendedBalance, SequentialPhragmen};
-use frame_support::pallet_prelude::Get;
-use parity_scale_codec::Decode;
+use frame_support::{
+	pallet_prelude::Get,
+	storage::{storage_prefix, unhashed},
+	traits::{tokens::BalanceConversion, PalletInfoAccess},
+	Blake2_128Concat, StorageHasher,
+};
+use pallet_asset_tx_payment::ChargeAssetTxPayment;
+use pallet_assets::FrozenBalance;
+use pallet_evm::{AddressMapping, HashedAddressMapping};
+use pallet_staking::EraPayout;
+use pallet_staking_reward_fn::compute_inflation;
+use pallet_standard_parameters::{keys, PerbillParameter};
+use parity_scale_codec::{Decode, Encode};
+use primitives::{
+	constants::asset_ids,
+	traits::{ComputeEraReward, EraFinder, GovernancePositions},
+	AssetId, Balance, EraIndex, LpTokenId,
+};
+use scale_info::TypeInfo;
+use sp_core::H160;
+use sp_runtime::{
+	helpers_128bit::multiply_by_rational,
+	traits::{
+		BlakeTwo256, Convert, DispatchInfoOf, PostDispatchInfoOf, Saturating, SignedExtension,
+	},
+	transaction_validity::{
+		TransactionPriority, TransactionValidity, TransactionValidityError, ValidTransaction,
+	},
+	ArithmeticError, DispatchError, DispatchResult, FixedPointNumber, Perbill, RuntimeDebug,
+};
 
 /// Maximum number of iterations for balancing that will be executed in the embedded OCW
 /// miner of election provider multi phase.
@@ -50,3 +83,305 @@ impl onchain::ExecutionConfig for OnChainSeqPhragmen {
 	>;
 	type DataProvider = <Runtime as pallet_election_provider_multi_phase::Config>::DataProvider;
 }
+
+/// Active era of `pallet_staking`, so that oracle slashes and rewards follow the validator eras.
+pub struct StakingEras;
+
+impl EraFinder for StakingEras {
+	fn active_era() -> EraIndex {
+		Staking::active_era().map_or(0, |era| era.index.into())
+	}
+}
+
+/// Splits the staking inflation of an era by `OracleInflationShare` and `TreasuryInflationShare`,
+/// the validators keep the rest.
+pub struct InflationSplit;
+
+impl ComputeEraReward for InflationSplit {
+	fn compute_era_reward(total: Balance) -> (Balance, Balance, Balance) {
+		let oracle = OracleInflationShare::get() * total;
+		let treasury = (TreasuryInflationShare::get() * total).min(total - oracle);
+		(total - oracle - treasury, oracle, treasury)
+	}
+}
+
+/// Milliseconds in a Julian year, which the yearly inflation is spread over
+pub const MILLISECONDS_PER_YEAR: u64 = 1000 * 3600 * 24 * 36525 / 100;
+
+/// NPoS staking inflation whose parameters governance sets through `pallet_standard_parameters`,
+/// falling back to the constants of the runtime.
+///
+/// The yearly inflation grows linearly from `MinInflation` without any stake to `MaxInflation` at
+/// `IdealStake`, then decays back to `MinInflation`, halving every `InflationFalloff` of
+/// additional stake. The era payout is the share of the yearly inflation for the duration of the
+/// era, the remainder up to `MaxInflation` goes to the treasury.
+pub struct InflationCurve;
+
+impl InflationCurve {
+	/// Minimum and maximum yearly inflation, the maximum being at least the minimum
+	fn bounds() -> (Perbill, Perbill) {
+		let min = PerbillParameter::<Runtime, keys::MinInflation, MinInflation>::get();
+		let max = PerbillParameter::<Runtime, keys::MaxInflation, MaxInflation>::get();
+		(min, max.max(min))
+	}
+
+	/// Yearly inflation with `stake` of the issuance staked
+	pub fn yearly_inflation(stake: Perbill) -> Perbill {
+		let (min, max) = Self::bounds();
+		let ideal_stake = PerbillParameter::<Runtime, keys::IdealStake, IdealStake>::get();
+		let falloff = PerbillParameter::<Runtime, keys::InflationFalloff, InflationFalloff>::get();
+		min.saturating_add(max.saturating_sub(min) * compute_inflation(stake, ideal_stake, falloff))
+	}
+}
+
+impl EraPayout<Balance> for InflationCurve {
+	fn era_payout(
+		total_staked: Balance,
+		total_issuance: Balance,
+		era_duration_millis: u64,
+	) -> (Balance, Balance) {
+		let portion = Perbill::from_rational(era_duration_millis, MILLISECONDS_PER_YEAR);
+		let stake = Perbill::from_rational(total_staked, total_issuance);
+		let payout = portion * (Self::yearly_inflation(stake) * total_issuance);
+		let maximum = portion * (Self::bounds().1 * total_issuance);
+		(payout, maximum.saturating_sub(payout))
+	}
+}
+
+/// Era payout of `InflationCurve` whose validator payout is split by `InflationSplit`.
+///
+/// The oracle and treasury shares go to the treasury with the remainder of the curve. The oracle
+/// share is recorded in `OracleInflationReward`, which the oracle pays its providers from the
+/// treasury once the era ended.
+pub struct SplitEraPayout;
+
+impl EraPayout<Balance> for SplitEraPayout {
+	fn era_payout(
+		total_staked: Balance,
+		total_issuance: Balance,
+		era_duration_millis: u64,
+	) -> (Balance, Balance) {
+		let (payout, remainder) =
+			InflationCurve::era_payout(total_staked, total_issuance, era_duration_millis);
+		let (validators, oracle, treasury) = InflationSplit::compute_era_reward(payout);
+		OracleInflationReward::set(&oracle);
+		(validators, remainder.saturating_add(oracle).saturating_add(treasury))
+	}
+}
+
+/// Converts transaction fees from the native currency to an asset at the oracle prices of STND
+/// and the asset, marked up by `AssetFeeSurcharge`.
+///
+/// The fee assets are expected to have the decimals of the native currency.
+pub struct OraclePriceConversion;
+
+impl BalanceConversion<Balance, AssetId, Balance> for OraclePriceConversion {
+	type Error = DispatchError;
+
+	fn to_asset_balance(balance: Balance, asset_id: AssetId) -> Result<Balance, DispatchError> {
+		let native_price = Oracle::price(asset_ids::STND)?;
+		let asset_price = Oracle::price(asset_id)?;
+		let amount = multiply_by_rational(
+			balance,
+			native_price.into_inner(),
+			asset_price.into_inner().max(1),
+		)
+		.map_err(|_| ArithmeticError::Overflow)?;
+		Ok(amount.saturating_add(AssetFeeSurcharge::get() * amount))
+	}
+}
+
+/// `ChargeAssetTxPayment` paying the fee in the native currency when the requested asset is not
+/// one of the `FeeAssets`.
+///
+/// Encoded like `ChargeAssetTxPayment` and under its identifier, so wallets build it as usual.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct ChargeFeeInAsset {
+	#[codec(compact)]
+	tip: Balance,
+	asset_id: Option<AssetId>,
+}
+
+impl ChargeFeeInAsset {
+	/// Pays the fee and `tip` in `asset_id`, or in the native currency without one.
+	pub fn from(tip: Balance, asset_id: Option<AssetId>) -> Self {
+		Self { tip, asset_id }
+	}
+
+	fn charge(&self) -> ChargeAssetTxPayment<Runtime> {
+		let asset_id = self.asset_id.filter(|id| FeeAssets::get().contains(id));
+		ChargeAssetTxPayment::from(self.tip, asset_id)
+	}
+}
+
+impl SignedExtension for ChargeFeeInAsset {
+	const IDENTIFIER: &'static str = "ChargeAssetTxPayment";
+	type AccountId = AccountId;
+	type Call = Call;
+	type AdditionalSigned = ();
+	type Pre = <ChargeAssetTxPayment<Runtime> as SignedExtension>::Pre;
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &AccountId,
+		call: &Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> TransactionValidity {
+		self.charge().validate(who, call, info, len)
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &AccountId,
+		call: &Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		self.charge().pre_dispatch(who, call, info, len)
+	}
+
+	fn post_dispatch(
+		pre: Self::Pre,
+		info: &DispatchInfoOf<Call>,
+		post_info: &PostDispatchInfoOf<Call>,
+		len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		ChargeAssetTxPayment::<Runtime>::post_dispatch(pre, info, post_info, len, result)
+	}
+}
+
+/// Raises the priority of oracle reports, liquidations and bridge proposal votes by the boosts of
+/// `constants::priority`, so that they are not crowded out by regular transactions.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, Default, RuntimeDebug, TypeInfo)]
+pub struct BoostPriority;
+
+impl BoostPriority {
+	/// Priority added to `call` on top of its fee based priority
+	pub fn boost(call: &Call) -> TransactionPriority {
+		match call {
+			Call::Oracle(
+				pallet_standard_oracle::Call::report(..) |
+				pallet_standard_oracle::Call::answer_request(..),
+			) => priority::ORACLE_REPORT_BOOST,
+			Call::Vault(pallet_standard_vault::Call::liquidate_vault(..)) =>
+				priority::LIQUIDATION_BOOST,
+			Call::ChainBridge(
+				pallet_standard_chainbridge::Call::acknowledge_proposal { .. } |
+				pallet_standard_chainbridge::Call::reject_proposal { .. },
+			) => priority::BRIDGE_PROPOSAL_BOOST,
+			_ => 0,
+		}
+	}
+}
+
+impl SignedExtension for BoostPriority {
+	const IDENTIFIER: &'static str = "BoostPriority";
+	type AccountId = AccountId;
+	type Call = Call;
+	type AdditionalSigned = ();
+	type Pre = ();
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		_who: &AccountId,
+		call: &Call,
+		_info: &DispatchInfoOf<Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		Ok(ValidTransaction { priority: Self::boost(call), ..Default::default() })
+	}
+
+	fn pre_dispatch(
+		self,
+		_who: &AccountId,
+		_call: &Call,
+		_info: &DispatchInfoOf<Call>,
+		_len: usize,
+	) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+}
+
+/// Owners of the assets of `pallet_assets`, who can propose to bridge them.
+pub struct AssetOwners;
+impl pallet_standard_bridge_transfer::AssetOwner<AccountId> for AssetOwners {
+	fn owner(asset: AssetId) -> Option<AccountId> {
+		// `pallet_assets` does not expose the details of an asset, which start with its owner
+		let mut key =
+			storage_prefix(<Assets as PalletInfoAccess>::name().as_bytes(), b"Asset").to_vec();
+		key.extend(Blake2_128Concat::hash(&asset.encode()));
+		unhashed::get_raw(&key).and_then(|details| AccountId::decode(&mut &details[..]).ok())
+	}
+}
+
+/// Governance tokens of an account in the market pools and in the collateral of its vault,
+/// counted in its voting power.
+pub struct DefiPositions;
+
+impl GovernancePositions<AccountId> for DefiPositions {
+	fn liquidity(who: &AccountId, lp_token: LpTokenId) -> Option<(Balance, Balance)> {
+		let native = NativeAssetId::get();
+		let (token0, token1) = Market::lp_token_info(lp_token)?.pair;
+		if token0 != native && token1 != native {
+			return None
+		}
+		Some(
+			Market::liquidity_position(who, lp_token)
+				.map(|position| {
+					let underlying = position
+						.underlying
+						.iter()
+						.find(|(asset, _)| *asset == native)
+						.map_or(0, |(_, amount)| *amount);
+					(position.amount, underlying)
+				})
+				.unwrap_or_default(),
+		)
+	}
+
+	fn collateral(who: &AccountId) -> Balance {
+		let native = NativeAssetId::get();
+		Vault::vault_of(who, native)
+			.and_then(|vault| vault.collateral.get(&native).copied())
+			.unwrap_or_default()
+	}
+}
+
+/// Freezes the assets reserved by the market for swap orders and the liquidity tokens backing
+/// votes.
+pub struct AssetFreezer;
+
+impl FrozenBalance<AssetId, AccountId, Balance> for AssetFreezer {
+	fn frozen_balance(asset: AssetId, who: &AccountId) -> Option<Balance> {
+		match (
+			<Market as FrozenBalance<_, _, _>>::frozen_balance(asset, who),
+			<VotingPower as FrozenBalance<_, _, _>>::frozen_balance(asset, who),
+		) {
+			(Some(reserved), Some(frozen)) => Some(reserved.saturating_add(frozen)),
+			(reserved, frozen) => reserved.or(frozen),
+		}
+	}
+
+	fn died(asset: AssetId, who: &AccountId) {
+		<Market as FrozenBalance<_, _, _>>::died(asset, who);
+		<VotingPower as FrozenBalance<_, _, _>>::died(asset, who);
+	}
+}
+
+/// Local account of an Ethereum address, the one its EVM transactions are paid from.
+pub struct EthereumAccounts;
+impl Convert<H160, AccountId> for EthereumAccounts {
+	fn convert(address: H160) -> AccountId {
+		HashedAddressMapping::<BlakeTwo256>::into_account_id(address)
+	}
+}
@@ -8,8 +8,8 @@ use frame_election_provider_support::{
 use frame_support::{
 	construct_runtime, parameter_types,
 	traits::{
-		ConstU128, ConstU16, ConstU32, EnsureOneOf, EqualPrivilegeOnly, FindAuthor,
-		KeyOwnerProofSystem, LockIdentifier, U128CurrencyToVote,
+		ConstU128, ConstU16, ConstU32, Contains, EnsureOneOf, EqualPrivilegeOnly, FindAuthor,
+		KeyOwnerProofSystem, LockIdentifier, Nothing, U128CurrencyToVote,
 	},
 	weights::{
 		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
@@ -32,9 +32,7 @@ use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata, H160, H256, U256};
 use sp_inherents::{CheckInherentsResult, InherentData};
 use sp_runtime::{
-	create_runtime_str,
-	curve::PiecewiseLinear,
-	generic,
+	create_runtime_str, generic,
 	generic::Era,
 	impl_opaque_keys,
 	traits::{
@@ -42,8 +40,7 @@ use sp_runtime::{
 		StaticLookup, Verify,
 	},
 	transaction_validity::{TransactionPriority, TransactionSource, TransactionValidity},
-	ApplyExtrinsicResult, FixedPointNumber, Perbill, Percent, Permill, Perquintill,
-	RuntimeAppPublic,
+	ApplyExtrinsicResult, Perbill, Percent, Permill, RuntimeAppPublic,
 };
 use sp_std::prelude::*;
 #[cfg(feature = "std")]
@@ -67,7 +64,7 @@ use fp_rpc::TransactionStatus;
 use pallet_ethereum::{Call::transact, Transaction as EthereumTransaction};
 use pallet_evm::{Account as EVMAccount, EnsureAddressTruncated, HashedAddressMapping, Runner};
 use pallet_session::historical as pallet_session_historical;
-use pallet_transaction_payment::{CurrencyAdapter, Multiplier, TargetedFeeAdjustment};
+use pallet_transaction_payment::{CurrencyAdapter, TargetedFeeAdjustment};
 
 use precompiles::FrontierPrecompiles;
 mod precompiles;
@@ -75,10 +72,23 @@ pub type Precompiles = FrontierPrecompiles<Runtime>;
 
 /// Implementations of some helper traits passed into runtime modules as associated types.
 pub mod impls;
-use impls::{ElectionProviderBenchmarkConfig, OffchainRandomBalancing, OnChainSeqPhragmen};
+use impls::{
+	AssetFreezer, AssetOwners, BoostPriority, ChargeFeeInAsset, DefiPositions,
+	ElectionProviderBenchmarkConfig, EthereumAccounts, OffchainRandomBalancing, OnChainSeqPhragmen,
+	OraclePriceConversion, SplitEraPayout, StakingEras,
+};
 
+use pallet_standard_parameters::{keys, BalanceParameter, BlockNumberParameter};
 use primitives::{
-	AccountId, AccountIndex, AssetId, Balance, BlockNumber, Hash, Header, Index, Moment, Signature,
+	constants::{asset_ids, network, pallet_ids},
+	AccountId, AccountIndex, AssetId, Balance, BlockNumber, EraIndex, Hash, Header, Index,
+	LpTokenId, Moment, Signature, SocketIndex, VolumeEra,
+};
+use runtime_common::{
+	defi::*,
+	fee::*,
+	staking::{BondingDuration, SessionsPerEra, SlashDeferDuration},
+	weights::{AVERAGE_ON_INITIALIZE_RATIO, NORMAL_DISPATCH_RATIO},
 };
 
 pub mod constants;
@@ -102,7 +112,10 @@ pub type SignedExtra = (
 	frame_system::CheckEra<Runtime>,
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
-	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	ChargeFeeInAsset,
+	pallet_standard_oracle::CheckProviderReport<Runtime>,
+	pallet_standard_vault::CheckPriceAge<Runtime>,
+	BoostPriority,
 );
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
@@ -120,12 +133,6 @@ pub type Executive = frame_executive::Executive<
 	(),
 >;
 
-/// We assume that ~10% of the block weight is consumed by `on_initalize` handlers.
-/// This is used to limit the maximal weight of a single extrinsic.
-const AVERAGE_ON_INITIALIZE_RATIO: Perbill = Perbill::from_percent(10);
-/// We allow `Normal` extrinsics to fill up the block up to 75%, the rest can be used
-/// by  Operational  extrinsics.
-const NORMAL_DISPATCH_RATIO: Perbill = Perbill::from_percent(75);
 /// We allow for 2 seconds of compute with a 6 second average block time.
 const MAXIMUM_BLOCK_WEIGHT: Weight = 2 * WEIGHT_PER_SECOND;
 
@@ -142,10 +149,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("opportunity"),
 	impl_name: create_runtime_str!("opportunity10"),
 	authoring_version: 2,
-	spec_version: 9190,
+	spec_version: 9195,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 7,
+	transaction_version: 11,
 	state_version: 1,
 };
 
@@ -192,14 +199,14 @@ parameter_types! {
 		})
 		.avg_block_initialization(AVERAGE_ON_INITIALIZE_RATIO)
 		.build_or_panic();
-	pub const SS58Prefix: u8 = 42;
+	pub const SS58Prefix: u16 = network::SS58_PREFIX;
 }
 
 // Configure FRAME pallets to include in runtime.
 
 impl frame_system::Config for Runtime {
 	/// The basic call filter to use in dispatchable.
-	type BaseCallFilter = frame_support::traits::Everything;
+	type BaseCallFilter = Maintenance;
 	/// Block & extrinsics weights: base values and limits.
 	type BlockWeights = RuntimeBlockWeights;
 	/// The maximum length of a block (in bytes).
@@ -305,7 +312,7 @@ parameter_types! {
 	pub const TipCountdown: BlockNumber = 1 * DAYS;
 	pub const TipFindersFee: Percent = Percent::from_percent(20);
 	pub const TipReportDepositBase: Balance = 1 * DOLLARS;
-	pub const TreasuryPalletId: PalletId = PalletId(*b"ty/trsry");
+	pub const TreasuryPalletId: PalletId = PalletId(pallet_ids::TREASURY);
 	pub const MaxApprovals: u32 = 100;
 	pub const MaxAuthorities: u32 = 32;
 }
@@ -382,10 +389,18 @@ parameter_types! {
 	pub const TechnicalMaxProposals: u32 = 100;
 	/// The maximum number of technical committee members.
 	pub const TechnicalMaxMembers: u32 = 100;
+
+	/// The maximum amount of time (in blocks) for risk committee members to vote on motions.
+	pub const RiskMotionDuration: BlockNumber = 3 * DAYS;
+	/// The maximum number of Proposlas that can be open in the risk committee at once.
+	pub const RiskMaxProposals: u32 = 20;
+	/// The maximum number of risk committee members.
+	pub const RiskMaxMembers: u32 = 20;
 }
 
 type CouncilCollective = pallet_collective::Instance1;
 type TechnicalCollective = pallet_collective::Instance2;
+type RiskCollective = pallet_collective::Instance3;
 
 impl pallet_collective::Config<CouncilCollective> for Runtime {
 	type Origin = Origin;
@@ -409,6 +424,31 @@ impl pallet_collective::Config<TechnicalCollective> for Runtime {
 	type WeightInfo = pallet_collective::weights::SubstrateWeight<Runtime>;
 }
 
+impl pallet_collective::Config<RiskCollective> for Runtime {
+	type Origin = Origin;
+	type Proposal = Call;
+	type Event = Event;
+	type MotionDuration = RiskMotionDuration;
+	type MaxProposals = RiskMaxProposals;
+	type MaxMembers = RiskMaxMembers;
+	type DefaultVote = pallet_collective::PrimeDefaultVote;
+	type WeightInfo = pallet_collective::weights::SubstrateWeight<Runtime>;
+}
+
+/// Collateral positions are set by root, which scheduled position changes are dispatched as,
+/// or by two thirds of the risk committee.
+type EnsureRootOrRiskCommittee = EnsureOneOf<
+	EnsureRoot<AccountId>,
+	pallet_collective::EnsureProportionAtLeast<AccountId, RiskCollective, 2, 3>,
+>;
+
+// Risk parameters can also be set by the governance of a bridged chain, with the calls it is
+// whitelisted for
+type EnsureRiskGovernance = EnsureOneOf<
+	EnsureRootOrRiskCommittee,
+	pallet_standard_bridge_governance::EnsureRemoteGovernance,
+>;
+
 impl pallet_membership::Config<pallet_membership::Instance1> for Runtime {
 	type Event = Event;
 	type AddOrigin = EnsureRootOrHalfCouncil;
@@ -496,7 +536,8 @@ parameter_types! {
 impl pallet_democracy::Config for Runtime {
 	type Proposal = Call;
 	type Event = Event;
-	type Currency = Balances;
+	// Balances, with the governance tokens in liquidity and vault positions counted in the votes
+	type Currency = VotingPower;
 	type EnactmentPeriod = EnactmentPeriod;
 	type VoteLockingPeriod = EnactmentPeriod;
 	type LaunchPeriod = LaunchPeriod;
@@ -546,6 +587,18 @@ impl pallet_democracy::Config for Runtime {
 	type MaxProposals = MaxProposals;
 }
 
+parameter_types! {
+	pub const MaxVotingLiquidityPositions: u32 = 8;
+}
+
+impl pallet_standard_voting_power::Config for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type Positions = DefiPositions;
+	type GovernanceAssetId = NativeAssetId;
+	type MaxLiquidityPositions = MaxVotingLiquidityPositions;
+}
+
 impl onchain::BoundedExecutionConfig for OnChainSeqPhragmen {
 	type VotersBound = ConstU32<20_000>;
 	type TargetsBound = ConstU32<2_000>;
@@ -597,7 +650,9 @@ impl pallet_election_provider_multi_phase::Config for Runtime {
 	type RewardHandler = (); // nothing to do upon rewards
 	type DataProvider = Staking;
 	type Solution = NposCompactSolution24;
-	type Fallback = onchain::BoundedExecution<OnChainSeqPhragmen>;
+	// An election that fails to produce a queued solution must not fall back to a full on-chain
+	// phragmen over the bags-list voter set; the emergency phase is resolved by governance instead.
+	type Fallback = pallet_election_provider_multi_phase::NoFallback<Self>;
 	type GovernanceFallback = onchain::BoundedExecution<OnChainSeqPhragmen>;
 	type Solver = SequentialPhragmen<AccountId, SolutionAccuracyOf<Self>, OffchainRandomBalancing>;
 	type ForceOrigin = EnsureRootOrHalfCouncil;
@@ -672,27 +727,26 @@ impl pallet_offences::Config for Runtime {
 	type OnOffenceHandler = Staking;
 }
 
-pallet_staking_reward_curve::build! {
-  const REWARD_CURVE: PiecewiseLinear<'static> = curve!(
-	min_inflation: 0_025_000,
-	max_inflation: 0_100_000,
-	ideal_stake: 0_500_000,
-	falloff: 0_050_000,
-	max_piece_count: 45,
-	test_precision: 0_005_000,
-  );
-}
-
 parameter_types! {
-	pub const SessionsPerEra: sp_staking::SessionIndex = 24;
-	pub const BondingDuration: sp_staking::EraIndex = 12;
-	pub const RewardCurve: &'static PiecewiseLinear<'static> = &REWARD_CURVE;
-	pub const SlashDeferDuration: sp_staking::EraIndex = 3; // 1/4 the bonding duration.
+	/// Yearly staking inflation without any stake, unless governance set `MinInflation`
+	pub const MinInflation: Perbill = Perbill::from_parts(25_000_000);
+	/// Yearly staking inflation at the ideal stake, unless governance set `MaxInflation`
+	pub const MaxInflation: Perbill = Perbill::from_percent(10);
+	/// Staked share of the issuance where the inflation peaks, unless governance set `IdealStake`
+	pub const IdealStake: Perbill = Perbill::from_percent(50);
+	/// Stake above the ideal stake halving the inflation, unless governance set `InflationFalloff`
+	pub const InflationFalloff: Perbill = Perbill::from_percent(5);
 	pub const MaxNominatorRewardedPerValidator: u32 = 256;
 	pub const OffendingValidatorsThreshold: Perbill = Perbill::from_percent(17);
 	pub const MaxNominations: u32 = <NposCompactSolution24 as NposSolution>::LIMIT as u32;
 	/// We take the top 22500 nominators as electing voters..
 	pub const MaxElectingVoters: u32 = 22_500;
+	/// Share of the staking inflation of an era paid to the oracle providers
+	pub storage OracleInflationShare: Perbill = Perbill::from_percent(5);
+	/// Share of the staking inflation of an era kept by the treasury
+	pub storage TreasuryInflationShare: Perbill = Perbill::zero();
+	/// Oracle share of the inflation of the last era, paid to the providers by the oracle
+	pub storage OracleInflationReward: Balance = 0;
 }
 
 generate_solution_type!(
@@ -728,7 +782,7 @@ impl pallet_staking::Config for Runtime {
 	type NextNewSession = Session;
 	type MaxNominatorRewardedPerValidator = MaxNominatorRewardedPerValidator;
 	type OffendingValidatorsThreshold = OffendingValidatorsThreshold;
-	type EraPayout = pallet_staking::ConvertCurve<RewardCurve>;
+	type EraPayout = SplitEraPayout;
 	type ElectionProvider = ElectionProviderMultiPhase;
 	type GenesisElectionProvider = onchain::UnboundedExecution<OnChainSeqPhragmen>;
 	type VoterList = BagsList;
@@ -824,19 +878,11 @@ impl pallet_assets::Config for Runtime {
 	type MetadataDepositPerByte = MetadataDepositPerByte;
 	type ApprovalDeposit = ApprovalDeposit;
 	type StringLimit = StringLimit;
-	type Freezer = ();
+	type Freezer = AssetFreezer;
 	type Extra = ();
 	type WeightInfo = pallet_assets::weights::SubstrateWeight<Runtime>;
 }
 
-parameter_types! {
-	pub const TransactionByteFee: Balance = 1;
-	pub const OperationalFeeMultiplier: u8 = 5;
-	pub const TargetBlockFullness: Perquintill = Perquintill::from_percent(25);
-	pub AdjustmentVariable: Multiplier = Multiplier::saturating_from_rational(1, 100_000);
-	pub MinimumMultiplier: Multiplier = Multiplier::saturating_from_rational(1, 1_000_000_000u128);
-}
-
 impl pallet_transaction_payment::Config for Runtime {
 	type OnChargeTransaction = CurrencyAdapter<Balances, ()>;
 	type OperationalFeeMultiplier = OperationalFeeMultiplier;
@@ -846,32 +892,223 @@ impl pallet_transaction_payment::Config for Runtime {
 		TargetedFeeAdjustment<Self, TargetBlockFullness, AdjustmentVariable, MinimumMultiplier>;
 }
 
+parameter_types! {
+	/// Assets fees can be paid in, converted at their oracle price
+	pub storage FeeAssets: Vec<AssetId> = vec![asset_ids::MTR, asset_ids::DOT, asset_ids::KSM];
+}
+
+impl pallet_asset_tx_payment::Config for Runtime {
+	type Fungibles = Assets;
+	type OnChargeAssetTransaction =
+		pallet_asset_tx_payment::FungiblesAdapter<OraclePriceConversion, ()>;
+}
+
 impl pallet_sudo::Config for Runtime {
 	type Event = Event;
 	type Call = Call;
 }
 
+/// Calls allowed while the chain is in maintenance mode: inherents, consensus and governance.
+pub struct MaintenanceCallFilter;
+impl Contains<Call> for MaintenanceCallFilter {
+	fn contains(call: &Call) -> bool {
+		matches!(
+			call,
+			Call::System(_) |
+				Call::Timestamp(_) |
+				Call::Scheduler(_) |
+				Call::Preimage(_) |
+				Call::Sudo(_) |
+				Call::Grandpa(_) |
+				Call::ImOnline(_) |
+				Call::ElectionProviderMultiPhase(_) |
+				Call::Democracy(_) |
+				Call::Council(_) |
+				Call::TechnicalCommittee(_) |
+				Call::Maintenance(_) |
+				Call::TransactionPause(_)
+		)
+	}
+}
+
+/// Pallets that can be disabled individually by the maintenance pallet during incidents.
+pub struct PausablePallets;
+impl Contains<Vec<u8>> for PausablePallets {
+	fn contains(pallet_name: &Vec<u8>) -> bool {
+		matches!(
+			pallet_name.as_slice(),
+			b"Market" | b"Vault" | b"Oracle" | b"ChainBridge" | b"Psm" | b"Dca" |
+				b"BridgeGovernance" | b"Htlc" |
+				b"BridgeTransfer" | b"BridgeOracle"
+		)
+	}
+}
+
+impl pallet_standard_maintenance::Config for Runtime {
+	type Event = Event;
+	type NormalCallFilter = pallet_standard_transaction_pause::PausedTransactionFilter<Runtime>;
+	type MaintenanceCallFilter = MaintenanceCallFilter;
+	type PausablePallets = PausablePallets;
+	type MaintenanceOrigin = EnsureRoot<AccountId>;
+	type SunsetCallFilter = Nothing;
+	type SunsetBlock = ();
+}
+
+/// Pallets whose calls can not be paused: the ones with the inherents and unsigned calls block
+/// production and consensus rely on, and governance, so that it can lift any pause.
+pub struct UnpausablePallets;
+impl Contains<Vec<u8>> for UnpausablePallets {
+	fn contains(pallet_name: &Vec<u8>) -> bool {
+		matches!(
+			pallet_name.as_slice(),
+			b"System" | b"Timestamp" | b"Authorship" | b"Grandpa" | b"ImOnline" | b"Session" |
+				b"ElectionProviderMultiPhase" | b"DynamicFee" | b"Preimage" | b"Sudo" |
+				b"Democracy" | b"Council" | b"TechnicalCommittee" | b"RiskCommittee" |
+				b"Maintenance"
+		)
+	}
+}
+
+impl pallet_standard_transaction_pause::Config for Runtime {
+	type Event = Event;
+	type PauseOrigin = EnsureRoot<AccountId>;
+	type UnpausablePallets = UnpausablePallets;
+}
+
+impl pallet_standard_parameters::Config for Runtime {
+	type Event = Event;
+	type AdminOrigin = EnsureRootOrRiskCommittee;
+}
+
+parameter_types! {
+	pub const BuybackPeriod: BlockNumber = 1 * HOURS;
+}
+
+impl pallet_standard_buyback::Config for Runtime {
+	type Event = Event;
+	type GovernanceOrigin = EnsureRootOrHalfCouncil;
+	type Treasury = TreasuryAccount;
+	type BuybackAsset = NativeAssetId;
+	type BuybackPeriod = BuybackPeriod;
+	type EraFinder = StakingEras;
+	type MaxPolicies = MaxBuybackPolicies;
+}
+
 impl pallet_asset_registry::Config for Runtime {
 	type AssetId = AssetId;
 }
 
+parameter_types! {
+	pub const StalePeriod: BlockNumber = 1 * HOURS;
+	pub const OracleRoundLength: BlockNumber = 1 * MINUTES;
+	pub const OracleMinRequestFee: Balance = 1 * DOLLARS;
+	pub const OracleMaxRequestPeriod: BlockNumber = 1 * HOURS;
+	pub const OracleMaxSymbolLength: u32 = 32;
+	pub const OracleRequestQuorum: u32 = 3;
+	/// Well above the number of oracle assets, only there to bound fee-less reports
+	pub const OracleMaxReportsPerRound: u32 = 32;
+	/// One session
+	pub const OracleHeartbeatPeriod: BlockNumber = EPOCH_DURATION_IN_BLOCKS;
+	pub const OracleHeartbeatSlash: Balance = 1 * DOLLARS;
+	pub const OracleMaxMissedHeartbeats: u32 = 4;
+}
+
 impl pallet_standard_oracle::Config for Runtime {
 	type Event = Event;
 	type WeightInfo = pallet_standard_oracle::weights::SubstrateWeight<Runtime>;
+	type StalePeriod = StalePeriod;
+	type Twap = Market;
+	type TwapWindow = TwapPeriod;
+	type TwapQuoteAsset = MtrAssetId;
+	type RoundLength = OracleRoundLength;
+	type RoundsPerEra = OracleRoundsPerEra;
+	type MaxMissedRounds = OracleMaxMissedRounds;
+	type Currency = Balances;
+	type MinRequestFee = OracleMinRequestFee;
+	type MaxRequestPeriod = OracleMaxRequestPeriod;
+	type MaxSymbolLength = OracleMaxSymbolLength;
+	type RequestQuorum = OracleRequestQuorum;
+	type OnPriceResponse = ();
+	type OnRoundClosed = BridgeOracle;
+	type MaxReportsPerRound = OracleMaxReportsPerRound;
+	type EraFinder = StakingEras;
+	type EraReward = OracleInflationReward;
+	type RewardPot = TreasuryAccount;
+	type HeartbeatPeriod = OracleHeartbeatPeriod;
+	type HeartbeatSlash = OracleHeartbeatSlash;
+	type MaxMissedHeartbeats = OracleMaxMissedHeartbeats;
+	// `SessionValidators<Historical, Offences>` makes the active validators the providers
+	type ProviderValidators = ();
+	type Proposal = Call;
+	type PalletsOrigin = OriginCaller;
+	type Scheduler = Scheduler;
+	type MinimumEnactmentDelay = RiskEnactmentDelay;
 }
 
 parameter_types! {
-	pub const SysPalletId: PalletId = PalletId(*b"stnd/mkt");
+	pub const TwapPeriod: BlockNumber = 1 * HOURS;
+	/// A day of hourly observations
+	pub const MaxTwapObservations: u32 = 25;
+	/// 10, 20 and 30 bps off the 30 bps swap fee
+	pub FeeDiscountTiers: Vec<(Balance, u32)> =
+		vec![(1_000 * STD, 10), (10_000 * STD, 20), (100_000 * STD, 30)];
+	pub const MinFeeLockPeriod: BlockNumber = 30 * DAYS;
+	pub const VolumeEraLength: BlockNumber = 7 * DAYS;
+	pub const VolumeHistoryDepth: VolumeEra = 12;
+	pub const GuardedWithdrawalDelay: BlockNumber = 2 * DAYS;
+	pub const BlocksPerDay: BlockNumber = 1 * DAYS;
+	pub const SwapCommitBond: Balance = 1 * DOLLARS;
+	pub const SwapRevealWindow: BlockNumber = 10;
+	pub const MaxBatchOrders: u32 = 64;
+	pub const ParameterProposalBond: Balance = 100 * DOLLARS;
+	pub const ParameterChallengePeriod: BlockNumber = 3 * DAYS;
+	pub const ParameterChallengeQuorum: Permill = Permill::from_percent(10);
+	pub const MaxParameterProposals: u32 = 16;
 }
 
 impl pallet_standard_market::Config for Runtime {
 	type Event = Event;
 	type Assets = Assets;
 	type SystemPalletId = SysPalletId;
+	type TwapPeriod = TwapPeriod;
+	type MaxTwapObservations = MaxTwapObservations;
+	type Currency = Balances;
+	type FeeDiscountTiers = FeeDiscountTiers;
+	type MinFeeLockPeriod = MinFeeLockPeriod;
+	type VolumeEraLength = VolumeEraLength;
+	type VolumeHistoryDepth = VolumeHistoryDepth;
+	type GuardedWithdrawalDelay = GuardedWithdrawalDelay;
+	type BlocksPerDay = BlocksPerDay;
+	type SwapCommitBond = SwapCommitBond;
+	type SwapRevealWindow = SwapRevealWindow;
+	type MaxBatchOrders = MaxBatchOrders;
+	type ParameterProposalBond = ParameterProposalBond;
+	type ParameterChallengePeriod = ParameterChallengePeriod;
+	type ParameterChallengeQuorum = ParameterChallengeQuorum;
+	type MaxParameterProposals = MaxParameterProposals;
+	type SharedFunds = Vault;
+	type Proposal = Call;
+	type PalletsOrigin = OriginCaller;
+	type Scheduler = Scheduler;
+	type MinimumEnactmentDelay = RiskEnactmentDelay;
 }
 
 parameter_types! {
-	pub const VltPalletId: PalletId = PalletId(*b"stnd/vlt");
+	pub const RiskEnactmentDelay: BlockNumber = 1 * DAYS;
+	pub const LiquidationGracePeriod: BlockNumber = 10 * MINUTES;
+	pub const RateUpdatePeriod: BlockNumber = 1 * DAYS;
+	pub const MaxCollateralAssets: u32 = 5;
+	pub const MaxLeverageIterations: u32 = 5;
+	pub const MinCollateralProviders: u32 = 3;
+	pub const MinCollateralLiquidity: Balance = 100_000 * DOLLARS;
+	/// Caps on the MTR minted by the vaults and the peg stability module
+	pub const MaxMtrMintPerBlock: Balance = 1_000_000 * DOLLARS;
+	pub const MaxMtrMintPerEra: Balance = 10_000_000 * DOLLARS;
+	pub const MtrMintEraLength: BlockNumber = 1 * DAYS;
+	pub const MaxIdleLiquidations: u32 = 20;
+	pub const PurgeReward: Balance = 10 * CENTS;
+	/// Oracle prices older than this can't open debt or liquidate vaults
+	pub const MaxPriceAge: BlockNumber = 5 * MINUTES;
 }
 
 impl pallet_standard_vault::Config for Runtime {
@@ -879,19 +1116,145 @@ impl pallet_standard_vault::Config for Runtime {
 	type VaultPalletId = VltPalletId;
 	type Assets = Assets;
 	type SystemPalletId = SysPalletId;
+	type MtrAssetId = MtrAssetId;
+	type NativeAssetId = NativeAssetId;
+	type Proposal = Call;
+	type PalletsOrigin = OriginCaller;
+	type Scheduler = Scheduler;
+	type RiskOrigin = EnsureRiskGovernance;
+	type MinimumEnactmentDelay = RiskEnactmentDelay;
+	// Tunable with the parameters pallet, the constants are the defaults
+	type LiquidationGracePeriod =
+		BlockNumberParameter<Runtime, keys::LiquidationGracePeriod, LiquidationGracePeriod>;
+	type MtrPeg = MtrPeg;
+	type PegTolerance = PegTolerance;
+	type RateUpdatePeriod = RateUpdatePeriod;
+	type MaxCollateralAssets = MaxCollateralAssets;
+	type MaxLeverageIterations = MaxLeverageIterations;
+	type MinCollateralProviders = MinCollateralProviders;
+	type MinCollateralLiquidity = MinCollateralLiquidity;
+	type MaxMintPerBlock = BalanceParameter<Runtime, keys::MaxMtrMintPerBlock, MaxMtrMintPerBlock>;
+	type MaxMintPerEra = BalanceParameter<Runtime, keys::MaxMtrMintPerEra, MaxMtrMintPerEra>;
+	type MintEraLength = MtrMintEraLength;
+	type MaxIdleLiquidations = MaxIdleLiquidations;
+	type PurgeReward = BalanceParameter<Runtime, keys::PurgeReward, PurgeReward>;
+	type TreasuryAccount = TreasuryAccount;
+	type HistoryDepth = VaultHistoryDepth;
+	type MaxPriceAge = BlockNumberParameter<Runtime, keys::MaxPriceAge, MaxPriceAge>;
+	type FrozenCollateral = VotingPower;
+}
+
+impl pallet_standard_psm::Config for Runtime {
+	type Event = Event;
+	type Assets = Assets;
+	type MtrAssetId = MtrAssetId;
+	type PalletId = PsmPalletId;
+	type GovernanceOrigin = EnsureRootOrHalfCouncil;
+	type MintGuard = Vault;
+}
+
+impl pallet_standard_stream::Config for Runtime {
+	type Event = Event;
+	type Assets = Assets;
+	type PalletId = StreamPalletId;
+}
+
+impl pallet_standard_dca::Config for Runtime {
+	type Event = Event;
+	type PalletId = DcaPalletId;
+	type MaxOrdersPerBlock = MaxDcaOrdersPerBlock;
+	type MaxSkippedRounds = MaxDcaSkippedRounds;
+	type OrderDeposit = DcaOrderDeposit;
+}
+
+impl pallet_standard_htlc::Config for Runtime {
+	type Event = Event;
+	type Assets = Assets;
+	type PalletId = HtlcPalletId;
+}
+
+parameter_types! {
+	pub TreasuryAccount: AccountId = Treasury::account_id();
+}
+
+impl pallet_standard_merkle_distributor::Config for Runtime {
+	type Event = Event;
+	type Assets = Assets;
+	type PalletId = MerkleDistributorPalletId;
+	type TreasuryAccount = TreasuryAccount;
+	type MaxProofLength = MaxMerkleProofLength;
+	type GovernanceOrigin = EnsureRootOrHalfCouncil;
 }
 
 parameter_types! {
 	pub const BridgeChainId: u8 = 100;
 	pub const ProposalLifetime: BlockNumber = 1000;
+	pub const TransferLifetime: BlockNumber = 1 * DAYS;
+	pub const NonceWindow: u64 = 1024;
+	pub const MaxRelayerSnapshots: u32 = 32;
+	pub const MaxVotesPerBlock: u32 = 64;
+	pub const MaxQueuedProposals: u32 = 256;
+	pub const DeliveryQueueTimeout: BlockNumber = 1 * DAYS;
+	/// Calls the relayers could propose before the proposal whitelist
+	pub DefaultAllowedProposals: Vec<(Vec<u8>, Vec<u8>)> =
+		vec![
+			(b"BridgeGovernance".to_vec(), b"execute".to_vec()),
+			(b"BridgeTransfer".to_vec(), b"transfer_in".to_vec()),
+			(b"RemoteVault".to_vec(), b"execute_intent".to_vec()),
+		];
 }
 
 impl pallet_standard_chainbridge::Config for Runtime {
 	type Event = Event;
-	type AdminOrigin = frame_system::EnsureRoot<Self::AccountId>;
+	type RelayerAdminOrigin = EnsureRootOrHalfCouncil;
+	type ResourceAdminOrigin = frame_system::EnsureRoot<Self::AccountId>;
+	type PauseOrigin = EnsureRootOrHalfCouncil;
 	type Proposal = Call;
 	type BridgeChainId = BridgeChainId;
 	type ProposalLifetime = ProposalLifetime;
+	type TransferCallback = BridgeTransfer;
+	// Tunable with the parameters pallet, the constant is the default
+	type TransferLifetime =
+		BlockNumberParameter<Runtime, keys::BridgeTransferLifetime, TransferLifetime>;
+	type NonceWindow = NonceWindow;
+	type MaxRelayerSnapshots = MaxRelayerSnapshots;
+	type MaxVotesPerBlock = MaxVotesPerBlock;
+	type DefaultAllowedProposals = DefaultAllowedProposals;
+	type MaxQueuedProposals = MaxQueuedProposals;
+	type DeliveryQueueTimeout = DeliveryQueueTimeout;
+}
+
+impl pallet_standard_bridge_governance::Config for Runtime {
+	type Event = Event;
+	type Origin = Origin;
+	type Call = Call;
+	type BridgeOrigin = pallet_standard_chainbridge::EnsureBridge<Runtime>;
+	type GovernanceOrigin = EnsureRootOrHalfCouncil;
+}
+
+impl pallet_standard_bridge_transfer::Config for Runtime {
+	type Event = Event;
+	type Assets = Assets;
+	type BridgeOrigin = pallet_standard_chainbridge::EnsureBridge<Runtime>;
+	type AdminOrigin = EnsureRootOrHalfCouncil;
+	type AssetOwner = AssetOwners;
+	type Reserves = Market;
+	type PalletId = BridgeTransferPalletId;
+}
+
+impl pallet_standard_bridge_oracle::Config for Runtime {
+	type Event = Event;
+	type AdminOrigin = EnsureRootOrHalfCouncil;
+	type PalletId = BridgeOraclePalletId;
+}
+
+impl pallet_standard_remote_vault::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type BridgeOrigin = pallet_standard_chainbridge::EnsureBridge<Runtime>;
+	type GovernanceOrigin = EnsureRootOrHalfCouncil;
+	type AddressMapping = EthereumAccounts;
+	type BridgeChainId = BridgeChainId;
 }
 
 parameter_types! {
@@ -984,6 +1347,9 @@ impl pallet_base_fee::Config for Runtime {
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
+// Pallet indices and the position of each call in its pallet are encoded in signed transactions.
+// Keep the indices explicit and append new calls to the end of their pallet, the call indices of
+// the Standard pallets are pinned by `call_indices.txt`.
 construct_runtime!(
 	pub enum Runtime where
 		Block = Block,
@@ -996,6 +1362,8 @@ construct_runtime!(
 		TransactionPayment: pallet_transaction_payment::{Pallet, Storage} = 4,
 		Sudo: pallet_sudo::{Pallet, Call, Config<T>, Storage, Event<T>} = 5,
 		Preimage: pallet_preimage::{Pallet, Call, Storage, Event<T>} = 6,
+		Maintenance: pallet_standard_maintenance::{Pallet, Call, Storage, Event<T>} = 7,
+		TransactionPause: pallet_standard_transaction_pause::{Pallet, Call, Storage, Event<T>} = 8,
 		// Consensus pallets
 		Aura: pallet_aura::{Pallet, Config<T>} = 10,
 		Grandpa: pallet_grandpa::{Pallet, Call, Storage, Config, Event, ValidateUnsigned} = 11,
@@ -1003,6 +1371,7 @@ construct_runtime!(
 		// Balance pallets
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>} = 20,
 		Assets: pallet_assets::{Pallet, Call, Storage, Event<T>} = 21,
+		AssetTxPayment: pallet_asset_tx_payment::{Pallet} = 22,
 		// Staking pallets
 		Offences: pallet_offences::{Pallet, Storage, Event} = 30,
 		Staking: pallet_staking::{Pallet, Call, Config<T>, Storage, Event<T>} = 31,
@@ -1019,16 +1388,29 @@ construct_runtime!(
 		Council: pallet_collective::<Instance1>::{Pallet, Call, Storage, Origin<T>, Event<T>, Config<T>} = 42,
 		TechnicalCommittee: pallet_collective::<Instance2>::{Pallet, Call, Storage, Origin<T>, Event<T>, Config<T>} = 43,
 		TechnicalMembership: pallet_membership::<Instance1>::{Pallet, Call, Storage, Event<T>, Config<T>} = 44,
+		RiskCommittee: pallet_collective::<Instance3>::{Pallet, Call, Storage, Origin<T>, Event<T>, Config<T>} = 65,
+		Parameters: pallet_standard_parameters::{Pallet, Call, Storage, Event<T>} = 66,
+		Buyback: pallet_standard_buyback::{Pallet, Call, Storage, Event<T>} = 67,
 		Elections: pallet_elections_phragmen::{Pallet, Call, Storage, Event<T>, Config<T>} = 45,
 		Treasury: pallet_treasury::{Pallet, Call, Storage, Config, Event<T>} = 46,
 		Bounties: pallet_bounties::{Pallet, Call, Storage, Event<T>} = 47,
 		Tips: pallet_tips::{Pallet, Call, Storage, Event<T>} = 48,
+		MerkleDistributor: pallet_standard_merkle_distributor::{Pallet, Call, Storage, Event<T>} = 49,
 		// Standard pallets
 		AssetRegistry: pallet_asset_registry::{Pallet, Storage, Config<T>} = 50,
-		Market: pallet_standard_market::{Pallet, Call, Storage, Event} = 51,
+		Market: pallet_standard_market::{Pallet, Call, Storage, Event<T>} = 51,
 		Oracle: pallet_standard_oracle::{Pallet, Call, Storage, Event<T>, Config<T>} = 52,
 		Vault: pallet_standard_vault::{Pallet, Call, Storage, Event<T>} = 53,
+		Psm: pallet_standard_psm::{Pallet, Call, Storage, Event<T>} = 55,
+		Stream: pallet_standard_stream::{Pallet, Call, Storage, Event<T>} = 56,
+		Dca: pallet_standard_dca::{Pallet, Call, Storage, Event<T>} = 57,
 		ChainBridge: pallet_standard_chainbridge::{Pallet, Call, Storage, Event<T>} = 54,
+		BridgeGovernance: pallet_standard_bridge_governance::{Pallet, Call, Storage, Event<T>, Origin} = 58,
+		BridgeTransfer: pallet_standard_bridge_transfer::{Pallet, Call, Storage, Event<T>} = 64,
+		BridgeOracle: pallet_standard_bridge_oracle::{Pallet, Call, Storage, Event<T>} = 68,
+		RemoteVault: pallet_standard_remote_vault::{Pallet, Call, Storage, Event<T>} = 69,
+		VotingPower: pallet_standard_voting_power::{Pallet, Call, Storage, Event<T>} = 70,
+		Htlc: pallet_standard_htlc::{Pallet, Call, Storage, Event<T>} = 59,
 		// EVM pallets
 		Ethereum: pallet_ethereum::{Pallet, Call, Storage, Event, Origin, Config} = 60,
 		EVM: pallet_evm::{Pallet, Config, Call, Storage, Event<T>} = 61,
@@ -1088,7 +1470,10 @@ where
 			frame_system::CheckEra::<Runtime>::from(era),
 			frame_system::CheckNonce::<Runtime>::from(nonce),
 			frame_system::CheckWeight::<Runtime>::new(),
-			pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(tip),
+			ChargeFeeInAsset::from(tip, None),
+			pallet_standard_oracle::CheckProviderReport::<Runtime>::new(),
+			pallet_standard_vault::CheckPriceAge::<Runtime>::new(),
+			BoostPriority,
 		);
 		let raw_payload = SignedPayload::new(call, extra)
 			.map_err(|e| {
@@ -1115,6 +1500,16 @@ where
 	type OverarchingCall = Call;
 }
 
+/// Asset ids registered at genesis keyed by their symbol
+fn well_known_assets() -> Vec<(Vec<u8>, AssetId)> {
+	vec![
+		(b"STD".to_vec(), asset_ids::STND),
+		(b"MTR".to_vec(), asset_ids::MTR),
+		(b"DOT".to_vec(), asset_ids::DOT),
+		(b"KSM".to_vec(), asset_ids::KSM),
+	]
+}
+
 sp_api::impl_runtime_apis! {
 	impl sp_api::Core<Block> for Runtime {
 		fn version() -> RuntimeVersion {
@@ -1248,6 +1643,160 @@ sp_api::impl_runtime_apis! {
 		}
 	}
 
+	impl primitives::MarketApi<Block> for Runtime {
+		fn reserve_discrepancies() -> Vec<(AssetId, Balance, Balance)> {
+			Market::reserve_discrepancies()
+		}
+
+		fn lp_token_info(lp_token: LpTokenId) -> Option<primitives::overview::LpTokenInfo> {
+			Market::lp_token_info(lp_token)
+		}
+
+		fn lp_tokens() -> Vec<primitives::overview::LpTokenInfo> {
+			Market::lp_tokens()
+		}
+
+		fn pool_stats(lp_token: LpTokenId) -> Option<primitives::overview::PoolStats> {
+			Market::pool_stats(lp_token).map(|stats| stats.priced(|id| Oracle::price(id).ok()))
+		}
+
+		fn all_pool_stats() -> Vec<primitives::overview::PoolStats> {
+			Market::all_pool_stats()
+				.into_iter()
+				.map(|stats| stats.priced(|id| Oracle::price(id).ok()))
+				.collect()
+		}
+	}
+
+	impl primitives::MarketVolumeApi<Block, AccountId> for Runtime {
+		fn current_volume_era() -> VolumeEra {
+			Market::current_volume_era()
+		}
+
+		fn account_volume(account: AccountId, era: VolumeEra) -> Vec<(LpTokenId, Balance)> {
+			Market::account_volume(era, account)
+		}
+
+		fn pair_volume(lp_token: LpTokenId, era: VolumeEra) -> Balance {
+			Market::pair_volume(era, lp_token)
+		}
+	}
+
+	impl primitives::OracleApi<Block, AccountId> for Runtime {
+		fn providers() -> Vec<AccountId> {
+			Oracle::providers()
+		}
+
+		fn slot_assignments() -> Vec<(SocketIndex, AccountId)> {
+			Oracle::slot_assignments()
+		}
+
+		fn slashes(era: EraIndex) -> Vec<AccountId> {
+			Oracle::slashes(era)
+		}
+	}
+
+	impl primitives::LiquidityReturnsApi<Block, AccountId> for Runtime {
+		fn liquidity_returns(account: AccountId) -> Vec<primitives::overview::LiquidityReturn> {
+			Market::liquidity_returns(&account)
+				.into_iter()
+				.map(|position| position.priced(|id| Oracle::price(id).ok()))
+				.collect()
+		}
+	}
+
+	impl primitives::AccountApi<Block, AccountId> for Runtime {
+		fn account_overview(account: AccountId) -> primitives::overview::AccountOverview {
+			let mut balances = vec![(primitives::CORE_ASSET_ID, Balances::free_balance(&account))];
+			balances.extend(
+				well_known_assets().into_iter().map(|(_, id)| (id, Assets::balance(id, &account))),
+			);
+			primitives::overview::AccountOverview {
+				balances,
+				liquidity: Market::liquidity_of(&account),
+				vaults: Vault::vaults_of(&account),
+				rewards: Stream::pending_rewards(&account),
+			}
+		}
+	}
+
+	impl primitives::VaultHistoryApi<Block> for Runtime {
+		fn vault_era_stats(era: EraIndex) -> Option<primitives::snapshot::VaultEraStats> {
+			Vault::vault_era_stats(era)
+		}
+
+		fn vault_history() -> Vec<primitives::snapshot::VaultEraStats> {
+			Vault::vault_history()
+		}
+	}
+
+	impl primitives::DefiSnapshotApi<Block, AccountId> for Runtime {
+		fn defi_snapshot() -> primitives::snapshot::DefiSnapshot<AccountId> {
+			let mut assets: Vec<AssetId> =
+				well_known_assets().into_iter().map(|(_, id)| id).collect();
+			assets.extend(Vault::collateral_ids());
+			assets.sort();
+			assets.dedup();
+			primitives::snapshot::DefiSnapshot {
+				pools: Market::pools(),
+				vaults: Vault::vault_snapshots(),
+				prices: assets
+					.iter()
+					.filter_map(|&id| Oracle::price(id).ok().map(|price| (id, price)))
+					.collect(),
+				issuance: assets.iter().map(|&id| (id, Assets::total_supply(id))).collect(),
+			}
+		}
+	}
+
+	impl primitives::ChainBridgeApi<Block, AccountId, BlockNumber> for Runtime {
+		fn transfer_receipt(
+			dest_id: primitives::bridge::BridgeChainId,
+			nonce: primitives::bridge::DepositNonce,
+		) -> Option<primitives::bridge::TransferReceipt<AccountId, BlockNumber>> {
+			ChainBridge::transfer_receipts(dest_id, nonce)
+		}
+
+		fn catalogue() -> primitives::bridge::BridgeCatalogue<AccountId> {
+			ChainBridge::catalogue()
+		}
+	}
+
+	impl primitives::StateProofApi<Block> for Runtime {
+		fn proven_vault(
+			vault_id: primitives::VaultId,
+		) -> primitives::proof::Proven<Option<primitives::overview::VaultPosition>> {
+			Vault::proven_vault(vault_id)
+		}
+
+		fn proven_pool(
+			lp_token: LpTokenId,
+		) -> primitives::proof::Proven<Option<primitives::snapshot::PoolSnapshot>> {
+			let mut pool = Market::proven_pool(lp_token);
+			let asset = AssetId::from(lp_token);
+			pool.keys.push(pallet_assets::Asset::<Runtime>::hashed_key_for(asset));
+			pool
+		}
+	}
+
+	impl primitives::ConstantsApi<Block> for Runtime {
+		fn native_asset_id() -> AssetId {
+			primitives::CORE_ASSET_ID
+		}
+
+		fn mtr_asset_id() -> AssetId {
+			MtrAssetId::get()
+		}
+
+		fn well_known_assets() -> Vec<(Vec<u8>, AssetId)> {
+			well_known_assets()
+		}
+
+		fn ss58_prefix() -> u16 {
+			SS58Prefix::get()
+		}
+	}
+
 	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<
 		Block,
 		Balance,
@@ -1391,3 +1940,160 @@ sp_api::impl_runtime_apis! {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_support::{assert_ok, metadata::RuntimeMetadata};
+	use impls::{InflationCurve, MILLISECONDS_PER_YEAR};
+	use pallet_staking::EraPayout;
+	use pallet_standard_parameters::Parameter;
+	use scale_info::TypeDef;
+
+	/// Pallets maintained in this repository
+	const STANDARD_PALLETS: &[&str] = &[
+		"Maintenance",
+		"TransactionPause",
+		"Parameters",
+		"Buyback",
+		"Market",
+		"Oracle",
+		"Vault",
+		"Psm",
+		"Stream",
+		"Dca",
+		"Htlc",
+		"MerkleDistributor",
+		"ChainBridge",
+		"BridgeGovernance",
+		"BridgeTransfer",
+		"BridgeOracle",
+		"RemoteVault",
+		"VotingPower",
+	];
+
+	#[test]
+	fn call_indices_match_snapshot() {
+		let metadata = match Runtime::metadata().1 {
+			RuntimeMetadata::V14(metadata) => metadata,
+			_ => panic!("metadata is not V14"),
+		};
+		let mut indices = Vec::new();
+		for pallet in &metadata.pallets {
+			let calls = match &pallet.calls {
+				Some(calls) if STANDARD_PALLETS.contains(&pallet.name.as_str()) => calls,
+				_ => continue,
+			};
+			let ty = metadata.types.resolve(calls.ty.id()).expect("call type is registered");
+			if let TypeDef::Variant(variants) = ty.type_def() {
+				for call in variants.variants() {
+					let name = format!("{}::{}", pallet.name, call.name());
+					indices.push((pallet.index, call.index(), name));
+				}
+			}
+		}
+		indices.sort();
+		let snapshot: String = indices
+			.into_iter()
+			.map(|(pallet, call, name)| format!("{}.{} {}\n", pallet, call, name))
+			.collect();
+		// Changing an index breaks transactions signed against the previous runtime
+		assert_eq!(snapshot, include_str!("../call_indices.txt"));
+	}
+
+	#[test]
+	fn era_inflation_is_shared_with_oracle_providers() {
+		sp_io::TestExternalities::default().execute_with(|| {
+			OracleInflationShare::set(&Perbill::from_percent(10));
+			TreasuryInflationShare::set(&Perbill::from_percent(20));
+			let (staked, issuance, year) = (500 * DOLLARS, 1_000 * DOLLARS, 365 * 24 * 3_600_000);
+			let (payout, remainder) = InflationCurve::era_payout(staked, issuance, year);
+
+			let (validators, rest) = SplitEraPayout::era_payout(staked, issuance, year);
+			let oracle = Perbill::from_percent(10) * payout;
+			let treasury = Perbill::from_percent(20) * payout;
+			assert_eq!(validators, payout - oracle - treasury);
+			assert_eq!(rest, remainder + oracle + treasury);
+			assert_eq!(OracleInflationReward::get(), oracle);
+		})
+	}
+
+	#[test]
+	fn session_validators_can_be_the_oracle_providers() {
+		use pallet_standard_oracle::{OracleOffenceKind, ProviderValidators, SessionValidators};
+		type Validators = SessionValidators<Historical, Offences>;
+
+		sp_io::TestExternalities::default().execute_with(|| {
+			System::set_block_number(1);
+			assert_eq!(Validators::session_index(), Some(0));
+			assert_eq!(Validators::validators(), Vec::<AccountId>::new());
+
+			// Validators are identified by their exposure in the active era
+			let validator = AccountId::from([1; 32]);
+			pallet_session::Validators::<Runtime>::put(vec![validator.clone()]);
+			pallet_staking::ActiveEra::<Runtime>::put(pallet_staking::ActiveEraInfo {
+				index: 0,
+				start: None,
+			});
+			assert_eq!(Validators::validators(), vec![validator.clone()]);
+
+			Validators::report_offence(0, vec![validator], OracleOffenceKind::Outlier);
+			assert!(System::events().iter().any(|record| matches!(
+				&record.event,
+				Event::Offences(pallet_offences::Event::Offence { kind, .. })
+					if kind == b"oracle:misbehave"
+			)));
+		})
+	}
+
+	#[test]
+	fn inherents_and_governance_can_not_be_paused() {
+		for pallet in ["System", "Timestamp", "ImOnline", "ElectionProviderMultiPhase", "Council"] {
+			assert!(UnpausablePallets::contains(&pallet.as_bytes().to_vec()), "{}", pallet);
+		}
+		assert!(!UnpausablePallets::contains(&b"Market".to_vec()));
+	}
+
+	#[test]
+	fn inflation_curve_follows_parameters() {
+		sp_io::TestExternalities::default().execute_with(|| {
+			let (issuance, year) = (1_000 * DOLLARS, MILLISECONDS_PER_YEAR);
+			// Minimum without any stake, maximum at the ideal stake
+			assert_eq!(InflationCurve::era_payout(0, issuance, year), (25 * DOLLARS, 75 * DOLLARS));
+			assert_eq!(
+				InflationCurve::era_payout(500 * DOLLARS, issuance, year),
+				(100 * DOLLARS, 0)
+			);
+			// Halfway back to the minimum a falloff above the ideal stake
+			let (payout, _) = InflationCurve::era_payout(550 * DOLLARS, issuance, year);
+			let expected = 6_250 * DOLLARS / 100;
+			assert!(payout > expected - DOLLARS / 100 && payout < expected + DOLLARS / 100);
+
+			assert_ok!(Parameters::set_parameter(
+				Origin::root(),
+				Parameter::MaxInflation(Perbill::from_percent(20))
+			));
+			assert_ok!(Parameters::set_parameter(
+				Origin::root(),
+				Parameter::IdealStake(Perbill::from_percent(60))
+			));
+			assert_eq!(
+				InflationCurve::era_payout(300 * DOLLARS, issuance, year),
+				(1_125 * DOLLARS / 10, 875 * DOLLARS / 10)
+			);
+			assert_eq!(
+				InflationCurve::era_payout(600 * DOLLARS, issuance, year),
+				(200 * DOLLARS, 0)
+			);
+			// The maximum never falls below the minimum
+			assert_ok!(Parameters::set_parameter(
+				Origin::root(),
+				Parameter::MaxInflation(Perbill::from_percent(1))
+			));
+			assert_eq!(
+				InflationCurve::era_payout(300 * DOLLARS, issuance, year),
+				(25 * DOLLARS, 0)
+			);
+		})
+	}
+}
@@ -18,42 +18,12 @@
 //! A set of constant values used in substrate runtime.
 
 /// Money matters.
-pub mod currency {
+pub use runtime_common::currency;
 
-	pub use primitives::Balance;
-	pub const MILLICENTS: Balance = 1_000_000_000;
-	pub const CENTS: Balance = 1_000 * MILLICENTS; // assume this is worth about a cent.
-	pub const DOLLARS: Balance = 100 * CENTS;
-	pub const MICROSTD: Balance = 1_000_000_000_000;
-	pub const MILLISTD: Balance = 1_000_000_000_000_000;
-	pub const STD: Balance = 1_000 * MILLISTD;
-
-	pub const fn deposit(items: u32, bytes: u32) -> Balance {
-		items as Balance * 15 * CENTS + (bytes as Balance) * 6 * CENTS
-	}
-}
+/// Transaction priorities.
+pub use runtime_common::priority;
 
 /// Time.
 pub mod time {
-	use primitives::{BlockNumber, Moment};
-
-	pub const MILLISECS_PER_BLOCK: Moment = 12000;
-	pub const SECS_PER_BLOCK: Moment = MILLISECS_PER_BLOCK / 1000;
-
-	pub const SLOT_DURATION: Moment = MILLISECS_PER_BLOCK;
-
-	// 1 in 4 blocks (on average, not counting collisions) will be primary BABE blocks.
-	pub const PRIMARY_PROBABILITY: (u64, u64) = (1, 4);
-
-	pub const EPOCH_DURATION_IN_BLOCKS: BlockNumber = 1 * HOURS;
-	pub const EPOCH_DURATION_IN_SLOTS: u64 = {
-		const SLOT_FILL_RATE: f64 = MILLISECS_PER_BLOCK as f64 / SLOT_DURATION as f64;
-
-		(EPOCH_DURATION_IN_BLOCKS as f64 * SLOT_FILL_RATE) as u64
-	};
-
-	// These time units are defined in number of blocks.
-	pub const MINUTES: BlockNumber = 60 / (SECS_PER_BLOCK as BlockNumber);
-	pub const HOURS: BlockNumber = MINUTES * 60;
-	pub const DAYS: BlockNumber = HOURS * 24;
+	runtime_common::impl_time_units!(12000);
 }
@@ -0,0 +1,331 @@
+//! Adapters used in the runtime configuration.
+
+use crate::{
+	constants::priority, AccountId, AssetFeeSurcharge, Assets, Authorship, Balances, Call,
+	DustTreasuryShare, FeeAssets, FeeBurnShare, FeeTreasuryShare, Oracle, Runtime, TipBurnShare,
+	TipTreasuryShare, TreasuryAccount,
+};
+use frame_support::{
+	storage::{storage_prefix, unhashed},
+	traits::{tokens::BalanceConversion, Currency, Imbalance, OnUnbalanced, PalletInfoAccess},
+	Blake2_128Concat, StorageHasher,
+};
+use pallet_asset_tx_payment::ChargeAssetTxPayment;
+use pallet_evm::{AddressMapping, HashedAddressMapping};
+use parity_scale_codec::{Decode, Encode};
+use primitives::{constants::asset_ids, AssetId, Balance};
+use scale_info::TypeInfo;
+use sp_core::H160;
+use sp_runtime::{
+	helpers_128bit::multiply_by_rational,
+	traits::{BlakeTwo256, Convert, DispatchInfoOf, PostDispatchInfoOf, SignedExtension},
+	transaction_validity::{
+		TransactionPriority, TransactionValidity, TransactionValidityError, ValidTransaction,
+	},
+	ArithmeticError, DispatchError, DispatchResult, FixedPointNumber, Perbill, RuntimeDebug,
+};
+
+type NegativeImbalance = <Balances as Currency<AccountId>>::NegativeImbalance;
+
+/// Dust handler of `pallet_balances` sending `DustTreasuryShare` of the removed dust to the
+/// treasury and burning the rest.
+///
+/// `DustTreasuryShare` is a runtime storage parameter, so governance changes it with
+/// `system.set_storage`. Balances emits `DustLost` for the removed dust and `Deposit` for the
+/// treasury share, the burned share is the difference.
+pub struct DustToTreasury;
+
+impl OnUnbalanced<NegativeImbalance> for DustToTreasury {
+	fn on_nonzero_unbalanced(dust: NegativeImbalance) {
+		let share = DustTreasuryShare::get() * dust.peek();
+		let (to_treasury, burned) = dust.split(share);
+		// Dust the treasury account can not take is burned as well
+		Balances::resolve_creating(&TreasuryAccount::get(), to_treasury);
+		// Dropping the imbalance reduces the total issuance
+		drop(burned);
+	}
+}
+
+/// Transaction fee handler of `pallet_transaction_payment` burning `FeeBurnShare` of the fees,
+/// sending `FeeTreasuryShare` to the treasury and the rest to the block author. Tips are split
+/// alike by `TipBurnShare` and `TipTreasuryShare`.
+///
+/// The shares are runtime storage parameters, so governance changes them with
+/// `system.set_storage`. The treasury takes the share of the author while `pallet_authorship`
+/// knows none.
+pub struct DealWithFees;
+
+impl DealWithFees {
+	// Burns `burn` of `amount`, sends `treasury` of it to the treasury and the rest to the author
+	fn split(amount: NegativeImbalance, burn: Perbill, treasury: Perbill) {
+		let total = amount.peek();
+		let (burned, rest) = amount.split(burn * total);
+		let (mut to_treasury, to_author) = rest.split(treasury * total);
+		match Authorship::author() {
+			Some(author) => Balances::resolve_creating(&author, to_author),
+			None => to_treasury.subsume(to_author),
+		}
+		Balances::resolve_creating(&TreasuryAccount::get(), to_treasury);
+		// Dropping the imbalance reduces the total issuance
+		drop(burned);
+	}
+}
+
+impl OnUnbalanced<NegativeImbalance> for DealWithFees {
+	fn on_unbalanceds<B>(mut fees_then_tips: impl Iterator<Item = NegativeImbalance>) {
+		if let Some(fees) = fees_then_tips.next() {
+			Self::split(fees, FeeBurnShare::get(), FeeTreasuryShare::get());
+		}
+		if let Some(tips) = fees_then_tips.next() {
+			Self::split(tips, TipBurnShare::get(), TipTreasuryShare::get());
+		}
+	}
+}
+
+/// Converts transaction fees from the native currency to an asset at the oracle prices of STND
+/// and the asset, marked up by `AssetFeeSurcharge`.
+///
+/// The fee assets are expected to have the decimals of the native currency.
+pub struct OraclePriceConversion;
+
+impl BalanceConversion<Balance, AssetId, Balance> for OraclePriceConversion {
+	type Error = DispatchError;
+
+	fn to_asset_balance(balance: Balance, asset_id: AssetId) -> Result<Balance, DispatchError> {
+		let native_price = Oracle::price(asset_ids::STND)?;
+		let asset_price = Oracle::price(asset_id)?;
+		let amount = multiply_by_rational(
+			balance,
+			native_price.into_inner(),
+			asset_price.into_inner().max(1),
+		)
+		.map_err(|_| ArithmeticError::Overflow)?;
+		Ok(amount.saturating_add(AssetFeeSurcharge::get() * amount))
+	}
+}
+
+/// `ChargeAssetTxPayment` paying the fee in the native currency when the requested asset is not
+/// one of the `FeeAssets`.
+///
+/// Encoded like `ChargeAssetTxPayment` and under its identifier, so wallets build it as usual.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct ChargeFeeInAsset {
+	#[codec(compact)]
+	tip: Balance,
+	asset_id: Option<AssetId>,
+}
+
+impl ChargeFeeInAsset {
+	/// Pays the fee and `tip` in `asset_id`, or in the native currency without one.
+	pub fn from(tip: Balance, asset_id: Option<AssetId>) -> Self {
+		Self { tip, asset_id }
+	}
+
+	fn charge(&self) -> ChargeAssetTxPayment<Runtime> {
+		let asset_id = self.asset_id.filter(|id| FeeAssets::get().contains(id));
+		ChargeAssetTxPayment::from(self.tip, asset_id)
+	}
+}
+
+impl SignedExtension for ChargeFeeInAsset {
+	const IDENTIFIER: &'static str = "ChargeAssetTxPayment";
+	type AccountId = AccountId;
+	type Call = Call;
+	type AdditionalSigned = ();
+	type Pre = <ChargeAssetTxPayment<Runtime> as SignedExtension>::Pre;
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &AccountId,
+		call: &Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> TransactionValidity {
+		self.charge().validate(who, call, info, len)
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &AccountId,
+		call: &Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		self.charge().pre_dispatch(who, call, info, len)
+	}
+
+	fn post_dispatch(
+		pre: Self::Pre,
+		info: &DispatchInfoOf<Call>,
+		post_info: &PostDispatchInfoOf<Call>,
+		len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		ChargeAssetTxPayment::<Runtime>::post_dispatch(pre, info, post_info, len, result)
+	}
+}
+
+/// Raises the priority of oracle reports, liquidations and bridge proposal votes by the boosts of
+/// `constants::priority`, so that they are not crowded out by regular transactions.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, Default, RuntimeDebug, TypeInfo)]
+pub struct BoostPriority;
+
+impl BoostPriority {
+	/// Priority added to `call` on top of its fee based priority
+	pub fn boost(call: &Call) -> TransactionPriority {
+		match call {
+			Call::Oracle(
+				pallet_standard_oracle::Call::report(..) |
+				pallet_standard_oracle::Call::answer_request(..),
+			) => priority::ORACLE_REPORT_BOOST,
+			Call::Vault(pallet_standard_vault::Call::liquidate_vault(..)) =>
+				priority::LIQUIDATION_BOOST,
+			Call::ChainBridge(
+				pallet_standard_chainbridge::Call::acknowledge_proposal { .. } |
+				pallet_standard_chainbridge::Call::reject_proposal { .. },
+			) => priority::BRIDGE_PROPOSAL_BOOST,
+			_ => 0,
+		}
+	}
+}
+
+impl SignedExtension for BoostPriority {
+	const IDENTIFIER: &'static str = "BoostPriority";
+	type AccountId = AccountId;
+	type Call = Call;
+	type AdditionalSigned = ();
+	type Pre = ();
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		_who: &AccountId,
+		call: &Call,
+		_info: &DispatchInfoOf<Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		Ok(ValidTransaction { priority: Self::boost(call), ..Default::default() })
+	}
+
+	fn pre_dispatch(
+		self,
+		_who: &AccountId,
+		_call: &Call,
+		_info: &DispatchInfoOf<Call>,
+		_len: usize,
+	) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+}
+
+/// Owners of the assets of `pallet_assets`, who can propose to bridge them.
+pub struct AssetOwners;
+impl pallet_standard_bridge_transfer::AssetOwner<AccountId> for AssetOwners {
+	fn owner(asset: AssetId) -> Option<AccountId> {
+		// `pallet_assets` does not expose the details of an asset, which start with its owner
+		let mut key =
+			storage_prefix(<Assets as PalletInfoAccess>::name().as_bytes(), b"Asset").to_vec();
+		key.extend(Blake2_128Concat::hash(&asset.encode()));
+		unhashed::get_raw(&key).and_then(|details| AccountId::decode(&mut &details[..]).ok())
+	}
+}
+
+/// Local account of an Ethereum address, the one its EVM transactions are paid from.
+pub struct EthereumAccounts;
+impl Convert<H160, AccountId> for EthereumAccounts {
+	fn convert(address: H160) -> AccountId {
+		HashedAddressMapping::<BlakeTwo256>::into_account_id(address)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ExistentialDeposit, Origin};
+	use frame_support::{
+		assert_ok,
+		storage::{StorageDoubleMap, StorageValue},
+	};
+	use pallet_standard_bridge_transfer::AssetOwner;
+	use pallet_standard_oracle::{ProviderCount, SocketPrices};
+	use sp_runtime::{FixedU128, Perbill};
+
+	fn new_test_ext() -> sp_io::TestExternalities {
+		frame_system::GenesisConfig::default()
+			.build_storage::<Runtime>()
+			.unwrap()
+			.into()
+	}
+
+	#[test]
+	fn dust_is_split_between_treasury_and_burn() {
+		new_test_ext().execute_with(|| {
+			let treasury = TreasuryAccount::get();
+			let existential_deposit = ExistentialDeposit::get();
+			Balances::make_free_balance_be(&treasury, existential_deposit);
+			DustTreasuryShare::set(&Perbill::from_percent(30));
+
+			DustToTreasury::on_unbalanced(Balances::issue(1_000));
+			assert_eq!(Balances::free_balance(&treasury), existential_deposit + 300);
+			assert_eq!(Balances::total_issuance(), existential_deposit + 300);
+
+			// Without a treasury share all of the dust is burned
+			DustTreasuryShare::set(&Perbill::zero());
+			DustToTreasury::on_unbalanced(Balances::issue(1_000));
+			assert_eq!(Balances::free_balance(&treasury), existential_deposit + 300);
+			assert_eq!(Balances::total_issuance(), existential_deposit + 300);
+		})
+	}
+
+	#[test]
+	fn asset_fees_convert_at_oracle_prices() {
+		new_test_ext().execute_with(|| {
+			// STND at 2 USD and DOT at 20 USD
+			ProviderCount::put(1);
+			let usd = FixedU128::saturating_from_integer;
+			SocketPrices::<Runtime>::insert(asset_ids::STND, 0, (usd(2), 0));
+			SocketPrices::<Runtime>::insert(asset_ids::DOT, 0, (usd(20), 0));
+
+			assert_eq!(
+				OraclePriceConversion::to_asset_balance(1_000_000, asset_ids::DOT),
+				Ok(100_000 + AssetFeeSurcharge::get() * 100_000)
+			);
+			// Assets without a price can not pay fees
+			assert!(OraclePriceConversion::to_asset_balance(1_000_000, asset_ids::KSM).is_err());
+		})
+	}
+
+	#[test]
+	fn liquidations_are_boosted_over_regular_calls() {
+		let liquidation = Call::Vault(pallet_standard_vault::Call::liquidate_vault(0));
+		let transfer = Call::Balances(pallet_balances::Call::transfer {
+			dest: sp_runtime::MultiAddress::Id(TreasuryAccount::get()),
+			value: 1,
+		});
+		assert_eq!(BoostPriority::boost(&liquidation), priority::LIQUIDATION_BOOST);
+		assert_eq!(BoostPriority::boost(&transfer), 0);
+	}
+
+	#[test]
+	fn asset_owners_are_read_from_assets() {
+		new_test_ext().execute_with(|| {
+			let owner = TreasuryAccount::get();
+			assert_eq!(AssetOwners::owner(asset_ids::DOT), None);
+			assert_ok!(Assets::force_create(
+				Origin::root(),
+				asset_ids::DOT,
+				sp_runtime::MultiAddress::Id(owner.clone()),
+				true,
+				1
+			));
+			assert_eq!(AssetOwners::owner(asset_ids::DOT), Some(owner));
+		})
+	}
+}
@@ -4,7 +4,9 @@
 
 use frame_support::{
 	construct_runtime, parameter_types,
-	traits::{ConstU128, ConstU32, EqualPrivilegeOnly, Everything, FindAuthor},
+	traits::{
+		ConstU128, ConstU32, Contains, EnsureOneOf, EqualPrivilegeOnly, Everything, FindAuthor,
+	},
 	weights::{
 		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, WEIGHT_PER_SECOND},
 		ConstantMultiplier, DispatchClass, IdentityFee, Weight,
@@ -23,11 +25,11 @@ use sp_runtime::{
 	generic::Era,
 	impl_opaque_keys,
 	traits::{
-		BlakeTwo256, Block as BlockT, ConvertInto, Extrinsic, SaturatedConversion, StaticLookup,
-		Verify,
+		AccountIdConversion, BlakeTwo256, Block as BlockT, ConvertInto, Extrinsic,
+		SaturatedConversion, StaticLookup, Verify,
 	},
 	transaction_validity::{TransactionSource, TransactionValidity},
-	ApplyExtrinsicResult, FixedPointNumber, Perbill, Permill, Perquintill, RuntimeAppPublic,
+	ApplyExtrinsicResult, Perbill, Permill, RuntimeAppPublic,
 };
 use sp_std::{marker::PhantomData, prelude::*};
 #[cfg(any(feature = "std", test))]
@@ -38,7 +40,7 @@ use sp_version::RuntimeVersion;
 use fp_rpc::TransactionStatus;
 use pallet_ethereum::{Call::transact, Transaction as EthereumTransaction};
 use pallet_evm::{Account as EVMAccount, EnsureAddressTruncated, HashedAddressMapping, Runner};
-use pallet_transaction_payment::{Multiplier, TargetedFeeAdjustment};
+use pallet_transaction_payment::TargetedFeeAdjustment;
 pub use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use xcm::latest::prelude::*;
 use xcm_builder::{
@@ -46,8 +48,16 @@ use xcm_builder::{
 };
 use xcm_executor::{Config, XcmExecutor};
 
+use pallet_standard_parameters::{keys, BalanceParameter, BlockNumberParameter};
 use primitives::{
-	AccountId, AccountIndex, AssetId, Balance, BlockNumber, Hash, Header, Index, Signature,
+	constants::{asset_ids, network, pallet_ids},
+	AccountId, AccountIndex, AssetId, Balance, BlockNumber, EraIndex, Hash, Header, Index,
+	LpTokenId, Signature, SocketIndex, VolumeEra,
+};
+use runtime_common::{
+	defi::*,
+	fee::*,
+	weights::{AVERAGE_ON_INITIALIZE_RATIO, NORMAL_DISPATCH_RATIO},
 };
 
 // A few exports that help ease life for downstream crates.
@@ -70,6 +80,12 @@ use xcm_config::{
 	AssetTransactors, Barrier, LocalOriginToLocation, XcmOriginToTransactDispatchOrigin, XcmRouter,
 };
 
+mod impls;
+use impls::{
+	AssetOwners, BoostPriority, ChargeFeeInAsset, DealWithFees, DustToTreasury, EthereumAccounts,
+	OraclePriceConversion,
+};
+
 use precompiles::FrontierPrecompiles;
 mod precompiles;
 pub type Precompiles = FrontierPrecompiles<Runtime>;
@@ -93,10 +109,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("standard"),
 	impl_name: create_runtime_str!("standard10"),
 	authoring_version: 1,
-	spec_version: 9190,
+	spec_version: 9195,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 6,
+	transaction_version: 10,
 	state_version: 1,
 };
 
@@ -122,7 +138,10 @@ pub type SignedExtra = (
 	frame_system::CheckEra<Runtime>,
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
-	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	ChargeFeeInAsset,
+	pallet_standard_oracle::CheckProviderReport<Runtime>,
+	pallet_standard_vault::CheckPriceAge<Runtime>,
+	BoostPriority,
 );
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
@@ -146,12 +165,6 @@ impl_opaque_keys! {
 	}
 }
 
-/// We assume that ~10% of the block weight is consumed by `on_initalize` handlers.
-/// This is used to limit the maximal weight of a single extrinsic.
-const AVERAGE_ON_INITIALIZE_RATIO: Perbill = Perbill::from_percent(10);
-/// We allow `Normal` extrinsics to fill up the block up to 75%, the rest can be used
-/// by  Operational  extrinsics.
-const NORMAL_DISPATCH_RATIO: Perbill = Perbill::from_percent(75);
 /// We allow for 0.5 of a second of compute with a 12 second average block time.
 const MAXIMUM_BLOCK_WEIGHT: Weight = WEIGHT_PER_SECOND / 2;
 /// The existential deposit. Set to 1/10 of the Connected Relay Chain.
@@ -180,7 +193,7 @@ parameter_types! {
 		})
 		.avg_block_initialization(AVERAGE_ON_INITIALIZE_RATIO)
 		.build_or_panic();
-	pub const SS58Prefix: u8 = 42;
+	pub const SS58Prefix: u16 = network::SS58_PREFIX;
 }
 
 impl frame_system::Config for Runtime {
@@ -218,7 +231,7 @@ impl frame_system::Config for Runtime {
 	/// The weight of database operations that the runtime can invoke.
 	type DbWeight = ();
 	/// The basic call filter to use in dispatchable.
-	type BaseCallFilter = Everything;
+	type BaseCallFilter = Maintenance;
 	/// Weight information for the extrinsics of this pallet.
 	type SystemWeightInfo = ();
 	/// Block & extrinsics weights: base values and limits.
@@ -428,6 +441,11 @@ parameter_types! {
 	pub const ExistentialDeposit: Balance = 1_000_000;
 	pub const MaxLocks: u32 = 50;
 	pub const MaxReserves: u32 = 50;
+	/// Module account collecting protocol dust, the same account as the Opportunity treasury
+	pub const TreasuryPalletId: PalletId = PalletId(pallet_ids::TREASURY);
+	pub TreasuryAccount: AccountId = TreasuryPalletId::get().into_account();
+	/// Share of the dust removed from accounts which goes to the treasury, the rest is burned
+	pub storage DustTreasuryShare: Perbill = Perbill::from_percent(50);
 }
 
 impl pallet_balances::Config for Runtime {
@@ -435,7 +453,7 @@ impl pallet_balances::Config for Runtime {
 	type Balance = Balance;
 	/// The ubiquitous event type.
 	type Event = Event;
-	type DustRemoval = ();
+	type DustRemoval = DustToTreasury;
 	type ExistentialDeposit = ExistentialDeposit;
 	type AccountStore = System;
 	type WeightInfo = pallet_balances::weights::SubstrateWeight<Runtime>;
@@ -464,7 +482,7 @@ impl pallet_assets::Config for Runtime {
 	type MetadataDepositPerByte = MetadataDepositPerByte;
 	type ApprovalDeposit = ApprovalDeposit;
 	type StringLimit = StringLimit;
-	type Freezer = ();
+	type Freezer = Market;
 	type Extra = ();
 	type WeightInfo = pallet_assets::weights::SubstrateWeight<Runtime>;
 }
@@ -486,42 +504,253 @@ impl pallet_asset_registry::Config for Runtime {
 	type AssetId = AssetId;
 }
 
+parameter_types! {
+	pub const StalePeriod: BlockNumber = 1 * HOURS;
+	pub const OracleRoundLength: BlockNumber = 1 * MINUTES;
+	pub const OracleMinRequestFee: Balance = 1 * DOLLARS;
+	pub const OracleMaxRequestPeriod: BlockNumber = 1 * HOURS;
+	pub const OracleMaxSymbolLength: u32 = 32;
+	pub const OracleRequestQuorum: u32 = 3;
+	/// Well above the number of oracle assets, only there to bound fee-less reports
+	pub const OracleMaxReportsPerRound: u32 = 32;
+	/// One collator session
+	pub const OracleHeartbeatPeriod: BlockNumber = 6 * HOURS;
+	pub const OracleHeartbeatSlash: Balance = 1 * DOLLARS;
+	pub const OracleMaxMissedHeartbeats: u32 = 4;
+}
+
 impl pallet_standard_oracle::Config for Runtime {
 	type Event = Event;
 	type WeightInfo = pallet_standard_oracle::weights::SubstrateWeight<Runtime>;
+	type StalePeriod = StalePeriod;
+	type Twap = Market;
+	type TwapWindow = TwapPeriod;
+	type TwapQuoteAsset = MtrAssetId;
+	type RoundLength = OracleRoundLength;
+	type RoundsPerEra = OracleRoundsPerEra;
+	type MaxMissedRounds = OracleMaxMissedRounds;
+	type Currency = Balances;
+	type MinRequestFee = OracleMinRequestFee;
+	type MaxRequestPeriod = OracleMaxRequestPeriod;
+	type MaxSymbolLength = OracleMaxSymbolLength;
+	type RequestQuorum = OracleRequestQuorum;
+	type OnPriceResponse = ();
+	type OnRoundClosed = BridgeOracle;
+	type MaxReportsPerRound = OracleMaxReportsPerRound;
+	type EraFinder = Oracle;
+	type EraReward = OracleEraReward;
+	type RewardPot = TreasuryAccount;
+	type HeartbeatPeriod = OracleHeartbeatPeriod;
+	type HeartbeatSlash = OracleHeartbeatSlash;
+	type MaxMissedHeartbeats = OracleMaxMissedHeartbeats;
+	type ProviderValidators = ();
+	type Proposal = Call;
+	type PalletsOrigin = OriginCaller;
+	type Scheduler = Scheduler;
+	type MinimumEnactmentDelay = RiskEnactmentDelay;
 }
 
 parameter_types! {
-	pub const SysPalletId: PalletId = PalletId(*b"stnd/mkt");
+	pub const TwapPeriod: BlockNumber = 1 * HOURS;
+	/// A day of hourly observations
+	pub const MaxTwapObservations: u32 = 25;
+	/// 10, 20 and 30 bps off the 30 bps swap fee
+	pub FeeDiscountTiers: Vec<(Balance, u32)> =
+		vec![(1_000 * STD, 10), (10_000 * STD, 20), (100_000 * STD, 30)];
+	pub const MinFeeLockPeriod: BlockNumber = 30 * DAYS;
+	pub const VolumeEraLength: BlockNumber = 7 * DAYS;
+	pub const VolumeHistoryDepth: VolumeEra = 12;
+	pub const GuardedWithdrawalDelay: BlockNumber = 2 * DAYS;
+	pub const BlocksPerDay: BlockNumber = 1 * DAYS;
+	pub const SwapCommitBond: Balance = 1 * DOLLARS;
+	pub const SwapRevealWindow: BlockNumber = 10;
+	pub const MaxBatchOrders: u32 = 64;
+	pub const ParameterProposalBond: Balance = 100 * DOLLARS;
+	pub const ParameterChallengePeriod: BlockNumber = 3 * DAYS;
+	pub const ParameterChallengeQuorum: Permill = Permill::from_percent(10);
+	pub const MaxParameterProposals: u32 = 16;
 }
 
 impl pallet_standard_market::Config for Runtime {
 	type Event = Event;
 	type Assets = Assets;
 	type SystemPalletId = SysPalletId;
+	type TwapPeriod = TwapPeriod;
+	type MaxTwapObservations = MaxTwapObservations;
+	type Currency = Balances;
+	type FeeDiscountTiers = FeeDiscountTiers;
+	type MinFeeLockPeriod = MinFeeLockPeriod;
+	type VolumeEraLength = VolumeEraLength;
+	type VolumeHistoryDepth = VolumeHistoryDepth;
+	type GuardedWithdrawalDelay = GuardedWithdrawalDelay;
+	type BlocksPerDay = BlocksPerDay;
+	type SwapCommitBond = SwapCommitBond;
+	type SwapRevealWindow = SwapRevealWindow;
+	type MaxBatchOrders = MaxBatchOrders;
+	type ParameterProposalBond = ParameterProposalBond;
+	type ParameterChallengePeriod = ParameterChallengePeriod;
+	type ParameterChallengeQuorum = ParameterChallengeQuorum;
+	type MaxParameterProposals = MaxParameterProposals;
+	type SharedFunds = Vault;
+	type Proposal = Call;
+	type PalletsOrigin = OriginCaller;
+	type Scheduler = Scheduler;
+	type MinimumEnactmentDelay = RiskEnactmentDelay;
+}
+
+parameter_types! {
+	pub const RiskEnactmentDelay: BlockNumber = 1 * DAYS;
+	pub const LiquidationGracePeriod: BlockNumber = 10 * MINUTES;
+	pub const RateUpdatePeriod: BlockNumber = 1 * DAYS;
+	pub const MaxCollateralAssets: u32 = 5;
+	pub const MaxLeverageIterations: u32 = 5;
+	pub const MinCollateralProviders: u32 = 3;
+	pub const MinCollateralLiquidity: Balance = 100_000 * DOLLARS;
+	/// Caps on the MTR minted by the vaults and the peg stability module
+	pub const MaxMtrMintPerBlock: Balance = 1_000_000 * DOLLARS;
+	pub const MaxMtrMintPerEra: Balance = 10_000_000 * DOLLARS;
+	pub const MtrMintEraLength: BlockNumber = 1 * DAYS;
+	pub const MaxIdleLiquidations: u32 = 20;
+	pub const PurgeReward: Balance = 10 * CENTS;
+	/// Oracle prices older than this can't open debt or liquidate vaults
+	pub const MaxPriceAge: BlockNumber = 5 * MINUTES;
 }
 
 parameter_types! {
-	pub const VltPalletId: PalletId = PalletId(*b"stnd/vlt");
+	pub const RiskMotionDuration: BlockNumber = 3 * DAYS;
+	pub const RiskMaxProposals: u32 = 20;
+	pub const RiskMaxMembers: u32 = 20;
+}
+
+type RiskCollective = pallet_collective::Instance1;
+
+impl pallet_collective::Config<RiskCollective> for Runtime {
+	type Origin = Origin;
+	type Proposal = Call;
+	type Event = Event;
+	type MotionDuration = RiskMotionDuration;
+	type MaxProposals = RiskMaxProposals;
+	type MaxMembers = RiskMaxMembers;
+	type DefaultVote = pallet_collective::PrimeDefaultVote;
+	type WeightInfo = pallet_collective::weights::SubstrateWeight<Runtime>;
 }
 
+/// Collateral positions are set by root, which scheduled position changes are dispatched as,
+/// or by two thirds of the risk committee.
+type EnsureRootOrRiskCommittee = EnsureOneOf<
+	EnsureRoot<AccountId>,
+	pallet_collective::EnsureProportionAtLeast<AccountId, RiskCollective, 2, 3>,
+>;
+
+// Risk parameters can also be set by the governance of a bridged chain, with the calls it is
+// whitelisted for
+type EnsureRiskGovernance = EnsureOneOf<
+	EnsureRootOrRiskCommittee,
+	pallet_standard_bridge_governance::EnsureRemoteGovernance,
+>;
+
 impl pallet_standard_vault::Config for Runtime {
 	type Event = Event;
 	type VaultPalletId = VltPalletId;
 	type Assets = Assets;
 	type SystemPalletId = SysPalletId;
+	type MtrAssetId = MtrAssetId;
+	type NativeAssetId = NativeAssetId;
+	type Proposal = Call;
+	type PalletsOrigin = OriginCaller;
+	type Scheduler = Scheduler;
+	type RiskOrigin = EnsureRiskGovernance;
+	type MinimumEnactmentDelay = RiskEnactmentDelay;
+	// Tunable with the parameters pallet, the constants are the defaults
+	type LiquidationGracePeriod =
+		BlockNumberParameter<Runtime, keys::LiquidationGracePeriod, LiquidationGracePeriod>;
+	type MtrPeg = MtrPeg;
+	type PegTolerance = PegTolerance;
+	type RateUpdatePeriod = RateUpdatePeriod;
+	type MaxCollateralAssets = MaxCollateralAssets;
+	type MaxLeverageIterations = MaxLeverageIterations;
+	type MinCollateralProviders = MinCollateralProviders;
+	type MinCollateralLiquidity = MinCollateralLiquidity;
+	type MaxMintPerBlock = BalanceParameter<Runtime, keys::MaxMtrMintPerBlock, MaxMtrMintPerBlock>;
+	type MaxMintPerEra = BalanceParameter<Runtime, keys::MaxMtrMintPerEra, MaxMtrMintPerEra>;
+	type MintEraLength = MtrMintEraLength;
+	type MaxIdleLiquidations = MaxIdleLiquidations;
+	type PurgeReward = BalanceParameter<Runtime, keys::PurgeReward, PurgeReward>;
+	type TreasuryAccount = TreasuryAccount;
+	type HistoryDepth = VaultHistoryDepth;
+	type MaxPriceAge = BlockNumberParameter<Runtime, keys::MaxPriceAge, MaxPriceAge>;
+	type FrozenCollateral = ();
+}
+
+impl pallet_standard_psm::Config for Runtime {
+	type Event = Event;
+	type Assets = Assets;
+	type MtrAssetId = MtrAssetId;
+	type PalletId = PsmPalletId;
+	type GovernanceOrigin = EnsureRoot<AccountId>;
+	type MintGuard = Vault;
+}
+
+impl pallet_standard_stream::Config for Runtime {
+	type Event = Event;
+	type Assets = Assets;
+	type PalletId = StreamPalletId;
+}
+
+impl pallet_standard_dca::Config for Runtime {
+	type Event = Event;
+	type PalletId = DcaPalletId;
+	type MaxOrdersPerBlock = MaxDcaOrdersPerBlock;
+	type MaxSkippedRounds = MaxDcaSkippedRounds;
+	type OrderDeposit = DcaOrderDeposit;
+}
+
+impl pallet_standard_htlc::Config for Runtime {
+	type Event = Event;
+	type Assets = Assets;
+	type PalletId = HtlcPalletId;
+}
+
+impl pallet_standard_merkle_distributor::Config for Runtime {
+	type Event = Event;
+	type Assets = Assets;
+	type PalletId = MerkleDistributorPalletId;
+	type TreasuryAccount = TreasuryAccount;
+	type MaxProofLength = MaxMerkleProofLength;
+	type GovernanceOrigin = EnsureRoot<AccountId>;
 }
 
 parameter_types! {
-	pub const TransactionByteFee: Balance = 1;
-	pub const OperationalFeeMultiplier: u8 = 5;
-	pub const TargetBlockFullness: Perquintill = Perquintill::from_percent(25);
-	pub AdjustmentVariable: Multiplier = Multiplier::saturating_from_rational(1, 100_000);
-	pub MinimumMultiplier: Multiplier = Multiplier::saturating_from_rational(1, 1_000_000_000u128);
+	pub const CrowdloanRewardsPalletId: PalletId = PalletId(*b"stnd/cwr");
+	pub const CrowdloanInitialUnlock: Perbill = Perbill::from_percent(30);
+	pub const CrowdloanVestingPeriod: BlockNumber = 48 * 7 * DAYS;
+	pub const CrowdloanMaxProofLength: u32 = 32;
+}
+
+impl pallet_standard_crowdloan_rewards::Config for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type Vesting = Vesting;
+	type PalletId = CrowdloanRewardsPalletId;
+	type InitialUnlock = CrowdloanInitialUnlock;
+	type VestingPeriod = CrowdloanVestingPeriod;
+	type MaxProofLength = CrowdloanMaxProofLength;
+	type GovernanceOrigin = EnsureRoot<AccountId>;
+}
+
+parameter_types! {
+	/// Share of the transaction fees which is burned
+	pub storage FeeBurnShare: Perbill = Perbill::from_percent(20);
+	/// Share of the transaction fees which goes to the treasury, the block author gets the rest
+	pub storage FeeTreasuryShare: Perbill = Perbill::from_percent(60);
+	/// Share of the tips which is burned
+	pub storage TipBurnShare: Perbill = Perbill::zero();
+	/// Share of the tips which goes to the treasury, the block author gets the rest
+	pub storage TipTreasuryShare: Perbill = Perbill::zero();
 }
 
 impl pallet_transaction_payment::Config for Runtime {
-	type OnChargeTransaction = pallet_transaction_payment::CurrencyAdapter<Balances, ()>;
+	type OnChargeTransaction = pallet_transaction_payment::CurrencyAdapter<Balances, DealWithFees>;
 	type OperationalFeeMultiplier = OperationalFeeMultiplier;
 	type WeightToFee = IdentityFee<Balance>;
 	type LengthToFee = ConstantMultiplier<Balance, TransactionByteFee>;
@@ -529,11 +758,114 @@ impl pallet_transaction_payment::Config for Runtime {
 		TargetedFeeAdjustment<Self, TargetBlockFullness, AdjustmentVariable, MinimumMultiplier>;
 }
 
+parameter_types! {
+	/// Assets fees can be paid in, converted at their oracle price
+	pub storage FeeAssets: Vec<AssetId> = vec![asset_ids::MTR, asset_ids::DOT, asset_ids::KSM];
+}
+
+impl pallet_asset_tx_payment::Config for Runtime {
+	type Fungibles = Assets;
+	type OnChargeAssetTransaction =
+		pallet_asset_tx_payment::FungiblesAdapter<OraclePriceConversion, ()>;
+}
+
 impl pallet_sudo::Config for Runtime {
 	type Event = Event;
 	type Call = Call;
 }
 
+/// Calls allowed while the chain is in maintenance mode: inherents and governance.
+pub struct MaintenanceCallFilter;
+impl Contains<Call> for MaintenanceCallFilter {
+	fn contains(call: &Call) -> bool {
+		matches!(
+			call,
+			Call::System(_) |
+				Call::ParachainSystem(_) |
+				Call::Timestamp(_) |
+				Call::Scheduler(_) |
+				Call::Preimage(_) |
+				Call::Sudo(_) |
+				Call::Maintenance(_) |
+				Call::TransactionPause(_)
+		)
+	}
+}
+
+/// Pallets that can be disabled individually by the maintenance pallet during incidents.
+pub struct PausablePallets;
+impl Contains<Vec<u8>> for PausablePallets {
+	fn contains(pallet_name: &Vec<u8>) -> bool {
+		matches!(
+			pallet_name.as_slice(),
+			b"Market" | b"Vault" | b"Oracle" | b"ChainBridge" | b"Psm" | b"Dca" |
+				b"BridgeGovernance" | b"Htlc" |
+				b"BridgeTransfer" | b"BridgeOracle"
+		)
+	}
+}
+
+/// Sudo calls, rejected from `SudoSunsetBlock` on.
+pub struct SudoCalls;
+impl Contains<Call> for SudoCalls {
+	fn contains(call: &Call) -> bool {
+		matches!(call, Call::Sudo(_))
+	}
+}
+
+parameter_types! {
+	/// Block from which sudo can no longer be used, leaving the chain to governance.
+	pub const SudoSunsetBlock: Option<BlockNumber> = Some(365 * DAYS);
+}
+
+impl pallet_standard_maintenance::Config for Runtime {
+	type Event = Event;
+	type NormalCallFilter = pallet_standard_transaction_pause::PausedTransactionFilter<Runtime>;
+	type MaintenanceCallFilter = MaintenanceCallFilter;
+	type PausablePallets = PausablePallets;
+	type MaintenanceOrigin = EnsureRoot<AccountId>;
+	type SunsetCallFilter = SudoCalls;
+	type SunsetBlock = SudoSunsetBlock;
+}
+
+/// Pallets whose calls can not be paused: the ones with the inherents block production relies
+/// on, and governance, so that it can lift any pause.
+pub struct UnpausablePallets;
+impl Contains<Vec<u8>> for UnpausablePallets {
+	fn contains(pallet_name: &Vec<u8>) -> bool {
+		matches!(
+			pallet_name.as_slice(),
+			b"System" | b"ParachainSystem" | b"Timestamp" | b"Authorship" | b"Session" |
+				b"DynamicFee" | b"Preimage" | b"Sudo" | b"RiskCommittee" | b"Maintenance"
+		)
+	}
+}
+
+impl pallet_standard_transaction_pause::Config for Runtime {
+	type Event = Event;
+	type PauseOrigin = EnsureRoot<AccountId>;
+	type UnpausablePallets = UnpausablePallets;
+}
+
+impl pallet_standard_parameters::Config for Runtime {
+	type Event = Event;
+	type AdminOrigin = EnsureRootOrRiskCommittee;
+}
+
+parameter_types! {
+	pub const BuybackPeriod: BlockNumber = 1 * HOURS;
+}
+
+impl pallet_standard_buyback::Config for Runtime {
+	type Event = Event;
+	type GovernanceOrigin = EnsureRootOrRiskCommittee;
+	type Treasury = TreasuryAccount;
+	type BuybackAsset = NativeAssetId;
+	type BuybackPeriod = BuybackPeriod;
+	type EraFinder = Oracle;
+	type MaxPolicies = MaxBuybackPolicies;
+}
+
 impl frame_system::offchain::SigningTypes for Runtime {
 	type Public = <Signature as sp_runtime::traits::Verify>::Signer;
 	type Signature = Signature;
@@ -550,21 +882,78 @@ where
 parameter_types! {
 	pub const BridgeChainId: u8 = 101;
 	pub const ProposalLifetime: BlockNumber = 1000;
+	pub const TransferLifetime: BlockNumber = 1 * DAYS;
+	pub const NonceWindow: u64 = 1024;
+	pub const MaxRelayerSnapshots: u32 = 32;
+	pub const MaxVotesPerBlock: u32 = 64;
+	pub const MaxQueuedProposals: u32 = 256;
+	pub const DeliveryQueueTimeout: BlockNumber = 1 * DAYS;
+	/// Calls the relayers could propose before the proposal whitelist
+	pub DefaultAllowedProposals: Vec<(Vec<u8>, Vec<u8>)> =
+		vec![
+			(b"BridgeGovernance".to_vec(), b"execute".to_vec()),
+			(b"BridgeTransfer".to_vec(), b"transfer_in".to_vec()),
+			(b"RemoteVault".to_vec(), b"execute_intent".to_vec()),
+		];
 }
 
 impl pallet_standard_chainbridge::Config for Runtime {
 	type Event = Event;
-	type AdminOrigin = EnsureRoot<AccountId>;
+	type RelayerAdminOrigin = EnsureRoot<AccountId>;
+	type ResourceAdminOrigin = EnsureRoot<AccountId>;
+	type PauseOrigin = EnsureRoot<AccountId>;
 	type Proposal = Call;
 	type BridgeChainId = BridgeChainId;
 	type ProposalLifetime = ProposalLifetime;
+	type TransferCallback = BridgeTransfer;
+	// Tunable with the parameters pallet, the constant is the default
+	type TransferLifetime =
+		BlockNumberParameter<Runtime, keys::BridgeTransferLifetime, TransferLifetime>;
+	type NonceWindow = NonceWindow;
+	type MaxRelayerSnapshots = MaxRelayerSnapshots;
+	type MaxVotesPerBlock = MaxVotesPerBlock;
+	type DefaultAllowedProposals = DefaultAllowedProposals;
+	type MaxQueuedProposals = MaxQueuedProposals;
+	type DeliveryQueueTimeout = DeliveryQueueTimeout;
+}
+
+impl pallet_standard_bridge_governance::Config for Runtime {
+	type Event = Event;
+	type Origin = Origin;
+	type Call = Call;
+	type BridgeOrigin = pallet_standard_chainbridge::EnsureBridge<Runtime>;
+	type GovernanceOrigin = EnsureRoot<AccountId>;
+}
+
+impl pallet_standard_bridge_transfer::Config for Runtime {
+	type Event = Event;
+	type Assets = Assets;
+	type BridgeOrigin = pallet_standard_chainbridge::EnsureBridge<Runtime>;
+	type AdminOrigin = EnsureRoot<AccountId>;
+	type AssetOwner = AssetOwners;
+	type Reserves = Market;
+	type PalletId = BridgeTransferPalletId;
+}
+
+impl pallet_standard_bridge_oracle::Config for Runtime {
+	type Event = Event;
+	type AdminOrigin = EnsureRoot<AccountId>;
+	type PalletId = BridgeOraclePalletId;
+}
+
+impl pallet_standard_remote_vault::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type BridgeOrigin = pallet_standard_chainbridge::EnsureBridge<Runtime>;
+	type GovernanceOrigin = EnsureRoot<AccountId>;
+	type AddressMapping = EthereumAccounts;
+	type BridgeChainId = BridgeChainId;
 }
 
 parameter_types! {
 	pub const PotId: PalletId = PalletId(*b"PotStake");
 	pub const MaxCandidates: u32 = 1000;
 	pub const MinCandidates: u32 = 5;
-	pub const SessionLength: BlockNumber = 6 * HOURS;
 	pub const MaxInvulnerables: u32 = 100;
 	pub const ExecutiveBody: BodyId = BodyId::Executive;
 }
@@ -664,6 +1053,9 @@ impl pallet_base_fee::Config for Runtime {
 	type DefaultBaseFeePerGas = DefaultBaseFeePerGas;
 }
 
+// Pallet indices and the position of each call in its pallet are encoded in signed transactions.
+// Keep the indices explicit and append new calls to the end of their pallet, the call indices of
+// the Standard pallets are pinned by `call_indices.txt`.
 construct_runtime!(
 	pub enum Runtime where
 		Block = Block,
@@ -679,10 +1071,13 @@ construct_runtime!(
 		Preimage: pallet_preimage::{Pallet, Call, Storage, Event<T>} = 5,
 		TransactionPayment: pallet_transaction_payment::{Pallet, Storage} = 6,
 		Sudo: pallet_sudo::{Pallet, Call, Storage, Event<T>, Config<T>} = 7,
+		Maintenance: pallet_standard_maintenance::{Pallet, Call, Storage, Event<T>} = 8,
+		TransactionPause: pallet_standard_transaction_pause::{Pallet, Call, Storage, Event<T>} = 9,
 		// Balance pallets
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>} = 10,
-		Assets: pallet_assets::{Pallet, Call, Storage, Event<T>} = 11,
+		Assets: pallet_assets::{Pallet, Call, Storage, Event<T>, Config<T>} = 11,
 		Vesting: pallet_vesting::{Pallet, Call, Storage, Config<T>, Event<T>} = 12,
+		AssetTxPayment: pallet_asset_tx_payment::{Pallet} = 13,
 		// Consensus pallets
 		Authorship: pallet_authorship::{Pallet, Call, Storage, Inherent} = 20,
 		CollatorSelection: pallet_collator_selection::{Pallet, Call, Storage, Event<T>, Config<T>} = 21,
@@ -695,13 +1090,27 @@ construct_runtime!(
 		XcmPallet: pallet_xcm::{Pallet, Call, Event<T>, Origin} = 31,
 		CumulusXcm: cumulus_pallet_xcm::{Pallet, Call, Event<T>, Origin} = 32,
 		DmpQueue: cumulus_pallet_dmp_queue::{Pallet, Call, Storage, Event<T>} = 33,
+		// Governance pallets
+		RiskCommittee: pallet_collective::<Instance1>::{Pallet, Call, Storage, Origin<T>, Event<T>, Config<T>} = 35,
+		Parameters: pallet_standard_parameters::{Pallet, Call, Storage, Event<T>} = 36,
+		Buyback: pallet_standard_buyback::{Pallet, Call, Storage, Event<T>} = 37,
 		// Standard pallets
 		AssetRegistry: pallet_asset_registry::{Pallet, Storage, Config<T>} = 40,
-		Market: pallet_standard_market::{Pallet, Call, Storage, Event} = 41,
+		Market: pallet_standard_market::{Pallet, Call, Storage, Event<T>, Config<T>} = 41,
 		Oracle: pallet_standard_oracle::{Pallet, Call, Storage, Event<T>, Config<T>} = 42,
-		Vault: pallet_standard_vault::{Pallet, Call, Storage, Event<T>} = 43,
+		Vault: pallet_standard_vault::{Pallet, Call, Storage, Event<T>, Config} = 43,
+		Psm: pallet_standard_psm::{Pallet, Call, Storage, Event<T>} = 44,
+		Stream: pallet_standard_stream::{Pallet, Call, Storage, Event<T>} = 45,
+		Dca: pallet_standard_dca::{Pallet, Call, Storage, Event<T>} = 46,
+		Htlc: pallet_standard_htlc::{Pallet, Call, Storage, Event<T>} = 47,
+		CrowdloanRewards: pallet_standard_crowdloan_rewards::{Pallet, Call, Storage, Event<T>} = 48,
+		MerkleDistributor: pallet_standard_merkle_distributor::{Pallet, Call, Storage, Event<T>} = 49,
 		// Chainbridge pallets
 		ChainBridge: pallet_standard_chainbridge::{Pallet, Call, Storage, Event<T>}= 50,
+		BridgeGovernance: pallet_standard_bridge_governance::{Pallet, Call, Storage, Event<T>, Origin} = 51,
+		BridgeTransfer: pallet_standard_bridge_transfer::{Pallet, Call, Storage, Event<T>} = 52,
+		BridgeOracle: pallet_standard_bridge_oracle::{Pallet, Call, Storage, Event<T>} = 53,
+		RemoteVault: pallet_standard_remote_vault::{Pallet, Call, Storage, Event<T>} = 54,
 		// EVM pallets
 		Ethereum: pallet_ethereum::{Pallet, Call, Storage, Event, Origin, Config} = 60,
 		EVM: pallet_evm::{Pallet, Config, Call, Storage, Event<T>} = 61,
@@ -761,7 +1170,10 @@ where
 			frame_system::CheckEra::<Runtime>::from(era),
 			frame_system::CheckNonce::<Runtime>::from(nonce),
 			frame_system::CheckWeight::<Runtime>::new(),
-			pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(tip),
+			ChargeFeeInAsset::from(tip, None),
+			pallet_standard_oracle::CheckProviderReport::<Runtime>::new(),
+			pallet_standard_vault::CheckPriceAge::<Runtime>::new(),
+			BoostPriority,
 		);
 		let raw_payload = SignedPayload::new(call, extra)
 			.map_err(|e| {
@@ -775,6 +1187,17 @@ where
 	}
 }
 
+/// Asset ids registered at genesis keyed by their symbol
+fn well_known_assets() -> Vec<(Vec<u8>, AssetId)> {
+	vec![
+		(b"STND".to_vec(), asset_ids::STND),
+		(b"MTR".to_vec(), asset_ids::MTR),
+		(b"DOT".to_vec(), asset_ids::DOT),
+		(b"KSM".to_vec(), asset_ids::KSM),
+		(b"ROC".to_vec(), asset_ids::ROC),
+	]
+}
+
 sp_api::impl_runtime_apis! {
 	impl sp_consensus_aura::AuraApi<Block, AuraId> for Runtime {
 		fn slot_duration() -> sp_consensus_aura::SlotDuration {
@@ -1022,6 +1445,223 @@ sp_api::impl_runtime_apis! {
 			)
 		}
 	}
+
+	impl primitives::MarketApi<Block> for Runtime {
+		fn reserve_discrepancies() -> Vec<(AssetId, Balance, Balance)> {
+			Market::reserve_discrepancies()
+		}
+
+		fn lp_token_info(lp_token: LpTokenId) -> Option<primitives::overview::LpTokenInfo> {
+			Market::lp_token_info(lp_token)
+		}
+
+		fn lp_tokens() -> Vec<primitives::overview::LpTokenInfo> {
+			Market::lp_tokens()
+		}
+
+		fn pool_stats(lp_token: LpTokenId) -> Option<primitives::overview::PoolStats> {
+			Market::pool_stats(lp_token).map(|stats| stats.priced(|id| Oracle::price(id).ok()))
+		}
+
+		fn all_pool_stats() -> Vec<primitives::overview::PoolStats> {
+			Market::all_pool_stats()
+				.into_iter()
+				.map(|stats| stats.priced(|id| Oracle::price(id).ok()))
+				.collect()
+		}
+	}
+
+	impl primitives::MarketVolumeApi<Block, AccountId> for Runtime {
+		fn current_volume_era() -> VolumeEra {
+			Market::current_volume_era()
+		}
+
+		fn account_volume(account: AccountId, era: VolumeEra) -> Vec<(LpTokenId, Balance)> {
+			Market::account_volume(era, account)
+		}
+
+		fn pair_volume(lp_token: LpTokenId, era: VolumeEra) -> Balance {
+			Market::pair_volume(era, lp_token)
+		}
+	}
+
+	impl primitives::OracleApi<Block, AccountId> for Runtime {
+		fn providers() -> Vec<AccountId> {
+			Oracle::providers()
+		}
+
+		fn slot_assignments() -> Vec<(SocketIndex, AccountId)> {
+			Oracle::slot_assignments()
+		}
+
+		fn slashes(era: EraIndex) -> Vec<AccountId> {
+			Oracle::slashes(era)
+		}
+	}
+
+	impl primitives::LiquidityReturnsApi<Block, AccountId> for Runtime {
+		fn liquidity_returns(account: AccountId) -> Vec<primitives::overview::LiquidityReturn> {
+			Market::liquidity_returns(&account)
+				.into_iter()
+				.map(|position| position.priced(|id| Oracle::price(id).ok()))
+				.collect()
+		}
+	}
+
+	impl primitives::AccountApi<Block, AccountId> for Runtime {
+		fn account_overview(account: AccountId) -> primitives::overview::AccountOverview {
+			let mut balances = vec![(primitives::CORE_ASSET_ID, Balances::free_balance(&account))];
+			balances.extend(
+				well_known_assets().into_iter().map(|(_, id)| (id, Assets::balance(id, &account))),
+			);
+			primitives::overview::AccountOverview {
+				balances,
+				liquidity: Market::liquidity_of(&account),
+				vaults: Vault::vaults_of(&account),
+				rewards: Stream::pending_rewards(&account),
+			}
+		}
+	}
+
+	impl primitives::VaultHistoryApi<Block> for Runtime {
+		fn vault_era_stats(era: EraIndex) -> Option<primitives::snapshot::VaultEraStats> {
+			Vault::vault_era_stats(era)
+		}
+
+		fn vault_history() -> Vec<primitives::snapshot::VaultEraStats> {
+			Vault::vault_history()
+		}
+	}
+
+	impl primitives::DefiSnapshotApi<Block, AccountId> for Runtime {
+		fn defi_snapshot() -> primitives::snapshot::DefiSnapshot<AccountId> {
+			let mut assets: Vec<AssetId> =
+				well_known_assets().into_iter().map(|(_, id)| id).collect();
+			assets.extend(Vault::collateral_ids());
+			assets.sort();
+			assets.dedup();
+			primitives::snapshot::DefiSnapshot {
+				pools: Market::pools(),
+				vaults: Vault::vault_snapshots(),
+				prices: assets
+					.iter()
+					.filter_map(|&id| Oracle::price(id).ok().map(|price| (id, price)))
+					.collect(),
+				issuance: assets.iter().map(|&id| (id, Assets::total_supply(id))).collect(),
+			}
+		}
+	}
+
+	impl primitives::ChainBridgeApi<Block, AccountId, BlockNumber> for Runtime {
+		fn transfer_receipt(
+			dest_id: primitives::bridge::BridgeChainId,
+			nonce: primitives::bridge::DepositNonce,
+		) -> Option<primitives::bridge::TransferReceipt<AccountId, BlockNumber>> {
+			ChainBridge::transfer_receipts(dest_id, nonce)
+		}
+
+		fn catalogue() -> primitives::bridge::BridgeCatalogue<AccountId> {
+			ChainBridge::catalogue()
+		}
+	}
+
+	impl primitives::StateProofApi<Block> for Runtime {
+		fn proven_vault(
+			vault_id: primitives::VaultId,
+		) -> primitives::proof::Proven<Option<primitives::overview::VaultPosition>> {
+			Vault::proven_vault(vault_id)
+		}
+
+		fn proven_pool(
+			lp_token: LpTokenId,
+		) -> primitives::proof::Proven<Option<primitives::snapshot::PoolSnapshot>> {
+			let mut pool = Market::proven_pool(lp_token);
+			let asset = AssetId::from(lp_token);
+			pool.keys.push(pallet_assets::Asset::<Runtime>::hashed_key_for(asset));
+			pool
+		}
+	}
+
+	impl primitives::ConstantsApi<Block> for Runtime {
+		fn native_asset_id() -> AssetId {
+			primitives::CORE_ASSET_ID
+		}
+
+		fn mtr_asset_id() -> AssetId {
+			MtrAssetId::get()
+		}
+
+		fn well_known_assets() -> Vec<(Vec<u8>, AssetId)> {
+			well_known_assets()
+		}
+
+		fn ss58_prefix() -> u16 {
+			SS58Prefix::get()
+		}
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	impl frame_benchmarking::Benchmark<Block> for Runtime {
+		fn benchmark_metadata(extra: bool) -> (
+			Vec<frame_benchmarking::BenchmarkList>,
+			Vec<frame_support::traits::StorageInfo>,
+		) {
+			use frame_benchmarking::{list_benchmark, BenchmarkList, Benchmarking};
+			use frame_support::traits::StorageInfoTrait;
+			use frame_system_benchmarking::Pallet as SystemBench;
+
+			let mut list = Vec::<BenchmarkList>::new();
+			list_benchmark!(list, extra, frame_system, SystemBench::<Runtime>);
+			list_benchmark!(list, extra, pallet_balances, Balances);
+			list_benchmark!(list, extra, pallet_assets, Assets);
+
+			let storage_info = AllPalletsWithSystem::storage_info();
+			(list, storage_info)
+		}
+
+		fn dispatch_benchmark(
+			config: frame_benchmarking::BenchmarkConfig,
+		) -> Result<Vec<frame_benchmarking::BenchmarkBatch>, sp_runtime::RuntimeString> {
+			use frame_benchmarking::{add_benchmark, BenchmarkBatch, Benchmarking, TrackedStorageKey};
+			use frame_system_benchmarking::Pallet as SystemBench;
+
+			impl frame_system_benchmarking::Config for Runtime {}
+
+			let whitelist: Vec<TrackedStorageKey> = vec![
+				// Block Number
+				hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef702a5c1b19ab7a04f536c519aca4983ac")
+					.to_vec()
+					.into(),
+				// Total Issuance
+				hex_literal::hex!("c2261276cc9d1f8598ea4b6a74b15c2f57c875e4cff74148e4628f264b974c80")
+					.to_vec()
+					.into(),
+				// Execution Phase
+				hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef7ff553b5a9862a516939d82b3d3d8661a")
+					.to_vec()
+					.into(),
+				// Event Count
+				hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef70a98fdbe9ce6c55837576c60c7af3850")
+					.to_vec()
+					.into(),
+				// System Events
+				hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef780d41e5e16056765bc8461851072c9d7")
+					.to_vec()
+					.into(),
+			];
+
+			let mut batches = Vec::<BenchmarkBatch>::new();
+			let params = (&config, &whitelist);
+			add_benchmark!(params, batches, frame_system, SystemBench::<Runtime>);
+			add_benchmark!(params, batches, pallet_balances, Balances);
+			add_benchmark!(params, batches, pallet_assets, Assets);
+
+			if batches.is_empty() {
+				return Err("Benchmark not found for this pallet.".into())
+			}
+			Ok(batches)
+		}
+	}
 }
 
 struct CheckInherents;
@@ -1052,3 +1692,94 @@ cumulus_pallet_parachain_system::register_validate_block!(
 	BlockExecutor = cumulus_pallet_aura_ext::BlockExecutor::<Runtime, Executive>,
 	CheckInherents = CheckInherents,
 );
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_support::{
+		metadata::RuntimeMetadata,
+		traits::{Currency, OnUnbalanced},
+	};
+	use scale_info::TypeDef;
+
+	/// Pallets maintained in this repository
+	const STANDARD_PALLETS: &[&str] = &[
+		"Maintenance",
+		"TransactionPause",
+		"Parameters",
+		"Buyback",
+		"Market",
+		"Oracle",
+		"Vault",
+		"Psm",
+		"Stream",
+		"Dca",
+		"Htlc",
+		"CrowdloanRewards",
+		"MerkleDistributor",
+		"ChainBridge",
+		"BridgeGovernance",
+		"BridgeTransfer",
+		"BridgeOracle",
+		"RemoteVault",
+	];
+
+	#[test]
+	fn call_indices_match_snapshot() {
+		let metadata = match Runtime::metadata().1 {
+			RuntimeMetadata::V14(metadata) => metadata,
+			_ => panic!("metadata is not V14"),
+		};
+		let mut indices = Vec::new();
+		for pallet in &metadata.pallets {
+			let calls = match &pallet.calls {
+				Some(calls) if STANDARD_PALLETS.contains(&pallet.name.as_str()) => calls,
+				_ => continue,
+			};
+			let ty = metadata.types.resolve(calls.ty.id()).expect("call type is registered");
+			if let TypeDef::Variant(variants) = ty.type_def() {
+				for call in variants.variants() {
+					let name = format!("{}::{}", pallet.name, call.name());
+					indices.push((pallet.index, call.index(), name));
+				}
+			}
+		}
+		indices.sort();
+		let snapshot: String = indices
+			.into_iter()
+			.map(|(pallet, call, name)| format!("{}.{} {}\n", pallet, call, name))
+			.collect();
+		// Changing an index breaks transactions signed against the previous runtime
+		assert_eq!(snapshot, include_str!("../call_indices.txt"));
+	}
+
+	#[test]
+	fn fees_are_split_between_burning_treasury_and_author() {
+		sp_io::TestExternalities::default().execute_with(|| {
+			let (fee, tip) = (1_000 * ExistentialDeposit::get(), 100 * ExistentialDeposit::get());
+			DealWithFees::on_unbalanceds(
+				vec![Balances::issue(fee), Balances::issue(tip)].into_iter(),
+			);
+
+			// 20% of the fee is burned, the treasury takes the share of the missing author
+			let burned = fee / 5;
+			assert_eq!(Balances::free_balance(TreasuryAccount::get()), fee + tip - burned);
+			assert_eq!(Balances::total_issuance(), fee + tip - burned);
+
+			TipBurnShare::set(&Perbill::from_percent(50));
+			DealWithFees::on_unbalanceds(
+				vec![Balances::issue(fee), Balances::issue(tip)].into_iter(),
+			);
+			let burned = burned + fee / 5 + tip / 2;
+			assert_eq!(Balances::total_issuance(), 2 * (fee + tip) - burned);
+		})
+	}
+
+	#[test]
+	fn inherents_and_governance_can_not_be_paused() {
+		for pallet in ["System", "ParachainSystem", "Timestamp", "Sudo", "Maintenance"] {
+			assert!(UnpausablePallets::contains(&pallet.as_bytes().to_vec()), "{}", pallet);
+		}
+		assert!(!UnpausablePallets::contains(&b"Market".to_vec()));
+	}
+}
@@ -1,5 +1,10 @@
-use pallet_evm::{Context, Precompile, PrecompileResult, PrecompileSet};
-use sp_core::H160;
+use pallet_evm::{
+	Context, ExitError, ExitSucceed, Precompile, PrecompileFailure, PrecompileOutput,
+	PrecompileResult, PrecompileSet,
+};
+use parity_scale_codec::Encode;
+use sp_core::{H160, U256};
+use sp_runtime::{traits::Convert, FixedPointNumber};
 use sp_std::marker::PhantomData;
 
 use pallet_evm_precompile_modexp::Modexp;
@@ -10,7 +15,7 @@ pub struct FrontierPrecompiles<R>(PhantomData<R>);
 
 impl<R> FrontierPrecompiles<R>
 where
-	R: pallet_evm::Config,
+	R: pallet_evm::Config + pallet_standard_market::Config + pallet_standard_remote_vault::Config,
 {
 	pub fn new() -> Self {
 		Self(Default::default())
@@ -18,13 +23,16 @@ where
 	/// Return all addresses that contain precompiles. This can be used to populate dummy code
 	/// under the precompile.
 	pub fn used_addresses() -> sp_std::vec::Vec<H160> {
-		sp_std::vec![1, 2, 3, 4, 5, 1024, 1025].into_iter().map(|x| hash(x)).collect()
+		sp_std::vec![1, 2, 3, 4, 5, 1024, 1025, 2048, 2049]
+			.into_iter()
+			.map(|x| hash(x))
+			.collect()
 	}
 }
 
 impl<R> PrecompileSet for FrontierPrecompiles<R>
 where
-	R: pallet_evm::Config,
+	R: pallet_evm::Config + pallet_standard_market::Config + pallet_standard_remote_vault::Config,
 {
 	fn execute(
 		&self,
@@ -46,6 +54,11 @@ where
 				Some(Sha3FIPS256::execute(input, target_gas, context, is_static)),
 			a if a == hash(1025) =>
 				Some(ECRecoverPublicKey::execute(input, target_gas, context, is_static)),
+			// Standard precompiles :
+			a if a == hash(2048) =>
+				Some(MarketTwap::<R>::execute(input, target_gas, context, is_static)),
+			a if a == hash(2049) =>
+				Some(IntentSigner::<R>::execute(input, target_gas, context, is_static)),
 			_ => None,
 		}
 	}
@@ -55,6 +68,108 @@ where
 	}
 }
 
+/// Gas charged for reading a TWAP, covering the pair, its price observations and its reserves
+const TWAP_GAS: u64 = 10_000;
+
+/// Market TWAP for contracts.
+///
+/// Takes the ABI encoded `(uint256 assetIn, uint256 assetOut, uint256 window)`, without a
+/// selector, and returns the average amount of `assetOut` paid for one `assetIn` over at least
+/// `window` blocks as a `uint256` with 18 decimals. Fails without a pool or enough history.
+pub struct MarketTwap<R>(PhantomData<R>);
+
+impl<R> Precompile for MarketTwap<R>
+where
+	R: pallet_standard_market::Config,
+{
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		_context: &Context,
+		_is_static: bool,
+	) -> PrecompileResult {
+		if target_gas.map_or(false, |gas| gas < TWAP_GAS) {
+			return Err(PrecompileFailure::Error { exit_status: ExitError::OutOfGas })
+		}
+		let word = |index: usize| -> Result<u32, PrecompileFailure> {
+			let bytes = input
+				.get(index * 32..(index + 1) * 32)
+				.ok_or_else(|| error("input too short"))?;
+			let value = U256::from_big_endian(bytes);
+			(value <= U256::from(u32::MAX))
+				.then(|| value.low_u32())
+				.ok_or_else(|| error("value out of range"))
+		};
+		let (asset_in, asset_out, window) = (word(0)?, word(1)?, word(2)?);
+		let price =
+			pallet_standard_market::Module::<R>::twap_over(asset_in, asset_out, window.into())
+				.ok_or_else(|| error("no TWAP for the pair"))?;
+
+		let mut output = [0u8; 32];
+		U256::from(price.into_inner()).to_big_endian(&mut output);
+		Ok(PrecompileOutput {
+			exit_status: ExitSucceed::Returned,
+			cost: TWAP_GAS,
+			output: output.to_vec(),
+			logs: Default::default(),
+		})
+	}
+}
+
+/// Gas charged for recovering the signer of an intent, as for `ecrecover`
+const INTENT_SIGNER_GAS: u64 = 3_000;
+/// Gas charged for each word of the payload, as for `keccak256`
+const INTENT_SIGNER_WORD_GAS: u64 = 6;
+
+/// Signer of a remote vault intent for contracts.
+///
+/// Takes the 65 bytes signature of the intent followed by its payload, the SCALE encoded
+/// `(chain_id, nonce, call)` signed with `personal_sign`, and returns the ABI encoded
+/// `(address signer, bytes32 account)`, with the local account the intent is executed for.
+/// Fails if no signer can be recovered.
+pub struct IntentSigner<R>(PhantomData<R>);
+
+impl<R> Precompile for IntentSigner<R>
+where
+	R: pallet_standard_remote_vault::Config,
+{
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		_context: &Context,
+		_is_static: bool,
+	) -> PrecompileResult {
+		let words = (input.len() as u64).saturating_add(31) / 32;
+		let cost = INTENT_SIGNER_GAS.saturating_add(words.saturating_mul(INTENT_SIGNER_WORD_GAS));
+		if target_gas.map_or(false, |gas| gas < cost) {
+			return Err(PrecompileFailure::Error { exit_status: ExitError::OutOfGas })
+		}
+		if input.len() < 65 {
+			return Err(error("input too short"))
+		}
+		let mut signature = [0u8; 65];
+		signature.copy_from_slice(&input[..65]);
+		let signer = pallet_standard_remote_vault::recover_signer(&signature, &input[65..])
+			.ok_or_else(|| error("invalid signature"))?;
+		let account = R::AddressMapping::convert(signer).encode();
+
+		let mut output = [0u8; 64];
+		output[12..32].copy_from_slice(signer.as_bytes());
+		let length = account.len().min(32);
+		output[32..32 + length].copy_from_slice(&account[..length]);
+		Ok(PrecompileOutput {
+			exit_status: ExitSucceed::Returned,
+			cost,
+			output: output.to_vec(),
+			logs: Default::default(),
+		})
+	}
+}
+
+fn error(message: &'static str) -> PrecompileFailure {
+	PrecompileFailure::Error { exit_status: ExitError::Other(message.into()) }
+}
+
 fn hash(a: u64) -> H160 {
 	H160::from_low_u64_be(a)
 }